@@ -0,0 +1,61 @@
+//! Parsing speed of mainnet-shaped blocks and receipts. Run with `cargo bench --bench models`.
+
+use serde_json::Value;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+use web3_rpc::model::{Block, Receipt};
+
+fn fixture(name: &str) -> Value {
+    let path = format!(
+        "{}/tests/fixtures/{}.json",
+        env!("CARGO_MANIFEST_DIR"),
+        name
+    );
+    serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap()
+}
+
+/// A Cancun block with 150 full transactions of the usual mix, about what mainnet carries.
+fn block() -> String {
+    let mut block = fixture("block_cancun");
+    let txs: Vec<Value> = [
+        "tx_eip1559",
+        "tx_eip1559",
+        "tx_legacy",
+        "tx_eip2930",
+        "tx_eip4844",
+    ]
+    .iter()
+    .cycle()
+    .take(150)
+    .map(|name| fixture(name))
+    .collect();
+    block["transactions"] = Value::Array(txs);
+    block.to_string()
+}
+
+fn bench<T>(name: &str, iterations: u32, f: impl Fn() -> T) {
+    for _ in 0..iterations / 10 {
+        black_box(f());
+    }
+    let start = Instant::now();
+    for _ in 0..iterations {
+        black_box(f());
+    }
+    let per_iteration = start.elapsed() / iterations;
+    println!(
+        "{:<24} {:>10?}",
+        name,
+        per_iteration.max(Duration::from_nanos(1))
+    );
+}
+
+fn main() {
+    let block = block();
+    let receipt = fixture("receipt_many_logs").to_string();
+    bench("block, 150 txs", 500, || {
+        serde_json::from_str::<Block>(&block).unwrap()
+    });
+    bench("receipt, 4 logs", 20_000, || {
+        serde_json::from_str::<Receipt>(&receipt).unwrap()
+    });
+}
@@ -0,0 +1,662 @@
+//! Minimal Solidity ABI encoding and decoding, enough for calling view functions and
+//! reading their results.
+//!
+//! Covers `address`, `uint<N>`, `bool`, `bytes<N>`, `bytes`, `string`, dynamic arrays and
+//! tuples. Signed integers and fixed-size arrays are not supported.
+//!
+//! Reverts are explained by [`revert_reason`] for the standard `Error(string)` and
+//! `Panic(uint256)`, and by an [`ErrorRegistry`] of custom error signatures.
+
+use crate::hex;
+use crate::keccak::keccak256;
+use crate::types::{Address, U256};
+use std::collections::HashMap;
+use std::fmt::{self, Display, Formatter};
+
+/// A decoded or to-be-encoded ABI value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Token {
+    Address(Address),
+    Uint(U256),
+    Bool(bool),
+    /// `bytes1` to `bytes32`, left-aligned.
+    FixedBytes(Vec<u8>),
+    Bytes(Vec<u8>),
+    String(String),
+    Array(Vec<Token>),
+    Tuple(Vec<Token>),
+}
+
+/// The type to decode a value as.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParamType {
+    Address,
+    Uint,
+    Bool,
+    FixedBytes(usize),
+    Bytes,
+    String,
+    Array(Box<ParamType>),
+    Tuple(Vec<ParamType>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AbiError {
+    /// The data ends before the word at `offset`.
+    TooShort {
+        offset: usize,
+        len: usize,
+    },
+    /// An offset or length does not fit the data.
+    InvalidOffset(usize),
+    InvalidBool,
+    InvalidAddress,
+    InvalidUtf8,
+    /// A signature that is not `Name(type,...)` over the supported types.
+    InvalidSignature(String),
+}
+
+impl Display for AbiError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
+
+impl std::error::Error for AbiError {}
+
+/// Solidity-like rendering: numbers in decimal, bytes in hex, strings quoted.
+impl Display for Token {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::Address(a) => Display::fmt(a, f),
+            Token::Uint(n) => Display::fmt(n, f),
+            Token::Bool(b) => Display::fmt(b, f),
+            Token::FixedBytes(b) | Token::Bytes(b) => f.write_str(&hex::data(b)),
+            Token::String(s) => write!(f, "{:?}", s),
+            Token::Array(tokens) => write_list(f, "[", tokens, "]"),
+            Token::Tuple(tokens) => write_list(f, "(", tokens, ")"),
+        }
+    }
+}
+
+fn write_list(f: &mut Formatter<'_>, open: &str, tokens: &[Token], close: &str) -> fmt::Result {
+    f.write_str(open)?;
+    for (i, token) in tokens.iter().enumerate() {
+        if i > 0 {
+            f.write_str(", ")?;
+        }
+        Display::fmt(token, f)?;
+    }
+    f.write_str(close)
+}
+
+impl Token {
+    pub fn into_address(self) -> Option<Address> {
+        match self {
+            Token::Address(a) => Some(a),
+            _ => None,
+        }
+    }
+
+    pub fn into_uint(self) -> Option<U256> {
+        match self {
+            Token::Uint(n) => Some(n),
+            _ => None,
+        }
+    }
+
+    pub fn into_bool(self) -> Option<bool> {
+        match self {
+            Token::Bool(b) => Some(b),
+            _ => None,
+        }
+    }
+
+    /// `bytes` and `bytes<N>` values.
+    pub fn into_bytes(self) -> Option<Vec<u8>> {
+        match self {
+            Token::Bytes(b) | Token::FixedBytes(b) => Some(b),
+            _ => None,
+        }
+    }
+
+    pub fn into_string(self) -> Option<String> {
+        match self {
+            Token::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// Elements of an array or members of a tuple.
+    pub fn into_tokens(self) -> Option<Vec<Token>> {
+        match self {
+            Token::Array(t) | Token::Tuple(t) => Some(t),
+            _ => None,
+        }
+    }
+
+    fn head_size(&self) -> usize {
+        match self {
+            Token::Tuple(members) if !self.is_dynamic() => {
+                members.iter().map(Token::head_size).sum()
+            }
+            _ => 32,
+        }
+    }
+
+    fn is_dynamic(&self) -> bool {
+        match self {
+            Token::Bytes(_) | Token::String(_) | Token::Array(_) => true,
+            Token::Tuple(members) => members.iter().any(Token::is_dynamic),
+            _ => false,
+        }
+    }
+}
+
+impl ParamType {
+    fn is_dynamic(&self) -> bool {
+        match self {
+            ParamType::Bytes | ParamType::String | ParamType::Array(_) => true,
+            ParamType::Tuple(members) => members.iter().any(ParamType::is_dynamic),
+            _ => false,
+        }
+    }
+
+    /// Bytes taken in the head of the enclosing tuple.
+    fn head_size(&self) -> usize {
+        match self {
+            ParamType::Tuple(members) if !self.is_dynamic() => {
+                members.iter().map(ParamType::head_size).sum()
+            }
+            _ => 32,
+        }
+    }
+}
+
+/// First four bytes of the keccak hash of a canonical signature such as
+/// `"transfer(address,uint256)"`.
+pub fn selector(signature: &str) -> [u8; 4] {
+    let hash = keccak256(signature.as_bytes());
+    [hash[0], hash[1], hash[2], hash[3]]
+}
+
+/// Calldata for `signature` called with `args`.
+pub fn encode_call(signature: &str, args: &[Token]) -> Vec<u8> {
+    let mut out = selector(signature).to_vec();
+    out.extend(encode(args));
+    out
+}
+
+/// Encodes `tokens` as a tuple, the layout of function arguments and return values.
+pub fn encode(tokens: &[Token]) -> Vec<u8> {
+    let head_size: usize = tokens.iter().map(Token::head_size).sum();
+    let mut head = Vec::with_capacity(head_size);
+    let mut tail = Vec::new();
+    for token in tokens {
+        if token.is_dynamic() {
+            head.extend(word(U256::from((head_size + tail.len()) as u64)));
+            tail.extend(encode_token(token));
+        } else {
+            head.extend(encode_token(token));
+        }
+    }
+    head.extend(tail);
+    head
+}
+
+fn encode_token(token: &Token) -> Vec<u8> {
+    match token {
+        Token::Address(a) => {
+            let mut w = [0u8; 32];
+            w[12..].copy_from_slice(a.as_bytes());
+            w.to_vec()
+        }
+        Token::Uint(n) => word(*n).to_vec(),
+        Token::Bool(b) => word(U256::from(*b as u64)).to_vec(),
+        Token::FixedBytes(b) => padded(b),
+        Token::Bytes(b) => {
+            let mut out = word(U256::from(b.len() as u64)).to_vec();
+            out.extend(padded(b));
+            out
+        }
+        Token::String(s) => encode_token(&Token::Bytes(s.as_bytes().to_vec())),
+        Token::Array(items) => {
+            let mut out = word(U256::from(items.len() as u64)).to_vec();
+            out.extend(encode(items));
+            out
+        }
+        Token::Tuple(members) => encode(members),
+    }
+}
+
+fn word(n: U256) -> [u8; 32] {
+    n.to_be_bytes()
+}
+
+fn padded(bytes: &[u8]) -> Vec<u8> {
+    let mut out = bytes.to_vec();
+    out.resize(bytes.len().div_ceil(32) * 32, 0);
+    out
+}
+
+/// Decodes `data` as a tuple of `types`, e.g. the return data of a call.
+pub fn decode(types: &[ParamType], data: &[u8]) -> Result<Vec<Token>, AbiError> {
+    decode_tuple(types, data, 0)
+}
+
+fn decode_tuple(types: &[ParamType], data: &[u8], base: usize) -> Result<Vec<Token>, AbiError> {
+    let mut tokens = Vec::with_capacity(types.len());
+    let mut head = base;
+    for param in types {
+        let token = if param.is_dynamic() {
+            let offset = read_usize(data, head)?;
+            let start = base
+                .checked_add(offset)
+                .ok_or(AbiError::InvalidOffset(offset))?;
+            decode_at(param, data, start)?
+        } else {
+            decode_at(param, data, head)?
+        };
+        tokens.push(token);
+        head += param.head_size();
+    }
+    Ok(tokens)
+}
+
+fn decode_at(param: &ParamType, data: &[u8], at: usize) -> Result<Token, AbiError> {
+    match param {
+        ParamType::Address => {
+            let w = read_word(data, at)?;
+            if w[..12].iter().any(|b| *b != 0) {
+                return Err(AbiError::InvalidAddress);
+            }
+            let mut a = [0u8; 20];
+            a.copy_from_slice(&w[12..]);
+            Ok(Token::Address(Address(a)))
+        }
+        ParamType::Uint => Ok(Token::Uint(U256::from_be_bytes(read_word(data, at)?))),
+        ParamType::Bool => match U256::from_be_bytes(read_word(data, at)?) {
+            n if n.is_zero() => Ok(Token::Bool(false)),
+            n if n == U256::ONE => Ok(Token::Bool(true)),
+            _ => Err(AbiError::InvalidBool),
+        },
+        ParamType::FixedBytes(n) => Ok(Token::FixedBytes(
+            read_word(data, at)?[..(*n).min(32)].to_vec(),
+        )),
+        ParamType::Bytes => Ok(Token::Bytes(read_dynamic_bytes(data, at)?)),
+        ParamType::String => String::from_utf8(read_dynamic_bytes(data, at)?)
+            .map(Token::String)
+            .map_err(|_| AbiError::InvalidUtf8),
+        ParamType::Array(inner) => {
+            let len = read_usize(data, at)?;
+            // every element takes at least one word, which bounds `len` by the data size
+            if len > data.len() / 32 {
+                return Err(AbiError::InvalidOffset(len));
+            }
+            let types = vec![(**inner).clone(); len];
+            decode_tuple(&types, data, at + 32).map(Token::Array)
+        }
+        ParamType::Tuple(members) => decode_tuple(members, data, at).map(Token::Tuple),
+    }
+}
+
+fn read_word(data: &[u8], at: usize) -> Result<[u8; 32], AbiError> {
+    let end = at.checked_add(32).ok_or(AbiError::InvalidOffset(at))?;
+    let w = data.get(at..end).ok_or(AbiError::TooShort {
+        offset: at,
+        len: data.len(),
+    })?;
+    let mut out = [0u8; 32];
+    out.copy_from_slice(w);
+    Ok(out)
+}
+
+fn read_usize(data: &[u8], at: usize) -> Result<usize, AbiError> {
+    let n = U256::from_be_bytes(read_word(data, at)?);
+    u64::try_from(n)
+        .ok()
+        .and_then(|n| usize::try_from(n).ok())
+        .ok_or(AbiError::InvalidOffset(at))
+}
+
+fn read_dynamic_bytes(data: &[u8], at: usize) -> Result<Vec<u8>, AbiError> {
+    let len = read_usize(data, at)?;
+    let start = at + 32;
+    let end = start.checked_add(len).ok_or(AbiError::InvalidOffset(len))?;
+    data.get(start..end)
+        .map(<[u8]>::to_vec)
+        .ok_or(AbiError::TooShort {
+            offset: start,
+            len: data.len(),
+        })
+}
+
+/// Human-readable reason from revert data: the message of `Error(string)`, or the code of
+/// a compiler-inserted `Panic(uint256)`. `None` for custom errors and empty reverts.
+pub fn revert_reason(data: &[u8]) -> Option<String> {
+    let (selector, args) = (data.get(..4)?, &data[4..]);
+    if selector == [0x08, 0xc3, 0x79, 0xa0] {
+        return decode(&[ParamType::String], args)
+            .ok()?
+            .pop()?
+            .into_string();
+    }
+    if selector == PANIC_SELECTOR {
+        let code = decode(&[ParamType::Uint], args).ok()?.pop()?.into_uint()?;
+        return Some(format!("panic {:#x}", code));
+    }
+    None
+}
+
+/// Code of a compiler-inserted `Panic(uint256)` revert: 0x01 for a failed assert, 0x11 for
+/// arithmetic overflow, 0x12 for division by zero, 0x32 for an index out of bounds.
+pub fn panic_code(data: &[u8]) -> Option<u64> {
+    if data.get(..4)? != PANIC_SELECTOR {
+        return None;
+    }
+    let code = decode(&[ParamType::Uint], &data[4..])
+        .ok()?
+        .pop()?
+        .into_uint()?;
+    code.try_into().ok()
+}
+
+const PANIC_SELECTOR: [u8; 4] = [0x4e, 0x48, 0x7b, 0x71];
+
+/// Splits a signature such as `"InsufficientBalance(address,uint256)"` into its name and
+/// parameter types.
+pub fn parse_signature(signature: &str) -> Result<(String, Vec<ParamType>), AbiError> {
+    let invalid = || AbiError::InvalidSignature(signature.to_string());
+    let (name, params) = signature.split_once('(').ok_or_else(invalid)?;
+    let params = params.strip_suffix(')').ok_or_else(invalid)?;
+    let name = name.trim();
+    if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+        return Err(invalid());
+    }
+    let params = parse_params(params).ok_or_else(invalid)?;
+    Ok((name.to_string(), params))
+}
+
+fn parse_params(list: &str) -> Option<Vec<ParamType>> {
+    if list.trim().is_empty() {
+        return Some(Vec::new());
+    }
+    let (mut params, mut depth, mut start) = (Vec::new(), 0usize, 0);
+    for (i, c) in list.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth = depth.checked_sub(1)?,
+            ',' if depth == 0 => {
+                params.push(parse_param(&list[start..i])?);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    params.push(parse_param(&list[start..])?);
+    Some(params)
+}
+
+fn parse_param(param: &str) -> Option<ParamType> {
+    let param = param.trim();
+    if let Some(element) = param.strip_suffix("[]") {
+        return Some(ParamType::Array(Box::new(parse_param(element)?)));
+    }
+    if let Some(members) = param.strip_prefix('(').and_then(|p| p.strip_suffix(')')) {
+        return Some(ParamType::Tuple(parse_params(members)?));
+    }
+    let sized = |prefix: &str, max: usize, step: usize| {
+        let n: usize = param.strip_prefix(prefix)?.parse().ok()?;
+        (n > 0 && n <= max && n.is_multiple_of(step)).then_some(n)
+    };
+    match param {
+        "address" => Some(ParamType::Address),
+        "bool" => Some(ParamType::Bool),
+        "bytes" => Some(ParamType::Bytes),
+        "string" => Some(ParamType::String),
+        _ if param.starts_with("uint") => sized("uint", 256, 8).map(|_| ParamType::Uint),
+        _ => sized("bytes", 32, 1).map(ParamType::FixedBytes),
+    }
+}
+
+/// Custom error signatures by selector, for explaining reverts that are neither
+/// `Error(string)` nor `Panic(uint256)`.
+#[derive(Debug, Clone, Default)]
+pub struct ErrorRegistry {
+    errors: HashMap<[u8; 4], (String, Vec<ParamType>)>,
+}
+
+impl ErrorRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds `signature`, which must use canonical types (`uint256`, not `uint`) since its
+    /// hash is the selector.
+    pub fn register(&mut self, signature: &str) -> Result<(), AbiError> {
+        let (name, params) = parse_signature(signature)?;
+        let canonical: String = signature.chars().filter(|c| !c.is_whitespace()).collect();
+        self.errors.insert(selector(&canonical), (name, params));
+        Ok(())
+    }
+
+    /// Readable form of revert `data`: the standard reasons of [`revert_reason`], a
+    /// registered error with its arguments (`InsufficientBalance(0x48..., 100)`), or for
+    /// anything else the selector and raw arguments. `None` for an empty revert.
+    pub fn describe(&self, data: &[u8]) -> Option<String> {
+        if data.is_empty() {
+            return None;
+        }
+        if let Some(reason) = revert_reason(data) {
+            return Some(reason);
+        }
+        let Some(selector) = data.get(..4) else {
+            return Some(format!("malformed revert data {}", hex::data(data)));
+        };
+        let args = &data[4..];
+        if let Some((name, params)) = self.errors.get(selector) {
+            if let Ok(tokens) = decode(params, args) {
+                return Some(format!("{}{}", name, Token::Tuple(tokens)));
+            }
+        }
+        Some(format!(
+            "unknown error {} with args {}",
+            hex::data(selector),
+            hex::data(args)
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hex(data: &[u8]) -> String {
+        data.iter().map(|b| format!("{:02x}", b)).collect()
+    }
+
+    #[test]
+    fn encodes_static_calls() {
+        let to: Address = "0x4e910ef89c176119744977d90093858089454223"
+            .parse()
+            .unwrap();
+        assert_eq!(hex(&selector("transfer(address,uint256)")), "a9059cbb");
+        let calldata = encode_call(
+            "transfer(address,uint256)",
+            &[Token::Address(to), Token::Uint(U256::from(1000u64))],
+        );
+        assert_eq!(
+            hex(&calldata),
+            "a9059cbb\
+             0000000000000000000000004e910ef89c176119744977d90093858089454223\
+             00000000000000000000000000000000000000000000000000000000000003e8"
+        );
+    }
+
+    #[test]
+    fn dynamic_values_round_trip() {
+        // the example from the Solidity ABI spec: f(uint256,uint32[],bytes10,bytes)
+        let tokens = vec![
+            Token::Uint(U256::from(0x123u64)),
+            Token::Array(vec![
+                Token::Uint(U256::from(0x456u64)),
+                Token::Uint(U256::from(0x789u64)),
+            ]),
+            Token::FixedBytes(b"1234567890".to_vec()),
+            Token::Bytes(b"Hello, world!".to_vec()),
+        ];
+        let encoded = encode(&tokens);
+        assert_eq!(
+            hex(&encoded),
+            "0000000000000000000000000000000000000000000000000000000000000123\
+             0000000000000000000000000000000000000000000000000000000000000080\
+             3132333435363738393000000000000000000000000000000000000000000000\
+             00000000000000000000000000000000000000000000000000000000000000e0\
+             0000000000000000000000000000000000000000000000000000000000000002\
+             0000000000000000000000000000000000000000000000000000000000000456\
+             0000000000000000000000000000000000000000000000000000000000000789\
+             000000000000000000000000000000000000000000000000000000000000000d\
+             48656c6c6f2c20776f726c642100000000000000000000000000000000000000"
+        );
+        let types = [
+            ParamType::Uint,
+            ParamType::Array(Box::new(ParamType::Uint)),
+            ParamType::FixedBytes(10),
+            ParamType::Bytes,
+        ];
+        assert_eq!(decode(&types, &encoded).unwrap(), tokens);
+
+        let nested = vec![Token::Array(vec![
+            Token::Tuple(vec![Token::Bool(true), Token::Bytes(vec![])]),
+            Token::Tuple(vec![Token::Bool(false), Token::String("revert".into())]),
+        ])];
+        let types = [ParamType::Array(Box::new(ParamType::Tuple(vec![
+            ParamType::Bool,
+            ParamType::Bytes,
+        ])))];
+        let decoded = decode(&types, &encode(&nested)).unwrap();
+        assert_eq!(
+            decoded[0].clone().into_tokens().unwrap()[1],
+            Token::Tuple(vec![Token::Bool(false), Token::Bytes(b"revert".to_vec())])
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_data() {
+        assert_eq!(
+            decode(&[ParamType::Uint], &[0u8; 31]),
+            Err(AbiError::TooShort { offset: 0, len: 31 })
+        );
+        let mut bad_bool = [0u8; 32];
+        bad_bool[31] = 2;
+        assert_eq!(
+            decode(&[ParamType::Bool], &bad_bool),
+            Err(AbiError::InvalidBool)
+        );
+        // an offset pointing far past the end
+        let mut bad_offset = [0u8; 32];
+        bad_offset[0] = 0xff;
+        assert!(decode(&[ParamType::Bytes], &bad_offset).is_err());
+        // a huge array length is refused before allocating
+        let mut long_array = [0u8; 64];
+        long_array[31] = 32;
+        long_array[32] = 0xff;
+        assert!(decode(&[ParamType::Array(Box::new(ParamType::Uint))], &long_array).is_err());
+    }
+
+    #[test]
+    fn revert_reasons() {
+        let reason = encode_call(
+            "Error(string)",
+            &[Token::String("ERC721: invalid token ID".into())],
+        );
+        assert_eq!(
+            revert_reason(&reason).as_deref(),
+            Some("ERC721: invalid token ID")
+        );
+        let panic = encode_call("Panic(uint256)", &[Token::Uint(U256::from(0x11u64))]);
+        assert_eq!(revert_reason(&panic).as_deref(), Some("panic 0x11"));
+        assert_eq!(
+            revert_reason(&selector("ERC721NonexistentToken(uint256)")),
+            None
+        );
+        assert_eq!(revert_reason(&[]), None);
+    }
+
+    #[test]
+    fn parses_signatures() {
+        assert_eq!(
+            parse_signature("InsufficientBalance(address,uint256)").unwrap(),
+            (
+                "InsufficientBalance".to_string(),
+                vec![ParamType::Address, ParamType::Uint]
+            )
+        );
+        assert_eq!(
+            parse_signature("Unauthorized()").unwrap(),
+            ("Unauthorized".to_string(), vec![])
+        );
+        assert_eq!(
+            parse_signature("Bad((uint8,bytes32)[], string)").unwrap().1,
+            vec![
+                ParamType::Array(Box::new(ParamType::Tuple(vec![
+                    ParamType::Uint,
+                    ParamType::FixedBytes(32)
+                ]))),
+                ParamType::String
+            ]
+        );
+        for invalid in [
+            "Foo",
+            "Foo(uint256",
+            "(uint256)",
+            "Foo(int256)",
+            "Foo(uint7)",
+            "Foo(bytes33)",
+            "Foo(uint256[2])",
+        ] {
+            assert!(
+                matches!(parse_signature(invalid), Err(AbiError::InvalidSignature(_))),
+                "{}",
+                invalid
+            );
+        }
+    }
+
+    #[test]
+    fn describes_custom_errors() {
+        let mut registry = ErrorRegistry::new();
+        registry
+            .register("InsufficientBalance(address, uint256)")
+            .unwrap();
+        let holder: Address = "0x4838b106fce9647bdf1e7877bf73ce8b0bad5f97"
+            .parse()
+            .unwrap();
+        let data = encode_call(
+            "InsufficientBalance(address,uint256)",
+            &[Token::Address(holder), Token::Uint(U256::from(100u64))],
+        );
+        assert_eq!(
+            registry.describe(&data).unwrap(),
+            "InsufficientBalance(0x4838B106FCe9647Bdf1E7877BF73cE8B0BAD5f97, 100)"
+        );
+
+        let unknown = encode_call("Paused()", &[Token::Uint(U256::from(1u64))]);
+        assert_eq!(
+            registry.describe(&unknown).unwrap(),
+            format!(
+                "unknown error 0x{} with args 0x{:064x}",
+                hex(&selector("Paused()")),
+                1
+            )
+        );
+        // standard reverts need no registration, empty ones have nothing to say
+        let panic = encode_call("Panic(uint256)", &[Token::Uint(U256::from(0x11u64))]);
+        assert_eq!(registry.describe(&panic).unwrap(), "panic 0x11");
+        assert_eq!(registry.describe(&[]), None);
+        assert_eq!(
+            registry.describe(&[0xde, 0xad]).unwrap(),
+            "malformed revert data 0xdead"
+        );
+    }
+}
@@ -0,0 +1,409 @@
+//! Activity of a set of addresses as the chain grows: ETH sent from or to them, ERC-20 and
+//! ERC-721 transfers touching them, and validator withdrawals credited to them. Meant for
+//! deposit detection.
+//!
+//! ```no_run
+//! # use std::collections::HashSet;
+//! # use std::time::Duration;
+//! # async fn run(web3: web3_rpc::web3::Web3, wallet: web3_rpc::types::Address) {
+//! use web3_rpc::activity::Activity;
+//!
+//! let mut activity = web3.watch_address_activity(HashSet::from([wallet]), Duration::from_secs(12));
+//! loop {
+//!     match activity.next().await {
+//!         Ok(Activity::Retracted { block }) => println!("void: block {}", block.hash),
+//!         Ok(event) => println!("{:?}", event),
+//!         Err(report) => eprintln!("{:?}", report),
+//!     }
+//! }
+//! # }
+//! ```
+//!
+//! Blocks are read with full transactions and their logs fetched by range with the
+//! `Transfer` topic and the addresses as topics. Only top-level transactions are seen: ETH
+//! moved by a contract, as a multisig paying out, emits nothing to find it by.
+//!
+//! Each block is checked to extend the last one handed out. When one does not, the chain
+//! has reorganized: the blocks handed out since the fork are retracted, newest first, and
+//! the new branch is read from there. Only the last [`REORG_DEPTH`] blocks are remembered.
+
+use crate::client::{rpc_error, Error};
+use crate::endpoints::head;
+use crate::erc20::{Erc20, Transfer};
+use crate::events::NftTransfer;
+use crate::logs::DEFAULT_CHUNK_BLOCKS;
+use crate::model::{Block, JsonRpcResult, LogFilter, Receipt, Transaction, Withdrawal};
+use crate::transactions::BlockSummary;
+use crate::types::{Address, H256, U256};
+use crate::web3::Web3;
+use error_stack::{Report, ResultExt};
+use serde_json::json;
+use std::collections::{BTreeMap, HashSet, VecDeque};
+use std::time::Duration;
+
+/// Blocks remembered to find where a reorg forks off; a deeper one goes unnoticed.
+pub const REORG_DEPTH: usize = 64;
+
+/// Blocks read per poll, at most, while catching up.
+pub const DEFAULT_BATCH_BLOCKS: u64 = 10;
+
+/// An event of [`AddressActivity`], with the block it happened in.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Activity {
+    /// A successful transaction with value, from or to a watched address.
+    Native {
+        block: BlockSummary,
+        tx_hash: H256,
+        from: Address,
+        /// `None` for a contract creation.
+        to: Option<Address>,
+        value: U256,
+    },
+    /// An ERC-20 `Transfer` log with a watched address on either side.
+    Erc20 {
+        block: BlockSummary,
+        tx_hash: H256,
+        log_index: u64,
+        token: Address,
+        transfer: Transfer,
+    },
+    /// An ERC-721 `Transfer` log with a watched address on either side.
+    Erc721 {
+        block: BlockSummary,
+        tx_hash: H256,
+        log_index: u64,
+        token: Address,
+        transfer: NftTransfer,
+    },
+    /// A validator withdrawal credited to a watched address.
+    Withdrawal {
+        block: BlockSummary,
+        withdrawal: Withdrawal,
+    },
+    /// `block` is no longer part of the chain: every event reported for it is void.
+    Retracted { block: BlockSummary },
+}
+
+impl Activity {
+    pub fn block(&self) -> &BlockSummary {
+        match self {
+            Activity::Native { block, .. }
+            | Activity::Erc20 { block, .. }
+            | Activity::Erc721 { block, .. }
+            | Activity::Withdrawal { block, .. }
+            | Activity::Retracted { block } => block,
+        }
+    }
+}
+
+/// Returned by [`Web3::watch_address_activity`]; call [`next`](Self::next) for each event.
+pub struct AddressActivity<'a> {
+    web3: &'a Web3,
+    addresses: HashSet<Address>,
+    poll: Duration,
+    batch_size: u64,
+    /// `None` until the first poll, which starts at the head.
+    next_block: Option<u64>,
+    /// Blocks handed out, oldest first.
+    recent: VecDeque<BlockSummary>,
+    buffer: VecDeque<Activity>,
+}
+
+impl Web3 {
+    /// Activity of `addresses` from the current head on, polling for new blocks every
+    /// `poll`. See [`crate::activity`].
+    pub fn watch_address_activity(
+        &self,
+        addresses: HashSet<Address>,
+        poll: Duration,
+    ) -> AddressActivity<'_> {
+        AddressActivity {
+            web3: self,
+            addresses,
+            poll,
+            batch_size: DEFAULT_BATCH_BLOCKS,
+            next_block: None,
+            recent: VecDeque::new(),
+            buffer: VecDeque::new(),
+        }
+    }
+}
+
+impl AddressActivity<'_> {
+    /// Starts at `block` instead of the head, to catch up from a checkpoint.
+    pub fn from_block(mut self, block: u64) -> Self {
+        self.next_block = Some(block);
+        self
+    }
+
+    pub fn batch_size(mut self, blocks: u64) -> Self {
+        self.batch_size = blocks.max(1);
+        self
+    }
+
+    /// The block the next poll reads first: after a crash, resume with
+    /// [`from_block`](Self::from_block) there.
+    pub fn next_block(&self) -> Option<u64> {
+        self.next_block
+    }
+
+    /// The next event, waiting for new blocks as long as it takes. After an error the next
+    /// call reads the same blocks again.
+    pub async fn next(&mut self) -> Result<Activity, Report<Error>> {
+        loop {
+            if let Some(activity) = self.buffer.pop_front() {
+                return Ok(activity);
+            }
+            if !self.advance().await? {
+                tokio::time::sleep(self.poll).await;
+            }
+        }
+    }
+
+    /// Reads the blocks after the last one handed out, if there are any, and whether it got
+    /// anywhere: `false` when there is no new block, or the node answered from two branches
+    /// of a reorg in progress and the blocks are better read again later.
+    async fn advance(&mut self) -> Result<bool, Report<Error>> {
+        let head = head(self.web3).await?;
+        let from = *self.next_block.get_or_insert(head);
+        if from > head {
+            return Ok(false);
+        }
+        let to = head.min(from.saturating_add(self.batch_size - 1));
+        let numbers: Vec<u64> = (from..=to).collect();
+        let blocks = self.web3.full_blocks(&numbers).await?;
+
+        if let (Some(last), Some(first)) = (self.recent.back().copied(), blocks.first()) {
+            if last.number + 1 == from && first.parent_hash != last.hash {
+                // undo one block and read again from its height, until the branches meet
+                self.recent.pop_back();
+                tracing::debug!(number = last.number, hash = %last.hash, "block reorged out");
+                self.buffer.push_back(Activity::Retracted { block: last });
+                self.next_block = Some(last.number);
+                return Ok(true);
+            }
+        }
+        let mut summaries: Vec<BlockSummary> = Vec::with_capacity(blocks.len());
+        for (i, block) in blocks.iter().enumerate() {
+            let summary = summary(block)?;
+            if i > 0 && block.parent_hash != summaries[i - 1].hash {
+                return Ok(false);
+            }
+            summaries.push(summary);
+        }
+
+        let Some(logs) = self.transfer_logs(&summaries).await? else {
+            return Ok(false);
+        };
+        let Some(natives) = self.native_transfers(&blocks, &summaries).await? else {
+            return Ok(false);
+        };
+        let mut logs = logs.into_iter().peekable();
+        let mut natives = natives.into_iter().peekable();
+        for (block, summary) in blocks.iter().zip(&summaries) {
+            while let Some(native) = natives.next_if(|native| native.block() == summary) {
+                self.buffer.push_back(native);
+            }
+            while let Some(log) = logs.next_if(|log| log.block() == summary) {
+                self.buffer.push_back(log);
+            }
+            for withdrawal in block.withdrawals.iter().flatten() {
+                if self.addresses.contains(&withdrawal.address) {
+                    self.buffer.push_back(Activity::Withdrawal {
+                        block: *summary,
+                        withdrawal: withdrawal.clone(),
+                    });
+                }
+            }
+            self.recent.push_back(*summary);
+            if self.recent.len() > REORG_DEPTH {
+                self.recent.pop_front();
+            }
+        }
+        self.next_block = Some(to + 1);
+        Ok(true)
+    }
+
+    /// ERC-20 and ERC-721 transfers from or to the addresses in `blocks`, in log order;
+    /// `None` when a log is from another branch than the blocks.
+    async fn transfer_logs(
+        &self,
+        blocks: &[BlockSummary],
+    ) -> Result<Option<Vec<Activity>>, Report<Error>> {
+        let (Some(first), Some(last)) = (blocks.first(), blocks.last()) else {
+            return Ok(Some(Vec::new()));
+        };
+        // without an address topic the filters would match every transfer
+        if self.addresses.is_empty() {
+            return Ok(Some(Vec::new()));
+        }
+        // one filter per side: topics are ANDed across positions
+        let mut by_position = BTreeMap::new();
+        for side in [1, 2] {
+            let mut filter = LogFilter::new().topic(0, Erc20::transfer_topic());
+            for address in &self.addresses {
+                filter = filter.topic(side, address_topic(address));
+            }
+            let logs = self
+                .web3
+                .get_logs_chunked(&filter, first.number, last.number, DEFAULT_CHUNK_BLOCKS)
+                .await?;
+            for log in logs {
+                let (Some(number), Some(index)) =
+                    (quantity(&log.block_number), quantity(&log.log_index))
+                else {
+                    continue;
+                };
+                by_position.insert((number, index), log);
+            }
+        }
+
+        let mut transfers = Vec::new();
+        for ((number, log_index), log) in by_position {
+            let Some(block) = blocks.iter().find(|block| block.number == number) else {
+                continue;
+            };
+            if log.block_hash != Some(block.hash) {
+                return Ok(None);
+            }
+            let (token, tx_hash) = (log.address, log.transaction_hash.unwrap_or_default());
+            let activity = match (Transfer::try_decode(&log), NftTransfer::try_decode(&log)) {
+                (Some(transfer), _) => Activity::Erc20 {
+                    block: *block,
+                    tx_hash,
+                    log_index,
+                    token,
+                    transfer,
+                },
+                (None, Some(transfer)) => Activity::Erc721 {
+                    block: *block,
+                    tx_hash,
+                    log_index,
+                    token,
+                    transfer,
+                },
+                (None, None) => continue,
+            };
+            transfers.push(activity);
+        }
+        Ok(Some(transfers))
+    }
+
+    /// Transactions with value from or to the addresses that succeeded, in block order;
+    /// `None` when a receipt is from another branch than the blocks.
+    async fn native_transfers(
+        &self,
+        blocks: &[Block],
+        summaries: &[BlockSummary],
+    ) -> Result<Option<Vec<Activity>>, Report<Error>> {
+        let mut candidates: Vec<(BlockSummary, &Transaction, U256)> = Vec::new();
+        for (block, summary) in blocks.iter().zip(summaries) {
+            for tx in block.transactions.as_full().unwrap_or_default() {
+                let touches = self.addresses.contains(&tx.from)
+                    || tx.to.is_some_and(|to| self.addresses.contains(&to));
+                if !touches {
+                    continue;
+                }
+                let value = tx
+                    .value
+                    .parse::<U256>()
+                    .change_context(Error::UnexpectedResponseFormat)
+                    .attach_printable_lazy(|| format!("value of {}", tx.hash))?;
+                if value != U256::from(0u64) {
+                    candidates.push((*summary, tx, value));
+                }
+            }
+        }
+        if candidates.is_empty() {
+            return Ok(Some(Vec::new()));
+        }
+
+        // the value only moved if the transaction did not revert
+        let payloads = candidates
+            .iter()
+            .map(|(_, tx, _)| {
+                json!({ "jsonrpc": "2.0", "method": "eth_getTransactionReceipt", "params": [tx.hash], "id": "328" })
+            })
+            .collect();
+        let responses: Vec<JsonRpcResult<Receipt>> = self.web3.client.call_batch(payloads).await?;
+        let mut transfers = Vec::new();
+        for ((block, tx, value), response) in candidates.into_iter().zip(responses) {
+            let Some(receipt) = response.into_option().map_err(rpc_error)? else {
+                return Ok(None);
+            };
+            if receipt.block_hash != block.hash {
+                return Ok(None);
+            }
+            if receipt.succeeded() == Some(false) {
+                continue;
+            }
+            transfers.push(Activity::Native {
+                block,
+                tx_hash: tx.hash,
+                from: tx.from,
+                to: tx.to,
+                value,
+            });
+        }
+        Ok(Some(transfers))
+    }
+}
+
+fn summary(block: &Block) -> Result<BlockSummary, Report<Error>> {
+    match (block.number, block.hash) {
+        (Some(number), Some(hash)) => Ok(BlockSummary {
+            number,
+            hash,
+            timestamp: block.timestamp,
+        }),
+        _ => Err(Report::new(Error::UnexpectedResponseFormat)
+            .attach_printable("block has no number or hash")),
+    }
+}
+
+fn quantity(field: &Option<String>) -> Option<u64> {
+    let digits = field.as_deref()?.strip_prefix("0x")?;
+    u64::from_str_radix(digits, 16).ok()
+}
+
+/// `address` as an indexed event parameter.
+fn address_topic(address: &Address) -> H256 {
+    let mut topic = [0u8; 32];
+    topic[12..].copy_from_slice(address.as_bytes());
+    H256(topic)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::erc20::topic_address;
+    use crate::model::Log;
+
+    #[test]
+    fn address_topics_round_trip() {
+        let address: Address = "0xb9d7934878b5fb9610b3fe8a5e441e8fad7e293f"
+            .parse()
+            .unwrap();
+        assert_eq!(topic_address(&address_topic(&address)), Some(address));
+        assert_eq!(quantity(&Some("0x1a".to_string())), Some(26));
+        assert_eq!(quantity(&None), None);
+    }
+
+    #[test]
+    fn logs_decode_by_topic_count() {
+        let erc20: Log = serde_json::from_value(json!({
+            "address": "0xdac17f958d2ee523a2206206994597c13d831ec7",
+            "blockHash": null, "blockNumber": null, "logIndex": null,
+            "transactionHash": null, "transactionIndex": null,
+            "data": "0x0000000000000000000000000000000000000000000000000000000005f5e100",
+            "topics": [
+                Erc20::transfer_topic(),
+                "0x000000000000000000000000ae2fc483527b8ef99eb5d9b44875f005ba1fae13",
+                "0x000000000000000000000000b9d7934878b5fb9610b3fe8a5e441e8fad7e293f"
+            ]
+        }))
+        .unwrap();
+        assert!(Transfer::try_decode(&erc20).is_some());
+        assert!(NftTransfer::try_decode(&erc20).is_none());
+    }
+}
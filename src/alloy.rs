@@ -0,0 +1,295 @@
+//! Conversions to and from [alloy](https://github.com/alloy-rs/alloy) types, behind the `alloy`
+//! feature, for code that mixes the two.
+//!
+//! Primitives convert both ways with `From`, byte for byte: [`Address`], [`H256`] (alloy's
+//! `B256`), [`U256`] and [`Bloom`]. alloy's are also taken wherever methods take an
+//! [`AddressParam`], [`H256Param`], [`StorageSlot`] or [`BlockId`], sent as the lowercase hex
+//! ours are. Blocks, transactions, receipts and logs convert with
+//! `TryFrom` to and from their `alloy_rpc_types_eth` counterparts, through the JSON-RPC form
+//! both sides read and write:
+//!
+//! ```
+//! # fn example(block: web3_rpc::model::Block) -> Result<(), error_stack::Report<web3_rpc::client::Error>> {
+//! let theirs = alloy_rpc_types_eth::Block::try_from(block)?;
+//! let ours = web3_rpc::model::Block::try_from(theirs)?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Every field of an Ethereum block, transaction, receipt and log survives the trip, up to
+//! Prague; alloy fills in some it can derive, a legacy transaction's `chainId` from its `v`
+//! for one. It is lossy elsewhere: alloy's types are Ethereum's only, so rollup members kept
+//! in our `extra` maps (an OP-stack receipt's `l1Fee`, say) are dropped, and OP-stack deposit
+//! transactions and receipts fail to convert, as do pending blocks, which alloy requires a
+//! hash of.
+
+use crate::client::Error;
+use crate::model::{Block, BlockId, Log, Receipt, Transaction};
+use crate::types::{Address, AddressParam, Bloom, H256Param, StorageSlot, H256, U256};
+use error_stack::{Report, ResultExt};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+impl From<Address> for alloy_primitives::Address {
+    fn from(address: Address) -> Self {
+        alloy_primitives::Address::from(address.0)
+    }
+}
+
+impl From<alloy_primitives::Address> for Address {
+    fn from(address: alloy_primitives::Address) -> Self {
+        Address(address.into_array())
+    }
+}
+
+impl From<H256> for alloy_primitives::B256 {
+    fn from(hash: H256) -> Self {
+        alloy_primitives::B256::from(hash.0)
+    }
+}
+
+impl From<alloy_primitives::B256> for H256 {
+    fn from(hash: alloy_primitives::B256) -> Self {
+        H256(hash.0)
+    }
+}
+
+impl From<U256> for alloy_primitives::U256 {
+    fn from(value: U256) -> Self {
+        // both little-endian 64-bit limbs
+        alloy_primitives::U256::from_limbs(*value.as_limbs())
+    }
+}
+
+impl From<alloy_primitives::U256> for U256 {
+    fn from(value: alloy_primitives::U256) -> Self {
+        U256::from_limbs(value.into_limbs())
+    }
+}
+
+impl From<Bloom> for alloy_primitives::Bloom {
+    fn from(bloom: Bloom) -> Self {
+        alloy_primitives::Bloom::from(bloom.0)
+    }
+}
+
+impl From<alloy_primitives::Bloom> for Bloom {
+    fn from(bloom: alloy_primitives::Bloom) -> Self {
+        Bloom(bloom.0 .0)
+    }
+}
+
+impl From<alloy_primitives::Address> for AddressParam {
+    fn from(address: alloy_primitives::Address) -> Self {
+        AddressParam::from(Address::from(address))
+    }
+}
+
+impl From<&alloy_primitives::Address> for AddressParam {
+    fn from(address: &alloy_primitives::Address) -> Self {
+        AddressParam::from(*address)
+    }
+}
+
+impl From<alloy_primitives::B256> for H256Param {
+    fn from(hash: alloy_primitives::B256) -> Self {
+        H256Param::from(H256::from(hash))
+    }
+}
+
+impl From<&alloy_primitives::B256> for H256Param {
+    fn from(hash: &alloy_primitives::B256) -> Self {
+        H256Param::from(*hash)
+    }
+}
+
+impl From<alloy_primitives::B256> for StorageSlot {
+    fn from(word: alloy_primitives::B256) -> Self {
+        StorageSlot::from(H256::from(word))
+    }
+}
+
+impl From<alloy_primitives::U256> for StorageSlot {
+    fn from(slot: alloy_primitives::U256) -> Self {
+        StorageSlot::from(U256::from(slot))
+    }
+}
+
+/// A block hash.
+impl From<alloy_primitives::B256> for BlockId {
+    fn from(hash: alloy_primitives::B256) -> Self {
+        BlockId::Hash(hash.into())
+    }
+}
+
+/// `TryFrom` both ways between one of our models and alloy's, through JSON.
+macro_rules! via_json {
+    ($ours:ty, $theirs:ty, $what:literal) => {
+        impl TryFrom<$ours> for $theirs {
+            type Error = Report<Error>;
+
+            fn try_from(ours: $ours) -> Result<Self, Self::Error> {
+                convert(&ours, $what)
+            }
+        }
+
+        impl TryFrom<$theirs> for $ours {
+            type Error = Report<Error>;
+
+            fn try_from(theirs: $theirs) -> Result<Self, Self::Error> {
+                convert(&theirs, $what)
+            }
+        }
+    };
+}
+
+via_json!(Block, alloy_rpc_types_eth::Block, "block");
+via_json!(Transaction, alloy_rpc_types_eth::Transaction, "transaction");
+via_json!(Receipt, alloy_rpc_types_eth::TransactionReceipt, "receipt");
+via_json!(Log, alloy_rpc_types_eth::Log, "log");
+
+fn convert<T: Serialize, U: DeserializeOwned>(from: &T, what: &str) -> Result<U, Report<Error>> {
+    let json = serde_json::to_value(from)
+        .change_context(Error::FailedToDeserialize)
+        .attach_printable_lazy(|| format!("{} to JSON", what))?;
+    serde_path_to_error::deserialize(json)
+        .change_context(Error::FailedToDeserialize)
+        .attach_printable_lazy(|| format!("{} from JSON", what))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::Value;
+
+    /// Whether `fixture`, parsed as ours, to alloy and back, keeps every member it had. alloy
+    /// may add some it derives, a legacy transaction's `chainId` from `v` for one.
+    fn round_trip<Ours, Theirs>(fixture: &Value) -> Result<bool, Report<Error>>
+    where
+        Ours: DeserializeOwned + Serialize + TryFrom<Theirs, Error = Report<Error>>,
+        Theirs: TryFrom<Ours, Error = Report<Error>>,
+    {
+        let ours: Ours = serde_json::from_value(fixture.clone()).unwrap();
+        let before = serde_json::to_value(&ours).unwrap();
+        let back = Ours::try_from(Theirs::try_from(ours)?)?;
+        Ok(kept(&before, &serde_json::to_value(&back).unwrap()))
+    }
+
+    fn kept(before: &Value, after: &Value) -> bool {
+        match (before, after) {
+            (Value::Object(before), Value::Object(after)) => before
+                .iter()
+                .all(|(key, value)| after.get(key).is_some_and(|other| kept(value, other))),
+            (Value::Array(before), Value::Array(after)) => {
+                before.len() == after.len() && before.iter().zip(after).all(|(a, b)| kept(a, b))
+            }
+            _ => before == after,
+        }
+    }
+
+    #[test]
+    fn fixtures_round_trip_through_alloy() {
+        let dir = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures");
+        let mut checked = 0;
+        for entry in std::fs::read_dir(&dir).unwrap() {
+            let path = entry.unwrap().path();
+            if path.is_dir() {
+                continue;
+            }
+            let name = path.file_stem().unwrap().to_str().unwrap().to_string();
+            let fixture: Value =
+                serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+            let outcome = match name.split('_').next().unwrap() {
+                "block" => round_trip::<Block, alloy_rpc_types_eth::Block>(&fixture),
+                "tx" => round_trip::<Transaction, alloy_rpc_types_eth::Transaction>(&fixture),
+                "receipt" => {
+                    round_trip::<Receipt, alloy_rpc_types_eth::TransactionReceipt>(&fixture)
+                }
+                "log" => round_trip::<Log, alloy_rpc_types_eth::Log>(&fixture),
+                _ => continue,
+            };
+            checked += 1;
+            match name.as_str() {
+                // OP-stack deposits are no Ethereum transaction type, and alloy's blocks have
+                // a hash
+                "tx_deposit" | "receipt_deposit" | "block_pending" | "block_pending_geth" => {
+                    assert!(outcome.is_err(), "{}", name)
+                }
+                // rollup members only our models keep
+                "block_polygon_zkevm" | "receipt_base" => assert!(!outcome.unwrap(), "{}", name),
+                _ => assert!(outcome.unwrap(), "{} lost members", name),
+            }
+        }
+        assert!(checked > 20);
+    }
+
+    #[test]
+    fn primitives() {
+        let address: Address = "0x388c818ca8b9251b393131c08a736a67ccb19297"
+            .parse()
+            .unwrap();
+        let theirs = alloy_primitives::Address::from(address);
+        assert_eq!(
+            theirs.to_string().to_lowercase(),
+            "0x388c818ca8b9251b393131c08a736a67ccb19297"
+        );
+        assert_eq!(Address::from(theirs), address);
+
+        let hash: H256 = "0x7a3c1b9e5d2f4a6c8e0b1d3f5a7c9e1b3d5f7a9c1e3b5d7f9a1c3e5b7d9f1a3c"
+            .parse()
+            .unwrap();
+        assert_eq!(H256::from(alloy_primitives::B256::from(hash)), hash);
+        assert_eq!(
+            format!("{:?}", alloy_primitives::B256::from(hash)),
+            format!("{:?}", hash)
+        );
+
+        for value in [
+            U256::ZERO,
+            U256::from(0x1bc16d674ec80000u64),
+            U256::from(u128::MAX),
+            U256::MAX,
+        ] {
+            let theirs: alloy_primitives::U256 = value.into();
+            assert_eq!(theirs.to_string(), value.to_string());
+            assert_eq!(U256::from(theirs), value);
+        }
+
+        let mut bloom = Bloom([0; 256]);
+        bloom.0[3] = 0x80;
+        bloom.0[255] = 1;
+        assert_eq!(Bloom::from(alloy_primitives::Bloom::from(bloom)), bloom);
+    }
+
+    #[test]
+    fn params_are_lowercase_hex() {
+        let checksummed = "0x388C818CA8B9251b393131C08a736A67ccB19297";
+        let address: alloy_primitives::Address = checksummed.parse().unwrap();
+        assert_eq!(
+            AddressParam::from(address).as_str(),
+            checksummed.to_lowercase()
+        );
+        assert_eq!(AddressParam::from(&address), AddressParam::from(address));
+
+        let hash = "0x7a3c1b9e5d2f4a6c8e0b1d3f5a7c9e1b3d5f7a9c1e3b5d7f9a1c3e5b7d9f1a3c";
+        let b256: alloy_primitives::B256 =
+            hash.to_uppercase().replacen("0X", "0x", 1).parse().unwrap();
+        assert_eq!(H256Param::from(b256).as_str(), hash);
+        assert_eq!(H256Param::from(&b256).as_str(), hash);
+        assert_eq!(BlockId::from(b256).to_string(), hash);
+
+        // slots are quantities, as ours
+        assert_eq!(
+            StorageSlot::from(alloy_primitives::U256::from(42u64)).as_str(),
+            "0x2a"
+        );
+        assert_eq!(
+            StorageSlot::from(alloy_primitives::B256::with_last_byte(0x2a)).as_str(),
+            "0x2a"
+        );
+        assert_eq!(
+            StorageSlot::from(alloy_primitives::U256::ZERO).as_str(),
+            "0x0"
+        );
+    }
+}
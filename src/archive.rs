@@ -0,0 +1,199 @@
+//! How far back a node keeps state. Pruned nodes answer historical state queries with an
+//! error whose text differs per client; [`is_state_unavailable`] knows the ones in [`RULES`].
+//!
+//! ```no_run
+//! # use web3_rpc::archive::StateDepth;
+//! # use web3_rpc::web3::Web3;
+//! # async fn example(rpc: Web3) -> Result<(), error_stack::Report<web3_rpc::client::Error>> {
+//! match rpc.detect_state_depth().await? {
+//!     StateDepth::Archive => {}
+//!     StateDepth::Pruned { earliest_available_block } => println!("state from {}", earliest_available_block),
+//!     StateDepth::Unknown => {}
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::client::Error;
+use crate::endpoints::head;
+use crate::model::BlockId;
+use crate::types::Address;
+use crate::web3::Web3;
+use error_stack::Report;
+
+/// One client's way of saying the state for a block is gone. Every text must be in the
+/// message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rule {
+    pub client: &'static str,
+    /// Lowercase.
+    pub contains: &'static [&'static str],
+}
+
+pub const RULES: &[Rule] = &[
+    Rule {
+        client: "geth",
+        contains: &["missing trie node"],
+    },
+    Rule {
+        client: "geth",
+        contains: &["historical state", "not available"],
+    },
+    Rule {
+        client: "erigon",
+        contains: &["not available due to pruning"],
+    },
+    Rule {
+        client: "erigon",
+        contains: &["state", "not available"],
+    },
+    Rule {
+        client: "reth",
+        contains: &["state at block", "is pruned"],
+    },
+    Rule {
+        client: "nethermind",
+        contains: &["no state available"],
+    },
+];
+
+/// The client whose rule matches a JSON-RPC error message, if one does.
+pub fn state_unavailable_client(message: &str) -> Option<&'static str> {
+    let message = message.to_lowercase();
+    RULES
+        .iter()
+        .find(|rule| rule.contains.iter().all(|text| message.contains(text)))
+        .map(|rule| rule.client)
+}
+
+/// Whether a JSON-RPC error message says the requested state has been pruned.
+pub fn is_state_unavailable(message: &str) -> bool {
+    state_unavailable_client(message).is_some()
+}
+
+/// The answer of [`Web3::detect_state_depth`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StateDepth {
+    /// State back to block 1 is there.
+    Archive,
+    /// State before `earliest_available_block` is gone. Pruning goes on, so this moves up
+    /// with the head.
+    Pruned { earliest_available_block: u64 },
+    /// The node failed historical queries with an error none of [`RULES`] matches.
+    Unknown,
+}
+
+impl Web3 {
+    /// Finds the oldest block whose state the node still has, by binary search with
+    /// eth_getBalance between block 1 and the head: about 2 + log2(head) requests.
+    ///
+    /// Errors that do not come from the node, such as connection failures, are returned.
+    pub async fn detect_state_depth(&self) -> Result<StateDepth, Report<Error>> {
+        if self.has_state_at(1).await? == Some(true) {
+            return Ok(StateDepth::Archive);
+        }
+        let mut available = head(self).await?;
+        // a chain without block 1 has no history to keep
+        if available <= 1 || self.has_state_at(available).await? != Some(true) {
+            return Ok(StateDepth::Unknown);
+        }
+        // state at `missing` is gone, at `available` it is there
+        let mut missing = 1;
+        while available - missing > 1 {
+            let mid = missing + (available - missing) / 2;
+            match self.has_state_at(mid).await? {
+                Some(true) => available = mid,
+                Some(false) => missing = mid,
+                None => return Ok(StateDepth::Unknown),
+            }
+        }
+        Ok(StateDepth::Pruned {
+            earliest_available_block: available,
+        })
+    }
+
+    /// Whether the node has the state at block 1, with one request. `false` as well when it
+    /// fails the query in a way [`RULES`] does not know.
+    pub async fn is_archive(&self) -> Result<bool, Report<Error>> {
+        Ok(self.has_state_at(1).await? == Some(true))
+    }
+
+    /// `None` for a JSON-RPC error that does not say the state is gone.
+    async fn has_state_at(&self, block: u64) -> Result<Option<bool>, Report<Error>> {
+        let balance = self
+            .eth_get_balance_at_block(Address::ZERO, BlockId::Number(block))
+            .await;
+        match balance {
+            Ok(r) => match r.error {
+                None => Ok(Some(true)),
+                Some(error) => Ok(is_state_unavailable(&error.message).then_some(false)),
+            },
+            Err(report) => match report.current_context() {
+                Error::JsonRpcError { message, .. } => {
+                    Ok(is_state_unavailable(message).then_some(false))
+                }
+                _ => Err(report),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::RpcErrorObject;
+    use serde::Deserialize;
+
+    #[derive(Deserialize)]
+    struct ErrorResponse {
+        error: RpcErrorObject,
+    }
+
+    fn message(fixture: &str) -> String {
+        serde_json::from_str::<ErrorResponse>(fixture)
+            .unwrap()
+            .error
+            .message
+    }
+
+    #[test]
+    fn state_unavailable_per_client() {
+        for (fixture, client) in [
+            (
+                include_str!("../tests/fixtures/state_missing_geth.json"),
+                "geth",
+            ),
+            (
+                include_str!("../tests/fixtures/state_missing_geth_path.json"),
+                "geth",
+            ),
+            (
+                include_str!("../tests/fixtures/state_missing_erigon.json"),
+                "erigon",
+            ),
+            (
+                include_str!("../tests/fixtures/state_missing_reth.json"),
+                "reth",
+            ),
+            (
+                include_str!("../tests/fixtures/state_missing_nethermind.json"),
+                "nethermind",
+            ),
+        ] {
+            let message = message(fixture);
+            assert_eq!(
+                state_unavailable_client(&message),
+                Some(client),
+                "{}",
+                message
+            );
+        }
+        for message in [
+            "header not found",
+            "execution reverted",
+            "missing required field 'to'",
+        ] {
+            assert!(!is_state_unavailable(message), "{}", message);
+        }
+    }
+}
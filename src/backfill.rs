@@ -0,0 +1,219 @@
+//! Ordered, resumable block backfill for indexers.
+//!
+//! Blocks are fetched with full transactions, a bounded window of them at once, and handed
+//! out strictly in ascending order however the requests complete.
+//!
+//! ```no_run
+//! # async fn run(web3: web3_rpc::web3::Web3, checkpoint: u64) {
+//! let mut backfill = web3
+//!     .backfill_blocks(17_000_000..=17_100_000, 8, true)
+//!     .resume_from(checkpoint);
+//! while let Some(item) = backfill.next().await {
+//!     let (block, receipts) = item.unwrap();
+//!     println!("{:?} {}", block.number, receipts.unwrap_or_default().len());
+//!     let progress = backfill.progress();
+//!     println!("{} done, {} to go", progress.done, progress.remaining);
+//! }
+//! # }
+//! ```
+
+use crate::client::{rpc_error, Error, RetryPolicy};
+use crate::model::{Block, JsonRpcResult, Receipt};
+use crate::web3::Web3;
+use error_stack::Report;
+use serde_json::json;
+use std::collections::BTreeMap;
+use std::ops::RangeInclusive;
+use tokio::task::JoinSet;
+
+/// A block, and its receipts in transaction order when they were asked for.
+pub type BackfilledBlock = (Block, Option<Vec<Receipt>>);
+
+/// Where a [`Backfill`] stands.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Progress {
+    /// Blocks of the range handed out so far, including any skipped by
+    /// [`Backfill::resume_from`].
+    pub done: u64,
+    pub remaining: u64,
+    /// The block the next call to [`Backfill::next`] returns, the one to resume from after
+    /// a crash or an error. `None` once the range is exhausted.
+    pub next_block: Option<u64>,
+}
+
+/// Returned by [`Web3::backfill_blocks`]; call [`next`](Self::next) until it returns `None`.
+pub struct Backfill<'a> {
+    web3: &'a Web3,
+    start: u64,
+    end: u64,
+    /// Next block to hand out.
+    cursor: u64,
+    /// Next block to request; at most `concurrency` ahead of `cursor`.
+    requested: u64,
+    concurrency: u64,
+    with_receipts: bool,
+    retry: RetryPolicy,
+    tasks: JoinSet<(u64, Result<BackfilledBlock, Report<Error>>)>,
+    ready: BTreeMap<u64, BackfilledBlock>,
+    failed: bool,
+}
+
+impl Web3 {
+    /// Blocks of `range` in ascending order, fetching up to `concurrency` of them at once.
+    /// With `with_receipts` each block comes with the receipts of its transactions.
+    pub fn backfill_blocks(
+        &self,
+        range: RangeInclusive<u64>,
+        concurrency: usize,
+        with_receipts: bool,
+    ) -> Backfill<'_> {
+        let (start, end) = range.into_inner();
+        Backfill {
+            web3: self,
+            start,
+            end,
+            cursor: start,
+            requested: start,
+            concurrency: concurrency.max(1) as u64,
+            with_receipts,
+            retry: RetryPolicy::default(),
+            tasks: JoinSet::new(),
+            ready: BTreeMap::new(),
+            failed: false,
+        }
+    }
+}
+
+impl<'a> Backfill<'a> {
+    /// Skips the blocks before `block`, typically [`Progress::next_block`] saved by an
+    /// earlier run. Call it before the first [`next`](Self::next).
+    pub fn resume_from(mut self, block: u64) -> Self {
+        self.cursor = block.max(self.start);
+        self.requested = self.cursor;
+        self
+    }
+
+    /// Retries for each block failing with a retryable error, on top of any retry policy
+    /// on the client. [`RetryPolicy::default`] unless set.
+    pub fn retry(mut self, policy: RetryPolicy) -> Self {
+        self.retry = policy;
+        self
+    }
+
+    pub fn progress(&self) -> Progress {
+        let total = (self.end + 1).saturating_sub(self.start);
+        let done = self.cursor.saturating_sub(self.start).min(total);
+        Progress {
+            done,
+            remaining: total - done,
+            next_block: (self.cursor <= self.end).then_some(self.cursor),
+        }
+    }
+
+    /// The next block in order, `None` when the range is done. After an error the backfill
+    /// stops; resume a new one from [`Progress::next_block`].
+    pub async fn next(&mut self) -> Option<Result<BackfilledBlock, Report<Error>>> {
+        if self.failed || self.cursor > self.end {
+            return None;
+        }
+        loop {
+            if let Some(block) = self.ready.remove(&self.cursor) {
+                self.cursor += 1;
+                return Some(Ok(block));
+            }
+            while self.requested <= self.end && self.requested < self.cursor + self.concurrency {
+                let (web3, number) = (self.web3.clone(), self.requested);
+                let (with_receipts, retry) = (self.with_receipts, self.retry);
+                self.tasks.spawn(async move {
+                    (
+                        number,
+                        fetch_retrying(&web3, number, with_receipts, retry).await,
+                    )
+                });
+                self.requested += 1;
+            }
+            let joined = match self.tasks.join_next().await {
+                Some(joined) => joined,
+                None => return None,
+            };
+            match joined {
+                Ok((number, Ok(block))) => {
+                    self.ready.insert(number, block);
+                }
+                Ok((number, Err(report))) => {
+                    return Some(Err(
+                        self.fail(report.attach_printable(format!("block {}", number)))
+                    ))
+                }
+                Err(e) => {
+                    let report = Report::new(Error::IoError)
+                        .attach_printable(format!("backfill task failed: {}", e));
+                    return Some(Err(self.fail(report)));
+                }
+            }
+        }
+    }
+
+    fn fail(&mut self, report: Report<Error>) -> Report<Error> {
+        self.failed = true;
+        self.tasks.abort_all();
+        self.ready.clear();
+        report
+    }
+}
+
+async fn fetch_retrying(
+    web3: &Web3,
+    number: u64,
+    with_receipts: bool,
+    retry: RetryPolicy,
+) -> Result<BackfilledBlock, Report<Error>> {
+    let mut attempt = 0;
+    loop {
+        match fetch(web3, number, with_receipts).await {
+            Err(e) if e.current_context().is_retryable() && attempt < retry.max_retries => {
+                tracing::debug!(number, attempt, "retrying block: {}", e.current_context());
+                tokio::time::sleep(retry.backoff(attempt)).await;
+                attempt += 1;
+            }
+            r => return r,
+        }
+    }
+}
+
+async fn fetch(
+    web3: &Web3,
+    number: u64,
+    with_receipts: bool,
+) -> Result<BackfilledBlock, Report<Error>> {
+    let block = web3
+        .eth_get_block(number, true)
+        .await?
+        .result
+        .ok_or_else(|| {
+            Report::new(Error::UnexpectedResponseFormat)
+                .attach_printable(format!("block {} not found", number))
+        })?;
+    if !with_receipts {
+        return Ok((block, None));
+    }
+    let hashes = block.transactions.hashes();
+    let payloads = hashes
+        .iter()
+        .map(|hash| {
+            json!({ "jsonrpc": "2.0", "method": "eth_getTransactionReceipt", "params": [hash], "id": "328" })
+        })
+        .collect();
+    let responses: Vec<JsonRpcResult<Receipt>> = web3.client.call_batch(payloads).await?;
+    let receipts = responses
+        .into_iter()
+        .zip(&hashes)
+        .map(|(response, hash)| match (response.result, response.error) {
+            (_, Some(error)) => Err(rpc_error(error)),
+            (Some(receipt), None) => Ok(receipt),
+            (None, None) => Err(Report::new(Error::UnexpectedResponseFormat)
+                .attach_printable(format!("receipt for {} not found", hash))),
+        })
+        .collect::<Result<_, _>>()?;
+    Ok((block, Some(receipts)))
+}
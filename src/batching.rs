@@ -0,0 +1,322 @@
+//! Micro-batching: calls made by concurrent tasks within a few milliseconds of each other go out
+//! as one JSON-RPC batch, each caller still getting its own response or error.
+//!
+//! Install with [`crate::client::Client::with_micro_batching`] or
+//! [`crate::builder::Web3Builder::micro_batch`]. A call made while the client has nothing
+//! queued or in flight goes out as soon as the runtime has run the tasks already woken, so a
+//! lone call waits for no window. Others wait up to [`MicroBatchPolicy::window`] for company,
+//! and a full batch goes out at once. Batches the endpoint refuses are sent again call by call.
+//!
+//! A batch is retried only when every call in it may be (see
+//! [`crate::client::Client::idempotency`]); a call answered with a retryable error is resent
+//! on its own unless it is [`crate::client::Idempotency::Never`]. Methods in [`UNBATCHED_METHODS`], or excluded by the policy, always go
+//! out alone.
+
+use crate::client::Error;
+use bytes::Bytes;
+use error_stack::{Report, ResultExt};
+use serde_json::Value;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::oneshot;
+
+/// How long a call waits for others to share its batch, unless the policy says otherwise.
+pub const DEFAULT_WINDOW: Duration = Duration::from_millis(2);
+
+/// Calls in a batch unless the policy says otherwise.
+pub const DEFAULT_MAX_BATCH: usize = 100;
+
+/// Never batched: subscriptions live on their own.
+pub const UNBATCHED_METHODS: &[&str] = &["eth_subscribe", "eth_unsubscribe"];
+
+/// Methods that send transactions, see [`MicroBatchPolicy::exclude_sends`].
+const SEND_METHODS: &[&str] = &["eth_sendRawTransaction", "eth_sendTransaction"];
+
+/// When calls are batched.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MicroBatchPolicy {
+    /// The longest a call waits for others while the client is busy.
+    pub window: Duration,
+    /// A batch this large goes out without waiting for the window.
+    pub max_batch: usize,
+    /// Methods always sent alone, besides [`UNBATCHED_METHODS`].
+    pub excluded: Vec<String>,
+}
+
+impl Default for MicroBatchPolicy {
+    fn default() -> Self {
+        MicroBatchPolicy {
+            window: DEFAULT_WINDOW,
+            max_batch: DEFAULT_MAX_BATCH,
+            excluded: Vec::new(),
+        }
+    }
+}
+
+impl MicroBatchPolicy {
+    pub fn new(window: Duration) -> Self {
+        MicroBatchPolicy {
+            window,
+            ..Default::default()
+        }
+    }
+
+    pub fn max_batch(mut self, calls: usize) -> Self {
+        self.max_batch = calls.max(1);
+        self
+    }
+
+    pub fn exclude(mut self, method: impl Into<String>) -> Self {
+        self.excluded.push(method.into());
+        self
+    }
+
+    /// Sends transactions alone, so that a resend of one is checked against the node's
+    /// "already known" as [`crate::client::Idempotency::CheckedResend`] describes.
+    pub fn exclude_sends(self) -> Self {
+        SEND_METHODS
+            .iter()
+            .fold(self, |policy, method| policy.exclude(*method))
+    }
+}
+
+/// A call's response, or its error shared with the rest of a failed batch.
+pub(crate) type Answer = Result<Bytes, Arc<Report<Error>>>;
+
+pub(crate) type Reply = oneshot::Sender<Answer>;
+
+/// Calls waiting to be sent.
+#[derive(Debug)]
+pub(crate) struct Batcher {
+    policy: MicroBatchPolicy,
+    state: Mutex<State>,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    queue: Vec<(Value, Reply)>,
+    /// Batches and lone calls sent and not answered yet.
+    in_flight: usize,
+}
+
+/// What the caller that queued a call has to set off.
+pub(crate) enum Flush {
+    /// A send of whatever is queued after the delay; zero when the client was idle.
+    After(Duration),
+    /// A send of these calls now.
+    Now(Vec<(Value, Reply)>),
+}
+
+impl Batcher {
+    pub(crate) fn new(policy: MicroBatchPolicy) -> Self {
+        Batcher {
+            policy,
+            state: Mutex::default(),
+        }
+    }
+
+    /// Whether `payload` may share a batch.
+    pub(crate) fn batches(&self, payload: &Value) -> bool {
+        let Some(method) = payload.get("method").and_then(Value::as_str) else {
+            return false;
+        };
+        !UNBATCHED_METHODS.contains(&method) && !self.policy.excluded.iter().any(|m| m == method)
+    }
+
+    /// Queues `payload`; its response arrives on the receiver once a flush sends it.
+    pub(crate) fn queue(&self, payload: Value) -> (oneshot::Receiver<Answer>, Option<Flush>) {
+        let (reply, receiver) = oneshot::channel();
+        let mut state = self.state.lock().unwrap();
+        state.queue.push((payload, reply));
+        let flush = if state.queue.len() >= self.policy.max_batch {
+            state.in_flight += 1;
+            Some(Flush::Now(std::mem::take(&mut state.queue)))
+        } else if state.queue.len() == 1 && state.in_flight == 0 {
+            Some(Flush::After(Duration::ZERO))
+        } else if state.queue.len() == 1 {
+            Some(Flush::After(self.policy.window))
+        } else {
+            None
+        };
+        (receiver, flush)
+    }
+
+    /// Everything queued, to be sent now; `None` if an earlier flush took it.
+    pub(crate) fn take(&self) -> Option<Vec<(Value, Reply)>> {
+        let mut state = self.state.lock().unwrap();
+        if state.queue.is_empty() {
+            return None;
+        }
+        state.in_flight += 1;
+        Some(std::mem::take(&mut state.queue))
+    }
+
+    /// A send from [`Batcher::take`] or [`Flush::Now`] was answered.
+    fn sent(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.in_flight = state.in_flight.saturating_sub(1);
+    }
+}
+
+/// A send from [`Batcher::take`] or [`Flush::Now`], counted as answered when dropped, even
+/// by a task that panicked or was aborted.
+pub(crate) struct InFlight(pub(crate) Arc<Batcher>);
+
+impl Drop for InFlight {
+    fn drop(&mut self) {
+        self.0.sent();
+    }
+}
+
+/// `payloads` as one batch, their ids replaced with their positions.
+pub(crate) fn batch(payloads: &[Value]) -> Value {
+    Value::Array(
+        payloads
+            .iter()
+            .enumerate()
+            .map(|(i, payload)| {
+                let mut payload = payload.clone();
+                payload["id"] = Value::String(i.to_string());
+                payload
+            })
+            .collect(),
+    )
+}
+
+/// The body of a batch response split into each call's response, with the call's own id back.
+/// A call the batch does not answer gets an error of its own.
+pub(crate) fn split(
+    body: &[u8],
+    payloads: &[Value],
+) -> Result<Vec<Result<Bytes, Report<Error>>>, Report<Error>> {
+    let responses: Vec<Value> = serde_json::from_slice(body)
+        .change_context(Error::UnexpectedResponseFormat)
+        .attach_printable("batch response is not an array")?;
+    let mut split: Vec<Option<Value>> = vec![None; payloads.len()];
+    for response in responses {
+        let index = response["id"]
+            .as_str()
+            .and_then(|id| id.parse::<usize>().ok());
+        match index.and_then(|i| split.get_mut(i)) {
+            Some(slot @ None) => *slot = Some(response),
+            _ => {
+                return Err(Report::new(Error::IdMismatch {
+                    expected: format!("0 to {}", payloads.len() - 1),
+                    got: response["id"].to_string(),
+                })
+                .attach_printable("batch responses must answer distinct requests"))
+            }
+        }
+    }
+    Ok(split
+        .into_iter()
+        .zip(payloads)
+        .map(|(response, payload)| {
+            let mut response = response.ok_or_else(|| {
+                Report::new(Error::UnexpectedResponseFormat)
+                    .attach_printable("the batch response has no answer to this call")
+            })?;
+            response["id"] = payload["id"].clone();
+            serde_json::to_vec(&response)
+                .map(Bytes::from)
+                .change_context(Error::UnexpectedResponseFormat)
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn call(method: &str, id: &str) -> Value {
+        json!({ "jsonrpc": "2.0", "method": method, "params": [], "id": id })
+    }
+
+    #[test]
+    fn idle_clients_flush_at_once() {
+        let batcher = Batcher::new(MicroBatchPolicy::new(Duration::from_millis(5)).max_batch(3));
+        let (_a, flush) = batcher.queue(call("eth_chainId", "339"));
+        assert!(matches!(flush, Some(Flush::After(delay)) if delay.is_zero()));
+        let (_b, flush) = batcher.queue(call("eth_blockNumber", "327"));
+        assert!(flush.is_none());
+        assert_eq!(batcher.take().unwrap().len(), 2);
+        assert!(batcher.take().is_none());
+
+        // busy: the next call waits for the window, until the batch is full
+        let (_c, flush) = batcher.queue(call("eth_chainId", "339"));
+        assert!(matches!(flush, Some(Flush::After(delay)) if delay == Duration::from_millis(5)));
+        batcher.queue(call("eth_chainId", "339"));
+        let (_e, flush) = batcher.queue(call("eth_chainId", "339"));
+        assert!(matches!(flush, Some(Flush::Now(calls)) if calls.len() == 3));
+
+        batcher.sent();
+        batcher.sent();
+        let (_f, flush) = batcher.queue(call("eth_chainId", "339"));
+        assert!(matches!(flush, Some(Flush::After(delay)) if delay.is_zero()));
+    }
+
+    #[tokio::test]
+    async fn aborted_sends_leave_the_client_idle() {
+        let batcher = Arc::new(Batcher::new(MicroBatchPolicy::new(Duration::from_millis(
+            5,
+        ))));
+        batcher.queue(call("eth_chainId", "339"));
+        let calls = batcher.take().unwrap();
+        let in_flight = InFlight(batcher.clone());
+        let send = tokio::spawn(async move {
+            let _in_flight = in_flight;
+            let _calls = calls;
+            std::future::pending::<()>().await;
+        });
+        send.abort();
+        let _ = send.await;
+
+        let (_a, flush) = batcher.queue(call("eth_chainId", "339"));
+        assert!(matches!(flush, Some(Flush::After(delay)) if delay.is_zero()));
+    }
+
+    #[test]
+    fn excluded_methods() {
+        let batcher = Batcher::new(MicroBatchPolicy::default().exclude_sends());
+        assert!(batcher.batches(&call("eth_getBalance", "311")));
+        assert!(!batcher.batches(&call("eth_subscribe", "1")));
+        assert!(!batcher.batches(&call("eth_sendRawTransaction", "1")));
+        assert!(!batcher.batches(&json!([call("eth_getBalance", "311")])));
+    }
+
+    #[test]
+    fn responses_go_back_to_their_calls() {
+        let payloads = vec![
+            call("eth_chainId", "339"),
+            call("eth_blockNumber", "327"),
+            call("eth_gasPrice", "9"),
+        ];
+        let sent = batch(&payloads);
+        assert_eq!(sent[1]["id"], "1");
+        assert_eq!(payloads[1]["id"], "327");
+
+        let body = json!([
+            { "jsonrpc": "2.0", "id": "1", "result": "0x10" },
+            { "jsonrpc": "2.0", "id": "0", "error": { "code": -32601, "message": "nope" } },
+        ]);
+        let split = split(&serde_json::to_vec(&body).unwrap(), &payloads).unwrap();
+        let response =
+            |i: usize| -> Value { serde_json::from_slice(split[i].as_ref().unwrap()).unwrap() };
+        assert_eq!(response(0)["id"], "339");
+        assert_eq!(response(0)["error"]["code"], -32601);
+        assert_eq!(
+            response(1),
+            json!({ "jsonrpc": "2.0", "id": "327", "result": "0x10" })
+        );
+        assert!(split[2].is_err());
+
+        let duplicate = json!([{ "jsonrpc": "2.0", "id": "0", "result": 1 }, { "jsonrpc": "2.0", "id": "0", "result": 1 }]);
+        assert!(super::split(&serde_json::to_vec(&duplicate).unwrap(), &payloads).is_err());
+        assert!(super::split(
+            br#"{"jsonrpc":"2.0","id":null,"error":{"code":-32600,"message":"batch"}}"#,
+            &payloads
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,99 @@
+//! Providers tell clients to slow down in different shapes: Infura answers -32005 with a
+//! `backoff_seconds` hint, Alchemy HTTP 429 with a JSON-RPC body about compute units, public
+//! nodes -32097 or a bare 503. [`classify`] maps the known shapes, listed in [`RULES`], to
+//! [`Error::CapacityExceeded`].
+
+use crate::client::Error;
+use crate::model::RpcErrorObject;
+use reqwest::StatusCode;
+use std::time::Duration;
+
+/// One known shape of a capacity error. Every field that is set must match.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rule {
+    /// Reported as `provider_hint`.
+    pub provider: &'static str,
+    /// HTTP status of the response.
+    pub status: Option<StatusCode>,
+    /// Code of the JSON-RPC error object.
+    pub code: Option<i64>,
+    /// Lowercase text the error message contains.
+    pub message: Option<&'static str>,
+}
+
+/// Checked in order, the first match wins.
+pub const RULES: &[Rule] = &[
+    Rule {
+        provider: "infura",
+        status: None,
+        code: Some(-32005),
+        message: Some("request rate exceeded"),
+    },
+    Rule {
+        provider: "infura",
+        status: None,
+        code: Some(-32005),
+        message: Some("daily request count exceeded"),
+    },
+    Rule {
+        provider: "alchemy",
+        status: None,
+        code: Some(429),
+        message: Some("compute units"),
+    },
+    Rule {
+        provider: "public node",
+        status: None,
+        code: Some(-32097),
+        message: None,
+    },
+    Rule {
+        provider: "http 429",
+        status: Some(StatusCode::TOO_MANY_REQUESTS),
+        code: None,
+        message: None,
+    },
+    Rule {
+        provider: "http 503",
+        status: Some(StatusCode::SERVICE_UNAVAILABLE),
+        code: None,
+        message: None,
+    },
+];
+
+impl Rule {
+    fn matches(&self, status: StatusCode, error: Option<&RpcErrorObject>) -> bool {
+        self.status.is_none_or(|s| s == status)
+            && self
+                .code
+                .is_none_or(|code| error.is_some_and(|e| e.code == code))
+            && self
+                .message
+                .is_none_or(|text| error.is_some_and(|e| e.message.to_lowercase().contains(text)))
+    }
+}
+
+/// The [`Error::CapacityExceeded`] for a response, if it matches one of [`RULES`].
+/// `retry_after` comes from the `Retry-After` header; Infura's `data.backoff_seconds` is used
+/// when the header is missing.
+pub fn classify(
+    status: StatusCode,
+    error: Option<&RpcErrorObject>,
+    retry_after: Option<Duration>,
+) -> Option<Error> {
+    let rule = RULES.iter().find(|rule| rule.matches(status, error))?;
+    let backoff = error
+        .and_then(|e| e.data.as_ref())
+        .and_then(|data| data["backoff_seconds"].as_f64())
+        .and_then(|seconds| Duration::try_from_secs_f64(seconds).ok());
+    Some(Error::CapacityExceeded {
+        retry_after: retry_after.or(backoff),
+        provider_hint: rule.provider,
+    })
+}
+
+/// Delay in a `Retry-After` header given in seconds; HTTP dates are not used by RPC providers.
+pub(crate) fn retry_after(headers: &reqwest::header::HeaderMap) -> Option<Duration> {
+    let value = headers.get(reqwest::header::RETRY_AFTER)?.to_str().ok()?;
+    value.trim().parse().ok().map(Duration::from_secs)
+}
@@ -0,0 +1,255 @@
+//! Circuit breaking for an endpoint that fails hard. After enough failed requests the circuit
+//! opens and calls fail fast with [`Error::CircuitOpen`] instead of adding to the load; once
+//! the cool-down is over a single probe request decides whether it closes again.
+//!
+//! Install with [`crate::client::Client::with_circuit_breaker`] or
+//! [`crate::builder::Web3Builder::circuit_breaker`]. Failures are requests that got no response
+//! and HTTP 5xx; JSON-RPC errors and 429s come from a node that is up and do not count.
+
+use crate::client::Error;
+use error_stack::Report;
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// When the circuit opens and for how long.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CircuitBreakerPolicy {
+    /// Failures that open the circuit...
+    pub failures: u32,
+    /// ...among this many of the latest requests; equal to `failures` for consecutive ones.
+    pub window: u32,
+    /// How long calls fail fast before a probe is let through.
+    pub cool_down: Duration,
+}
+
+impl Default for CircuitBreakerPolicy {
+    fn default() -> Self {
+        CircuitBreakerPolicy {
+            failures: 5,
+            window: 5,
+            cool_down: Duration::from_secs(30),
+        }
+    }
+}
+
+impl CircuitBreakerPolicy {
+    /// Opens after `failures` consecutive failures.
+    pub fn new(failures: u32) -> Self {
+        let failures = failures.max(1);
+        CircuitBreakerPolicy {
+            failures,
+            window: failures,
+            ..Default::default()
+        }
+    }
+
+    /// Opens after `failures` of the last `window` requests failed, instead of consecutive
+    /// ones.
+    pub fn of_last(mut self, window: u32) -> Self {
+        self.window = window.max(self.failures);
+        self
+    }
+
+    pub fn cool_down(mut self, cool_down: Duration) -> Self {
+        self.cool_down = cool_down;
+        self
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CircuitState {
+    /// Requests go out as usual.
+    Closed,
+    /// Requests fail fast until the cool-down is over.
+    Open,
+    /// One probe is in flight; others fail fast until it comes back.
+    HalfOpen,
+}
+
+/// Told about every state change, e.g. to export it as a metric.
+pub trait CircuitObserver: Send + Sync {
+    /// `endpoint` is the redacted host, as in error reports.
+    fn transition(&self, endpoint: &str, from: CircuitState, to: CircuitState);
+}
+
+/// A state change, for the client to report.
+pub(crate) type Transition = Option<(CircuitState, CircuitState)>;
+
+/// Breaker state behind [`crate::client::Client::with_circuit_breaker`].
+#[derive(Debug)]
+pub(crate) struct CircuitBreaker {
+    policy: CircuitBreakerPolicy,
+    state: Mutex<Breaker>,
+}
+
+#[derive(Debug)]
+struct Breaker {
+    state: CircuitState,
+    /// Latest outcomes while closed, `true` for a failure.
+    outcomes: VecDeque<bool>,
+    retry_at: Instant,
+}
+
+impl CircuitBreaker {
+    pub(crate) fn new(policy: CircuitBreakerPolicy) -> Self {
+        CircuitBreaker {
+            policy,
+            state: Mutex::new(Breaker {
+                state: CircuitState::Closed,
+                outcomes: VecDeque::new(),
+                retry_at: Instant::now(),
+            }),
+        }
+    }
+
+    pub(crate) fn policy(&self) -> CircuitBreakerPolicy {
+        self.policy
+    }
+
+    pub(crate) fn state(&self) -> CircuitState {
+        self.lock().state
+    }
+
+    /// Whether [`CircuitBreaker::admit`] would let a request through at `now`.
+    pub(crate) fn available(&self, now: Instant) -> bool {
+        let breaker = self.lock();
+        breaker.state == CircuitState::Closed || now >= breaker.retry_at
+    }
+
+    /// When a probe is let through, for an open circuit.
+    pub(crate) fn retry_at(&self) -> Instant {
+        self.lock().retry_at
+    }
+
+    /// Lets a request through, turning the first one after the cool-down into the probe.
+    pub(crate) fn admit(&self) -> (Result<(), Report<Error>>, Transition) {
+        let mut breaker = self.lock();
+        let now = Instant::now();
+        match breaker.state {
+            CircuitState::Closed => (Ok(()), None),
+            // a probe dropped by its caller never reports back, another goes out a cool-down later
+            state if now >= breaker.retry_at => {
+                breaker.state = CircuitState::HalfOpen;
+                breaker.retry_at = now + self.policy.cool_down;
+                let transition =
+                    (state == CircuitState::Open).then_some((state, CircuitState::HalfOpen));
+                (Ok(()), transition)
+            }
+            CircuitState::Open | CircuitState::HalfOpen => {
+                let retry_at = breaker.retry_at.max(now);
+                let report = Report::new(Error::CircuitOpen { retry_at })
+                    .attach_printable(format!("retry in {:?}", retry_at - now));
+                (Err(report), None)
+            }
+        }
+    }
+
+    /// Records the outcome of an admitted request.
+    pub(crate) fn record(&self, failed: bool) -> Transition {
+        let mut breaker = self.lock();
+        match breaker.state {
+            CircuitState::HalfOpen if failed => {
+                breaker.state = CircuitState::Open;
+                breaker.retry_at = Instant::now() + self.policy.cool_down;
+                Some((CircuitState::HalfOpen, CircuitState::Open))
+            }
+            CircuitState::HalfOpen => {
+                breaker.state = CircuitState::Closed;
+                breaker.outcomes.clear();
+                Some((CircuitState::HalfOpen, CircuitState::Closed))
+            }
+            CircuitState::Closed => {
+                breaker.outcomes.push_back(failed);
+                while breaker.outcomes.len() > self.policy.window as usize {
+                    breaker.outcomes.pop_front();
+                }
+                let failures = breaker.outcomes.iter().filter(|failed| **failed).count();
+                if failures < self.policy.failures as usize {
+                    return None;
+                }
+                breaker.state = CircuitState::Open;
+                breaker.retry_at = Instant::now() + self.policy.cool_down;
+                breaker.outcomes.clear();
+                Some((CircuitState::Closed, CircuitState::Open))
+            }
+            // answers to requests sent before the circuit opened
+            CircuitState::Open => None,
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Breaker> {
+        self.state
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn consecutive_failures_open_the_circuit() {
+        let breaker = CircuitBreaker::new(CircuitBreakerPolicy::new(3));
+        assert_eq!(breaker.record(true), None);
+        assert_eq!(breaker.record(true), None);
+        // a success in between starts the count over
+        assert_eq!(breaker.record(false), None);
+        assert_eq!(breaker.record(true), None);
+        assert_eq!(breaker.record(true), None);
+        assert_eq!(
+            breaker.record(true),
+            Some((CircuitState::Closed, CircuitState::Open))
+        );
+        let (admitted, _) = breaker.admit();
+        assert!(matches!(
+            admitted.unwrap_err().current_context(),
+            Error::CircuitOpen { .. }
+        ));
+    }
+
+    #[test]
+    fn failures_within_a_window() {
+        let breaker = CircuitBreaker::new(CircuitBreakerPolicy::new(2).of_last(4));
+        for failed in [true, false, false, false, true, false] {
+            assert_eq!(breaker.record(failed), None);
+        }
+        assert_eq!(
+            breaker.record(true),
+            Some((CircuitState::Closed, CircuitState::Open))
+        );
+    }
+
+    #[test]
+    fn probe_after_the_cool_down() {
+        let cool_down = Duration::from_millis(20);
+        let breaker = CircuitBreaker::new(CircuitBreakerPolicy::new(1).cool_down(cool_down));
+        assert!(breaker.record(true).is_some());
+        assert!(breaker.admit().0.is_err());
+        std::thread::sleep(cool_down);
+
+        let (admitted, transition) = breaker.admit();
+        assert!(admitted.is_ok());
+        assert_eq!(
+            transition,
+            Some((CircuitState::Open, CircuitState::HalfOpen))
+        );
+        // only the probe goes out
+        assert!(breaker.admit().0.is_err());
+        assert_eq!(
+            breaker.record(true),
+            Some((CircuitState::HalfOpen, CircuitState::Open))
+        );
+
+        std::thread::sleep(cool_down);
+        assert!(breaker.admit().0.is_ok());
+        assert_eq!(
+            breaker.record(false),
+            Some((CircuitState::HalfOpen, CircuitState::Closed))
+        );
+        assert_eq!(breaker.state(), CircuitState::Closed);
+        assert!(breaker.admit().0.is_ok());
+    }
+}
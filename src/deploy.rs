@@ -0,0 +1,83 @@
+//! Contract deployment, mainly for test environments on anvil or a geth dev chain.
+//!
+//! ```no_run
+//! # async fn run(web3: web3_rpc::web3::Web3, deployer: web3_rpc::types::Address, bytecode: &str) {
+//! use web3_rpc::abi::Token;
+//! use web3_rpc::deploy::DeployOptions;
+//! use web3_rpc::types::U256;
+//!
+//! let address = web3
+//!     .deploy_contract(bytecode, &[Token::Uint(U256::from(42u64))], deployer, DeployOptions::default())
+//!     .await
+//!     .unwrap();
+//! # }
+//! ```
+
+use crate::abi::{self, Token};
+use crate::client::Error;
+use crate::hex;
+use crate::model::TransactionRequest;
+use crate::send::{Sender, DEFAULT_RECEIPT_POLL_INTERVAL};
+use crate::types::{Address, U256};
+use crate::web3::Web3;
+use error_stack::{Report, ResultExt};
+use std::time::Duration;
+
+/// Optional settings for [`Web3::deploy_contract`].
+#[derive(Debug, Clone)]
+pub struct DeployOptions {
+    /// Gas limit, estimated when `None`.
+    pub gas: Option<U256>,
+    /// Wei sent to a payable constructor.
+    pub value: Option<U256>,
+    pub poll_interval: Duration,
+    /// How long to wait for the creation to be mined.
+    pub timeout: Duration,
+}
+
+impl Default for DeployOptions {
+    fn default() -> Self {
+        DeployOptions {
+            gas: None,
+            value: None,
+            poll_interval: DEFAULT_RECEIPT_POLL_INTERVAL,
+            timeout: Duration::from_secs(60),
+        }
+    }
+}
+
+impl Web3 {
+    /// Deploys `bytecode` (the creation code, `0x`-prefixed) with `constructor_args`
+    /// ABI-encoded after it, waits for the receipt and returns the new contract's address.
+    ///
+    /// A creation that reverts fails with [`Error::TransactionReverted`].
+    pub async fn deploy_contract<'a>(
+        &self,
+        bytecode: &str,
+        constructor_args: &[Token],
+        sender: impl Into<Sender<'a>>,
+        options: DeployOptions,
+    ) -> Result<Address, Report<Error>> {
+        let mut code = hex::parse_data(bytecode)
+            .change_context(Error::InvalidTransactionRequest)
+            .attach_printable("bytecode is not 0x-prefixed hex")?;
+        code.extend(abi::encode(constructor_args));
+
+        let mut request = TransactionRequest::new().data(hex::data(&code));
+        request.gas = options.gas;
+        request.value = options.value;
+        let tx_hash = self.submit_transaction(request, sender.into()).await?;
+        let receipt = self
+            .wait_for_receipt(tx_hash, options.poll_interval, options.timeout)
+            .await?;
+        if receipt.status == Some(false) {
+            let reason = self.replay_revert_reason(tx_hash, &receipt).await;
+            return Err(Report::new(Error::TransactionReverted { tx_hash, reason })
+                .attach_printable(format!("contract creation used {} gas", receipt.gas_used)));
+        }
+        receipt.contract_address.ok_or_else(|| {
+            Report::new(Error::UnexpectedResponseFormat)
+                .attach_printable(format!("receipt of {} has no contract address", tx_hash))
+        })
+    }
+}
@@ -0,0 +1,139 @@
+//! DepositEvent logs of the beacon chain deposit contract.
+//!
+//! Every field of the event is an ABI `bytes` value, and the two integers inside
+//! (`amount` in gwei and the deposit `index`) are 8-byte little-endian, as SSZ encodes them.
+
+use crate::abi::{self, ParamType, Token};
+use crate::client::Error;
+use crate::hex;
+use crate::keccak::keccak256;
+use crate::logs::DEFAULT_CHUNK_BLOCKS;
+use crate::model::{Log, LogFilter};
+use crate::types::{Address, H256};
+use crate::web3::Web3;
+use error_stack::Report;
+
+/// The mainnet deposit contract, `0x00000000219ab540356cBB839Cbe05303d7705Fa`.
+pub const DEPOSIT_CONTRACT: Address = Address([
+    0x00, 0x00, 0x00, 0x00, 0x21, 0x9a, 0xb5, 0x40, 0x35, 0x6c, 0xbb, 0x83, 0x9c, 0xbe, 0x05, 0x30,
+    0x3d, 0x77, 0x05, 0xfa,
+]);
+
+/// A decoded `DepositEvent(bytes,bytes,bytes,bytes,bytes)`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DepositEvent {
+    /// 48-byte BLS public key.
+    pub pubkey: Vec<u8>,
+    pub withdrawal_credentials: H256,
+    /// In gwei; 32 ETH is `32_000_000_000`.
+    pub amount: u64,
+    /// 96-byte BLS signature.
+    pub signature: Vec<u8>,
+    /// Position in the deposit contract's Merkle tree, starting at 0.
+    pub index: u64,
+    pub block_number: Option<u64>,
+    pub transaction_hash: Option<H256>,
+}
+
+impl DepositEvent {
+    /// `keccak256("DepositEvent(bytes,bytes,bytes,bytes,bytes)")`.
+    pub fn topic() -> H256 {
+        H256(keccak256(b"DepositEvent(bytes,bytes,bytes,bytes,bytes)"))
+    }
+
+    /// `None` unless `log` is a well-formed DepositEvent.
+    pub fn decode(log: &Log) -> Option<DepositEvent> {
+        if log.topics.first() != Some(&Self::topic()) {
+            return None;
+        }
+        let data = hex::parse_data(&log.data).ok()?;
+        let mut fields = abi::decode(&vec![ParamType::Bytes; 5], &data)
+            .ok()?
+            .into_iter()
+            .map(Token::into_bytes);
+        let mut next = || fields.next().flatten();
+        let (pubkey, credentials, amount, signature, index) =
+            (next()?, next()?, next()?, next()?, next()?);
+        if pubkey.len() != 48 || signature.len() != 96 {
+            return None;
+        }
+        Some(DepositEvent {
+            pubkey,
+            withdrawal_credentials: H256(credentials.try_into().ok()?),
+            amount: u64::from_le_bytes(amount.try_into().ok()?),
+            signature,
+            index: u64::from_le_bytes(index.try_into().ok()?),
+            block_number: log
+                .block_number
+                .as_deref()
+                .and_then(|n| n.strip_prefix("0x"))
+                .and_then(|digits| u64::from_str_radix(digits, 16).ok()),
+            transaction_hash: log.transaction_hash,
+        })
+    }
+}
+
+impl Web3 {
+    /// Every deposit made to [`DEPOSIT_CONTRACT`] in `from_block..=to_block`, in order.
+    pub async fn scan_deposits(
+        &self,
+        from_block: u64,
+        to_block: u64,
+    ) -> Result<Vec<DepositEvent>, Report<Error>> {
+        let filter = LogFilter::new()
+            .address(DEPOSIT_CONTRACT)
+            .topic(0, DepositEvent::topic());
+        self.get_logs_chunked(&filter, from_block, to_block, DEFAULT_CHUNK_BLOCKS)
+            .await?
+            .iter()
+            .map(|log| {
+                DepositEvent::decode(log).ok_or_else(|| {
+                    Report::new(Error::FailedToDeserialize)
+                        .attach_printable(format!("malformed DepositEvent: {}", log.data))
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deposit_constants() {
+        assert_eq!(
+            DEPOSIT_CONTRACT.to_checksum(),
+            "0x00000000219ab540356cBB839Cbe05303d7705Fa"
+        );
+        assert_eq!(
+            DepositEvent::topic().to_hex(),
+            "0x649bbc62d0e31342afea4e5cd82d4049e7e1ee912fc0889aa790803be39038c5"
+        );
+    }
+
+    #[test]
+    fn decodes_little_endian_fields() {
+        let mut log: Log =
+            serde_json::from_str(include_str!("../tests/fixtures/log_deposit.json")).unwrap();
+        // the 32 ETH amount as it appears in every full deposit
+        assert!(log.data.contains("0040597307000000"));
+
+        let deposit = DepositEvent::decode(&log).unwrap();
+        assert_eq!(deposit.amount, 32_000_000_000);
+        assert_eq!(deposit.index, 123_456);
+        assert_eq!(
+            crate::hex::data(&deposit.pubkey),
+            "0x933ad9491b62059dd065b560d256d8957a8c402cc6e8d8ee7290ae11e8f7329267a8811c397529dac52ae1342ba58c95"
+        );
+        assert_eq!(
+            deposit.withdrawal_credentials.to_hex(),
+            "0x0100000000000000000000000d369bb49efa5100fd3b86a9f828c55da04d2d50"
+        );
+        assert_eq!(deposit.signature.len(), 96);
+        assert_eq!(deposit.block_number, Some(17_000_000));
+
+        log.topics[0] = H256::ZERO;
+        assert_eq!(DepositEvent::decode(&log), None);
+    }
+}
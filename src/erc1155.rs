@@ -0,0 +1,93 @@
+//! Read-only ERC-1155 helpers over eth_call.
+
+use crate::abi::{self, ParamType, Token};
+use crate::client::Error;
+use crate::erc20::{call_contract, decode_string, decode_uint};
+use crate::model::Tag;
+use crate::types::{Address, U256};
+use crate::web3::Web3;
+use error_stack::{Report, ResultExt};
+
+/// An ERC-1155 contract at `address`.
+#[derive(Clone)]
+pub struct Erc1155 {
+    web3: Web3,
+    pub address: Address,
+}
+
+impl Erc1155 {
+    pub fn new(web3: &Web3, address: Address) -> Self {
+        Erc1155 {
+            web3: web3.clone(),
+            address,
+        }
+    }
+
+    pub async fn balance_of(&self, owner: Address, id: U256) -> Result<U256, Report<Error>> {
+        let data = abi::encode_call(
+            "balanceOf(address,uint256)",
+            &[Token::Address(owner), Token::Uint(id)],
+        );
+        let output = call_contract(&self.web3, self.address, data, Tag::Latest.into()).await?;
+        decode_uint(&output)
+    }
+
+    /// One balance per `(owner, id)` pair, in order.
+    pub async fn balance_of_batch(
+        &self,
+        queries: &[(Address, U256)],
+    ) -> Result<Vec<U256>, Report<Error>> {
+        let owners = queries.iter().map(|(a, _)| Token::Address(*a)).collect();
+        let ids = queries.iter().map(|(_, id)| Token::Uint(*id)).collect();
+        let data = abi::encode_call(
+            "balanceOfBatch(address[],uint256[])",
+            &[Token::Array(owners), Token::Array(ids)],
+        );
+        let output = call_contract(&self.web3, self.address, data, Tag::Latest.into()).await?;
+        let balances = abi::decode(&[ParamType::Array(Box::new(ParamType::Uint))], &output)
+            .change_context(Error::FailedToDeserialize)?
+            .pop()
+            .and_then(Token::into_tokens)
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(Token::into_uint)
+            .collect::<Vec<_>>();
+        if balances.len() != queries.len() {
+            return Err(
+                Report::new(Error::UnexpectedResponseFormat).attach_printable(format!(
+                    "balanceOfBatch returned {} balances for {} queries",
+                    balances.len(),
+                    queries.len()
+                )),
+            );
+        }
+        Ok(balances)
+    }
+
+    /// The metadata URI with `{id}` already substituted.
+    pub async fn uri(&self, id: U256) -> Result<String, Report<Error>> {
+        let data = abi::encode_call("uri(uint256)", &[Token::Uint(id)]);
+        let output = call_contract(&self.web3, self.address, data, Tag::Latest.into()).await?;
+        Ok(substitute_id(&decode_string(&output)?, id))
+    }
+}
+
+/// Replaces `{id}` with the id as 64 lowercase hex digits without a prefix, as the
+/// ERC-1155 metadata extension requires.
+pub fn substitute_id(uri: &str, id: U256) -> String {
+    uri.replace("{id}", &format!("{:064x}", id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn substitutes_token_ids() {
+        assert_eq!(
+            substitute_id("https://token-cdn-domain/{id}.json", U256::from(314_592u64)),
+            "https://token-cdn-domain/000000000000000000000000000000000000000000000000000000000004cce0.json"
+        );
+        assert_eq!(substitute_id("ipfs://static", U256::ONE), "ipfs://static");
+    }
+}
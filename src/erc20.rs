@@ -0,0 +1,223 @@
+//! Read-only ERC-20 helpers over eth_call.
+
+use crate::abi::{self, ParamType, Token};
+use crate::client::Error;
+use crate::hex;
+use crate::keccak::keccak256;
+use crate::model::{BlockId, Log, Tag, TransactionRequest};
+use crate::types::{Address, H256, U256};
+use crate::web3::Web3;
+use error_stack::{Report, ResultExt};
+
+/// A decoded `Transfer(address,address,uint256)` event.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Transfer {
+    pub from: Address,
+    pub to: Address,
+    pub value: U256,
+}
+
+/// An ERC-20 token at `address`.
+#[derive(Clone)]
+pub struct Erc20 {
+    web3: Web3,
+    pub address: Address,
+}
+
+impl Erc20 {
+    pub fn new(web3: &Web3, address: Address) -> Self {
+        Erc20 {
+            web3: web3.clone(),
+            address,
+        }
+    }
+
+    pub async fn balance_of(
+        &self,
+        owner: Address,
+        block: impl Into<BlockId>,
+    ) -> Result<U256, Report<Error>> {
+        let data = abi::encode_call("balanceOf(address)", &[Token::Address(owner)]);
+        let output = call_contract(&self.web3, self.address, data, block.into()).await?;
+        decode_uint(&output)
+    }
+
+    pub async fn total_supply(&self) -> Result<U256, Report<Error>> {
+        let data = abi::encode_call("totalSupply()", &[]);
+        let output = call_contract(&self.web3, self.address, data, Tag::Latest.into()).await?;
+        decode_uint(&output)
+    }
+
+    pub async fn decimals(&self) -> Result<u8, Report<Error>> {
+        let data = abi::encode_call("decimals()", &[]);
+        let output = call_contract(&self.web3, self.address, data, Tag::Latest.into()).await?;
+        let decimals = decode_uint(&output)?;
+        u8::try_from(u64::try_from(decimals).unwrap_or(u64::MAX))
+            .change_context(Error::FailedToDeserialize)
+            .attach_printable_lazy(|| format!("decimals out of range: {}", decimals))
+    }
+
+    /// Accepts both `string` and the `bytes32` some older tokens (MKR, SAI) return.
+    pub async fn symbol(&self) -> Result<String, Report<Error>> {
+        let data = abi::encode_call("symbol()", &[]);
+        let output = call_contract(&self.web3, self.address, data, Tag::Latest.into()).await?;
+        decode_string(&output)
+    }
+
+    /// Accepts both `string` and `bytes32`, like [`symbol`](Self::symbol).
+    pub async fn name(&self) -> Result<String, Report<Error>> {
+        let data = abi::encode_call("name()", &[]);
+        let output = call_contract(&self.web3, self.address, data, Tag::Latest.into()).await?;
+        decode_string(&output)
+    }
+
+    pub async fn allowance(&self, owner: Address, spender: Address) -> Result<U256, Report<Error>> {
+        let data = abi::encode_call(
+            "allowance(address,address)",
+            &[Token::Address(owner), Token::Address(spender)],
+        );
+        let output = call_contract(&self.web3, self.address, data, Tag::Latest.into()).await?;
+        decode_uint(&output)
+    }
+
+    /// `keccak256("Transfer(address,address,uint256)")`, the first topic of every transfer.
+    pub fn transfer_topic() -> H256 {
+        H256(keccak256(b"Transfer(address,address,uint256)"))
+    }
+
+    /// `None` unless `log` is an ERC-20 Transfer. ERC-721 transfers share the signature but
+    /// index the token id, so they have four topics and are skipped.
+    pub fn decode_transfer(log: &Log) -> Option<Transfer> {
+        if log.topics.len() != 3 || log.topics[0] != Self::transfer_topic() {
+            return None;
+        }
+        let value = abi::decode(&[ParamType::Uint], &hex::parse_data(&log.data).ok()?)
+            .ok()?
+            .pop()?
+            .into_uint()?;
+        Some(Transfer {
+            from: topic_address(&log.topics[1])?,
+            to: topic_address(&log.topics[2])?,
+            value,
+        })
+    }
+}
+
+/// eth_call `calldata` against `to` and return the raw output bytes.
+pub(crate) async fn call_contract(
+    web3: &Web3,
+    to: Address,
+    calldata: Vec<u8>,
+    block: BlockId,
+) -> Result<Vec<u8>, Report<Error>> {
+    let request = TransactionRequest::new().to(to).data(hex::data(&calldata));
+    let output = web3
+        .eth_call_request_at_block(&request, block)
+        .await?
+        .result
+        .ok_or_else(|| {
+            Report::new(Error::UnexpectedResponseFormat).attach_printable("eth_call returned null")
+        })?;
+    hex::parse_data(&output)
+        .change_context(Error::FailedToDeserialize)
+        .attach_printable_lazy(|| format!("eth_call returned `{}`", output))
+}
+
+pub(crate) fn decode_uint(output: &[u8]) -> Result<U256, Report<Error>> {
+    abi::decode(&[ParamType::Uint], output)
+        .change_context(Error::FailedToDeserialize)
+        .map(|mut tokens| tokens.pop().and_then(Token::into_uint).unwrap_or_default())
+}
+
+pub(crate) fn decode_string(output: &[u8]) -> Result<String, Report<Error>> {
+    // a lone word cannot be an ABI string (offset plus length take two), so it is bytes32
+    if output.len() == 32 {
+        let end = output.iter().position(|b| *b == 0).unwrap_or(32);
+        return String::from_utf8(output[..end].to_vec())
+            .change_context(Error::FailedToDeserialize)
+            .attach_printable("bytes32 value is not UTF-8");
+    }
+    abi::decode(&[ParamType::String], output)
+        .change_context(Error::FailedToDeserialize)
+        .map(|mut tokens| {
+            tokens
+                .pop()
+                .and_then(Token::into_string)
+                .unwrap_or_default()
+        })
+}
+
+pub(crate) fn topic_address(topic: &H256) -> Option<Address> {
+    let bytes = topic.as_bytes();
+    if bytes[..12].iter().any(|b| *b != 0) {
+        return None;
+    }
+    let mut address = [0u8; 20];
+    address.copy_from_slice(&bytes[12..]);
+    Some(Address(address))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decodes_string_and_bytes32_names() {
+        let encoded = abi::encode(&[Token::String("USD Coin".into())]);
+        assert_eq!(decode_string(&encoded).unwrap(), "USD Coin");
+
+        let mut mkr = [0u8; 32];
+        mkr[..3].copy_from_slice(b"MKR");
+        assert_eq!(decode_string(&mkr).unwrap(), "MKR");
+
+        assert_eq!(
+            decode_string(&abi::encode(&[Token::String(String::new())])).unwrap(),
+            ""
+        );
+        assert!(decode_string(&[0xff; 32]).is_err());
+        assert!(decode_string(&[]).is_err());
+    }
+
+    #[test]
+    fn decodes_transfer_logs() {
+        let from: Address = "0x846c4dc9f4e2514206ef179eaa0bcfae007e37d2"
+            .parse()
+            .unwrap();
+        let to: Address = "0x4e910ef89c176119744977d90093858089454223"
+            .parse()
+            .unwrap();
+        let topic = |a: Address| {
+            let mut t = [0u8; 32];
+            t[12..].copy_from_slice(a.as_bytes());
+            H256(t)
+        };
+        assert_eq!(
+            Erc20::transfer_topic().to_hex(),
+            "0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef"
+        );
+        let mut log: Log = serde_json::from_value(serde_json::json!({
+            "address": "0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48",
+            "blockHash": null,
+            "blockNumber": null,
+            "data": hex::data(&U256::from(1_000_000u64).to_be_bytes()),
+            "logIndex": null,
+            "topics": [Erc20::transfer_topic(), topic(from), topic(to)],
+            "transactionHash": null,
+            "transactionIndex": null
+        }))
+        .unwrap();
+        assert_eq!(
+            Erc20::decode_transfer(&log),
+            Some(Transfer {
+                from,
+                to,
+                value: U256::from(1_000_000u64)
+            })
+        );
+
+        // an ERC-721 transfer indexes the token id
+        log.topics.push(H256([0; 32]));
+        log.data = "0x".into();
+        assert_eq!(Erc20::decode_transfer(&log), None);
+    }
+}
@@ -0,0 +1,83 @@
+//! Read-only ERC-721 helpers over eth_call.
+
+use crate::abi::{self, ParamType, Token};
+use crate::client::Error;
+use crate::erc20::{call_contract, decode_string, decode_uint};
+use crate::model::{RpcErrorObject, Tag};
+use crate::types::{Address, U256};
+use crate::web3::Web3;
+use error_stack::{Report, ResultExt};
+
+/// Result of [`Erc721::owner_of`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Ownership {
+    Owner(Address),
+    /// The contract reverted, which ERC-721 reserves for invalid token ids, or reported the
+    /// zero address. `reason` is the decoded revert message when there was one.
+    Nonexistent {
+        reason: Option<String>,
+    },
+}
+
+/// An ERC-721 collection at `address`.
+#[derive(Clone)]
+pub struct Erc721 {
+    web3: Web3,
+    pub address: Address,
+}
+
+impl Erc721 {
+    pub fn new(web3: &Web3, address: Address) -> Self {
+        Erc721 {
+            web3: web3.clone(),
+            address,
+        }
+    }
+
+    pub async fn owner_of(&self, token_id: U256) -> Result<Ownership, Report<Error>> {
+        let data = abi::encode_call("ownerOf(uint256)", &[Token::Uint(token_id)]);
+        match call_contract(&self.web3, self.address, data, Tag::Latest.into()).await {
+            Ok(output) => match decode_address(&output)? {
+                Address::ZERO => Ok(Ownership::Nonexistent { reason: None }),
+                owner => Ok(Ownership::Owner(owner)),
+            },
+            Err(e) if e.current_context().is_execution_reverted() => {
+                let reason = e
+                    .downcast_ref::<RpcErrorObject>()
+                    .and_then(RpcErrorObject::revert_reason);
+                Ok(Ownership::Nonexistent { reason })
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    pub async fn balance_of(&self, owner: Address) -> Result<U256, Report<Error>> {
+        let data = abi::encode_call("balanceOf(address)", &[Token::Address(owner)]);
+        let output = call_contract(&self.web3, self.address, data, Tag::Latest.into()).await?;
+        decode_uint(&output)
+    }
+
+    pub async fn token_uri(&self, token_id: U256) -> Result<String, Report<Error>> {
+        let data = abi::encode_call("tokenURI(uint256)", &[Token::Uint(token_id)]);
+        let output = call_contract(&self.web3, self.address, data, Tag::Latest.into()).await?;
+        decode_string(&output)
+    }
+
+    /// `None` when no address is approved for the token.
+    pub async fn get_approved(&self, token_id: U256) -> Result<Option<Address>, Report<Error>> {
+        let data = abi::encode_call("getApproved(uint256)", &[Token::Uint(token_id)]);
+        let output = call_contract(&self.web3, self.address, data, Tag::Latest.into()).await?;
+        Ok(Some(decode_address(&output)?).filter(|a| *a != Address::ZERO))
+    }
+}
+
+fn decode_address(output: &[u8]) -> Result<Address, Report<Error>> {
+    abi::decode(&[ParamType::Address], output)
+        .change_context(Error::FailedToDeserialize)
+        .map(|mut tokens| {
+            tokens
+                .pop()
+                .and_then(Token::into_address)
+                .unwrap_or_default()
+        })
+}
@@ -0,0 +1,290 @@
+//! When a future block will be mined, and which block will be the latest at a future time,
+//! projected from the block times of the last few hundred blocks. For scheduling around
+//! block heights.
+//!
+//! ```no_run
+//! # use web3_rpc::web3::Web3;
+//! # async fn example(rpc: Web3) -> Result<(), error_stack::Report<web3_rpc::client::Error>> {
+//! let eta = rpc.estimate_time_of_block(20_000_000).await?;
+//! println!("around {} (between {} and {})", eta.timestamp, eta.earliest, eta.latest);
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Chains producing blocks in fixed slots, as Ethereum since the merge every 12 seconds, are
+//! recognized from the sample: every block time is a multiple of the slot. There a block
+//! cannot come sooner than its slot, which is the estimate if no slot is missed, and the
+//! spread only covers the slots that will be.
+
+use crate::client::Error;
+use crate::endpoints::head;
+use crate::web3::Web3;
+use error_stack::Report;
+
+/// Block times sampled by [`Web3::estimate_block_at`] and [`Web3::estimate_time_of_block`].
+pub const DEFAULT_SAMPLE_BLOCKS: u64 = 256;
+
+/// Normal quantile of the two-sided 95% intervals.
+const Z_95: f64 = 1.96;
+
+/// Block times over recent blocks, from [`Web3::block_time`]. Times in seconds.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BlockTime {
+    /// The newest block of the sample, where projections start.
+    pub head: u64,
+    pub head_timestamp: u64,
+    /// Block times in the sample, one per block after the oldest.
+    pub samples: u64,
+    pub mean: f64,
+    pub std_dev: f64,
+    /// The slot length when every block time in the sample is a multiple of one, most of
+    /// them exactly one slot.
+    pub slot: Option<u64>,
+}
+
+/// When a block is expected, from [`Web3::estimate_time_of_block`]. Unix timestamps, with a 95%
+/// interval; all three equal for a block already mined.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TimeEstimate {
+    pub block: u64,
+    pub timestamp: u64,
+    pub earliest: u64,
+    pub latest: u64,
+    pub block_time: BlockTime,
+}
+
+/// The latest block expected at a time, from [`Web3::estimate_block_at`], with a 95% interval.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BlockEstimate {
+    pub timestamp: u64,
+    pub block: u64,
+    pub lowest: u64,
+    pub highest: u64,
+    pub block_time: BlockTime,
+}
+
+impl BlockTime {
+    /// Statistics of consecutive block `timestamps`, oldest first, of blocks up to `head`.
+    pub fn from_timestamps(head: u64, timestamps: &[u64]) -> Result<BlockTime, Report<Error>> {
+        let intervals: Vec<u64> = timestamps
+            .windows(2)
+            .map(|pair| pair[1].saturating_sub(pair[0]))
+            .collect();
+        let (Some(head_timestamp), false) = (timestamps.last(), intervals.is_empty()) else {
+            return Err(Report::new(Error::InvalidArgument {
+                param: "sample_blocks",
+                reason: "block times need two blocks at least".to_string(),
+            }));
+        };
+        let n = intervals.len() as f64;
+        let mean = intervals.iter().sum::<u64>() as f64 / n;
+        if mean == 0.0 {
+            return Err(Report::new(Error::UnexpectedResponseFormat)
+                .attach_printable("every block in the sample has the same timestamp"));
+        }
+        let variance = intervals
+            .iter()
+            .map(|interval| (*interval as f64 - mean).powi(2))
+            .sum::<f64>()
+            / n;
+        Ok(BlockTime {
+            head,
+            head_timestamp: *head_timestamp,
+            samples: intervals.len() as u64,
+            mean,
+            std_dev: variance.sqrt(),
+            slot: slot(&intervals),
+        })
+    }
+
+    /// When `block`, after the head, is expected.
+    pub fn time_of_block(&self, block: u64) -> TimeEstimate {
+        let blocks = block.saturating_sub(self.head) as f64;
+        let spread = Z_95 * self.std_dev * blocks.sqrt();
+        let expected = blocks * self.mean;
+        let (timestamp, earliest) = match self.slot {
+            Some(slot) => {
+                let slot = slot as f64;
+                ((expected / slot).round() * slot, blocks * slot)
+            }
+            None => (expected.round(), (expected - spread).max(0.0)),
+        };
+        TimeEstimate {
+            block,
+            timestamp: self.head_timestamp + timestamp as u64,
+            earliest: self.head_timestamp + earliest as u64,
+            latest: self.head_timestamp + (expected + spread).ceil() as u64,
+            block_time: *self,
+        }
+    }
+
+    /// The latest block expected at `timestamp`, after the head's.
+    pub fn block_at(&self, timestamp: u64) -> BlockEstimate {
+        let elapsed = timestamp.saturating_sub(self.head_timestamp) as f64;
+        let blocks = elapsed / self.mean;
+        let spread = Z_95 * self.std_dev * blocks.sqrt() / self.mean;
+        let mut highest = (blocks + spread).floor();
+        if let Some(slot) = self.slot {
+            // one block per slot at most
+            highest = highest.min((elapsed / slot as f64).floor());
+        }
+        BlockEstimate {
+            timestamp,
+            block: self.head + blocks.floor() as u64,
+            lowest: self.head + (blocks - spread).max(0.0).floor() as u64,
+            highest: self.head + highest as u64,
+            block_time: *self,
+        }
+    }
+}
+
+/// The common slot of `intervals`, if they have one above a second that most of them equal.
+fn slot(intervals: &[u64]) -> Option<u64> {
+    if intervals.contains(&0) {
+        return None;
+    }
+    let gcd = intervals.iter().fold(0, |a, b| gcd(a, *b));
+    let exact = intervals
+        .iter()
+        .filter(|interval| **interval == gcd)
+        .count();
+    (gcd >= 2 && exact * 2 > intervals.len()).then_some(gcd)
+}
+
+fn gcd(a: u64, b: u64) -> u64 {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+impl Web3 {
+    /// Block times over the `sample_blocks` blocks up to the head.
+    pub async fn block_time(&self, sample_blocks: u64) -> Result<BlockTime, Report<Error>> {
+        let head = head(self).await?;
+        let from = head.saturating_sub(sample_blocks);
+        let headers = self
+            .in_batches((from..=head).collect(), |web3, blocks| async move {
+                web3.block_headers(&blocks).await
+            })
+            .await?;
+        let timestamps: Vec<u64> = headers.iter().map(|header| header.timestamp).collect();
+        BlockTime::from_timestamps(head, &timestamps)
+    }
+
+    /// When `number` is expected to be mined, projected from the last
+    /// [`DEFAULT_SAMPLE_BLOCKS`] block times. A block already mined gives its own timestamp.
+    pub async fn estimate_time_of_block(&self, number: u64) -> Result<TimeEstimate, Report<Error>> {
+        let block_time = self.block_time(DEFAULT_SAMPLE_BLOCKS).await?;
+        if number > block_time.head {
+            return Ok(block_time.time_of_block(number));
+        }
+        let header = self.block_headers(&[number]).await?.remove(0);
+        Ok(TimeEstimate {
+            block: number,
+            timestamp: header.timestamp,
+            earliest: header.timestamp,
+            latest: header.timestamp,
+            block_time,
+        })
+    }
+
+    /// The block expected to be the latest at `timestamp`, projected from the last
+    /// [`DEFAULT_SAMPLE_BLOCKS`] block times. Times before the head's are refused with
+    /// [`Error::InvalidArgument`]: the block is known, and finding it is a search.
+    pub async fn estimate_block_at(&self, timestamp: u64) -> Result<BlockEstimate, Report<Error>> {
+        let block_time = self.block_time(DEFAULT_SAMPLE_BLOCKS).await?;
+        if timestamp < block_time.head_timestamp {
+            return Err(Report::new(Error::InvalidArgument {
+                param: "timestamp",
+                reason: format!(
+                    "{} is before block {} at {}",
+                    timestamp, block_time.head, block_time.head_timestamp
+                ),
+            }));
+        }
+        Ok(block_time.block_at(timestamp))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn block_time(intervals: &[u64]) -> BlockTime {
+        let mut timestamps = vec![1_700_000_000];
+        for interval in intervals {
+            timestamps.push(timestamps.last().unwrap() + interval);
+        }
+        BlockTime::from_timestamps(1_000, &timestamps).unwrap()
+    }
+
+    #[test]
+    fn slots_are_recognized() {
+        let merged = block_time(&[12, 12, 24, 12, 12, 36, 12, 12]);
+        assert_eq!(merged.slot, Some(12));
+        assert_eq!(merged.mean, 16.5);
+        assert_eq!(block_time(&[12; 10]).slot, Some(12));
+        // proof of work
+        assert_eq!(block_time(&[13, 2, 40, 9, 11]).slot, None);
+        // multiples of 2 but mostly not one slot
+        assert_eq!(block_time(&[4, 6, 8, 2, 10]).slot, None);
+        assert_eq!(block_time(&[1, 1, 1]).slot, None);
+        assert_eq!(block_time(&[2, 0, 2]).slot, None);
+    }
+
+    #[test]
+    fn fixed_slots_without_misses_are_exact() {
+        let bt = block_time(&[12; 100]);
+        assert_eq!(bt.std_dev, 0.0);
+        let head_timestamp = bt.head_timestamp;
+
+        let eta = bt.time_of_block(1_100);
+        assert_eq!(eta.timestamp, head_timestamp + 1_200);
+        assert_eq!((eta.earliest, eta.latest), (eta.timestamp, eta.timestamp));
+
+        let at = bt.block_at(head_timestamp + 1_211);
+        assert_eq!((at.lowest, at.block, at.highest), (1_100, 1_100, 1_100));
+    }
+
+    #[test]
+    fn missed_slots_widen_the_interval_one_way() {
+        let bt = block_time(&[12, 12, 12, 24, 12, 12, 12, 12, 12, 24]);
+        let eta = bt.time_of_block(1_100);
+        // never sooner than one block per slot
+        assert_eq!(eta.earliest, bt.head_timestamp + 1_200);
+        assert_eq!(eta.timestamp, bt.head_timestamp + 1_440);
+        assert!(eta.latest > eta.timestamp);
+
+        let at = bt.block_at(bt.head_timestamp + 1_440);
+        assert_eq!(at.block, 1_100);
+        assert!(at.lowest < at.block && at.block < at.highest);
+        // capped at one block per slot
+        assert!(at.highest <= 1_120);
+        let capped = BlockTime {
+            std_dev: 100.0,
+            ..bt
+        }
+        .block_at(bt.head_timestamp + 1_440);
+        assert_eq!(capped.highest, 1_120);
+    }
+
+    #[test]
+    fn variable_block_times() {
+        let bt = block_time(&[10, 14, 12, 16, 8]);
+        assert_eq!(bt.slot, None);
+        let eta = bt.time_of_block(1_025);
+        assert_eq!(eta.timestamp, bt.head_timestamp + 300);
+        assert!(eta.earliest < eta.timestamp && eta.timestamp < eta.latest);
+        // the interval grows as the square root of the distance
+        let far = bt.time_of_block(1_100);
+        assert!(far.latest - far.timestamp < 4 * (eta.latest - eta.timestamp));
+    }
+
+    #[test]
+    fn degenerate_samples_fail() {
+        assert!(BlockTime::from_timestamps(1, &[1_700_000_000]).is_err());
+        assert!(BlockTime::from_timestamps(2, &[1_700_000_000, 1_700_000_000]).is_err());
+    }
+}
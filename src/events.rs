@@ -0,0 +1,464 @@
+//! Typed decoders for the standard token events.
+//!
+//! ERC-20 and ERC-721 share the `Transfer` and `Approval` signatures and differ only in
+//! whether the third parameter is indexed: an ERC-20 log carries the amount in `data` and
+//! has three topics, an ERC-721 log carries the token id as a fourth topic and no data.
+//! Every decoder checks topic0 and the topic count, so a log decodes as at most one event.
+
+use crate::abi::{self, ParamType, Token};
+use crate::erc20::{topic_address, Erc20, Transfer};
+use crate::hex;
+use crate::keccak::keccak256;
+use crate::model::Log;
+use crate::types::{Address, H256, U256};
+
+/// A log decoded by [`decode_known_event`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KnownEvent {
+    Erc20Transfer(Transfer),
+    Erc721Transfer(NftTransfer),
+    Erc20Approval(Approval),
+    Erc721Approval(NftApproval),
+    Deposit(Deposit),
+    Withdrawal(Withdrawal),
+    TransferSingle(TransferSingle),
+    TransferBatch(TransferBatch),
+}
+
+/// The first of the events above that `log` is, `None` for anything else.
+pub fn decode_known_event(log: &Log) -> Option<KnownEvent> {
+    match log.topics.first()? {
+        topic if *topic == Erc20::transfer_topic() => Transfer::try_decode(log)
+            .map(KnownEvent::Erc20Transfer)
+            .or_else(|| NftTransfer::try_decode(log).map(KnownEvent::Erc721Transfer)),
+        topic if *topic == Approval::topic() => Approval::try_decode(log)
+            .map(KnownEvent::Erc20Approval)
+            .or_else(|| NftApproval::try_decode(log).map(KnownEvent::Erc721Approval)),
+        topic if *topic == Deposit::topic() => Deposit::try_decode(log).map(KnownEvent::Deposit),
+        topic if *topic == Withdrawal::topic() => {
+            Withdrawal::try_decode(log).map(KnownEvent::Withdrawal)
+        }
+        topic if *topic == TransferSingle::topic() => {
+            TransferSingle::try_decode(log).map(KnownEvent::TransferSingle)
+        }
+        topic if *topic == TransferBatch::topic() => {
+            TransferBatch::try_decode(log).map(KnownEvent::TransferBatch)
+        }
+        _ => None,
+    }
+}
+
+impl Transfer {
+    /// Same as [`Erc20::decode_transfer`].
+    pub fn try_decode(log: &Log) -> Option<Transfer> {
+        Erc20::decode_transfer(log)
+    }
+}
+
+/// An ERC-721 `Transfer(address,address,uint256)` with the token id indexed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NftTransfer {
+    pub from: Address,
+    pub to: Address,
+    pub token_id: U256,
+}
+
+impl NftTransfer {
+    pub fn try_decode(log: &Log) -> Option<NftTransfer> {
+        let [from, to, token_id] = indexed(log, Erc20::transfer_topic())?;
+        Some(NftTransfer {
+            from: topic_address(&from)?,
+            to: topic_address(&to)?,
+            token_id: topic_uint(&token_id),
+        })
+    }
+}
+
+/// An ERC-20 `Approval(address,address,uint256)`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Approval {
+    pub owner: Address,
+    pub spender: Address,
+    pub value: U256,
+}
+
+impl Approval {
+    /// `keccak256("Approval(address,address,uint256)")`, shared with ERC-721.
+    pub fn topic() -> H256 {
+        H256(keccak256(b"Approval(address,address,uint256)"))
+    }
+
+    pub fn try_decode(log: &Log) -> Option<Approval> {
+        let [owner, spender] = indexed(log, Self::topic())?;
+        let [value] = data_uints(log)?;
+        Some(Approval {
+            owner: topic_address(&owner)?,
+            spender: topic_address(&spender)?,
+            value,
+        })
+    }
+}
+
+/// An ERC-721 `Approval(address,address,uint256)` with the token id indexed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NftApproval {
+    pub owner: Address,
+    pub approved: Address,
+    pub token_id: U256,
+}
+
+impl NftApproval {
+    pub fn try_decode(log: &Log) -> Option<NftApproval> {
+        let [owner, approved, token_id] = indexed(log, Approval::topic())?;
+        Some(NftApproval {
+            owner: topic_address(&owner)?,
+            approved: topic_address(&approved)?,
+            token_id: topic_uint(&token_id),
+        })
+    }
+}
+
+/// WETH's `Deposit(address,uint256)`: `wad` wei wrapped into tokens for `dst`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Deposit {
+    pub dst: Address,
+    pub wad: U256,
+}
+
+impl Deposit {
+    /// `keccak256("Deposit(address,uint256)")`.
+    pub fn topic() -> H256 {
+        H256(keccak256(b"Deposit(address,uint256)"))
+    }
+
+    pub fn try_decode(log: &Log) -> Option<Deposit> {
+        let [dst] = indexed(log, Self::topic())?;
+        let [wad] = data_uints(log)?;
+        Some(Deposit {
+            dst: topic_address(&dst)?,
+            wad,
+        })
+    }
+}
+
+/// WETH's `Withdrawal(address,uint256)`: `wad` tokens of `src` unwrapped back into wei.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Withdrawal {
+    pub src: Address,
+    pub wad: U256,
+}
+
+impl Withdrawal {
+    /// `keccak256("Withdrawal(address,uint256)")`.
+    pub fn topic() -> H256 {
+        H256(keccak256(b"Withdrawal(address,uint256)"))
+    }
+
+    pub fn try_decode(log: &Log) -> Option<Withdrawal> {
+        let [src] = indexed(log, Self::topic())?;
+        let [wad] = data_uints(log)?;
+        Some(Withdrawal {
+            src: topic_address(&src)?,
+            wad,
+        })
+    }
+}
+
+/// An ERC-1155 `TransferSingle(address,address,address,uint256,uint256)`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TransferSingle {
+    pub operator: Address,
+    pub from: Address,
+    pub to: Address,
+    pub id: U256,
+    pub value: U256,
+}
+
+impl TransferSingle {
+    /// `keccak256("TransferSingle(address,address,address,uint256,uint256)")`.
+    pub fn topic() -> H256 {
+        H256(keccak256(
+            b"TransferSingle(address,address,address,uint256,uint256)",
+        ))
+    }
+
+    pub fn try_decode(log: &Log) -> Option<TransferSingle> {
+        let [operator, from, to] = indexed(log, Self::topic())?;
+        let [id, value] = data_uints(log)?;
+        Some(TransferSingle {
+            operator: topic_address(&operator)?,
+            from: topic_address(&from)?,
+            to: topic_address(&to)?,
+            id,
+            value,
+        })
+    }
+}
+
+/// An ERC-1155 `TransferBatch(address,address,address,uint256[],uint256[])`; `ids` and
+/// `values` have the same length.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TransferBatch {
+    pub operator: Address,
+    pub from: Address,
+    pub to: Address,
+    pub ids: Vec<U256>,
+    pub values: Vec<U256>,
+}
+
+impl TransferBatch {
+    /// `keccak256("TransferBatch(address,address,address,uint256[],uint256[])")`.
+    pub fn topic() -> H256 {
+        H256(keccak256(
+            b"TransferBatch(address,address,address,uint256[],uint256[])",
+        ))
+    }
+
+    pub fn try_decode(log: &Log) -> Option<TransferBatch> {
+        let [operator, from, to] = indexed(log, Self::topic())?;
+        let array = ParamType::Array(Box::new(ParamType::Uint));
+        let data = hex::parse_data(&log.data).ok()?;
+        let mut arrays = abi::decode(&[array.clone(), array], &data)
+            .ok()?
+            .into_iter()
+            .map(|token| {
+                token
+                    .into_tokens()?
+                    .into_iter()
+                    .map(Token::into_uint)
+                    .collect::<Option<Vec<_>>>()
+            });
+        let (ids, values) = (arrays.next()??, arrays.next()??);
+        if ids.len() != values.len() {
+            return None;
+        }
+        Some(TransferBatch {
+            operator: topic_address(&operator)?,
+            from: topic_address(&from)?,
+            to: topic_address(&to)?,
+            ids,
+            values,
+        })
+    }
+}
+
+/// The `N` indexed parameters of `log`, `None` unless it has topic0 `topic` and exactly
+/// `N` more topics.
+fn indexed<const N: usize>(log: &Log, topic: H256) -> Option<[H256; N]> {
+    match log.topics.split_first() {
+        Some((first, rest)) if *first == topic => rest.try_into().ok(),
+        _ => None,
+    }
+}
+
+/// The `N` uint words making up the data of `log`, `None` for any other length.
+fn data_uints<const N: usize>(log: &Log) -> Option<[U256; N]> {
+    let data = hex::parse_data(&log.data).ok()?;
+    if data.len() != 32 * N {
+        return None;
+    }
+    abi::decode(&vec![ParamType::Uint; N], &data)
+        .ok()?
+        .into_iter()
+        .map(Token::into_uint)
+        .collect::<Option<Vec<_>>>()?
+        .try_into()
+        .ok()
+}
+
+fn topic_uint(topic: &H256) -> U256 {
+    U256::from_be_bytes(topic.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hex;
+
+    fn address(byte: u8) -> Address {
+        Address([byte; 20])
+    }
+
+    fn topic(address: Address) -> H256 {
+        let mut t = [0u8; 32];
+        t[12..].copy_from_slice(address.as_bytes());
+        H256(t)
+    }
+
+    fn log(topics: Vec<H256>, data: Vec<u8>) -> Log {
+        serde_json::from_value(serde_json::json!({
+            "address": "0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2",
+            "blockHash": null,
+            "blockNumber": null,
+            "data": hex::data(&data),
+            "logIndex": null,
+            "topics": topics,
+            "transactionHash": null,
+            "transactionIndex": null
+        }))
+        .unwrap()
+    }
+
+    fn words(values: &[u64]) -> Vec<u8> {
+        abi::encode(
+            &values
+                .iter()
+                .map(|v| Token::Uint(U256::from(*v)))
+                .collect::<Vec<_>>(),
+        )
+    }
+
+    #[test]
+    fn event_topics() {
+        let cases = [
+            (
+                Approval::topic(),
+                "0x8c5be1e5ebec7d5bd14f71427d1e84f3dd0314c0f7b2291e5b200ac8c7c3b925",
+            ),
+            (
+                Deposit::topic(),
+                "0xe1fffcc4923d04b559f4d29a8bfc6cda04eb5b0d3c460751c2402c5c5cc9109c",
+            ),
+            (
+                Withdrawal::topic(),
+                "0x7fcf532c15f0a6db0bd6d0e038bea71d30d808c7d98cb3bf7268a95bf5081b65",
+            ),
+            (
+                TransferSingle::topic(),
+                "0xc3d58168c5ae7397731d063d5bbf3d657854427343f4c083240f7aacaa2d0f62",
+            ),
+            (
+                TransferBatch::topic(),
+                "0x4a39dc06d4c0dbc64b70af90fd698a233a518aa5d07e595d983b8c0526c8f7fb",
+            ),
+        ];
+        for (topic, expected) in cases {
+            assert_eq!(topic.to_hex(), expected);
+        }
+    }
+
+    #[test]
+    fn transfers_are_told_apart_by_topic_count() {
+        let (from, to) = (address(1), address(2));
+        let erc20 = log(
+            vec![Erc20::transfer_topic(), topic(from), topic(to)],
+            words(&[1_000]),
+        );
+        assert_eq!(
+            decode_known_event(&erc20),
+            Some(KnownEvent::Erc20Transfer(Transfer {
+                from,
+                to,
+                value: U256::from(1_000u64)
+            }))
+        );
+        assert_eq!(NftTransfer::try_decode(&erc20), None);
+
+        let token_id = H256(U256::from(7u64).to_be_bytes());
+        let erc721 = log(
+            vec![Erc20::transfer_topic(), topic(from), topic(to), token_id],
+            Vec::new(),
+        );
+        assert_eq!(
+            decode_known_event(&erc721),
+            Some(KnownEvent::Erc721Transfer(NftTransfer {
+                from,
+                to,
+                token_id: U256::from(7u64)
+            }))
+        );
+        assert_eq!(Transfer::try_decode(&erc721), None);
+
+        let approval = log(
+            vec![Approval::topic(), topic(from), topic(to), token_id],
+            Vec::new(),
+        );
+        assert_eq!(
+            decode_known_event(&approval),
+            Some(KnownEvent::Erc721Approval(NftApproval {
+                owner: from,
+                approved: to,
+                token_id: U256::from(7u64)
+            }))
+        );
+    }
+
+    #[test]
+    fn decodes_weth_and_erc1155_events() {
+        let (operator, from, to) = (address(1), address(2), address(3));
+        let deposit = log(vec![Deposit::topic(), topic(to)], words(&[5]));
+        assert_eq!(
+            decode_known_event(&deposit),
+            Some(KnownEvent::Deposit(Deposit {
+                dst: to,
+                wad: U256::from(5u64)
+            }))
+        );
+        let withdrawal = log(vec![Withdrawal::topic(), topic(from)], words(&[5]));
+        assert_eq!(
+            decode_known_event(&withdrawal),
+            Some(KnownEvent::Withdrawal(Withdrawal {
+                src: from,
+                wad: U256::from(5u64)
+            }))
+        );
+
+        let parties = vec![topic(operator), topic(from), topic(to)];
+        let single = log(
+            [vec![TransferSingle::topic()], parties.clone()].concat(),
+            words(&[9, 2]),
+        );
+        assert_eq!(
+            decode_known_event(&single),
+            Some(KnownEvent::TransferSingle(TransferSingle {
+                operator,
+                from,
+                to,
+                id: U256::from(9u64),
+                value: U256::from(2u64)
+            }))
+        );
+
+        let uints = |values: &[u64]| {
+            Token::Array(values.iter().map(|v| Token::Uint(U256::from(*v))).collect())
+        };
+        let batch = log(
+            [vec![TransferBatch::topic()], parties.clone()].concat(),
+            abi::encode(&[uints(&[1, 2]), uints(&[10, 20])]),
+        );
+        assert_eq!(
+            decode_known_event(&batch),
+            Some(KnownEvent::TransferBatch(TransferBatch {
+                operator,
+                from,
+                to,
+                ids: vec![U256::from(1u64), U256::from(2u64)],
+                values: vec![U256::from(10u64), U256::from(20u64)]
+            }))
+        );
+        let uneven = log(
+            [vec![TransferBatch::topic()], parties].concat(),
+            abi::encode(&[uints(&[1, 2]), uints(&[10])]),
+        );
+        assert_eq!(decode_known_event(&uneven), None);
+    }
+
+    #[test]
+    fn wrong_arity_is_rejected() {
+        // an indexed amount, as some non-standard tokens emit
+        let deposit = log(
+            vec![Deposit::topic(), topic(address(1)), H256::ZERO],
+            Vec::new(),
+        );
+        assert_eq!(decode_known_event(&deposit), None);
+        let short = log(
+            vec![Approval::topic(), topic(address(1)), topic(address(2))],
+            Vec::new(),
+        );
+        assert_eq!(decode_known_event(&short), None);
+        // an address topic with dirty upper bytes
+        let dirty = log(vec![Withdrawal::topic(), H256([0xff; 32])], words(&[1]));
+        assert_eq!(decode_known_event(&dirty), None);
+        assert_eq!(decode_known_event(&log(Vec::new(), Vec::new())), None);
+    }
+}
@@ -0,0 +1,546 @@
+//! What a mined transaction paid, split the way fee accounting needs it: the base fee burned,
+//! the priority tip to the block producer, blob gas, and on OP-stack chains the L1 data fee.
+//! And what the next transactions will have to pay, from the fee market of recent blocks
+//! ([`Web3::fee_percentiles`]).
+//!
+//! ```no_run
+//! # use web3_rpc::web3::Web3;
+//! # async fn example(rpc: Web3) -> Result<(), error_stack::Report<web3_rpc::client::Error>> {
+//! let hash = "0x9aba9e042034f025ebbe9fddde27c2c091afe6bb24d1c6f6418abbb3103519ee";
+//! if let Some(fee) = rpc.transaction_fee(hash).await? {
+//!     println!("burned {} tipped {} total {}", fee.base_fee, fee.priority_fee, fee.total);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::client::{rpc_error, Error};
+use crate::hex;
+use crate::model::{BlockId, FeeHistory, Receipt, Tag, TxType};
+use crate::types::{H256Param, U256};
+use crate::web3::Web3;
+use error_stack::{Report, ResultExt};
+
+/// Fees of one transaction, in wei.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FeeBreakdown {
+    pub gas_used: u64,
+    pub effective_gas_price: u128,
+    /// `effective_gas_price × gas_used`, which is `base_fee + priority_fee`.
+    pub execution_fee: u128,
+    /// Burned: the block's base fee per gas × `gas_used`. In blocks before London, which
+    /// have no base fee, the whole execution fee counts as base and the tip is 0.
+    pub base_fee: u128,
+    /// To the block producer: what the effective gas price pays above the base fee.
+    pub priority_fee: u128,
+    /// `blob_gas_used × blob_gas_price`, type-3 transactions only.
+    pub blob_fee: u128,
+    /// OP-stack chains' fee for posting the transaction to L1, from the receipt's `l1Fee`.
+    pub l1_fee: Option<u128>,
+    /// Everything the sender paid: execution, blob and L1 fees.
+    pub total: u128,
+}
+
+impl FeeBreakdown {
+    /// Breaks down `receipt`'s fees given its block's `baseFeePerGas` (`None` before London).
+    /// OP-stack deposits are bought on L1 and pay nothing here.
+    pub fn from_receipt(
+        receipt: &Receipt,
+        base_fee_per_gas: Option<U256>,
+    ) -> Result<FeeBreakdown, Report<Error>> {
+        if receipt.tx_type() == Some(TxType::Deposit) {
+            return Ok(FeeBreakdown {
+                gas_used: receipt.gas_used,
+                ..FeeBreakdown::default()
+            });
+        }
+        let gas_used = u128::from(receipt.gas_used);
+        let effective_gas_price = receipt
+            .effective_gas_price
+            .ok_or_else(|| Report::new(Error::UnexpectedResponseFormat))
+            .attach_printable("receipt has no effectiveGasPrice")?;
+        let execution_fee = mul(effective_gas_price, gas_used)?;
+        let base_fee = match base_fee_per_gas {
+            None => execution_fee,
+            Some(base) => {
+                let base = u128::try_from(base)
+                    .ok()
+                    .filter(|base| *base <= effective_gas_price)
+                    .ok_or_else(|| Report::new(Error::UnexpectedResponseFormat))
+                    .attach_printable_lazy(|| {
+                        format!(
+                            "base fee {} above the effective gas price {}",
+                            base, effective_gas_price
+                        )
+                    })?;
+                mul(base, gas_used)?
+            }
+        };
+        let blob_fee = match (receipt.blob_gas_used, receipt.blob_gas_price) {
+            (Some(used), Some(price)) => mul(price, u128::from(used))?,
+            _ => 0,
+        };
+        let l1_fee = match receipt.extra.get("l1Fee") {
+            None => None,
+            Some(fee) => Some(
+                fee.as_str()
+                    .and_then(|fee| hex::parse_quantity(fee).ok())
+                    .ok_or_else(|| Report::new(Error::UnexpectedResponseFormat))
+                    .attach_printable_lazy(|| format!("l1Fee {} is not a quantity", fee))?,
+            ),
+        };
+        let total = [blob_fee, l1_fee.unwrap_or(0)]
+            .into_iter()
+            .try_fold(execution_fee, u128::checked_add)
+            .ok_or_else(overflow)?;
+        Ok(FeeBreakdown {
+            gas_used: receipt.gas_used,
+            effective_gas_price,
+            execution_fee,
+            base_fee,
+            priority_fee: execution_fee - base_fee,
+            blob_fee,
+            l1_fee,
+            total,
+        })
+    }
+}
+
+impl Web3 {
+    /// Fees the transaction paid, read from its receipt and its block's base fee; `None`
+    /// until it is mined. See [`FeeBreakdown::from_receipt`].
+    pub async fn transaction_fee(
+        &self,
+        hash: impl Into<H256Param>,
+    ) -> Result<Option<FeeBreakdown>, Report<Error>> {
+        let Some(receipt) = self.get_receipt(hash).await? else {
+            return Ok(None);
+        };
+        let block = self
+            .eth_get_block_by_hash(receipt.block_hash, false)
+            .await?
+            .into_option()
+            .map_err(rpc_error)?
+            // reorged out between the two reads
+            .ok_or_else(|| Report::new(Error::UnexpectedResponseFormat))
+            .attach_printable_lazy(|| {
+                format!("block {} of the receipt not found", receipt.block_hash)
+            })?;
+        FeeBreakdown::from_receipt(&receipt, block.base_fee_per_gas)
+            .attach_printable_lazy(|| format!("transaction {}", receipt.transaction_hash))
+            .map(Some)
+    }
+}
+
+/// Blocks per eth_feeHistory request; geth and Erigon answer no more.
+const FEE_HISTORY_PAGE: u64 = 1024;
+
+/// EIP-1559: the gas target is the limit over this, and the base fee moves by at most one
+/// `BASE_FEE_MAX_CHANGE_DENOMINATOR`th per block.
+const ELASTICITY_MULTIPLIER: u64 = 2;
+const BASE_FEE_MAX_CHANGE_DENOMINATOR: u64 = 8;
+
+/// Which way the base fee is going: the next block's against the newest one's.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BaseFeeTrend {
+    Rising,
+    Falling,
+    Flat,
+}
+
+/// The priority fee paid at one percentile of blocks' gas, over a window.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PercentileFee {
+    pub percentile: f64,
+    /// Median over the blocks that used gas; nodes report 0 for empty blocks, which are left
+    /// out. 0 when every block was empty.
+    pub fee: u128,
+    /// Per block, oldest first.
+    pub per_block: Vec<u128>,
+}
+
+/// The fee market over a window of blocks, from [`Web3::fee_percentiles`]. Fees in wei.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FeeStats {
+    pub oldest_block: u64,
+    pub newest_block: u64,
+    /// Per block, oldest first.
+    pub base_fees: Vec<u128>,
+    /// Of the block after `newest_block`, which the EIP-1559 formula fixes from the newest
+    /// block alone.
+    pub next_base_fee: u128,
+    pub trend: BaseFeeTrend,
+    /// `gasUsed / gasLimit` per block, oldest first.
+    pub gas_used_ratios: Vec<f64>,
+    /// In the order the percentiles were asked for.
+    pub priority_fees: Vec<PercentileFee>,
+}
+
+impl FeeStats {
+    /// Reads `history`, fetched with `percentiles` as its reward percentiles.
+    pub fn from_history(
+        history: &FeeHistory,
+        percentiles: &[f64],
+    ) -> Result<FeeStats, Report<Error>> {
+        let blocks = history.gas_used_ratio.len();
+        if blocks == 0 {
+            return Err(Report::new(Error::UnexpectedResponseFormat)
+                .attach_printable("eth_feeHistory returned no blocks"));
+        }
+        if history.base_fee_per_gas.len() < blocks {
+            return Err(
+                Report::new(Error::UnexpectedResponseFormat).attach_printable(format!(
+                    "eth_feeHistory returned {} base fees for {} blocks",
+                    history.base_fee_per_gas.len(),
+                    blocks
+                )),
+            );
+        }
+        let base_fees = history.base_fee_per_gas[..blocks]
+            .iter()
+            .map(|fee| wei(*fee))
+            .collect::<Result<Vec<_>, _>>()?;
+        let newest_base_fee = base_fees[blocks - 1];
+        let next_base_fee = match history.base_fee_per_gas.get(blocks) {
+            Some(fee) => wei(*fee)?,
+            // the node left the projection out; the ratio is exact enough for it
+            None => {
+                let ratio = history.gas_used_ratio[blocks - 1].clamp(0.0, 1.0);
+                next_base_fee(newest_base_fee, (ratio * 1e9).round() as u64, 1_000_000_000)
+            }
+        };
+        let trend = match next_base_fee.cmp(&newest_base_fee) {
+            std::cmp::Ordering::Greater => BaseFeeTrend::Rising,
+            std::cmp::Ordering::Less => BaseFeeTrend::Falling,
+            std::cmp::Ordering::Equal => BaseFeeTrend::Flat,
+        };
+
+        let rewards = match (&history.reward, percentiles.is_empty()) {
+            (_, true) => Vec::new(),
+            (Some(rewards), false) if rewards.len() == blocks => rewards.clone(),
+            _ => {
+                return Err(Report::new(Error::UnexpectedResponseFormat)
+                    .attach_printable("eth_feeHistory reward does not cover every block"))
+            }
+        };
+        let mut priority_fees = Vec::with_capacity(percentiles.len());
+        for (i, percentile) in percentiles.iter().enumerate() {
+            let per_block = rewards
+                .iter()
+                .map(|block| {
+                    let reward = block.get(i).ok_or_else(|| {
+                        Report::new(Error::UnexpectedResponseFormat)
+                            .attach_printable(format!("no reward at percentile {}", percentile))
+                    })?;
+                    wei(*reward)
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            let mut used: Vec<u128> = per_block
+                .iter()
+                .zip(&history.gas_used_ratio)
+                .filter(|(_, ratio)| **ratio > 0.0)
+                .map(|(fee, _)| *fee)
+                .collect();
+            used.sort_unstable();
+            priority_fees.push(PercentileFee {
+                percentile: *percentile,
+                fee: used.get(used.len() / 2).copied().unwrap_or(0),
+                per_block,
+            });
+        }
+
+        Ok(FeeStats {
+            oldest_block: history.oldest_block,
+            newest_block: history.oldest_block + blocks as u64 - 1,
+            base_fees,
+            next_base_fee,
+            trend,
+            gas_used_ratios: history.gas_used_ratio.clone(),
+            priority_fees,
+        })
+    }
+
+    pub fn mean_gas_used_ratio(&self) -> f64 {
+        self.gas_used_ratios.iter().sum::<f64>() / self.gas_used_ratios.len() as f64
+    }
+
+    /// The highest the base fee can be `blocks` blocks after the next one, each full. A max
+    /// fee of this plus the tip keeps a transaction includable that long.
+    pub fn max_base_fee_after(&self, blocks: u32) -> u128 {
+        (0..blocks).fold(self.next_base_fee, |fee, _| next_base_fee(fee, 2, 2))
+    }
+}
+
+/// Base fee of the block after one with `base_fee`, `gas_used` and `gas_limit`, by the
+/// EIP-1559 formula: up or down by the gas used off target over the target, an eighth at most,
+/// rising by at least 1 wei. Not for the London block itself, which starts at 1 gwei.
+pub fn next_base_fee(base_fee: u128, gas_used: u64, gas_limit: u64) -> u128 {
+    let target = gas_limit / ELASTICITY_MULTIPLIER;
+    if target == 0 || gas_used == target {
+        return base_fee;
+    }
+    // base fees above 2^64 wei would overflow 128 bits here
+    let change = |delta: u64| -> U256 {
+        let scaled = U256::from(base_fee)
+            .checked_mul(U256::from(delta))
+            .unwrap_or(U256::MAX);
+        scaled
+            .checked_div(U256::from(target))
+            .and_then(|fee| fee.checked_div(U256::from(BASE_FEE_MAX_CHANGE_DENOMINATOR)))
+            .unwrap_or(U256::MAX)
+    };
+    if gas_used > target {
+        let delta = change(gas_used - target).max(U256::from(1u64));
+        u128::try_from(delta)
+            .ok()
+            .and_then(|delta| base_fee.checked_add(delta))
+            .unwrap_or(u128::MAX)
+    } else {
+        let delta = u128::try_from(change(target - gas_used)).unwrap_or(u128::MAX);
+        base_fee.saturating_sub(delta)
+    }
+}
+
+impl Web3 {
+    /// The fee market over the last `window_blocks` blocks: priority fees at each of
+    /// `percentiles` (0 to 100, ascending, of each block's gas), the base fee, where it goes
+    /// next, and how full the blocks were. Windows longer than nodes answer at once are read
+    /// in several eth_feeHistory requests.
+    pub async fn fee_percentiles(
+        &self,
+        window_blocks: u64,
+        percentiles: &[f64],
+    ) -> Result<FeeStats, Report<Error>> {
+        if window_blocks == 0 {
+            return Err(Report::new(Error::InvalidArgument {
+                param: "window_blocks",
+                reason: "empty window".to_string(),
+            }));
+        }
+        let in_range = percentiles.iter().all(|p| (0.0..=100.0).contains(p));
+        if !in_range || percentiles.windows(2).any(|pair| pair[0] > pair[1]) {
+            return Err(Report::new(Error::InvalidArgument {
+                param: "percentiles",
+                reason: format!("{:?} are not ascending between 0 and 100", percentiles),
+            }));
+        }
+
+        // newest page first
+        let mut pages = Vec::new();
+        let mut newest = BlockId::Tag(Tag::Latest);
+        let mut remaining = window_blocks;
+        while remaining > 0 {
+            let history = self
+                .eth_fee_history(remaining.min(FEE_HISTORY_PAGE), newest, percentiles)
+                .await?
+                .into_result()
+                .map_err(rpc_error)?;
+            let blocks = history.gas_used_ratio.len() as u64;
+            let oldest = history.oldest_block;
+            // a node short of history answers with fewer blocks, or none
+            if blocks > 0 || pages.is_empty() {
+                pages.push(history);
+            }
+            if blocks == 0 || oldest == 0 {
+                break;
+            }
+            remaining = remaining.saturating_sub(blocks);
+            newest = BlockId::Number(oldest - 1);
+        }
+        let history = merge(pages).ok_or_else(|| {
+            Report::new(Error::UnexpectedResponseFormat)
+                .attach_printable("eth_feeHistory returned nothing")
+        })?;
+        FeeStats::from_history(&history, percentiles)
+            .attach_printable_lazy(|| format!("fee history from block {}", history.oldest_block))
+    }
+}
+
+/// Consecutive histories, newest first, as one. Only the newest keeps its projection for the
+/// block after it; blob fields are dropped.
+fn merge(pages: Vec<FeeHistory>) -> Option<FeeHistory> {
+    let mut pages = pages.into_iter();
+    let mut newest = pages.next()?;
+    for page in pages {
+        let blocks = page.gas_used_ratio.len();
+        let mut base_fees = page.base_fee_per_gas;
+        base_fees.truncate(blocks);
+        base_fees.append(&mut newest.base_fee_per_gas);
+        newest.base_fee_per_gas = base_fees;
+        let mut ratios = page.gas_used_ratio;
+        ratios.append(&mut newest.gas_used_ratio);
+        newest.gas_used_ratio = ratios;
+        newest.reward = match (page.reward, newest.reward) {
+            (Some(mut older), Some(mut newer)) => {
+                older.append(&mut newer);
+                Some(older)
+            }
+            _ => None,
+        };
+        newest.oldest_block = page.oldest_block;
+        newest.base_fee_per_blob_gas = None;
+        newest.blob_gas_used_ratio = None;
+    }
+    Some(newest)
+}
+
+fn wei(fee: U256) -> Result<u128, Report<Error>> {
+    u128::try_from(fee)
+        .map_err(|_| overflow())
+        .attach_printable_lazy(|| format!("fee {}", fee))
+}
+
+fn mul(a: u128, b: u128) -> Result<u128, Report<Error>> {
+    a.checked_mul(b).ok_or_else(overflow)
+}
+
+fn overflow() -> Report<Error> {
+    Report::new(Error::UnexpectedResponseFormat).attach_printable("fee overflows 128 bits")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture;
+
+    const GWEI: u128 = 1_000_000_000;
+
+    #[test]
+    fn dynamic_fee_splits_base_and_tip() {
+        // 20 gwei effective at a 15 gwei base fee
+        let fee =
+            FeeBreakdown::from_receipt(&fixture("receipt_many_logs"), Some(U256::from(15 * GWEI)))
+                .unwrap();
+        assert_eq!(fee.gas_used, 0x2c8f1);
+        assert_eq!(fee.execution_fee, 0x2c8f1 * 20 * GWEI);
+        assert_eq!(fee.base_fee, 0x2c8f1 * 15 * GWEI);
+        assert_eq!(fee.priority_fee, 0x2c8f1 * 5 * GWEI);
+        assert_eq!((fee.blob_fee, fee.l1_fee), (0, None));
+        assert_eq!(fee.total, fee.execution_fee);
+    }
+
+    #[test]
+    fn legacy_transactions() {
+        // a legacy transaction in a London block pays the base fee like any other
+        let legacy = fixture("receipt_legacy");
+        let fee = FeeBreakdown::from_receipt(&legacy, Some(U256::from(15 * GWEI))).unwrap();
+        assert_eq!(fee.effective_gas_price, 25 * GWEI);
+        assert_eq!(fee.base_fee, 21_000 * 15 * GWEI);
+        assert_eq!(fee.priority_fee, 21_000 * 10 * GWEI);
+
+        // before London it is all base
+        let fee = FeeBreakdown::from_receipt(&legacy, None).unwrap();
+        assert_eq!(fee.base_fee, 21_000 * 25 * GWEI);
+        assert_eq!(fee.priority_fee, 0);
+        assert_eq!(fee.total, fee.execution_fee);
+
+        // pre-Byzantium receipts have no effective gas price
+        assert!(FeeBreakdown::from_receipt(&fixture("receipt_pre_byzantium"), None).is_err());
+    }
+
+    #[test]
+    fn blob_fee() {
+        let fee = FeeBreakdown::from_receipt(&fixture("receipt_blob"), Some(U256::from(20 * GWEI)))
+            .unwrap();
+        assert_eq!(fee.blob_fee, 0x20000);
+        assert_eq!(fee.priority_fee, 0);
+        assert_eq!(fee.total, 21_000 * 20 * GWEI + 0x20000);
+    }
+
+    #[test]
+    fn op_stack() {
+        let base = fixture("receipt_base");
+        let fee = FeeBreakdown::from_receipt(&base, Some(U256::from(1_900_000u64))).unwrap();
+        assert_eq!(fee.l1_fee, Some(0x1a3b5c7d9));
+        assert_eq!(fee.execution_fee, 0xb41e * 2_000_000);
+        assert_eq!(fee.priority_fee, 0xb41e * 100_000);
+        assert_eq!(fee.total, 0xb41e * 2_000_000 + 0x1a3b5c7d9);
+
+        let deposit =
+            FeeBreakdown::from_receipt(&fixture("receipt_deposit"), Some(U256::from(1_900_000u64)))
+                .unwrap();
+        assert_eq!(deposit.gas_used, 0xb4b2);
+        assert_eq!(
+            (deposit.execution_fee, deposit.base_fee, deposit.total),
+            (0, 0, 0)
+        );
+    }
+
+    #[test]
+    fn base_fee_formula() {
+        let base = 20 * GWEI;
+        assert_eq!(next_base_fee(base, 30_000_000, 30_000_000), base + base / 8);
+        assert_eq!(next_base_fee(base, 0, 30_000_000), base - base / 8);
+        assert_eq!(next_base_fee(base, 15_000_000, 30_000_000), base);
+        // a quarter above the target moves a quarter of the most
+        assert_eq!(
+            next_base_fee(base, 18_750_000, 30_000_000),
+            base + base / 32
+        );
+        assert_eq!(
+            next_base_fee(base, 11_250_000, 30_000_000),
+            base - base / 32
+        );
+        // rises by at least 1 wei, and falls by what rounds down
+        assert_eq!(next_base_fee(7, 15_000_001, 30_000_000), 8);
+        assert_eq!(next_base_fee(7, 14_999_999, 30_000_000), 7);
+        assert_eq!(next_base_fee(u128::MAX, 30_000_000, 30_000_000), u128::MAX);
+        assert_eq!(next_base_fee(base, 0, 1), base);
+    }
+
+    #[test]
+    fn stats_from_history() {
+        let stats = FeeStats::from_history(&fixture("fee_history"), &[10.0, 50.0, 90.0]).unwrap();
+        assert_eq!(
+            (stats.oldest_block, stats.newest_block),
+            (0x12f2a51, 0x12f2a54)
+        );
+        assert_eq!(stats.base_fees.len(), 4);
+        assert_eq!(stats.next_base_fee, 0x15d8e4a12);
+        assert_eq!(stats.trend, BaseFeeTrend::Falling);
+        assert_eq!(
+            stats.priority_fees[0].per_block,
+            [100_000_000, 50_000_000, 500_000_000, 100_000_000]
+        );
+        assert_eq!(stats.priority_fees[0].fee, 100_000_000);
+        assert_eq!(stats.priority_fees[1].fee, GWEI);
+        assert_eq!(stats.priority_fees[2].percentile, 90.0);
+        assert!((stats.mean_gas_used_ratio() - 0.406).abs() < 0.001);
+        assert_eq!(stats.max_base_fee_after(0), stats.next_base_fee);
+        assert_eq!(
+            stats.max_base_fee_after(2),
+            next_base_fee(next_base_fee(0x15d8e4a12, 2, 2), 2, 2)
+        );
+
+        // empty blocks do not count towards the median
+        let mut quiet: FeeHistory = fixture("fee_history");
+        quiet.gas_used_ratio[1] = 0.0;
+        quiet.gas_used_ratio[3] = 0.0;
+        let stats = FeeStats::from_history(&quiet, &[10.0, 50.0, 90.0]).unwrap();
+        assert_eq!(stats.priority_fees[0].fee, 500_000_000);
+
+        // without the projection it is computed from the newest block
+        let mut short: FeeHistory = fixture("fee_history");
+        short.base_fee_per_gas.pop();
+        let stats = FeeStats::from_history(&short, &[]).unwrap();
+        assert_eq!(
+            stats.next_base_fee,
+            next_base_fee(0x16ee2d8f0, 212_602_567, 1_000_000_000)
+        );
+        assert_eq!(stats.trend, BaseFeeTrend::Falling);
+        assert!(stats.priority_fees.is_empty());
+
+        let mut missing: FeeHistory = fixture("fee_history");
+        missing.reward = None;
+        assert!(FeeStats::from_history(&missing, &[50.0]).is_err());
+    }
+
+    #[test]
+    fn inconsistent_inputs_fail() {
+        let mut r = fixture("receipt_many_logs");
+        // base fee above what the transaction paid per gas
+        assert!(FeeBreakdown::from_receipt(&r, Some(U256::from(21 * GWEI))).is_err());
+        r.effective_gas_price = Some(u128::MAX);
+        assert!(FeeBreakdown::from_receipt(&r, None).is_err());
+    }
+}
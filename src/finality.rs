@@ -0,0 +1,214 @@
+//! Finality checks, for acting only on data that can no longer be reorganised.
+//!
+//! Proof-of-stake chains report the last finalized block under the `finalized` tag. Chains
+//! and older nodes without it fall back to a fixed confirmation depth below `latest`.
+//!
+//! ```no_run
+//! # async fn run(web3: web3_rpc::web3::Web3, tx: web3_rpc::types::H256) {
+//! use std::time::Duration;
+//!
+//! let receipt = web3
+//!     .finality()
+//!     .confirmations(12)
+//!     .wait_for_finalization(tx, Duration::from_secs(30 * 60))
+//!     .await
+//!     .unwrap();
+//! # }
+//! ```
+
+use crate::client::Error;
+use crate::model::{BlockId, Receipt, Tag};
+use crate::serde_helpers::deserialize_hex_u64;
+use crate::types::H256;
+use crate::web3::Web3;
+use error_stack::Report;
+use serde::Deserialize;
+use serde_json::json;
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// Depth below `latest` treated as final when the node has no `finalized` tag.
+pub const DEFAULT_CONFIRMATIONS: u64 = 64;
+
+/// How often [`Finality::wait_for_finalization`] polls, one slot on mainnet.
+pub const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(12);
+
+/// Messages of -32000 errors with which nodes reject the `finalized` tag itself, rather than
+/// failing to answer.
+const TAG_REJECTED: &[&str] = &[
+    "finalized block not found",
+    "safe block not found",
+    "unknown block tag",
+    "invalid block tag",
+];
+
+/// Builder returned by [`Web3::finality`].
+pub struct Finality<'a> {
+    web3: &'a Web3,
+    confirmations: u64,
+    poll_interval: Duration,
+}
+
+/// Whether `error` is the node not knowing the `finalized` tag.
+fn tag_rejected(error: &Error) -> bool {
+    match error {
+        Error::JsonRpcError {
+            code: -32601 | -32602,
+            ..
+        } => true,
+        Error::JsonRpcError {
+            code: -32000,
+            message,
+            ..
+        } => {
+            let message = message.to_lowercase();
+            TAG_REJECTED
+                .iter()
+                .any(|rejected| message.contains(rejected))
+        }
+        _ => false,
+    }
+}
+
+#[derive(Deserialize)]
+struct Head {
+    #[serde(deserialize_with = "deserialize_hex_u64")]
+    number: u64,
+    hash: H256,
+}
+
+impl Web3 {
+    pub fn finality(&self) -> Finality<'_> {
+        Finality {
+            web3: self,
+            confirmations: DEFAULT_CONFIRMATIONS,
+            poll_interval: DEFAULT_POLL_INTERVAL,
+        }
+    }
+
+    /// [`Finality::is_finalized`] with the default settings.
+    pub async fn is_finalized(&self, block: impl Into<BlockId>) -> Result<bool, Report<Error>> {
+        self.finality().is_finalized(block).await
+    }
+
+    /// [`Finality::wait_for_finalization`] with the default settings.
+    pub async fn wait_for_finalization(
+        &self,
+        tx_hash: H256,
+        timeout: Duration,
+    ) -> Result<Receipt, Report<Error>> {
+        self.finality()
+            .wait_for_finalization(tx_hash, timeout)
+            .await
+    }
+}
+
+impl<'a> Finality<'a> {
+    /// Confirmation depth for nodes without a `finalized` tag, [`DEFAULT_CONFIRMATIONS`]
+    /// by default.
+    pub fn confirmations(mut self, confirmations: u64) -> Self {
+        self.confirmations = confirmations;
+        self
+    }
+
+    pub fn poll_interval(mut self, interval: Duration) -> Self {
+        self.poll_interval = interval;
+        self
+    }
+
+    /// Number of the newest final block.
+    pub async fn finalized_number(&self) -> Result<u64, Report<Error>> {
+        match self.head(Tag::Finalized.into()).await {
+            Ok(Some(head)) => return Ok(head.number),
+            // pre-merge nodes reject the tag, some L2s answer null
+            Ok(None) => {}
+            Err(e) if tag_rejected(e.current_context()) => {}
+            // any other failure says nothing about what is final
+            Err(e) => return Err(e),
+        }
+        let latest = self.head(Tag::Latest.into()).await?.ok_or_else(|| {
+            Report::new(Error::UnexpectedResponseFormat).attach_printable("no latest block")
+        })?;
+        Ok(latest.number.saturating_sub(self.confirmations))
+    }
+
+    /// Whether `block` is final. A hash is only final while it is still the canonical
+    /// block at its height; an unknown hash is not final.
+    pub async fn is_finalized(&self, block: impl Into<BlockId>) -> Result<bool, Report<Error>> {
+        let block = block.into();
+        let head = match self.head(block.clone()).await? {
+            Some(head) => head,
+            None => return Ok(false),
+        };
+        if head.number > self.finalized_number().await? {
+            return Ok(false);
+        }
+        match block {
+            BlockId::Hash(hash) => self.is_canonical(head.number, hash).await,
+            _ => Ok(true),
+        }
+    }
+
+    /// Waits until `tx_hash` is mined in a final block and returns its receipt.
+    ///
+    /// Once the receipt's block is final, the canonical block at that height is fetched
+    /// again: if a reorg replaced it, the receipt is discarded and the wait goes on until
+    /// the transaction lands in a final block or `timeout` runs out.
+    pub async fn wait_for_finalization(
+        &self,
+        tx_hash: H256,
+        timeout: Duration,
+    ) -> Result<Receipt, Report<Error>> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            if let Some(receipt) = self.web3.eth_get_transaction_receipt(tx_hash).await?.result {
+                let number = receipt
+                    .block_number
+                    .strip_prefix("0x")
+                    .and_then(|digits| u64::from_str_radix(digits, 16).ok())
+                    .ok_or_else(|| {
+                        Report::new(Error::FailedToDeserialize).attach_printable(format!(
+                            "receipt block number `{}`",
+                            receipt.block_number
+                        ))
+                    })?;
+                if number <= self.finalized_number().await? {
+                    if self.is_canonical(number, receipt.block_hash).await? {
+                        return Ok(receipt);
+                    }
+                    tracing::warn!(
+                        tx = %tx_hash,
+                        block = number,
+                        "receipt block was reorged out before finalization"
+                    );
+                }
+            }
+            if Instant::now() + self.poll_interval > deadline {
+                return Err(Report::new(Error::Timeout)
+                    .attach_printable(format!("{} not finalized after {:?}", tx_hash, timeout)));
+            }
+            tokio::time::sleep(self.poll_interval).await;
+        }
+    }
+
+    async fn is_canonical(&self, number: u64, hash: H256) -> Result<bool, Report<Error>> {
+        Ok(self
+            .head(number.into())
+            .await?
+            .is_some_and(|head| head.hash == hash))
+    }
+
+    /// Number and hash of `block`, fetched without transactions.
+    async fn head(&self, block: BlockId) -> Result<Option<Head>, Report<Error>> {
+        let payload = match block {
+            BlockId::Hash(hash) => {
+                json!({ "jsonrpc": "2.0", "method": "eth_getBlockByHash", "params": [hash, false], "id": "324" })
+            }
+            other => {
+                json!({ "jsonrpc": "2.0", "method": "eth_getBlockByNumber", "params": [other, false], "id": "325" })
+            }
+        };
+        let head = self.web3.client.call::<Head>(payload).await?.result;
+        Ok(head)
+    }
+}
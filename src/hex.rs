@@ -0,0 +1,184 @@
+//! JSON-RPC hex encodings: quantities are minimal (`0x0`, `0x1b4`), data is `0x` followed by
+//! two lowercase digits per byte. Use these instead of `format!("0x{:x}", ..)`, which is
+//! right for quantities but not for data (`0x0` is not one byte of zero).
+
+use crate::types::ParseHexError;
+
+/// Minimal hex quantity, `0x0` for zero.
+pub fn quantity(n: u128) -> String {
+    format!("0x{:x}", n)
+}
+
+/// Even-length hex data, `0x` for no bytes.
+pub fn data(bytes: &[u8]) -> String {
+    let mut s = String::with_capacity(2 + bytes.len() * 2);
+    s.push_str("0x");
+    for b in bytes {
+        s.push_str(&format!("{:02x}", b));
+    }
+    s
+}
+
+/// A 32-byte word (hash, storage slot, topic) as 64 digits.
+pub fn fixed32(bytes: &[u8; 32]) -> String {
+    data(bytes)
+}
+
+/// Inverse of [`quantity`]. Leading zeros, which some nodes send, are accepted.
+pub fn parse_quantity(s: &str) -> Result<u128, ParseHexError> {
+    let digits = s.strip_prefix("0x").ok_or(ParseHexError::MissingPrefix)?;
+    if digits.is_empty() {
+        return Err(ParseHexError::Empty);
+    }
+    // from_str_radix alone would also accept a leading `+`
+    if let Some(c) = digits.chars().find(|c| !c.is_ascii_hexdigit()) {
+        return Err(ParseHexError::InvalidCharacter(c));
+    }
+    let significant = digits.trim_start_matches('0');
+    if significant.len() > 32 {
+        return Err(ParseHexError::Overflow);
+    }
+    Ok(u128::from_str_radix(digits, 16).unwrap_or_default())
+}
+
+/// Inverse of [`data`]: `0x`-prefixed hex of any even length.
+pub fn parse_data(s: &str) -> Result<Vec<u8>, ParseHexError> {
+    let digits = s.strip_prefix("0x").ok_or(ParseHexError::MissingPrefix)?;
+    if let Some(c) = digits.chars().find(|c| !c.is_ascii_hexdigit()) {
+        return Err(ParseHexError::InvalidCharacter(c));
+    }
+    if !digits.len().is_multiple_of(2) {
+        return Err(ParseHexError::InvalidLength {
+            expected: digits.len() + 1,
+            got: digits.len(),
+        });
+    }
+    Ok(digits.as_bytes().chunks(2).map(byte).collect())
+}
+
+/// Inverse of [`fixed32`]: exactly 64 digits.
+pub fn parse_fixed32(s: &str) -> Result<[u8; 32], ParseHexError> {
+    parse_fixed(s)
+}
+
+pub(crate) fn parse_fixed<const N: usize>(s: &str) -> Result<[u8; N], ParseHexError> {
+    let digits = s.strip_prefix("0x").ok_or(ParseHexError::MissingPrefix)?;
+    if digits.len() != N * 2 {
+        return Err(ParseHexError::InvalidLength {
+            expected: N * 2,
+            got: digits.len(),
+        });
+    }
+    // checked up front so multi-byte characters cannot split a digit pair
+    if let Some(c) = digits.chars().find(|c| !c.is_ascii_hexdigit()) {
+        return Err(ParseHexError::InvalidCharacter(c));
+    }
+    let mut out = [0u8; N];
+    for (i, pair) in digits.as_bytes().chunks(2).enumerate() {
+        out[i] = byte(pair);
+    }
+    Ok(out)
+}
+
+/// A pair of digits already checked to be hex.
+fn byte(pair: &[u8]) -> u8 {
+    let hi = (pair[0] as char).to_digit(16).unwrap_or_default();
+    let lo = (pair[1] as char).to_digit(16).unwrap_or_default();
+    (hi * 16 + lo) as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::collection::vec;
+    use proptest::prelude::*;
+
+    #[test]
+    fn edge_values() {
+        assert_eq!(quantity(0), "0x0");
+        assert_eq!(quantity(1), "0x1");
+        assert_eq!(quantity(u128::MAX), format!("0x{}", "f".repeat(32)));
+        assert_eq!(parse_quantity("0x0"), Ok(0));
+        assert_eq!(parse_quantity("0x1"), Ok(1));
+        assert_eq!(parse_quantity(&quantity(u128::MAX)), Ok(u128::MAX));
+        assert_eq!(parse_quantity("0x0001"), Ok(1));
+
+        assert_eq!(data(&[]), "0x");
+        assert_eq!(data(&[0]), "0x00");
+        assert_eq!(data(&[1]), "0x01");
+        assert_eq!(data(&[0xff; 2]), "0xffff");
+        assert_eq!(parse_data("0x"), Ok(vec![]));
+        assert_eq!(parse_data("0x00"), Ok(vec![0]));
+        assert_eq!(parse_data("0xABcd"), Ok(vec![0xab, 0xcd]));
+
+        assert_eq!(fixed32(&[0; 32]), format!("0x{}", "0".repeat(64)));
+        let mut one = [0; 32];
+        one[31] = 1;
+        assert_eq!(fixed32(&one), format!("0x{:064x}", 1));
+        assert_eq!(parse_fixed32(&fixed32(&[0xff; 32])), Ok([0xff; 32]));
+    }
+
+    #[test]
+    fn rejects_malformed_input() {
+        assert_eq!(parse_quantity("1"), Err(ParseHexError::MissingPrefix));
+        assert_eq!(parse_quantity("0x"), Err(ParseHexError::Empty));
+        assert_eq!(
+            parse_quantity("0x+1"),
+            Err(ParseHexError::InvalidCharacter('+'))
+        );
+        assert_eq!(
+            parse_quantity(&format!("0x1{}", "0".repeat(32))),
+            Err(ParseHexError::Overflow)
+        );
+        assert_eq!(parse_data("00"), Err(ParseHexError::MissingPrefix));
+        assert_eq!(
+            parse_data("0x0g"),
+            Err(ParseHexError::InvalidCharacter('g'))
+        );
+        assert_eq!(
+            parse_data("0x000"),
+            Err(ParseHexError::InvalidLength {
+                expected: 4,
+                got: 3
+            })
+        );
+        assert_eq!(
+            parse_fixed32("0x00"),
+            Err(ParseHexError::InvalidLength {
+                expected: 64,
+                got: 2
+            })
+        );
+        let wide = format!("0x{}é", "a".repeat(62));
+        assert_eq!(
+            parse_fixed32(&wide),
+            Err(ParseHexError::InvalidCharacter('é'))
+        );
+    }
+
+    proptest! {
+        #[test]
+        fn quantity_round_trip(n: u128) {
+            let hex = quantity(n);
+            let digits = hex.strip_prefix("0x").unwrap();
+            prop_assert!(digits == "0" || !digits.starts_with('0'), "{}", hex);
+            prop_assert_eq!(parse_quantity(&hex), Ok(n));
+        }
+
+        #[test]
+        fn data_round_trip(bytes in vec(any::<u8>(), 0..256)) {
+            let hex = data(&bytes);
+            prop_assert_eq!(hex.len(), 2 + bytes.len() * 2);
+            prop_assert!(!hex.chars().any(|c| c.is_ascii_uppercase()));
+            prop_assert_eq!(parse_data(&hex), Ok(bytes));
+        }
+
+        #[test]
+        fn fixed32_round_trip(bytes: [u8; 32]) {
+            let hex = fixed32(&bytes);
+            prop_assert_eq!(hex.len(), 66);
+            prop_assert_eq!(parse_fixed32(&hex), Ok(bytes));
+            prop_assert_eq!(parse_data(&hex), Ok(bytes.to_vec()));
+        }
+    }
+}
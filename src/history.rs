@@ -0,0 +1,72 @@
+//! Balances sampled over a block range, for charting an account over time.
+
+use crate::client::{rpc_error, Error};
+use crate::model::JsonRpcResult;
+use crate::types::{Address, U256};
+use crate::web3::Web3;
+use error_stack::{Report, ResultExt};
+use serde_json::json;
+
+impl Web3 {
+    /// `(block_number, timestamp, balance)` of `address` every `step` blocks from
+    /// `from_block`, plus `to_block` itself, in block order.
+    ///
+    /// Historical balances need an archive node; a node that has pruned the state fails
+    /// with [`Error::NotArchiveNode`].
+    pub async fn balance_history(
+        &self,
+        address: Address,
+        from_block: u64,
+        to_block: u64,
+        step: u64,
+    ) -> Result<Vec<(u64, u64, U256)>, Report<Error>> {
+        let mut heights: Vec<u64> = (from_block..=to_block)
+            .step_by(step.max(1) as usize)
+            .collect();
+        if heights.last().is_some_and(|last| *last != to_block) {
+            heights.push(to_block);
+        }
+        self.in_batches(heights, move |web3, blocks| async move {
+            let balances = web3.balances_at(address, &blocks).await?;
+            let headers = web3.block_headers(&blocks).await?;
+            Ok(headers
+                .into_iter()
+                .zip(balances)
+                .map(|(header, balance)| (header.number, header.timestamp, balance))
+                .collect())
+        })
+        .await
+    }
+
+    async fn balances_at(
+        &self,
+        address: Address,
+        blocks: &[u64],
+    ) -> Result<Vec<U256>, Report<Error>> {
+        let payloads = blocks
+            .iter()
+            .map(|n| {
+                json!({ "jsonrpc": "2.0", "method": "eth_getBalance", "params": [address, format!("{:#x}", n)], "id": "311" })
+            })
+            .collect();
+        let responses: Vec<JsonRpcResult<U256>> = self.client.call_batch(payloads).await?;
+        responses
+            .into_iter()
+            .zip(blocks)
+            .map(|(response, n)| match (response.result, response.error) {
+                (_, Some(error)) => {
+                    let report = rpc_error(error);
+                    if report.current_context().is_missing_state() {
+                        Err(report.change_context(Error::NotArchiveNode))
+                    } else {
+                        Err(report)
+                    }
+                    .attach_printable_lazy(|| format!("balance at block {}", n))
+                }
+                (Some(balance), None) => Ok(balance),
+                (None, None) => Err(Report::new(Error::UnexpectedResponseFormat)
+                    .attach_printable(format!("no balance at block {}", n))),
+            })
+            .collect()
+    }
+}
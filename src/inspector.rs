@@ -0,0 +1,81 @@
+//! Wire-level inspection of requests and responses, for reproducing provider bugs.
+//!
+//! Install with [`crate::client::Client::set_inspector`] or
+//! [`crate::builder::Web3Builder::inspector`]. Without an inspector nothing is recorded.
+
+use reqwest::StatusCode;
+use serde_json::{json, Value};
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write};
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// One HTTP round trip. A batch payload is a single exchange.
+#[derive(Debug)]
+pub struct Exchange<'a> {
+    /// The JSON body as sent.
+    pub request: &'a Value,
+    /// Names of the headers configured on the client; their values are never exposed.
+    pub headers: &'a [String],
+    /// `None` when the request failed before a response arrived.
+    pub status: Option<StatusCode>,
+    /// Raw response body, `None` when it could not be read.
+    pub response: Option<&'a str>,
+    pub duration: Duration,
+}
+
+/// Called once per HTTP attempt, retries included, before the response is interpreted.
+pub trait RequestInspector: Send + Sync {
+    fn inspect(&self, exchange: &Exchange<'_>);
+}
+
+/// Appends one JSON object per exchange to a file (newline-delimited JSON).
+#[derive(Debug)]
+pub struct FileInspector {
+    file: Mutex<File>,
+}
+
+impl FileInspector {
+    pub fn create(path: impl AsRef<Path>) -> io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(FileInspector {
+            file: Mutex::new(file),
+        })
+    }
+
+    fn record(exchange: &Exchange<'_>) -> Value {
+        let timestamp_ms = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or_default();
+        let headers: serde_json::Map<String, Value> = exchange
+            .headers
+            .iter()
+            .map(|name| (name.clone(), json!("<redacted>")))
+            .collect();
+        json!({
+            "timestamp_ms": timestamp_ms,
+            "request": exchange.request,
+            "headers": headers,
+            "status": exchange.status.map(|s| s.as_u16()),
+            "response": exchange.response,
+            "duration_ms": exchange.duration.as_secs_f64() * 1000.0,
+        })
+    }
+}
+
+impl RequestInspector for FileInspector {
+    fn inspect(&self, exchange: &Exchange<'_>) {
+        let mut line = Self::record(exchange).to_string();
+        line.push('\n');
+        let mut file = match self.file.lock() {
+            Ok(file) => file,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        // inspection must never fail the request it observes
+        if let Err(e) = file.write_all(line.as_bytes()) {
+            tracing::warn!("failed to write request log: {}", e);
+        }
+    }
+}
@@ -1,3 +1,61 @@
+pub mod abi;
+pub mod activity;
+#[cfg(feature = "alloy")]
+pub mod alloy;
+pub mod archive;
+pub mod backfill;
+pub mod batching;
+pub mod builder;
+pub mod capacity;
+pub mod circuit;
 pub mod client;
+pub mod deploy;
+pub mod deposit;
+pub mod endpoints;
+pub mod erc1155;
+pub mod erc20;
+pub mod erc721;
+pub mod eta;
+pub mod events;
+pub mod fees;
+pub mod finality;
+pub mod hex;
+pub mod history;
+pub mod inspector;
+pub mod keccak;
+pub mod logs;
+pub mod mempool;
 pub mod model;
+pub mod multicall;
+pub mod native;
+pub mod provider;
+pub mod raw_transaction;
+pub mod replacement;
+pub mod rlp;
+pub mod send;
+pub mod serde_helpers;
+#[cfg(feature = "std-error")]
+pub mod std_error;
+pub mod storage;
+pub mod streaming;
+pub mod strict;
+pub mod subscription;
+#[cfg(feature = "test-util")]
+pub mod test_util;
+pub mod transactions;
+pub mod types;
+pub mod verified;
+pub mod verify;
 pub mod web3;
+pub mod withdrawals;
+
+/// `tests/fixtures/<name>.json`, for the unit tests.
+#[cfg(test)]
+pub(crate) fn fixture<T: serde::de::DeserializeOwned>(name: &str) -> T {
+    let path = format!(
+        "{}/tests/fixtures/{}.json",
+        env!("CARGO_MANIFEST_DIR"),
+        name
+    );
+    serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap()
+}
@@ -0,0 +1,100 @@
+//! eth_getLogs over long block ranges, split into requests nodes will answer.
+
+use crate::client::Error;
+use crate::model::{Log, LogFilter};
+use crate::web3::Web3;
+use error_stack::Report;
+
+/// Blocks per eth_getLogs request unless the caller says otherwise; most providers cap
+/// ranges somewhere between 2k and 10k blocks.
+pub const DEFAULT_CHUNK_BLOCKS: u64 = 2_000;
+
+impl Web3 {
+    /// Logs matching `filter` in `from..=to`, fetched `chunk` blocks at a time and returned
+    /// in block order. A request the node rejects as too large is halved and retried, down
+    /// to a single block. The filter's own block range and hash are ignored.
+    pub async fn get_logs_chunked(
+        &self,
+        filter: &LogFilter,
+        from: u64,
+        to: u64,
+        chunk: u64,
+    ) -> Result<Vec<Log>, Report<Error>> {
+        let mut filter = LogFilter {
+            block_hash: None,
+            ..filter.clone()
+        };
+        let mut logs = Vec::new();
+        let mut size = chunk.max(1);
+        let mut cursor = from;
+        while cursor <= to {
+            let end = cursor.saturating_add(size - 1).min(to);
+            filter = filter.blocks(cursor, end);
+            match self.eth_get_logs(&filter).await {
+                Ok(r) => logs.extend(r.result.unwrap_or_default()),
+                Err(e) if end > cursor && is_range_too_large(e.current_context()) => {
+                    size = (end - cursor).div_ceil(2);
+                    tracing::debug!(from = cursor, to = end, "log range too large, halving");
+                    continue;
+                }
+                Err(e) => {
+                    return Err(e.attach_printable(format!("logs for blocks {}..={}", cursor, end)))
+                }
+            }
+            match end.checked_add(1) {
+                Some(next) => cursor = next,
+                None => break,
+            }
+        }
+        Ok(logs)
+    }
+}
+
+/// Providers word this differently: geth's "query returned more than 10000 results",
+/// Alchemy's "block range is too wide", Infura's -32005 "limit exceeded".
+fn is_range_too_large(e: &Error) -> bool {
+    match e {
+        Error::JsonRpcError { code: -32005, .. } => true,
+        Error::JsonRpcError { message, .. } => {
+            let message = message.to_lowercase();
+            [
+                "block range",
+                "more than",
+                "too many",
+                "response size",
+                "range too large",
+            ]
+            .iter()
+            .any(|needle| message.contains(needle))
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rpc_error(code: i64, message: &str) -> Error {
+        Error::JsonRpcError {
+            code,
+            message: message.into(),
+            data: None,
+        }
+    }
+
+    #[test]
+    fn recognises_range_errors() {
+        assert!(is_range_too_large(&rpc_error(
+            -32000,
+            "query returned more than 10000 results"
+        )));
+        assert!(is_range_too_large(&rpc_error(
+            -32602,
+            "Log response size exceeded. You can make eth_getLogs requests with up to a 2K block range"
+        )));
+        assert!(is_range_too_large(&rpc_error(-32005, "limit exceeded")));
+        assert!(!is_range_too_large(&rpc_error(-32000, "header not found")));
+        assert!(!is_range_too_large(&Error::IoError));
+    }
+}
@@ -1,206 +1,256 @@
-use error_stack::{Report, ResultExt};
-use serde_json::json;
+use clap::{Parser, Subcommand};
+use error_stack::Report;
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::process::ExitCode;
 use web3_rpc::client::Error;
-use web3_rpc::model::{JsonRpcResult, Tag};
+use web3_rpc::model::{BlockId, JsonRpcResult, Log, TransactionRequest};
+use web3_rpc::types::{Address, H256, U256};
 use web3_rpc::web3::Web3;
 
-#[tokio::main]
-async fn main() -> Result<(), Report<Error>> {
-    let rpc = Web3::new("http://127.0.0.1:8545".to_string());
-    let r = rpc.web3_client_version().await?;
-    println!("{:?}", r);
-    let r = rpc.web3_sha3("0x68656c6c6f20776f726c64").await?;
-    println!("{:?}", r);
-
-    let r = rpc.net_version().await?;
-    println!("{:?}", r);
-
-    let r = rpc.net_listening().await?;
-    println!("{:?}", r);
-
-    let r = rpc.net_peer_count().await?;
-    println!("{:?}", r);
-
-    let r = rpc.eth_protocol_version().await?;
-    println!("{:?}", r);
-
-    let r = rpc.eth_syncing().await?;
-    println!("{:?}", r);
-
-    let r = rpc.eth_coinbase().await?;
-    println!("{:?}", r);
-
-    let r = rpc.eth_mining().await?;
-    println!("{:?}", r);
+const EXIT_RPC_ERROR: u8 = 1;
+const EXIT_FAILURE: u8 = 3;
+const EXIT_NOT_FOUND: u8 = 4;
+
+/// Query an Ethereum JSON-RPC endpoint.
+#[derive(Parser)]
+#[command(
+    version,
+    after_help = "Exit codes: 0 success, 1 JSON-RPC error, 2 usage error, 3 transport or decoding error, 4 not found"
+)]
+struct Cli {
+    /// Endpoint, e.g. http://127.0.0.1:8545
+    #[arg(long, env = "WEB3_RPC_URL")]
+    url: String,
+    /// Print the result as JSON
+    #[arg(long, global = true)]
+    json: bool,
+    #[command(subcommand)]
+    command: Command,
+}
 
-    let r = rpc.eth_hashrate().await?;
-    println!("{:?}", r);
+#[derive(Subcommand)]
+enum Command {
+    /// Block by tag, number or hash
+    Block {
+        #[arg(value_parser = parse_block)]
+        block: BlockId,
+    },
+    /// Transaction by hash
+    Tx { hash: H256 },
+    /// Transaction receipt by hash
+    Receipt { hash: H256 },
+    /// Balance in wei
+    Balance {
+        address: Address,
+        #[arg(long, value_parser = parse_block, default_value = "latest")]
+        block: BlockId,
+    },
+    /// Logs in a block range
+    Logs {
+        #[arg(long)]
+        address: Option<Address>,
+        #[arg(long, value_parser = parse_block, default_value = "latest")]
+        from: BlockId,
+        #[arg(long, value_parser = parse_block, default_value = "latest")]
+        to: BlockId,
+        /// Topic filter by position, may be repeated
+        #[arg(long)]
+        topic: Vec<H256>,
+    },
+    /// eth_call against a contract
+    Call {
+        #[arg(long)]
+        to: Address,
+        #[arg(long, default_value = "0x")]
+        data: String,
+        #[arg(long)]
+        from: Option<Address>,
+        #[arg(long, value_parser = parse_block, default_value = "latest")]
+        block: BlockId,
+    },
+    /// Broadcast a signed transaction
+    SendRaw { tx: String },
+}
 
-    let r = rpc.eth_gas_price().await?;
-    println!("{:?}", r);
+/// `latest`/`safe`/..., a decimal or 0x block number, or a 32-byte block hash.
+fn parse_block(s: &str) -> Result<BlockId, String> {
+    BlockId::try_from(s).map_err(|e| e.to_string())
+}
 
-    let r = rpc.eth_accounts().await?;
-    println!("{:?}", r);
+enum Outcome {
+    Found(Value, String),
+    NotFound,
+}
 
-    let r = rpc
-        .eth_get_balance(
-            "0x846c4dc9f4e2514206ef179eaa0bcfae007e37d2",
-            Some(Tag::Latest),
-        )
-        .await?;
-    println!("{:?}", r);
+fn found<T: Serialize>(result: &T, human: String) -> Outcome {
+    Outcome::Found(json!(result), human)
+}
 
-    let r = rpc
-        .eth_get_storage_at("0x295a70b2de5e3953354a6a8344e616ed314d7251", "0x0", None)
-        .await?;
-    println!("{:?}", r);
+fn value_of<T>(r: JsonRpcResult<T>) -> Option<T> {
+    r.result
+}
 
-    let r = rpc
-        .eth_get_transaction_count("0x846c4dc9f4e2514206ef179eaa0bcfae007e37d2", None)
-        .await?;
-    println!("{:?}", r);
+async fn run(rpc: &Web3, command: Command) -> Result<Outcome, Report<Error>> {
+    let outcome = match command {
+        Command::Block { block } => match value_of(rpc.eth_get_block(block, true).await?) {
+            Some(b) => {
+                let human = format!(
+                    "number     {}\nhash       {}\ntimestamp  {}\ngas used   {} / {}\ntxs        {}",
+                    b.number.map_or("pending".to_string(), |n| n.to_string()),
+                    b.hash.map_or("pending".to_string(), |h| h.to_string()),
+                    b.timestamp,
+                    b.gas_used,
+                    b.gas_limit,
+                    b.transactions.len()
+                );
+                found(&b, human)
+            }
+            None => Outcome::NotFound,
+        },
+        Command::Tx { hash } => match value_of(rpc.eth_get_transaction_by_hash(hash).await?) {
+            Some(tx) => {
+                let human = format!(
+                    "hash   {}\nblock  {}\nfrom   {}\nto     {}\nvalue  {}\nnonce  {}",
+                    tx.hash,
+                    tx.block_number.as_deref().unwrap_or("pending"),
+                    tx.from,
+                    tx.to
+                        .map_or("(contract creation)".to_string(), |a| a.to_string()),
+                    tx.value,
+                    tx.nonce
+                );
+                found(&tx, human)
+            }
+            None => Outcome::NotFound,
+        },
+        Command::Receipt { hash } => match value_of(rpc.eth_get_transaction_receipt(hash).await?) {
+            Some(receipt) => {
+                let status = match receipt.status {
+                    Some(true) => "success",
+                    Some(false) => "failed",
+                    None => "unknown",
+                };
+                let human = format!(
+                    "status    {}\nblock     {}\ngas used  {}\nlogs      {}",
+                    status,
+                    receipt.block_number,
+                    receipt.gas_used,
+                    receipt.logs.len()
+                );
+                found(&receipt, human)
+            }
+            None => Outcome::NotFound,
+        },
+        Command::Balance { address, block } => {
+            match value_of(rpc.eth_get_balance_at_block(address, block).await?) {
+                Some(balance) => {
+                    let wei: U256 = balance
+                        .parse()
+                        .map_err(|e| Report::new(Error::FailedToDeserialize).attach_printable(e))?;
+                    found(&wei, wei.to_string())
+                }
+                None => Outcome::NotFound,
+            }
+        }
+        Command::Logs {
+            address,
+            from,
+            to,
+            topic,
+        } => {
+            let mut filter = json!({ "fromBlock": from, "toBlock": to });
+            if let Some(address) = address {
+                filter["address"] = json!(address);
+            }
+            if !topic.is_empty() {
+                filter["topics"] = json!(topic);
+            }
+            let logs: Vec<Log> = rpc
+                .request("eth_getLogs", json!([filter]))
+                .await?
+                .result
+                .unwrap_or_default();
+            let human = logs
+                .iter()
+                .map(|l| {
+                    format!(
+                        "{} {} {} {}",
+                        l.block_number.as_deref().unwrap_or("pending"),
+                        l.address,
+                        l.topics.first().map_or(String::new(), |t| t.to_string()),
+                        l.data
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join("\n");
+            found(&logs, human)
+        }
+        Command::Call {
+            to,
+            data,
+            from,
+            block,
+        } => {
+            let mut request = TransactionRequest::new().to(to).data(data);
+            if let Some(from) = from {
+                request = request.from(from);
+            }
+            match value_of(rpc.eth_call_request_at_block(&request, block).await?) {
+                Some(output) => found(&output, output.clone()),
+                None => Outcome::NotFound,
+            }
+        }
+        Command::SendRaw { tx } => match value_of(rpc.eth_send_raw_transaction(&tx).await?) {
+            Some(hash) => found(&hash, hash.clone()),
+            None => Outcome::NotFound,
+        },
+    };
+    Ok(outcome)
+}
 
-    match rpc
-        .eth_get_block_transaction_count_by_hash(
-            "0xe812a49745d691961893d7cfd3902d78d710751bab872f12215ee23f27f3efa9",
-        )
-        .await
-    {
-        Ok(r) => {
-            println!("{:?}", r);
+#[tokio::main]
+async fn main() -> ExitCode {
+    let cli = Cli::parse();
+    let rpc = match Web3::try_new(cli.url) {
+        Ok(rpc) => rpc,
+        Err(e) => {
+            eprintln!("{:?}", e);
+            return ExitCode::from(EXIT_FAILURE);
+        }
+    };
+
+    match run(&rpc, cli.command).await {
+        Ok(Outcome::Found(value, human)) => {
+            if cli.json {
+                println!("{}", value);
+            } else {
+                println!("{}", human);
+            }
+            ExitCode::SUCCESS
+        }
+        Ok(Outcome::NotFound) => {
+            if cli.json {
+                println!("null");
+            } else {
+                eprintln!("not found");
+            }
+            ExitCode::from(EXIT_NOT_FOUND)
         }
         Err(e) => {
-            println!("{:?}", e);
+            match e.current_context() {
+                Error::JsonRpcError { code, message, .. } if cli.json => {
+                    println!(
+                        "{}",
+                        json!({ "error": { "code": code, "message": message } })
+                    );
+                }
+                _ => eprintln!("{:?}", e),
+            }
+            match e.current_context() {
+                Error::JsonRpcError { .. } => ExitCode::from(EXIT_RPC_ERROR),
+                _ => ExitCode::from(EXIT_FAILURE),
+            }
         }
     }
-
-    /*
-    let r = rpc
-        .eth_get_block_transaction_count_by_number("0xdf969d")
-        .await?;
-    println!("{:?}", r);
-
-    let r = rpc
-        .eth_get_uncle_count_by_block_hash(
-            "0xb903239f8543d04b5dc1ba6579132b143087c68db1b2168786408fcbce568238",
-        )
-        .await?;
-    println!("{:?}", r);
-
-    let r = rpc.eth_get_uncle_count_by_block_number("0xdf969d").await?;
-    println!("{:?}", r);*/
-
-    /*let r = rpc
-        .eth_get_code("0xc00e94cb662c3520282e6f5717214004a7f26888", None)
-        .await?;
-    println!("{:?}", r);*/
-
-    let r = rpc
-        .eth_sign("0x846c4dc9f4e2514206ef179eaa0bcfae007e37d2", "0x84")
-        .await?;
-    println!("{:?}", r);
-
-    let r = rpc
-        .eth_send_transaction(
-            "0x846c4dc9f4e2514206ef179eaa0bcfae007e37d2",
-            "0x4e910ef89c176119744977d90093858089454223",
-            "0x76c0",
-            "0x9184e72a000",
-            "0x9184e72a",
-            "0x",
-        )
-        .await?;
-    println!("{:?}", r);
-
-    let r = rpc
-        .eth_call(json!({"to": "0x846c4dc9f4e2514206ef179eaa0bcfae007e37d2", "data": "0x313ce567"}))
-        .await?;
-    println!("{:?}", r);
-
-    let r = rpc
-        .eth_estimate_gas(json!({
-            "from": "0x846c4dc9f4e2514206ef179eaa0bcfae007e37d2",
-            "to": "0x4e910ef89c176119744977d90093858089454223",
-            "gas": "0x76c0",
-            "gasPrice": "0x9184e72a000",
-            "data": "0x"
-        }))
-        .await?;
-    println!("{:?}", r);
-
-    /*let r = rpc
-        .eth_get_block_by_hash(
-            "0x48d9882ff85b8130b68a1bdb7596c79b48bf7153ac647ea7a91931bf5adcaa8d",
-            true,
-        )
-        .await?;
-    println!("{:#?}", r.result.unwrap().transactions[0]);
-    let r = rpc.eth_get_block_by_number("0xdfa620", true).await?;
-    println!("{:#?}", r.result.unwrap().transactions[0]);*/
-
-    /*
-    let r = rpc
-        .eth_get_transaction_by_hash(
-            "0xd56a94392b8442e728a3f174856c96263f86c4bbaa8860b2456b3215b984bb4b",
-        )
-        .await?;
-    println!("{:?}", r);*/
-
-    let rpc =
-        Web3::new("https://mainnet.infura.io/v3/ab0e57bf11aa4ac2aef7091710de352f".to_string());
-    let r = rpc.eth_block_number().await?;
-    println!("{:?}", r);
-
-    let r = rpc
-        .eth_get_transaction_receipt(
-            "0x9aba9e042034f025ebbe9fddde27c2c091afe6bb24d1c6f6418abbb3103519ee",
-        )
-        .await?;
-    println!("{:?}", r);
-
-    let r = rpc
-        .eth_get_transaction_by_block_hash_and_index(
-            "0x5cd7861dde444b29a5d362a015795c291b8a51ec7e6bbb6e31ade8cfc96716b9",
-            "0x0",
-        )
-        .await?;
-    println!("{:?}", r);
-
-    let r = rpc
-        .eth_get_transaction_by_block_number_and_index("0xdf969d", "0x0")
-        .await?;
-    println!("{:?}", r);
-
-    let r = rpc
-        .eth_get_uncle_by_block_hash_and_index(
-            "0x5cd7861dde444b29a5d362a015795c291b8a51ec7e6bbb6e31ade8cfc96716b9",
-            "0x0",
-        )
-        .await?;
-    println!("{:?}", r);
-
-    let r = rpc
-        .eth_get_uncle_by_block_number_and_index("0xdf969d", "0x0")
-        .await?;
-    println!("{:?}", r);
-
-    let r = rpc.eth_get_compilers().await?;
-    println!("{:?}", r);
-
-    // your self rest
-    let accounts = your_self_rest().await?;
-    println!("{:?}", accounts);
-
-    Ok(())
-}
-
-async fn your_self_rest() -> Result<JsonRpcResult<Vec<String>>, Report<Error>> {
-    // example
-    // eth_accounts
-    let rpc = Web3::new("http://127.0.0.1:8545".to_string());
-    let payload = json!({ "jsonrpc": "2.0", "method": "eth_accounts", "params": [], "id": "310" });
-    let result = rpc.client.post(payload).await?;
-    let r: JsonRpcResult<Vec<String>> = serde_json::from_str(result.as_str()).change_context(Error::FailedToDeserialize)?;
-
-    Ok(r)
 }
@@ -0,0 +1,190 @@
+//! Pending transactions from or to a set of addresses, as they enter the node's pool. Meant for
+//! noticing our own transactions stuck there, and replacing them.
+//!
+//! The client has no websocket transport, so the hashes come from a pending transaction filter
+//! (`eth_newPendingTransactionFilter`) polled over HTTP. Each new hash is looked up in batches;
+//! a transaction mined or dropped before the lookup is skipped.
+//!
+//! ```no_run
+//! # use std::collections::HashSet;
+//! # async fn run(web3: web3_rpc::web3::Web3, hot_wallet: web3_rpc::types::Address) -> Result<(), error_stack::Report<web3_rpc::client::Error>> {
+//! let mut pending = web3.watch_mempool_for(HashSet::from([hot_wallet]));
+//! loop {
+//!     let tx = pending.next().await?;
+//!     println!("{} nonce {}", tx.hash, tx.nonce);
+//! }
+//! # }
+//! ```
+
+use crate::client::{rpc_error, Error};
+use crate::model::{JsonRpcResult, Transaction};
+use crate::types::{Address, H256};
+use crate::web3::Web3;
+use error_stack::{Report, ResultExt};
+use serde_json::{json, Value};
+use std::collections::{HashSet, VecDeque};
+use std::time::Duration;
+
+/// How often the filter is polled unless set with [`MempoolWatch::poll_interval`].
+pub const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Hashes looked up per batch request.
+pub const DEFAULT_BATCH_TRANSACTIONS: usize = 100;
+
+/// Hashes remembered to skip when announced again; the oldest are forgotten first.
+const SEEN_CAPACITY: usize = 50_000;
+
+/// Returned by [`Web3::watch_mempool_for`]; call [`next`](Self::next) for each transaction.
+pub struct MempoolWatch<'a> {
+    web3: &'a Web3,
+    addresses: HashSet<Address>,
+    poll: Duration,
+    batch_size: usize,
+    /// Installed on the first poll, and again when the node forgets it.
+    filter: Option<String>,
+    seen: HashSet<H256>,
+    seen_order: VecDeque<H256>,
+    buffer: VecDeque<Transaction>,
+}
+
+impl Web3 {
+    /// Pending transactions sent by or to `addresses`, each once. See [`crate::mempool`].
+    pub fn watch_mempool_for(&self, addresses: HashSet<Address>) -> MempoolWatch<'_> {
+        MempoolWatch {
+            web3: self,
+            addresses,
+            poll: DEFAULT_POLL_INTERVAL,
+            batch_size: DEFAULT_BATCH_TRANSACTIONS,
+            filter: None,
+            seen: HashSet::new(),
+            seen_order: VecDeque::new(),
+            buffer: VecDeque::new(),
+        }
+    }
+}
+
+impl MempoolWatch<'_> {
+    pub fn poll_interval(mut self, poll: Duration) -> Self {
+        self.poll = poll;
+        self
+    }
+
+    pub fn batch_size(mut self, transactions: usize) -> Self {
+        self.batch_size = transactions.max(1);
+        self
+    }
+
+    /// The next matching transaction, waiting for one as long as it takes.
+    pub async fn next(&mut self) -> Result<Transaction, Report<Error>> {
+        loop {
+            if let Some(tx) = self.buffer.pop_front() {
+                return Ok(tx);
+            }
+            if !self.poll_once().await? {
+                tokio::time::sleep(self.poll).await;
+            }
+        }
+    }
+
+    /// Removes the filter from the node, which otherwise drops it after a few minutes unpolled.
+    pub async fn close(mut self) -> Result<(), Report<Error>> {
+        if let Some(filter) = self.filter.take() {
+            self.web3
+                .eth_uninstall_filter(&filter)
+                .await?
+                .into_result()
+                .map_err(rpc_error)?;
+        }
+        Ok(())
+    }
+
+    /// Reads the hashes announced since the last poll and looks up the new ones; whether any
+    /// were announced.
+    async fn poll_once(&mut self) -> Result<bool, Report<Error>> {
+        let filter = match &self.filter {
+            Some(filter) => filter.clone(),
+            None => {
+                let filter = self
+                    .web3
+                    .eth_new_pending_transaction_filter()
+                    .await?
+                    .into_result()
+                    .map_err(rpc_error)?;
+                self.filter = Some(filter.clone());
+                // the filter only collects from now on
+                return Ok(false);
+            }
+        };
+        let changes = match self.web3.eth_get_filter_changes(&filter).await {
+            Ok(r) => r.into_result().map_err(rpc_error)?,
+            Err(report) => match report.current_context() {
+                Error::JsonRpcError { message, .. } => {
+                    // expired, or the node restarted: hashes until the new filter are lost
+                    tracing::debug!(filter, message, "pending transaction filter gone");
+                    self.filter = None;
+                    return Ok(false);
+                }
+                _ => return Err(report),
+            },
+        };
+        if changes.is_empty() {
+            return Ok(false);
+        }
+
+        let mut hashes = Vec::with_capacity(changes.len());
+        let mut announced = HashSet::with_capacity(changes.len());
+        for change in changes {
+            let hash: H256 = serde_json::from_value(change)
+                .change_context(Error::UnexpectedResponseFormat)
+                .attach_printable("eth_getFilterChanges of a pending transaction filter")?;
+            if !self.seen.contains(&hash) && announced.insert(hash) {
+                hashes.push(hash);
+            }
+        }
+        for batch in hashes.chunks(self.batch_size) {
+            for tx in self.lookup(batch).await? {
+                self.remember(tx.hash);
+                let watched = self.addresses.contains(&tx.from)
+                    || tx.to.is_some_and(|to| self.addresses.contains(&to));
+                if watched {
+                    self.buffer.push_back(tx);
+                }
+            }
+        }
+        Ok(true)
+    }
+
+    /// The transactions still known to the node, in the order of `hashes`.
+    async fn lookup(&self, hashes: &[H256]) -> Result<Vec<Transaction>, Report<Error>> {
+        let payloads = hashes
+            .iter()
+            .map(|hash| {
+                json!({ "jsonrpc": "2.0", "method": "eth_getTransactionByHash", "params": [hash], "id": "326" })
+            })
+            .collect();
+        let responses: Vec<JsonRpcResult<Value>> = self.web3.client.call_batch(payloads).await?;
+        let mut txs = Vec::with_capacity(hashes.len());
+        for (response, hash) in responses.into_iter().zip(hashes) {
+            let Some(tx) = response.into_option().map_err(rpc_error)? else {
+                continue;
+            };
+            let tx: Transaction = serde_json::from_value(tx)
+                .change_context(Error::FailedToDeserialize)
+                .attach_printable_lazy(|| format!("transaction {}", hash))?;
+            txs.push(tx);
+        }
+        Ok(txs)
+    }
+
+    fn remember(&mut self, hash: H256) {
+        if !self.seen.insert(hash) {
+            return;
+        }
+        self.seen_order.push_back(hash);
+        if self.seen_order.len() > SEEN_CAPACITY {
+            if let Some(oldest) = self.seen_order.pop_front() {
+                self.seen.remove(&oldest);
+            }
+        }
+    }
+}
@@ -1,134 +1,2123 @@
-use serde::{Deserialize, Serialize};
+use crate::abi;
+use crate::hex;
+use crate::serde_helpers::{
+    deserialize_hex_bool_opt, deserialize_hex_u128_opt, deserialize_hex_u64,
+    deserialize_hex_u64_opt, serialize_hex_bool_opt, serialize_hex_u128_opt, serialize_hex_u64,
+    serialize_hex_u64_opt,
+};
+use crate::types::{Address, Bloom, H256, U256};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::{self, Display, Formatter};
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct JsonRpcResult<T> {
     pub jsonrpc: String,
+    #[serde(deserialize_with = "deserialize_id")]
     pub id: String,
     pub result: Option<T>,
+    /// Only set when the node answered with an error envelope that reached the caller.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<RpcErrorObject>,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+impl<T> JsonRpcResult<T> {
+    pub fn result(&self) -> Option<&T> {
+        self.result.as_ref()
+    }
+
+    /// Consumes the envelope. An envelope with neither `result` nor `error` is reported as an
+    /// internal error, so methods that legitimately return `null` (unknown block, pending
+    /// receipt) should be read through [`JsonRpcResult::result`] instead.
+    pub fn into_result(self) -> Result<T, RpcErrorObject> {
+        match (self.result, self.error) {
+            (_, Some(error)) => Err(error),
+            (Some(result), None) => Ok(result),
+            (None, None) => Err(RpcErrorObject {
+                message: "response has neither result nor error".to_string(),
+                code: -32603,
+                data: None,
+            }),
+        }
+    }
+
+    /// Consumes the envelope of a method that may answer `null`, which becomes `None`.
+    pub fn into_option(self) -> Result<Option<T>, RpcErrorObject> {
+        match self.error {
+            Some(error) => Err(error),
+            None => Ok(self.result),
+        }
+    }
+}
+
+/// Nodes echo the request id back, but some answer malformed requests with a numeric or
+/// `null` id.
+fn deserialize_id<'de, D>(deserializer: D) -> Result<String, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    match Value::deserialize(deserializer)? {
+        Value::String(s) => Ok(s),
+        Value::Number(n) => Ok(n.to_string()),
+        Value::Null => Ok(String::new()),
+        other => Err(de::Error::custom(format!("invalid id {}", other))),
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct JsonRpcErrorMessage<T> {
     pub message: String,
     pub code: i64,
     pub data: Option<T>,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+/// Error object of a JSON-RPC response.
+pub type RpcErrorObject = JsonRpcErrorMessage<Value>;
+
+impl JsonRpcErrorMessage<Value> {
+    /// Revert data of a failed eth_call/eth_estimateGas, if the node returned any.
+    ///
+    /// Geth puts the hex string straight into `data` and some clients nest it as
+    /// `data.data`. Nethermind prefixes it (`Reverted 0x...`), and Infura has been seen to
+    /// leave `data` out and put the hex in the message instead.
+    pub fn revert_data(&self) -> Option<&str> {
+        revert_data(&self.message, self.data.as_ref())
+    }
+
+    /// The message of an `Error(string)` revert, or `panic <code>` for a `Panic(uint256)`.
+    /// Without decodable data, the reason geth appends to `execution reverted: ` is used.
+    pub fn revert_reason(&self) -> Option<String> {
+        revert_reason(&self.message, self.data.as_ref())
+    }
+
+    /// Code of a `Panic(uint256)` revert, see [`abi::panic_code`].
+    pub fn panic_code(&self) -> Option<u64> {
+        abi::panic_code(&hex::parse_data(self.revert_data()?).ok()?)
+    }
+}
+
+pub(crate) fn revert_data<'a>(message: &'a str, data: Option<&'a Value>) -> Option<&'a str> {
+    let data = match data {
+        Some(Value::String(s)) => Some(s.as_str()),
+        Some(Value::Object(o)) => o.get("data").and_then(Value::as_str),
+        _ => None,
+    };
+    match data {
+        Some(data) if data.starts_with("0x") => Some(data),
+        Some(data) => embedded_hex(data),
+        // other errors quote addresses and hashes in the message
+        None if message.contains("revert") => embedded_hex(message),
+        None => None,
+    }
+}
+
+pub(crate) fn revert_reason(message: &str, data: Option<&Value>) -> Option<String> {
+    revert_data(message, data)
+        .and_then(|data| hex::parse_data(data).ok())
+        .and_then(|data| abi::revert_reason(&data))
+        .or_else(|| {
+            let reason = message.strip_prefix("execution reverted: ")?;
+            (embedded_hex(reason) != Some(reason)).then(|| reason.to_string())
+        })
+}
+
+/// The first `0x`-prefixed run of hex in `text` long enough to hold a selector.
+fn embedded_hex(text: &str) -> Option<&str> {
+    text.match_indices("0x").find_map(|(start, _)| {
+        let digits = text[start + 2..]
+            .bytes()
+            .take_while(u8::is_ascii_hexdigit)
+            .count();
+        (digits >= 8 && digits.is_multiple_of(2)).then(|| &text[start..start + 2 + digits])
+    })
+}
+
+impl Display for JsonRpcErrorMessage<Value> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{} (code {})", self.message, self.code)
+    }
+}
+
+impl std::error::Error for JsonRpcErrorMessage<Value> {}
+
+/// One entry of a GraphQL response's `errors`. `path` leads to the field that failed, as
+/// `["block", "transactions", 3, "status"]`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct GraphQlError {
+    pub message: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub path: Vec<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub extensions: Option<Value>,
+}
+
+impl Display for GraphQlError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let path: Vec<String> = self
+            .path
+            .iter()
+            .map(|segment| match segment {
+                Value::String(name) => name.clone(),
+                index => index.to_string(),
+            })
+            .collect();
+        match path.is_empty() {
+            true => f.write_str(&self.message),
+            false => write!(f, "{} (at {})", self.message, path.join(".")),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct JsonRpcError<T> {
     pub jsonrpc: String,
+    #[serde(deserialize_with = "deserialize_id")]
     pub id: String,
     pub error: JsonRpcErrorMessage<T>,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Tag {
     Latest,
     Earliest,
     Pending,
+    Safe,
+    Finalized,
+    Number(u64),
+}
+
+/// Why a string is not a [`Tag`] or [`BlockId`]. Each variant holds the rejected input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseTagError {
+    /// Neither a tag name nor a number.
+    Unknown(String),
+    /// Starts like a number but is not one, e.g. `0x`, `0xzz` or `-1`.
+    InvalidNumber(String),
+    /// A block number above `u64::MAX`.
+    Overflow(String),
+    /// 64 digits after `0x` that are not a block hash.
+    InvalidHash(String),
+}
+
+impl Display for ParseTagError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseTagError::Unknown(s) => write!(
+                f,
+                "unknown block tag `{}`, expected latest, earliest, pending, safe, finalized or a block number",
+                s
+            ),
+            ParseTagError::InvalidNumber(s) => write!(f, "invalid block number `{}`", s),
+            ParseTagError::Overflow(s) => write!(f, "block number `{}` does not fit in 64 bits", s),
+            ParseTagError::InvalidHash(s) => write!(f, "invalid block hash `{}`", s),
+        }
+    }
+}
+
+impl std::error::Error for ParseTagError {}
+
+/// What the JSON-RPC layer sends: the lowercase name, or a minimal hex number.
+impl Display for Tag {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Tag::Latest => f.write_str("latest"),
+            Tag::Earliest => f.write_str("earliest"),
+            Tag::Pending => f.write_str("pending"),
+            Tag::Safe => f.write_str("safe"),
+            Tag::Finalized => f.write_str("finalized"),
+            Tag::Number(n) => f.write_str(&hex::quantity((*n).into())),
+        }
+    }
 }
 
 impl From<Tag> for String {
     fn from(t: Tag) -> Self {
-        String::from(match t {
-            Tag::Latest => "latest",
-            Tag::Earliest => "earliest",
-            Tag::Pending => "pending",
-        })
+        t.to_string()
     }
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+/// Tag names in any case, and block numbers in `0x` hex or decimal. Surrounding whitespace
+/// is rejected rather than trimmed.
+impl TryFrom<&str> for Tag {
+    type Error = ParseTagError;
+
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        let tag = match s.to_ascii_lowercase().as_str() {
+            "latest" => Tag::Latest,
+            "earliest" => Tag::Earliest,
+            "pending" => Tag::Pending,
+            "safe" => Tag::Safe,
+            "finalized" => Tag::Finalized,
+            _ => return parse_block_number(s).map(Tag::Number),
+        };
+        Ok(tag)
+    }
+}
+
+fn parse_block_number(s: &str) -> Result<u64, ParseTagError> {
+    let (digits, radix) = match s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+        Some(digits) => (digits, 16),
+        None => (s, 10),
+    };
+    let looks_numeric = s.starts_with(|c: char| c.is_ascii_digit() || c == '-' || c == '+');
+    // from_str_radix alone would also accept a leading `+`
+    if digits.is_empty() || !digits.chars().all(|c| c.is_digit(radix)) {
+        return Err(if looks_numeric {
+            ParseTagError::InvalidNumber(s.to_string())
+        } else {
+            ParseTagError::Unknown(s.to_string())
+        });
+    }
+    u64::from_str_radix(digits, radix).map_err(|_| ParseTagError::Overflow(s.to_string()))
+}
+
+impl Serialize for Tag {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&String::from(*self))
+    }
+}
+
+impl<'de> Deserialize<'de> for Tag {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        Tag::try_from(s.as_str()).map_err(de::Error::custom)
+    }
+}
+
+/// Block selector accepted by block-addressed methods: a number, a hash, or a tag.
+#[derive(Debug, Clone, PartialEq)]
+pub enum BlockId {
+    Number(u64),
+    Hash(H256),
+    Tag(Tag),
+}
+
+impl BlockId {
+    /// EIP-1898 form used by state methods (eth_getBalance, eth_call, ...):
+    /// hashes are wrapped as `{"blockHash": ...}`, everything else is unchanged.
+    pub fn eip1898(&self) -> Value {
+        match self {
+            BlockId::Hash(hash) => json!({ "blockHash": hash }),
+            other => json!(other),
+        }
+    }
+}
+
+impl From<u64> for BlockId {
+    fn from(n: u64) -> Self {
+        BlockId::Number(n)
+    }
+}
+
+impl From<H256> for BlockId {
+    fn from(hash: H256) -> Self {
+        BlockId::Hash(hash)
+    }
+}
+
+impl From<Tag> for BlockId {
+    fn from(tag: Tag) -> Self {
+        BlockId::Tag(tag)
+    }
+}
+
+/// A block hash (`0x` and 64 hex digits), or anything [`Tag`] accepts. Numbers become
+/// [`BlockId::Number`], also when given in tag position.
+impl TryFrom<&str> for BlockId {
+    type Error = ParseTagError;
+
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        if s.len() == 66 && s.starts_with("0x") {
+            return s
+                .parse::<H256>()
+                .map(BlockId::Hash)
+                .map_err(|_| ParseTagError::InvalidHash(s.to_string()));
+        }
+        match Tag::try_from(s)? {
+            Tag::Number(n) => Ok(BlockId::Number(n)),
+            tag => Ok(BlockId::Tag(tag)),
+        }
+    }
+}
+
+/// The string the block is sent as; [`BlockId::try_from`] reads it back.
+impl Display for BlockId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            BlockId::Number(n) => f.write_str(&hex::quantity((*n).into())),
+            BlockId::Hash(hash) => Display::fmt(hash, f),
+            BlockId::Tag(tag) => Display::fmt(tag, f),
+        }
+    }
+}
+
+impl Serialize for BlockId {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match self {
+            BlockId::Number(n) => serializer.serialize_str(&hex::quantity((*n).into())),
+            BlockId::Hash(hash) => hash.serialize(serializer),
+            BlockId::Tag(tag) => tag.serialize(serializer),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct Block {
-    pub base_fee_per_gas: String,
+    /// Absent before London.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base_fee_per_gas: Option<U256>,
+    /// Present from Cancun on.
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_hex_u64_opt",
+        serialize_with = "serialize_hex_u64_opt"
+    )]
+    pub blob_gas_used: Option<u64>,
     pub difficulty: String,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_hex_u64_opt",
+        serialize_with = "serialize_hex_u64_opt"
+    )]
+    pub excess_blob_gas: Option<u64>,
     pub extra_data: String,
-    pub gas_limit: String,
-    pub gas_used: String,
-    pub hash: String,
-    pub logs_bloom: String,
-    pub miner: String,
-    pub mix_hash: String,
-    pub nonce: String,
-    pub number: String,
-    pub parent_hash: String,
-    pub receipts_root: String,
-    pub sha3uncles: Option<String>,
-    pub size: String,
-    pub state_root: String,
-    pub timestamp: String,
-    pub total_difficulty: String,
-    pub transactions: Vec<Transaction>,
-    pub transactions_root: String,
-    pub uncles: Vec<String>,
-}
-
-#[derive(Serialize, Deserialize, Debug, Clone)]
+    #[serde(
+        deserialize_with = "deserialize_hex_u64",
+        serialize_with = "serialize_hex_u64"
+    )]
+    pub gas_limit: u64,
+    #[serde(
+        deserialize_with = "deserialize_hex_u64",
+        serialize_with = "serialize_hex_u64"
+    )]
+    pub gas_used: u64,
+    /// `None` for the pending block.
+    pub hash: Option<H256>,
+    pub logs_bloom: Bloom,
+    /// `None` for the pending block on Geth.
+    pub miner: Option<Address>,
+    /// `prevRandao` since the merge; nodes still call it `mixHash`.
+    #[serde(alias = "prevRandao")]
+    pub mix_hash: H256,
+    /// `None` for the pending block.
+    pub nonce: Option<String>,
+    /// `None` for the pending block.
+    #[serde(
+        default,
+        deserialize_with = "deserialize_hex_u64_opt",
+        serialize_with = "serialize_hex_u64_opt"
+    )]
+    pub number: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent_beacon_block_root: Option<H256>,
+    pub parent_hash: H256,
+    pub receipts_root: H256,
+    /// EIP-7685 commitment to the block's execution layer requests, from Prague on.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub requests_hash: Option<H256>,
+    #[serde(
+        rename = "sha3Uncles",
+        alias = "sha3uncles",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub sha3uncles: Option<H256>,
+    #[serde(
+        deserialize_with = "deserialize_hex_u64",
+        serialize_with = "serialize_hex_u64"
+    )]
+    pub size: u64,
+    pub state_root: H256,
+    #[serde(
+        deserialize_with = "deserialize_hex_u64",
+        serialize_with = "serialize_hex_u64"
+    )]
+    pub timestamp: u64,
+    /// `None` for the pending block, and dropped entirely by reth and recent Geth.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub total_difficulty: Option<String>,
+    pub transactions: BlockTransactions,
+    pub transactions_root: H256,
+    #[serde(default)]
+    pub uncles: Vec<H256>,
+    /// Present from Shanghai on.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub withdrawals: Option<Vec<Withdrawal>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub withdrawals_root: Option<H256>,
+    /// Members this model does not know, such as a rollup's L1 coordinates, kept as sent and
+    /// serialized back.
+    #[serde(flatten)]
+    pub extra: HashMap<String, Value>,
+}
+
+impl Block {
+    /// The pending block is still being built and has no hash yet; check this before
+    /// relying on `hash`, `number` or `nonce`.
+    pub fn is_pending(&self) -> bool {
+        self.hash.is_none()
+    }
+}
+
+/// `transactions` of a block: hashes when it was fetched with `obj = false`, full objects
+/// with `obj = true`. An empty block parses as empty [`Hashes`](Self::Hashes).
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum BlockTransactions {
+    Hashes(Vec<H256>),
+    Full(Vec<Transaction>),
+}
+
+impl BlockTransactions {
+    /// Transaction hashes in block order, whichever form the block came in.
+    pub fn hashes(&self) -> Vec<H256> {
+        match self {
+            BlockTransactions::Hashes(hashes) => hashes.clone(),
+            BlockTransactions::Full(transactions) => {
+                transactions.iter().map(|tx| tx.hash).collect()
+            }
+        }
+    }
+
+    /// Full transactions, or `None` when the block only carries hashes. An empty block
+    /// gives an empty slice either way.
+    pub fn as_full(&self) -> Option<&[Transaction]> {
+        match self {
+            BlockTransactions::Full(transactions) => Some(transactions),
+            BlockTransactions::Hashes(hashes) if hashes.is_empty() => Some(&[]),
+            BlockTransactions::Hashes(_) => None,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        match self {
+            BlockTransactions::Hashes(hashes) => hashes.len(),
+            BlockTransactions::Full(transactions) => transactions.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl Default for BlockTransactions {
+    fn default() -> Self {
+        BlockTransactions::Hashes(Vec::new())
+    }
+}
+
+impl<'de> Deserialize<'de> for BlockTransactions {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        // each entry is read as whichever form it is in, rather than untagged, so a
+        // malformed transaction reports its own error instead of "did not match any variant"
+        struct Entries;
+
+        impl<'de> de::Visitor<'de> for Entries {
+            type Value = BlockTransactions;
+
+            fn expecting(&self, f: &mut Formatter) -> fmt::Result {
+                f.write_str("an array of transaction hashes or objects")
+            }
+
+            fn visit_seq<A: de::SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+                let mut hashes = Vec::new();
+                let mut full = Vec::new();
+                while let Some(entry) = seq.next_element::<Entry>()? {
+                    match entry {
+                        Entry::Hash(hash) if full.is_empty() => hashes.push(hash),
+                        Entry::Full(tx) if hashes.is_empty() => full.push(*tx),
+                        _ => {
+                            return Err(de::Error::custom(
+                                "block mixes transaction hashes and objects",
+                            ))
+                        }
+                    }
+                }
+                Ok(match full.is_empty() {
+                    true => BlockTransactions::Hashes(hashes),
+                    false => BlockTransactions::Full(full),
+                })
+            }
+        }
+
+        enum Entry {
+            Hash(H256),
+            Full(Box<Transaction>),
+        }
+
+        impl<'de> Deserialize<'de> for Entry {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                deserializer.deserialize_any(EntryVisitor)
+            }
+        }
+
+        struct EntryVisitor;
+
+        impl<'de> de::Visitor<'de> for EntryVisitor {
+            type Value = Entry;
+
+            fn expecting(&self, f: &mut Formatter) -> fmt::Result {
+                f.write_str("a transaction hash or object")
+            }
+
+            fn visit_str<E: de::Error>(self, v: &str) -> Result<Entry, E> {
+                v.parse().map(Entry::Hash).map_err(E::custom)
+            }
+
+            fn visit_map<A: de::MapAccess<'de>>(self, map: A) -> Result<Entry, A::Error> {
+                Transaction::deserialize(de::value::MapAccessDeserializer::new(map))
+                    .map(|tx| Entry::Full(Box::new(tx)))
+            }
+        }
+
+        deserializer.deserialize_seq(Entries)
+    }
+}
+
+/// Validator withdrawal (EIP-4895) credited in a block.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct Withdrawal {
+    #[serde(
+        deserialize_with = "deserialize_hex_u64",
+        serialize_with = "serialize_hex_u64"
+    )]
+    pub index: u64,
+    #[serde(
+        deserialize_with = "deserialize_hex_u64",
+        serialize_with = "serialize_hex_u64"
+    )]
+    pub validator_index: u64,
+    pub address: Address,
+    /// Amount in gwei.
+    #[serde(
+        deserialize_with = "deserialize_hex_u64",
+        serialize_with = "serialize_hex_u64"
+    )]
+    pub amount: u64,
+}
+
+impl Withdrawal {
+    pub fn amount_wei(&self) -> U256 {
+        U256::from(self.amount as u128 * 1_000_000_000)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct Transaction {
-    pub access_list: Option<Vec<AccessList>>,
-    pub block_hash: String,
-    pub block_number: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub access_list: Option<AccessList>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub blob_versioned_hashes: Option<Vec<H256>>,
+    /// `None` while the transaction is pending, as are `block_number` and `transaction_index`.
+    pub block_hash: Option<H256>,
+    pub block_number: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub chain_id: Option<String>,
-    pub from: String,
+    /// OP-stack deposits only, from Canyon on.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deposit_receipt_version: Option<String>,
+    pub from: Address,
     pub gas: String,
-    pub gas_price: String,
-    pub hash: String,
+    /// Some clients omit this for EIP-1559 transactions, others report the effective price.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gas_price: Option<String>,
+    pub hash: H256,
     pub input: String,
+    /// OP-stack deposits only.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub is_system_tx: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_fee_per_blob_gas: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub max_fee_per_gas: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub max_priority_fee_per_gas: Option<String>,
+    /// OP-stack deposits only: ETH minted on L2, in wei.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mint: Option<String>,
     pub nonce: String,
     #[serde(default)]
     pub r: String,
     #[serde(default)]
     pub s: String,
-    pub to: Option<String>,
-    pub transaction_index: String,
-    #[serde(rename = "type")]
-    pub transaction_type: String,
+    /// OP-stack deposits only: hash identifying the L1 origin of the deposit.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source_hash: Option<H256>,
+    pub to: Option<Address>,
+    pub transaction_index: Option<String>,
+    #[serde(rename = "type", default, skip_serializing_if = "Option::is_none")]
+    pub transaction_type: Option<String>,
     #[serde(default)]
     pub v: String,
     pub value: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub y_parity: Option<String>,
+    /// Members this model does not know, as [`Block::extra`].
+    #[serde(flatten)]
+    pub extra: HashMap<String, Value>,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TxType {
+    Legacy,
+    /// EIP-2930
+    AccessList,
+    /// EIP-1559
+    DynamicFee,
+    /// EIP-4844
+    Blob,
+    /// OP-stack L1-to-L2 deposit (`0x7e`)
+    Deposit,
+    Other(u64),
+}
+
+impl TxType {
+    /// Parses a `type` field; transactions and receipts without one are legacy, and `None`
+    /// means the field is not a hex quantity.
+    fn from_field(field: Option<&str>) -> Option<Self> {
+        let Some(field) = field else {
+            return Some(TxType::Legacy);
+        };
+        let n = field
+            .strip_prefix("0x")
+            .and_then(|t| u64::from_str_radix(t, 16).ok())?;
+        Some(Self::from_number(n))
+    }
+
+    pub(crate) fn from_number(n: u64) -> Self {
+        match n {
+            0 => TxType::Legacy,
+            1 => TxType::AccessList,
+            2 => TxType::DynamicFee,
+            3 => TxType::Blob,
+            0x7e => TxType::Deposit,
+            n => TxType::Other(n),
+        }
+    }
+}
+
+impl Transaction {
+    /// Envelope type; transactions without a `type` field are legacy, and `None` means the
+    /// node sent a `type` that is not a hex quantity. Unknown types are [`TxType::Other`].
+    pub fn tx_type(&self) -> Option<TxType> {
+        TxType::from_field(self.transaction_type.as_deref())
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 #[serde(rename_all = "camelCase")]
-pub struct AccessList {
-    pub address: String,
-    pub storage_keys: Vec<String>,
+pub struct AccessListItem {
+    pub address: Address,
+    pub storage_keys: Vec<H256>,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+/// EIP-2930 access list.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(transparent)]
+pub struct AccessList(pub Vec<AccessListItem>);
+
+impl AccessList {
+    /// Intrinsic gas added by attaching this list: 2400 per address, 1900 per storage key.
+    pub fn intrinsic_gas(&self) -> u64 {
+        self.0
+            .iter()
+            .map(|item| 2400 + 1900 * item.storage_keys.len() as u64)
+            .sum()
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, AccessListItem> {
+        self.0.iter()
+    }
+}
+
+impl From<Vec<AccessListItem>> for AccessList {
+    fn from(items: Vec<AccessListItem>) -> Self {
+        AccessList(items)
+    }
+}
+
+/// Result of eth_createAccessList.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct AccessListResult {
+    pub access_list: AccessList,
+    pub gas_used: U256,
+    /// Set when the simulated call reverted.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Per-account override for eth_call and debug_traceCall.
+///
+/// `state` replaces the whole storage of the account, `state_diff` only patches the given
+/// slots; nodes reject overrides that set both.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountOverride {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub balance: Option<U256>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nonce: Option<U256>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub code: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub state: Option<BTreeMap<H256, H256>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub state_diff: Option<BTreeMap<H256, H256>>,
+}
+
+impl AccountOverride {
+    pub fn is_empty(&self) -> bool {
+        self.balance.is_none()
+            && self.nonce.is_none()
+            && self.code.is_none()
+            && self.state.is_none()
+            && self.state_diff.is_none()
+    }
+}
+
+/// State override set, keyed by account. Empty entries are left out of the payload.
+#[derive(Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(transparent)]
+pub struct StateOverride(pub BTreeMap<Address, AccountOverride>);
+
+impl StateOverride {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn account(&mut self, address: Address) -> &mut AccountOverride {
+        self.0.entry(address).or_default()
+    }
+
+    pub fn balance(mut self, address: Address, balance: impl Into<U256>) -> Self {
+        self.account(address).balance = Some(balance.into());
+        self
+    }
+
+    pub fn nonce(mut self, address: Address, nonce: impl Into<U256>) -> Self {
+        self.account(address).nonce = Some(nonce.into());
+        self
+    }
+
+    pub fn code(mut self, address: Address, code: impl Into<String>) -> Self {
+        self.account(address).code = Some(code.into());
+        self
+    }
+
+    pub fn state(mut self, address: Address, slot: H256, value: H256) -> Self {
+        self.account(address)
+            .state
+            .get_or_insert_with(BTreeMap::new)
+            .insert(slot, value);
+        self
+    }
+
+    pub fn state_diff(mut self, address: Address, slot: H256, value: H256) -> Self {
+        self.account(address)
+            .state_diff
+            .get_or_insert_with(BTreeMap::new)
+            .insert(slot, value);
+        self
+    }
+
+    /// True when no entry would be serialized.
+    pub fn is_empty(&self) -> bool {
+        self.0.values().all(AccountOverride::is_empty)
+    }
+}
+
+impl Serialize for StateOverride {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.collect_map(self.0.iter().filter(|(_, account)| !account.is_empty()))
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct Receipt {
-    pub block_hash: String,
+    /// Blob gas price paid, type-3 transactions only.
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_hex_u128_opt",
+        serialize_with = "serialize_hex_u128_opt"
+    )]
+    pub blob_gas_price: Option<u128>,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_hex_u64_opt",
+        serialize_with = "serialize_hex_u64_opt"
+    )]
+    pub blob_gas_used: Option<u64>,
+    pub block_hash: H256,
     pub block_number: String,
-    pub contract_address: Option<String>,
-    pub cumulative_gas_used: String,
-    pub effective_gas_price: String,
-    pub from: String,
-    pub gas_used: String,
-    pub logs: Vec<ReceiptLog>,
-    pub logs_bloom: String,
-    pub status: String,
-    pub to: Option<String>,
-    pub transaction_hash: String,
+    /// Set for contract creations, in which case `to` is `None`.
+    pub contract_address: Option<Address>,
+    #[serde(
+        deserialize_with = "deserialize_hex_u64",
+        serialize_with = "serialize_hex_u64"
+    )]
+    pub cumulative_gas_used: u64,
+    /// Missing on legacy transactions from older Besu releases.
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_hex_u128_opt",
+        serialize_with = "serialize_hex_u128_opt"
+    )]
+    pub effective_gas_price: Option<u128>,
+    pub from: Address,
+    #[serde(
+        deserialize_with = "deserialize_hex_u64",
+        serialize_with = "serialize_hex_u64"
+    )]
+    pub gas_used: u64,
+    pub logs: Vec<Log>,
+    pub logs_bloom: Bloom,
+    /// Post-transaction state root, reported instead of `status` before Byzantium.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub root: Option<H256>,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_hex_bool_opt",
+        serialize_with = "serialize_hex_bool_opt"
+    )]
+    pub status: Option<bool>,
+    pub to: Option<Address>,
+    pub transaction_hash: H256,
     pub transaction_index: String,
-    #[serde(rename = "type")]
-    pub receipt_type: String,
+    #[serde(rename = "type", default, skip_serializing_if = "Option::is_none")]
+    pub transaction_type: Option<String>,
+    /// Members this model does not know, as [`Block::extra`]; OP-stack chains report their L1
+    /// fee here (`l1Fee`, `l1GasUsed`, ...).
+    #[serde(flatten)]
+    pub extra: HashMap<String, Value>,
+}
+
+impl Receipt {
+    /// Whether the transaction executed without reverting, or `None` for pre-Byzantium
+    /// receipts, which carry only a state `root` and no way to tell.
+    pub fn succeeded(&self) -> Option<bool> {
+        self.status
+    }
+
+    /// Envelope type of the transaction, as [`Transaction::tx_type`].
+    pub fn tx_type(&self) -> Option<TxType> {
+        TxType::from_field(self.transaction_type.as_deref())
+    }
+
+    /// `effective_gas_price * gas_used`, or `None` when the node did not report the price.
+    pub fn fee_paid_wei(&self) -> Option<u128> {
+        self.effective_gas_price?
+            .checked_mul(u128::from(self.gas_used))
+    }
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+/// Event log, as found in receipts and returned by eth_getLogs.
+///
+/// Block and transaction coordinates are `None` for pending logs.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 #[serde(rename_all = "camelCase")]
-pub struct ReceiptLog {
-    pub address: String,
-    pub block_hash: String,
-    pub block_number: String,
+pub struct Log {
+    pub address: Address,
+    pub block_hash: Option<H256>,
+    pub block_number: Option<String>,
     pub data: String,
-    pub log_index: String,
+    pub log_index: Option<String>,
+    #[serde(default)]
     pub removed: bool,
-    pub topics: Vec<String>,
-    pub transaction_hash: String,
-    pub transaction_index: String,
+    pub topics: Vec<H256>,
+    pub transaction_hash: Option<H256>,
+    pub transaction_index: Option<String>,
+}
+
+#[deprecated(note = "renamed to `Log`")]
+pub type ReceiptLog = Log;
+
+/// eth_getLogs filter. Unset fields are omitted; a `None` topic position matches anything.
+#[derive(Serialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct LogFilter {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub from_block: Option<Tag>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub to_block: Option<Tag>,
+    /// Mutually exclusive with the block range.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub block_hash: Option<H256>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub address: Vec<Address>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub topics: Vec<Option<Vec<H256>>>,
+}
+
+impl LogFilter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn from_block(mut self, from_block: Tag) -> Self {
+        self.from_block = Some(from_block);
+        self
+    }
+
+    pub fn to_block(mut self, to_block: Tag) -> Self {
+        self.to_block = Some(to_block);
+        self
+    }
+
+    /// Inclusive range `from..=to`.
+    pub fn blocks(self, from: u64, to: u64) -> Self {
+        self.from_block(Tag::Number(from)).to_block(Tag::Number(to))
+    }
+
+    pub fn block_hash(mut self, block_hash: H256) -> Self {
+        self.block_hash = Some(block_hash);
+        self
+    }
+
+    /// Adds an emitting address; logs from any of the added addresses match.
+    pub fn address(mut self, address: Address) -> Self {
+        self.address.push(address);
+        self
+    }
+
+    /// Adds `topic` as an alternative at `position` (0 is the event signature).
+    pub fn topic(mut self, position: usize, topic: H256) -> Self {
+        if self.topics.len() <= position {
+            self.topics.resize(position + 1, None);
+        }
+        self.topics[position]
+            .get_or_insert_with(Vec::new)
+            .push(topic);
+        self
+    }
+}
+
+/// Call/transaction object shared by eth_call, eth_estimateGas, eth_sendTransaction
+/// and eth_signTransaction. Unset fields are omitted from the JSON, nodes reject nulls.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TransactionRequest {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub from: Option<Address>,
+    /// `None` for contract creation.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub to: Option<Address>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gas: Option<U256>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gas_price: Option<U256>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_fee_per_gas: Option<U256>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_priority_fee_per_gas: Option<U256>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value: Option<U256>,
+    #[serde(skip_serializing_if = "Option::is_none", alias = "input")]
+    pub data: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub nonce: Option<U256>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub access_list: Option<AccessList>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub chain_id: Option<U256>,
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    pub transaction_type: Option<U256>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransactionRequestError {
+    /// No `to` and no `data`: a contract creation needs init code.
+    MissingCreationCode,
+    /// Legacy `gasPrice` combined with EIP-1559 fee fields.
+    MixedFeeFields,
+    /// eth_sendTransaction and eth_signTransaction need a sender.
+    MissingFrom,
+}
+
+impl Display for TransactionRequestError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            TransactionRequestError::MissingCreationCode => {
+                f.write_str("contract creation (no `to`) requires `data`")
+            }
+            TransactionRequestError::MixedFeeFields => f.write_str(
+                "`gasPrice` cannot be combined with `maxFeePerGas`/`maxPriorityFeePerGas`",
+            ),
+            TransactionRequestError::MissingFrom => f.write_str("`from` is required"),
+        }
+    }
+}
+
+impl std::error::Error for TransactionRequestError {}
+
+impl TransactionRequest {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn from(mut self, from: Address) -> Self {
+        self.from = Some(from);
+        self
+    }
+
+    pub fn to(mut self, to: Address) -> Self {
+        self.to = Some(to);
+        self
+    }
+
+    pub fn gas(mut self, gas: impl Into<U256>) -> Self {
+        self.gas = Some(gas.into());
+        self
+    }
+
+    pub fn gas_price(mut self, gas_price: impl Into<U256>) -> Self {
+        self.gas_price = Some(gas_price.into());
+        self
+    }
+
+    pub fn max_fee_per_gas(mut self, max_fee_per_gas: impl Into<U256>) -> Self {
+        self.max_fee_per_gas = Some(max_fee_per_gas.into());
+        self
+    }
+
+    pub fn max_priority_fee_per_gas(mut self, max_priority_fee_per_gas: impl Into<U256>) -> Self {
+        self.max_priority_fee_per_gas = Some(max_priority_fee_per_gas.into());
+        self
+    }
+
+    pub fn value(mut self, value: impl Into<U256>) -> Self {
+        self.value = Some(value.into());
+        self
+    }
+
+    pub fn data(mut self, data: impl Into<String>) -> Self {
+        self.data = Some(data.into());
+        self
+    }
+
+    pub fn nonce(mut self, nonce: impl Into<U256>) -> Self {
+        self.nonce = Some(nonce.into());
+        self
+    }
+
+    pub fn access_list(mut self, access_list: impl Into<AccessList>) -> Self {
+        self.access_list = Some(access_list.into());
+        self
+    }
+
+    pub fn chain_id(mut self, chain_id: impl Into<U256>) -> Self {
+        self.chain_id = Some(chain_id.into());
+        self
+    }
+
+    pub fn transaction_type(mut self, transaction_type: impl Into<U256>) -> Self {
+        self.transaction_type = Some(transaction_type.into());
+        self
+    }
+
+    pub fn is_contract_creation(&self) -> bool {
+        self.to.is_none()
+    }
+
+    pub fn validate(&self) -> Result<(), TransactionRequestError> {
+        let has_code = self
+            .data
+            .as_deref()
+            .is_some_and(|d| !d.is_empty() && d != "0x");
+        if self.to.is_none() && !has_code {
+            return Err(TransactionRequestError::MissingCreationCode);
+        }
+        if self.gas_price.is_some()
+            && (self.max_fee_per_gas.is_some() || self.max_priority_fee_per_gas.is_some())
+        {
+            return Err(TransactionRequestError::MixedFeeFields);
+        }
+        Ok(())
+    }
+
+    /// [`validate`](Self::validate) plus the `from` requirement of send/sign.
+    pub fn validate_send(&self) -> Result<(), TransactionRequestError> {
+        if self.from.is_none() {
+            return Err(TransactionRequestError::MissingFrom);
+        }
+        self.validate()
+    }
+}
+
+/// Result of eth_syncing: `false` when the node is in sync, a progress object otherwise.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SyncStatus {
+    NotSyncing,
+    Syncing(SyncProgress),
+}
+
+impl SyncStatus {
+    pub fn is_synced(&self) -> bool {
+        match self {
+            SyncStatus::NotSyncing => true,
+            SyncStatus::Syncing(progress) => progress.is_synced(),
+        }
+    }
+
+    pub fn blocks_remaining(&self) -> u64 {
+        match self {
+            SyncStatus::NotSyncing => 0,
+            SyncStatus::Syncing(progress) => progress.blocks_remaining(),
+        }
+    }
+
+    pub fn progress(&self) -> Option<&SyncProgress> {
+        match self {
+            SyncStatus::NotSyncing => None,
+            SyncStatus::Syncing(progress) => Some(progress),
+        }
+    }
+}
+
+impl Serialize for SyncStatus {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match self {
+            SyncStatus::NotSyncing => serializer.serialize_bool(false),
+            SyncStatus::Syncing(progress) => progress.serialize(serializer),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for SyncStatus {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        match Value::deserialize(deserializer)? {
+            Value::Bool(false) => Ok(SyncStatus::NotSyncing),
+            v @ Value::Object(_) => serde_json::from_value(v)
+                .map(SyncStatus::Syncing)
+                .map_err(de::Error::custom),
+            other => Err(de::Error::custom(format!(
+                "expected `false` or a sync progress object, got {}",
+                other
+            ))),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncProgress {
+    #[serde(
+        deserialize_with = "deserialize_hex_u64",
+        serialize_with = "serialize_hex_u64"
+    )]
+    pub starting_block: u64,
+    #[serde(
+        deserialize_with = "deserialize_hex_u64",
+        serialize_with = "serialize_hex_u64"
+    )]
+    pub current_block: u64,
+    #[serde(
+        deserialize_with = "deserialize_hex_u64",
+        serialize_with = "serialize_hex_u64"
+    )]
+    pub highest_block: u64,
+    /// Client-specific counters (Geth's `healedBytecodes`, `syncedAccounts`, ...).
+    #[serde(flatten)]
+    pub extra: HashMap<String, Value>,
+}
+
+impl SyncProgress {
+    pub fn is_synced(&self) -> bool {
+        self.current_block >= self.highest_block
+    }
+
+    pub fn blocks_remaining(&self) -> u64 {
+        self.highest_block.saturating_sub(self.current_block)
+    }
+
+    /// Fraction of the sync range covered so far, in `0.0..=1.0`.
+    pub fn progress_ratio(&self) -> f64 {
+        let total = self.highest_block.saturating_sub(self.starting_block);
+        if total == 0 {
+            return 1.0;
+        }
+        let done = self.current_block.saturating_sub(self.starting_block);
+        (done as f64 / total as f64).min(1.0)
+    }
+}
+
+/// Result of eth_feeHistory. Per-block arrays cover `oldest_block` onwards; the fee
+/// arrays carry one extra entry for the block after the newest one.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct FeeHistory {
+    #[serde(
+        deserialize_with = "deserialize_hex_u64",
+        serialize_with = "serialize_hex_u64"
+    )]
+    pub oldest_block: u64,
+    pub base_fee_per_gas: Vec<U256>,
+    #[serde(serialize_with = "serialize_ratios")]
+    pub gas_used_ratio: Vec<f64>,
+    /// Post-Cancun nodes only.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base_fee_per_blob_gas: Option<Vec<U256>>,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "serialize_ratios_opt"
+    )]
+    pub blob_gas_used_ratio: Option<Vec<f64>>,
+    /// Priority fees at the requested percentiles, absent when none were requested.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reward: Option<Vec<Vec<U256>>>,
+}
+
+/// Nodes write empty and full blocks as `0` and `1`; keep that so the output matches.
+fn serialize_ratios<S>(ratios: &[f64], serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    use serde::ser::SerializeSeq;
+    let mut seq = serializer.serialize_seq(Some(ratios.len()))?;
+    for ratio in ratios {
+        if *ratio == 0.0 || *ratio == 1.0 {
+            seq.serialize_element(&(*ratio as u64))?;
+        } else {
+            seq.serialize_element(ratio)?;
+        }
+    }
+    seq.end()
+}
+
+fn serialize_ratios_opt<S>(ratios: &Option<Vec<f64>>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    match ratios {
+        Some(ratios) => serialize_ratios(ratios, serializer),
+        None => serializer.serialize_none(),
+    }
+}
+
+/// Result of eth_getProof (EIP-1186). Proof nodes are RLP-encoded trie nodes as hex.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountProof {
+    pub address: Address,
+    pub account_proof: Vec<String>,
+    pub balance: U256,
+    pub code_hash: H256,
+    pub nonce: U256,
+    pub storage_hash: H256,
+    pub storage_proof: Vec<StorageProof>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct StorageProof {
+    /// The slot as requested; nodes echo it back unpadded.
+    pub key: String,
+    pub value: U256,
+    pub proof: Vec<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn block_id_serialization() {
+        let hash: H256 = "0xe812a49745d691961893d7cfd3902d78d710751bab872f12215ee23f27f3efa9"
+            .parse()
+            .unwrap();
+        assert_eq!(json!(BlockId::from(0u64)), json!("0x0"));
+        assert_eq!(json!(BlockId::from(14_653_085u64)), json!("0xdf969d"));
+        assert_eq!(json!(BlockId::from(Tag::Latest)), json!("latest"));
+        assert_eq!(json!(BlockId::from(hash)), json!(hash.to_hex()));
+
+        assert_eq!(BlockId::from(Tag::Pending).eip1898(), json!("pending"));
+        assert_eq!(BlockId::from(16u64).eip1898(), json!("0x10"));
+        assert_eq!(
+            BlockId::from(hash).eip1898(),
+            json!({ "blockHash": hash.to_hex() })
+        );
+    }
+
+    #[test]
+    fn tag_variants_in_payload() {
+        let address = "0x846c4dc9f4e2514206ef179eaa0bcfae007e37d2";
+        let cases = [
+            (Tag::Latest, "latest"),
+            (Tag::Earliest, "earliest"),
+            (Tag::Pending, "pending"),
+            (Tag::Safe, "safe"),
+            (Tag::Finalized, "finalized"),
+            (Tag::Number(0), "0x0"),
+            (Tag::Number(255), "0xff"),
+            (Tag::Number(u64::MAX), "0xffffffffffffffff"),
+        ];
+        for (tag, expected) in cases {
+            let payload = json!({ "jsonrpc": "2.0", "method": "eth_getBalance", "params": [address, BlockId::from(tag).eip1898()], "id": "311" });
+            assert_eq!(payload["params"][1], json!(expected));
+            assert_eq!(String::from(tag), expected);
+            assert_eq!(tag.to_string(), expected);
+            assert_eq!(Tag::try_from(expected), Ok(tag));
+            assert_eq!(serde_json::from_value::<Tag>(json!(expected)).unwrap(), tag);
+        }
+    }
+
+    #[test]
+    fn tag_parsing() {
+        let accepted = [
+            ("latest", Tag::Latest),
+            ("Latest", Tag::Latest),
+            ("EARLIEST", Tag::Earliest),
+            ("Pending", Tag::Pending),
+            ("sAfE", Tag::Safe),
+            ("Finalized", Tag::Finalized),
+            ("0", Tag::Number(0)),
+            ("12", Tag::Number(12)),
+            ("007", Tag::Number(7)),
+            ("18446744073709551615", Tag::Number(u64::MAX)),
+            ("0x10", Tag::Number(16)),
+            ("0X10", Tag::Number(16)),
+            ("0x00ff", Tag::Number(255)),
+            ("0xFFFFFFFFFFFFFFFF", Tag::Number(u64::MAX)),
+        ];
+        for (input, tag) in accepted {
+            assert_eq!(Tag::try_from(input), Ok(tag), "{}", input);
+            assert_eq!(
+                BlockId::try_from(input).map(|b| b.to_string()),
+                Ok(tag.to_string())
+            );
+        }
+
+        let unknown = |s: &str| ParseTagError::Unknown(s.to_string());
+        let invalid = |s: &str| ParseTagError::InvalidNumber(s.to_string());
+        let overflow = |s: &str| ParseTagError::Overflow(s.to_string());
+        let rejected = [
+            ("", unknown("")),
+            ("newest", unknown("newest")),
+            ("latest ", unknown("latest ")),
+            (" 12", unknown(" 12")),
+            ("0x", invalid("0x")),
+            ("0xzz", invalid("0xzz")),
+            ("0x+1", invalid("0x+1")),
+            ("+1", invalid("+1")),
+            ("-1", invalid("-1")),
+            ("1.5", invalid("1.5")),
+            ("12a", invalid("12a")),
+            ("18446744073709551616", overflow("18446744073709551616")),
+            ("0x10000000000000000", overflow("0x10000000000000000")),
+        ];
+        for (input, error) in rejected {
+            assert_eq!(Tag::try_from(input), Err(error.clone()), "{}", input);
+            assert_eq!(BlockId::try_from(input), Err(error));
+        }
+        assert_eq!(
+            Tag::try_from("newest").unwrap_err().to_string(),
+            "unknown block tag `newest`, expected latest, earliest, pending, safe, finalized or a block number"
+        );
+        assert_eq!(
+            serde_json::from_value::<Tag>(json!("0xzz"))
+                .unwrap_err()
+                .to_string(),
+            "invalid block number `0xzz`"
+        );
+    }
+
+    #[test]
+    fn block_id_display_round_trips() {
+        let hash: H256 = "0xe812a49745d691961893d7cfd3902d78d710751bab872f12215ee23f27f3efa9"
+            .parse()
+            .unwrap();
+        let ids = [
+            BlockId::Number(0),
+            BlockId::Number(14_653_085),
+            BlockId::Number(u64::MAX),
+            BlockId::Hash(hash),
+            BlockId::Tag(Tag::Latest),
+            BlockId::Tag(Tag::Earliest),
+            BlockId::Tag(Tag::Pending),
+            BlockId::Tag(Tag::Safe),
+            BlockId::Tag(Tag::Finalized),
+        ];
+        for id in ids {
+            let shown = id.to_string();
+            assert_eq!(json!(id), json!(shown));
+            assert_eq!(BlockId::try_from(shown.as_str()), Ok(id));
+        }
+        // the same block number, however it was built
+        assert_eq!(BlockId::Tag(Tag::Number(16)).to_string(), "0x10");
+        assert_eq!(BlockId::try_from("0x10"), Ok(BlockId::Number(16)));
+
+        let upper = hash.to_hex().to_uppercase().replacen("0X", "0x", 1);
+        assert_eq!(BlockId::try_from(upper.as_str()), Ok(BlockId::Hash(hash)));
+        let bad = format!("0x{}", "g".repeat(64));
+        assert_eq!(
+            BlockId::try_from(bad.as_str()),
+            Err(ParseTagError::InvalidHash(bad.clone()))
+        );
+    }
+
+    #[test]
+    fn block_fixtures_parse() {
+        let pre_london: Block =
+            serde_json::from_str(include_str!("../tests/fixtures/block_pre_london.json")).unwrap();
+        assert_eq!(pre_london.number, Some(12_000_000));
+        assert_eq!(pre_london.base_fee_per_gas, None);
+        assert_eq!(pre_london.timestamp, 1_615_234_816);
+        assert_eq!(pre_london.gas_limit, 12_481_448);
+        assert_eq!(pre_london.gas_used, 12_474_777);
+        assert_eq!(pre_london.size, 59_379);
+
+        let post_merge: Block =
+            serde_json::from_str(include_str!("../tests/fixtures/block_post_merge.json")).unwrap();
+        assert_eq!(post_merge.number, Some(17_000_000));
+        assert_eq!(
+            post_merge.base_fee_per_gas,
+            Some(U256::from(0x3c27d9a4du64))
+        );
+        assert_eq!(post_merge.difficulty, "0x0");
+        assert_eq!(post_merge.transactions.len(), 1);
+        let full = post_merge.transactions.as_full().unwrap();
+        assert_eq!(post_merge.transactions.hashes(), vec![full[0].hash]);
+
+        // the same block fetched with obj = false
+        let hashes: Block =
+            serde_json::from_str(include_str!("../tests/fixtures/block_tx_hashes.json")).unwrap();
+        assert!(hashes.transactions.as_full().is_none());
+        assert_eq!(
+            hashes.transactions.hashes(),
+            post_merge.transactions.hashes()
+        );
+        let value = serde_json::to_value(&hashes).unwrap();
+        assert_eq!(value["transactions"][0], json!(full[0].hash));
+
+        // an empty block reads the same both ways
+        assert_eq!(pre_london.transactions, BlockTransactions::default());
+        assert_eq!(pre_london.transactions.as_full(), Some(&[][..]));
+        assert!(pre_london.transactions.hashes().is_empty());
+        assert_eq!(
+            serde_json::to_value(&pre_london).unwrap()["transactions"],
+            json!([])
+        );
+
+        // a broken transaction object reports its own problem
+        let mut value = serde_json::to_value(&post_merge).unwrap();
+        value["transactions"][0]["hash"] = json!("0x12");
+        let error = serde_json::from_value::<Block>(value)
+            .unwrap_err()
+            .to_string();
+        assert!(!error.contains("did not match any variant"), "{}", error);
+        let mut value = serde_json::to_value(&post_merge).unwrap();
+        value["transactions"] = json!([full[0].hash, full[0]]);
+        let error = serde_json::from_value::<Block>(value).unwrap_err();
+        assert!(error.to_string().contains("mixes"), "{}", error);
+
+        let pending: Block =
+            serde_json::from_str(include_str!("../tests/fixtures/block_pending.json")).unwrap();
+        assert_eq!(pending.number, None);
+        assert_eq!(pending.hash, None);
+        assert_eq!(pending.nonce, None);
+        assert!(pending.is_pending());
+        assert!(!post_merge.is_pending());
+
+        // Geth numbers its pending block but leaves the sealing fields null
+        let geth: Block =
+            serde_json::from_str(include_str!("../tests/fixtures/block_pending_geth.json"))
+                .unwrap();
+        assert!(geth.is_pending());
+        assert_eq!(geth.number, Some(19_449_568));
+        assert_eq!(geth.miner, None);
+        assert_eq!(geth.total_difficulty, None);
+        let mut value: Value =
+            serde_json::from_str(include_str!("../tests/fixtures/block_pending_geth.json"))
+                .unwrap();
+        value["totalDifficulty"] = Value::Null;
+        let older_geth: Block = serde_json::from_value(value).unwrap();
+        assert_eq!(older_geth.total_difficulty, None);
+
+        // post-merge reth: no totalDifficulty at all, difficulty still present as zero
+        let reth: Block =
+            serde_json::from_str(include_str!("../tests/fixtures/block_reth_cancun.json")).unwrap();
+        assert_eq!(reth.total_difficulty, None);
+        assert_eq!(reth.difficulty, "0x0");
+        assert!(reth.uncles.is_empty());
+        let mut value = serde_json::to_value(&reth).unwrap();
+        assert!(value.get("totalDifficulty").is_none());
+        assert!(value.get("mixHash").is_some());
+
+        // the execution payload name for mixHash, and no uncles field at all
+        let mix_hash = value["mixHash"].take();
+        value.as_object_mut().unwrap().remove("mixHash");
+        value.as_object_mut().unwrap().remove("uncles");
+        value["prevRandao"] = mix_hash;
+        let renamed: Block = serde_json::from_value(value).unwrap();
+        assert_eq!(renamed.mix_hash, reth.mix_hash);
+        assert!(renamed.uncles.is_empty());
+
+        // numeric fields serialize back to hex quantities
+        let value = serde_json::to_value(&post_merge).unwrap();
+        assert_eq!(value["number"], json!("0x1036640"));
+        assert_eq!(value["gasLimit"], json!("0x1c9c380"));
+    }
+
+    #[test]
+    fn block_fork_fields() {
+        // base fee, withdrawals root, blob gas used, excess blob gas, beacon root, requests hash
+        let forks = [
+            (
+                include_str!("../tests/fixtures/block_pre_london.json"),
+                [false; 6],
+            ),
+            (
+                include_str!("../tests/fixtures/block_post_merge.json"),
+                [true, false, false, false, false, false],
+            ),
+            (
+                include_str!("../tests/fixtures/block_post_shanghai.json"),
+                [true, true, false, false, false, false],
+            ),
+            (
+                include_str!("../tests/fixtures/block_cancun.json"),
+                [true, true, true, true, true, false],
+            ),
+            (
+                include_str!("../tests/fixtures/block_verify_prague_devnet.json"),
+                [true; 6],
+            ),
+        ];
+        for (fixture, expected) in forks {
+            let block: Block = serde_json::from_str(fixture).unwrap();
+            let present = [
+                block.base_fee_per_gas.is_some(),
+                block.withdrawals_root.is_some(),
+                block.blob_gas_used.is_some(),
+                block.excess_blob_gas.is_some(),
+                block.parent_beacon_block_root.is_some(),
+                block.requests_hash.is_some(),
+            ];
+            assert_eq!(present, expected, "block {:?}", block.number);
+        }
+
+        // fields from forks we do not know about yet are ignored
+        let mut value: Value = serde_json::from_str(include_str!(
+            "../tests/fixtures/block_verify_prague_devnet.json"
+        ))
+        .unwrap();
+        value["blockAccessListHash"] = json!(H256([0x11; 32]));
+        value["slotNumber"] = json!("0x10");
+        let block: Block = serde_json::from_value(value).unwrap();
+        assert_eq!(block.number, Some(789));
+    }
+
+    #[test]
+    fn transaction_type_fixtures_parse() {
+        let cases = [
+            (
+                include_str!("../tests/fixtures/tx_legacy.json"),
+                TxType::Legacy,
+            ),
+            (
+                include_str!("../tests/fixtures/tx_eip2930.json"),
+                TxType::AccessList,
+            ),
+            (
+                include_str!("../tests/fixtures/tx_eip1559.json"),
+                TxType::DynamicFee,
+            ),
+            (
+                include_str!("../tests/fixtures/tx_eip4844.json"),
+                TxType::Blob,
+            ),
+        ];
+        for (fixture, tx_type) in cases {
+            let tx: Transaction = serde_json::from_str(fixture).unwrap();
+            assert_eq!(tx.tx_type(), Some(tx_type));
+        }
+
+        let legacy: Transaction =
+            serde_json::from_str(include_str!("../tests/fixtures/tx_legacy.json")).unwrap();
+        assert!(legacy.transaction_type.is_none());
+        assert!(legacy.gas_price.is_some());
+        assert!(legacy.access_list.is_none());
+
+        // unknown types are kept, malformed ones are not taken for legacy
+        let mut set_code = legacy.clone();
+        set_code.transaction_type = Some("0x4".to_string());
+        assert_eq!(set_code.tx_type(), Some(TxType::Other(4)));
+        let mut malformed = legacy.clone();
+        malformed.transaction_type = Some("legacy".to_string());
+        assert_eq!(malformed.tx_type(), None);
+
+        let access_list: Transaction =
+            serde_json::from_str(include_str!("../tests/fixtures/tx_eip2930.json")).unwrap();
+        let list = access_list.access_list.unwrap();
+        assert_eq!(list.0[0].storage_keys.len(), 2);
+        assert_eq!(list.intrinsic_gas(), 2400 + 2 * 1900);
+
+        let dynamic_fee: Transaction =
+            serde_json::from_str(include_str!("../tests/fixtures/tx_eip1559.json")).unwrap();
+        assert!(dynamic_fee.gas_price.is_none());
+        assert!(dynamic_fee.max_priority_fee_per_gas.is_some());
+        assert_eq!(dynamic_fee.y_parity.as_deref(), Some("0x1"));
+
+        let blob: Transaction =
+            serde_json::from_str(include_str!("../tests/fixtures/tx_eip4844.json")).unwrap();
+        assert_eq!(blob.blob_versioned_hashes.unwrap().len(), 2);
+        assert_eq!(blob.max_fee_per_blob_gas.as_deref(), Some("0x3b9aca00"));
+    }
+
+    #[test]
+    fn receipt_fixtures_parse() {
+        let many_logs: Receipt =
+            serde_json::from_str(include_str!("../tests/fixtures/receipt_many_logs.json")).unwrap();
+        assert_eq!(many_logs.status, Some(true));
+        assert_eq!(many_logs.logs.len(), 4);
+        assert_eq!(many_logs.logs[0].topics.len(), 3);
+        assert_eq!(many_logs.effective_gas_price, Some(0x4a817c800));
+        assert_eq!(many_logs.tx_type(), Some(TxType::DynamicFee));
+        assert_eq!(many_logs.transaction_type.as_deref(), Some("0x2"));
+        assert!(many_logs
+            .logs_bloom
+            .contains_input(many_logs.logs[0].address.as_bytes()));
+
+        let failed: Receipt =
+            serde_json::from_str(include_str!("../tests/fixtures/receipt_failed.json")).unwrap();
+        assert_eq!(failed.status, Some(false));
+        assert_eq!(failed.succeeded(), Some(false));
+        assert_eq!(failed.gas_used, 0x7a1b);
+        assert_eq!(failed.cumulative_gas_used, 0x2b9f0a);
+        assert_eq!(failed.fee_paid_wei(), Some(0x7a1b * 0x4a817c800));
+        assert!(failed.root.is_none());
+        assert!(failed.logs.is_empty());
+        assert_eq!(many_logs.succeeded(), Some(true));
+
+        let pre_byzantium: Receipt =
+            serde_json::from_str(include_str!("../tests/fixtures/receipt_pre_byzantium.json"))
+                .unwrap();
+        assert_eq!(pre_byzantium.status, None);
+        assert_eq!(pre_byzantium.succeeded(), None);
+        assert_eq!(
+            pre_byzantium.root.unwrap().to_hex(),
+            "0x96a8e009d2b88b1483e6941e6812e32263b05683fac202abc622a3e31aed1957"
+        );
+        assert!(pre_byzantium.effective_gas_price.is_none());
+        assert_eq!(pre_byzantium.fee_paid_wei(), None);
+        assert_eq!(pre_byzantium.tx_type(), Some(TxType::Legacy));
+        assert_eq!(pre_byzantium.gas_used, 21_000);
+        let value = serde_json::to_value(&pre_byzantium).unwrap();
+        assert!(value.get("status").is_none());
+        assert_eq!(
+            value["root"],
+            json!("0x96a8e009d2b88b1483e6941e6812e32263b05683fac202abc622a3e31aed1957")
+        );
+
+        let creation: Receipt = serde_json::from_str(include_str!(
+            "../tests/fixtures/receipt_contract_creation.json"
+        ))
+        .unwrap();
+        assert!(creation.to.is_none());
+        assert!(creation.contract_address.is_some());
+
+        let value = serde_json::to_value(&failed).unwrap();
+        assert_eq!(value["status"], json!("0x0"));
+    }
+
+    #[test]
+    fn sync_status() {
+        let synced: SyncStatus = serde_json::from_value(json!(false)).unwrap();
+        assert_eq!(synced, SyncStatus::NotSyncing);
+        assert!(synced.is_synced());
+        assert_eq!(synced.blocks_remaining(), 0);
+
+        let syncing: SyncStatus = serde_json::from_value(json!({
+            "currentBlock": "0x1036640",
+            "healedBytecodeBytes": "0x0",
+            "healedBytecodes": "0x0",
+            "highestBlock": "0x1036a28",
+            "startingBlock": "0x1036258",
+            "syncedAccounts": "0x2b3a",
+        }))
+        .unwrap();
+        let progress = syncing.progress().unwrap();
+        assert!(!syncing.is_synced());
+        assert_eq!(syncing.blocks_remaining(), 1000);
+        assert!((progress.progress_ratio() - 0.5).abs() < f64::EPSILON);
+        assert_eq!(progress.extra["syncedAccounts"], json!("0x2b3a"));
+        assert_eq!(progress.extra.len(), 3);
+
+        assert!(serde_json::from_value::<SyncStatus>(json!(true)).is_err());
+    }
+
+    #[test]
+    fn transaction_request_serialization_and_validation() {
+        let from: Address = "0x846c4dc9f4e2514206ef179eaa0bcfae007e37d2"
+            .parse()
+            .unwrap();
+        let to: Address = "0x4e910ef89c176119744977d90093858089454223"
+            .parse()
+            .unwrap();
+
+        let call = TransactionRequest::new().to(to).data("0x313ce567");
+        assert_eq!(
+            serde_json::to_value(&call).unwrap(),
+            json!({ "to": "0x4e910ef89c176119744977d90093858089454223", "data": "0x313ce567" })
+        );
+        assert_eq!(call.validate(), Ok(()));
+
+        let dynamic_fee = TransactionRequest::new()
+            .from(from)
+            .to(to)
+            .gas(21_000u64)
+            .max_fee_per_gas(30_000_000_000u64)
+            .max_priority_fee_per_gas(1_000_000_000u64)
+            .value(1u64)
+            .nonce(7u64)
+            .chain_id(1u64)
+            .transaction_type(2u64);
+        assert_eq!(
+            serde_json::to_value(&dynamic_fee).unwrap(),
+            json!({
+                "from": "0x846c4dc9f4e2514206ef179eaa0bcfae007e37d2",
+                "to": "0x4e910ef89c176119744977d90093858089454223",
+                "gas": "0x5208",
+                "maxFeePerGas": "0x6fc23ac00",
+                "maxPriorityFeePerGas": "0x3b9aca00",
+                "value": "0x1",
+                "nonce": "0x7",
+                "chainId": "0x1",
+                "type": "0x2",
+            })
+        );
+        assert_eq!(dynamic_fee.validate(), Ok(()));
+        assert_eq!(
+            dynamic_fee.gas_price(1u64).validate(),
+            Err(TransactionRequestError::MixedFeeFields)
+        );
+
+        assert_eq!(
+            TransactionRequest::new().to(to).validate_send(),
+            Err(TransactionRequestError::MissingFrom)
+        );
+        // only `from` and `to` are sent, nothing empty for the node to choke on
+        assert_eq!(
+            serde_json::to_value(TransactionRequest::new().from(from).to(to)).unwrap(),
+            json!({
+                "from": "0x846c4dc9f4e2514206ef179eaa0bcfae007e37d2",
+                "to": "0x4e910ef89c176119744977d90093858089454223",
+            })
+        );
+
+        let creation = TransactionRequest::new().from(from);
+        assert!(creation.is_contract_creation());
+        assert_eq!(
+            creation.validate(),
+            Err(TransactionRequestError::MissingCreationCode)
+        );
+        assert_eq!(creation.data("0x6080").validate(), Ok(()));
+
+        let parsed: TransactionRequest =
+            serde_json::from_value(json!({ "to": to, "input": "0x01" })).unwrap();
+        assert_eq!(parsed.data.as_deref(), Some("0x01"));
+    }
+
+    #[test]
+    fn block_withdrawals() {
+        let block: Block =
+            serde_json::from_str(include_str!("../tests/fixtures/block_post_shanghai.json"))
+                .unwrap();
+        let withdrawals = block.withdrawals.as_ref().unwrap();
+        assert_eq!(withdrawals.len(), 2);
+        assert_eq!(withdrawals[0].index, 9_512_345);
+        assert_eq!(withdrawals[0].validator_index, 482_001);
+        assert_eq!(withdrawals[0].amount, 16_022_530);
+        assert_eq!(
+            withdrawals[0].amount_wei(),
+            U256::from(16_022_530_000_000_000u64)
+        );
+        assert!(block.withdrawals_root.is_some());
+
+        let value = serde_json::to_value(&block).unwrap();
+        assert_eq!(value["withdrawals"][0]["validatorIndex"], json!("0x75ad1"));
+
+        let pre: Block =
+            serde_json::from_str(include_str!("../tests/fixtures/block_post_merge.json")).unwrap();
+        assert!(pre.withdrawals.is_none());
+        assert!(serde_json::to_value(&pre)
+            .unwrap()
+            .get("withdrawals")
+            .is_none());
+    }
+
+    #[test]
+    fn blob_fields() {
+        let block: Block =
+            serde_json::from_str(include_str!("../tests/fixtures/block_cancun.json")).unwrap();
+        assert_eq!(block.blob_gas_used, Some(786_432));
+        assert_eq!(block.excess_blob_gas, Some(0x4b20000));
+        assert!(block.parent_beacon_block_root.is_some());
+        assert_eq!(
+            block.transactions.as_full().unwrap()[0].tx_type(),
+            Some(TxType::Blob)
+        );
+
+        let raw = include_str!("../tests/fixtures/receipt_blob.json");
+        let receipt: Receipt = serde_json::from_str(raw).unwrap();
+        assert_eq!(receipt.blob_gas_used, Some(131_072));
+        assert_eq!(receipt.blob_gas_price, Some(1));
+        assert_eq!(
+            serde_json::to_value(&receipt).unwrap(),
+            serde_json::from_str::<Value>(raw).unwrap()
+        );
+
+        let older: Receipt =
+            serde_json::from_str(include_str!("../tests/fixtures/receipt_failed.json")).unwrap();
+        assert_eq!(older.blob_gas_used, None);
+    }
+
+    #[test]
+    fn create_access_list_result() {
+        let r: AccessListResult = serde_json::from_value(json!({
+            "accessList": [
+                {
+                    "address": "0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2",
+                    "storageKeys": [
+                        "0x0000000000000000000000000000000000000000000000000000000000000003"
+                    ]
+                },
+                {
+                    "address": "0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48",
+                    "storageKeys": []
+                }
+            ],
+            "gasUsed": "0x7b55"
+        }))
+        .unwrap();
+        assert_eq!(r.access_list.len(), 2);
+        assert_eq!(r.access_list.intrinsic_gas(), 2400 + 1900 + 2400);
+        assert_eq!(r.gas_used, U256::from(0x7b55u64));
+        assert!(r.error.is_none());
+
+        let request = TransactionRequest::new().access_list(r.access_list.clone());
+        assert_eq!(
+            serde_json::to_value(&request).unwrap()["accessList"][0]["storageKeys"][0],
+            json!("0x0000000000000000000000000000000000000000000000000000000000000003")
+        );
+        assert_eq!(AccessList::default().intrinsic_gas(), 0);
+    }
+
+    #[test]
+    fn state_override_serialization() {
+        let token: Address = "0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48"
+            .parse()
+            .unwrap();
+        let holder: Address = "0x846c4dc9f4e2514206ef179eaa0bcfae007e37d2"
+            .parse()
+            .unwrap();
+        let slot = H256::from([0u8; 32]);
+        let mut value = [0u8; 32];
+        value[31] = 1;
+
+        let mut overrides = StateOverride::new()
+            .balance(holder, 1_000_000_000_000_000_000u128)
+            .nonce(holder, 7u64)
+            .code(token, "0x6080")
+            .state_diff(token, slot, H256::from(value));
+        let unused: Address = "0x4e910ef89c176119744977d90093858089454223"
+            .parse()
+            .unwrap();
+        overrides.account(unused);
+
+        assert_eq!(
+            serde_json::to_string(&overrides).unwrap(),
+            concat!(
+                r#"{"0x846c4dc9f4e2514206ef179eaa0bcfae007e37d2":{"balance":"0xde0b6b3a7640000","nonce":"0x7"},"#,
+                r#""0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48":{"code":"0x6080","stateDiff":{"#,
+                r#""0x0000000000000000000000000000000000000000000000000000000000000000":"#,
+                r#""0x0000000000000000000000000000000000000000000000000000000000000001"}}}"#
+            )
+        );
+        assert!(!overrides.is_empty());
+        assert!(StateOverride::new().is_empty());
+        assert_eq!(serde_json::to_string(&StateOverride::new()).unwrap(), "{}");
+
+        let parsed: StateOverride =
+            serde_json::from_str(&serde_json::to_string(&overrides).unwrap()).unwrap();
+        assert_eq!(parsed.0.len(), 2);
+        assert_eq!(parsed.0[&holder].nonce, Some(U256::from(7u64)));
+    }
+
+    #[test]
+    fn json_rpc_result_envelopes() {
+        let ok: JsonRpcResult<U256> =
+            serde_json::from_str(r#"{"jsonrpc":"2.0","id":"337","result":"0x10"}"#).unwrap();
+        assert_eq!(ok.result(), Some(&U256::from(16u64)));
+        assert_eq!(ok.into_result().unwrap(), U256::from(16u64));
+
+        let failed: JsonRpcResult<U256> = serde_json::from_str(
+            r#"{"jsonrpc":"2.0","id":7,"error":{"code":-32601,"message":"the method eth_foo does not exist"}}"#,
+        )
+        .unwrap();
+        assert_eq!(failed.id, "7");
+        assert!(failed.result().is_none());
+        let err = failed.into_result().unwrap_err();
+        assert_eq!(err.code, -32601);
+        assert_eq!(
+            err.to_string(),
+            "the method eth_foo does not exist (code -32601)"
+        );
+
+        let null: JsonRpcResult<Block> =
+            serde_json::from_str(r#"{"jsonrpc":"2.0","id":null,"result":null}"#).unwrap();
+        assert!(null.result().is_none());
+        assert_eq!(null.into_result().unwrap_err().code, -32603);
+    }
+
+    /// Deserializes `fixture` into `T` and writes it back, failing with the serde path of
+    /// the offending field.
+    fn round_trip<T>(name: &str, fixture: &str)
+    where
+        T: serde::de::DeserializeOwned + Serialize + PartialEq + fmt::Debug,
+    {
+        let parsed: T =
+            serde_path_to_error::deserialize(&mut serde_json::Deserializer::from_str(fixture))
+                .unwrap_or_else(|e| panic!("{}: at `{}`: {}", name, e.path(), e.inner()));
+        let written = serde_json::to_string(&parsed).unwrap();
+        let reparsed: T = serde_json::from_str(&written).unwrap();
+        assert_eq!(parsed, reparsed, "{}", name);
+        // nullable fields the node always sends keep their null, absent ones stay absent
+        assert_eq!(
+            serde_json::from_str::<Value>(&written).unwrap(),
+            serde_json::from_str::<Value>(fixture).unwrap(),
+            "{} does not survive a round trip",
+            name
+        );
+    }
+
+    #[test]
+    fn models_round_trip() {
+        // every fixture is checked; the file name prefix picks the model
+        let dir = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures");
+        let mut checked = 0;
+        for entry in std::fs::read_dir(dir).unwrap() {
+            let path = entry.unwrap().path();
+            if path.extension().and_then(|e| e.to_str()) != Some("json") {
+                continue;
+            }
+            let name = path.file_name().unwrap().to_str().unwrap().to_string();
+            let fixture = std::fs::read_to_string(&path).unwrap();
+            match name.split('_').next().unwrap() {
+                "block" => round_trip::<Block>(&name, &fixture),
+                "tx" => round_trip::<Transaction>(&name, &fixture),
+                "receipt" => round_trip::<Receipt>(&name, &fixture),
+                "syncing" => round_trip::<SyncStatus>(&name, &fixture),
+                "fee" => round_trip::<FeeHistory>(&name, &fixture),
+                "proof" => round_trip::<AccountProof>(&name, &fixture),
+                "log" => round_trip::<Log>(&name, &fixture),
+                // error envelopes, read in tests/reverts.rs, tests/capacity.rs and tests/archive.rs
+                "revert" | "capacity" | "state" => continue,
+                // GraphQL responses, read in src/web3/graphql.rs
+                "graphql" => continue,
+                _ => panic!("no model for fixture {}", name),
+            }
+            checked += 1;
+        }
+        assert!(checked >= 18);
+
+        let deposit: Transaction =
+            serde_json::from_str(include_str!("../tests/fixtures/tx_deposit.json")).unwrap();
+        assert_eq!(deposit.tx_type(), Some(TxType::Deposit));
+        assert_eq!(deposit.is_system_tx, Some(false));
+        let history: FeeHistory =
+            serde_json::from_str(include_str!("../tests/fixtures/fee_history.json")).unwrap();
+        assert_eq!(
+            history.base_fee_per_gas.len(),
+            history.gas_used_ratio.len() + 1
+        );
+        assert_eq!(history.reward.unwrap()[0].len(), 3);
+
+        let block: Block =
+            serde_json::from_str(include_str!("../tests/fixtures/block_post_merge.json")).unwrap();
+        assert!(block.sha3uncles.is_some());
+        assert!(serde_json::to_value(&block)
+            .unwrap()
+            .get("sha3Uncles")
+            .is_some());
+
+        let envelope: JsonRpcResult<Block> = serde_json::from_value(json!({
+            "jsonrpc": "2.0",
+            "id": "325",
+            "result": serde_json::to_value(&block).unwrap()
+        }))
+        .unwrap();
+        let reparsed: JsonRpcResult<Block> =
+            serde_json::from_str(&serde_json::to_string(&envelope).unwrap()).unwrap();
+        assert_eq!(envelope, reparsed);
+    }
+
+    #[test]
+    fn unknown_members_are_kept() {
+        let zkevm: Block =
+            serde_json::from_str(include_str!("../tests/fixtures/block_polygon_zkevm.json"))
+                .unwrap();
+        assert_eq!(
+            zkevm.extra.get("globalExitRoot"),
+            Some(&json!(
+                "0xad3228b676f7d3cd4284a5443f17f1962b36e491b30a40b2405849e597ba5fb5"
+            ))
+        );
+        assert!(zkevm.extra.contains_key("blockInfoRoot"));
+        let tx = &zkevm.transactions.as_full().unwrap()[0];
+        assert_eq!(tx.extra.keys().collect::<Vec<_>>(), ["l2Hash"]);
+
+        let base: Receipt =
+            serde_json::from_str(include_str!("../tests/fixtures/receipt_base.json")).unwrap();
+        assert_eq!(base.extra.get("l1Fee"), Some(&json!("0x1a3b5c7d9")));
+        assert_eq!(base.extra.len(), 6);
+        // known members stay out of it
+        assert!(!base.extra.contains_key("gasUsed"));
+        assert_eq!(base.gas_used, 0xb41e);
+
+        let mainnet: Block =
+            serde_json::from_str(include_str!("../tests/fixtures/block_cancun.json")).unwrap();
+        assert!(mainnet.extra.is_empty());
+    }
 }
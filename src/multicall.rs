@@ -0,0 +1,220 @@
+//! Many view calls in one `eth_call` through the Multicall3 contract.
+//!
+//! ```no_run
+//! # async fn run(web3: web3_rpc::web3::Web3, token: web3_rpc::types::Address, calldata: Vec<u8>) {
+//! let results = web3
+//!     .multicall()
+//!     .add(token, calldata.clone())
+//!     .add(token, calldata)
+//!     .try_aggregate(true)
+//!     .await
+//!     .unwrap();
+//! for result in results {
+//!     println!("{} {:?}", result.success, result.return_data);
+//! }
+//! # }
+//! ```
+
+use crate::abi::{self, ParamType, Token};
+use crate::client::{rpc_error, Error};
+use crate::hex;
+use crate::model::{BlockId, JsonRpcResult, RpcErrorObject, TransactionRequest};
+use crate::types::Address;
+use crate::web3::Web3;
+use error_stack::{Report, ResultExt};
+use serde_json::json;
+
+/// Multicall3, deployed at the same address on most chains.
+pub const MULTICALL3: Address = Address([
+    0xca, 0x11, 0xbd, 0xe0, 0x59, 0x77, 0xb3, 0x63, 0x11, 0x67, 0x02, 0x88, 0x62, 0xbe, 0x2a, 0x17,
+    0x39, 0x76, 0xca, 0x11,
+]);
+
+const TRY_AGGREGATE: &str = "tryAggregate(bool,(address,bytes)[])";
+
+/// Calls per `eth_call` unless [`Multicall::chunk_size`] says otherwise.
+pub const DEFAULT_CHUNK_SIZE: usize = 500;
+
+/// Outcome of one call in the aggregate.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CallResult {
+    pub success: bool,
+    /// Return data on success, revert data (possibly empty) on failure.
+    pub return_data: Vec<u8>,
+}
+
+/// Builder returned by [`Web3::multicall`].
+pub struct Multicall<'a> {
+    web3: &'a Web3,
+    calls: Vec<(Address, Vec<u8>)>,
+    block: BlockId,
+    chunk_size: usize,
+}
+
+impl Web3 {
+    pub fn multicall(&self) -> Multicall<'_> {
+        Multicall {
+            web3: self,
+            calls: Vec::new(),
+            block: self.client.default_tag().into(),
+            chunk_size: DEFAULT_CHUNK_SIZE,
+        }
+    }
+}
+
+impl<'a> Multicall<'a> {
+    pub fn add(mut self, target: Address, calldata: impl Into<Vec<u8>>) -> Self {
+        self.calls.push((target, calldata.into()));
+        self
+    }
+
+    /// Block to run every call against, the client's default tag (`latest`) by default.
+    pub fn block(mut self, block: impl Into<BlockId>) -> Self {
+        self.block = block.into();
+        self
+    }
+
+    /// Splits large aggregates so each `eth_call` stays under the node's gas cap.
+    pub fn chunk_size(mut self, calls: usize) -> Self {
+        self.chunk_size = calls.max(1);
+        self
+    }
+
+    /// Runs every call and returns one result per [`add`](Self::add), in order.
+    ///
+    /// With `allow_failure` false the first failing call fails the whole aggregate. When
+    /// Multicall3 has no code at the target block, the calls go out as a JSON-RPC batch of
+    /// plain `eth_call`s instead.
+    pub async fn try_aggregate(
+        self,
+        allow_failure: bool,
+    ) -> Result<Vec<CallResult>, Report<Error>> {
+        if self.calls.is_empty() {
+            return Ok(Vec::new());
+        }
+        let code = self
+            .web3
+            .eth_get_code_at_block(MULTICALL3, self.block.clone())
+            .await?
+            .result
+            .unwrap_or_default();
+        if code.is_empty() || code == "0x" {
+            tracing::debug!("Multicall3 is not deployed, falling back to a batch");
+            return self.batch(allow_failure).await;
+        }
+
+        let mut results = Vec::with_capacity(self.calls.len());
+        for chunk in self.calls.chunks(self.chunk_size) {
+            results.extend(self.aggregate(chunk, allow_failure).await?);
+        }
+        Ok(results)
+    }
+
+    async fn aggregate(
+        &self,
+        calls: &[(Address, Vec<u8>)],
+        allow_failure: bool,
+    ) -> Result<Vec<CallResult>, Report<Error>> {
+        let calls = calls
+            .iter()
+            .map(|(target, data)| {
+                Token::Tuple(vec![Token::Address(*target), Token::Bytes(data.clone())])
+            })
+            .collect();
+        let calldata = abi::encode_call(
+            TRY_AGGREGATE,
+            &[Token::Bool(!allow_failure), Token::Array(calls)],
+        );
+        let request = TransactionRequest::new()
+            .to(MULTICALL3)
+            .data(hex::data(&calldata));
+        let output = self
+            .web3
+            .eth_call_request_at_block(&request, self.block.clone())
+            .await?
+            .result
+            .unwrap_or_default();
+
+        let output = hex::parse_data(&output)
+            .change_context(Error::FailedToDeserialize)
+            .attach_printable("Multicall3 returned invalid hex")?;
+        let returned = ParamType::Tuple(vec![ParamType::Bool, ParamType::Bytes]);
+        let mut tokens = abi::decode(&[ParamType::Array(Box::new(returned))], &output)
+            .change_context(Error::FailedToDeserialize)
+            .attach_printable("Multicall3 returned malformed data")?;
+        tokens
+            .pop()
+            .and_then(Token::into_tokens)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|entry| {
+                let mut members = entry.into_tokens().unwrap_or_default().into_iter();
+                match (
+                    members.next().and_then(Token::into_bool),
+                    members.next().and_then(Token::into_bytes),
+                ) {
+                    (Some(success), Some(return_data)) => Ok(CallResult {
+                        success,
+                        return_data,
+                    }),
+                    _ => Err(Report::new(Error::FailedToDeserialize)
+                        .attach_printable("Multicall3 result is not a (bool, bytes) tuple")),
+                }
+            })
+            .collect()
+    }
+
+    async fn batch(&self, allow_failure: bool) -> Result<Vec<CallResult>, Report<Error>> {
+        let block = self.block.eip1898();
+        let payloads = self
+            .calls
+            .iter()
+            .map(|(target, data)| {
+                json!({ "jsonrpc": "2.0", "method": "eth_call", "params": [{ "to": target, "data": hex::data(data) }, block], "id": "322" })
+            })
+            .collect();
+        let responses: Vec<JsonRpcResult<String>> = self.web3.client.call_batch(payloads).await?;
+
+        responses
+            .into_iter()
+            .map(|response| match response.error {
+                None => {
+                    let output = response.result.unwrap_or_default();
+                    let return_data = hex::parse_data(&output)
+                        .change_context(Error::FailedToDeserialize)
+                        .attach_printable_lazy(|| format!("eth_call returned `{}`", output))?;
+                    Ok(CallResult {
+                        success: true,
+                        return_data,
+                    })
+                }
+                Some(error) if allow_failure => Ok(CallResult {
+                    success: false,
+                    return_data: revert_data(&error),
+                }),
+                Some(error) => Err(rpc_error(error)),
+            })
+            .collect()
+    }
+}
+
+fn revert_data(error: &RpcErrorObject) -> Vec<u8> {
+    error
+        .revert_data()
+        .and_then(|data| hex::parse_data(data).ok())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn multicall3_constants() {
+        assert_eq!(
+            MULTICALL3.to_checksum(),
+            "0xcA11bde05977b3631167028862bE2a173976CA11"
+        );
+        assert_eq!(abi::selector(TRY_AGGREGATE), [0xbc, 0xe3, 0x8b, 0xd7]);
+    }
+}
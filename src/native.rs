@@ -0,0 +1,196 @@
+//! Reads in plain Rust types, for call sites that want a number or a model rather than the
+//! JSON-RPC envelope: errors and `null` results are unwrapped, quantities parsed.
+//!
+//! ```no_run
+//! # use web3_rpc::web3::Web3;
+//! # async fn example(rpc: Web3) -> Result<(), error_stack::Report<web3_rpc::client::Error>> {
+//! let block = rpc.get_block(19_000_000).await?.expect("mined");
+//! let wei: u128 = rpc.balance("0x00000000219ab540356cbb839cbe05303d7705fa").await?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! They sit on top of the `eth_*` methods, which still give the node's answer as sent.
+
+use crate::client::{rpc_error, Error};
+use crate::model::{Block, BlockId, JsonRpcResult, Receipt, Transaction};
+use crate::types::{AddressParam, H256Param, U256};
+use crate::web3::Web3;
+use error_stack::{Report, ResultExt};
+use reqwest::StatusCode;
+use serde::de::DeserializeOwned;
+use serde_json::{json, Value};
+
+impl Web3 {
+    /// Block by number, tag or hash, with transaction hashes; `None` when the node does not
+    /// have it.
+    pub async fn get_block(
+        &self,
+        block: impl Into<BlockId>,
+    ) -> Result<Option<Block>, Report<Error>> {
+        nullable(self.eth_get_block(block, false).await?)
+    }
+
+    /// [`Web3::get_block`] with full transactions.
+    pub async fn get_block_with_txs(
+        &self,
+        block: impl Into<BlockId>,
+    ) -> Result<Option<Block>, Report<Error>> {
+        nullable(self.eth_get_block(block, true).await?)
+    }
+
+    /// `None` when the node does not know the transaction.
+    pub async fn get_transaction(
+        &self,
+        hash: impl Into<H256Param>,
+    ) -> Result<Option<Transaction>, Report<Error>> {
+        nullable(self.eth_get_transaction_by_hash(hash).await?)
+    }
+
+    /// `None` while the transaction is pending or unknown.
+    pub async fn get_receipt(
+        &self,
+        hash: impl Into<H256Param>,
+    ) -> Result<Option<Receipt>, Report<Error>> {
+        nullable(self.eth_get_transaction_receipt(hash).await?)
+    }
+
+    /// Balance in wei at the client's default tag.
+    pub async fn balance(&self, address: impl Into<AddressParam>) -> Result<u128, Report<Error>> {
+        quantity(
+            "eth_getBalance",
+            self.eth_get_balance_typed(address, None).await?,
+        )
+    }
+
+    /// Transactions sent from `address` at the client's default tag. The next nonce to use
+    /// counts pending ones too: see [`Web3::eth_get_transaction_count`] with `Tag::Pending`.
+    pub async fn nonce(&self, address: impl Into<AddressParam>) -> Result<u64, Report<Error>> {
+        quantity(
+            "eth_getTransactionCount",
+            self.eth_get_transaction_count_typed(address, None).await?,
+        )
+    }
+
+    /// The transaction and its receipt, read in one batch, or `None` when the node does not
+    /// know the transaction. The receipt is `None` while the transaction is pending.
+    ///
+    /// An endpoint rejecting batches gets the two requests concurrently instead. When the two
+    /// answers place the transaction in different blocks, as they may mid-reorg, both are read
+    /// again once before failing with [`Error::ReceiptMismatch`].
+    pub async fn get_transaction_with_receipt(
+        &self,
+        hash: impl Into<H256Param>,
+    ) -> Result<Option<(Transaction, Option<Receipt>)>, Report<Error>> {
+        let hash = hash.into();
+        self.client.check_hex("hash", hash.as_str(), Some(64))?;
+        let mut reread = false;
+        loop {
+            let (tx, receipt) = self.transaction_and_receipt(&hash).await?;
+            let transaction_block = tx.as_ref().and_then(|tx| tx.block_hash);
+            let receipt_block = receipt.as_ref().map(|receipt| receipt.block_hash);
+            match (tx, receipt) {
+                (None, None) => return Ok(None),
+                (Some(tx), None) => return Ok(Some((tx, None))),
+                (Some(tx), Some(receipt)) if transaction_block == receipt_block => {
+                    return Ok(Some((tx, Some(receipt))))
+                }
+                // a receipt without its transaction, or in another block
+                (tx, Some(receipt)) if reread => {
+                    let tx_hash = tx.map_or(receipt.transaction_hash, |tx| tx.hash);
+                    return Err(Report::new(Error::ReceiptMismatch {
+                        tx_hash,
+                        transaction_block,
+                        receipt_block,
+                    }));
+                }
+                _ => {
+                    tracing::debug!(
+                        hash = hash.as_str(),
+                        "transaction and receipt disagree, reading again"
+                    );
+                    reread = true;
+                }
+            }
+        }
+    }
+
+    async fn transaction_and_receipt(
+        &self,
+        hash: &H256Param,
+    ) -> Result<(Option<Transaction>, Option<Receipt>), Report<Error>> {
+        let payloads = vec![
+            json!({ "jsonrpc": "2.0", "method": "eth_getTransactionByHash", "params": [hash], "id": "326" }),
+            json!({ "jsonrpc": "2.0", "method": "eth_getTransactionReceipt", "params": [hash], "id": "328" }),
+        ];
+        match self.client.call_batch::<Value>(payloads).await {
+            Ok(responses) => {
+                // call_batch answers every request, in order
+                let [tx, receipt]: [JsonRpcResult<Value>; 2] = responses
+                    .try_into()
+                    .map_err(|_| Report::new(Error::UnexpectedResponseFormat))?;
+                Ok((parse(tx)?, parse(receipt)?))
+            }
+            Err(report) if batch_rejected(report.current_context()) => {
+                tracing::debug!(
+                    "batch rejected, sending the requests separately: {}",
+                    report.current_context()
+                );
+                let (tx, receipt) = tokio::try_join!(
+                    self.eth_get_transaction_by_hash(hash.clone()),
+                    self.eth_get_transaction_receipt(hash.clone()),
+                )?;
+                Ok((nullable(tx)?, nullable(receipt)?))
+            }
+            Err(report) => Err(report),
+        }
+    }
+
+    pub async fn block_number(&self) -> Result<u64, Report<Error>> {
+        quantity("eth_blockNumber", self.eth_block_number_typed().await?)
+    }
+
+    /// Legacy gas price in wei.
+    pub async fn gas_price(&self) -> Result<u128, Report<Error>> {
+        quantity("eth_gasPrice", self.eth_gas_price_typed().await?)
+    }
+}
+
+fn nullable<T>(r: JsonRpcResult<T>) -> Result<Option<T>, Report<Error>> {
+    r.into_option().map_err(rpc_error)
+}
+
+/// A batch response's result as `T`, `None` for `null`.
+fn parse<T: DeserializeOwned>(r: JsonRpcResult<Value>) -> Result<Option<T>, Report<Error>> {
+    match nullable(r)? {
+        None => Ok(None),
+        Some(value) => serde_path_to_error::deserialize(value)
+            .map(Some)
+            .change_context(Error::FailedToDeserialize),
+    }
+}
+
+/// Whether a batch failed because the endpoint does not take batches: it answered with an
+/// error envelope instead of an array, something else that is not an array, or a client
+/// error status.
+pub(crate) fn batch_rejected(e: &Error) -> bool {
+    match e {
+        Error::JsonRpcError { .. }
+        | Error::FailedToDeserialize
+        | Error::UnexpectedResponseFormat
+        | Error::InvalidEnvelope => true,
+        Error::HttpError(status) => {
+            status.is_client_error() && *status != StatusCode::TOO_MANY_REQUESTS
+        }
+        _ => false,
+    }
+}
+
+/// The result as `T`; a `null` result or one too large for `T` is a malformed response.
+fn quantity<T: TryFrom<U256>>(method: &str, r: JsonRpcResult<U256>) -> Result<T, Report<Error>> {
+    let value = r.into_result().map_err(rpc_error)?;
+    T::try_from(value)
+        .ok()
+        .ok_or_else(|| Report::new(Error::UnexpectedResponseFormat))
+        .attach_printable_lazy(|| format!("{} returned {}, out of range", method, value))
+}
@@ -0,0 +1,247 @@
+//! Hosted providers and where each expects its API key, see [`crate::builder::Web3Builder::provider`].
+
+use crate::client::Error;
+use error_stack::{Report, ResultExt};
+use reqwest::header::AUTHORIZATION;
+use std::fmt::{self, Debug};
+
+/// Stands in for the key wherever the endpoint is shown.
+const REDACTED: &str = "***";
+
+/// A provider credential. `Debug` never shows it.
+#[derive(Clone, PartialEq, Eq)]
+pub struct ApiKey(String);
+
+impl ApiKey {
+    pub fn new(key: impl Into<String>) -> Self {
+        ApiKey(key.into())
+    }
+}
+
+impl Debug for ApiKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "ApiKey({})", REDACTED)
+    }
+}
+
+impl From<&str> for ApiKey {
+    fn from(key: &str) -> Self {
+        ApiKey::new(key)
+    }
+}
+
+impl From<String> for ApiKey {
+    fn from(key: String) -> Self {
+        ApiKey(key)
+    }
+}
+
+/// Where a [`Provider::Custom`] endpoint takes its key.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KeyPlacement {
+    /// Last path segment, `https://rpc.example.com/v1/<key>`.
+    Path,
+    /// In place of `{key}` in the url, e.g. `https://{key}.example.com` or a path in the middle.
+    Template,
+    /// Query parameter with the given name.
+    Query(String),
+    /// Header with the given name, e.g. `x-api-key`.
+    Header(String),
+    /// `Authorization: Bearer <key>`.
+    Bearer,
+}
+
+/// A hosted endpoint and its key.
+///
+/// Rotating a key keeps everything else: `builder.provider(provider.with_key(new_key))`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Provider {
+    /// `https://<network>.infura.io/v3/<project_id>`.
+    Infura { network: String, project_id: ApiKey },
+    /// `https://<network>.g.alchemy.com/v2/<key>`.
+    Alchemy { network: String, key: ApiKey },
+    Custom {
+        url: String,
+        key: ApiKey,
+        placement: KeyPlacement,
+    },
+}
+
+/// What a [`Provider`] resolves to.
+pub(crate) struct Endpoint {
+    pub url: String,
+    /// `url` with the key replaced, for logs and error reports.
+    pub redacted_url: String,
+    pub headers: Vec<(String, String)>,
+}
+
+impl Provider {
+    /// Infura on `network`, e.g. `mainnet` or `sepolia`.
+    pub fn infura(network: impl Into<String>, project_id: impl Into<ApiKey>) -> Self {
+        Provider::Infura {
+            network: network.into(),
+            project_id: project_id.into(),
+        }
+    }
+
+    /// Alchemy on `network`, e.g. `eth-mainnet` or `arb-sepolia`.
+    pub fn alchemy(network: impl Into<String>, key: impl Into<ApiKey>) -> Self {
+        Provider::Alchemy {
+            network: network.into(),
+            key: key.into(),
+        }
+    }
+
+    pub fn custom(url: impl Into<String>, key: impl Into<ApiKey>, placement: KeyPlacement) -> Self {
+        Provider::Custom {
+            url: url.into(),
+            key: key.into(),
+            placement,
+        }
+    }
+
+    /// The same provider with another key.
+    pub fn with_key(mut self, new_key: impl Into<ApiKey>) -> Self {
+        match &mut self {
+            Provider::Infura {
+                project_id: key, ..
+            }
+            | Provider::Alchemy { key, .. }
+            | Provider::Custom { key, .. } => *key = new_key.into(),
+        }
+        self
+    }
+
+    pub(crate) fn endpoint(&self) -> Result<Endpoint, Report<Error>> {
+        let (url, key, placement) = match self {
+            Provider::Infura {
+                network,
+                project_id,
+            } => (
+                format!("https://{}.infura.io/v3", network),
+                project_id,
+                &KeyPlacement::Path,
+            ),
+            Provider::Alchemy { network, key } => (
+                format!("https://{}.g.alchemy.com/v2", network),
+                key,
+                &KeyPlacement::Path,
+            ),
+            Provider::Custom {
+                url,
+                key,
+                placement,
+            } => (url.clone(), key, placement),
+        };
+        if key.0.is_empty() {
+            return Err(
+                Report::new(Error::InvalidConfiguration).attach_printable("api key is empty")
+            );
+        }
+        let with_key = |key: &str| -> Result<String, Report<Error>> {
+            match placement {
+                KeyPlacement::Path => Ok(format!("{}/{}", url.trim_end_matches('/'), key)),
+                KeyPlacement::Template if url.contains("{key}") => Ok(url.replace("{key}", key)),
+                KeyPlacement::Template => Err(Report::new(Error::InvalidConfiguration)
+                    .attach_printable("provider url has no `{key}` placeholder")),
+                KeyPlacement::Query(name) => {
+                    let mut parsed = reqwest::Url::parse(&url)
+                        .change_context(Error::InvalidUrl)
+                        .attach_printable("cannot parse provider url")?;
+                    parsed.query_pairs_mut().append_pair(name, key);
+                    Ok(parsed.to_string())
+                }
+                KeyPlacement::Header(_) | KeyPlacement::Bearer => Ok(url.clone()),
+            }
+        };
+        let headers = match placement {
+            KeyPlacement::Header(name) => vec![(name.clone(), key.0.clone())],
+            KeyPlacement::Bearer => vec![(AUTHORIZATION.to_string(), format!("Bearer {}", key.0))],
+            _ => Vec::new(),
+        };
+        Ok(Endpoint {
+            url: with_key(&key.0)?,
+            redacted_url: with_key(REDACTED)?,
+            headers,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn endpoints() {
+        let infura = Provider::infura("mainnet", "abc123").endpoint().unwrap();
+        assert_eq!(infura.url, "https://mainnet.infura.io/v3/abc123");
+        assert_eq!(infura.redacted_url, "https://mainnet.infura.io/v3/***");
+        assert!(infura.headers.is_empty());
+
+        let alchemy = Provider::alchemy("eth-sepolia", "k").endpoint().unwrap();
+        assert_eq!(alchemy.url, "https://eth-sepolia.g.alchemy.com/v2/k");
+
+        let quicknode = Provider::custom(
+            "https://black-frost.quiknode.pro/{key}/",
+            "token",
+            KeyPlacement::Template,
+        )
+        .endpoint()
+        .unwrap();
+        assert_eq!(quicknode.url, "https://black-frost.quiknode.pro/token/");
+        assert_eq!(
+            quicknode.redacted_url,
+            "https://black-frost.quiknode.pro/***/"
+        );
+
+        let query = Provider::custom(
+            "https://rpc.example.com/",
+            "a&b",
+            KeyPlacement::Query("apikey".into()),
+        )
+        .endpoint()
+        .unwrap();
+        assert_eq!(query.url, "https://rpc.example.com/?apikey=a%26b");
+        assert_eq!(query.redacted_url, "https://rpc.example.com/?apikey=***");
+
+        let header = Provider::custom(
+            "https://rpc.example.com",
+            "secret",
+            KeyPlacement::Header("x-api-key".into()),
+        )
+        .endpoint()
+        .unwrap();
+        assert_eq!(header.url, "https://rpc.example.com");
+        assert_eq!(
+            header.headers,
+            [("x-api-key".to_string(), "secret".to_string())]
+        );
+
+        let bearer = Provider::custom("https://rpc.example.com", "secret", KeyPlacement::Bearer)
+            .endpoint()
+            .unwrap();
+        assert_eq!(
+            bearer.headers,
+            [("authorization".to_string(), "Bearer secret".to_string())]
+        );
+
+        for invalid in [
+            Provider::infura("mainnet", ""),
+            Provider::custom("https://rpc.example.com", "k", KeyPlacement::Template),
+        ] {
+            let err = invalid.endpoint().err().unwrap();
+            assert!(matches!(err.current_context(), Error::InvalidConfiguration));
+        }
+    }
+
+    #[test]
+    fn key_is_never_shown() {
+        let provider = Provider::infura("mainnet", "abc123");
+        assert_eq!(
+            format!("{:?}", provider),
+            r#"Infura { network: "mainnet", project_id: ApiKey(***) }"#
+        );
+        let rotated = provider.with_key("def456");
+        assert_eq!(rotated, Provider::infura("mainnet", "def456"));
+    }
+}
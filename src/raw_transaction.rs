@@ -0,0 +1,345 @@
+//! Decoding of signed transactions as broadcast with eth_sendRawTransaction: legacy ones,
+//! with or without EIP-155 replay protection, and the EIP-2718 typed envelopes 1 to 4,
+//! blob transactions also in their network form. The sender is recovered from the
+//! signature.
+//!
+//! ```
+//! # fn run(raw: &[u8]) {
+//! use web3_rpc::raw_transaction::RawTransaction;
+//!
+//! let tx = RawTransaction::decode(raw).unwrap();
+//! println!("{} from {} with nonce {}", tx.hash, tx.from, tx.nonce);
+//! # }
+//! ```
+
+use crate::hex;
+use crate::keccak::keccak256;
+use crate::model::{TransactionRequest, TxType};
+use crate::rlp::{self, RlpError};
+use crate::types::{Address, H256, U256};
+use k256::ecdsa::{RecoveryId, Signature, VerifyingKey};
+use std::fmt::{self, Display, Formatter};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DecodeError {
+    Rlp(RlpError),
+    /// An envelope type other than legacy and 1 to 4.
+    UnsupportedType(u8),
+    /// The list has the wrong number of fields for its type.
+    FieldCount {
+        expected: usize,
+        found: usize,
+    },
+    InvalidField(&'static str),
+    /// The signature does not recover to a public key.
+    InvalidSignature,
+}
+
+impl Display for DecodeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeError::Rlp(e) => Display::fmt(e, f),
+            DecodeError::UnsupportedType(ty) => write!(f, "unsupported transaction type {:#x}", ty),
+            DecodeError::FieldCount { expected, found } => {
+                write!(
+                    f,
+                    "expected {} transaction fields, found {}",
+                    expected, found
+                )
+            }
+            DecodeError::InvalidField(field) => write!(f, "invalid `{}`", field),
+            DecodeError::InvalidSignature => write!(f, "invalid signature"),
+        }
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+impl From<RlpError> for DecodeError {
+    fn from(e: RlpError) -> Self {
+        DecodeError::Rlp(e)
+    }
+}
+
+/// A decoded signed transaction.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RawTransaction {
+    pub tx_type: TxType,
+    /// The hash the transaction is known by once broadcast.
+    pub hash: H256,
+    /// Recovered from the signature.
+    pub from: Address,
+    /// `None` for legacy transactions signed without EIP-155.
+    pub chain_id: Option<U256>,
+    pub nonce: U256,
+    pub gas: U256,
+    /// Legacy and EIP-2930 transactions only.
+    pub gas_price: Option<U256>,
+    pub max_fee_per_gas: Option<U256>,
+    pub max_priority_fee_per_gas: Option<U256>,
+    /// `None` for contract creation.
+    pub to: Option<Address>,
+    pub value: U256,
+    pub data: Vec<u8>,
+}
+
+impl RawTransaction {
+    pub fn decode(raw: &[u8]) -> Result<Self, DecodeError> {
+        match raw.first() {
+            None => Err(RlpError::Truncated.into()),
+            Some(0xc0..=0xff) => Self::decode_legacy(raw),
+            Some(&ty @ 1..=4) => Self::decode_typed(ty, &raw[1..]),
+            Some(&ty) => Err(DecodeError::UnsupportedType(ty)),
+        }
+    }
+
+    /// The transaction as an eth_call or eth_estimateGas request: sender, recipient, value
+    /// and data, leaving gas and fees to the node.
+    pub fn call_request(&self) -> TransactionRequest {
+        TransactionRequest {
+            from: Some(self.from),
+            to: self.to,
+            value: Some(self.value),
+            data: Some(hex::data(&self.data)),
+            ..TransactionRequest::default()
+        }
+    }
+
+    // [nonce, gasPrice, gas, to, value, data, v, r, s]
+    fn decode_legacy(raw: &[u8]) -> Result<Self, DecodeError> {
+        let fields = rlp::decode_list(raw)?;
+        expect_fields(&fields, 9)?;
+        let (chain_id, parity) = match rlp::decode_u64(fields[6])? {
+            v @ (27 | 28) => (None, v - 27),
+            v if v >= 35 => (Some((v - 35) / 2), (v - 35) % 2),
+            _ => return Err(DecodeError::InvalidField("v")),
+        };
+        let mut unsigned = fields[..6].concat();
+        if let Some(chain_id) = chain_id {
+            rlp::append_u64(&mut unsigned, chain_id);
+            unsigned.extend([0x80, 0x80]);
+        }
+        let from = recover(
+            &keccak256(&rlp::list(&unsigned)),
+            parity,
+            fields[7],
+            fields[8],
+        )?;
+        Ok(RawTransaction {
+            tx_type: TxType::Legacy,
+            hash: H256::from(keccak256(raw)),
+            from,
+            chain_id: chain_id.map(U256::from),
+            nonce: rlp::decode_u256(fields[0])?,
+            gas: rlp::decode_u256(fields[2])?,
+            gas_price: Some(rlp::decode_u256(fields[1])?),
+            max_fee_per_gas: None,
+            max_priority_fee_per_gas: None,
+            to: decode_to(fields[3])?,
+            value: rlp::decode_u256(fields[4])?,
+            data: rlp::decode_bytes(fields[5])?.to_vec(),
+        })
+    }
+
+    // type 1: [chainId, nonce, gasPrice, gas, to, value, data, accessList, y, r, s]
+    // type 2: [chainId, nonce, maxPriorityFee, maxFee, gas, to, value, data, accessList, y, r, s]
+    // type 3 adds maxFeePerBlobGas and blobVersionedHashes, type 4 an authorizationList,
+    // before the signature
+    fn decode_typed(ty: u8, body: &[u8]) -> Result<Self, DecodeError> {
+        let mut signed = body;
+        let mut fields = rlp::decode_list(body)?;
+        // the network form of a blob transaction: [tx, blobs, commitments, proofs]
+        if ty == 3 && fields.first().is_some_and(|first| first[0] >= 0xc0) {
+            signed = fields[0];
+            fields = rlp::decode_list(signed)?;
+        }
+        expect_fields(
+            &fields,
+            match ty {
+                1 => 11,
+                2 => 12,
+                3 => 14,
+                _ => 13,
+            },
+        )?;
+        let n = fields.len();
+        let parity = match rlp::decode_u64(fields[n - 3])? {
+            parity @ (0 | 1) => parity,
+            _ => return Err(DecodeError::InvalidField("yParity")),
+        };
+        let unsigned = [&[ty][..], &rlp::list(&fields[..n - 3].concat())].concat();
+        let from = recover(&keccak256(&unsigned), parity, fields[n - 2], fields[n - 1])?;
+
+        let (gas_price, max_priority_fee_per_gas, max_fee_per_gas, rest) = if ty == 1 {
+            (Some(rlp::decode_u256(fields[2])?), None, None, &fields[3..])
+        } else {
+            let priority = rlp::decode_u256(fields[2])?;
+            let max = rlp::decode_u256(fields[3])?;
+            (None, Some(priority), Some(max), &fields[4..])
+        };
+        let to = decode_to(rest[1])?;
+        if ty >= 3 && to.is_none() {
+            return Err(DecodeError::InvalidField("to"));
+        }
+        Ok(RawTransaction {
+            tx_type: TxType::from_number(ty.into()),
+            hash: H256::from(keccak256(&[&[ty][..], signed].concat())),
+            from,
+            chain_id: Some(rlp::decode_u256(fields[0])?),
+            nonce: rlp::decode_u256(fields[1])?,
+            gas: rlp::decode_u256(rest[0])?,
+            gas_price,
+            max_fee_per_gas,
+            max_priority_fee_per_gas,
+            to,
+            value: rlp::decode_u256(rest[2])?,
+            data: rlp::decode_bytes(rest[3])?.to_vec(),
+        })
+    }
+}
+
+fn expect_fields(fields: &[&[u8]], expected: usize) -> Result<(), DecodeError> {
+    if fields.len() != expected {
+        return Err(DecodeError::FieldCount {
+            expected,
+            found: fields.len(),
+        });
+    }
+    Ok(())
+}
+
+fn decode_to(item: &[u8]) -> Result<Option<Address>, DecodeError> {
+    match rlp::decode_bytes(item)? {
+        [] => Ok(None),
+        bytes => <[u8; 20]>::try_from(bytes)
+            .map(|b| Some(Address::from(b)))
+            .map_err(|_| DecodeError::InvalidField("to")),
+    }
+}
+
+fn recover(prehash: &[u8; 32], parity: u64, r: &[u8], s: &[u8]) -> Result<Address, DecodeError> {
+    let (r, s) = (rlp::decode_u256(r)?, rlp::decode_u256(s)?);
+    let signature = Signature::from_scalars(r.to_be_bytes(), s.to_be_bytes())
+        .map_err(|_| DecodeError::InvalidSignature)?;
+    let recovery_id = RecoveryId::from_byte(parity as u8).ok_or(DecodeError::InvalidSignature)?;
+    let key = VerifyingKey::recover_from_prehash(prehash, &signature, recovery_id)
+        .map_err(|_| DecodeError::InvalidSignature)?;
+    let hash = keccak256(&key.to_encoded_point(false).as_bytes()[1..]);
+    let mut address = [0u8; 20];
+    address.copy_from_slice(&hash[12..]);
+    Ok(Address::from(address))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hex;
+    use k256::ecdsa::SigningKey;
+
+    // the EIP-155 example, signed by the key 0x4646...46
+    const EIP155: &str = "0xf86c098504a817c800825208943535353535353535353535353535353535353535880de0b6b3a76400008025a028ef61340bd939bc2195fe537567866003e1a15d3c71ff63e1590620aa636276a067cbe9d8997f761aecb703304b3800ccf555c9f3dc64214b297fb1966a3b6d83";
+
+    fn signer() -> Address {
+        "0x9d8a62f656a8d1615c1294fd71e9cfb3e4855a4f"
+            .parse()
+            .unwrap()
+    }
+
+    #[test]
+    fn decodes_eip155_legacy() {
+        let raw = hex::parse_data(EIP155).unwrap();
+        let tx = RawTransaction::decode(&raw).unwrap();
+        assert_eq!(tx.tx_type, TxType::Legacy);
+        assert_eq!(tx.from, signer());
+        assert_eq!(tx.chain_id, Some(U256::from(1u64)));
+        assert_eq!(tx.nonce, U256::from(9u64));
+        assert_eq!(tx.gas_price, Some(U256::from(20_000_000_000u64)));
+        assert_eq!(tx.gas, U256::from(21_000u64));
+        assert_eq!(
+            tx.to,
+            Some(
+                "0x3535353535353535353535353535353535353535"
+                    .parse()
+                    .unwrap()
+            )
+        );
+        assert_eq!(tx.value, U256::from(1_000_000_000_000_000_000u64));
+        assert!(tx.data.is_empty());
+        assert_eq!(tx.hash, H256::from(keccak256(&raw)));
+    }
+
+    /// Signs a type-2 transaction with the EIP-155 example key.
+    fn sign_dynamic_fee(to: &[u8], data: &[u8]) -> Vec<u8> {
+        let mut fields = Vec::new();
+        rlp::append_u64(&mut fields, 1);
+        rlp::append_u64(&mut fields, 3);
+        rlp::append_u64(&mut fields, 1_000_000_000);
+        rlp::append_u64(&mut fields, 30_000_000_000);
+        rlp::append_u64(&mut fields, 100_000);
+        rlp::append_bytes(&mut fields, to);
+        rlp::append_u64(&mut fields, 5);
+        rlp::append_bytes(&mut fields, data);
+        fields.extend(rlp::list(&[]));
+        let unsigned = [&[2u8][..], &rlp::list(&fields)].concat();
+
+        let key = SigningKey::from_slice(&[0x46; 32]).unwrap();
+        let (signature, recovery_id) = key.sign_prehash_recoverable(&keccak256(&unsigned)).unwrap();
+        rlp::append_u64(&mut fields, recovery_id.to_byte().into());
+        rlp::append_uint(&mut fields, &signature.r().to_bytes());
+        rlp::append_uint(&mut fields, &signature.s().to_bytes());
+        [&[2u8][..], &rlp::list(&fields)].concat()
+    }
+
+    #[test]
+    fn decodes_dynamic_fee() {
+        let raw = sign_dynamic_fee(&[0x35; 20], &[0xa9, 0x05, 0x9c, 0xbb]);
+        let tx = RawTransaction::decode(&raw).unwrap();
+        assert_eq!(tx.tx_type, TxType::DynamicFee);
+        assert_eq!(tx.from, signer());
+        assert_eq!(tx.chain_id, Some(U256::from(1u64)));
+        assert_eq!(tx.nonce, U256::from(3u64));
+        assert_eq!(tx.gas_price, None);
+        assert_eq!(
+            tx.max_priority_fee_per_gas,
+            Some(U256::from(1_000_000_000u64))
+        );
+        assert_eq!(tx.max_fee_per_gas, Some(U256::from(30_000_000_000u64)));
+        assert_eq!(tx.gas, U256::from(100_000u64));
+        assert_eq!(tx.to, Some(Address::from([0x35; 20])));
+        assert_eq!(tx.value, U256::from(5u64));
+        assert_eq!(tx.data, [0xa9, 0x05, 0x9c, 0xbb]);
+        assert_eq!(tx.hash, H256::from(keccak256(&raw)));
+
+        let creation = RawTransaction::decode(&sign_dynamic_fee(&[], &[0x60, 0x00])).unwrap();
+        assert_eq!(creation.to, None);
+        assert_eq!(creation.from, signer());
+    }
+
+    #[test]
+    fn rejects_malformed_transactions() {
+        let mut raw = hex::parse_data(EIP155).unwrap();
+        assert_eq!(
+            RawTransaction::decode(&[0x05, 0xc0]),
+            Err(DecodeError::UnsupportedType(5))
+        );
+        assert_eq!(
+            RawTransaction::decode(&[0x02, 0xc0]),
+            Err(DecodeError::FieldCount {
+                expected: 12,
+                found: 0
+            })
+        );
+        // v = 0x25 becomes 0x1d, neither 27/28 nor EIP-155
+        let v = raw.len() - 67;
+        raw[v] = 0x1d;
+        assert_eq!(
+            RawTransaction::decode(&raw),
+            Err(DecodeError::InvalidField("v"))
+        );
+        raw.truncate(20);
+        assert_eq!(
+            RawTransaction::decode(&raw),
+            Err(DecodeError::Rlp(RlpError::Truncated))
+        );
+    }
+}
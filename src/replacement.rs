@@ -0,0 +1,228 @@
+//! Replacing a stuck transaction with the same one paying more, so that miners take it in the
+//! original's place.
+
+use crate::client::Error;
+use crate::hex;
+use crate::model::{Transaction, TransactionRequest, TxType};
+use crate::send::{parse_tx_hash, TransactionSigner};
+use crate::types::{H256, U256};
+use crate::web3::Web3;
+use error_stack::{Report, ResultExt};
+
+/// The least a replacement must raise every fee by for geth's pool (and most others) to take
+/// it instead of answering "replacement transaction underpriced".
+pub const MIN_REPLACEMENT_BUMP_PERCENT: u64 = 10;
+
+/// Node rejections of a transaction whose nonce the sender has used.
+const NONCE_USED: &[&str] = &["nonce too low", "oldnonce"];
+
+impl Web3 {
+    /// Resends the pending transaction `original` with its fees raised by `fee_bump_percent`,
+    /// at least [`MIN_REPLACEMENT_BUMP_PERCENT`], signed by `signer`. Returns the hash of the
+    /// replacement.
+    ///
+    /// Nonce, recipient, value, data, gas and access list are the original's; an EIP-1559
+    /// transaction has both `maxFeePerGas` and `maxPriorityFeePerGas` raised, a legacy one its
+    /// `gasPrice`. Fails with [`Error::AlreadyMined`] if the original is in a block, or
+    /// lands between the lookup and the broadcast, and with [`Error::TransactionDropped`] if
+    /// the node does not know it. Blob transactions cannot be rebuilt without their blobs and
+    /// are refused.
+    pub async fn speed_up(
+        &self,
+        original: H256,
+        fee_bump_percent: u64,
+        signer: &dyn TransactionSigner,
+    ) -> Result<H256, Report<Error>> {
+        let tx = self
+            .eth_get_transaction_by_hash(original)
+            .await?
+            .result
+            .ok_or_else(|| {
+                Report::new(Error::TransactionDropped { tx_hash: original })
+                    .attach_printable("the node does not know the transaction to replace")
+            })?;
+        if tx.block_hash.is_some() {
+            return Err(
+                Report::new(Error::AlreadyMined { tx_hash: original }).attach_printable(format!(
+                    "in block {}",
+                    tx.block_number.as_deref().unwrap_or("?")
+                )),
+            );
+        }
+        if tx.from != signer.address() {
+            return Err(
+                Report::new(Error::InvalidTransactionRequest).attach_printable(format!(
+                    "{} was sent by {}, the signer is {}",
+                    original,
+                    tx.from,
+                    signer.address()
+                )),
+            );
+        }
+
+        let mut request = replacement(&tx, fee_bump_percent.max(MIN_REPLACEMENT_BUMP_PERCENT))?;
+        if request.chain_id.is_none() {
+            // pre-EIP-155 legacy transactions have none; sign the replacement for this chain
+            let chain_id = self.eth_chain_id().await?.result.ok_or_else(|| {
+                Report::new(Error::UnexpectedResponseFormat)
+                    .attach_printable("eth_chainId returned null")
+            })?;
+            request.chain_id = Some(chain_id);
+        }
+        let raw = signer
+            .sign_transaction(&request)
+            .map_err(|e| Report::new(Error::SigningFailed).attach_printable(e.to_string()))?;
+
+        let hash = match self.eth_send_raw_transaction(&hex::data(&raw)).await {
+            Ok(r) => r.result,
+            Err(report) if nonce_used(report.current_context()) => {
+                return Err(report
+                    .change_context(Error::AlreadyMined { tx_hash: original })
+                    .attach_printable("mined before its replacement was broadcast"));
+            }
+            Err(report) => return Err(report),
+        };
+        parse_tx_hash(hash)
+    }
+}
+
+/// `tx` with its fees raised by `percent`, everything else unchanged.
+fn replacement(tx: &Transaction, percent: u64) -> Result<TransactionRequest, Report<Error>> {
+    let field = |value: &str, what: &str| -> Result<U256, Report<Error>> {
+        value
+            .parse::<U256>()
+            .change_context(Error::UnexpectedResponseFormat)
+            .attach_printable_lazy(|| format!("{} `{}` of {}", what, value, tx.hash))
+    };
+    let bumped = |value: &str, what: &str| -> Result<U256, Report<Error>> {
+        bump(field(value, what)?, percent).ok_or_else(|| {
+            Report::new(Error::InvalidTransactionRequest)
+                .attach_printable(format!("{} raised by {}% overflows", what, percent))
+        })
+    };
+
+    let mut request = TransactionRequest {
+        from: Some(tx.from),
+        to: tx.to,
+        gas: Some(field(&tx.gas, "gas")?),
+        value: Some(field(&tx.value, "value")?),
+        data: Some(tx.input.clone()),
+        nonce: Some(field(&tx.nonce, "nonce")?),
+        access_list: tx.access_list.clone(),
+        chain_id: tx
+            .chain_id
+            .as_deref()
+            .map(|id| field(id, "chain id"))
+            .transpose()?,
+        ..TransactionRequest::default()
+    };
+    match tx.tx_type() {
+        Some(TxType::Legacy | TxType::AccessList) => {
+            let gas_price = tx.gas_price.as_deref().ok_or_else(|| {
+                Report::new(Error::UnexpectedResponseFormat)
+                    .attach_printable(format!("{} has no gasPrice", tx.hash))
+            })?;
+            request.gas_price = Some(bumped(gas_price, "gasPrice")?);
+        }
+        Some(TxType::DynamicFee) => {
+            let (Some(max_fee), Some(priority_fee)) = (
+                tx.max_fee_per_gas.as_deref(),
+                tx.max_priority_fee_per_gas.as_deref(),
+            ) else {
+                return Err(Report::new(Error::UnexpectedResponseFormat)
+                    .attach_printable(format!("{} has no EIP-1559 fees", tx.hash)));
+            };
+            request.max_fee_per_gas = Some(bumped(max_fee, "maxFeePerGas")?);
+            request.max_priority_fee_per_gas = Some(bumped(priority_fee, "maxPriorityFeePerGas")?);
+        }
+        Some(other) => {
+            return Err(Report::new(Error::InvalidTransactionRequest)
+                .attach_printable(format!("cannot replace a {:?} transaction", other)));
+        }
+        None => {
+            return Err(Report::new(Error::UnexpectedResponseFormat)
+                .attach_printable(format!("{} has an invalid type", tx.hash)));
+        }
+    }
+    if let Some(tx_type) = tx.transaction_type.as_deref() {
+        request.transaction_type = Some(field(tx_type, "type")?);
+    }
+    Ok(request)
+}
+
+/// `fee` raised by `percent`, rounded up so that a small fee still rises by the percentage.
+fn bump(fee: U256, percent: u64) -> Option<U256> {
+    let scaled = fee.checked_mul(U256::from(100 + percent))?;
+    let hundred = U256::from(100u64);
+    let raised = scaled.checked_div(hundred)?;
+    if scaled.checked_rem(hundred)? == U256::from(0u64) {
+        Some(raised)
+    } else {
+        raised.checked_add(U256::from(1u64))
+    }
+}
+
+fn nonce_used(error: &Error) -> bool {
+    match error {
+        Error::JsonRpcError { message, .. } => {
+            let message = message.to_lowercase();
+            NONCE_USED.iter().any(|known| message.contains(known))
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture;
+
+    #[test]
+    fn fees_round_up() {
+        assert_eq!(bump(U256::from(100u64), 10), Some(U256::from(110u64)));
+        assert_eq!(bump(U256::from(1u64), 10), Some(U256::from(2u64)));
+        assert_eq!(bump(U256::from(0u64), 10), Some(U256::from(0u64)));
+        assert_eq!(bump(U256::MAX, 10), None);
+    }
+
+    #[test]
+    fn replacements_keep_everything_but_fees() {
+        let pending = fixture("tx_pending");
+        let request = replacement(&pending, 12).unwrap();
+        assert_eq!(request.nonce, Some(U256::from(0x1a2b4u64)));
+        assert_eq!(request.to, pending.to);
+        assert_eq!(request.data.as_deref(), Some("0x"));
+        assert_eq!(
+            request.max_fee_per_gas,
+            Some(U256::from(0x6fc23ac00u64 * 112 / 100))
+        );
+        assert_eq!(
+            request.max_priority_fee_per_gas,
+            Some(U256::from(1_120_000_000u64))
+        );
+        assert_eq!(request.gas_price, None);
+        assert_eq!(request.transaction_type, Some(U256::from(2u64)));
+
+        let legacy = replacement(&fixture("tx_legacy"), 10).unwrap();
+        assert!(legacy.gas_price.is_some());
+        assert_eq!(legacy.max_fee_per_gas, None);
+
+        assert!(replacement(&fixture("tx_eip4844"), 10).is_err());
+    }
+
+    #[test]
+    fn used_nonces() {
+        let rpc_error = |message: &str| Error::JsonRpcError {
+            code: -32000,
+            message: message.to_string(),
+            data: None,
+        };
+        assert!(nonce_used(&rpc_error(
+            "nonce too low: next nonce 5, tx nonce 4"
+        )));
+        assert!(nonce_used(&rpc_error("OldNonce")));
+        assert!(!nonce_used(&rpc_error(
+            "replacement transaction underpriced"
+        )));
+    }
+}
@@ -0,0 +1,264 @@
+//! Minimal RLP encoding and decoding, enough to hash headers, derive addresses and read
+//! signed transactions.
+//!
+//! Items are built up into a [`Vec<u8>`] with the `append_*` functions and wrapped with
+//! [`list`]. Decoding is lazy: [`decode_list`] splits a list into its still-encoded items,
+//! which [`decode_bytes`] and [`decode_u256`] then read.
+
+use crate::types::U256;
+use std::fmt::{self, Display, Formatter};
+
+/// Appends `bytes` as an RLP string.
+pub fn append_bytes(out: &mut Vec<u8>, bytes: &[u8]) {
+    match bytes {
+        [b] if *b < 0x80 => out.push(*b),
+        _ => {
+            append_length(out, 0x80, bytes.len());
+            out.extend_from_slice(bytes);
+        }
+    }
+}
+
+/// Appends an unsigned integer given as big-endian bytes, minus its leading zeros; zero is
+/// the empty string.
+pub fn append_uint(out: &mut Vec<u8>, be_bytes: &[u8]) {
+    let start = be_bytes
+        .iter()
+        .position(|b| *b != 0)
+        .unwrap_or(be_bytes.len());
+    append_bytes(out, &be_bytes[start..]);
+}
+
+pub fn append_u64(out: &mut Vec<u8>, n: u64) {
+    append_uint(out, &n.to_be_bytes());
+}
+
+/// Wraps already-encoded `items` as an RLP list.
+pub fn list(items: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(items.len() + 9);
+    append_length(&mut out, 0xc0, items.len());
+    out.extend_from_slice(items);
+    out
+}
+
+fn append_length(out: &mut Vec<u8>, offset: u8, len: usize) {
+    if len < 56 {
+        out.push(offset + len as u8);
+    } else {
+        let be = (len as u64).to_be_bytes();
+        let start = be.iter().position(|b| *b != 0).unwrap_or(7);
+        out.push(offset + 55 + (8 - start) as u8);
+        out.extend_from_slice(&be[start..]);
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RlpError {
+    /// The input ends inside an item.
+    Truncated,
+    /// Bytes left over after the item.
+    TrailingBytes,
+    ExpectedList,
+    ExpectedString,
+    /// A length or integer with leading zeros, or a single byte wrapped as a string.
+    NonCanonical,
+    /// An integer wider than the target type.
+    Overflow,
+}
+
+impl Display for RlpError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            RlpError::Truncated => write!(f, "RLP input is truncated"),
+            RlpError::TrailingBytes => write!(f, "trailing bytes after RLP item"),
+            RlpError::ExpectedList => write!(f, "expected an RLP list"),
+            RlpError::ExpectedString => write!(f, "expected an RLP string"),
+            RlpError::NonCanonical => write!(f, "non-canonical RLP encoding"),
+            RlpError::Overflow => write!(f, "RLP integer overflows"),
+        }
+    }
+}
+
+impl std::error::Error for RlpError {}
+
+/// The items of the list that makes up all of `encoded`, each still encoded.
+pub fn decode_list(encoded: &[u8]) -> Result<Vec<&[u8]>, RlpError> {
+    let (is_list, mut payload) = decode_exact(encoded)?;
+    if !is_list {
+        return Err(RlpError::ExpectedList);
+    }
+    let mut items = Vec::new();
+    while !payload.is_empty() {
+        let (_, _, len) = split(payload)?;
+        let (item, rest) = payload.split_at(len);
+        items.push(item);
+        payload = rest;
+    }
+    Ok(items)
+}
+
+/// The payload of the string that makes up all of `encoded`.
+pub fn decode_bytes(encoded: &[u8]) -> Result<&[u8], RlpError> {
+    match decode_exact(encoded)? {
+        (false, payload) => Ok(payload),
+        (true, _) => Err(RlpError::ExpectedString),
+    }
+}
+
+/// An unsigned integer, which must not have leading zeros.
+pub fn decode_u256(encoded: &[u8]) -> Result<U256, RlpError> {
+    let bytes = decode_bytes(encoded)?;
+    if bytes.first() == Some(&0) {
+        return Err(RlpError::NonCanonical);
+    }
+    if bytes.len() > 32 {
+        return Err(RlpError::Overflow);
+    }
+    let mut be = [0u8; 32];
+    be[32 - bytes.len()..].copy_from_slice(bytes);
+    Ok(U256::from_be_bytes(be))
+}
+
+pub fn decode_u64(encoded: &[u8]) -> Result<u64, RlpError> {
+    decode_u256(encoded)?
+        .try_into()
+        .map_err(|_| RlpError::Overflow)
+}
+
+fn decode_exact(encoded: &[u8]) -> Result<(bool, &[u8]), RlpError> {
+    let (is_list, header, len) = split(encoded)?;
+    if len != encoded.len() {
+        return Err(RlpError::TrailingBytes);
+    }
+    Ok((is_list, &encoded[header..]))
+}
+
+/// Reads the header of the item at the front of `data`: whether it is a list, the header
+/// length and the length of the whole item.
+fn split(data: &[u8]) -> Result<(bool, usize, usize), RlpError> {
+    let first = *data.first().ok_or(RlpError::Truncated)?;
+    let (is_list, header, payload) = match first {
+        0x00..=0x7f => (false, 0, 1),
+        0x80..=0xb7 => {
+            let len = (first - 0x80) as usize;
+            if len == 1 && data.get(1).is_some_and(|b| *b < 0x80) {
+                return Err(RlpError::NonCanonical);
+            }
+            (false, 1, len)
+        }
+        0xb8..=0xbf => {
+            let len_of_len = (first - 0xb7) as usize;
+            (false, 1 + len_of_len, long_length(&data[1..], len_of_len)?)
+        }
+        0xc0..=0xf7 => (true, 1, (first - 0xc0) as usize),
+        0xf8..=0xff => {
+            let len_of_len = (first - 0xf7) as usize;
+            (true, 1 + len_of_len, long_length(&data[1..], len_of_len)?)
+        }
+    };
+    let len = header.checked_add(payload).ok_or(RlpError::Overflow)?;
+    if len > data.len() {
+        return Err(RlpError::Truncated);
+    }
+    Ok((is_list, header, len))
+}
+
+fn long_length(data: &[u8], len_of_len: usize) -> Result<usize, RlpError> {
+    let bytes = data.get(..len_of_len).ok_or(RlpError::Truncated)?;
+    if bytes[0] == 0 {
+        return Err(RlpError::NonCanonical);
+    }
+    if len_of_len > std::mem::size_of::<usize>() {
+        return Err(RlpError::Overflow);
+    }
+    let len = bytes.iter().fold(0usize, |n, b| (n << 8) | *b as usize);
+    if len < 56 {
+        return Err(RlpError::NonCanonical);
+    }
+    Ok(len)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // examples from the RLP section of the Ethereum wiki
+    #[test]
+    fn spec_vectors() {
+        let string = |b: &[u8]| {
+            let mut out = Vec::new();
+            append_bytes(&mut out, b);
+            out
+        };
+        let uint = |n: u64| {
+            let mut out = Vec::new();
+            append_u64(&mut out, n);
+            out
+        };
+        assert_eq!(string(b"dog"), b"\x83dog");
+        assert_eq!(string(b""), [0x80]);
+        assert_eq!(string(&[0x00]), [0x00]);
+        assert_eq!(string(&[0x0f]), [0x0f]);
+        assert_eq!(string(&[0x80]), [0x81, 0x80]);
+        assert_eq!(uint(0), [0x80]);
+        assert_eq!(uint(15), [0x0f]);
+        assert_eq!(uint(1024), [0x82, 0x04, 0x00]);
+
+        let lorem = b"Lorem ipsum dolor sit amet, consectetur adipisicing elit";
+        assert_eq!(string(lorem)[..2], [0xb8, 0x38]);
+        assert_eq!(&string(lorem)[2..], lorem);
+
+        let mut items = string(b"cat");
+        items.extend(string(b"dog"));
+        assert_eq!(list(&items), b"\xc8\x83cat\x83dog");
+        assert_eq!(list(&[]), [0xc0]);
+        // [ [], [[]], [ [], [[]] ] ]
+        let set = list(
+            &[
+                list(&[]),
+                list(&list(&[])),
+                list(&[list(&[]), list(&list(&[]))].concat()),
+            ]
+            .concat(),
+        );
+        assert_eq!(set, [0xc7, 0xc0, 0xc1, 0xc0, 0xc3, 0xc0, 0xc1, 0xc0]);
+    }
+
+    #[test]
+    fn decodes_what_it_encodes() {
+        let lorem = b"Lorem ipsum dolor sit amet, consectetur adipisicing elit";
+        let mut items = Vec::new();
+        append_bytes(&mut items, b"dog");
+        append_bytes(&mut items, lorem);
+        append_u64(&mut items, 1024);
+        append_u64(&mut items, 0);
+        items.extend(list(&[]));
+        let encoded = list(&items);
+
+        let decoded = decode_list(&encoded).unwrap();
+        assert_eq!(decoded.len(), 5);
+        assert_eq!(decode_bytes(decoded[0]).unwrap(), b"dog");
+        assert_eq!(decode_bytes(decoded[1]).unwrap(), lorem);
+        assert_eq!(decode_u64(decoded[2]).unwrap(), 1024);
+        assert_eq!(decode_u64(decoded[3]).unwrap(), 0);
+        assert_eq!(decode_list(decoded[4]).unwrap(), Vec::<&[u8]>::new());
+        assert_eq!(decode_bytes(decoded[4]), Err(RlpError::ExpectedString));
+    }
+
+    #[test]
+    fn rejects_malformed_input() {
+        assert_eq!(decode_list(&[]), Err(RlpError::Truncated));
+        assert_eq!(decode_list(b"\xc8\x83cat\x83do"), Err(RlpError::Truncated));
+        assert_eq!(decode_bytes(b"\x83dogs"), Err(RlpError::TrailingBytes));
+        assert_eq!(decode_list(b"\x83dog"), Err(RlpError::ExpectedList));
+        // a single byte below 0x80 is its own encoding
+        assert_eq!(decode_bytes(&[0x81, 0x05]), Err(RlpError::NonCanonical));
+        // a short string in the long form
+        assert_eq!(decode_bytes(b"\xb8\x03dog"), Err(RlpError::NonCanonical));
+        assert_eq!(decode_u64(&[0x82, 0x00, 0x01]), Err(RlpError::NonCanonical));
+        assert_eq!(
+            decode_u64(&[0x89, 1, 0, 0, 0, 0, 0, 0, 0, 0]),
+            Err(RlpError::Overflow)
+        );
+    }
+}
@@ -0,0 +1,367 @@
+//! Submitting transactions, from an account the node manages or through a local signer, and
+//! waiting for them to be mined.
+//!
+//! The crate does no signing itself; implement [`TransactionSigner`] over whatever key
+//! storage is in use. Already signed transactions can be simulated before broadcast with
+//! [`Web3::send_raw_transaction_checked`].
+
+use crate::client::Error;
+use crate::hex;
+use crate::model::{Receipt, StateOverride, Tag, TransactionRequest};
+use crate::raw_transaction::RawTransaction;
+use crate::types::{Address, H256, U256};
+use crate::web3::Web3;
+use error_stack::{Report, ResultExt};
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// How often [`Web3::wait_for_receipt`] polls unless told otherwise.
+pub const DEFAULT_RECEIPT_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Signs transactions with a key held by the caller.
+pub trait TransactionSigner: Send + Sync {
+    fn address(&self) -> Address;
+
+    /// The signed, RLP-encoded transaction for `request`, whose nonce, chain id, gas and fee
+    /// fields are all set.
+    fn sign_transaction(
+        &self,
+        request: &TransactionRequest,
+    ) -> Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>>;
+}
+
+/// Who sends a transaction.
+#[derive(Clone, Copy)]
+pub enum Sender<'a> {
+    /// An account unlocked on the node, as on anvil or a geth dev chain; sent with
+    /// eth_sendTransaction and signed by the node.
+    Node(Address),
+    /// Signed locally and sent with eth_sendRawTransaction.
+    Signer(&'a dyn TransactionSigner),
+}
+
+impl Sender<'_> {
+    pub fn address(&self) -> Address {
+        match self {
+            Sender::Node(address) => *address,
+            Sender::Signer(signer) => signer.address(),
+        }
+    }
+}
+
+impl From<Address> for Sender<'_> {
+    fn from(address: Address) -> Self {
+        Sender::Node(address)
+    }
+}
+
+impl<'a> From<&'a dyn TransactionSigner> for Sender<'a> {
+    fn from(signer: &'a dyn TransactionSigner) -> Self {
+        Sender::Signer(signer)
+    }
+}
+
+/// What [`Web3::send_and_confirm`] broadcasts.
+pub enum Submission<'a> {
+    /// A signed transaction, `0x`-prefixed, sent as is with eth_sendRawTransaction.
+    Raw(&'a str),
+    /// Sent with [`Web3::submit_transaction`].
+    Request(Box<TransactionRequest>, Sender<'a>),
+}
+
+impl<'a> From<&'a str> for Submission<'a> {
+    fn from(raw: &'a str) -> Self {
+        Submission::Raw(raw)
+    }
+}
+
+impl<'a> From<(TransactionRequest, Sender<'a>)> for Submission<'a> {
+    fn from((request, sender): (TransactionRequest, Sender<'a>)) -> Self {
+        Submission::Request(Box::new(request), sender)
+    }
+}
+
+impl Web3 {
+    /// Sends `request` from `sender` and returns the transaction hash.
+    ///
+    /// `from` is set to the sender and a missing `gas` is estimated. For a local signer a
+    /// missing nonce (the pending count), chain id and fee (`gasPrice` from eth_gasPrice, unless
+    /// EIP-1559 fees are set) are filled in before signing; the node fills them itself for its
+    /// own accounts.
+    pub async fn submit_transaction(
+        &self,
+        mut request: TransactionRequest,
+        sender: Sender<'_>,
+    ) -> Result<H256, Report<Error>> {
+        request.from = Some(sender.address());
+        request
+            .validate_send()
+            .change_context(Error::InvalidTransactionRequest)?;
+        if request.gas.is_none() {
+            let gas = self
+                .quantity("eth_estimateGas", serde_json::json!([request]))
+                .await?;
+            request.gas = Some(gas);
+        }
+        let hash = match sender {
+            Sender::Node(_) => self.eth_send_transaction(&request).await?.result,
+            Sender::Signer(signer) => {
+                if request.nonce.is_none() {
+                    let nonce = self
+                        .quantity(
+                            "eth_getTransactionCount",
+                            serde_json::json!([sender.address(), Tag::Pending]),
+                        )
+                        .await?;
+                    request.nonce = Some(nonce);
+                }
+                if request.chain_id.is_none() {
+                    let chain_id = self.eth_chain_id().await?.result.ok_or_else(|| {
+                        Report::new(Error::UnexpectedResponseFormat)
+                            .attach_printable("eth_chainId returned null")
+                    })?;
+                    request.chain_id = Some(chain_id);
+                }
+                if request.gas_price.is_none() && request.max_fee_per_gas.is_none() {
+                    request.gas_price =
+                        Some(self.quantity("eth_gasPrice", serde_json::json!([])).await?);
+                }
+                let raw = signer.sign_transaction(&request).map_err(|e| {
+                    Report::new(Error::SigningFailed).attach_printable(e.to_string())
+                })?;
+                self.eth_send_raw_transaction(&hex::data(&raw))
+                    .await?
+                    .result
+            }
+        };
+        parse_tx_hash(hash)
+    }
+
+    /// Simulates the signed transaction `raw` with eth_call at the latest block and
+    /// broadcasts it only if the call succeeds. A revert fails with [`Error::WouldRevert`]
+    /// and nothing is sent; `force` broadcasts anyway, logging why the simulation failed.
+    ///
+    /// The call uses the decoded sender, recipient, value and data, with the sender's nonce
+    /// overridden to the transaction's so that a contract creation runs at the address it
+    /// will deploy to; nodes that reject state overrides get the plain call. It runs against
+    /// the current state, so a transaction that depends on earlier ones from the same sender
+    /// still in the mempool (spending an approval they grant, say) can be reported as
+    /// reverting.
+    pub async fn send_raw_transaction_checked(
+        &self,
+        raw: &str,
+        force: bool,
+    ) -> Result<H256, Report<Error>> {
+        let bytes = hex::parse_data(raw)
+            .change_context(Error::InvalidTransactionRequest)
+            .attach_printable("raw transaction is not 0x-prefixed hex")?;
+        let tx = RawTransaction::decode(&bytes).change_context(Error::InvalidTransactionRequest)?;
+        match self.simulate(&tx).await {
+            Ok(()) => {}
+            Err(e) if force => {
+                tracing::warn!(tx = %tx.hash, "broadcasting despite failed simulation: {}", e.current_context());
+            }
+            Err(e) => return Err(e.attach_printable(format!("{} was not broadcast", tx.hash))),
+        }
+        parse_tx_hash(self.eth_send_raw_transaction(raw).await?.result)
+    }
+
+    /// Broadcasts `tx` and waits until it is mined with `confirmations` blocks on top
+    /// (counting its own, so 1 returns as soon as it is mined), polling every
+    /// [`DEFAULT_RECEIPT_POLL_INTERVAL`].
+    ///
+    /// Each terminal outcome short of success has its own error: [`Error::TransactionReverted`]
+    /// with the revert reason replayed at the receipt's block, [`Error::TransactionDropped`]
+    /// once the node forgets the transaction and the sender's nonce moves past it, and
+    /// [`Error::Timeout`]. The receipt is fetched again on every poll, so one reorged out
+    /// before reaching the depth is waited for again.
+    pub async fn send_and_confirm<'a>(
+        &self,
+        tx: impl Into<Submission<'a>>,
+        confirmations: u64,
+        timeout: Duration,
+    ) -> Result<Receipt, Report<Error>> {
+        let deadline = Instant::now() + timeout;
+        let (tx_hash, mut sender_nonce) = match tx.into() {
+            Submission::Raw(raw) => {
+                // only a hint for dropped detection, the node has the last word on validity
+                let decoded = hex::parse_data(raw)
+                    .ok()
+                    .and_then(|bytes| RawTransaction::decode(&bytes).ok());
+                let hash = parse_tx_hash(self.eth_send_raw_transaction(raw).await?.result)?;
+                (hash, decoded.map(|tx| (tx.from, tx.nonce)))
+            }
+            Submission::Request(request, sender) => {
+                (self.submit_transaction(*request, sender).await?, None)
+            }
+        };
+        loop {
+            match self.eth_get_transaction_receipt(tx_hash).await?.result {
+                Some(receipt) => {
+                    let mined = hex_u64(&receipt.block_number, "receipt block number")?;
+                    let head = self
+                        .quantity("eth_blockNumber", serde_json::json!([]))
+                        .await?;
+                    let head = u64::try_from(head).unwrap_or(u64::MAX);
+                    if head.saturating_add(1) >= mined.saturating_add(confirmations) {
+                        if receipt.status == Some(false) {
+                            let reason = self.replay_revert_reason(tx_hash, &receipt).await;
+                            return Err(Report::new(Error::TransactionReverted {
+                                tx_hash,
+                                reason,
+                            })
+                            .attach_printable(format!("reverted in block {}", mined)));
+                        }
+                        return Ok(receipt);
+                    }
+                }
+                None => match self.eth_get_transaction_by_hash(tx_hash).await?.result {
+                    Some(pending) => {
+                        let nonce = hex_u64(&pending.nonce, "transaction nonce")?;
+                        sender_nonce = Some((pending.from, U256::from(nonce)));
+                    }
+                    None => {
+                        if let Some((from, nonce)) = sender_nonce {
+                            let count = self
+                                .quantity(
+                                    "eth_getTransactionCount",
+                                    serde_json::json!([from, Tag::Latest]),
+                                )
+                                .await?;
+                            if count > nonce {
+                                return Err(Report::new(Error::TransactionDropped { tx_hash })
+                                    .attach_printable(format!(
+                                        "nonce {} of {} was used",
+                                        nonce, from
+                                    )));
+                            }
+                        }
+                    }
+                },
+            }
+            if Instant::now() + DEFAULT_RECEIPT_POLL_INTERVAL > deadline {
+                return Err(Report::new(Error::Timeout).attach_printable(format!(
+                    "{} not confirmed {} deep after {:?}",
+                    tx_hash, confirmations, timeout
+                )));
+            }
+            tokio::time::sleep(DEFAULT_RECEIPT_POLL_INTERVAL).await;
+        }
+    }
+
+    /// Revert reason of a mined, reverted transaction, found by replaying it with eth_call
+    /// at its block. `None` when the replay fails for another reason or succeeds.
+    pub(crate) async fn replay_revert_reason(
+        &self,
+        tx_hash: H256,
+        receipt: &Receipt,
+    ) -> Option<String> {
+        let tx = self
+            .eth_get_transaction_by_hash(tx_hash)
+            .await
+            .ok()?
+            .result?;
+        let call = TransactionRequest {
+            from: Some(tx.from),
+            to: tx.to,
+            gas: tx.gas.parse().ok(),
+            value: tx.value.parse().ok(),
+            data: Some(tx.input),
+            ..TransactionRequest::default()
+        };
+        let block = hex_u64(&receipt.block_number, "receipt block number").ok()?;
+        let err = self.eth_call_request_at_block(&call, block).await.err()?;
+        match self.would_revert(err.current_context()) {
+            Some(Error::WouldRevert { reason, .. }) => reason,
+            _ => None,
+        }
+    }
+
+    async fn simulate(&self, tx: &RawTransaction) -> Result<(), Report<Error>> {
+        let request = tx.call_request();
+        let overrides = StateOverride::new().nonce(tx.from, tx.nonce);
+        let result = match self
+            .eth_call_with_overrides(&request, Tag::Latest, &overrides)
+            .await
+        {
+            Err(e) if e.current_context().is_invalid_params() => {
+                tracing::debug!("state overrides rejected, simulating without");
+                self.eth_call_request(&request).await
+            }
+            result => result,
+        };
+        match result {
+            Ok(_) => Ok(()),
+            Err(e) => match self.would_revert(e.current_context()) {
+                Some(revert) => Err(e.change_context(revert)),
+                None => Err(e),
+            },
+        }
+    }
+
+    /// Polls for the receipt of `tx_hash` until it is mined or `timeout` runs out. The
+    /// receipt is returned whatever its status.
+    pub async fn wait_for_receipt(
+        &self,
+        tx_hash: H256,
+        poll_interval: Duration,
+        timeout: Duration,
+    ) -> Result<Receipt, Report<Error>> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            if let Some(receipt) = self.eth_get_transaction_receipt(tx_hash).await?.result {
+                return Ok(receipt);
+            }
+            if Instant::now() + poll_interval > deadline {
+                return Err(Report::new(Error::Timeout)
+                    .attach_printable(format!("{} not mined after {:?}", tx_hash, timeout)));
+            }
+            tokio::time::sleep(poll_interval).await;
+        }
+    }
+
+    /// [`Error::WouldRevert`] for a reverted call.
+    fn would_revert(&self, error: &Error) -> Option<Error> {
+        if !error.is_execution_reverted() {
+            return None;
+        }
+        Some(Error::WouldRevert {
+            reason: self.client.revert_reason(error),
+            data: error.revert_data().map(str::to_string),
+        })
+    }
+
+    async fn quantity(
+        &self,
+        method: &str,
+        params: serde_json::Value,
+    ) -> Result<U256, Report<Error>> {
+        self.request::<U256>(method, params)
+            .await?
+            .result
+            .ok_or_else(|| {
+                Report::new(Error::UnexpectedResponseFormat)
+                    .attach_printable(format!("{} returned null", method))
+            })
+    }
+}
+
+fn hex_u64(quantity: &str, what: &str) -> Result<u64, Report<Error>> {
+    quantity
+        .strip_prefix("0x")
+        .and_then(|digits| u64::from_str_radix(digits, 16).ok())
+        .ok_or_else(|| {
+            Report::new(Error::FailedToDeserialize)
+                .attach_printable(format!("{} `{}`", what, quantity))
+        })
+}
+
+pub(crate) fn parse_tx_hash(hash: Option<String>) -> Result<H256, Report<Error>> {
+    let hash = hash.ok_or_else(|| {
+        Report::new(Error::UnexpectedResponseFormat)
+            .attach_printable("no transaction hash returned")
+    })?;
+    hash.parse::<H256>()
+        .change_context(Error::FailedToDeserialize)
+        .attach_printable_lazy(|| format!("transaction hash `{}`", hash))
+}
@@ -0,0 +1,188 @@
+//! Serde helpers for hex-encoded JSON-RPC quantities.
+//!
+//! Use with `#[serde(deserialize_with = "...", serialize_with = "...")]`; the
+//! `_opt` variants additionally need `#[serde(default)]` so absent fields map to `None`.
+
+use crate::hex;
+use serde::{de, Deserialize, Deserializer, Serializer};
+
+fn parse_hex_u64<E: de::Error>(s: &str) -> Result<u64, E> {
+    let digits = s
+        .strip_prefix("0x")
+        .ok_or_else(|| E::custom(format!("quantity `{}` is missing the 0x prefix", s)))?;
+    if digits.is_empty() {
+        return Err(E::custom("empty hex quantity"));
+    }
+    // from_str_radix alone would also accept a leading `+`
+    if let Some(c) = digits.chars().find(|c| !c.is_ascii_hexdigit()) {
+        return Err(E::custom(format!(
+            "invalid character `{}` in quantity `{}`",
+            c, s
+        )));
+    }
+    u64::from_str_radix(digits, 16)
+        .map_err(|e| E::custom(format!("invalid quantity `{}`: {}", s, e)))
+}
+
+pub fn deserialize_hex_u64<'de, D>(deserializer: D) -> Result<u64, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let s = String::deserialize(deserializer)?;
+    parse_hex_u64(&s)
+}
+
+pub fn deserialize_hex_u64_opt<'de, D>(deserializer: D) -> Result<Option<u64>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    match Option::<String>::deserialize(deserializer)? {
+        Some(s) => parse_hex_u64(&s).map(Some),
+        None => Ok(None),
+    }
+}
+
+pub fn serialize_hex_u64<S>(value: &u64, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    serializer.serialize_str(&hex::quantity((*value).into()))
+}
+
+pub fn serialize_hex_u64_opt<S>(value: &Option<u64>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    match value {
+        Some(v) => serialize_hex_u64(v, serializer),
+        None => serializer.serialize_none(),
+    }
+}
+
+fn parse_hex_u128<E: de::Error>(s: &str) -> Result<u128, E> {
+    let digits = s
+        .strip_prefix("0x")
+        .ok_or_else(|| E::custom(format!("quantity `{}` is missing the 0x prefix", s)))?;
+    if digits.is_empty() {
+        return Err(E::custom("empty hex quantity"));
+    }
+    if let Some(c) = digits.chars().find(|c| !c.is_ascii_hexdigit()) {
+        return Err(E::custom(format!(
+            "invalid character `{}` in quantity `{}`",
+            c, s
+        )));
+    }
+    u128::from_str_radix(digits, 16)
+        .map_err(|e| E::custom(format!("invalid quantity `{}`: {}", s, e)))
+}
+
+pub fn deserialize_hex_u128_opt<'de, D>(deserializer: D) -> Result<Option<u128>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    match Option::<String>::deserialize(deserializer)? {
+        Some(s) => parse_hex_u128(&s).map(Some),
+        None => Ok(None),
+    }
+}
+
+pub fn serialize_hex_u128_opt<S>(value: &Option<u128>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    match value {
+        Some(v) => serializer.serialize_str(&hex::quantity(*v)),
+        None => serializer.serialize_none(),
+    }
+}
+
+/// Receipt `status`: `"0x1"` is success, `"0x0"` is failure.
+pub fn deserialize_hex_bool_opt<'de, D>(deserializer: D) -> Result<Option<bool>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    match Option::<String>::deserialize(deserializer)? {
+        Some(s) => match parse_hex_u64::<D::Error>(&s)? {
+            0 => Ok(Some(false)),
+            1 => Ok(Some(true)),
+            _ => Err(de::Error::custom(format!("invalid status `{}`", s))),
+        },
+        None => Ok(None),
+    }
+}
+
+pub fn serialize_hex_bool_opt<S>(value: &Option<bool>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    match value {
+        Some(v) => serializer.serialize_str(if *v { "0x1" } else { "0x0" }),
+        None => serializer.serialize_none(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+    use serde::Serialize;
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Quantities {
+        #[serde(
+            deserialize_with = "deserialize_hex_u64",
+            serialize_with = "serialize_hex_u64"
+        )]
+        small: u64,
+        #[serde(
+            default,
+            deserialize_with = "deserialize_hex_u128_opt",
+            serialize_with = "serialize_hex_u128_opt"
+        )]
+        large: Option<u128>,
+    }
+
+    fn parse(small: &str) -> Result<u64, serde_json::Error> {
+        serde_json::from_value::<Quantities>(serde_json::json!({ "small": small })).map(|q| q.small)
+    }
+
+    #[test]
+    fn tricky_quantities() {
+        assert_eq!(parse("0x0").unwrap(), 0);
+        assert_eq!(parse("0x00").unwrap(), 0);
+        assert_eq!(parse("0xabc").unwrap(), 0xabc);
+        assert_eq!(parse("0xABC").unwrap(), 0xabc);
+        assert!(parse("0x").is_err());
+        assert!(parse("abc").is_err());
+        assert!(parse("0x+1").is_err());
+        assert!(parse("0x-1").is_err());
+        assert!(parse("0x 1").is_err());
+        assert!(parse("0x10000000000000000").is_err());
+    }
+
+    proptest! {
+        #[test]
+        fn quantities_round_trip_minimal(small: u64, large: u128) {
+            let value = Quantities { small, large: Some(large) };
+            let json = serde_json::to_value(&value).unwrap();
+            for field in ["small", "large"] {
+                let hex = json[field].as_str().unwrap();
+                let digits = hex.strip_prefix("0x").unwrap();
+                prop_assert!(digits == "0" || !digits.starts_with('0'), "{}", hex);
+            }
+            prop_assert_eq!(serde_json::from_value::<Quantities>(json).unwrap(), value);
+        }
+
+        #[test]
+        fn leading_zeros_are_accepted(n: u64, zeros in 0usize..16) {
+            let padded = format!("0x{}{:x}", "0".repeat(zeros), n);
+            prop_assert_eq!(parse(&padded).unwrap(), n);
+        }
+
+        #[test]
+        fn arbitrary_input_never_panics(s in "\\PC*") {
+            let _ = parse(&s);
+            let _ = parse(&format!("0x{}", s));
+        }
+    }
+}
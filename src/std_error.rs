@@ -0,0 +1,195 @@
+//! [`Report<Error>`] as a conventional error: [`Web3Error`] implements `std::error::Error`,
+//! so it goes into `anyhow`, `Box<dyn Error>` or another crate's error enum with `?`. The
+//! report's attachments and underlying errors become its `source()` chain.
+//!
+//! ```no_run
+//! # use web3_rpc::std_error::IntoStdResult;
+//! # use web3_rpc::web3::Web3;
+//! async fn client_version(rpc: &Web3) -> anyhow::Result<String> {
+//!     let version = rpc.web3_client_version().await.into_std()?;
+//!     Ok(version.result.unwrap_or_default())
+//! }
+//! ```
+
+use crate::client::Error;
+use error_stack::{AttachmentKind, FrameKind, Report};
+use serde_json::Value;
+
+/// What a [`Report<Error>`] was for, with what the report knew about it below.
+#[derive(Debug, Clone, thiserror::Error)]
+pub enum Web3Error {
+    /// The node answered with a JSON-RPC error object.
+    #[error("JSON-RPC error {code}: {message}")]
+    JsonRpc {
+        code: i64,
+        message: String,
+        data: Option<Value>,
+        #[source]
+        context: Option<Box<ContextMessage>>,
+    },
+    /// Any other [`Error`].
+    #[error("{kind}")]
+    Client {
+        kind: Error,
+        #[source]
+        context: Option<Box<ContextMessage>>,
+    },
+}
+
+/// One attachment or underlying error of the report, most recent first.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("{message}")]
+pub struct ContextMessage {
+    pub message: String,
+    #[source]
+    pub source: Option<Box<ContextMessage>>,
+}
+
+impl Web3Error {
+    /// The report's context, as [`Report::current_context`] had it.
+    pub fn kind(&self) -> Error {
+        match self {
+            Web3Error::JsonRpc {
+                code,
+                message,
+                data,
+                ..
+            } => Error::JsonRpcError {
+                code: *code,
+                message: message.clone(),
+                data: data.clone(),
+            },
+            Web3Error::Client { kind, .. } => kind.clone(),
+        }
+    }
+
+    /// Code of a JSON-RPC error response, `None` for everything else.
+    pub fn code(&self) -> Option<i64> {
+        match self {
+            Web3Error::JsonRpc { code, .. } => Some(*code),
+            Web3Error::Client { .. } => None,
+        }
+    }
+
+    /// The messages of the `source()` chain, most recent first.
+    pub fn context(&self) -> Vec<&str> {
+        let (Web3Error::JsonRpc { context, .. } | Web3Error::Client { context, .. }) = self;
+        let mut messages = Vec::new();
+        let mut next = context.as_deref();
+        while let Some(context) = next {
+            messages.push(context.message.as_str());
+            next = context.source.as_deref();
+        }
+        messages
+    }
+}
+
+impl From<Report<Error>> for Web3Error {
+    fn from(report: Report<Error>) -> Self {
+        // the first context frame is the one `current_context` returns, the rest is context
+        let mut seen_current = false;
+        let messages: Vec<String> = report
+            .frames()
+            .filter_map(|frame| match frame.kind() {
+                FrameKind::Context(_) if !seen_current => {
+                    seen_current = true;
+                    None
+                }
+                FrameKind::Context(context) => Some(context.to_string()),
+                FrameKind::Attachment(AttachmentKind::Printable(printable)) => {
+                    Some(printable.to_string())
+                }
+                FrameKind::Attachment(_) => None,
+            })
+            .collect();
+        let context = messages.into_iter().rev().fold(None, |source, message| {
+            Some(Box::new(ContextMessage { message, source }))
+        });
+        match report.current_context().clone() {
+            Error::JsonRpcError {
+                code,
+                message,
+                data,
+            } => Web3Error::JsonRpc {
+                code,
+                message,
+                data,
+                context,
+            },
+            kind => Web3Error::Client { kind, context },
+        }
+    }
+}
+
+/// `.into_std()` on the result of any method, for `?` into a function returning another
+/// error type.
+pub trait IntoStdResult<T> {
+    fn into_std(self) -> Result<T, Web3Error>;
+}
+
+impl<T> IntoStdResult<T> for Result<T, Report<Error>> {
+    fn into_std(self) -> Result<T, Web3Error> {
+        self.map_err(Web3Error::from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::StatusCode;
+
+    fn failing() -> Result<(), Report<Error>> {
+        Err(Report::new(Error::HttpError(StatusCode::BAD_GATEWAY))
+            .attach_printable("POST https://rpc.example")
+            .attach_printable("method eth_call"))
+    }
+
+    #[test]
+    fn reports_keep_their_context() {
+        let error = Web3Error::from(failing().unwrap_err());
+        assert!(matches!(
+            error.kind(),
+            Error::HttpError(StatusCode::BAD_GATEWAY)
+        ));
+        assert_eq!(error.code(), None);
+        assert_eq!(
+            error.context(),
+            vec!["method eth_call", "POST https://rpc.example"]
+        );
+
+        let io = Report::new(std::io::Error::other("connection reset"))
+            .change_context(Error::IoError)
+            .attach_printable("after 3 retries");
+        let error = Web3Error::from(io);
+        assert_eq!(error.context(), vec!["after 3 retries", "connection reset"]);
+
+        let rpc = crate::client::rpc_error(crate::model::RpcErrorObject {
+            code: -32000,
+            message: "header not found".to_string(),
+            data: None,
+        });
+        let error = Web3Error::from(rpc);
+        assert_eq!(error.code(), Some(-32000));
+        assert_eq!(error.to_string(), "JSON-RPC error -32000: header not found");
+    }
+
+    #[test]
+    fn question_mark_into_anyhow() {
+        fn boundary() -> anyhow::Result<()> {
+            failing().into_std()?;
+            Ok(())
+        }
+        let error = boundary().unwrap_err();
+        assert_eq!(
+            format!("{:#}", error),
+            "HttpError(502): method eth_call: POST https://rpc.example"
+        );
+        assert!(error.downcast_ref::<Web3Error>().is_some());
+
+        fn boxed() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+            failing().into_std()?;
+            Ok(())
+        }
+        assert!(boxed().is_err());
+    }
+}
@@ -0,0 +1,158 @@
+//! Storage slot arithmetic for reading Solidity state variables with `eth_getStorageAt`.
+//!
+//! Follows the compiler's layout rules: a mapping value lives at
+//! `keccak256(pad(key) ++ pad(slot))`, element `i` of a dynamic array at
+//! `keccak256(pad(slot)) + i`, and small values declared next to each other share a slot,
+//! packed from the low-order end.
+//!
+//! ```no_run
+//! # async fn run(web3: web3_rpc::web3::Web3, usdc: web3_rpc::types::Address, holder: web3_rpc::types::Address) {
+//! use web3_rpc::abi::Token;
+//! use web3_rpc::model::Tag;
+//! use web3_rpc::types::U256;
+//!
+//! // FiatTokenV2 keeps `balances` in slot 9
+//! let word = web3
+//!     .read_mapping_slot(usdc, U256::from(9u64), &Token::Address(holder), Tag::Latest)
+//!     .await
+//!     .unwrap();
+//! let balance = U256::from_be_bytes(word.0);
+//! # }
+//! ```
+
+use crate::abi::{self, Token};
+use crate::client::Error;
+use crate::keccak::keccak256;
+use crate::model::BlockId;
+use crate::types::{Address, H256, U256};
+use crate::web3::Web3;
+use error_stack::{Report, ResultExt};
+
+/// Slot of `mapping[key]` for a mapping declared at `base`.
+///
+/// Value-type keys (`Address`, `Uint`, `Bool`, `FixedBytes`) are padded to a word; `String`
+/// and `Bytes` keys are hashed as their raw bytes, as Solidity does.
+pub fn mapping_slot(base: U256, key: &Token) -> U256 {
+    let mut preimage = match key {
+        Token::String(s) => s.as_bytes().to_vec(),
+        Token::Bytes(b) => b.clone(),
+        key => abi::encode(std::slice::from_ref(key)),
+    };
+    preimage.extend_from_slice(&base.to_be_bytes());
+    U256::from_be_bytes(keccak256(&preimage))
+}
+
+/// Slot of element `index` of a dynamic array declared at `base`, for elements that take
+/// one slot each. Multiply `index` by the element's slot count for larger elements.
+pub fn array_slot(base: U256, index: U256) -> U256 {
+    U256::from_be_bytes(keccak256(&base.to_be_bytes())).wrapping_add(index)
+}
+
+/// Slot of `mapping[keys[0]][keys[1]]...` for nested mappings declared at `base`, such as
+/// an ERC-20 `allowance[owner][spender]`.
+pub fn nested(base: U256, keys: &[Token]) -> U256 {
+    keys.iter().fold(base, mapping_slot)
+}
+
+/// The `size` bytes of a packed value starting `offset` bytes from the low-order end of
+/// `word`, which is how the compiler places e.g. a `uint64` declared after an `address`.
+///
+/// Panics if `offset + size` exceeds 32.
+pub fn packed(word: &H256, offset: usize, size: usize) -> &[u8] {
+    assert!(offset + size <= 32, "packed field runs past the slot");
+    &word.0[32 - offset - size..32 - offset]
+}
+
+impl Web3 {
+    /// Reads the word at [`mapping_slot`]`(base_slot, key)` of `contract`.
+    pub async fn read_mapping_slot(
+        &self,
+        contract: Address,
+        base_slot: U256,
+        key: &Token,
+        block: impl Into<BlockId>,
+    ) -> Result<H256, Report<Error>> {
+        let word = self
+            .eth_get_storage_at_block(contract, mapping_slot(base_slot, key), block)
+            .await?
+            .result
+            .ok_or_else(|| {
+                Report::new(Error::UnexpectedResponseFormat)
+                    .attach_printable("eth_getStorageAt returned null")
+            })?;
+        word.parse::<H256>()
+            .change_context(Error::FailedToDeserialize)
+            .attach_printable_lazy(|| format!("eth_getStorageAt returned `{}`", word))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn slot(hex: &str) -> U256 {
+        U256::from_str_radix_16(hex).unwrap()
+    }
+
+    #[test]
+    fn array_slots_start_at_the_hashed_base() {
+        assert_eq!(
+            array_slot(U256::ZERO, U256::ZERO),
+            slot("0x290decd9548b62a8d60345a988386fc84ba6bc95484008f6362f93160ef3e563")
+        );
+        assert_eq!(
+            array_slot(U256::ONE, U256::from(2u64)),
+            slot("0xb10e2d527612073b26eecdfd717e6a320cf44b4afac2b0732d9fcbe2b7fa0cf8")
+        );
+    }
+
+    #[test]
+    fn mapping_slots_hash_key_then_base() {
+        assert_eq!(
+            mapping_slot(U256::ZERO, &Token::Address(Address::ZERO)),
+            slot("0xad3228b676f7d3cd4284a5443f17f1962b36e491b30a40b2405849e597ba5fb5")
+        );
+
+        let holder: Address = "0x846c4dc9f4e2514206ef179eaa0bcfae007e37d2"
+            .parse()
+            .unwrap();
+        let mut preimage = [0u8; 64];
+        preimage[12..32].copy_from_slice(holder.as_bytes());
+        preimage[63] = 9;
+        assert_eq!(
+            mapping_slot(U256::from(9u64), &Token::Address(holder)),
+            U256::from_be_bytes(keccak256(&preimage))
+        );
+
+        let mut preimage = b"hello".to_vec();
+        preimage.extend_from_slice(&U256::ONE.to_be_bytes());
+        assert_eq!(
+            mapping_slot(U256::ONE, &Token::String("hello".into())),
+            U256::from_be_bytes(keccak256(&preimage))
+        );
+    }
+
+    #[test]
+    fn nested_mappings_fold_keys_in_order() {
+        let owner = Token::Address(Address([1; 20]));
+        let spender = Token::Address(Address([2; 20]));
+        let base = U256::from(10u64);
+        assert_eq!(
+            nested(base, &[owner.clone(), spender.clone()]),
+            mapping_slot(mapping_slot(base, &owner), &spender)
+        );
+        assert_eq!(nested(base, &[]), base);
+    }
+
+    #[test]
+    fn unpacks_fields_from_the_low_end() {
+        // `address owner; uint64 nonce;` in one slot
+        let mut word = [0u8; 32];
+        word[4..12].copy_from_slice(&7u64.to_be_bytes());
+        word[12..].copy_from_slice(&[0xaa; 20]);
+        let word = H256(word);
+        assert_eq!(packed(&word, 0, 20), &[0xaa; 20]);
+        assert_eq!(packed(&word, 20, 8), &7u64.to_be_bytes());
+        assert_eq!(packed(&word, 28, 4), &[0; 4]);
+    }
+}
@@ -0,0 +1,371 @@
+//! Array results handed over element by element while the body is still arriving, for
+//! responses too large to buffer: eth_getLogs over a wide range, debug_traceBlock on a full
+//! block. Neither the body nor the whole array is ever held in memory.
+//!
+//! ```no_run
+//! # use web3_rpc::model::LogFilter;
+//! # use web3_rpc::web3::Web3;
+//! # async fn example(rpc: Web3, filter: LogFilter) -> Result<(), error_stack::Report<web3_rpc::client::Error>> {
+//! let mut transfers = 0;
+//! rpc.eth_get_logs_streaming(&filter, |_log| transfers += 1).await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::capacity;
+use crate::client::{rpc_error, Client, Error};
+use crate::model::{Log, LogFilter, RpcErrorObject};
+use crate::web3::Web3;
+use bytes::{Buf, Bytes};
+use error_stack::{Report, ResultExt};
+use reqwest::StatusCode;
+use serde::de::{
+    self, DeserializeOwned, DeserializeSeed, IgnoredAny, MapAccess, SeqAccess, Visitor,
+};
+use serde::Deserializer;
+use serde_json::{json, Value};
+use std::fmt;
+use std::io::{BufReader, Read};
+use std::marker::PhantomData;
+use tokio::sync::mpsc;
+use tokio::time::Instant;
+
+/// Chunks buffered between the connection and the parser.
+const CHUNKS_IN_FLIGHT: usize = 8;
+/// Parsed elements buffered between the parser and the callback.
+const ITEMS_IN_FLIGHT: usize = 64;
+
+/// Reads a JSON-RPC response from `reader` and calls `on_item` with each element of its
+/// array `result`, as parsed. Returns the number of elements; a `null` result has none.
+///
+/// An error response fails with [`Error::JsonRpcError`], or [`Error::CapacityExceeded`] for
+/// rate limiting, and anything else that is not such a response with
+/// [`Error::FailedToDeserialize`]. Elements before the failure have been handed over.
+pub fn read_items<T, R>(reader: R, mut on_item: impl FnMut(T)) -> Result<usize, Report<Error>>
+where
+    T: DeserializeOwned,
+    R: Read,
+{
+    // serde_json reads byte by byte, which needs a buffer below it
+    let mut deserializer = serde_json::Deserializer::from_reader(BufReader::new(reader));
+    let envelope = Envelope {
+        on_item: &mut on_item,
+        marker: PhantomData,
+    };
+    let outcome = envelope
+        .deserialize(&mut deserializer)
+        .and_then(|outcome| deserializer.end().map(|()| outcome))
+        .change_context(Error::FailedToDeserialize)
+        .attach_printable("streamed response")?;
+    match outcome {
+        Ok(count) => Ok(count),
+        Err(error) => {
+            let capacity = capacity::classify(StatusCode::OK, Some(&error), None);
+            let report = rpc_error(error);
+            Err(match capacity {
+                Some(capacity) => report.change_context(capacity),
+                None => report,
+            })
+        }
+    }
+}
+
+/// The envelope: `result` is streamed, `error` kept, everything else skipped.
+struct Envelope<'a, T, F> {
+    on_item: &'a mut F,
+    marker: PhantomData<T>,
+}
+
+impl<'de, T, F> DeserializeSeed<'de> for Envelope<'_, T, F>
+where
+    T: DeserializeOwned,
+    F: FnMut(T),
+{
+    type Value = Result<usize, RpcErrorObject>;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_map(self)
+    }
+}
+
+impl<'de, T, F> Visitor<'de> for Envelope<'_, T, F>
+where
+    T: DeserializeOwned,
+    F: FnMut(T),
+{
+    type Value = Result<usize, RpcErrorObject>;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a JSON-RPC response")
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let mut count = None;
+        let mut error = None;
+        while let Some(key) = map.next_key::<String>()? {
+            match key.as_str() {
+                "result" => {
+                    count = Some(map.next_value_seed(Elements {
+                        on_item: &mut *self.on_item,
+                        marker: PhantomData,
+                    })?)
+                }
+                "error" => error = Some(map.next_value::<RpcErrorObject>()?),
+                _ => {
+                    map.next_value::<IgnoredAny>()?;
+                }
+            }
+        }
+        match (error, count) {
+            (Some(error), _) => Ok(Err(error)),
+            (None, Some(count)) => Ok(Ok(count)),
+            (None, None) => Err(de::Error::missing_field("result")),
+        }
+    }
+}
+
+/// The `result` array, each element handed to the callback.
+struct Elements<'a, T, F> {
+    on_item: &'a mut F,
+    marker: PhantomData<T>,
+}
+
+impl<'de, T, F> DeserializeSeed<'de> for Elements<'_, T, F>
+where
+    T: DeserializeOwned,
+    F: FnMut(T),
+{
+    type Value = usize;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_any(self)
+    }
+}
+
+impl<'de, T, F> Visitor<'de> for Elements<'_, T, F>
+where
+    T: DeserializeOwned,
+    F: FnMut(T),
+{
+    type Value = usize;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("an array or null")
+    }
+
+    fn visit_unit<E>(self) -> Result<Self::Value, E> {
+        Ok(0)
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let mut count = 0;
+        while let Some(item) = seq.next_element::<T>()? {
+            (self.on_item)(item);
+            count += 1;
+        }
+        Ok(count)
+    }
+}
+
+/// Blocking reader over the chunks the connection task forwards; the end of the body once
+/// the sender is gone.
+struct ChunkReader {
+    chunks: mpsc::Receiver<Bytes>,
+    current: Bytes,
+}
+
+impl Read for ChunkReader {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        while self.current.is_empty() {
+            match self.chunks.blocking_recv() {
+                Some(chunk) => self.current = chunk,
+                None => return Ok(0),
+            }
+        }
+        let n = buf.len().min(self.current.len());
+        self.current.copy_to_slice(&mut buf[..n]);
+        Ok(n)
+    }
+}
+
+impl Client {
+    /// Sends `payload` and calls `on_item` with each element of the array result as it is
+    /// parsed from the arriving body. Returns the number of elements.
+    ///
+    /// The request is sent once, without coalescing or retries, since part of the result may
+    /// already have been handed over when a failure shows. Bodies are not limited by
+    /// `max_response_size`, which is about buffering them.
+    pub async fn call_streaming<T, F>(
+        &self,
+        payload: Value,
+        on_item: F,
+    ) -> Result<usize, Report<Error>>
+    where
+        T: DeserializeOwned + Send + 'static,
+        F: FnMut(T),
+    {
+        let start = Instant::now();
+        self.stream(&payload, on_item)
+            .await
+            .map_err(|e| self.request_context(e, &payload, start))
+    }
+
+    async fn stream<T, F>(&self, payload: &Value, mut on_item: F) -> Result<usize, Report<Error>>
+    where
+        T: DeserializeOwned + Send + 'static,
+        F: FnMut(T),
+    {
+        let mut response = self.post_unread(payload).await?;
+        let (chunk_sender, chunks) = mpsc::channel(CHUNKS_IN_FLIGHT);
+        let (item_sender, mut items) = mpsc::channel(ITEMS_IN_FLIGHT);
+        let parser = tokio::task::spawn_blocking(move || {
+            let reader = ChunkReader {
+                chunks,
+                current: Bytes::new(),
+            };
+            // once the receiving side is gone the rest of the body is not sent either
+            read_items(reader, |item: T| {
+                let _ = item_sender.blocking_send(item);
+            })
+        });
+
+        let mut chunk_sender = Some(chunk_sender);
+        let mut pending: Option<Bytes> = None;
+        let mut failed = None;
+        loop {
+            tokio::select! {
+                item = items.recv() => match item {
+                    Some(item) => on_item(item),
+                    None => break,
+                },
+                permit = reserve(chunk_sender.clone()), if pending.is_some() => match permit {
+                    Some(permit) => {
+                        permit.send(pending.take().unwrap_or_default());
+                    }
+                    // the parser has stopped, its result says why
+                    None => {
+                        pending = None;
+                        chunk_sender = None;
+                    }
+                },
+                chunk = response.chunk(), if pending.is_none() && chunk_sender.is_some() => match chunk {
+                    Ok(Some(chunk)) => pending = Some(chunk),
+                    Ok(None) => chunk_sender = None,
+                    Err(e) => {
+                        failed = Some(e.without_url());
+                        chunk_sender = None;
+                    }
+                },
+            }
+        }
+        let parsed = parser
+            .await
+            .change_context(Error::IoError)
+            .attach_printable("response parser stopped")?;
+        match failed {
+            Some(e) => Err(Report::new(e)
+                .change_context(Error::IoError)
+                .attach_printable("failed to read response body")),
+            None => parsed,
+        }
+    }
+}
+
+async fn reserve(sender: Option<mpsc::Sender<Bytes>>) -> Option<mpsc::OwnedPermit<Bytes>> {
+    sender?.reserve_owned().await.ok()
+}
+
+impl Web3 {
+    /// eth_getLogs with each log handed to `on_log` as it is parsed, see [`Client::call_streaming`].
+    /// Returns the number of logs.
+    pub async fn eth_get_logs_streaming(
+        &self,
+        filter: &LogFilter,
+        on_log: impl FnMut(Log),
+    ) -> Result<usize, Report<Error>> {
+        let payload =
+            json!({ "jsonrpc": "2.0", "method": "eth_getLogs", "params": [filter], "id": "338" });
+        self.client.call_streaming(payload, on_log).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn collect(body: &str) -> Result<Vec<u64>, Report<Error>> {
+        let mut items = Vec::new();
+        let count = read_items(body.as_bytes(), |n: u64| items.push(n))?;
+        assert_eq!(count, items.len());
+        Ok(items)
+    }
+
+    #[test]
+    fn envelopes() {
+        assert_eq!(
+            collect(r#"{"jsonrpc":"2.0","id":"1","result":[1,2,3]}"#).unwrap(),
+            [1, 2, 3]
+        );
+        // key order is not fixed
+        assert_eq!(
+            collect(r#"{"result":[4],"id":1,"jsonrpc":"2.0","extra":{"a":[1]}}"#).unwrap(),
+            [4]
+        );
+        assert_eq!(
+            collect(r#"{"jsonrpc":"2.0","id":"1","result":[]}"#).unwrap(),
+            Vec::<u64>::new()
+        );
+        assert_eq!(
+            collect(r#"{"jsonrpc":"2.0","id":"1","result":null}"#).unwrap(),
+            Vec::<u64>::new()
+        );
+
+        let err = collect(r#"{"jsonrpc":"2.0","id":"1","error":{"code":-32000,"message":"query returned more than 10000 results"}}"#)
+            .unwrap_err();
+        assert!(matches!(
+            err.current_context(),
+            Error::JsonRpcError { code: -32000, .. }
+        ));
+        let err = collect(r#"{"jsonrpc":"2.0","id":"1","error":{"code":-32005,"message":"request rate exceeded"}}"#)
+            .unwrap_err();
+        assert!(matches!(
+            err.current_context(),
+            Error::CapacityExceeded { .. }
+        ));
+
+        for bad in [
+            r#"{"jsonrpc":"2.0","id":"1","result":[1,"x"]}"#,
+            r#"{"jsonrpc":"2.0","id":"1","result":[1,2"#,
+            r#"{"jsonrpc":"2.0","id":"1"}"#,
+            r#"{"jsonrpc":"2.0","id":"1","result":[1]} trailing"#,
+            "<html>502 Bad Gateway</html>",
+        ] {
+            let err = collect(bad).unwrap_err();
+            assert!(
+                matches!(err.current_context(), Error::FailedToDeserialize),
+                "{}",
+                bad
+            );
+        }
+    }
+
+    #[test]
+    fn elements_before_a_failure_are_handed_over() {
+        let mut seen = Vec::new();
+        let body = r#"{"jsonrpc":"2.0","id":"1","result":[1,2,"x",4]}"#;
+        assert!(read_items(body.as_bytes(), |n: u64| seen.push(n)).is_err());
+        assert_eq!(seen, [1, 2]);
+    }
+}
@@ -0,0 +1,241 @@
+//! Schema drift detection: members of a block, transaction, receipt or log that the models
+//! do not know. Blocks, transactions and receipts keep them in their `extra` map and logs
+//! ignore them; a client made with [`Web3::strict`] fails with [`Error::UnknownFields`]
+//! instead, so a canary notices when a fork adds a field.
+//!
+//! [`Web3::strict`]: crate::web3::Web3::strict
+
+use crate::client::Error;
+use crate::model::{AccessListItem, Block, Log, Receipt, Transaction, Withdrawal};
+use error_stack::Report;
+use serde::de::{self, DeserializeOwned, Visitor};
+use serde::Deserializer;
+use serde_json::Value;
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Model {
+    Block,
+    Transaction,
+    Receipt,
+    Log,
+    Withdrawal,
+    AccessListItem,
+}
+
+impl Model {
+    /// The model a method's result is made of, alone or in an array.
+    fn of(method: &str) -> Option<Model> {
+        match method {
+            "eth_getBlockByHash"
+            | "eth_getBlockByNumber"
+            | "eth_getUncleByBlockHashAndIndex"
+            | "eth_getUncleByBlockNumberAndIndex" => Some(Model::Block),
+            "eth_getTransactionByHash"
+            | "eth_getTransactionByBlockHashAndIndex"
+            | "eth_getTransactionByBlockNumberAndIndex" => Some(Model::Transaction),
+            "eth_getTransactionReceipt" | "eth_getBlockReceipts" => Some(Model::Receipt),
+            "eth_getLogs" | "eth_getFilterLogs" => Some(Model::Log),
+            _ => None,
+        }
+    }
+
+    /// Names of the members of `object` the model does not know.
+    fn unknown(self, object: &Value) -> Vec<String> {
+        let fields = match self {
+            Model::Block => return extra::<Block>(object, |block| &block.extra),
+            Model::Transaction => return extra::<Transaction>(object, |tx| &tx.extra),
+            Model::Receipt => return extra::<Receipt>(object, |receipt| &receipt.extra),
+            Model::Log => fields_of::<Log>(),
+            Model::Withdrawal => fields_of::<Withdrawal>(),
+            Model::AccessListItem => fields_of::<AccessListItem>(),
+        };
+        let members = object
+            .as_object()
+            .into_iter()
+            .flat_map(|members| members.keys());
+        members
+            .filter(|name| !fields.contains(&name.as_str()))
+            .cloned()
+            .collect()
+    }
+
+    /// Members holding other models, checked in turn.
+    fn nested(self) -> &'static [(&'static str, Model)] {
+        match self {
+            Model::Block => &[
+                ("transactions", Model::Transaction),
+                ("withdrawals", Model::Withdrawal),
+            ],
+            Model::Transaction => &[("accessList", Model::AccessListItem)],
+            Model::Receipt => &[("logs", Model::Log)],
+            _ => &[],
+        }
+    }
+}
+
+/// Paths of the members of `result` that the model `method` returns does not know, as
+/// `transactions[3].authorizationList`. Empty for methods returning none of the models.
+pub fn unknown_fields(method: &str, result: &Value) -> Vec<String> {
+    let mut found = Vec::new();
+    if let Some(model) = Model::of(method) {
+        check(model, result, "", &mut found);
+    }
+    found
+}
+
+/// [`unknown_fields`] as an error.
+pub(crate) fn check_result(method: &str, result: &Value) -> Result<(), Report<Error>> {
+    let paths = unknown_fields(method, result);
+    if paths.is_empty() {
+        return Ok(());
+    }
+    Err(Report::new(Error::UnknownFields { paths }))
+}
+
+fn check(model: Model, value: &Value, path: &str, found: &mut Vec<String>) {
+    match value {
+        Value::Array(items) => {
+            for (i, item) in items.iter().enumerate() {
+                check(model, item, &format!("{}[{}]", path, i), found);
+            }
+        }
+        Value::Object(members) => {
+            let unknown = model.unknown(value);
+            for (name, member) in members {
+                let member_path = match path {
+                    "" => name.clone(),
+                    path => format!("{}.{}", path, name),
+                };
+                if unknown.contains(name) {
+                    found.push(member_path);
+                } else if let Some((_, nested)) = model.nested().iter().find(|(n, _)| n == name) {
+                    check(*nested, member, &member_path, found);
+                }
+            }
+        }
+        // null results, and transactions given as hashes
+        _ => {}
+    }
+}
+
+/// The `extra` members of `object` parsed as `T`. A result that does not parse fails the call
+/// anyway.
+fn extra<T: DeserializeOwned>(
+    object: &Value,
+    extra: impl Fn(&T) -> &HashMap<String, Value>,
+) -> Vec<String> {
+    T::deserialize(object)
+        .map(|model| extra(&model).keys().cloned().collect())
+        .unwrap_or_default()
+}
+
+/// Names a derived `Deserialize` accepts for `T`'s members, aliases included, read from what
+/// it hands to `deserialize_struct`.
+fn fields_of<T: DeserializeOwned>() -> &'static [&'static str] {
+    let mut fields = FieldNames(&[]);
+    let _ = T::deserialize(&mut fields);
+    fields.0
+}
+
+struct FieldNames(&'static [&'static str]);
+
+impl<'de> Deserializer<'de> for &mut FieldNames {
+    type Error = de::value::Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, _: V) -> Result<V::Value, Self::Error> {
+        Err(de::Error::custom("not a struct"))
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _: &'static str,
+        fields: &'static [&'static str],
+        _: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.0 = fields;
+        Err(de::Error::custom("field names only"))
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes byte_buf
+        option unit unit_struct newtype_struct seq tuple tuple_struct map enum identifier
+        ignored_any
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const ADDRESS: &str = "0x00000000219ab540356cbb839cbe05303d7705fa";
+
+    #[test]
+    fn model_fields() {
+        assert!(fields_of::<Log>().contains(&"logIndex"));
+        assert!(fields_of::<Withdrawal>().contains(&"validatorIndex"));
+        // models with an `extra` map take any member
+        assert!(fields_of::<Block>().is_empty());
+    }
+
+    #[test]
+    fn unknown_fields_by_path() {
+        let fixture = crate::fixture::<Value>;
+        // the fixtures are what nodes send, the rollup ones with members of their own
+        let dir = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures");
+        for entry in std::fs::read_dir(dir).unwrap() {
+            let name = entry.unwrap().file_name().into_string().unwrap();
+            let method = match name.split('_').next().unwrap() {
+                "block" => "eth_getBlockByNumber",
+                "tx" => "eth_getTransactionByHash",
+                "receipt" => "eth_getTransactionReceipt",
+                "log" => "eth_getLogs",
+                _ => continue,
+            };
+            let name = name.trim_end_matches(".json");
+            let expected: &[&str] = match name {
+                "block_polygon_zkevm" => {
+                    &["blockInfoRoot", "globalExitRoot", "transactions[0].l2Hash"]
+                }
+                "receipt_deposit" => &["depositNonce", "depositReceiptVersion"],
+                "receipt_base" => &[
+                    "l1BaseFeeScalar",
+                    "l1BlobBaseFee",
+                    "l1BlobBaseFeeScalar",
+                    "l1Fee",
+                    "l1GasPrice",
+                    "l1GasUsed",
+                ],
+                _ => &[],
+            };
+            assert_eq!(unknown_fields(method, &fixture(name)), expected, "{}", name);
+        }
+
+        let mut block = fixture("block_cancun");
+        block["requestsRoot"] = json!("0x00");
+        let mut tx = fixture("tx_eip1559");
+        tx["authorizationList"] = json!([]);
+        tx["accessList"] = json!([{ "address": ADDRESS, "storageKeys": [], "slot": "0x0" }]);
+        block["transactions"] = json!([fixture("tx_legacy"), tx]);
+        assert_eq!(
+            unknown_fields("eth_getBlockByHash", &block),
+            [
+                "requestsRoot",
+                "transactions[1].accessList[0].slot",
+                "transactions[1].authorizationList"
+            ]
+        );
+
+        // aliases are known too
+        let mut block = fixture("block_cancun");
+        let mix_hash = block.as_object_mut().unwrap().remove("mixHash").unwrap();
+        block["prevRandao"] = mix_hash;
+        assert!(unknown_fields("eth_getBlockByNumber", &block).is_empty());
+
+        let logs = json!([{ "address": ADDRESS, "blockTimestamp": "0x1" }, null]);
+        assert_eq!(unknown_fields("eth_getLogs", &logs), ["[0].blockTimestamp"]);
+        assert!(unknown_fields("eth_getBalance", &json!({ "x": 1 })).is_empty());
+        assert!(unknown_fields("eth_getBlockByNumber", &Value::Null).is_empty());
+    }
+}
@@ -0,0 +1,412 @@
+//! Subscriptions to new heads and pending transactions, with an explicit buffering policy for
+//! consumers slower than the chain.
+//!
+//! The client has no websocket transport, so a task of the subscription's own polls over HTTP
+//! (`eth_blockNumber`, a pending transaction filter) and pushes what it finds into a bounded
+//! buffer, which [`Subscription::next`] drains. When the buffer is full, [`OnFull`] decides:
+//! wait for the consumer, which stops the polling as a blocked socket reader would, drop the
+//! oldest or the newest item, or end the subscription with [`Error::SubscriptionOverflow`].
+//! Drops are counted by [`Subscription::dropped`] and show in the stream as a
+//! [`SubscriptionItem::Lagged`] marker where the gap is, for the consumer to resynchronize.
+//!
+//! ```no_run
+//! # use std::time::Duration;
+//! # use web3_rpc::subscription::{BufferPolicy, OnFull, SubscriptionItem};
+//! # async fn run(web3: web3_rpc::web3::Web3) -> Result<(), error_stack::Report<web3_rpc::client::Error>> {
+//! let policy = BufferPolicy::Bounded { capacity: 10_000, on_full: OnFull::DropOldest };
+//! let mut pending = web3.subscribe_pending_transactions(Duration::from_secs(1), policy);
+//! while let Some(item) = pending.next().await {
+//!     match item? {
+//!         SubscriptionItem::Item(hash) => println!("{}", hash),
+//!         SubscriptionItem::Lagged(n) => println!("missed {} transactions", n),
+//!     }
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Dropping the subscription stops its task; a pending transaction filter it installed is left
+//! for the node to expire.
+
+use crate::client::{rpc_error, Error};
+use crate::endpoints::head;
+use crate::model::Block;
+use crate::types::H256;
+use crate::web3::Web3;
+use error_stack::{Report, ResultExt};
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::Notify;
+use tokio::task::JoinHandle;
+
+/// Items buffered by [`BufferPolicy::default`].
+pub const DEFAULT_CAPACITY: usize = 1024;
+
+/// What happens to an item arriving at a full buffer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OnFull {
+    /// Wait for the consumer to take one; nothing is missed, and nothing is polled meanwhile.
+    Block,
+    /// Make room by dropping the oldest buffered item.
+    DropOldest,
+    /// Drop the arriving item.
+    DropNewest,
+    /// End the subscription with [`Error::SubscriptionOverflow`] after the buffered items.
+    Error,
+}
+
+/// How a subscription buffers items its consumer has not taken yet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BufferPolicy {
+    Bounded { capacity: usize, on_full: OnFull },
+}
+
+/// [`DEFAULT_CAPACITY`] items, then [`OnFull::Block`].
+impl Default for BufferPolicy {
+    fn default() -> Self {
+        BufferPolicy::Bounded {
+            capacity: DEFAULT_CAPACITY,
+            on_full: OnFull::Block,
+        }
+    }
+}
+
+/// An item of a [`Subscription`], or the number dropped at this point of the stream.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SubscriptionItem<T> {
+    Item(T),
+    Lagged(u64),
+}
+
+/// Returned by [`Web3::subscribe_new_heads`] and [`Web3::subscribe_pending_transactions`];
+/// call [`next`](Self::next) until it returns `None`.
+pub struct Subscription<T> {
+    shared: Arc<Shared<T>>,
+    task: JoinHandle<()>,
+}
+
+/// The pushing end of a [`Subscription`].
+pub(crate) struct Publisher<T> {
+    shared: Arc<Shared<T>>,
+}
+
+struct Shared<T> {
+    capacity: usize,
+    on_full: OnFull,
+    buffer: Mutex<Buffer<T>>,
+    readable: Notify,
+    writable: Notify,
+    dropped: AtomicU64,
+}
+
+struct Buffer<T> {
+    /// Each item with the number dropped just before it.
+    items: VecDeque<(u64, T)>,
+    /// Dropped after the last buffered item.
+    tail_gap: u64,
+    /// Set when the publisher stops, with the error it stopped on.
+    end: Option<Option<Report<Error>>>,
+}
+
+impl<T: Send + 'static> Subscription<T> {
+    /// A subscription fed by `produce`, run as a task of its own until it returns or the
+    /// subscription is dropped. An error it returns ends the stream after the buffered items.
+    pub(crate) fn spawn<F, Fut>(policy: BufferPolicy, produce: F) -> Subscription<T>
+    where
+        F: FnOnce(Publisher<T>) -> Fut,
+        Fut: std::future::Future<Output = Result<(), Report<Error>>> + Send + 'static,
+    {
+        let BufferPolicy::Bounded { capacity, on_full } = policy;
+        let shared = Arc::new(Shared {
+            capacity: capacity.max(1),
+            on_full,
+            buffer: Mutex::new(Buffer {
+                items: VecDeque::new(),
+                tail_gap: 0,
+                end: None,
+            }),
+            readable: Notify::new(),
+            writable: Notify::new(),
+            dropped: AtomicU64::new(0),
+        });
+        let publisher = Publisher {
+            shared: shared.clone(),
+        };
+        let end = shared.clone();
+        let produced = produce(publisher);
+        let task = tokio::spawn(async move {
+            let result = produced.await;
+            end.buffer.lock().unwrap().end = Some(result.err());
+            end.readable.notify_one();
+        });
+        Subscription { shared, task }
+    }
+}
+
+impl<T> Subscription<T> {
+    /// The next item, or a [`SubscriptionItem::Lagged`] marker where items were dropped.
+    /// `None` once the subscription has ended and everything before was taken; an error it
+    /// ended on comes before that.
+    pub async fn next(&mut self) -> Option<Result<SubscriptionItem<T>, Report<Error>>> {
+        loop {
+            let notified = self.shared.readable.notified();
+            {
+                let mut buffer = self.shared.buffer.lock().unwrap();
+                if let Some((gap, _)) = buffer.items.front_mut() {
+                    if *gap > 0 {
+                        return Some(Ok(SubscriptionItem::Lagged(std::mem::take(gap))));
+                    }
+                    let (_, item) = buffer.items.pop_front()?;
+                    self.shared.writable.notify_one();
+                    return Some(Ok(SubscriptionItem::Item(item)));
+                }
+                if buffer.tail_gap > 0 {
+                    return Some(Ok(SubscriptionItem::Lagged(std::mem::take(
+                        &mut buffer.tail_gap,
+                    ))));
+                }
+                if let Some(end) = buffer.end.as_mut() {
+                    return end.take().map(Err);
+                }
+            }
+            notified.await;
+        }
+    }
+
+    /// Items dropped so far under [`OnFull::DropOldest`] or [`OnFull::DropNewest`].
+    pub fn dropped(&self) -> u64 {
+        self.shared.dropped.load(Ordering::Relaxed)
+    }
+}
+
+impl<T> Drop for Subscription<T> {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+impl<T> Publisher<T> {
+    /// Buffers `item` as the policy says, waiting for room under [`OnFull::Block`].
+    pub(crate) async fn publish(&self, item: T) -> Result<(), Report<Error>> {
+        let shared = &self.shared;
+        loop {
+            let writable = shared.writable.notified();
+            {
+                let mut buffer = shared.buffer.lock().unwrap();
+                if buffer.items.len() < shared.capacity {
+                    let gap = std::mem::take(&mut buffer.tail_gap);
+                    buffer.items.push_back((gap, item));
+                    shared.readable.notify_one();
+                    return Ok(());
+                }
+                match shared.on_full {
+                    OnFull::Block => {}
+                    OnFull::DropOldest => {
+                        let (gap, _) = buffer.items.pop_front().expect("a full buffer");
+                        match buffer.items.front_mut() {
+                            Some((next, _)) => *next += gap + 1,
+                            None => buffer.tail_gap += gap + 1,
+                        }
+                        let gap = std::mem::take(&mut buffer.tail_gap);
+                        buffer.items.push_back((gap, item));
+                        shared.dropped.fetch_add(1, Ordering::Relaxed);
+                        shared.readable.notify_one();
+                        return Ok(());
+                    }
+                    OnFull::DropNewest => {
+                        buffer.tail_gap += 1;
+                        shared.dropped.fetch_add(1, Ordering::Relaxed);
+                        return Ok(());
+                    }
+                    OnFull::Error => {
+                        return Err(Report::new(Error::SubscriptionOverflow {
+                            capacity: shared.capacity,
+                        }));
+                    }
+                }
+            }
+            writable.await;
+        }
+    }
+}
+
+impl Web3 {
+    /// Every new block from the next one on, by number and without skipping any, polling the
+    /// head every `poll`. Blocks reorganized away after they were handed out are not retracted.
+    pub fn subscribe_new_heads(&self, poll: Duration, policy: BufferPolicy) -> Subscription<Block> {
+        let web3 = self.clone();
+        Subscription::spawn(policy, |publisher| async move {
+            let mut next = head(&web3).await? + 1;
+            loop {
+                let head = head(&web3).await?;
+                while next <= head {
+                    let block = web3.get_block(next).await?.ok_or_else(|| {
+                        Report::new(Error::UnexpectedResponseFormat).attach_printable(format!(
+                            "block {} not found below head {}",
+                            next, head
+                        ))
+                    })?;
+                    publisher.publish(block).await?;
+                    next += 1;
+                }
+                tokio::time::sleep(poll).await;
+            }
+        })
+    }
+
+    /// Hashes of transactions entering the node's pool, from a pending transaction filter
+    /// polled every `poll`. A filter the node forgot is installed again; hashes announced in
+    /// between are missed, without a [`SubscriptionItem::Lagged`] marker.
+    pub fn subscribe_pending_transactions(
+        &self,
+        poll: Duration,
+        policy: BufferPolicy,
+    ) -> Subscription<H256> {
+        let web3 = self.clone();
+        Subscription::spawn(policy, |publisher| async move {
+            let mut filter: Option<String> = None;
+            loop {
+                let Some(id) = filter.clone() else {
+                    let id = web3
+                        .eth_new_pending_transaction_filter()
+                        .await?
+                        .into_result()
+                        .map_err(rpc_error)?;
+                    filter = Some(id);
+                    tokio::time::sleep(poll).await;
+                    continue;
+                };
+                let changes = match web3.eth_get_filter_changes(&id).await {
+                    Ok(r) => r.into_result().map_err(rpc_error)?,
+                    Err(report)
+                        if matches!(report.current_context(), Error::JsonRpcError { .. }) =>
+                    {
+                        tracing::debug!(filter = id, "pending transaction filter gone");
+                        filter = None;
+                        continue;
+                    }
+                    Err(report) => return Err(report),
+                };
+                for change in changes {
+                    let hash: H256 = serde_json::from_value(change)
+                        .change_context(Error::UnexpectedResponseFormat)
+                        .attach_printable("eth_getFilterChanges of a pending transaction filter")?;
+                    publisher.publish(hash).await?;
+                }
+                tokio::time::sleep(poll).await;
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A subscription to `items`, pushed as fast as `policy` lets them.
+    fn feeding(items: Vec<u64>, policy: BufferPolicy) -> Subscription<u64> {
+        Subscription::spawn(policy, |publisher| async move {
+            for item in items {
+                publisher.publish(item).await?;
+            }
+            Ok(())
+        })
+    }
+
+    fn bounded(capacity: usize, on_full: OnFull) -> BufferPolicy {
+        BufferPolicy::Bounded { capacity, on_full }
+    }
+
+    async fn drain(subscription: &mut Subscription<u64>) -> Vec<SubscriptionItem<u64>> {
+        let mut items = Vec::new();
+        while let Some(item) = subscription.next().await {
+            items.push(item.unwrap());
+        }
+        items
+    }
+
+    /// Lets the producer run until it is blocked or done, before the consumer takes anything.
+    async fn settle() {
+        for _ in 0..10 {
+            tokio::task::yield_now().await;
+        }
+    }
+
+    #[tokio::test]
+    async fn blocking_misses_nothing() {
+        let mut subscription = feeding((0..10).collect(), bounded(2, OnFull::Block));
+        settle().await;
+        let items = drain(&mut subscription).await;
+        let expected: Vec<_> = (0..10).map(SubscriptionItem::Item).collect();
+        assert_eq!(items, expected);
+        assert_eq!(subscription.dropped(), 0);
+    }
+
+    #[tokio::test]
+    async fn dropping_oldest_keeps_the_latest() {
+        let mut subscription = feeding((0..10).collect(), bounded(3, OnFull::DropOldest));
+        settle().await;
+        assert_eq!(subscription.dropped(), 7);
+        use SubscriptionItem::*;
+        assert_eq!(
+            drain(&mut subscription).await,
+            vec![Lagged(7), Item(7), Item(8), Item(9)]
+        );
+    }
+
+    #[tokio::test]
+    async fn dropping_newest_keeps_the_earliest() {
+        let mut subscription = feeding((0..10).collect(), bounded(3, OnFull::DropNewest));
+        settle().await;
+        assert_eq!(subscription.dropped(), 7);
+        use SubscriptionItem::*;
+        assert_eq!(
+            drain(&mut subscription).await,
+            vec![Item(0), Item(1), Item(2), Lagged(7)]
+        );
+    }
+
+    #[tokio::test]
+    async fn gaps_are_marked_where_they_are() {
+        let resume = Arc::new(Notify::new());
+        let resumed = resume.clone();
+        let mut subscription =
+            Subscription::spawn(bounded(3, OnFull::DropNewest), |publisher| async move {
+                for item in 0..5 {
+                    publisher.publish(item).await?;
+                }
+                resumed.notified().await;
+                publisher.publish(5).await
+            });
+        settle().await;
+        use SubscriptionItem::*;
+        assert_eq!(subscription.next().await.unwrap().unwrap(), Item(0));
+        // room again for an item after the gap
+        resume.notify_one();
+        settle().await;
+        assert_eq!(
+            drain(&mut subscription).await,
+            vec![Item(1), Item(2), Lagged(2), Item(5)]
+        );
+    }
+
+    #[tokio::test]
+    async fn overflowing_ends_the_subscription() {
+        let mut subscription = feeding((0..10).collect(), bounded(3, OnFull::Error));
+        settle().await;
+        for expected in 0..3 {
+            assert_eq!(
+                subscription.next().await.unwrap().unwrap(),
+                SubscriptionItem::Item(expected)
+            );
+        }
+        let report = subscription.next().await.unwrap().unwrap_err();
+        assert!(matches!(
+            report.current_context(),
+            Error::SubscriptionOverflow { capacity: 3 }
+        ));
+        assert!(subscription.next().await.is_none());
+    }
+}
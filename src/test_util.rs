@@ -0,0 +1,318 @@
+//! Local chains for tests, behind the `test-util` feature.
+//!
+//! ```no_run
+//! # use web3_rpc::test_util::{AnvilInstance, AnvilOptions};
+//! # async fn example() -> Result<(), error_stack::Report<web3_rpc::client::Error>> {
+//! let anvil = AnvilInstance::spawn(AnvilOptions::default()).await?;
+//! let rpc = anvil.web3();
+//! let funded = anvil.accounts()[0];
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::client::Error;
+use crate::types::{Address, H256};
+use crate::web3::Web3;
+use error_stack::{Report, ResultExt};
+use std::io::{BufRead, BufReader, Read};
+use std::path::PathBuf;
+use std::process::{Child, Command, Stdio};
+use std::time::{Duration, Instant};
+
+/// Settings for [`AnvilInstance::spawn`]; unset ones are left to anvil.
+#[derive(Debug, Clone)]
+pub struct AnvilOptions {
+    /// `anvil` from the PATH by default.
+    pub binary: PathBuf,
+    /// Chain to fork, e.g. an archive node url.
+    pub fork_url: Option<String>,
+    /// Block of `fork_url` to fork at, its latest when `None`.
+    pub fork_block_number: Option<u64>,
+    /// Seconds between blocks; without it every transaction is mined at once.
+    pub block_time: Option<u64>,
+    /// 31337 when `None`, or the forked chain's id.
+    pub chain_id: Option<u64>,
+    /// Funded dev accounts, 10 when `None`.
+    pub accounts: Option<u32>,
+    /// How long to wait for the RPC to answer, up from the default for slow forks.
+    pub timeout: Duration,
+}
+
+impl Default for AnvilOptions {
+    fn default() -> Self {
+        AnvilOptions {
+            binary: PathBuf::from("anvil"),
+            fork_url: None,
+            fork_block_number: None,
+            block_time: None,
+            chain_id: None,
+            accounts: None,
+            timeout: Duration::from_secs(10),
+        }
+    }
+}
+
+impl AnvilOptions {
+    /// Whether `binary` can be run, for tests that skip without it.
+    pub fn is_installed(&self) -> bool {
+        Command::new(&self.binary)
+            .arg("--version")
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .is_ok_and(|status| status.success())
+    }
+
+    fn args(&self) -> Vec<String> {
+        // port 0 lets the OS pick, anvil prints the one it got
+        let mut args = vec!["--port".to_string(), "0".to_string()];
+        let mut push = |flag: &str, value: Option<String>| {
+            if let Some(value) = value {
+                args.extend([flag.to_string(), value]);
+            }
+        };
+        push("--fork-url", self.fork_url.clone());
+        push(
+            "--fork-block-number",
+            self.fork_block_number.map(|n| n.to_string()),
+        );
+        push("--block-time", self.block_time.map(|n| n.to_string()));
+        push("--chain-id", self.chain_id.map(|n| n.to_string()));
+        push("--accounts", self.accounts.map(|n| n.to_string()));
+        args
+    }
+}
+
+/// A running anvil process, killed on drop.
+#[derive(Debug)]
+pub struct AnvilInstance {
+    child: Child,
+    url: String,
+    chain_id: u64,
+    accounts: Vec<Address>,
+    private_keys: Vec<H256>,
+}
+
+/// What anvil prints before it starts serving.
+#[derive(Debug, Default)]
+struct Banner {
+    accounts: Vec<Address>,
+    private_keys: Vec<H256>,
+    listening: Option<String>,
+}
+
+impl AnvilInstance {
+    /// Starts anvil on a free port and waits until it answers eth_chainId.
+    ///
+    /// Fails with [`Error::IoError`] when the binary cannot be run or exits early, and with
+    /// [`Error::Timeout`] when it does not answer within `options.timeout`.
+    pub async fn spawn(options: AnvilOptions) -> Result<Self, Report<Error>> {
+        let deadline = Instant::now() + options.timeout;
+        let mut child = Command::new(&options.binary)
+            .args(options.args())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .change_context(Error::IoError)
+            .attach_printable_lazy(|| format!("cannot run {}", options.binary.display()))?;
+        let stdout = child.stdout.take();
+        // from here on a failure drops `instance`, which kills the process
+        let mut instance = AnvilInstance {
+            child,
+            url: String::new(),
+            chain_id: 0,
+            accounts: Vec::new(),
+            private_keys: Vec::new(),
+        };
+
+        let stdout = stdout.ok_or_else(|| Report::new(Error::IoError))?;
+        let read = tokio::task::spawn_blocking(move || read_banner(stdout));
+        let banner = tokio::time::timeout_at(deadline.into(), read)
+            .await
+            .map_err(|_| Report::new(Error::Timeout))
+            .attach_printable("anvil did not start listening")?
+            .change_context(Error::IoError)?
+            .change_context(Error::IoError)?;
+        let address = banner.listening.ok_or_else(|| {
+            Report::new(Error::IoError).attach_printable("anvil exited before listening")
+        })?;
+        instance.url = format!("http://{}", address);
+        instance.accounts = banner.accounts;
+        instance.private_keys = banner.private_keys;
+
+        let rpc = instance.web3();
+        loop {
+            if let Ok(Some(chain_id)) = rpc.eth_chain_id().await.map(|r| r.result) {
+                instance.chain_id = u64::try_from(chain_id)
+                    .ok()
+                    .ok_or_else(|| Report::new(Error::UnexpectedResponseFormat))
+                    .attach_printable_lazy(|| format!("eth_chainId returned {}", chain_id))?;
+                return Ok(instance);
+            }
+            if Instant::now() >= deadline {
+                return Err(Report::new(Error::Timeout)
+                    .attach_printable(format!("anvil at {} does not answer", instance.url)));
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+    }
+
+    /// `http://127.0.0.1:<port>`.
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    pub fn chain_id(&self) -> u64 {
+        self.chain_id
+    }
+
+    /// The funded dev accounts, unlocked on the node.
+    pub fn accounts(&self) -> &[Address] {
+        &self.accounts
+    }
+
+    /// Keys of [`AnvilInstance::accounts`], in the same order.
+    pub fn private_keys(&self) -> &[H256] {
+        &self.private_keys
+    }
+
+    /// A client for this instance.
+    pub fn web3(&self) -> Web3 {
+        Web3::new(self.url.clone())
+    }
+}
+
+impl Drop for AnvilInstance {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+/// Reads the accounts and keys anvil lists until its `Listening on` line, then keeps
+/// draining stdout on a thread so the request log never fills the pipe.
+fn read_banner(stdout: impl Read + Send + 'static) -> std::io::Result<Banner> {
+    let mut banner = Banner::default();
+    let mut lines = BufReader::new(stdout).lines();
+    let mut in_keys = false;
+    for line in lines.by_ref() {
+        let line = line?;
+        if let Some(address) = line.strip_prefix("Listening on ") {
+            banner.listening = Some(address.trim().to_string());
+            break;
+        }
+        match line.trim() {
+            "Available Accounts" => in_keys = false,
+            "Private Keys" => in_keys = true,
+            entry if entry.starts_with('(') => {
+                let value = entry.split_whitespace().nth(1).unwrap_or_default();
+                if in_keys {
+                    banner.private_keys.extend(value.parse::<H256>().ok());
+                } else {
+                    banner.accounts.extend(value.parse::<Address>().ok());
+                }
+            }
+            _ => {}
+        }
+    }
+    std::thread::spawn(move || lines.for_each(drop));
+    Ok(banner)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn banner_parsing() {
+        let output = "\
+                             _   _
+Available Accounts
+==================
+
+(0) 0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266 (10000.000000000000000000 ETH)
+(1) 0x70997970C51812dc3A010C7d01b50e0d17dc79C8 (10000.000000000000000000 ETH)
+
+Private Keys
+==================
+
+(0) 0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80
+(1) 0x59c6995e998f97a5a0044966f0945389dc9e86dae88c7a8412f4603b6b78690d
+
+Wallet
+==================
+Mnemonic:          test test test test test test test test test test test junk
+Derivation path:   m/44'/60'/0'/0/
+
+Chain ID
+==================
+
+31337
+
+Listening on 127.0.0.1:41237
+eth_chainId
+";
+        let banner = read_banner(std::io::Cursor::new(output)).unwrap();
+
+        assert_eq!(banner.listening.as_deref(), Some("127.0.0.1:41237"));
+        assert_eq!(
+            banner.accounts,
+            [
+                "0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266"
+                    .parse()
+                    .unwrap(),
+                "0x70997970C51812dc3A010C7d01b50e0d17dc79C8"
+                    .parse::<Address>()
+                    .unwrap(),
+            ]
+        );
+        assert_eq!(banner.private_keys.len(), 2);
+        assert_eq!(
+            banner.private_keys[0],
+            "0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80"
+                .parse()
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn command_line() {
+        assert_eq!(AnvilOptions::default().args(), ["--port", "0"]);
+        let options = AnvilOptions {
+            fork_url: Some("https://rpc.example.com".into()),
+            fork_block_number: Some(17_000_000),
+            block_time: Some(2),
+            chain_id: Some(1),
+            accounts: Some(3),
+            ..Default::default()
+        };
+        assert_eq!(
+            options.args(),
+            [
+                "--port",
+                "0",
+                "--fork-url",
+                "https://rpc.example.com",
+                "--fork-block-number",
+                "17000000",
+                "--block-time",
+                "2",
+                "--chain-id",
+                "1",
+                "--accounts",
+                "3"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn missing_binary_is_an_io_error() {
+        let options = AnvilOptions {
+            binary: PathBuf::from("/nonexistent/anvil"),
+            ..Default::default()
+        };
+        assert!(!options.is_installed());
+        let err = AnvilInstance::spawn(options).await.unwrap_err();
+        assert!(matches!(err.current_context(), Error::IoError));
+    }
+}
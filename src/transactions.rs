@@ -0,0 +1,272 @@
+//! Every transaction in a block range, for address activity that getLogs cannot see (plain
+//! ETH transfers emit no logs).
+//!
+//! Blocks are fetched with full transactions in JSON-RPC batches, one batch at a time, so
+//! at most one batch of blocks is held however long the range.
+//!
+//! ```no_run
+//! # async fn run(web3: web3_rpc::web3::Web3, wallet: web3_rpc::types::Address) {
+//! let mut txs = web3
+//!     .transactions_in_range(17_000_000, 17_001_000)
+//!     .from_addresses(&[wallet])
+//!     .to_addresses(&[wallet]);
+//! while let Some(item) = txs.next().await {
+//!     let (block, tx) = item.unwrap();
+//!     println!("{} {} {}", block.number, tx.hash, tx.value);
+//! }
+//! # }
+//! ```
+//!
+//! A block already fetched with hashes only gives its full transactions through
+//! [`Block::transactions_iter`], fetched by hash in batches as they are needed.
+
+use crate::client::{copy_report, rpc_error, Error};
+use crate::model::{Block, BlockTransactions, JsonRpcResult, Transaction};
+use crate::types::{Address, H256};
+use crate::web3::Web3;
+use error_stack::{Report, ResultExt};
+use serde_json::{json, Value};
+use std::collections::VecDeque;
+
+/// Blocks per JSON-RPC batch unless [`TransactionStream::batch_size`] says otherwise; full
+/// blocks are large, so this is kept well below [`crate::withdrawals::BATCH_BLOCKS`].
+pub const DEFAULT_BATCH_BLOCKS: u64 = 10;
+
+/// Transactions per JSON-RPC batch of [`BlockTransactionIter`] unless
+/// [`BlockTransactionIter::batch_size`] says otherwise.
+pub const DEFAULT_BATCH_TRANSACTIONS: usize = 100;
+
+/// The block a streamed transaction was included in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BlockSummary {
+    pub number: u64,
+    pub hash: H256,
+    pub timestamp: u64,
+}
+
+/// Returned by [`Web3::transactions_in_range`]; call [`next`](Self::next) until it returns
+/// `None`.
+pub struct TransactionStream<'a> {
+    web3: &'a Web3,
+    cursor: u64,
+    end: u64,
+    batch_size: u64,
+    from: Vec<Address>,
+    to: Vec<Address>,
+    buffer: VecDeque<(BlockSummary, Transaction)>,
+    /// Set at the end of the range or after an error.
+    finished: bool,
+}
+
+impl Web3 {
+    /// Transactions of `from_block..=to_block` in canonical order: by block, then by
+    /// position in the block.
+    pub fn transactions_in_range(&self, from_block: u64, to_block: u64) -> TransactionStream<'_> {
+        TransactionStream {
+            web3: self,
+            cursor: from_block,
+            end: to_block,
+            batch_size: DEFAULT_BATCH_BLOCKS,
+            from: Vec::new(),
+            to: Vec::new(),
+            buffer: VecDeque::new(),
+            finished: from_block > to_block,
+        }
+    }
+
+    /// `blocks` with full transactions, in one batch.
+    pub(crate) async fn full_blocks(&self, blocks: &[u64]) -> Result<Vec<Block>, Report<Error>> {
+        let payloads = blocks
+            .iter()
+            .map(|n| {
+                json!({ "jsonrpc": "2.0", "method": "eth_getBlockByNumber", "params": [format!("{:#x}", n), true], "id": "325" })
+            })
+            .collect();
+        let responses: Vec<JsonRpcResult<Block>> = self.client.call_batch(payloads).await?;
+        responses
+            .into_iter()
+            .zip(blocks)
+            .map(|(response, n)| match (response.result, response.error) {
+                (_, Some(error)) => Err(rpc_error(error)),
+                (Some(block), None) => Ok(block),
+                (None, None) => Err(Report::new(Error::UnexpectedResponseFormat)
+                    .attach_printable(format!("block {} not found", n))),
+            })
+            .collect()
+    }
+}
+
+impl<'a> TransactionStream<'a> {
+    pub fn batch_size(mut self, blocks: u64) -> Self {
+        self.batch_size = blocks.max(1);
+        self
+    }
+
+    /// Keeps transactions sent by one of `addresses`. With [`to_addresses`](Self::to_addresses)
+    /// as well, a transaction matching either set is kept.
+    pub fn from_addresses(mut self, addresses: &[Address]) -> Self {
+        self.from = addresses.to_vec();
+        self
+    }
+
+    /// Keeps transactions sent to one of `addresses`; contract creations have no recipient
+    /// and never match.
+    pub fn to_addresses(mut self, addresses: &[Address]) -> Self {
+        self.to = addresses.to_vec();
+        self
+    }
+
+    /// The next matching transaction, `None` at the end of the range. The stream stops
+    /// after an error.
+    pub async fn next(&mut self) -> Option<Result<(BlockSummary, Transaction), Report<Error>>> {
+        loop {
+            if let Some(item) = self.buffer.pop_front() {
+                return Some(Ok(item));
+            }
+            if self.finished {
+                return None;
+            }
+            let last = self
+                .end
+                .min(self.cursor.saturating_add(self.batch_size - 1));
+            let blocks: Vec<u64> = (self.cursor..=last).collect();
+            let pushed = match self.web3.full_blocks(&blocks).await {
+                Ok(blocks) => blocks.into_iter().try_for_each(|block| self.push(block)),
+                Err(report) => Err(report),
+            };
+            if let Err(report) = pushed {
+                self.finished = true;
+                self.buffer.clear();
+                return Some(Err(report));
+            }
+            match last < self.end {
+                true => self.cursor = last + 1,
+                false => self.finished = true,
+            }
+        }
+    }
+
+    fn matches(&self, tx: &Transaction) -> bool {
+        if self.from.is_empty() && self.to.is_empty() {
+            return true;
+        }
+        self.from.contains(&tx.from) || tx.to.is_some_and(|to| self.to.contains(&to))
+    }
+
+    fn push(&mut self, block: Block) -> Result<(), Report<Error>> {
+        let (Some(number), Some(hash)) = (block.number, block.hash) else {
+            return Err(Report::new(Error::UnexpectedResponseFormat)
+                .attach_printable("block in range has no number or hash"));
+        };
+        let summary = BlockSummary {
+            number,
+            hash,
+            timestamp: block.timestamp,
+        };
+        let transactions = match block.transactions {
+            BlockTransactions::Full(transactions) => transactions,
+            BlockTransactions::Hashes(hashes) if hashes.is_empty() => Vec::new(),
+            BlockTransactions::Hashes(_) => {
+                return Err(Report::new(Error::UnexpectedResponseFormat)
+                    .attach_printable(format!("block {} has only transaction hashes", number)))
+            }
+        };
+        for tx in transactions {
+            if self.matches(&tx) {
+                self.buffer.push_back((summary, tx));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Returned by [`Block::transactions_iter`]; call [`next`](Self::next) until it returns
+/// `None`.
+pub struct BlockTransactionIter<'a> {
+    web3: &'a Web3,
+    /// Hashes not fetched yet, in block order.
+    hashes: VecDeque<H256>,
+    ready: VecDeque<Result<Transaction, Report<Error>>>,
+    batch_size: usize,
+}
+
+impl Block {
+    /// The block's full transactions in block order. A block fetched with hashes only has
+    /// them fetched from `web3` by hash, a batch at a time as the iteration gets there; one
+    /// fetched with full transactions just hands them out.
+    pub fn transactions_iter<'a>(&self, web3: &'a Web3) -> BlockTransactionIter<'a> {
+        let (hashes, ready) = match &self.transactions {
+            BlockTransactions::Hashes(hashes) => {
+                (hashes.iter().copied().collect(), VecDeque::new())
+            }
+            BlockTransactions::Full(transactions) => (
+                VecDeque::new(),
+                transactions.iter().cloned().map(Ok).collect(),
+            ),
+        };
+        BlockTransactionIter {
+            web3,
+            hashes,
+            ready,
+            batch_size: DEFAULT_BATCH_TRANSACTIONS,
+        }
+    }
+}
+
+impl BlockTransactionIter<'_> {
+    pub fn batch_size(mut self, transactions: usize) -> Self {
+        self.batch_size = transactions.max(1);
+        self
+    }
+
+    /// Transactions still to come.
+    pub fn remaining(&self) -> usize {
+        self.hashes.len() + self.ready.len()
+    }
+
+    /// The next transaction, `None` after the last. A transaction the node does not return
+    /// (unknown, malformed, or its whole batch failed) is an `Err` in its place, and the
+    /// iteration goes on with the next one.
+    pub async fn next(&mut self) -> Option<Result<Transaction, Report<Error>>> {
+        if self.ready.is_empty() && !self.hashes.is_empty() {
+            let count = self.batch_size.min(self.hashes.len());
+            let batch: Vec<H256> = self.hashes.drain(..count).collect();
+            self.fetch(&batch).await;
+        }
+        self.ready.pop_front()
+    }
+
+    async fn fetch(&mut self, hashes: &[H256]) {
+        let payloads = hashes
+            .iter()
+            .map(|hash| {
+                json!({ "jsonrpc": "2.0", "method": "eth_getTransactionByHash", "params": [hash], "id": "326" })
+            })
+            .collect();
+        // read as `Value` so one transaction that does not parse fails alone
+        let responses: Vec<JsonRpcResult<Value>> = match self.web3.client.call_batch(payloads).await
+        {
+            Ok(responses) => responses,
+            Err(report) => {
+                for hash in hashes {
+                    self.ready.push_back(Err(
+                        copy_report(&report).attach_printable(format!("transaction {}", hash))
+                    ));
+                }
+                return;
+            }
+        };
+        for (response, hash) in responses.into_iter().zip(hashes) {
+            let tx = match (response.result, response.error) {
+                (_, Some(error)) => Err(rpc_error(error)),
+                (None, None) => Err(Report::new(Error::UnexpectedResponseFormat)
+                    .attach_printable("transaction not found")),
+                (Some(tx), None) => {
+                    serde_path_to_error::deserialize(tx).change_context(Error::FailedToDeserialize)
+                }
+            };
+            self.ready
+                .push_back(tx.attach_printable_lazy(|| format!("transaction {}", hash)));
+        }
+    }
+}
@@ -0,0 +1,1244 @@
+use crate::hex;
+use crate::model::Tag;
+use serde::de::{self, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::borrow::Borrow;
+use std::cmp::Ordering;
+use std::fmt::{self, Debug, Display, Formatter, LowerHex, UpperHex};
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseHexError {
+    Empty,
+    MissingPrefix,
+    InvalidCharacter(char),
+    InvalidLength { expected: usize, got: usize },
+    InvalidChecksum,
+    Overflow,
+}
+
+impl Display for ParseHexError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        Debug::fmt(self, f)
+    }
+}
+
+impl std::error::Error for ParseHexError {}
+
+/// 256-bit unsigned integer used for JSON-RPC quantities (balances, gas, fees).
+///
+/// Serializes as a minimal `0x`-prefixed hex string (`"0x0"`, `"0x1b4"`), which
+/// is the encoding the JSON-RPC spec requires for quantities.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct U256([u64; 4]);
+
+impl U256 {
+    pub const ZERO: U256 = U256([0; 4]);
+    pub const ONE: U256 = U256([1, 0, 0, 0]);
+    pub const MAX: U256 = U256([u64::MAX; 4]);
+
+    /// Builds a value from little-endian 64-bit limbs.
+    pub const fn from_limbs(limbs: [u64; 4]) -> Self {
+        U256(limbs)
+    }
+
+    /// Little-endian 64-bit limbs.
+    pub const fn as_limbs(&self) -> &[u64; 4] {
+        &self.0
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.0 == [0; 4]
+    }
+
+    /// Number of significant bits.
+    pub fn bits(&self) -> u32 {
+        for i in (0..4).rev() {
+            if self.0[i] != 0 {
+                return 64 * i as u32 + (64 - self.0[i].leading_zeros());
+            }
+        }
+        0
+    }
+
+    pub fn from_be_bytes(bytes: [u8; 32]) -> Self {
+        let mut limbs = [0u64; 4];
+        for (i, limb) in limbs.iter_mut().enumerate() {
+            let start = 32 - (i + 1) * 8;
+            let mut b = [0u8; 8];
+            b.copy_from_slice(&bytes[start..start + 8]);
+            *limb = u64::from_be_bytes(b);
+        }
+        U256(limbs)
+    }
+
+    pub fn to_be_bytes(&self) -> [u8; 32] {
+        let mut bytes = [0u8; 32];
+        for (i, limb) in self.0.iter().enumerate() {
+            let start = 32 - (i + 1) * 8;
+            bytes[start..start + 8].copy_from_slice(&limb.to_be_bytes());
+        }
+        bytes
+    }
+
+    /// Parses hex digits, with or without a `0x` prefix.
+    pub fn from_str_radix_16(s: &str) -> Result<Self, ParseHexError> {
+        let digits = s
+            .strip_prefix("0x")
+            .or_else(|| s.strip_prefix("0X"))
+            .unwrap_or(s);
+        if digits.is_empty() {
+            return Err(ParseHexError::Empty);
+        }
+        let mut result = U256::ZERO;
+        for c in digits.chars() {
+            let d = c.to_digit(16).ok_or(ParseHexError::InvalidCharacter(c))?;
+            if result.0[3] >> 60 != 0 {
+                return Err(ParseHexError::Overflow);
+            }
+            result = result.shl4() | U256::from(d as u64);
+        }
+        Ok(result)
+    }
+
+    /// Parses decimal digits.
+    pub fn from_dec_str(s: &str) -> Result<Self, ParseHexError> {
+        if s.is_empty() {
+            return Err(ParseHexError::Empty);
+        }
+        let ten = U256::from(10u64);
+        let mut result = U256::ZERO;
+        for c in s.chars() {
+            let d = c.to_digit(10).ok_or(ParseHexError::InvalidCharacter(c))?;
+            result = result
+                .checked_mul(ten)
+                .and_then(|r| r.checked_add(U256::from(d as u64)))
+                .ok_or(ParseHexError::Overflow)?;
+        }
+        Ok(result)
+    }
+
+    /// Minimal `0x`-prefixed hex encoding, `"0x0"` for zero.
+    pub fn to_hex_string(&self) -> String {
+        format!("{:#x}", self)
+    }
+
+    pub fn checked_add(self, rhs: U256) -> Option<U256> {
+        match self.overflowing_add(rhs) {
+            (sum, false) => Some(sum),
+            (_, true) => None,
+        }
+    }
+
+    /// Addition modulo 2^256, the way the EVM adds.
+    pub fn wrapping_add(self, rhs: U256) -> U256 {
+        self.overflowing_add(rhs).0
+    }
+
+    fn overflowing_add(self, rhs: U256) -> (U256, bool) {
+        let mut out = [0u64; 4];
+        let mut carry = 0u128;
+        for (i, limb) in out.iter_mut().enumerate() {
+            let sum = self.0[i] as u128 + rhs.0[i] as u128 + carry;
+            *limb = sum as u64;
+            carry = sum >> 64;
+        }
+        (U256(out), carry != 0)
+    }
+
+    pub fn checked_sub(self, rhs: U256) -> Option<U256> {
+        if self < rhs {
+            return None;
+        }
+        let mut out = [0u64; 4];
+        let mut borrow = false;
+        for (i, limb) in out.iter_mut().enumerate() {
+            let (d, b1) = self.0[i].overflowing_sub(rhs.0[i]);
+            let (d, b2) = d.overflowing_sub(borrow as u64);
+            *limb = d;
+            borrow = b1 || b2;
+        }
+        Some(U256(out))
+    }
+
+    pub fn checked_mul(self, rhs: U256) -> Option<U256> {
+        let mut out = [0u64; 4];
+        for i in 0..4 {
+            if self.0[i] == 0 {
+                continue;
+            }
+            if rhs.0[4 - i..].iter().any(|l| *l != 0) {
+                return None;
+            }
+            let mut carry = 0u128;
+            for j in 0..4 - i {
+                let acc = out[i + j] as u128 + self.0[i] as u128 * rhs.0[j] as u128 + carry;
+                out[i + j] = acc as u64;
+                carry = acc >> 64;
+            }
+            if carry != 0 {
+                return None;
+            }
+        }
+        Some(U256(out))
+    }
+
+    pub fn checked_div(self, rhs: U256) -> Option<U256> {
+        self.div_rem(rhs).map(|(q, _)| q)
+    }
+
+    pub fn checked_rem(self, rhs: U256) -> Option<U256> {
+        self.div_rem(rhs).map(|(_, r)| r)
+    }
+
+    pub fn saturating_add(self, rhs: U256) -> U256 {
+        self.checked_add(rhs).unwrap_or(U256::MAX)
+    }
+
+    pub fn saturating_sub(self, rhs: U256) -> U256 {
+        self.checked_sub(rhs).unwrap_or(U256::ZERO)
+    }
+
+    fn div_rem(self, rhs: U256) -> Option<(U256, U256)> {
+        if rhs.is_zero() {
+            return None;
+        }
+        if self < rhs {
+            return Some((U256::ZERO, self));
+        }
+        let mut quotient = U256::ZERO;
+        let mut remainder = U256::ZERO;
+        for i in (0..self.bits()).rev() {
+            remainder = remainder.shl1();
+            if self.bit(i) {
+                remainder.0[0] |= 1;
+            }
+            if remainder >= rhs {
+                remainder = remainder.checked_sub(rhs)?;
+                quotient.0[(i / 64) as usize] |= 1 << (i % 64);
+            }
+        }
+        Some((quotient, remainder))
+    }
+
+    fn div_rem_u64(self, rhs: u64) -> (U256, u64) {
+        let mut out = [0u64; 4];
+        let mut rem = 0u128;
+        for i in (0..4).rev() {
+            let cur = (rem << 64) | self.0[i] as u128;
+            out[i] = (cur / rhs as u128) as u64;
+            rem = cur % rhs as u128;
+        }
+        (U256(out), rem as u64)
+    }
+
+    fn bit(&self, i: u32) -> bool {
+        (self.0[(i / 64) as usize] >> (i % 64)) & 1 == 1
+    }
+
+    fn shl1(self) -> U256 {
+        let mut out = [0u64; 4];
+        for (i, limb) in out.iter_mut().enumerate() {
+            *limb = self.0[i] << 1;
+            if i > 0 {
+                *limb |= self.0[i - 1] >> 63;
+            }
+        }
+        U256(out)
+    }
+
+    fn shl4(self) -> U256 {
+        let mut out = [0u64; 4];
+        for (i, limb) in out.iter_mut().enumerate() {
+            *limb = self.0[i] << 4;
+            if i > 0 {
+                *limb |= self.0[i - 1] >> 60;
+            }
+        }
+        U256(out)
+    }
+}
+
+impl std::ops::BitOr for U256 {
+    type Output = U256;
+
+    fn bitor(self, rhs: U256) -> U256 {
+        U256([
+            self.0[0] | rhs.0[0],
+            self.0[1] | rhs.0[1],
+            self.0[2] | rhs.0[2],
+            self.0[3] | rhs.0[3],
+        ])
+    }
+}
+
+impl Ord for U256 {
+    fn cmp(&self, other: &Self) -> Ordering {
+        for i in (0..4).rev() {
+            match self.0[i].cmp(&other.0[i]) {
+                Ordering::Equal => continue,
+                o => return o,
+            }
+        }
+        Ordering::Equal
+    }
+}
+
+impl PartialOrd for U256 {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl From<u64> for U256 {
+    fn from(v: u64) -> Self {
+        U256([v, 0, 0, 0])
+    }
+}
+
+impl From<u128> for U256 {
+    fn from(v: u128) -> Self {
+        U256([v as u64, (v >> 64) as u64, 0, 0])
+    }
+}
+
+impl TryFrom<U256> for u64 {
+    type Error = ParseHexError;
+
+    fn try_from(v: U256) -> Result<Self, Self::Error> {
+        if v.0[1..].iter().any(|l| *l != 0) {
+            return Err(ParseHexError::Overflow);
+        }
+        Ok(v.0[0])
+    }
+}
+
+impl TryFrom<U256> for u128 {
+    type Error = ParseHexError;
+
+    fn try_from(v: U256) -> Result<Self, Self::Error> {
+        if v.0[2..].iter().any(|l| *l != 0) {
+            return Err(ParseHexError::Overflow);
+        }
+        Ok(((v.0[1] as u128) << 64) | v.0[0] as u128)
+    }
+}
+
+impl FromStr for U256 {
+    type Err = ParseHexError;
+
+    /// Accepts `0x`-prefixed hex or plain decimal.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.starts_with("0x") || s.starts_with("0X") {
+            U256::from_str_radix_16(s)
+        } else {
+            U256::from_dec_str(s)
+        }
+    }
+}
+
+impl Display for U256 {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if self.is_zero() {
+            return f.pad_integral(true, "", "0");
+        }
+        // peel off 19 decimal digits at a time
+        const CHUNK: u64 = 10_000_000_000_000_000_000;
+        let mut chunks = Vec::new();
+        let mut v = *self;
+        while !v.is_zero() {
+            let (q, r) = v.div_rem_u64(CHUNK);
+            chunks.push(r);
+            v = q;
+        }
+        let mut s = chunks.pop().unwrap_or_default().to_string();
+        for c in chunks.iter().rev() {
+            s.push_str(&format!("{:019}", c));
+        }
+        f.pad_integral(true, "", &s)
+    }
+}
+
+impl Debug for U256 {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        Display::fmt(self, f)
+    }
+}
+
+impl LowerHex for U256 {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let mut s = String::new();
+        for limb in self.0.iter().rev() {
+            if s.is_empty() {
+                if *limb != 0 {
+                    s = format!("{:x}", limb);
+                }
+            } else {
+                s.push_str(&format!("{:016x}", limb));
+            }
+        }
+        if s.is_empty() {
+            s.push('0');
+        }
+        f.pad_integral(true, "0x", &s)
+    }
+}
+
+impl UpperHex for U256 {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let s = format!("{:x}", self).to_uppercase();
+        f.pad_integral(true, "0x", &s)
+    }
+}
+
+impl Serialize for U256 {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&self.to_hex_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for U256 {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct U256Visitor;
+
+        impl<'de> Visitor<'de> for U256Visitor {
+            type Value = U256;
+
+            fn expecting(&self, f: &mut Formatter) -> fmt::Result {
+                f.write_str("a 0x-prefixed hex quantity")
+            }
+
+            fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+            where
+                E: de::Error,
+            {
+                if !v.starts_with("0x") && !v.starts_with("0X") {
+                    return Err(E::custom(ParseHexError::MissingPrefix));
+                }
+                U256::from_str_radix_16(v).map_err(E::custom)
+            }
+
+            fn visit_u64<E>(self, v: u64) -> Result<Self::Value, E>
+            where
+                E: de::Error,
+            {
+                Ok(U256::from(v))
+            }
+        }
+
+        deserializer.deserialize_any(U256Visitor)
+    }
+}
+
+/// 20-byte account address.
+///
+/// Parses from lowercase, uppercase, or EIP-55 checksummed hex (mixed case with
+/// a wrong checksum is rejected), displays as EIP-55 and serializes as lowercase hex.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Address(pub [u8; 20]);
+
+impl Address {
+    pub const ZERO: Address = Address([0; 20]);
+
+    pub fn as_bytes(&self) -> &[u8; 20] {
+        &self.0
+    }
+
+    /// Lowercase `0x`-prefixed hex, the form nodes send and expect.
+    pub fn to_lower_hex(&self) -> String {
+        hex::data(&self.0)
+    }
+
+    /// EIP-55 mixed-case checksum encoding.
+    pub fn to_checksum(&self) -> String {
+        let lower = self.to_lower_hex();
+        let hash = crate::keccak::keccak256(&lower.as_bytes()[2..]);
+        let mut out = String::with_capacity(42);
+        out.push_str("0x");
+        for (i, c) in lower[2..].chars().enumerate() {
+            let nibble = (hash[i / 2] >> (if i % 2 == 0 { 4 } else { 0 })) & 0x0f;
+            if c.is_ascii_alphabetic() && nibble >= 8 {
+                out.push(c.to_ascii_uppercase());
+            } else {
+                out.push(c);
+            }
+        }
+        out
+    }
+}
+
+impl FromStr for Address {
+    type Err = ParseHexError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let address = Address(hex::parse_fixed(s)?);
+        let digits = &s[2..];
+        let mixed = digits.chars().any(|c| c.is_ascii_lowercase())
+            && digits.chars().any(|c| c.is_ascii_uppercase());
+        if mixed && address.to_checksum() != s {
+            return Err(ParseHexError::InvalidChecksum);
+        }
+        Ok(address)
+    }
+}
+
+impl Display for Address {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.to_checksum())
+    }
+}
+
+impl Debug for Address {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.to_checksum())
+    }
+}
+
+impl From<[u8; 20]> for Address {
+    fn from(bytes: [u8; 20]) -> Self {
+        Address(bytes)
+    }
+}
+
+impl From<Address> for [u8; 20] {
+    fn from(address: Address) -> Self {
+        address.0
+    }
+}
+
+impl AsRef<[u8]> for Address {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl Borrow<[u8; 20]> for Address {
+    fn borrow(&self) -> &[u8; 20] {
+        &self.0
+    }
+}
+
+impl Serialize for Address {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&self.to_lower_hex())
+    }
+}
+
+impl<'de> Deserialize<'de> for Address {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        Address::from_str(&s).map_err(de::Error::custom)
+    }
+}
+
+/// Address argument accepted by RPC methods: either a typed [`Address`] or a
+/// plain string, which is passed through unchanged for compatibility.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AddressParam(String);
+
+impl AddressParam {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl From<&str> for AddressParam {
+    fn from(s: &str) -> Self {
+        AddressParam(s.to_string())
+    }
+}
+
+impl From<String> for AddressParam {
+    fn from(s: String) -> Self {
+        AddressParam(s)
+    }
+}
+
+impl From<&String> for AddressParam {
+    fn from(s: &String) -> Self {
+        AddressParam(s.clone())
+    }
+}
+
+impl From<Address> for AddressParam {
+    fn from(address: Address) -> Self {
+        AddressParam(address.to_lower_hex())
+    }
+}
+
+impl From<&Address> for AddressParam {
+    fn from(address: &Address) -> Self {
+        AddressParam(address.to_lower_hex())
+    }
+}
+
+impl Serialize for AddressParam {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&self.0)
+    }
+}
+
+/// Block number argument of the `*ByNumber` methods: a number or [`Tag`], formatted as the
+/// node expects, or a plain string passed through unchanged for compatibility.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlockNumberParam(String);
+
+impl BlockNumberParam {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl From<u64> for BlockNumberParam {
+    fn from(n: u64) -> Self {
+        BlockNumberParam(hex::quantity(n.into()))
+    }
+}
+
+impl From<Tag> for BlockNumberParam {
+    fn from(tag: Tag) -> Self {
+        BlockNumberParam(String::from(tag))
+    }
+}
+
+impl From<&str> for BlockNumberParam {
+    fn from(s: &str) -> Self {
+        BlockNumberParam(s.to_string())
+    }
+}
+
+impl From<String> for BlockNumberParam {
+    fn from(s: String) -> Self {
+        BlockNumberParam(s)
+    }
+}
+
+impl From<&String> for BlockNumberParam {
+    fn from(s: &String) -> Self {
+        BlockNumberParam(s.clone())
+    }
+}
+
+impl Serialize for BlockNumberParam {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&self.0)
+    }
+}
+
+/// Position of a transaction or uncle in its block, sent as a hex quantity; plain strings
+/// are passed through unchanged for compatibility.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IndexParam(String);
+
+impl IndexParam {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl From<u64> for IndexParam {
+    fn from(n: u64) -> Self {
+        IndexParam(hex::quantity(n.into()))
+    }
+}
+
+impl From<&str> for IndexParam {
+    fn from(s: &str) -> Self {
+        IndexParam(s.to_string())
+    }
+}
+
+impl From<String> for IndexParam {
+    fn from(s: String) -> Self {
+        IndexParam(s)
+    }
+}
+
+impl From<&String> for IndexParam {
+    fn from(s: &String) -> Self {
+        IndexParam(s.clone())
+    }
+}
+
+impl Serialize for IndexParam {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&self.0)
+    }
+}
+
+/// Storage position of eth_getStorageAt. Numbers, words and [`U256`] slots, such as those
+/// of [`crate::storage::mapping_slot`], are sent as hex quantities. Strings are sent as given
+/// but must be `0x`-prefixed hex of at most 64 digits, so a decimal `"5"` fails with
+/// [`crate::client::Error::InvalidArgument`] before anything is sent.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StorageSlot(String);
+
+impl StorageSlot {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl From<u64> for StorageSlot {
+    fn from(n: u64) -> Self {
+        StorageSlot(hex::quantity(n.into()))
+    }
+}
+
+impl From<U256> for StorageSlot {
+    fn from(n: U256) -> Self {
+        StorageSlot(n.to_hex_string())
+    }
+}
+
+impl From<[u8; 32]> for StorageSlot {
+    fn from(word: [u8; 32]) -> Self {
+        StorageSlot::from(U256::from_be_bytes(word))
+    }
+}
+
+impl From<H256> for StorageSlot {
+    fn from(word: H256) -> Self {
+        StorageSlot::from(word.0)
+    }
+}
+
+impl From<&str> for StorageSlot {
+    fn from(s: &str) -> Self {
+        StorageSlot(s.to_string())
+    }
+}
+
+impl From<String> for StorageSlot {
+    fn from(s: String) -> Self {
+        StorageSlot(s)
+    }
+}
+
+impl From<&String> for StorageSlot {
+    fn from(s: &String) -> Self {
+        StorageSlot(s.clone())
+    }
+}
+
+impl Serialize for StorageSlot {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&self.0)
+    }
+}
+
+/// 32-byte hash (block hashes, transaction hashes, topics, storage keys).
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct H256(pub [u8; 32]);
+
+impl H256 {
+    pub const ZERO: H256 = H256([0; 32]);
+
+    pub fn as_bytes(&self) -> &[u8; 32] {
+        &self.0
+    }
+
+    pub fn to_hex(&self) -> String {
+        hex::data(&self.0)
+    }
+}
+
+impl FromStr for H256 {
+    type Err = ParseHexError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(H256(hex::parse_fixed(s)?))
+    }
+}
+
+impl Display for H256 {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.to_hex())
+    }
+}
+
+impl Debug for H256 {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.to_hex())
+    }
+}
+
+impl From<[u8; 32]> for H256 {
+    fn from(bytes: [u8; 32]) -> Self {
+        H256(bytes)
+    }
+}
+
+impl From<H256> for [u8; 32] {
+    fn from(hash: H256) -> Self {
+        hash.0
+    }
+}
+
+impl AsRef<[u8]> for H256 {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl Borrow<[u8; 32]> for H256 {
+    fn borrow(&self) -> &[u8; 32] {
+        &self.0
+    }
+}
+
+impl Serialize for H256 {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&self.to_hex())
+    }
+}
+
+impl<'de> Deserialize<'de> for H256 {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        H256::from_str(&s).map_err(de::Error::custom)
+    }
+}
+
+/// Hash argument accepted by RPC methods: either a typed [`H256`] or a plain
+/// string, which is passed through unchanged for compatibility.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct H256Param(String);
+
+impl H256Param {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl From<&str> for H256Param {
+    fn from(s: &str) -> Self {
+        H256Param(s.to_string())
+    }
+}
+
+impl From<String> for H256Param {
+    fn from(s: String) -> Self {
+        H256Param(s)
+    }
+}
+
+impl From<&String> for H256Param {
+    fn from(s: &String) -> Self {
+        H256Param(s.clone())
+    }
+}
+
+impl From<H256> for H256Param {
+    fn from(hash: H256) -> Self {
+        H256Param(hash.to_hex())
+    }
+}
+
+impl From<&H256> for H256Param {
+    fn from(hash: &H256) -> Self {
+        H256Param(hash.to_hex())
+    }
+}
+
+impl Serialize for H256Param {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&self.0)
+    }
+}
+
+/// 2048-bit logs bloom filter.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Bloom(pub [u8; 256]);
+
+impl Bloom {
+    /// Whether `input` (an address or topic) may be present; false positives are possible.
+    pub fn contains_input(&self, input: &[u8]) -> bool {
+        Self::bits(input).all(|(byte, mask)| self.0[byte] & mask != 0)
+    }
+
+    /// Adds `input` (an address or topic), as a node does for each log of a receipt.
+    pub fn accrue(&mut self, input: &[u8]) {
+        for (byte, mask) in Self::bits(input) {
+            self.0[byte] |= mask;
+        }
+    }
+
+    /// The three bits `input` sets, as byte index and mask.
+    fn bits(input: &[u8]) -> impl Iterator<Item = (usize, u8)> {
+        let hash = crate::keccak::keccak256(input);
+        (0..3).map(move |i| {
+            let bit = (((hash[2 * i] as usize) << 8) | hash[2 * i + 1] as usize) & 2047;
+            (255 - bit / 8, 1 << (bit % 8))
+        })
+    }
+
+    pub fn to_hex(&self) -> String {
+        hex::data(&self.0)
+    }
+}
+
+impl Default for Bloom {
+    fn default() -> Self {
+        Bloom([0; 256])
+    }
+}
+
+impl FromStr for Bloom {
+    type Err = ParseHexError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Bloom(hex::parse_fixed(s)?))
+    }
+}
+
+impl Debug for Bloom {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.to_hex())
+    }
+}
+
+impl Serialize for Bloom {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&self.to_hex())
+    }
+}
+
+impl<'de> Deserialize<'de> for Bloom {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        Bloom::from_str(&s).map_err(de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn storage_slot_forms() {
+        let mut word = [0u8; 32];
+        word[31] = 0x2a;
+        let max = format!("0x{}", "f".repeat(64));
+        let cases = [
+            (StorageSlot::from(0u64), "0x0"),
+            (StorageSlot::from(9u64), "0x9"),
+            (StorageSlot::from(u64::MAX), "0xffffffffffffffff"),
+            (StorageSlot::from(U256::ZERO), "0x0"),
+            (StorageSlot::from(U256::from(0x1b4u64)), "0x1b4"),
+            (StorageSlot::from(U256::MAX), max.as_str()),
+            (StorageSlot::from([0u8; 32]), "0x0"),
+            (StorageSlot::from(word), "0x2a"),
+            (StorageSlot::from([0xff; 32]), max.as_str()),
+            (StorageSlot::from(H256(word)), "0x2a"),
+            (StorageSlot::from("0x02"), "0x02"),
+            (StorageSlot::from(String::from("0x2a")), "0x2a"),
+        ];
+        for (slot, expected) in cases {
+            assert_eq!(serde_json::to_value(&slot).unwrap(), expected);
+            assert_eq!(slot.as_str(), expected);
+        }
+    }
+
+    #[test]
+    fn u256_serde_round_trip_minimal_hex() {
+        let cases = [
+            ("\"0x0\"", U256::ZERO),
+            ("\"0x1\"", U256::ONE),
+            ("\"0x1b4\"", U256::from(0x1b4u64)),
+            ("\"0x10000000000000000\"", U256::from(1u128 << 64)),
+            (
+                "\"0xffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff\"",
+                U256::MAX,
+            ),
+        ];
+        for (json, value) in cases {
+            let parsed: U256 = serde_json::from_str(json).unwrap();
+            assert_eq!(parsed, value);
+            assert_eq!(serde_json::to_string(&value).unwrap(), json);
+        }
+
+        // leading zeros are accepted on input but never produced
+        let parsed: U256 = serde_json::from_str("\"0x00ff\"").unwrap();
+        assert_eq!(serde_json::to_string(&parsed).unwrap(), "\"0xff\"");
+
+        assert!(serde_json::from_str::<U256>("\"0x\"").is_err());
+        assert!(serde_json::from_str::<U256>("\"1b4\"").is_err());
+        assert!(serde_json::from_str::<U256>("\"0xzz\"").is_err());
+        assert!(serde_json::from_str::<U256>(
+            "\"0x1ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff\""
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn u256_display_and_arithmetic() {
+        let max = "115792089237316195423570985008687907853269984665640564039457584007913129639935";
+        assert_eq!(U256::from_dec_str(max).unwrap(), U256::MAX);
+        assert_eq!(U256::MAX.to_string(), max);
+        assert_eq!(U256::ZERO.to_string(), "0");
+        assert_eq!(format!("{:x}", U256::from(255u64)), "ff");
+        assert_eq!(format!("{:#x}", U256::from(255u64)), "0xff");
+
+        let gas = U256::from(21_000u64);
+        let price = U256::from(30_000_000_000u64);
+        assert_eq!(
+            gas.checked_mul(price),
+            Some(U256::from(630_000_000_000_000u64))
+        );
+        assert_eq!(U256::MAX.checked_add(U256::ONE), None);
+        assert_eq!(U256::MAX.wrapping_add(U256::from(2u64)), U256::ONE);
+        assert_eq!(U256::ZERO.checked_sub(U256::ONE), None);
+        assert_eq!(U256::MAX.checked_mul(U256::from(2u64)), None);
+        let hundred = U256::from(100u64);
+        assert_eq!(
+            hundred.checked_div(U256::from(7u64)),
+            Some(U256::from(14u64))
+        );
+        assert_eq!(
+            hundred.checked_rem(U256::from(7u64)),
+            Some(U256::from(2u64))
+        );
+        assert_eq!(U256::ONE.checked_div(U256::ZERO), None);
+
+        assert_eq!(u64::try_from(U256::from(42u64)), Ok(42));
+        assert!(u64::try_from(U256::from(u128::MAX)).is_err());
+        assert_eq!(u128::try_from(U256::from(u128::MAX)), Ok(u128::MAX));
+        assert_eq!(U256::from_be_bytes(U256::MAX.to_be_bytes()), U256::MAX);
+    }
+
+    #[test]
+    fn address_parse_and_checksum() {
+        let checksummed = "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed";
+        let a: Address = checksummed.parse().unwrap();
+        assert_eq!(a.to_string(), checksummed);
+        assert_eq!(a, checksummed.to_lowercase().parse().unwrap());
+        assert_eq!(
+            a,
+            checksummed
+                .to_uppercase()
+                .replace("0X", "0x")
+                .parse()
+                .unwrap()
+        );
+        assert_eq!(
+            serde_json::to_string(&a).unwrap(),
+            "\"0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed\""
+        );
+        let back: Address = serde_json::from_str(&serde_json::to_string(&a).unwrap()).unwrap();
+        assert_eq!(back, a);
+
+        assert_eq!(
+            "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAeD".parse::<Address>(),
+            Err(ParseHexError::InvalidChecksum)
+        );
+        assert_eq!(
+            "0x5aaeb6053f3e94c9b9a09f33669435e7ef1bea".parse::<Address>(),
+            Err(ParseHexError::InvalidLength {
+                expected: 40,
+                got: 38
+            })
+        );
+        assert_eq!(
+            "5aaeb6053f3e94c9b9a09f33669435e7ef1beaed".parse::<Address>(),
+            Err(ParseHexError::MissingPrefix)
+        );
+        assert_eq!(
+            "0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaeg".parse::<Address>(),
+            Err(ParseHexError::InvalidCharacter('g'))
+        );
+
+        assert_eq!(AddressParam::from(a).as_str(), checksummed.to_lowercase());
+        assert_eq!(AddressParam::from(checksummed).as_str(), checksummed);
+    }
+
+    #[test]
+    fn h256_parse_and_serde() {
+        let hex = "0xe812a49745d691961893d7cfd3902d78d710751bab872f12215ee23f27f3efa9";
+        let h: H256 = hex.parse().unwrap();
+        assert_eq!(h.to_string(), hex);
+        assert_eq!(h, hex.to_uppercase().replace("0X", "0x").parse().unwrap());
+        assert_eq!(serde_json::to_string(&h).unwrap(), format!("\"{}\"", hex));
+        let back: H256 = serde_json::from_str(&format!("\"{}\"", hex)).unwrap();
+        assert_eq!(back, h);
+
+        assert!(matches!(
+            hex[..hex.len() - 1].parse::<H256>(),
+            Err(ParseHexError::InvalidLength { .. })
+        ));
+        assert_eq!(hex[2..].parse::<H256>(), Err(ParseHexError::MissingPrefix));
+        assert_eq!(H256Param::from(h).as_str(), hex);
+    }
+
+    #[test]
+    fn tricky_hex_vectors() {
+        assert_eq!(U256::from_str_radix_16("0x"), Err(ParseHexError::Empty));
+        assert_eq!(U256::from_str_radix_16("0x0"), Ok(U256::ZERO));
+        assert_eq!(U256::ZERO.to_hex_string(), "0x0");
+        assert_eq!(U256::from_str_radix_16("0xabc"), Ok(U256::from(0xabcu64)));
+        assert_eq!(U256::from_str_radix_16("0XABC"), Ok(U256::from(0xabcu64)));
+        assert_eq!(
+            U256::from_str_radix_16("0x+1"),
+            Err(ParseHexError::InvalidCharacter('+'))
+        );
+
+        // data must be exactly two digits per byte
+        let odd = format!("0x{}", "a".repeat(39));
+        assert_eq!(
+            odd.parse::<Address>(),
+            Err(ParseHexError::InvalidLength {
+                expected: 40,
+                got: 39
+            })
+        );
+        assert_eq!(hex::data(&[]), "0x");
+        assert_eq!(hex::data(&[0x00, 0x0f, 0xff]), "0x000fff");
+        let upper = format!("0x{}", "AB".repeat(32));
+        assert_eq!(upper.parse::<H256>().unwrap(), H256([0xab; 32]));
+
+        // a multi-byte character must not split a digit pair
+        let wide = format!("0x{}é", "a".repeat(38));
+        assert_eq!(
+            wide.parse::<Address>(),
+            Err(ParseHexError::InvalidCharacter('é'))
+        );
+    }
+
+    mod props {
+        use super::*;
+        use proptest::collection::vec;
+        use proptest::prelude::*;
+
+        fn decode_pairs(hex: &str) -> Vec<u8> {
+            (2..hex.len())
+                .step_by(2)
+                .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+                .collect()
+        }
+
+        proptest! {
+            #[test]
+            fn quantity_round_trip(n: u128) {
+                let value = U256::from(n);
+                let hex = value.to_hex_string();
+                let digits = hex.strip_prefix("0x").unwrap();
+                prop_assert!(digits == "0" || !digits.starts_with('0'), "{}", hex);
+                prop_assert_eq!(&hex, &format!("{:#x}", n));
+                prop_assert_eq!(U256::from_str_radix_16(&hex), Ok(value));
+                let json = serde_json::to_string(&value).unwrap();
+                prop_assert_eq!(serde_json::from_str::<U256>(&json).unwrap(), value);
+                prop_assert_eq!(u128::try_from(value), Ok(n));
+            }
+
+            #[test]
+            fn u256_bytes_round_trip(bytes: [u8; 32]) {
+                let value = U256::from_be_bytes(bytes);
+                prop_assert_eq!(value.to_be_bytes(), bytes);
+                prop_assert_eq!(U256::from_str_radix_16(&value.to_hex_string()), Ok(value));
+            }
+
+            #[test]
+            fn data_hex_round_trip(bytes in vec(any::<u8>(), 0..256)) {
+                let hex = hex::data(&bytes);
+                prop_assert_eq!(hex.len(), 2 + bytes.len() * 2);
+                prop_assert!(!hex[2..].chars().any(|c| c.is_ascii_uppercase()));
+                prop_assert_eq!(decode_pairs(&hex), bytes.clone());
+                prop_assert_eq!(hex::parse_data(&hex), Ok(bytes));
+            }
+
+            #[test]
+            fn h256_round_trip(bytes: [u8; 32]) {
+                let hash = H256(bytes);
+                prop_assert_eq!(hash.to_hex().parse::<H256>(), Ok(hash));
+                let upper = format!("0x{}", hash.to_hex()[2..].to_ascii_uppercase());
+                prop_assert_eq!(upper.parse::<H256>(), Ok(hash));
+            }
+
+            #[test]
+            fn address_checksum_round_trip(bytes: [u8; 20]) {
+                let address = Address(bytes);
+                let checksum = address.to_checksum();
+                prop_assert_eq!(checksum.parse::<Address>(), Ok(address));
+                prop_assert_eq!(checksum.to_ascii_lowercase(), address.to_lower_hex());
+                prop_assert_eq!(address.to_lower_hex().parse::<Address>(), Ok(address));
+                let upper = format!("0x{}", address.to_lower_hex()[2..].to_ascii_uppercase());
+                prop_assert_eq!(upper.parse::<Address>(), Ok(address));
+
+                // flipping one letter breaks the checksum as long as the result stays mixed-case
+                let letters: Vec<usize> = checksum
+                    .char_indices()
+                    .skip(2)
+                    .filter(|(_, c)| c.is_ascii_alphabetic())
+                    .map(|(i, _)| i)
+                    .collect();
+                if letters.len() >= 2 {
+                    let mut flipped: Vec<char> = checksum.chars().collect();
+                    let c = flipped[letters[0]];
+                    flipped[letters[0]] = if c.is_ascii_uppercase() {
+                        c.to_ascii_lowercase()
+                    } else {
+                        c.to_ascii_uppercase()
+                    };
+                    let flipped: String = flipped.into_iter().collect();
+                    let digits = &flipped[2..];
+                    if digits.chars().any(|c| c.is_ascii_lowercase())
+                        && digits.chars().any(|c| c.is_ascii_uppercase())
+                    {
+                        prop_assert_eq!(
+                            flipped.parse::<Address>(),
+                            Err(ParseHexError::InvalidChecksum)
+                        );
+                    }
+                }
+            }
+
+            #[test]
+            fn parsers_never_panic(s in "\\PC*", digits in "[0-9a-fA-Fé]{38,42}") {
+                for input in [s.clone(), format!("0x{}", s), format!("0x{}", digits)] {
+                    let _ = input.parse::<Address>();
+                    let _ = input.parse::<H256>();
+                    let _ = U256::from_str_radix_16(&input);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,206 @@
+//! High-value reads checked against a second, independent provider: both are asked at the
+//! same time and the value is returned only when they agree, otherwise
+//! [`Error::ProvidersDisagree`].
+//!
+//! ```no_run
+//! # use web3_rpc::web3::Web3;
+//! # async fn example(infura: Web3, alchemy: Web3) -> Result<(), error_stack::Report<web3_rpc::client::Error>> {
+//! let address = "0x00000000219ab540356cbb839cbe05303d7705fa";
+//! let balance = infura.verified(&alchemy).pinned().balance(address, None).await?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Answers are compared after parsing, so hex case and leading zeros do not matter, and with
+//! `null` fields removed, so a field one node leaves out and another sends as `null` does not
+//! either.
+
+use crate::client::{rpc_error, Error};
+use crate::endpoints::head;
+use crate::model::{Block, BlockId, JsonRpcResult, Receipt};
+use crate::types::{AddressParam, H256Param, StorageSlot, H256, U256};
+use crate::web3::Web3;
+use error_stack::{Report, ResultExt};
+use serde::Serialize;
+use serde_json::Value;
+use std::future::Future;
+
+/// See [`Web3::verified`].
+#[derive(Clone, Copy)]
+pub struct Verified<'a> {
+    ours: &'a Web3,
+    theirs: &'a Web3,
+    pinned: bool,
+}
+
+impl Web3 {
+    /// Reads that go to this client and `other` and must agree, see [`crate::verified`].
+    pub fn verified<'a>(&'a self, other: &'a Web3) -> Verified<'a> {
+        Verified {
+            ours: self,
+            theirs: other,
+            pinned: false,
+        }
+    }
+}
+
+impl Verified<'_> {
+    /// Reads given no block go to the same block number on both, the highest both have,
+    /// instead of each node's own default tag, which may be a block apart.
+    pub fn pinned(mut self) -> Self {
+        self.pinned = true;
+        self
+    }
+
+    pub async fn balance(
+        &self,
+        address: impl Into<AddressParam>,
+        block: Option<BlockId>,
+    ) -> Result<U256, Report<Error>> {
+        let address = address.into();
+        let block = self.block(block).await?;
+        let balance = self
+            .agree(
+                "eth_getBalance",
+                self.ours
+                    .eth_get_balance_at_block(address.clone(), block.clone()),
+                self.theirs.eth_get_balance_at_block(address, block),
+                parse_u256,
+            )
+            .await?;
+        Ok(balance)
+    }
+
+    pub async fn storage(
+        &self,
+        address: impl Into<AddressParam>,
+        slot: impl Into<StorageSlot>,
+        block: Option<BlockId>,
+    ) -> Result<H256, Report<Error>> {
+        let (address, slot) = (address.into(), slot.into());
+        let block = self.block(block).await?;
+        let word = self
+            .agree(
+                "eth_getStorageAt",
+                self.ours
+                    .eth_get_storage_at_block(address.clone(), slot.clone(), block.clone()),
+                self.theirs.eth_get_storage_at_block(address, slot, block),
+                parse_u256,
+            )
+            .await?;
+        Ok(H256(word.to_be_bytes()))
+    }
+
+    /// `None` when both agree the block is unknown.
+    pub async fn block_by_hash(
+        &self,
+        hash: impl Into<H256Param>,
+        full: bool,
+    ) -> Result<Option<Block>, Report<Error>> {
+        let hash = hash.into();
+        self.agree(
+            "eth_getBlockByHash",
+            self.ours.eth_get_block_by_hash(hash.clone(), full),
+            self.theirs.eth_get_block_by_hash(hash, full),
+            |r: JsonRpcResult<Block>| nullable(r),
+        )
+        .await
+    }
+
+    /// `None` when both agree the transaction is not mined.
+    pub async fn receipt(
+        &self,
+        hash: impl Into<H256Param>,
+    ) -> Result<Option<Receipt>, Report<Error>> {
+        let hash = hash.into();
+        self.agree(
+            "eth_getTransactionReceipt",
+            self.ours.eth_get_transaction_receipt(hash.clone()),
+            self.theirs.eth_get_transaction_receipt(hash),
+            |r: JsonRpcResult<Receipt>| nullable(r),
+        )
+        .await
+    }
+
+    async fn block(&self, block: Option<BlockId>) -> Result<BlockId, Report<Error>> {
+        match block {
+            Some(block) => Ok(block),
+            None if self.pinned => {
+                let (ours, theirs) = tokio::try_join!(head(self.ours), head(self.theirs))?;
+                Ok(BlockId::Number(ours.min(theirs)))
+            }
+            None => Ok(self.ours.client.default_tag().into()),
+        }
+    }
+
+    /// Runs both reads at once and compares what `parse` makes of them.
+    async fn agree<R, T>(
+        &self,
+        method: &'static str,
+        ours: impl Future<Output = Result<R, Report<Error>>>,
+        theirs: impl Future<Output = Result<R, Report<Error>>>,
+        parse: impl Fn(R) -> Result<T, Report<Error>>,
+    ) -> Result<T, Report<Error>>
+    where
+        T: Serialize,
+    {
+        let (ours, theirs) = tokio::try_join!(ours, theirs)?;
+        let (ours, theirs) = (parse(ours)?, parse(theirs)?);
+        let (a, b) = (normalize(&ours), normalize(&theirs));
+        if a != b {
+            return Err(Report::new(Error::ProvidersDisagree {
+                method,
+                ours: a,
+                theirs: b,
+            })
+            .attach_printable(format!("other endpoint: {}", self.theirs.url())));
+        }
+        Ok(ours)
+    }
+}
+
+fn parse_u256(r: JsonRpcResult<String>) -> Result<U256, Report<Error>> {
+    let value = r.into_result().map_err(rpc_error)?;
+    value
+        .parse::<U256>()
+        .change_context(Error::UnexpectedResponseFormat)
+        .attach_printable_lazy(|| format!("not a word: {}", value))
+}
+
+fn nullable<T>(r: JsonRpcResult<T>) -> Result<Option<T>, Report<Error>> {
+    r.into_option().map_err(rpc_error)
+}
+
+/// The value as JSON with `null` fields dropped and hex strings lowercased.
+fn normalize(value: &impl Serialize) -> Value {
+    fn clean(value: Value) -> Value {
+        match value {
+            Value::String(s) if s.starts_with("0x") => Value::String(s.to_ascii_lowercase()),
+            Value::Array(items) => items.into_iter().map(clean).collect(),
+            Value::Object(fields) => fields
+                .into_iter()
+                .filter(|(_, v)| !v.is_null())
+                .map(|(k, v)| (k, clean(v)))
+                .collect(),
+            value => value,
+        }
+    }
+    clean(serde_json::to_value(value).unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn normalized_representations() {
+        let a = json!({ "hash": "0xABCD", "baseFee": null, "logs": [{ "data": "0xFF", "removed": false }] });
+        let b = json!({ "hash": "0xabcd", "logs": [{ "data": "0xff", "removed": false }] });
+        assert_eq!(normalize(&a), normalize(&b));
+        let c = json!({ "hash": "0xabcd", "logs": [{ "data": "0xfe", "removed": false }] });
+        assert_ne!(normalize(&a), normalize(&c));
+        // only hex is case folded
+        assert_ne!(normalize(&json!("Transfer")), normalize(&json!("transfer")));
+    }
+}
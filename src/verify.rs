@@ -0,0 +1,253 @@
+//! Local integrity checks on blocks served by an untrusted node: recompute the header hash
+//! and check that consecutive blocks link up.
+
+use crate::hex;
+use crate::keccak::keccak256;
+use crate::model::Block;
+use crate::rlp;
+use crate::types::{H256, U256};
+use std::fmt::{self, Display, Formatter};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VerifyError {
+    /// Pending blocks have no hash, number or nonce to check.
+    Pending,
+    /// The field could not be parsed.
+    InvalidField(&'static str),
+    /// The field is missing although the block has fields introduced after it.
+    MissingField(&'static str),
+    HashMismatch {
+        reported: H256,
+        computed: H256,
+    },
+    /// `child.parentHash` is not the parent's hash.
+    ParentHashMismatch {
+        parent: H256,
+        child_parent: H256,
+    },
+    /// `child.number` is not `parent.number + 1`.
+    NonConsecutive {
+        parent: u64,
+        child: u64,
+    },
+}
+
+impl Display for VerifyError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            VerifyError::Pending => write!(f, "pending block has no hash"),
+            VerifyError::InvalidField(field) => write!(f, "invalid `{}`", field),
+            VerifyError::MissingField(field) => write!(f, "missing `{}`", field),
+            VerifyError::HashMismatch { reported, computed } => write!(
+                f,
+                "block hash is {} but the header hashes to {}",
+                reported, computed
+            ),
+            VerifyError::ParentHashMismatch {
+                parent,
+                child_parent,
+            } => write!(
+                f,
+                "child's parent hash {} is not the parent's hash {}",
+                child_parent, parent
+            ),
+            VerifyError::NonConsecutive { parent, child } => {
+                write!(f, "block {} does not follow block {}", child, parent)
+            }
+        }
+    }
+}
+
+impl std::error::Error for VerifyError {}
+
+impl Block {
+    /// RLP encoding of the header, the preimage of the block hash.
+    ///
+    /// Fields added by later forks (base fee in London, withdrawals root in Shanghai, blob
+    /// gas and beacon root in Cancun, requests hash in Prague) are appended when present.
+    pub fn header_rlp(&self) -> Result<Vec<u8>, VerifyError> {
+        let number = self.number.ok_or(VerifyError::Pending)?;
+        let nonce = self.nonce.as_deref().ok_or(VerifyError::Pending)?;
+        let nonce = hex::parse_data(nonce).map_err(|_| VerifyError::InvalidField("nonce"))?;
+        let difficulty = U256::from_str_radix_16(&self.difficulty)
+            .map_err(|_| VerifyError::InvalidField("difficulty"))?;
+        let extra_data = hex::parse_data(&self.extra_data)
+            .map_err(|_| VerifyError::InvalidField("extraData"))?;
+        let miner = self.miner.ok_or(VerifyError::Pending)?;
+        let uncles = self
+            .sha3uncles
+            .ok_or(VerifyError::MissingField("sha3Uncles"))?;
+
+        let mut out = Vec::with_capacity(640);
+        rlp::append_bytes(&mut out, self.parent_hash.as_bytes());
+        rlp::append_bytes(&mut out, uncles.as_bytes());
+        rlp::append_bytes(&mut out, miner.as_bytes());
+        rlp::append_bytes(&mut out, self.state_root.as_bytes());
+        rlp::append_bytes(&mut out, self.transactions_root.as_bytes());
+        rlp::append_bytes(&mut out, self.receipts_root.as_bytes());
+        rlp::append_bytes(&mut out, &self.logs_bloom.0);
+        rlp::append_uint(&mut out, &difficulty.to_be_bytes());
+        rlp::append_u64(&mut out, number);
+        rlp::append_u64(&mut out, self.gas_limit);
+        rlp::append_u64(&mut out, self.gas_used);
+        rlp::append_u64(&mut out, self.timestamp);
+        rlp::append_bytes(&mut out, &extra_data);
+        rlp::append_bytes(&mut out, self.mix_hash.as_bytes());
+        rlp::append_bytes(&mut out, &nonce);
+
+        let uint = |be_bytes: &[u8]| {
+            let mut item = Vec::new();
+            rlp::append_uint(&mut item, be_bytes);
+            item
+        };
+        let hash = |hash: H256| {
+            let mut item = Vec::new();
+            rlp::append_bytes(&mut item, hash.as_bytes());
+            item
+        };
+        let forks = [
+            (
+                "baseFeePerGas",
+                self.base_fee_per_gas.map(|fee| uint(&fee.to_be_bytes())),
+            ),
+            ("withdrawalsRoot", self.withdrawals_root.map(hash)),
+            (
+                "blobGasUsed",
+                self.blob_gas_used.map(|gas| uint(&gas.to_be_bytes())),
+            ),
+            (
+                "excessBlobGas",
+                self.excess_blob_gas.map(|gas| uint(&gas.to_be_bytes())),
+            ),
+            (
+                "parentBeaconBlockRoot",
+                self.parent_beacon_block_root.map(hash),
+            ),
+            ("requestsHash", self.requests_hash.map(hash)),
+        ];
+        // each fork's fields follow the previous fork's, so only trailing ones may be absent
+        let present = forks
+            .iter()
+            .rposition(|(_, item)| item.is_some())
+            .map_or(0, |last| last + 1);
+        for (name, item) in &forks[..present] {
+            out.extend(item.as_ref().ok_or(VerifyError::MissingField(name))?);
+        }
+        Ok(rlp::list(&out))
+    }
+
+    /// Keccak hash of [`header_rlp`](Self::header_rlp).
+    pub fn compute_hash(&self) -> Result<H256, VerifyError> {
+        Ok(H256(keccak256(&self.header_rlp()?)))
+    }
+
+    /// Checks that the header fields hash to the reported `hash`.
+    pub fn verify_hash(&self) -> Result<(), VerifyError> {
+        let reported = self.hash.ok_or(VerifyError::Pending)?;
+        let computed = self.compute_hash()?;
+        if computed != reported {
+            return Err(VerifyError::HashMismatch { reported, computed });
+        }
+        Ok(())
+    }
+}
+
+/// Checks that `child` directly follows `parent`: its parent hash is the parent's hash and
+/// its number is one higher. Neither hash is recomputed; use [`Block::verify_hash`] too.
+pub fn verify_parent_link(parent: &Block, child: &Block) -> Result<(), VerifyError> {
+    let parent_hash = parent.hash.ok_or(VerifyError::Pending)?;
+    if child.parent_hash != parent_hash {
+        return Err(VerifyError::ParentHashMismatch {
+            parent: parent_hash,
+            child_parent: child.parent_hash,
+        });
+    }
+    let (parent, child) = match (parent.number, child.number) {
+        (Some(parent), Some(child)) => (parent, child),
+        _ => return Err(VerifyError::Pending),
+    };
+    if parent.checked_add(1) != Some(child) {
+        return Err(VerifyError::NonConsecutive { parent, child });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(fixture: &str) -> Block {
+        serde_json::from_str(fixture).unwrap()
+    }
+
+    fn cancun() -> Block {
+        parse(include_str!("../tests/fixtures/block_verify_cancun.json"))
+    }
+
+    #[test]
+    fn recomputes_hashes_across_forks() {
+        for fixture in [
+            include_str!("../tests/fixtures/block_verify_frontier_genesis.json"),
+            include_str!("../tests/fixtures/block_verify_muir_glacier.json"),
+            include_str!("../tests/fixtures/block_verify_london_sepolia_genesis.json"),
+            include_str!("../tests/fixtures/block_verify_cancun.json"),
+            include_str!("../tests/fixtures/block_verify_prague_devnet.json"),
+        ] {
+            let block = parse(fixture);
+            assert_eq!(block.verify_hash(), Ok(()), "block {:?}", block.number);
+        }
+    }
+
+    #[test]
+    fn detects_tampering() {
+        let mut block = cancun();
+        block.gas_used += 1;
+        assert!(matches!(
+            block.verify_hash(),
+            Err(VerifyError::HashMismatch { .. })
+        ));
+
+        // dropping the Cancun fields changes the layout, not just a value
+        let mut block = cancun();
+        block.blob_gas_used = None;
+        block.excess_blob_gas = None;
+        block.parent_beacon_block_root = None;
+        assert!(matches!(
+            block.verify_hash(),
+            Err(VerifyError::HashMismatch { .. })
+        ));
+
+        let mut block = cancun();
+        block.withdrawals_root = None;
+        assert_eq!(
+            block.verify_hash(),
+            Err(VerifyError::MissingField("withdrawalsRoot"))
+        );
+
+        let pending = parse(include_str!("../tests/fixtures/block_pending.json"));
+        assert_eq!(pending.verify_hash(), Err(VerifyError::Pending));
+    }
+
+    #[test]
+    fn links_parent_and_child() {
+        let parent = cancun();
+        let mut child = cancun();
+        child.parent_hash = parent.hash.unwrap();
+        child.number = Some(19_449_568);
+        assert_eq!(verify_parent_link(&parent, &child), Ok(()));
+
+        child.number = Some(19_449_569);
+        assert_eq!(
+            verify_parent_link(&parent, &child),
+            Err(VerifyError::NonConsecutive {
+                parent: 19_449_567,
+                child: 19_449_569
+            })
+        );
+
+        assert!(matches!(
+            verify_parent_link(&parent, &parent),
+            Err(VerifyError::ParentHashMismatch { .. })
+        ));
+    }
+}
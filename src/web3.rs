@@ -1,445 +1,627 @@
-use crate::client::{Client, Error};
-use crate::model::{Block, JsonRpcResult, Receipt, Tag, Transaction};
-use error_stack::{Report, ResultExt};
-use serde::Deserialize;
+use crate::abi::AbiError;
+use crate::builder::Web3Builder;
+use crate::client::{normalize_url, Client, Error};
+use crate::model::{JsonRpcResult, Tag};
+use error_stack::Report;
+use serde::de::DeserializeOwned;
 use serde_json::{json, Value};
 
+mod eth;
+mod net;
+
+#[cfg(feature = "admin-api")]
+pub mod admin;
+#[cfg(feature = "alchemy")]
+pub mod alchemy;
+#[cfg(feature = "debug-api")]
+pub mod debug;
+#[cfg(feature = "dev-chains")]
+pub mod dev_chains;
+#[cfg(feature = "engine-api")]
+pub mod engine;
+#[cfg(feature = "graphql")]
+pub mod graphql;
+#[cfg(feature = "parity-api")]
+pub mod parity;
+#[cfg(feature = "polygon")]
+pub mod polygon;
+#[cfg(feature = "trace-api")]
+pub mod trace;
+#[cfg(feature = "txpool-api")]
+pub mod txpool;
+#[cfg(feature = "zksync")]
+pub mod zksync;
+
+/// Cloning is cheap: clones share the HTTP client, its connection pool and any rate limit, so
+/// build one `Web3` per endpoint and clone it into tasks.
 #[derive(Clone)]
 pub struct Web3 {
     pub client: Client,
 }
 
 impl Web3 {
+    /// Does not validate `url`; use [`Web3::try_new`] or the builder to reject a bad endpoint
+    /// before the first request.
     pub fn new(url: String) -> Self {
+        let url = normalize_url(&url).unwrap_or(url);
         Web3 {
             client: Client::new(url),
         }
     }
 
-    pub fn new_with_client(url: String, client: reqwest_middleware::ClientWithMiddleware) -> Self {
-        Web3 {
-            client: Client::new_with_client(url, client),
-        }
-    }
-
-    fn parse_json<'de, T>(entity_str: &'de str) -> Result<T, Report<Error>>
-    where
-        T: Deserialize<'de>,
-    {
-        let jd = &mut serde_json::Deserializer::from_str(entity_str);
-        let entity: T = serde_path_to_error::deserialize(jd).change_context(Error::FailedToDeserialize)?;
-        Ok(entity)
-    }
-
-    // web3
-    pub async fn web3_client_version(&self) -> Result<JsonRpcResult<String>, Report<Error>> {
-        let payload =
-            json!({ "jsonrpc": "2.0", "method": "net_version", "params": [], "id": "101" });
-        let result = self.client.post(payload).await?;
-        let r: JsonRpcResult<String> = Self::parse_json(result.as_str())?;
-
-        Ok(r)
-    }
-
-    pub async fn web3_sha3(&self, sha3: &str) -> Result<JsonRpcResult<String>, Report<Error>> {
-        let payload =
-            json!({ "jsonrpc": "2.0", "method": "web3_sha3", "params": [sha3], "id": "102" });
-        let result = self.client.post(payload).await?;
-        let r: JsonRpcResult<String> = Self::parse_json(result.as_str())?;
-
-        Ok(r)
-    }
-
-    // net
-    pub async fn net_version(&self) -> Result<JsonRpcResult<String>, Report<Error>> {
-        let payload =
-            json!({ "jsonrpc": "2.0", "method": "net_version", "params": [], "id": "401" });
-        let result = self.client.post(payload).await?;
-        let r: JsonRpcResult<String> = Self::parse_json(result.as_str())?;
-
-        Ok(r)
-    }
-
-    pub async fn net_listening(&self) -> Result<JsonRpcResult<bool>, Report<Error>> {
-        let payload =
-            json!({ "jsonrpc": "2.0", "method": "net_listening", "params": [], "id": "402" });
-        let result = self.client.post(payload).await?;
-        let r: JsonRpcResult<bool> = Self::parse_json(result.as_str())?;
-
-        Ok(r)
-    }
-
-    pub async fn net_peer_count(&self) -> Result<JsonRpcResult<i64>, Report<Error>> {
-        let payload =
-            json!({ "jsonrpc": "2.0", "method": "net_peerCount", "params": [], "id": "303" });
-        let result = self.client.post(payload).await?;
-        let r: JsonRpcResult<i64> = Self::parse_json(result.as_str())?;
-
-        Ok(r)
-    }
-
-    // eth
-    pub async fn eth_protocol_version(&self) -> Result<JsonRpcResult<String>, Report<Error>> {
-        let payload =
-            json!({ "jsonrpc": "2.0", "method": "eth_protocolVersion", "params": [], "id": "304" });
-        let result = self.client.post(payload).await?;
-        let r: JsonRpcResult<String> = Self::parse_json(result.as_str())?;
-
-        Ok(r)
-    }
-
-    pub async fn eth_syncing(&self) -> Result<JsonRpcResult<bool>, Report<Error>> {
-        let payload =
-            json!({ "jsonrpc": "2.0", "method": "eth_syncing", "params": [], "id": "305" });
-        let result = self.client.post(payload).await?;
-        let r: JsonRpcResult<bool> = Self::parse_json(result.as_str())?;
-
-        Ok(r)
-    }
-
-    pub async fn eth_coinbase(&self) -> Result<JsonRpcResult<String>, Report<Error>> {
-        let payload =
-            json!({ "jsonrpc": "2.0", "method": "eth_coinbase", "params": [], "id": "306" });
-        let result = self.client.post(payload).await?;
-        let r: JsonRpcResult<String> = Self::parse_json(result.as_str())?;
-
-        Ok(r)
-    }
-
-    pub async fn eth_mining(&self) -> Result<JsonRpcResult<bool>, Report<Error>> {
-        let payload =
-            json!({ "jsonrpc": "2.0", "method": "eth_mining", "params": [], "id": "307" });
-        let result = self.client.post(payload).await?;
-        let r: JsonRpcResult<bool> = Self::parse_json(result.as_str())?;
-
-        Ok(r)
-    }
-
-    pub async fn eth_hashrate(&self) -> Result<JsonRpcResult<String>, Report<Error>> {
-        let payload =
-            json!({ "jsonrpc": "2.0", "method": "eth_hashrate", "params": [], "id": "308" });
-        let result = self.client.post(payload).await?;
-        let r: JsonRpcResult<String> = Self::parse_json(result.as_str())?;
-
-        Ok(r)
-    }
-
-    pub async fn eth_gas_price(&self) -> Result<JsonRpcResult<String>, Report<Error>> {
-        let payload =
-            json!({ "jsonrpc": "2.0", "method": "eth_gasPrice", "params": [], "id": "309" });
-        let result = self.client.post(payload).await?;
-        let r: JsonRpcResult<String> = Self::parse_json(result.as_str())?;
-
-        Ok(r)
+    pub fn try_new(url: impl Into<String>) -> Result<Self, Report<Error>> {
+        Web3::builder().url(url).build()
     }
 
-    pub async fn eth_accounts(&self) -> Result<JsonRpcResult<Vec<String>>, Report<Error>> {
-        let payload =
-            json!({ "jsonrpc": "2.0", "method": "eth_accounts", "params": [], "id": "310" });
-        let result = self.client.post(payload).await?;
-        let r: JsonRpcResult<Vec<String>> = Self::parse_json(result.as_str())?;
-
-        Ok(r)
+    /// Endpoint with credentials and provider keys stripped, safe to log.
+    pub fn url(&self) -> String {
+        self.client.redacted_url()
     }
 
-    pub async fn eth_get_balance(
-        &self,
-        address: &str,
-        tag: Option<Tag>,
-    ) -> Result<JsonRpcResult<String>, Report<Error>> {
-        let mut t = String::from(Tag::Latest);
-        if let Some(tag) = tag {
-            t = String::from(tag);
+    pub fn new_with_client(url: String, client: reqwest_middleware::ClientWithMiddleware) -> Self {
+        Web3 {
+            client: Client::new_with_client(url, client),
         }
-        let payload = json!({ "jsonrpc": "2.0", "method": "eth_getBalance", "params": [address, t], "id": "311" });
-        let result = self.client.post(payload).await?;
-        let r: JsonRpcResult<String> = Self::parse_json(result.as_str())?;
-
-        Ok(r)
     }
 
-    pub async fn eth_get_storage_at(
-        &self,
-        data: &str,
-        quantity: &str,
-        tag: Option<Tag>,
-    ) -> Result<JsonRpcResult<String>, Report<Error>> {
-        let mut t = String::from(Tag::Latest);
-        if let Some(tag) = tag {
-            t = String::from(tag);
-        }
-        let payload = json!({ "jsonrpc": "2.0", "method": "eth_getStorageAt", "params": [data, quantity, t], "id": "312" });
-        let result = self.client.post(payload).await?;
-        let r: JsonRpcResult<String> = Self::parse_json(result.as_str())?;
-
-        Ok(r)
+    /// Timeouts, headers, retries and rate limits; `Web3::new` is the shortcut for none of them.
+    pub fn builder() -> Web3Builder {
+        Web3Builder::new()
     }
 
-    pub async fn eth_get_transaction_count(
-        &self,
-        address: &str,
-        tag: Option<Tag>,
-    ) -> Result<JsonRpcResult<String>, Report<Error>> {
-        let mut t = String::from(Tag::Latest);
-        if let Some(tag) = tag {
-            t = String::from(tag);
+    /// A clone whose responses may be up to `bytes` long, for the odd call known to return
+    /// more than the client's limit (a whole-block trace, a wide getLogs range).
+    pub fn with_max_response_size(&self, bytes: usize) -> Web3 {
+        Web3 {
+            client: self.client.clone().with_max_response_size(bytes),
         }
-        let payload = json!({ "jsonrpc": "2.0", "method": "eth_getTransactionCount", "params": [address, t], "id": "313" });
-        let result = self.client.post(payload).await?;
-        let r: JsonRpcResult<String> = Self::parse_json(result.as_str())?;
-
-        Ok(r)
-    }
-
-    pub async fn eth_get_block_transaction_count_by_hash(
-        &self,
-        hash: &str,
-    ) -> Result<JsonRpcResult<String>, Report<Error>> {
-        let payload = json!({ "jsonrpc": "2.0", "method": "eth_getBlockTransactionCountByHash", "params": [hash], "id": "314" });
-        let result = self.client.post(payload).await?;
-        let r: JsonRpcResult<String> = Self::parse_json(result.as_str())?;
-
-        Ok(r)
-    }
-
-    pub async fn eth_get_block_transaction_count_by_number(
-        &self,
-        number: &str,
-    ) -> Result<JsonRpcResult<String>, Report<Error>> {
-        let payload = json!({ "jsonrpc": "2.0", "method": "eth_getBlockTransactionCountByNumber", "params": [number], "id": "315" });
-        let result = self.client.post(payload).await?;
-        let r: JsonRpcResult<String> = Self::parse_json(result.as_str())?;
-
-        Ok(r)
-    }
-
-    pub async fn eth_get_uncle_count_by_block_hash(
-        &self,
-        hash: &str,
-    ) -> Result<JsonRpcResult<String>, Report<Error>> {
-        let payload = json!({ "jsonrpc": "2.0", "method": "eth_getUncleCountByBlockHash", "params": [hash], "id": "316" });
-        let result = self.client.post(payload).await?;
-        let r: JsonRpcResult<String> = Self::parse_json(result.as_str())?;
-
-        Ok(r)
     }
 
-    pub async fn eth_get_uncle_count_by_block_number(
-        &self,
-        number: &str,
-    ) -> Result<JsonRpcResult<String>, Report<Error>> {
-        let payload = json!({ "jsonrpc": "2.0", "method": "eth_getUncleCountByBlockNumber", "params": [number], "id": "317" });
-        let result = self.client.post(payload).await?;
-        let r: JsonRpcResult<String> = Self::parse_json(result.as_str())?;
-
-        Ok(r)
+    /// A clone reading at `tag` wherever a method's tag is left out, e.g. a `finalized` view
+    /// next to the `latest` one. It shares the connection pool, rate limit and registered
+    /// errors, so one per task is cheap.
+    pub fn with_default_tag(&self, tag: Tag) -> Web3 {
+        Web3 {
+            client: self.client.clone().with_default_tag(tag),
+        }
     }
 
-    pub async fn eth_get_code(
-        &self,
-        address: &str,
-        tag: Option<Tag>,
-    ) -> Result<JsonRpcResult<String>, Report<Error>> {
-        let mut t = String::from(Tag::Latest);
-        if let Some(tag) = tag {
-            t = String::from(tag);
+    /// A clone failing with [`Error::UnknownFields`] when a block, transaction, receipt or
+    /// log has a member the models do not know, for canaries watching for schema changes.
+    pub fn strict(&self) -> Web3 {
+        Web3 {
+            client: self.client.clone().with_strict_models(true),
         }
-        let payload = json!({ "jsonrpc": "2.0", "method": "eth_getCode", "params": [address, t], "id": "318" });
-        let result = self.client.post(payload).await?;
-        let r: JsonRpcResult<String> = Self::parse_json(result.as_str())?;
-
-        Ok(r)
     }
 
-    pub async fn eth_sign(
-        &self,
-        address: &str,
-        data: &str,
-    ) -> Result<JsonRpcResult<String>, Report<Error>> {
-        let payload = json!({ "jsonrpc": "2.0", "method": "eth_sign", "params": [address, data], "id": "319" });
-        let result = self.client.post(payload).await?;
-        let r: JsonRpcResult<String> = Self::parse_json(result.as_str())?;
-
-        Ok(r)
+    /// Registers a custom error signature for every call made through this instance and its
+    /// clones, see [`Client::register_error`].
+    pub fn register_error(&self, signature: &str) -> Result<(), AbiError> {
+        self.client.register_error(signature)
     }
 
-    pub async fn eth_send_transaction(
+    /// Any method, deserialized into `T`.
+    pub async fn request<T>(
         &self,
-        from: &str,
-        to: &str,
-        gas: &str,
-        gas_price: &str,
-        value: &str,
-        data: &str,
-    ) -> Result<JsonRpcResult<String>, Report<Error>> {
-        let payload = json!({ "jsonrpc": "2.0", "method": "eth_sendTransaction", "params": [{
-            "from": from,
-            "to": to,
-            "gas": gas,
-            "gasPrice": gas_price,
-            "value": value,
-            "data": data
-        }], "id": "320" });
-        let result = self.client.post(payload).await?;
-        let r: JsonRpcResult<String> = Self::parse_json(result.as_str())?;
+        method: &str,
+        params: Value,
+    ) -> Result<JsonRpcResult<T>, Report<Error>>
+    where
+        T: DeserializeOwned,
+    {
+        let payload = json!({ "jsonrpc": "2.0", "method": method, "params": params, "id": "1" });
+        let r: JsonRpcResult<T> = self.client.call(payload).await?;
 
         Ok(r)
     }
 
-    pub async fn eth_send_raw_transaction(
+    /// Any method with the result left untyped, for responses the models do not cover.
+    ///
+    /// Typed methods that fail with `FailedToDeserialize` carry the body as a
+    /// [`RawResponse`] attachment, so the same data is reachable without a second request.
+    pub async fn call_raw(
         &self,
-        hash: &str,
-    ) -> Result<JsonRpcResult<String>, Report<Error>> {
-        let payload = json!({ "jsonrpc": "2.0", "method": "eth_sendRawTransaction", "params": [hash], "id": "321" });
-        let result = self.client.post(payload).await?;
-        let r: JsonRpcResult<String> = Self::parse_json(result.as_str())?;
-
-        Ok(r)
+        method: &str,
+        params: Value,
+    ) -> Result<JsonRpcResult<Value>, Report<Error>> {
+        self.request(method, params).await
     }
 
-    pub async fn eth_call(&self, data: Value) -> Result<JsonRpcResult<String>, Report<Error>> {
+    // web3
+    pub async fn web3_client_version(&self) -> Result<JsonRpcResult<String>, Report<Error>> {
         let payload =
-            json!({ "jsonrpc": "2.0", "method": "eth_call", "params": [data], "id": "322" });
-        let result = self.client.post(payload).await?;
-        let r: JsonRpcResult<String> = Self::parse_json(result.as_str())?;
+            json!({ "jsonrpc": "2.0", "method": "web3_clientVersion", "params": [], "id": "101" });
+        let r: JsonRpcResult<String> = self.client.call(payload).await?;
 
         Ok(r)
     }
 
-    pub async fn eth_estimate_gas(&self, data: Value) -> Result<JsonRpcResult<String>, Report<Error>> {
+    pub async fn web3_sha3(&self, sha3: &str) -> Result<JsonRpcResult<String>, Report<Error>> {
+        self.client.check_hex("data", sha3, None)?;
         let payload =
-            json!({ "jsonrpc": "2.0", "method": "eth_estimateGas", "params": [data], "id": "323" });
-        let result = self.client.post(payload).await?;
-        let r: JsonRpcResult<String> = Self::parse_json(result.as_str())?;
-
-        Ok(r)
-    }
-
-    pub async fn eth_get_block_by_hash(
-        &self,
-        hash: &str,
-        obj: bool,
-    ) -> Result<JsonRpcResult<Block>, Report<Error>> {
-        let payload = json!({ "jsonrpc": "2.0", "method": "eth_getBlockByHash", "params": [hash, obj], "id": "324" });
-        let result = self.client.post(payload).await?;
-        let r: JsonRpcResult<Block> = Self::parse_json(result.as_str())?;
+            json!({ "jsonrpc": "2.0", "method": "web3_sha3", "params": [sha3], "id": "102" });
+        let r: JsonRpcResult<String> = self.client.call(payload).await?;
 
         Ok(r)
     }
+}
 
-    pub async fn eth_get_block_by_number(
-        &self,
-        number: &str,
-        obj: bool,
-    ) -> Result<JsonRpcResult<Block>, Report<Error>> {
-        let payload = json!({ "jsonrpc": "2.0", "method": "eth_getBlockByNumber", "params": [number, obj], "id": "325" });
-        let result = self.client.post(payload).await?;
-        let r: JsonRpcResult<Block> = Self::parse_json(result.as_str())?;
-
-        Ok(r)
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::{truncate, Idempotency, RawResponse, RetryPolicy};
+    use crate::model::{JsonRpcErrorMessage, StateOverride, Tag, TransactionRequest};
+    use crate::types::{Address, U256};
+    use serde_json::Value;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    /// Serves one HTTP response per connection, in order, and hands back the JSON bodies
+    /// of the requests.
+    async fn serve(
+        replies: Vec<(u16, &'static str)>,
+    ) -> (String, tokio::task::JoinHandle<Vec<Value>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let handle = tokio::spawn(async move {
+            let mut bodies = Vec::new();
+            for (status, response) in replies {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let mut buf = Vec::new();
+                let mut chunk = [0u8; 4096];
+                let body = loop {
+                    let n = socket.read(&mut chunk).await.unwrap();
+                    buf.extend_from_slice(&chunk[..n]);
+                    let text = String::from_utf8_lossy(&buf).to_string();
+                    if let Some(end) = text.find("\r\n\r\n") {
+                        let length = text[..end]
+                            .lines()
+                            .find_map(|l| {
+                                let l = l.to_ascii_lowercase();
+                                l.strip_prefix("content-length:")
+                                    .map(|v| v.trim().parse::<usize>().unwrap())
+                            })
+                            .unwrap_or(0);
+                        if buf.len() >= end + 4 + length {
+                            break buf[end + 4..end + 4 + length].to_vec();
+                        }
+                    }
+                };
+                let reply = format!(
+                    "HTTP/1.1 {} X\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+                    status,
+                    response.len(),
+                    response
+                );
+                socket.write_all(reply.as_bytes()).await.unwrap();
+                bodies.push(serde_json::from_slice(&body).unwrap());
+            }
+            bodies
+        });
+        (url, handle)
+    }
+
+    /// Answers every request with `response` over keep-alive connections and counts the
+    /// connections accepted.
+    async fn serve_keep_alive(
+        response: &'static str,
+    ) -> (String, std::sync::Arc<std::sync::atomic::AtomicUsize>) {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let connections = std::sync::Arc::new(AtomicUsize::new(0));
+        let accepted = connections.clone();
+        tokio::spawn(async move {
+            loop {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                accepted.fetch_add(1, Ordering::SeqCst);
+                tokio::spawn(async move {
+                    let mut buf = Vec::new();
+                    let mut chunk = [0u8; 4096];
+                    loop {
+                        let request_end = loop {
+                            let text = String::from_utf8_lossy(&buf).to_string();
+                            if let Some(end) = text.find("\r\n\r\n") {
+                                let length = text[..end]
+                                    .lines()
+                                    .find_map(|l| {
+                                        l.to_ascii_lowercase()
+                                            .strip_prefix("content-length:")
+                                            .map(|v| v.trim().parse::<usize>().unwrap())
+                                    })
+                                    .unwrap_or(0);
+                                if buf.len() >= end + 4 + length {
+                                    break end + 4 + length;
+                                }
+                            }
+                            match socket.read(&mut chunk).await {
+                                Ok(0) | Err(_) => return,
+                                Ok(n) => buf.extend_from_slice(&chunk[..n]),
+                            }
+                        };
+                        buf.drain(..request_end);
+                        let reply = format!(
+                            "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\n\r\n{}",
+                            response.len(),
+                            response
+                        );
+                        if socket.write_all(reply.as_bytes()).await.is_err() {
+                            return;
+                        }
+                    }
+                });
+            }
+        });
+        (url, connections)
+    }
+
+    /// Serves a single HTTP response and hands back the JSON body of the request.
+    async fn serve_once(response: &'static str) -> (String, tokio::task::JoinHandle<Value>) {
+        let (url, handle) = serve(vec![(200, response)]).await;
+        (
+            url,
+            tokio::spawn(async move { handle.await.unwrap().remove(0) }),
+        )
     }
 
-    pub async fn eth_get_transaction_by_hash(
-        &self,
-        hash: &str,
-    ) -> Result<JsonRpcResult<Transaction>, Report<Error>> {
-        let payload = json!({ "jsonrpc": "2.0", "method": "eth_getTransactionByHash", "params": [hash], "id": "326" });
-        let result = self.client.post(payload).await?;
-        let r: JsonRpcResult<Transaction> = Self::parse_json(result.as_str())?;
+    #[tokio::test]
+    async fn eth_call_sends_block_parameter() {
+        let response = r#"{"jsonrpc":"2.0","id":"322","result":"0x12"}"#;
 
-        Ok(r)
+        let (url, request) = serve_once(response).await;
+        let rpc = Web3::new(url);
+        let r = rpc
+            .eth_call(
+                json!({"to": "0x846c4dc9f4e2514206ef179eaa0bcfae007e37d2", "data": "0x313ce567"}),
+            )
+            .await
+            .unwrap();
+        assert_eq!(r.result.as_deref(), Some("0x12"));
+        let params = request.await.unwrap()["params"].clone();
+        assert_eq!(params.as_array().unwrap().len(), 2);
+        assert_eq!(params[1], json!("latest"));
+
+        let (url, request) = serve_once(response).await;
+        let rpc = Web3::new(url);
+        let call = TransactionRequest::new()
+            .to("0x846c4dc9f4e2514206ef179eaa0bcfae007e37d2"
+                .parse()
+                .unwrap())
+            .data("0x313ce567");
+        rpc.eth_call_request_at_block(&call, 17_000_000u64)
+            .await
+            .unwrap();
+        let params = request.await.unwrap()["params"].clone();
+        assert_eq!(params.as_array().unwrap().len(), 2);
+        assert_eq!(params[1], json!("0x1036640"));
     }
 
-    pub async fn eth_block_number(&self) -> Result<JsonRpcResult<String>, Report<Error>> {
-        let payload =
-            json!({ "jsonrpc": "2.0", "method": "eth_blockNumber", "params": [], "id": "327" });
-        let result = self.client.post(payload).await?;
-        let r: JsonRpcResult<String> = Self::parse_json(result.as_str())?;
-
-        Ok(r)
+    #[tokio::test]
+    async fn eth_estimate_gas_surfaces_error_data() {
+        let call = TransactionRequest::new()
+            .from(
+                "0x846c4dc9f4e2514206ef179eaa0bcfae007e37d2"
+                    .parse()
+                    .unwrap(),
+            )
+            .to("0x4e910ef89c176119744977d90093858089454223"
+                .parse()
+                .unwrap())
+            .data("0xa9059cbb");
+
+        let out_of_gas = r#"{"jsonrpc":"2.0","id":"323","error":{"code":-32000,"message":"gas required exceeds allowance (30000000)"}}"#;
+        let (url, request) = serve_once(out_of_gas).await;
+        let err = Web3::new(url)
+            .eth_estimate_gas_request_at_block(&call, Tag::Pending)
+            .await
+            .unwrap_err();
+        assert_eq!(request.await.unwrap()["params"][1], json!("pending"));
+        assert!(
+            matches!(err.current_context(), Error::JsonRpcError { code: -32000, message, .. } if message.starts_with("gas required"))
+        );
+        let object = err.downcast_ref::<JsonRpcErrorMessage<Value>>().unwrap();
+        assert_eq!(object.code, -32000);
+        assert_eq!(object.revert_data(), None);
+
+        let reverted = r#"{"jsonrpc":"2.0","id":"323","error":{"code":3,"message":"execution reverted: Ownable: caller is not the owner","data":"0x08c379a0000000000000000000000000000000000000000000000000000000000000002000000000000000000000000000000000000000000000000000000000000000204f776e61626c653a2063616c6c6572206973206e6f7420746865206f776e6572"}}"#;
+        let (url, _request) = serve_once(reverted).await;
+        let err = Web3::new(url)
+            .eth_estimate_gas_request_at_block(&call, Tag::Latest)
+            .await
+            .unwrap_err();
+        assert!(err.current_context().is_execution_reverted());
+        let object = err.downcast_ref::<JsonRpcErrorMessage<Value>>().unwrap();
+        assert_eq!(object.code, 3);
+        assert!(object.revert_data().unwrap().starts_with("0x08c379a0"));
+    }
+
+    fn override_fixture() -> (TransactionRequest, Address, StateOverride) {
+        let holder: Address = "0x846c4dc9f4e2514206ef179eaa0bcfae007e37d2"
+            .parse()
+            .unwrap();
+        let call = TransactionRequest::new()
+            .from(holder)
+            .to("0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48"
+                .parse()
+                .unwrap())
+            .data("0x70a08231");
+        (call, holder, StateOverride::new().balance(holder, 1u64))
     }
 
-    pub async fn eth_get_transaction_receipt(
-        &self,
-        hash: &str,
-    ) -> Result<JsonRpcResult<Receipt>, Report<Error>> {
-        let payload = json!({ "jsonrpc": "2.0", "method": "eth_getTransactionReceipt", "params": [hash], "id": "328" });
-        let result = self.client.post(payload).await?;
-        let r: JsonRpcResult<Receipt> = Self::parse_json(result.as_str())?;
-
-        Ok(r)
+    #[tokio::test]
+    async fn state_overrides_are_sent() {
+        let (call, holder, overrides) = override_fixture();
+        let (url, request) = serve_once(r#"{"jsonrpc":"2.0","id":"322","result":"0x"}"#).await;
+        Web3::new(url)
+            .eth_call_with_overrides(&call, Tag::Latest, &overrides)
+            .await
+            .unwrap();
+        let params = request.await.unwrap()["params"].clone();
+        assert_eq!(params.as_array().unwrap().len(), 3);
+        assert_eq!(
+            params[2],
+            json!({ holder.to_lower_hex(): { "balance": "0x1" } })
+        );
+    }
+
+    #[cfg(feature = "debug-api")]
+    #[tokio::test]
+    async fn debug_trace_call_sends_state_overrides() {
+        let (call, _, overrides) = override_fixture();
+        let (url, request) = serve_once(r#"{"jsonrpc":"2.0","id":"501","result":{}}"#).await;
+        Web3::new(url)
+            .debug_trace_call(
+                &call,
+                Tag::Latest,
+                Some(json!({ "tracer": "callTracer" })),
+                Some(&overrides),
+            )
+            .await
+            .unwrap();
+        let params = request.await.unwrap()["params"].clone();
+        assert_eq!(params[2]["tracer"], json!("callTracer"));
+        assert_eq!(params[2]["stateOverrides"], json!(overrides));
     }
 
-    pub async fn eth_get_transaction_by_block_hash_and_index(
-        &self,
-        hash: &str,
-        index: &str,
-    ) -> Result<JsonRpcResult<Transaction>, Report<Error>> {
-        let payload = json!({ "jsonrpc": "2.0", "method": "eth_getTransactionByBlockHashAndIndex", "params": [hash, index], "id": "329" });
-        let result = self.client.post(payload).await?;
-        let r: JsonRpcResult<Transaction> = Self::parse_json(result.as_str())?;
-
-        Ok(r)
+    #[tokio::test]
+    async fn builder_retries_retryable_errors() {
+        let (url, requests) = serve(vec![
+            (503, "service unavailable"),
+            (
+                200,
+                r#"{"jsonrpc":"2.0","id":"330","error":{"code":-32005,"message":"rate limited"}}"#,
+            ),
+            (200, r#"{"jsonrpc":"2.0","id":"327","result":"0x1036640"}"#),
+        ])
+        .await;
+        let rpc = Web3::builder()
+            .url(url)
+            .retry(RetryPolicy::new(2).initial_backoff(std::time::Duration::from_millis(1)))
+            .build()
+            .unwrap();
+        let r = rpc.eth_block_number_typed().await.unwrap();
+        assert_eq!(r.into_result().unwrap(), U256::from(17_000_000u64));
+        assert_eq!(requests.await.unwrap().len(), 3);
+
+        let (url, _requests) = serve(vec![(200, r#"{"jsonrpc":"2.0","id":"330","error":{"code":-32602,"message":"invalid argument 0"}}"#)]).await;
+        let rpc = Web3::builder()
+            .url(url)
+            .retry(RetryPolicy::new(2))
+            .build()
+            .unwrap();
+        let err = rpc.eth_block_number_typed().await.unwrap_err();
+        assert!(err.current_context().is_invalid_params());
     }
 
-    pub async fn eth_get_transaction_by_block_number_and_index(
-        &self,
-        number: &str,
-        index: &str,
-    ) -> Result<JsonRpcResult<Transaction>, Report<Error>> {
-        let payload = json!({ "jsonrpc": "2.0", "method": "eth_getTransactionByBlockNumberAndIndex", "params": [number, index], "id": "330" });
-        let result = self.client.post(payload).await?;
-        let r: JsonRpcResult<Transaction> = Self::parse_json(result.as_str())?;
-
-        Ok(r)
+    #[tokio::test]
+    async fn retries_follow_idempotency() {
+        let policy = RetryPolicy::new(2).initial_backoff(std::time::Duration::from_millis(1));
+        let raw = "0x02f8b00181a0";
+        let bytes = [0x02, 0xf8, 0xb0, 0x01, 0x81, 0xa0];
+        let hash = crate::hex::fixed32(&crate::keccak::keccak256(&bytes));
+
+        // the first attempt got through before failing, the retry finds it in the pool
+        let (url, requests) = serve(vec![
+            (502, "bad gateway"),
+            (
+                200,
+                r#"{"jsonrpc":"2.0","id":"321","error":{"code":-32000,"message":"already known"}}"#,
+            ),
+        ])
+        .await;
+        let rpc = Web3::builder().url(url).retry(policy).build().unwrap();
+        let r = rpc.eth_send_raw_transaction(raw).await.unwrap();
+        assert_eq!(r.into_result().unwrap(), hash);
+        assert_eq!(requests.await.unwrap().len(), 2);
+
+        // failures after the first retry are retried as well
+        let (url, requests) = serve(vec![
+            (502, "bad gateway"),
+            (503, "unavailable"),
+            (
+                200,
+                r#"{"jsonrpc":"2.0","id":"321","error":{"code":-32000,"message":"already known"}}"#,
+            ),
+        ])
+        .await;
+        let rpc = Web3::builder().url(url).retry(policy).build().unwrap();
+        let r = rpc.eth_send_raw_transaction(raw).await.unwrap();
+        assert_eq!(r.into_result().unwrap(), hash);
+        assert_eq!(requests.await.unwrap().len(), 3);
+
+        // answered on the first attempt, the rejection stands
+        let (url, _requests) = serve(vec![(
+            200,
+            r#"{"jsonrpc":"2.0","id":"321","error":{"code":-32000,"message":"nonce too low"}}"#,
+        )])
+        .await;
+        let rpc = Web3::builder().url(url).retry(policy).build().unwrap();
+        let err = rpc.eth_send_raw_transaction(raw).await.unwrap_err();
+        assert_eq!(err.current_context().code(), Some(-32000));
+
+        let (url, requests) = serve(vec![(502, "bad gateway")]).await;
+        let rpc = Web3::builder().url(url).retry(policy).build().unwrap();
+        let err = rpc
+            .call_raw("eth_sendTransaction", json!([{}]))
+            .await
+            .unwrap_err();
+        assert!(matches!(err.current_context(), Error::HttpError(_)));
+        assert_eq!(requests.await.unwrap().len(), 1);
+
+        let (url, requests) = serve(vec![(502, "bad gateway")]).await;
+        let rpc = Web3::builder()
+            .url(url)
+            .retry(policy)
+            .idempotency("custom_sendBundle", Idempotency::Never)
+            .build()
+            .unwrap();
+        assert!(rpc.call_raw("custom_sendBundle", json!([])).await.is_err());
+        assert_eq!(requests.await.unwrap().len(), 1);
+
+        let (url, requests) = serve(vec![
+            (502, "bad gateway"),
+            (200, r#"{"jsonrpc":"2.0","id":"1","result":true}"#),
+        ])
+        .await;
+        let rpc = Web3::builder()
+            .url(url)
+            .retry(policy)
+            .idempotency("evm_mine", Idempotency::Safe)
+            .build()
+            .unwrap();
+        rpc.call_raw("evm_mine", json!([])).await.unwrap();
+        assert_eq!(requests.await.unwrap().len(), 2);
+    }
+
+    #[cfg(all(feature = "txpool-api", feature = "trace-api"))]
+    #[tokio::test]
+    async fn optional_namespaces() {
+        let (url, _request) =
+            serve_once(r#"{"jsonrpc":"2.0","id":"701","result":{"pending":"0xa","queued":"0x7"}}"#)
+                .await;
+        let status = Web3::new(url)
+            .txpool_status()
+            .await
+            .unwrap()
+            .into_result()
+            .unwrap();
+        assert_eq!((status.pending, status.queued), (10, 7));
+
+        let (url, request) = serve_once(r#"{"jsonrpc":"2.0","id":"602","result":[]}"#).await;
+        Web3::new(url).trace_block(17_000_000u64).await.unwrap();
+        assert_eq!(request.await.unwrap()["params"], json!(["0x1036640"]));
     }
 
-    pub async fn eth_get_uncle_by_block_hash_and_index(
-        &self,
-        hash: &str,
-        index: &str,
-    ) -> Result<JsonRpcResult<Block>, Report<Error>> {
-        let payload = json!({ "jsonrpc": "2.0", "method": "eth_getUncleByBlockHashAndIndex", "params": [hash, index], "id": "331" });
-        let result = self.client.post(payload).await?;
-        let r: JsonRpcResult<Block> = Self::parse_json(result.as_str())?;
-
-        Ok(r)
+    #[tokio::test]
+    async fn raw_calls_and_raw_body_on_mismatch() {
+        let body = r#"{"jsonrpc":"2.0","id":"1","result":{"pending":"0x1","queued":"0x0"}}"#;
+        let (url, request) = serve_once(body).await;
+        let r = Web3::new(url)
+            .call_raw("txpool_status", json!([]))
+            .await
+            .unwrap();
+        assert_eq!(r.into_result().unwrap()["pending"], json!("0x1"));
+        assert_eq!(request.await.unwrap()["method"], json!("txpool_status"));
+
+        let body = r#"{"jsonrpc":"2.0","id":"330","result":{"unexpected":true}}"#;
+        let (url, _request) = serve_once(body).await;
+        let err = Web3::new(url).eth_block_number().await.unwrap_err();
+        assert!(matches!(err.current_context(), Error::FailedToDeserialize));
+        assert_eq!(err.downcast_ref::<RawResponse>().unwrap().0, body);
+        assert!(format!("{:?}", err).contains("unexpected"));
+
+        let long = "x".repeat(3000);
+        assert_eq!(
+            truncate(&long, 1024),
+            format!("{}... (3000 bytes)", &long[..1024])
+        );
+        assert_eq!(truncate("é", 1), "... (2 bytes)");
     }
 
-    pub async fn eth_get_uncle_by_block_number_and_index(
-        &self,
-        hash: &str,
-        index: &str,
-    ) -> Result<JsonRpcResult<Block>, Report<Error>> {
-        let payload = json!({ "jsonrpc": "2.0", "method": "eth_getUncleByBlockNumberAndIndex", "params": [hash, index], "id": "332" });
-        let result = self.client.post(payload).await?;
-        let r: JsonRpcResult<Block> = Self::parse_json(result.as_str())?;
-
-        Ok(r)
+    #[tokio::test]
+    async fn errors_carry_request_context() {
+        let body =
+            r#"{"jsonrpc":"2.0","id":"327","error":{"code":-32005,"message":"limit exceeded"}}"#;
+        let (listener_url, _request) = serve_once(body).await;
+        let url = listener_url.replace("http://", "http://user:secret@") + "/v3/apikey";
+        let err = Web3::new(url).eth_block_number().await.unwrap_err();
+        let report = format!("{:?}", err);
+        assert!(report.contains("method: eth_blockNumber"), "{}", report);
+        assert!(report.contains("request id: 327"));
+        assert!(report.contains(&format!("endpoint: {}", &listener_url["http://".len()..])));
+        assert!(report.contains("elapsed: "));
+        assert!(!report.contains("secret") && !report.contains("apikey"));
+
+        let (url, _request) = serve_once(r#"{"jsonrpc":"2.0","id":"321","result":{}}"#).await;
+        let err = Web3::new(url)
+            .eth_send_raw_transaction("0x02f8b00181a0")
+            .await
+            .unwrap_err();
+        let report = format!("{:?}", err);
+        assert!(report.contains("method: eth_sendRawTransaction"));
+        assert!(report.contains("params: <redacted>"));
+        assert!(!report.contains("0x02f8b00181a0"));
     }
 
-    pub async fn eth_get_compilers(&self) -> Result<JsonRpcResult<Vec<String>>, Report<Error>> {
-        let payload =
-            json!({ "jsonrpc": "2.0", "method": "eth_getCompilers", "params": [], "id": "333" });
-        let result = self.client.post(payload).await?;
-        let r: JsonRpcResult<Vec<String>> = Self::parse_json(result.as_str())?;
-
-        Ok(r)
+    #[tokio::test]
+    async fn inspector_sees_every_exchange() {
+        let path =
+            std::env::temp_dir().join(format!("web3-rpc-inspect-{}.ndjson", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let (url, _requests) = serve(vec![
+            (502, "bad gateway"),
+            (200, r#"{"jsonrpc":"2.0","id":"327","result":"0x10"}"#),
+        ])
+        .await;
+        let rpc = Web3::builder()
+            .url(url)
+            .bearer_token("secret-token")
+            .retry(RetryPolicy::new(1).initial_backoff(std::time::Duration::from_millis(1)))
+            .inspector(Box::new(
+                crate::inspector::FileInspector::create(&path).unwrap(),
+            ))
+            .build()
+            .unwrap();
+        rpc.eth_block_number().await.unwrap();
+
+        let log = std::fs::read_to_string(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+        assert!(!log.contains("secret-token"));
+        let lines: Vec<Value> = log
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["status"], json!(502));
+        assert_eq!(lines[0]["response"], json!("bad gateway"));
+        assert_eq!(lines[1]["request"]["method"], json!("eth_blockNumber"));
+        assert_eq!(
+            lines[1]["response"],
+            json!(r#"{"jsonrpc":"2.0","id":"327","result":"0x10"}"#)
+        );
+        assert_eq!(lines[1]["headers"]["authorization"], json!("<redacted>"));
     }
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
 
     #[tokio::test]
-    pub async fn get_block_transaction_count_by_hash() {
-        let rpc = Web3::new("https://mainnet.infura.io/v3/xxx".to_string());
-        let r = rpc
-            .eth_get_block_transaction_count_by_hash(
-                "0xe812a49745d691961893d7cfd3902d78d710751bab872f12215ee23f27f3efa9",
-            )
-            .await;
-        println!("{:?}", r);
+    async fn clones_share_the_connection_pool() {
+        let (url, connections) =
+            serve_keep_alive(r#"{"jsonrpc":"2.0","id":"327","result":"0x10"}"#).await;
+        let rpc = Web3::builder()
+            .url(url)
+            .pool_max_idle_per_host(4)
+            .pool_idle_timeout(Some(std::time::Duration::from_secs(30)))
+            .tcp_keepalive(std::time::Duration::from_secs(60))
+            .build()
+            .unwrap();
+        for _ in 0..1000 {
+            rpc.clone().eth_block_number().await.unwrap();
+        }
+        let opened = connections.load(std::sync::atomic::Ordering::SeqCst);
+        assert!(
+            opened <= 2,
+            "{} connections for 1000 sequential calls",
+            opened
+        );
     }
 }
@@ -0,0 +1,25 @@
+//! Geth `admin_*` namespace, behind the `admin-api` feature.
+
+use crate::client::Error;
+use crate::model::JsonRpcResult;
+use crate::web3::Web3;
+use error_stack::Report;
+use serde_json::{json, Value};
+
+impl Web3 {
+    pub async fn admin_node_info(&self) -> Result<JsonRpcResult<Value>, Report<Error>> {
+        let payload =
+            json!({ "jsonrpc": "2.0", "method": "admin_nodeInfo", "params": [], "id": "801" });
+        let r: JsonRpcResult<Value> = self.client.call(payload).await?;
+
+        Ok(r)
+    }
+
+    pub async fn admin_peers(&self) -> Result<JsonRpcResult<Vec<Value>>, Report<Error>> {
+        let payload =
+            json!({ "jsonrpc": "2.0", "method": "admin_peers", "params": [], "id": "802" });
+        let r: JsonRpcResult<Vec<Value>> = self.client.call(payload).await?;
+
+        Ok(r)
+    }
+}
@@ -0,0 +1,231 @@
+//! Alchemy's enhanced `alchemy_*` API, behind the `alchemy` feature. One call here stands in
+//! for many eth_getLogs and trace queries, on Alchemy endpoints only.
+
+use crate::client::Error;
+use crate::model::{BlockId, JsonRpcResult};
+use crate::serde_helpers::{deserialize_hex_u64, serialize_hex_u64, serialize_hex_u64_opt};
+use crate::types::{Address, H256, U256};
+use crate::web3::Web3;
+use error_stack::{Report, ResultExt};
+use serde::{Deserialize, Serialize, Serializer};
+use serde_json::json;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum TransferCategory {
+    /// Top level ether transfers.
+    External,
+    /// Ether moved by contract calls.
+    Internal,
+    Erc20,
+    Erc721,
+    Erc1155,
+    /// NFTs that predate ERC-721, such as CryptoPunks.
+    Specialnft,
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Order {
+    Asc,
+    Desc,
+}
+
+/// Params of alchemy_getAssetTransfers. Unset fields are left to Alchemy's defaults; at
+/// least one category is required.
+#[derive(Serialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct AssetTransfersRequest {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub from_block: Option<BlockId>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub to_block: Option<BlockId>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub from_address: Option<Address>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub to_address: Option<Address>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub contract_addresses: Vec<Address>,
+    pub category: Vec<TransferCategory>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub order: Option<Order>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub with_metadata: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exclude_zero_value: Option<bool>,
+    /// Transfers per page, up to 1000.
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "serialize_hex_u64_opt"
+    )]
+    pub max_count: Option<u64>,
+    /// `page_key` of the previous page.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub page_key: Option<String>,
+}
+
+/// Token contract involved in a transfer, with the raw amount.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct RawContract {
+    pub value: Option<U256>,
+    /// `None` for ether.
+    pub address: Option<Address>,
+    pub decimal: Option<U256>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct Erc1155Amount {
+    pub token_id: U256,
+    pub value: U256,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct TransferMetadata {
+    /// ISO 8601, e.g. `2023-04-14T09:52:35.000Z`.
+    pub block_timestamp: String,
+}
+
+/// One entry of alchemy_getAssetTransfers. Which token fields are set depends on `category`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct AssetTransfer {
+    #[serde(
+        deserialize_with = "deserialize_hex_u64",
+        serialize_with = "serialize_hex_u64"
+    )]
+    pub block_num: u64,
+    pub unique_id: String,
+    pub hash: H256,
+    pub from: Address,
+    /// `None` for contract creations.
+    pub to: Option<Address>,
+    /// Amount in whole units of `asset`, as Alchemy rounds it; `raw_contract.value` is exact.
+    pub value: Option<f64>,
+    pub erc721_token_id: Option<U256>,
+    pub erc1155_metadata: Option<Vec<Erc1155Amount>>,
+    /// Token id of ERC-721 and special NFT transfers.
+    pub token_id: Option<U256>,
+    /// `ETH` or the token symbol, when known.
+    pub asset: Option<String>,
+    pub category: TransferCategory,
+    pub raw_contract: RawContract,
+    /// Only with `with_metadata`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<TransferMetadata>,
+}
+
+/// A page of transfers.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct AssetTransfers {
+    pub transfers: Vec<AssetTransfer>,
+    /// Set when there are more transfers, see [`AssetTransfersRequest::page_key`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub page_key: Option<String>,
+}
+
+/// Tokens alchemy_getTokenBalances reports on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TokenSelection {
+    /// Every ERC-20 the address has held.
+    Erc20,
+    Contracts(Vec<Address>),
+}
+
+impl Serialize for TokenSelection {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match self {
+            TokenSelection::Erc20 => serializer.serialize_str("erc20"),
+            TokenSelection::Contracts(contracts) => contracts.serialize(serializer),
+        }
+    }
+}
+
+/// Balance of one token; Alchemy reports failures per token rather than failing the call.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct TokenBalance {
+    pub contract_address: Address,
+    pub token_balance: Option<U256>,
+    #[serde(default)]
+    pub error: Option<String>,
+}
+
+impl TokenBalance {
+    pub fn balance(&self) -> Result<U256, &str> {
+        match (&self.error, self.token_balance) {
+            (Some(error), _) => Err(error),
+            (None, Some(balance)) => Ok(balance),
+            (None, None) => Err("no balance returned"),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct TokenBalances {
+    pub address: Address,
+    pub token_balances: Vec<TokenBalance>,
+    /// Set when [`TokenSelection::Erc20`] has more balances than fit one response; pass it
+    /// back for the next page.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub page_key: Option<String>,
+}
+
+impl Web3 {
+    pub async fn alchemy_get_asset_transfers(
+        &self,
+        request: &AssetTransfersRequest,
+    ) -> Result<JsonRpcResult<AssetTransfers>, Report<Error>> {
+        let payload = json!({ "jsonrpc": "2.0", "method": "alchemy_getAssetTransfers", "params": [request], "id": "1301" });
+        let r: JsonRpcResult<AssetTransfers> = self.client.call(payload).await?;
+
+        Ok(r)
+    }
+
+    /// Every transfer matching `request`, following `page_key` from the given page to the
+    /// last one.
+    pub async fn alchemy_get_all_asset_transfers(
+        &self,
+        request: &AssetTransfersRequest,
+    ) -> Result<Vec<AssetTransfer>, Report<Error>> {
+        let mut request = request.clone();
+        let mut transfers = Vec::new();
+        loop {
+            let page = self
+                .alchemy_get_asset_transfers(&request)
+                .await?
+                .into_result()
+                .map_err(crate::client::rpc_error)
+                .attach_printable_lazy(|| format!("transfers page {:?}", request.page_key))?;
+            transfers.extend(page.transfers);
+            match page.page_key {
+                Some(key) if request.page_key.as_ref() != Some(&key) => {
+                    request.page_key = Some(key)
+                }
+                _ => return Ok(transfers),
+            }
+        }
+    }
+
+    pub async fn alchemy_get_token_balances(
+        &self,
+        address: Address,
+        tokens: &TokenSelection,
+        page_key: Option<&str>,
+    ) -> Result<JsonRpcResult<TokenBalances>, Report<Error>> {
+        let params = match page_key {
+            Some(key) => json!([address, tokens, { "pageKey": key }]),
+            None => json!([address, tokens]),
+        };
+        let payload = json!({ "jsonrpc": "2.0", "method": "alchemy_getTokenBalances", "params": params, "id": "1302" });
+        let r: JsonRpcResult<TokenBalances> = self.client.call(payload).await?;
+
+        Ok(r)
+    }
+}
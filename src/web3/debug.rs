@@ -0,0 +1,111 @@
+//! Geth `debug_*` namespace, behind the `debug-api` feature.
+
+use crate::client::Error;
+use crate::model::{Block, BlockId, JsonRpcResult, StateOverride, TransactionRequest};
+use crate::types::{H256Param, H256};
+use crate::web3::Web3;
+use error_stack::{Report, ResultExt};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+/// Entry of debug_getBadBlocks: a block the node saw and rejected.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct BadBlock {
+    pub hash: H256,
+    /// Kept as sent, since a block the node rejected may not fit [`Block`]; see
+    /// [`BadBlock::block`].
+    pub block: Value,
+    /// The block exactly as received, RLP encoded.
+    pub rlp: String,
+}
+
+impl BadBlock {
+    pub fn block(&self) -> Result<Block, Report<Error>> {
+        serde_json::from_value(self.block.clone())
+            .change_context(Error::FailedToDeserialize)
+            .attach_printable_lazy(|| format!("bad block {}", self.hash))
+    }
+}
+
+/// Trace of one transaction of a block, as debug_traceBlockByNumber lists them.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TransactionTrace {
+    /// Left out by older geth versions.
+    #[serde(default)]
+    pub tx_hash: Option<H256>,
+    /// Output of the tracer given in the config.
+    pub result: Value,
+}
+
+impl Web3 {
+    /// `config` is the geth trace config (`tracer`, `tracerConfig`, ...); a non-empty
+    /// `overrides` set is sent as its `stateOverrides` key.
+    pub async fn debug_trace_call(
+        &self,
+        request: &TransactionRequest,
+        block: impl Into<BlockId>,
+        config: Option<Value>,
+        overrides: Option<&StateOverride>,
+    ) -> Result<JsonRpcResult<Value>, Report<Error>> {
+        request
+            .validate()
+            .change_context(Error::InvalidTransactionRequest)?;
+        let mut config = config.unwrap_or_else(|| json!({}));
+        if let Some(overrides) = overrides.filter(|o| !o.is_empty()) {
+            config["stateOverrides"] = json!(overrides);
+        }
+        let payload = json!({ "jsonrpc": "2.0", "method": "debug_traceCall", "params": [request, block.into().eip1898(), config], "id": "501" });
+        let r: JsonRpcResult<Value> = self.client.call(payload).await?;
+
+        Ok(r)
+    }
+
+    /// Replays a mined transaction; `config` as for [`Web3::debug_trace_call`].
+    pub async fn debug_trace_transaction(
+        &self,
+        hash: impl Into<H256Param>,
+        config: Option<Value>,
+    ) -> Result<JsonRpcResult<Value>, Report<Error>> {
+        let config = config.unwrap_or_else(|| json!({}));
+        let payload = json!({ "jsonrpc": "2.0", "method": "debug_traceTransaction", "params": [hash.into(), config], "id": "502" });
+        let r: JsonRpcResult<Value> = self.client.call(payload).await?;
+
+        Ok(r)
+    }
+
+    /// Invalid blocks the node has seen recently, newest first; usually empty.
+    pub async fn debug_get_bad_blocks(
+        &self,
+    ) -> Result<JsonRpcResult<Vec<BadBlock>>, Report<Error>> {
+        let payload =
+            json!({ "jsonrpc": "2.0", "method": "debug_getBadBlocks", "params": [], "id": "503" });
+        let r: JsonRpcResult<Vec<BadBlock>> = self.client.call(payload).await?;
+
+        Ok(r)
+    }
+
+    /// Traces every transaction of a block (debug_traceBlockByHash or debug_traceBlockByNumber)
+    /// and hands each trace to `on_trace` as it is parsed, see [`Client::call_streaming`]; a
+    /// full block traced with the struct logger easily runs into gigabytes. `config` as for
+    /// [`Web3::debug_trace_call`]. Returns the number of traces.
+    ///
+    /// [`Client::call_streaming`]: crate::client::Client::call_streaming
+    pub async fn debug_trace_block_streaming(
+        &self,
+        block: impl Into<BlockId>,
+        config: Option<Value>,
+        on_trace: impl FnMut(TransactionTrace),
+    ) -> Result<usize, Report<Error>> {
+        let config = config.unwrap_or_else(|| json!({}));
+        let payload = match block.into() {
+            BlockId::Hash(hash) => {
+                json!({ "jsonrpc": "2.0", "method": "debug_traceBlockByHash", "params": [hash, config], "id": "504" })
+            }
+            block => {
+                json!({ "jsonrpc": "2.0", "method": "debug_traceBlockByNumber", "params": [block, config], "id": "505" })
+            }
+        };
+        self.client.call_streaming(payload, on_trace).await
+    }
+}
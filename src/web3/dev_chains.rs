@@ -0,0 +1,241 @@
+//! Cheat methods of local development chains, behind the `dev-chains` feature: Anvil's
+//! `anvil_*` and Hardhat Network's `hardhat_*`. Both take the same params, so one macro stamps
+//! out each set. They change the state of the chain, so most are sent for their effect and
+//! return `()`. The classic `evm_*` methods both chains speak are here too.
+
+use crate::client::Error;
+use crate::hex;
+use crate::model::JsonRpcResult;
+use crate::types::{Address, StorageSlot, H256, U256};
+use crate::web3::Web3;
+use error_stack::{Report, ResultExt};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+/// Fork to restart from in [`Web3::anvil_reset`] and [`Web3::hardhat_reset`].
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct Forking {
+    pub json_rpc_url: String,
+    /// Latest block of the remote chain when `None`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub block_number: Option<u64>,
+}
+
+impl Forking {
+    pub fn new(json_rpc_url: impl Into<String>) -> Self {
+        Forking {
+            json_rpc_url: json_rpc_url.into(),
+            block_number: None,
+        }
+    }
+
+    pub fn block_number(mut self, block_number: u64) -> Self {
+        self.block_number = Some(block_number);
+        self
+    }
+}
+
+/// Handle of an [`Web3::evm_snapshot`], a hex quantity kept exactly as the chain sent it.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
+#[serde(transparent)]
+pub struct SnapshotId(pub String);
+
+/// Methods named `<prefix>_<name>` with ids `<id>0` to `<id>9`. The result of dropping a
+/// transaction differs between chains and is given as `$dropped`.
+macro_rules! cheat_methods {
+    ($prefix:literal, $id:literal, $dropped:ty {
+        $impersonate:ident,
+        $stop_impersonating:ident,
+        $set_balance:ident,
+        $set_code:ident,
+        $set_nonce:ident,
+        $set_storage_at:ident,
+        $mine:ident,
+        $reset:ident,
+        $drop_transaction:ident,
+        $set_next_block_base_fee:ident $(,)?
+    }) => {
+        impl Web3 {
+            /// Lets `eth_sendTransaction` send from `address` without its key.
+            pub async fn $impersonate(&self, address: Address) -> Result<(), Report<Error>> {
+                let payload = json!({ "jsonrpc": "2.0", "method": concat!($prefix, "_impersonateAccount"), "params": [address], "id": concat!($id, "1") });
+                self.client.call_unit(payload).await
+            }
+
+            pub async fn $stop_impersonating(&self, address: Address) -> Result<(), Report<Error>> {
+                let payload = json!({ "jsonrpc": "2.0", "method": concat!($prefix, "_stopImpersonatingAccount"), "params": [address], "id": concat!($id, "2") });
+                self.client.call_unit(payload).await
+            }
+
+            pub async fn $set_balance(
+                &self,
+                address: Address,
+                balance: U256,
+            ) -> Result<(), Report<Error>> {
+                let payload = json!({ "jsonrpc": "2.0", "method": concat!($prefix, "_setBalance"), "params": [address, balance], "id": concat!($id, "3") });
+                self.client.call_unit(payload).await
+            }
+
+            /// Replaces the runtime code at `address`.
+            pub async fn $set_code(&self, address: Address, code: &[u8]) -> Result<(), Report<Error>> {
+                let payload = json!({ "jsonrpc": "2.0", "method": concat!($prefix, "_setCode"), "params": [address, hex::data(code)], "id": concat!($id, "4") });
+                self.client.call_unit(payload).await
+            }
+
+            pub async fn $set_nonce(&self, address: Address, nonce: u64) -> Result<(), Report<Error>> {
+                let payload = json!({ "jsonrpc": "2.0", "method": concat!($prefix, "_setNonce"), "params": [address, hex::quantity(nonce.into())], "id": concat!($id, "5") });
+                self.client.call_unit(payload).await
+            }
+
+            pub async fn $set_storage_at(
+                &self,
+                address: Address,
+                slot: impl Into<StorageSlot>,
+                value: H256,
+            ) -> Result<(), Report<Error>> {
+                let slot = slot.into();
+                self.client.check_quantity("slot", slot.as_str(), 64)?;
+                let payload = json!({ "jsonrpc": "2.0", "method": concat!($prefix, "_setStorageAt"), "params": [address, slot, value], "id": concat!($id, "6") });
+                self.client.call_unit(payload).await
+            }
+
+            /// Mines `blocks` blocks (one when `None`), `interval` seconds apart.
+            pub async fn $mine(
+                &self,
+                blocks: Option<u64>,
+                interval: Option<u64>,
+            ) -> Result<(), Report<Error>> {
+                let quantity = |n: Option<u64>| n.map(|n| hex::quantity(n.into()));
+                let payload = json!({ "jsonrpc": "2.0", "method": concat!($prefix, "_mine"), "params": [quantity(blocks), quantity(interval)], "id": concat!($id, "7") });
+                self.client.call_unit(payload).await
+            }
+
+            /// Restarts the chain, from `forking` or, when `None`, from genesis or the fork it
+            /// was started with.
+            pub async fn $reset(&self, forking: Option<&Forking>) -> Result<(), Report<Error>> {
+                let params = match forking {
+                    Some(forking) => json!([{ "forking": forking }]),
+                    None => json!([]),
+                };
+                let payload = json!({ "jsonrpc": "2.0", "method": concat!($prefix, "_reset"), "params": params, "id": concat!($id, "8") });
+                self.client.call_unit(payload).await
+            }
+
+            /// Removes a transaction from the pool.
+            pub async fn $drop_transaction(
+                &self,
+                hash: H256,
+            ) -> Result<JsonRpcResult<$dropped>, Report<Error>> {
+                let payload = json!({ "jsonrpc": "2.0", "method": concat!($prefix, "_dropTransaction"), "params": [hash], "id": concat!($id, "9") });
+                let r: JsonRpcResult<$dropped> = self.client.call(payload).await?;
+
+                Ok(r)
+            }
+
+            /// Base fee of the next block only; later blocks follow EIP-1559 from there.
+            pub async fn $set_next_block_base_fee(&self, base_fee: U256) -> Result<(), Report<Error>> {
+                let payload = json!({ "jsonrpc": "2.0", "method": concat!($prefix, "_setNextBlockBaseFeePerGas"), "params": [base_fee], "id": concat!($id, "0") });
+                self.client.call_unit(payload).await
+            }
+        }
+    };
+}
+
+// anvil answers with the dropped hash, or null if the transaction was not in the pool
+cheat_methods!("anvil", "140", Option<H256> {
+    anvil_impersonate_account,
+    anvil_stop_impersonating_account,
+    anvil_set_balance,
+    anvil_set_code,
+    anvil_set_nonce,
+    anvil_set_storage_at,
+    anvil_mine,
+    anvil_reset,
+    anvil_drop_transaction,
+    anvil_set_next_block_base_fee_per_gas,
+});
+
+// hardhat answers whether the transaction was in the pool
+cheat_methods!(
+    "hardhat",
+    "142",
+    bool {
+        hardhat_impersonate_account,
+        hardhat_stop_impersonating_account,
+        hardhat_set_balance,
+        hardhat_set_code,
+        hardhat_set_nonce,
+        hardhat_set_storage_at,
+        hardhat_mine,
+        hardhat_reset,
+        hardhat_drop_transaction,
+        hardhat_set_next_block_base_fee_per_gas,
+    }
+);
+
+impl Web3 {
+    /// Saves the chain state for [`Web3::evm_revert`].
+    pub async fn evm_snapshot(&self) -> Result<SnapshotId, Report<Error>> {
+        let payload =
+            json!({ "jsonrpc": "2.0", "method": "evm_snapshot", "params": [], "id": "1441" });
+        let r: JsonRpcResult<SnapshotId> = self.client.call(payload).await?;
+        r.result.ok_or_else(|| {
+            Report::new(Error::UnexpectedResponseFormat)
+                .attach_printable("evm_snapshot returned null")
+        })
+    }
+
+    /// Goes back to `snapshot`. A snapshot can be reverted to once; `false`, not an error, for
+    /// one already used or unknown.
+    pub async fn evm_revert(&self, snapshot: &SnapshotId) -> Result<bool, Report<Error>> {
+        let payload =
+            json!({ "jsonrpc": "2.0", "method": "evm_revert", "params": [snapshot], "id": "1442" });
+        let r: JsonRpcResult<bool> = self.client.call(payload).await?;
+        Ok(r.result.unwrap_or(false))
+    }
+
+    /// Moves the clock of later blocks forward; the total offset in seconds.
+    pub async fn evm_increase_time(&self, seconds: u64) -> Result<i64, Report<Error>> {
+        let payload = json!({ "jsonrpc": "2.0", "method": "evm_increaseTime", "params": [seconds], "id": "1443" });
+        let r: JsonRpcResult<Value> = self.client.call(payload).await?;
+        let offset = r.result.unwrap_or(Value::Null);
+        time_offset(&offset)
+            .ok_or_else(|| Report::new(Error::UnexpectedResponseFormat))
+            .attach_printable_lazy(|| format!("evm_increaseTime returned {}", offset))
+    }
+
+    pub async fn evm_set_next_block_timestamp(&self, timestamp: u64) -> Result<(), Report<Error>> {
+        let payload = json!({ "jsonrpc": "2.0", "method": "evm_setNextBlockTimestamp", "params": [timestamp], "id": "1444" });
+        self.client.call_unit(payload).await
+    }
+
+    /// With automine off, transactions wait in the pool until [`Web3::evm_mine`].
+    pub async fn evm_set_automine(&self, enabled: bool) -> Result<(), Report<Error>> {
+        let payload = json!({ "jsonrpc": "2.0", "method": "evm_setAutomine", "params": [enabled], "id": "1445" });
+        self.client.call_unit(payload).await
+    }
+
+    /// Mines one block, at `timestamp` when given.
+    pub async fn evm_mine(&self, timestamp: Option<u64>) -> Result<(), Report<Error>> {
+        let params = match timestamp {
+            Some(timestamp) => json!([timestamp]),
+            None => json!([]),
+        };
+        let payload =
+            json!({ "jsonrpc": "2.0", "method": "evm_mine", "params": params, "id": "1446" });
+        self.client.call_unit(payload).await
+    }
+}
+
+/// Anvil answers with a number, Hardhat with a decimal string; hex is accepted too.
+fn time_offset(value: &Value) -> Option<i64> {
+    match value {
+        Value::Number(n) => n.as_i64(),
+        Value::String(s) if s.starts_with("0x") => {
+            hex::parse_quantity(s).ok().and_then(|n| n.try_into().ok())
+        }
+        Value::String(s) => s.parse().ok(),
+        _ => None,
+    }
+}
@@ -0,0 +1,22 @@
+//! Consensus-layer `engine_*` namespace, behind the `engine-api` feature.
+//!
+//! The engine port requires a JWT; pass it with [`crate::builder::Web3Builder::bearer_token`].
+
+use crate::client::Error;
+use crate::model::JsonRpcResult;
+use crate::web3::Web3;
+use error_stack::Report;
+use serde_json::json;
+
+impl Web3 {
+    /// Exchanges the list of supported engine methods with the execution client.
+    pub async fn engine_exchange_capabilities(
+        &self,
+        methods: &[&str],
+    ) -> Result<JsonRpcResult<Vec<String>>, Report<Error>> {
+        let payload = json!({ "jsonrpc": "2.0", "method": "engine_exchangeCapabilities", "params": [methods], "id": "901" });
+        let r: JsonRpcResult<Vec<String>> = self.client.call(payload).await?;
+
+        Ok(r)
+    }
+}
@@ -0,0 +1,622 @@
+use crate::client::Error;
+use crate::model::{
+    AccessListResult, AccountProof, Block, BlockId, FeeHistory, JsonRpcResult, Log, LogFilter,
+    Receipt, StateOverride, SyncStatus, Tag, Transaction, TransactionRequest,
+};
+use crate::types::{
+    AddressParam, BlockNumberParam, H256Param, IndexParam, StorageSlot, H256, U256,
+};
+use crate::web3::Web3;
+use error_stack::{Report, ResultExt};
+use serde_json::{json, Value};
+
+impl Web3 {
+    pub async fn eth_protocol_version(&self) -> Result<JsonRpcResult<String>, Report<Error>> {
+        let payload =
+            json!({ "jsonrpc": "2.0", "method": "eth_protocolVersion", "params": [], "id": "304" });
+        let r: JsonRpcResult<String> = self.client.call(payload).await?;
+
+        Ok(r)
+    }
+
+    pub async fn eth_syncing(&self) -> Result<JsonRpcResult<SyncStatus>, Report<Error>> {
+        let payload =
+            json!({ "jsonrpc": "2.0", "method": "eth_syncing", "params": [], "id": "305" });
+        let r: JsonRpcResult<SyncStatus> = self.client.call(payload).await?;
+
+        Ok(r)
+    }
+
+    pub async fn eth_coinbase(&self) -> Result<JsonRpcResult<String>, Report<Error>> {
+        let payload =
+            json!({ "jsonrpc": "2.0", "method": "eth_coinbase", "params": [], "id": "306" });
+        let r: JsonRpcResult<String> = self.client.call(payload).await?;
+
+        Ok(r)
+    }
+
+    pub async fn eth_mining(&self) -> Result<JsonRpcResult<bool>, Report<Error>> {
+        let payload =
+            json!({ "jsonrpc": "2.0", "method": "eth_mining", "params": [], "id": "307" });
+        let r: JsonRpcResult<bool> = self.client.call(payload).await?;
+
+        Ok(r)
+    }
+
+    pub async fn eth_hashrate(&self) -> Result<JsonRpcResult<String>, Report<Error>> {
+        let payload =
+            json!({ "jsonrpc": "2.0", "method": "eth_hashrate", "params": [], "id": "308" });
+        let r: JsonRpcResult<String> = self.client.call(payload).await?;
+
+        Ok(r)
+    }
+
+    pub async fn eth_gas_price(&self) -> Result<JsonRpcResult<String>, Report<Error>> {
+        let payload =
+            json!({ "jsonrpc": "2.0", "method": "eth_gasPrice", "params": [], "id": "309" });
+        let r: JsonRpcResult<String> = self.client.call(payload).await?;
+
+        Ok(r)
+    }
+
+    pub async fn eth_gas_price_typed(&self) -> Result<JsonRpcResult<U256>, Report<Error>> {
+        let payload =
+            json!({ "jsonrpc": "2.0", "method": "eth_gasPrice", "params": [], "id": "309" });
+        let r: JsonRpcResult<U256> = self.client.call(payload).await?;
+
+        Ok(r)
+    }
+
+    pub async fn eth_chain_id(&self) -> Result<JsonRpcResult<U256>, Report<Error>> {
+        let payload =
+            json!({ "jsonrpc": "2.0", "method": "eth_chainId", "params": [], "id": "339" });
+        let r: JsonRpcResult<U256> = self.client.call(payload).await?;
+
+        Ok(r)
+    }
+
+    pub async fn eth_accounts(&self) -> Result<JsonRpcResult<Vec<String>>, Report<Error>> {
+        let payload =
+            json!({ "jsonrpc": "2.0", "method": "eth_accounts", "params": [], "id": "310" });
+        let r: JsonRpcResult<Vec<String>> = self.client.call(payload).await?;
+
+        Ok(r)
+    }
+
+    pub async fn eth_get_balance(
+        &self,
+        address: impl Into<AddressParam>,
+        tag: Option<Tag>,
+    ) -> Result<JsonRpcResult<String>, Report<Error>> {
+        self.eth_get_balance_at_block(address, tag.unwrap_or(self.client.default_tag()))
+            .await
+    }
+
+    pub async fn eth_get_balance_at_block(
+        &self,
+        address: impl Into<AddressParam>,
+        block: impl Into<BlockId>,
+    ) -> Result<JsonRpcResult<String>, Report<Error>> {
+        let payload = json!({ "jsonrpc": "2.0", "method": "eth_getBalance", "params": [address.into(), block.into().eip1898()], "id": "311" });
+        let r: JsonRpcResult<String> = self.client.call(payload).await?;
+
+        Ok(r)
+    }
+
+    pub async fn eth_get_balance_typed(
+        &self,
+        address: impl Into<AddressParam>,
+        tag: Option<Tag>,
+    ) -> Result<JsonRpcResult<U256>, Report<Error>> {
+        let t = String::from(tag.unwrap_or(self.client.default_tag()));
+        let payload = json!({ "jsonrpc": "2.0", "method": "eth_getBalance", "params": [address.into(), t], "id": "311" });
+        let r: JsonRpcResult<U256> = self.client.call(payload).await?;
+
+        Ok(r)
+    }
+
+    pub async fn eth_get_storage_at(
+        &self,
+        data: impl Into<AddressParam>,
+        slot: impl Into<StorageSlot>,
+        tag: Option<Tag>,
+    ) -> Result<JsonRpcResult<String>, Report<Error>> {
+        self.eth_get_storage_at_block(data, slot, tag.unwrap_or(self.client.default_tag()))
+            .await
+    }
+
+    pub async fn eth_get_storage_at_block(
+        &self,
+        data: impl Into<AddressParam>,
+        slot: impl Into<StorageSlot>,
+        block: impl Into<BlockId>,
+    ) -> Result<JsonRpcResult<String>, Report<Error>> {
+        let slot = slot.into();
+        self.client.check_quantity("slot", slot.as_str(), 64)?;
+        let payload = json!({ "jsonrpc": "2.0", "method": "eth_getStorageAt", "params": [data.into(), slot, block.into().eip1898()], "id": "312" });
+        let r: JsonRpcResult<String> = self.client.call(payload).await?;
+
+        Ok(r)
+    }
+
+    pub async fn eth_get_transaction_count(
+        &self,
+        address: impl Into<AddressParam>,
+        tag: Option<Tag>,
+    ) -> Result<JsonRpcResult<String>, Report<Error>> {
+        self.eth_get_transaction_count_at_block(address, tag.unwrap_or(self.client.default_tag()))
+            .await
+    }
+
+    pub async fn eth_get_transaction_count_at_block(
+        &self,
+        address: impl Into<AddressParam>,
+        block: impl Into<BlockId>,
+    ) -> Result<JsonRpcResult<String>, Report<Error>> {
+        let payload = json!({ "jsonrpc": "2.0", "method": "eth_getTransactionCount", "params": [address.into(), block.into().eip1898()], "id": "313" });
+        let r: JsonRpcResult<String> = self.client.call(payload).await?;
+
+        Ok(r)
+    }
+
+    pub async fn eth_get_transaction_count_typed(
+        &self,
+        address: impl Into<AddressParam>,
+        tag: Option<Tag>,
+    ) -> Result<JsonRpcResult<U256>, Report<Error>> {
+        let t = String::from(tag.unwrap_or(self.client.default_tag()));
+        let payload = json!({ "jsonrpc": "2.0", "method": "eth_getTransactionCount", "params": [address.into(), t], "id": "313" });
+        let r: JsonRpcResult<U256> = self.client.call(payload).await?;
+
+        Ok(r)
+    }
+
+    pub async fn eth_get_block_transaction_count_by_hash(
+        &self,
+        hash: impl Into<H256Param>,
+    ) -> Result<JsonRpcResult<String>, Report<Error>> {
+        let hash = hash.into();
+        self.client.check_hex("hash", hash.as_str(), Some(64))?;
+        let payload = json!({ "jsonrpc": "2.0", "method": "eth_getBlockTransactionCountByHash", "params": [hash], "id": "314" });
+        let r: JsonRpcResult<String> = self.client.call(payload).await?;
+
+        Ok(r)
+    }
+
+    pub async fn eth_get_block_transaction_count_by_number(
+        &self,
+        number: impl Into<BlockNumberParam>,
+    ) -> Result<JsonRpcResult<String>, Report<Error>> {
+        let payload = json!({ "jsonrpc": "2.0", "method": "eth_getBlockTransactionCountByNumber", "params": [number.into()], "id": "315" });
+        let r: JsonRpcResult<String> = self.client.call(payload).await?;
+
+        Ok(r)
+    }
+
+    pub async fn eth_get_uncle_count_by_block_hash(
+        &self,
+        hash: impl Into<H256Param>,
+    ) -> Result<JsonRpcResult<String>, Report<Error>> {
+        let hash = hash.into();
+        self.client.check_hex("hash", hash.as_str(), Some(64))?;
+        let payload = json!({ "jsonrpc": "2.0", "method": "eth_getUncleCountByBlockHash", "params": [hash], "id": "316" });
+        let r: JsonRpcResult<String> = self.client.call(payload).await?;
+
+        Ok(r)
+    }
+
+    pub async fn eth_get_uncle_count_by_block_number(
+        &self,
+        number: impl Into<BlockNumberParam>,
+    ) -> Result<JsonRpcResult<String>, Report<Error>> {
+        let payload = json!({ "jsonrpc": "2.0", "method": "eth_getUncleCountByBlockNumber", "params": [number.into()], "id": "317" });
+        let r: JsonRpcResult<String> = self.client.call(payload).await?;
+
+        Ok(r)
+    }
+
+    pub async fn eth_get_code(
+        &self,
+        address: impl Into<AddressParam>,
+        tag: Option<Tag>,
+    ) -> Result<JsonRpcResult<String>, Report<Error>> {
+        self.eth_get_code_at_block(address, tag.unwrap_or(self.client.default_tag()))
+            .await
+    }
+
+    pub async fn eth_get_code_at_block(
+        &self,
+        address: impl Into<AddressParam>,
+        block: impl Into<BlockId>,
+    ) -> Result<JsonRpcResult<String>, Report<Error>> {
+        let payload = json!({ "jsonrpc": "2.0", "method": "eth_getCode", "params": [address.into(), block.into().eip1898()], "id": "318" });
+        let r: JsonRpcResult<String> = self.client.call(payload).await?;
+
+        Ok(r)
+    }
+
+    pub async fn eth_sign(
+        &self,
+        address: impl Into<AddressParam>,
+        data: &str,
+    ) -> Result<JsonRpcResult<String>, Report<Error>> {
+        let payload = json!({ "jsonrpc": "2.0", "method": "eth_sign", "params": [address.into(), data], "id": "319" });
+        let r: JsonRpcResult<String> = self.client.call(payload).await?;
+
+        Ok(r)
+    }
+
+    /// Only `from` is required; the node fills in gas, fees and nonce when they are omitted,
+    /// and a request without `to` creates a contract.
+    pub async fn eth_send_transaction(
+        &self,
+        request: &TransactionRequest,
+    ) -> Result<JsonRpcResult<String>, Report<Error>> {
+        request
+            .validate_send()
+            .change_context(Error::InvalidTransactionRequest)?;
+        let payload = json!({ "jsonrpc": "2.0", "method": "eth_sendTransaction", "params": [request], "id": "320" });
+        let r: JsonRpcResult<String> = self.client.call(payload).await?;
+
+        Ok(r)
+    }
+
+    /// Signs without broadcasting; the result is the RLP-encoded raw transaction.
+    pub async fn eth_sign_transaction(
+        &self,
+        request: &TransactionRequest,
+    ) -> Result<JsonRpcResult<String>, Report<Error>> {
+        request
+            .validate_send()
+            .change_context(Error::InvalidTransactionRequest)?;
+        let payload = json!({ "jsonrpc": "2.0", "method": "eth_signTransaction", "params": [request], "id": "334" });
+        let r: JsonRpcResult<String> = self.client.call(payload).await?;
+
+        Ok(r)
+    }
+
+    pub async fn eth_send_raw_transaction(
+        &self,
+        hash: &str,
+    ) -> Result<JsonRpcResult<String>, Report<Error>> {
+        self.client.check_hex("raw_transaction", hash, None)?;
+        let payload = json!({ "jsonrpc": "2.0", "method": "eth_sendRawTransaction", "params": [hash], "id": "321" });
+        let r: JsonRpcResult<String> = self.client.call(payload).await?;
+
+        Ok(r)
+    }
+
+    pub async fn eth_call(&self, data: Value) -> Result<JsonRpcResult<String>, Report<Error>> {
+        self.eth_call_at_block(data, self.client.default_tag())
+            .await
+    }
+
+    pub async fn eth_call_at_block(
+        &self,
+        data: Value,
+        block: impl Into<BlockId>,
+    ) -> Result<JsonRpcResult<String>, Report<Error>> {
+        let payload = json!({ "jsonrpc": "2.0", "method": "eth_call", "params": [data, block.into().eip1898()], "id": "322" });
+        let r: JsonRpcResult<String> = self.client.call(payload).await?;
+
+        Ok(r)
+    }
+
+    pub async fn eth_call_request(
+        &self,
+        request: &TransactionRequest,
+    ) -> Result<JsonRpcResult<String>, Report<Error>> {
+        self.eth_call_request_at_block(request, self.client.default_tag())
+            .await
+    }
+
+    pub async fn eth_call_request_at_block(
+        &self,
+        request: &TransactionRequest,
+        block: impl Into<BlockId>,
+    ) -> Result<JsonRpcResult<String>, Report<Error>> {
+        request
+            .validate()
+            .change_context(Error::InvalidTransactionRequest)?;
+        let payload = json!({ "jsonrpc": "2.0", "method": "eth_call", "params": [request, block.into().eip1898()], "id": "322" });
+        let r: JsonRpcResult<String> = self.client.call(payload).await?;
+
+        Ok(r)
+    }
+
+    /// eth_call with a state override set as the third parameter.
+    pub async fn eth_call_with_overrides(
+        &self,
+        request: &TransactionRequest,
+        block: impl Into<BlockId>,
+        overrides: &StateOverride,
+    ) -> Result<JsonRpcResult<String>, Report<Error>> {
+        request
+            .validate()
+            .change_context(Error::InvalidTransactionRequest)?;
+        let payload = json!({ "jsonrpc": "2.0", "method": "eth_call", "params": [request, block.into().eip1898(), overrides], "id": "322" });
+        let r: JsonRpcResult<String> = self.client.call(payload).await?;
+
+        Ok(r)
+    }
+
+    pub async fn eth_estimate_gas(
+        &self,
+        data: Value,
+    ) -> Result<JsonRpcResult<String>, Report<Error>> {
+        let payload =
+            json!({ "jsonrpc": "2.0", "method": "eth_estimateGas", "params": [data], "id": "323" });
+        let r: JsonRpcResult<String> = self.client.call(payload).await?;
+
+        Ok(r)
+    }
+
+    pub async fn eth_estimate_gas_at_block(
+        &self,
+        data: Value,
+        block: impl Into<BlockId>,
+    ) -> Result<JsonRpcResult<String>, Report<Error>> {
+        let payload = json!({ "jsonrpc": "2.0", "method": "eth_estimateGas", "params": [data, block.into().eip1898()], "id": "323" });
+        let r: JsonRpcResult<String> = self.client.call(payload).await?;
+
+        Ok(r)
+    }
+
+    pub async fn eth_estimate_gas_request(
+        &self,
+        request: &TransactionRequest,
+    ) -> Result<JsonRpcResult<String>, Report<Error>> {
+        request
+            .validate()
+            .change_context(Error::InvalidTransactionRequest)?;
+        let payload = json!({ "jsonrpc": "2.0", "method": "eth_estimateGas", "params": [request], "id": "323" });
+        let r: JsonRpcResult<String> = self.client.call(payload).await?;
+
+        Ok(r)
+    }
+
+    /// On revert the report carries the node's `JsonRpcErrorMessage<Value>`, whose
+    /// `revert_data()` holds the ABI-encoded reason.
+    pub async fn eth_estimate_gas_request_at_block(
+        &self,
+        request: &TransactionRequest,
+        block: impl Into<BlockId>,
+    ) -> Result<JsonRpcResult<String>, Report<Error>> {
+        request
+            .validate()
+            .change_context(Error::InvalidTransactionRequest)?;
+        let payload = json!({ "jsonrpc": "2.0", "method": "eth_estimateGas", "params": [request, block.into().eip1898()], "id": "323" });
+        let r: JsonRpcResult<String> = self.client.call(payload).await?;
+
+        Ok(r)
+    }
+
+    pub async fn eth_estimate_gas_typed(
+        &self,
+        data: Value,
+    ) -> Result<JsonRpcResult<U256>, Report<Error>> {
+        let payload =
+            json!({ "jsonrpc": "2.0", "method": "eth_estimateGas", "params": [data], "id": "323" });
+        let r: JsonRpcResult<U256> = self.client.call(payload).await?;
+
+        Ok(r)
+    }
+
+    pub async fn eth_get_block_by_hash(
+        &self,
+        hash: impl Into<H256Param>,
+        obj: bool,
+    ) -> Result<JsonRpcResult<Block>, Report<Error>> {
+        let hash = hash.into();
+        self.client.check_hex("hash", hash.as_str(), Some(64))?;
+        let payload = json!({ "jsonrpc": "2.0", "method": "eth_getBlockByHash", "params": [hash, obj], "id": "324" });
+        let r: JsonRpcResult<Block> = self.client.call(payload).await?;
+
+        Ok(r)
+    }
+
+    pub async fn eth_get_block_by_number(
+        &self,
+        number: impl Into<BlockNumberParam>,
+        obj: bool,
+    ) -> Result<JsonRpcResult<Block>, Report<Error>> {
+        let payload = json!({ "jsonrpc": "2.0", "method": "eth_getBlockByNumber", "params": [number.into(), obj], "id": "325" });
+        let r: JsonRpcResult<Block> = self.client.call(payload).await?;
+
+        Ok(r)
+    }
+
+    pub async fn eth_get_block(
+        &self,
+        block: impl Into<BlockId>,
+        obj: bool,
+    ) -> Result<JsonRpcResult<Block>, Report<Error>> {
+        let payload = match block.into() {
+            BlockId::Hash(hash) => {
+                json!({ "jsonrpc": "2.0", "method": "eth_getBlockByHash", "params": [hash, obj], "id": "324" })
+            }
+            other => {
+                json!({ "jsonrpc": "2.0", "method": "eth_getBlockByNumber", "params": [other, obj], "id": "325" })
+            }
+        };
+        let r: JsonRpcResult<Block> = self.client.call(payload).await?;
+
+        Ok(r)
+    }
+
+    pub async fn eth_get_transaction_by_hash(
+        &self,
+        hash: impl Into<H256Param>,
+    ) -> Result<JsonRpcResult<Transaction>, Report<Error>> {
+        let hash = hash.into();
+        self.client.check_hex("hash", hash.as_str(), Some(64))?;
+        let payload = json!({ "jsonrpc": "2.0", "method": "eth_getTransactionByHash", "params": [hash], "id": "326" });
+        let r: JsonRpcResult<Transaction> = self.client.call(payload).await?;
+
+        Ok(r)
+    }
+
+    pub async fn eth_block_number(&self) -> Result<JsonRpcResult<String>, Report<Error>> {
+        let payload =
+            json!({ "jsonrpc": "2.0", "method": "eth_blockNumber", "params": [], "id": "327" });
+        let r: JsonRpcResult<String> = self.client.call(payload).await?;
+
+        Ok(r)
+    }
+
+    pub async fn eth_block_number_typed(&self) -> Result<JsonRpcResult<U256>, Report<Error>> {
+        let payload =
+            json!({ "jsonrpc": "2.0", "method": "eth_blockNumber", "params": [], "id": "327" });
+        let r: JsonRpcResult<U256> = self.client.call(payload).await?;
+
+        Ok(r)
+    }
+
+    pub async fn eth_get_transaction_receipt(
+        &self,
+        hash: impl Into<H256Param>,
+    ) -> Result<JsonRpcResult<Receipt>, Report<Error>> {
+        let hash = hash.into();
+        self.client.check_hex("hash", hash.as_str(), Some(64))?;
+        let payload = json!({ "jsonrpc": "2.0", "method": "eth_getTransactionReceipt", "params": [hash], "id": "328" });
+        let r: JsonRpcResult<Receipt> = self.client.call(payload).await?;
+
+        Ok(r)
+    }
+
+    pub async fn eth_get_transaction_by_block_hash_and_index(
+        &self,
+        hash: impl Into<H256Param>,
+        index: impl Into<IndexParam>,
+    ) -> Result<JsonRpcResult<Transaction>, Report<Error>> {
+        let hash = hash.into();
+        self.client.check_hex("hash", hash.as_str(), Some(64))?;
+        let payload = json!({ "jsonrpc": "2.0", "method": "eth_getTransactionByBlockHashAndIndex", "params": [hash, index.into()], "id": "329" });
+        let r: JsonRpcResult<Transaction> = self.client.call(payload).await?;
+
+        Ok(r)
+    }
+
+    pub async fn eth_get_transaction_by_block_number_and_index(
+        &self,
+        number: impl Into<BlockNumberParam>,
+        index: impl Into<IndexParam>,
+    ) -> Result<JsonRpcResult<Transaction>, Report<Error>> {
+        let payload = json!({ "jsonrpc": "2.0", "method": "eth_getTransactionByBlockNumberAndIndex", "params": [number.into(), index.into()], "id": "330" });
+        let r: JsonRpcResult<Transaction> = self.client.call(payload).await?;
+
+        Ok(r)
+    }
+
+    pub async fn eth_get_uncle_by_block_hash_and_index(
+        &self,
+        hash: impl Into<H256Param>,
+        index: impl Into<IndexParam>,
+    ) -> Result<JsonRpcResult<Block>, Report<Error>> {
+        let hash = hash.into();
+        self.client.check_hex("hash", hash.as_str(), Some(64))?;
+        let payload = json!({ "jsonrpc": "2.0", "method": "eth_getUncleByBlockHashAndIndex", "params": [hash, index.into()], "id": "331" });
+        let r: JsonRpcResult<Block> = self.client.call(payload).await?;
+
+        Ok(r)
+    }
+
+    pub async fn eth_get_uncle_by_block_number_and_index(
+        &self,
+        number: impl Into<BlockNumberParam>,
+        index: impl Into<IndexParam>,
+    ) -> Result<JsonRpcResult<Block>, Report<Error>> {
+        let payload = json!({ "jsonrpc": "2.0", "method": "eth_getUncleByBlockNumberAndIndex", "params": [number.into(), index.into()], "id": "332" });
+        let r: JsonRpcResult<Block> = self.client.call(payload).await?;
+
+        Ok(r)
+    }
+
+    pub async fn eth_get_compilers(&self) -> Result<JsonRpcResult<Vec<String>>, Report<Error>> {
+        let payload =
+            json!({ "jsonrpc": "2.0", "method": "eth_getCompilers", "params": [], "id": "333" });
+        let r: JsonRpcResult<Vec<String>> = self.client.call(payload).await?;
+
+        Ok(r)
+    }
+
+    pub async fn eth_create_access_list(
+        &self,
+        request: &TransactionRequest,
+        block: impl Into<BlockId>,
+    ) -> Result<JsonRpcResult<AccessListResult>, Report<Error>> {
+        let payload = json!({ "jsonrpc": "2.0", "method": "eth_createAccessList", "params": [request, block.into().eip1898()], "id": "335" });
+        let r: JsonRpcResult<AccessListResult> = self.client.call(payload).await?;
+
+        Ok(r)
+    }
+
+    /// `newest` must be a number or tag; pass an empty `reward_percentiles` to skip the
+    /// `reward` field.
+    pub async fn eth_fee_history(
+        &self,
+        block_count: u64,
+        newest: impl Into<BlockId>,
+        reward_percentiles: &[f64],
+    ) -> Result<JsonRpcResult<FeeHistory>, Report<Error>> {
+        let payload = json!({ "jsonrpc": "2.0", "method": "eth_feeHistory", "params": [format!("{:#x}", block_count), newest.into(), reward_percentiles], "id": "336" });
+        let r: JsonRpcResult<FeeHistory> = self.client.call(payload).await?;
+
+        Ok(r)
+    }
+
+    pub async fn eth_get_proof(
+        &self,
+        address: impl Into<AddressParam>,
+        storage_keys: &[H256],
+        block: impl Into<BlockId>,
+    ) -> Result<JsonRpcResult<AccountProof>, Report<Error>> {
+        let payload = json!({ "jsonrpc": "2.0", "method": "eth_getProof", "params": [address.into(), storage_keys, block.into().eip1898()], "id": "337" });
+        let r: JsonRpcResult<AccountProof> = self.client.call(payload).await?;
+
+        Ok(r)
+    }
+
+    pub async fn eth_get_logs(
+        &self,
+        filter: &LogFilter,
+    ) -> Result<JsonRpcResult<Vec<Log>>, Report<Error>> {
+        let payload =
+            json!({ "jsonrpc": "2.0", "method": "eth_getLogs", "params": [filter], "id": "338" });
+        let r: JsonRpcResult<Vec<Log>> = self.client.call(payload).await?;
+
+        Ok(r)
+    }
+
+    /// Id of a filter collecting the hashes of transactions entering the node's pool.
+    pub async fn eth_new_pending_transaction_filter(
+        &self,
+    ) -> Result<JsonRpcResult<String>, Report<Error>> {
+        let payload = json!({ "jsonrpc": "2.0", "method": "eth_newPendingTransactionFilter", "params": [], "id": "340" });
+        let r: JsonRpcResult<String> = self.client.call(payload).await?;
+
+        Ok(r)
+    }
+
+    /// What the filter collected since the last call: hashes for block and pending
+    /// transaction filters, logs for log filters.
+    pub async fn eth_get_filter_changes(
+        &self,
+        filter_id: &str,
+    ) -> Result<JsonRpcResult<Vec<Value>>, Report<Error>> {
+        let payload = json!({ "jsonrpc": "2.0", "method": "eth_getFilterChanges", "params": [filter_id], "id": "341" });
+        let r: JsonRpcResult<Vec<Value>> = self.client.call(payload).await?;
+
+        Ok(r)
+    }
+
+    pub async fn eth_uninstall_filter(
+        &self,
+        filter_id: &str,
+    ) -> Result<JsonRpcResult<bool>, Report<Error>> {
+        let payload = json!({ "jsonrpc": "2.0", "method": "eth_uninstallFilter", "params": [filter_id], "id": "342" });
+        let r: JsonRpcResult<bool> = self.client.call(payload).await?;
+
+        Ok(r)
+    }
+}
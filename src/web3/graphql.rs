@@ -0,0 +1,356 @@
+//! Geth's GraphQL endpoint, behind the `graphql` feature. One query can fetch a block with
+//! just the fields wanted, its receipts and logs included, where JSON-RPC takes a call per
+//! receipt or an `eth_getBlockReceipts` with everything.
+//!
+//! Queries go to [`Client::graphql_url`](crate::client::Client::graphql_url), through the
+//! same circuit breaker and rate limit as JSON-RPC calls but without retries.
+
+use crate::client::{truncate_bytes, Error};
+use crate::model::{Block, GraphQlError, Receipt};
+use crate::types::Bloom;
+use crate::web3::Web3;
+use error_stack::{Report, ResultExt};
+use reqwest::StatusCode;
+use serde::Deserialize;
+use serde_json::{json, Map, Value};
+
+/// Query behind [`Web3::gql_block_with_receipts`]: the members of [`Block`], [`Transaction`]
+/// and [`Receipt`] Geth's schema has.
+///
+/// [`Transaction`]: crate::model::Transaction
+pub const BLOCK_WITH_RECEIPTS: &str = "query($number: Long) {
+  block(number: $number) {
+    number hash parent { hash } nonce transactionsRoot stateRoot receiptsRoot
+    miner { address } extraData gasLimit gasUsed baseFeePerGas timestamp logsBloom mixHash
+    difficulty ommerHash ommers { hash } withdrawalsRoot blobGasUsed excessBlobGas
+    withdrawals { index validator address amount }
+    transactions {
+      hash nonce index from { address } to { address } value gasPrice maxFeePerGas
+      maxPriorityFeePerGas maxFeePerBlobGas gas inputData r s v yParity type
+      accessList { address storageKeys } blobVersionedHashes
+      status gasUsed cumulativeGasUsed effectiveGasPrice blobGasUsed blobGasPrice
+      createdContract { address }
+      logs { index account { address } topics data }
+    }
+  }
+}";
+
+/// A block with its transactions in full and their receipts, in block order.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BlockWithReceipts {
+    /// `size` is 0 and `total_difficulty` and `parent_beacon_block_root` are `None`: Geth's
+    /// schema does not have them.
+    pub block: Block,
+    pub receipts: Vec<Receipt>,
+}
+
+#[derive(Deserialize)]
+struct Response {
+    #[serde(default)]
+    data: Option<Value>,
+    #[serde(default)]
+    errors: Vec<GraphQlError>,
+}
+
+impl Web3 {
+    /// Runs `query` with `variables` and returns its `data`. A response with `errors` fails
+    /// with [`Error::GraphQl`], which keeps whatever partial `data` came with them.
+    pub async fn graphql(&self, query: &str, variables: Value) -> Result<Value, Report<Error>> {
+        let payload = json!({ "query": query, "variables": variables });
+        let (status, body) = self.client.post_graphql(&payload).await?;
+        read_response(status, &body)
+    }
+
+    /// Block `number` with its receipts and logs in one query, or `None` when the node does
+    /// not have it.
+    pub async fn gql_block_with_receipts(
+        &self,
+        number: u64,
+    ) -> Result<Option<BlockWithReceipts>, Report<Error>> {
+        let data = self
+            .graphql(BLOCK_WITH_RECEIPTS, json!({ "number": number }))
+            .await?;
+        match &data["block"] {
+            Value::Null => Ok(None),
+            block => block_with_receipts(block)
+                .attach_printable_lazy(|| format!("graphql block {}", number))
+                .map(Some),
+        }
+    }
+}
+
+fn read_response(status: StatusCode, body: &[u8]) -> Result<Value, Report<Error>> {
+    // Geth answers a query with errors 400, other servers 200; both carry the same shape
+    match serde_json::from_slice::<Response>(body) {
+        Ok(Response { errors, data }) if !errors.is_empty() => {
+            let data = data.filter(|data| !data.is_null());
+            Err(Report::new(Error::GraphQl { errors, data })
+                .attach_printable(format!("status {}", status)))
+        }
+        Ok(Response {
+            data: Some(data), ..
+        }) if status == StatusCode::OK => Ok(data),
+        _ if status != StatusCode::OK => Err(Report::new(Error::HttpError(status))
+            .attach_printable(format!("response: {}", truncate_bytes(body, 256)))),
+        _ => Err(Report::new(Error::UnexpectedResponseFormat)
+            .attach_printable(format!("response: {}", truncate_bytes(body, 256)))),
+    }
+}
+
+/// `gql`'s block as the JSON-RPC models read it. Geth's `Long`s come as hex strings or,
+/// before 1.12, numbers; both become quantities.
+fn block_with_receipts(gql: &Value) -> Result<BlockWithReceipts, Report<Error>> {
+    let hash = &gql["hash"];
+    let number = quantity(&gql["number"]);
+    let mut transactions = Vec::new();
+    let mut receipts = Vec::new();
+    for tx in gql["transactions"].as_array().into_iter().flatten() {
+        let index = quantity(&tx["index"]);
+        let logs: Vec<Value> = tx["logs"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .map(|log| {
+                object(json!({
+                    "address": log["account"]["address"],
+                    "blockHash": hash,
+                    "blockNumber": number,
+                    "data": log["data"],
+                    "logIndex": quantity(&log["index"]),
+                    "removed": false,
+                    "topics": log["topics"],
+                    "transactionHash": tx["hash"],
+                    "transactionIndex": index,
+                }))
+            })
+            .collect();
+        transactions.push(object(json!({
+            "accessList": tx["accessList"],
+            "blobVersionedHashes": tx["blobVersionedHashes"],
+            "blockHash": hash,
+            "blockNumber": number,
+            "from": tx["from"]["address"],
+            "gas": quantity(&tx["gas"]),
+            "gasPrice": quantity(&tx["gasPrice"]),
+            "hash": tx["hash"],
+            "input": tx["inputData"],
+            "maxFeePerBlobGas": quantity(&tx["maxFeePerBlobGas"]),
+            "maxFeePerGas": quantity(&tx["maxFeePerGas"]),
+            "maxPriorityFeePerGas": quantity(&tx["maxPriorityFeePerGas"]),
+            "nonce": quantity(&tx["nonce"]),
+            "r": quantity(&tx["r"]),
+            "s": quantity(&tx["s"]),
+            "to": tx["to"]["address"],
+            "transactionIndex": index,
+            "type": quantity(&tx["type"]),
+            "v": quantity(&tx["v"]),
+            "value": quantity(&tx["value"]),
+            "yParity": quantity(&tx["yParity"]),
+        })));
+        receipts.push(object(json!({
+            "blobGasPrice": quantity(&tx["blobGasPrice"]),
+            "blobGasUsed": quantity(&tx["blobGasUsed"]),
+            "blockHash": hash,
+            "blockNumber": number,
+            "contractAddress": tx["createdContract"]["address"],
+            "cumulativeGasUsed": quantity(&tx["cumulativeGasUsed"]),
+            "effectiveGasPrice": quantity(&tx["effectiveGasPrice"]),
+            "from": tx["from"]["address"],
+            "gasUsed": quantity(&tx["gasUsed"]),
+            "logs": logs,
+            // not in the schema, rebuilt from the logs below
+            "logsBloom": Bloom::default(),
+            "status": quantity(&tx["status"]),
+            "to": tx["to"]["address"],
+            "transactionHash": tx["hash"],
+            "transactionIndex": index,
+            "type": quantity(&tx["type"]),
+        })));
+    }
+    let withdrawals = gql["withdrawals"].as_array().map(|withdrawals| {
+        withdrawals
+            .iter()
+            .map(|w| {
+                json!({
+                    "address": w["address"],
+                    "amount": quantity(&w["amount"]),
+                    "index": quantity(&w["index"]),
+                    "validatorIndex": quantity(&w["validator"]),
+                })
+            })
+            .collect::<Vec<_>>()
+    });
+    let ommers: Vec<&Value> = gql["ommers"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .map(|ommer| &ommer["hash"])
+        .collect();
+    let block = object(json!({
+        "baseFeePerGas": quantity(&gql["baseFeePerGas"]),
+        "blobGasUsed": quantity(&gql["blobGasUsed"]),
+        "difficulty": quantity(&gql["difficulty"]),
+        "excessBlobGas": quantity(&gql["excessBlobGas"]),
+        "extraData": gql["extraData"],
+        "gasLimit": quantity(&gql["gasLimit"]),
+        "gasUsed": quantity(&gql["gasUsed"]),
+        "hash": hash,
+        "logsBloom": gql["logsBloom"],
+        "miner": gql["miner"]["address"],
+        "mixHash": gql["mixHash"],
+        "nonce": gql["nonce"],
+        "number": number,
+        "parentHash": gql["parent"]["hash"],
+        "receiptsRoot": gql["receiptsRoot"],
+        "sha3Uncles": gql["ommerHash"],
+        "size": "0x0",
+        "stateRoot": gql["stateRoot"],
+        "timestamp": quantity(&gql["timestamp"]),
+        "transactions": transactions,
+        "transactionsRoot": gql["transactionsRoot"],
+        "uncles": ommers,
+        "withdrawals": withdrawals,
+        "withdrawalsRoot": gql["withdrawalsRoot"],
+    }));
+
+    let block: Block = serde_path_to_error::deserialize(block)
+        .change_context(Error::FailedToDeserialize)
+        .attach_printable("graphql block does not fit the block model")?;
+    let mut receipts: Vec<Receipt> = serde_path_to_error::deserialize(Value::Array(receipts))
+        .change_context(Error::FailedToDeserialize)
+        .attach_printable("graphql transaction does not fit the receipt model")?;
+    for receipt in &mut receipts {
+        for log in &receipt.logs {
+            receipt.logs_bloom.accrue(log.address.as_bytes());
+            for topic in &log.topics {
+                receipt.logs_bloom.accrue(topic.as_bytes());
+            }
+        }
+    }
+    Ok(BlockWithReceipts { block, receipts })
+}
+
+/// A `Long` or `BigInt` as a hex quantity; `null` stays `null`.
+fn quantity(value: &Value) -> Value {
+    match value {
+        Value::Number(n) => match n.as_u64() {
+            Some(n) => Value::String(format!("{:#x}", n)),
+            None => value.clone(),
+        },
+        Value::String(s) if !s.starts_with("0x") => match s.parse::<u128>() {
+            Ok(n) => Value::String(format!("{:#x}", n)),
+            Err(_) => value.clone(),
+        },
+        value => value.clone(),
+    }
+}
+
+/// `value` without its `null` members, which the models read as absent but not always as
+/// `null` (a legacy transaction has no `accessList`, not a `null` one).
+fn object(value: Value) -> Value {
+    match value {
+        Value::Object(members) => Value::Object(
+            members
+                .into_iter()
+                .filter(|(_, member)| !member.is_null())
+                .collect::<Map<_, _>>(),
+        ),
+        value => value,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::TxType;
+    use crate::types::Address;
+
+    fn fixture() -> Value {
+        serde_json::from_str(include_str!("../../tests/fixtures/graphql_block.json")).unwrap()
+    }
+
+    #[test]
+    fn quantities() {
+        assert_eq!(quantity(&json!(16)), json!("0x10"));
+        assert_eq!(quantity(&json!("0x10")), json!("0x10"));
+        assert_eq!(quantity(&json!("16")), json!("0x10"));
+        assert_eq!(quantity(&Value::Null), Value::Null);
+    }
+
+    #[test]
+    fn block_maps_into_the_models() {
+        let data = read_response(StatusCode::OK, fixture().to_string().as_bytes()).unwrap();
+        let BlockWithReceipts { block, receipts } = block_with_receipts(&data["block"]).unwrap();
+        assert_eq!(block.number, Some(19_000_000));
+        assert_eq!(block.gas_used, 0x1c9c380);
+        assert_eq!(block.uncles.len(), 0);
+        assert_eq!(
+            block.withdrawals.as_ref().unwrap()[0].validator_index,
+            0x3a8b1
+        );
+        let txs = block.transactions.as_full().unwrap();
+        assert_eq!(txs.len(), 2);
+        assert_eq!(receipts.len(), 2);
+        assert_eq!(txs[0].tx_type(), Some(TxType::DynamicFee));
+        assert_eq!(txs[1].tx_type(), Some(TxType::Legacy));
+        assert!(txs[1].access_list.is_none());
+        assert!(block.extra.is_empty() && txs[0].extra.is_empty());
+
+        let receipt = &receipts[0];
+        assert_eq!(receipt.transaction_hash, txs[0].hash);
+        assert_eq!(receipt.transaction_index, "0x0");
+        assert_eq!(receipt.status, Some(true));
+        assert_eq!(receipt.fee_paid_wei(), Some(0x5208 * 0x3b9aca0e));
+        let log = &receipt.logs[0];
+        assert_eq!(log.block_hash, block.hash);
+        assert_eq!(log.log_index.as_deref(), Some("0x0"));
+        assert!(receipt.logs_bloom.contains_input(log.address.as_bytes()));
+        assert!(receipt.logs_bloom.contains_input(log.topics[1].as_bytes()));
+        // a receipt without logs has an empty bloom
+        assert_eq!(receipts[1].status, Some(false));
+        let created: Address = "0x5fbdb2315678afecb367f032d93f642f64180aa3"
+            .parse()
+            .unwrap();
+        assert_eq!(receipts[1].contract_address, Some(created));
+        assert_eq!(receipts[1].logs_bloom, Bloom::default());
+    }
+
+    #[test]
+    fn errors_are_not_success() {
+        let body = json!({
+            "errors": [{ "message": "header not found", "path": ["block", "transactions", 1, "status"] }],
+            "data": { "block": { "number": "0x1" } }
+        });
+        let report = read_response(StatusCode::OK, body.to_string().as_bytes()).unwrap_err();
+        match report.current_context() {
+            Error::GraphQl { errors, data } => {
+                assert_eq!(
+                    errors[0].to_string(),
+                    "header not found (at block.transactions.1.status)"
+                );
+                assert_eq!(data.as_ref().unwrap()["block"]["number"], "0x1");
+            }
+            other => panic!("unexpected error {:?}", other),
+        }
+
+        // Geth's validation errors: 400, `data` null
+        let body = json!({ "errors": [{ "message": "Cannot query field \"size\" on type \"Block\"." }], "data": null });
+        let report =
+            read_response(StatusCode::BAD_REQUEST, body.to_string().as_bytes()).unwrap_err();
+        assert!(matches!(
+            report.current_context(),
+            Error::GraphQl { data: None, .. }
+        ));
+
+        let report = read_response(StatusCode::NOT_FOUND, b"404 page not found").unwrap_err();
+        assert!(matches!(
+            report.current_context(),
+            Error::HttpError(StatusCode::NOT_FOUND)
+        ));
+        let report = read_response(StatusCode::OK, b"{}").unwrap_err();
+        assert!(matches!(
+            report.current_context(),
+            Error::UnexpectedResponseFormat
+        ));
+    }
+}
@@ -0,0 +1,31 @@
+use crate::client::Error;
+use crate::model::JsonRpcResult;
+use crate::web3::Web3;
+use error_stack::Report;
+use serde_json::json;
+
+impl Web3 {
+    pub async fn net_version(&self) -> Result<JsonRpcResult<String>, Report<Error>> {
+        let payload =
+            json!({ "jsonrpc": "2.0", "method": "net_version", "params": [], "id": "401" });
+        let r: JsonRpcResult<String> = self.client.call(payload).await?;
+
+        Ok(r)
+    }
+
+    pub async fn net_listening(&self) -> Result<JsonRpcResult<bool>, Report<Error>> {
+        let payload =
+            json!({ "jsonrpc": "2.0", "method": "net_listening", "params": [], "id": "402" });
+        let r: JsonRpcResult<bool> = self.client.call(payload).await?;
+
+        Ok(r)
+    }
+
+    pub async fn net_peer_count(&self) -> Result<JsonRpcResult<i64>, Report<Error>> {
+        let payload =
+            json!({ "jsonrpc": "2.0", "method": "net_peerCount", "params": [], "id": "303" });
+        let r: JsonRpcResult<i64> = self.client.call(payload).await?;
+
+        Ok(r)
+    }
+}
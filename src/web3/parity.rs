@@ -0,0 +1,108 @@
+//! OpenEthereum `parity_*` namespace as kept by Erigon and Nethermind, behind the
+//! `parity-api` feature.
+
+use crate::client::Error;
+use crate::model::{JsonRpcResult, Transaction};
+use crate::types::{Address, U256};
+use crate::web3::Web3;
+use error_stack::Report;
+use serde::Serialize;
+use serde_json::json;
+
+/// Comparison against a numeric transaction field, sent as `{"eq": ...}`, `{"gt": ...}` or
+/// `{"lt": ...}`.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Comparison {
+    Eq(U256),
+    Gt(U256),
+    Lt(U256),
+}
+
+/// Match on a transaction's sender or recipient.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum AddressFilter {
+    Eq(Address),
+    /// Only valid for `to`: matches transactions that deploy a contract.
+    Action(ContractCreation),
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ContractCreation {
+    ContractCreation,
+}
+
+/// parity_pendingTransactions filter. Unset fields are omitted and match anything.
+#[derive(Serialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct PendingTxFilter {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub from: Option<AddressFilter>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub to: Option<AddressFilter>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gas: Option<Comparison>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gas_price: Option<Comparison>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value: Option<Comparison>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub nonce: Option<Comparison>,
+}
+
+impl PendingTxFilter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn from(mut self, from: Address) -> Self {
+        self.from = Some(AddressFilter::Eq(from));
+        self
+    }
+
+    pub fn to(mut self, to: Address) -> Self {
+        self.to = Some(AddressFilter::Eq(to));
+        self
+    }
+
+    pub fn contract_creation(mut self) -> Self {
+        self.to = Some(AddressFilter::Action(ContractCreation::ContractCreation));
+        self
+    }
+
+    pub fn gas(mut self, gas: Comparison) -> Self {
+        self.gas = Some(gas);
+        self
+    }
+
+    pub fn gas_price(mut self, gas_price: Comparison) -> Self {
+        self.gas_price = Some(gas_price);
+        self
+    }
+
+    pub fn value(mut self, value: Comparison) -> Self {
+        self.value = Some(value);
+        self
+    }
+
+    pub fn nonce(mut self, nonce: Comparison) -> Self {
+        self.nonce = Some(nonce);
+        self
+    }
+}
+
+impl Web3 {
+    /// Pooled transactions, at most `limit` of them. They are not mined yet, so their block
+    /// fields are `None`.
+    pub async fn parity_pending_transactions(
+        &self,
+        limit: Option<u64>,
+        filter: Option<PendingTxFilter>,
+    ) -> Result<JsonRpcResult<Vec<Transaction>>, Report<Error>> {
+        let payload = json!({ "jsonrpc": "2.0", "method": "parity_pendingTransactions", "params": [limit, filter], "id": "1201" });
+        let r: JsonRpcResult<Vec<Transaction>> = self.client.call(payload).await?;
+
+        Ok(r)
+    }
+}
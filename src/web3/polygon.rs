@@ -0,0 +1,33 @@
+//! Polygon PoS `bor_*` namespace, behind the `polygon` feature.
+
+use crate::client::Error;
+use crate::model::{BlockId, JsonRpcResult};
+use crate::types::Address;
+use crate::web3::Web3;
+use error_stack::Report;
+use serde_json::json;
+
+impl Web3 {
+    /// Block producer of `block`.
+    pub async fn bor_get_author(
+        &self,
+        block: impl Into<BlockId>,
+    ) -> Result<JsonRpcResult<Address>, Report<Error>> {
+        let payload = json!({ "jsonrpc": "2.0", "method": "bor_getAuthor", "params": [block.into()], "id": "1001" });
+        let r: JsonRpcResult<Address> = self.client.call(payload).await?;
+
+        Ok(r)
+    }
+
+    /// Root hash of the block range checkpointed to Ethereum.
+    pub async fn bor_get_root_hash(
+        &self,
+        from: u64,
+        to: u64,
+    ) -> Result<JsonRpcResult<String>, Report<Error>> {
+        let payload = json!({ "jsonrpc": "2.0", "method": "bor_getRootHash", "params": [from, to], "id": "1002" });
+        let r: JsonRpcResult<String> = self.client.call(payload).await?;
+
+        Ok(r)
+    }
+}
@@ -0,0 +1,74 @@
+//! OpenEthereum/Erigon `trace_*` namespace, behind the `trace-api` feature.
+
+use crate::client::Error;
+use crate::hex;
+use crate::model::{BlockId, JsonRpcResult};
+use crate::types::{H256Param, H256};
+use crate::web3::Web3;
+use error_stack::Report;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+/// One entry of a flat trace, as returned by trace_get and listed by trace_transaction.
+///
+/// `action` and `result` depend on `trace_type` (`call`, `create`, `suicide`, `reward`)
+/// and are left untyped.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TraceEntry {
+    pub action: Value,
+    pub block_hash: H256,
+    pub block_number: u64,
+    /// Set when the call reverted or ran out of gas; `result` is then null.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub result: Option<Value>,
+    pub subtraces: u64,
+    /// Path of call indices from the top-level call, empty for the top-level call itself.
+    pub trace_address: Vec<u64>,
+    /// `None` for block rewards.
+    pub transaction_hash: Option<H256>,
+    pub transaction_position: Option<u64>,
+    #[serde(rename = "type")]
+    pub trace_type: String,
+}
+
+impl Web3 {
+    pub async fn trace_transaction(
+        &self,
+        hash: impl Into<H256Param>,
+    ) -> Result<JsonRpcResult<Vec<Value>>, Report<Error>> {
+        let payload = json!({ "jsonrpc": "2.0", "method": "trace_transaction", "params": [hash.into()], "id": "601" });
+        let r: JsonRpcResult<Vec<Value>> = self.client.call(payload).await?;
+
+        Ok(r)
+    }
+
+    /// Takes a block number or tag; trace_block does not accept block hashes.
+    pub async fn trace_block(
+        &self,
+        block: impl Into<BlockId>,
+    ) -> Result<JsonRpcResult<Vec<Value>>, Report<Error>> {
+        let payload = json!({ "jsonrpc": "2.0", "method": "trace_block", "params": [block.into()], "id": "602" });
+        let r: JsonRpcResult<Vec<Value>> = self.client.call(payload).await?;
+
+        Ok(r)
+    }
+
+    /// The single trace at `trace_address` in the transaction's call tree; the result is
+    /// `None` when there is no call at that path.
+    pub async fn trace_get(
+        &self,
+        hash: impl Into<H256Param>,
+        trace_address: &[u64],
+    ) -> Result<JsonRpcResult<TraceEntry>, Report<Error>> {
+        let indices: Vec<String> = trace_address
+            .iter()
+            .map(|i| hex::quantity((*i).into()))
+            .collect();
+        let payload = json!({ "jsonrpc": "2.0", "method": "trace_get", "params": [hash.into(), indices], "id": "603" });
+        let r: JsonRpcResult<TraceEntry> = self.client.call(payload).await?;
+
+        Ok(r)
+    }
+}
@@ -0,0 +1,51 @@
+//! Geth `txpool_*` namespace, behind the `txpool-api` feature.
+
+use crate::client::Error;
+use crate::model::JsonRpcResult;
+use crate::serde_helpers::{deserialize_hex_u64, serialize_hex_u64};
+use crate::web3::Web3;
+use error_stack::Report;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+/// Result of txpool_status.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TxpoolStatus {
+    #[serde(
+        deserialize_with = "deserialize_hex_u64",
+        serialize_with = "serialize_hex_u64"
+    )]
+    pub pending: u64,
+    #[serde(
+        deserialize_with = "deserialize_hex_u64",
+        serialize_with = "serialize_hex_u64"
+    )]
+    pub queued: u64,
+}
+
+impl Web3 {
+    pub async fn txpool_status(&self) -> Result<JsonRpcResult<TxpoolStatus>, Report<Error>> {
+        let payload =
+            json!({ "jsonrpc": "2.0", "method": "txpool_status", "params": [], "id": "701" });
+        let r: JsonRpcResult<TxpoolStatus> = self.client.call(payload).await?;
+
+        Ok(r)
+    }
+
+    /// Pending and queued transactions, grouped by sender and nonce.
+    pub async fn txpool_content(&self) -> Result<JsonRpcResult<Value>, Report<Error>> {
+        let payload =
+            json!({ "jsonrpc": "2.0", "method": "txpool_content", "params": [], "id": "702" });
+        let r: JsonRpcResult<Value> = self.client.call(payload).await?;
+
+        Ok(r)
+    }
+
+    pub async fn txpool_inspect(&self) -> Result<JsonRpcResult<Value>, Report<Error>> {
+        let payload =
+            json!({ "jsonrpc": "2.0", "method": "txpool_inspect", "params": [], "id": "703" });
+        let r: JsonRpcResult<Value> = self.client.call(payload).await?;
+
+        Ok(r)
+    }
+}
@@ -0,0 +1,34 @@
+//! zkSync Era `zks_*` namespace, behind the `zksync` feature.
+
+use crate::client::Error;
+use crate::model::JsonRpcResult;
+use crate::types::U256;
+use crate::web3::Web3;
+use error_stack::Report;
+use serde_json::{json, Value};
+
+impl Web3 {
+    pub async fn zks_l1_chain_id(&self) -> Result<JsonRpcResult<U256>, Report<Error>> {
+        let payload =
+            json!({ "jsonrpc": "2.0", "method": "zks_L1ChainId", "params": [], "id": "1101" });
+        let r: JsonRpcResult<U256> = self.client.call(payload).await?;
+
+        Ok(r)
+    }
+
+    pub async fn zks_l1_batch_number(&self) -> Result<JsonRpcResult<U256>, Report<Error>> {
+        let payload =
+            json!({ "jsonrpc": "2.0", "method": "zks_L1BatchNumber", "params": [], "id": "1102" });
+        let r: JsonRpcResult<U256> = self.client.call(payload).await?;
+
+        Ok(r)
+    }
+
+    /// L1/L2 addresses of the default bridges.
+    pub async fn zks_get_bridge_contracts(&self) -> Result<JsonRpcResult<Value>, Report<Error>> {
+        let payload = json!({ "jsonrpc": "2.0", "method": "zks_getBridgeContracts", "params": [], "id": "1103" });
+        let r: JsonRpcResult<Value> = self.client.call(payload).await?;
+
+        Ok(r)
+    }
+}
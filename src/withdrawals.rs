@@ -0,0 +1,145 @@
+//! Validator withdrawals (EIP-4895) credited to a set of addresses over a block range.
+//!
+//! Withdrawals are not transactions and emit no logs, so the only way to find them is to
+//! read every block. Blocks are fetched without their transactions, in JSON-RPC batches
+//! with a few batches in flight.
+
+use crate::client::{rpc_error, Error};
+use crate::model::{JsonRpcResult, Withdrawal};
+use crate::serde_helpers::deserialize_hex_u64;
+use crate::types::{Address, U256};
+use crate::web3::Web3;
+use error_stack::Report;
+use serde::Deserialize;
+use serde_json::json;
+use std::future::Future;
+use tokio::task::JoinSet;
+
+/// Blocks per JSON-RPC batch.
+pub const BATCH_BLOCKS: u64 = 50;
+
+/// Batches in flight at once.
+pub const MAX_IN_FLIGHT: usize = 4;
+
+/// A withdrawal with the block that credited it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlockWithdrawal {
+    pub block_number: u64,
+    pub timestamp: u64,
+    pub withdrawal: Withdrawal,
+}
+
+/// Result of [`Web3::scan_withdrawals`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WithdrawalScan {
+    /// In block order, then withdrawal index order.
+    pub withdrawals: Vec<BlockWithdrawal>,
+    pub total_wei: U256,
+}
+
+/// The parts of a block the scans read; leaves the rest of the header unparsed.
+#[derive(Deserialize)]
+pub(crate) struct BlockHeader {
+    #[serde(deserialize_with = "deserialize_hex_u64")]
+    pub number: u64,
+    #[serde(deserialize_with = "deserialize_hex_u64")]
+    pub timestamp: u64,
+    #[serde(default)]
+    pub withdrawals: Option<Vec<Withdrawal>>,
+}
+
+impl Web3 {
+    /// Withdrawals to any of `addresses` in `from_block..=to_block`, or every withdrawal
+    /// when `addresses` is empty.
+    pub async fn scan_withdrawals(
+        &self,
+        from_block: u64,
+        to_block: u64,
+        addresses: &[Address],
+    ) -> Result<WithdrawalScan, Report<Error>> {
+        let headers = self
+            .in_batches(
+                (from_block..=to_block).collect(),
+                |web3, blocks| async move { web3.block_headers(&blocks).await },
+            )
+            .await?;
+
+        let mut scan = WithdrawalScan::default();
+        for header in headers {
+            for withdrawal in header.withdrawals.unwrap_or_default() {
+                if !addresses.is_empty() && !addresses.contains(&withdrawal.address) {
+                    continue;
+                }
+                scan.total_wei = scan.total_wei.saturating_add(withdrawal.amount_wei());
+                scan.withdrawals.push(BlockWithdrawal {
+                    block_number: header.number,
+                    timestamp: header.timestamp,
+                    withdrawal,
+                });
+            }
+        }
+        Ok(scan)
+    }
+
+    /// Splits `blocks` into batches of [`BATCH_BLOCKS`], runs `fetch` on up to
+    /// [`MAX_IN_FLIGHT`] of them at once and concatenates the results in block order.
+    pub(crate) async fn in_batches<T, F, Fut>(
+        &self,
+        blocks: Vec<u64>,
+        fetch: F,
+    ) -> Result<Vec<T>, Report<Error>>
+    where
+        T: Send + 'static,
+        F: Fn(Web3, Vec<u64>) -> Fut,
+        Fut: Future<Output = Result<Vec<T>, Report<Error>>> + Send + 'static,
+    {
+        let mut batches = blocks.chunks(BATCH_BLOCKS as usize).enumerate();
+        let mut tasks = JoinSet::new();
+        let mut done = Vec::new();
+        loop {
+            while tasks.len() < MAX_IN_FLIGHT {
+                let Some((i, batch)) = batches.next() else {
+                    break;
+                };
+                let batch = fetch(self.clone(), batch.to_vec());
+                tasks.spawn(async move { (i, batch.await) });
+            }
+            match tasks.join_next().await {
+                Some(joined) => {
+                    let (i, batch) = joined.map_err(|e| {
+                        Report::new(Error::IoError)
+                            .attach_printable(format!("scan task failed: {}", e))
+                    })?;
+                    done.push((i, batch?));
+                }
+                None => break,
+            }
+        }
+        done.sort_by_key(|(i, _)| *i);
+        Ok(done.into_iter().flat_map(|(_, batch)| batch).collect())
+    }
+
+    /// Headers of `blocks` in one batch, fetched without transactions.
+    pub(crate) async fn block_headers(
+        &self,
+        blocks: &[u64],
+    ) -> Result<Vec<BlockHeader>, Report<Error>> {
+        let payloads = blocks
+            .iter()
+            .map(|n| {
+                json!({ "jsonrpc": "2.0", "method": "eth_getBlockByNumber", "params": [format!("{:#x}", n), false], "id": "325" })
+            })
+            .collect();
+        let responses: Vec<JsonRpcResult<BlockHeader>> = self.client.call_batch(payloads).await?;
+        responses
+            .into_iter()
+            .zip(blocks)
+            .map(|(response, n)| match (response.result, response.error) {
+                (_, Some(error)) => Err(rpc_error(error)),
+                (Some(header), None) => Ok(header),
+                (None, None) => Err(Report::new(Error::UnexpectedResponseFormat)
+                    .attach_printable(format!("block {} not found", n))),
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,318 @@
+//! Address activity against a mock chain that can reorganize.
+
+use serde_json::{json, Value};
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use web3_rpc::activity::Activity;
+use web3_rpc::erc20::Erc20;
+use web3_rpc::types::{Address, U256};
+use web3_rpc::web3::Web3;
+use wiremock::matchers::method;
+use wiremock::{Mock, MockServer, Request, ResponseTemplate};
+
+const BLOCK: &str = include_str!("fixtures/block_post_merge.json");
+const RECEIPT: &str = include_str!("fixtures/receipt_failed.json");
+const WALLET: &str = "0x1111111111111111111111111111111111111111";
+const OTHER: &str = "0x2222222222222222222222222222222222222222";
+const TOKEN: &str = "0xdac17f958d2ee523a2206206994597c13d831ec7";
+const NFT: &str = "0xbc4ca0eda7647a8ab7c2061c2e118a18a936f13d";
+
+/// Blocks from `fork` on are on the second branch, which has only a withdrawal in block 102.
+struct Chain {
+    head: u64,
+    fork: Option<u64>,
+}
+
+impl Chain {
+    fn branch(&self, n: u64) -> u64 {
+        match self.fork {
+            Some(fork) if n >= fork => 1,
+            _ => 0,
+        }
+    }
+
+    fn hash(&self, n: u64) -> String {
+        format!("0x{:02x}{:062x}", self.branch(n), n)
+    }
+
+    fn block(&self, n: u64) -> Value {
+        let mut block: Value = serde_json::from_str(BLOCK).unwrap();
+        block["number"] = json!(format!("{:#x}", n));
+        block["hash"] = json!(self.hash(n));
+        block["parentHash"] = json!(self.hash(n - 1));
+        let template = block["transactions"][0].clone();
+        let mut txs = Vec::new();
+        let mut withdrawals = Vec::new();
+        if (n, self.branch(n)) == (101, 0) {
+            // paid out, failed deposit, and someone else's transfer
+            for (i, (from, to)) in [(WALLET, OTHER), (OTHER, WALLET), (OTHER, OTHER)]
+                .iter()
+                .enumerate()
+            {
+                let mut tx = template.clone();
+                tx["hash"] = json!(format!("0x{:062x}{:02x}", n, i));
+                tx["from"] = json!(from);
+                tx["to"] = json!(to);
+                tx["blockHash"] = block["hash"].clone();
+                tx["blockNumber"] = block["number"].clone();
+                txs.push(tx);
+            }
+            withdrawals.push(withdrawal(n, WALLET));
+        }
+        if n == 102 {
+            withdrawals.push(withdrawal(n, OTHER));
+            if self.branch(n) == 1 {
+                withdrawals.push(withdrawal(n, WALLET));
+            }
+        }
+        block["transactions"] = json!(txs);
+        block["withdrawals"] = json!(withdrawals);
+        block
+    }
+
+    fn logs(&self) -> Vec<Value> {
+        if self.branch(102) == 1 {
+            return Vec::new();
+        }
+        let topic = |address: &str| format!("0x000000000000000000000000{}", &address[2..]);
+        let log = |index: u64, token: &str, topics: Vec<String>, data: &str| {
+            json!({
+                "address": token,
+                "blockHash": self.hash(102),
+                "blockNumber": "0x66",
+                "logIndex": format!("{:#x}", index),
+                "transactionHash": format!("0x{:064x}", 0xabc + index),
+                "transactionIndex": "0x0",
+                "data": data,
+                "topics": topics,
+                "removed": false
+            })
+        };
+        let transfer = format!("{:?}", Erc20::transfer_topic());
+        vec![
+            log(
+                0,
+                TOKEN,
+                vec![transfer.clone(), topic(OTHER), topic(WALLET)],
+                "0x0000000000000000000000000000000000000000000000000000000005f5e100",
+            ),
+            log(
+                1,
+                NFT,
+                vec![
+                    transfer.clone(),
+                    topic(WALLET),
+                    topic(OTHER),
+                    format!("0x{:064x}", 7),
+                ],
+                "0x",
+            ),
+            log(2, TOKEN, vec![transfer, topic(OTHER), topic(OTHER)], "0x"),
+        ]
+    }
+
+    fn answer(&self, request: &Value) -> Value {
+        let params = &request["params"];
+        let result = match request["method"].as_str().unwrap() {
+            "eth_blockNumber" => json!(format!("{:#x}", self.head)),
+            "eth_getBlockByNumber" => {
+                let n = u64::from_str_radix(&params[0].as_str().unwrap()[2..], 16).unwrap();
+                self.block(n)
+            }
+            "eth_getLogs" => {
+                let filter = &params[0];
+                let topics = filter["topics"].as_array().unwrap();
+                let logs: Vec<Value> = self
+                    .logs()
+                    .into_iter()
+                    .filter(|log| {
+                        topics.iter().enumerate().all(|(i, wanted)| {
+                            wanted.is_null()
+                                || wanted.as_array().unwrap().contains(&log["topics"][i])
+                        })
+                    })
+                    .collect();
+                json!(logs)
+            }
+            "eth_getTransactionReceipt" => {
+                let hash = params[0].as_str().unwrap();
+                let mut receipt: Value = serde_json::from_str(RECEIPT).unwrap();
+                receipt["transactionHash"] = json!(hash);
+                receipt["blockHash"] = json!(self.hash(101));
+                receipt["blockNumber"] = json!("0x65");
+                receipt["status"] = json!(if hash.ends_with("01") { "0x0" } else { "0x1" });
+                receipt
+            }
+            other => panic!("unexpected method {}", other),
+        };
+        json!({ "jsonrpc": "2.0", "id": request["id"], "result": result })
+    }
+}
+
+fn withdrawal(n: u64, address: &str) -> Value {
+    json!({ "index": format!("{:#x}", n), "validatorIndex": "0x1", "address": address, "amount": "0x3b9aca00" })
+}
+
+async fn serving(chain: Arc<Mutex<Chain>>) -> MockServer {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(move |request: &Request| {
+            let request: Value = serde_json::from_slice(&request.body).unwrap();
+            let chain = chain.lock().unwrap();
+            let response = match request.as_array() {
+                Some(batch) => json!(batch.iter().map(|r| chain.answer(r)).collect::<Vec<_>>()),
+                None => chain.answer(&request),
+            };
+            ResponseTemplate::new(200).set_body_json(response)
+        })
+        .mount(&server)
+        .await;
+    server
+}
+
+fn wallet() -> HashSet<Address> {
+    HashSet::from([WALLET.parse().unwrap()])
+}
+
+#[tokio::test]
+async fn transfers_and_withdrawals_in_block_order() {
+    let chain = Arc::new(Mutex::new(Chain {
+        head: 102,
+        fork: None,
+    }));
+    let server = serving(chain).await;
+    let rpc = Web3::new(server.uri());
+    let mut activity = rpc
+        .watch_address_activity(wallet(), Duration::from_millis(10))
+        .from_block(100);
+
+    let mut events = Vec::new();
+    for _ in 0..4 {
+        events.push(activity.next().await.unwrap());
+    }
+    assert_eq!(activity.next_block(), Some(103));
+
+    match &events[0] {
+        Activity::Native {
+            block,
+            from,
+            to,
+            value,
+            ..
+        } => {
+            assert_eq!(block.number, 101);
+            assert_eq!(*from, WALLET.parse().unwrap());
+            assert_eq!(*to, Some(OTHER.parse().unwrap()));
+            assert_eq!(*value, U256::from(2_000_000_000_000_000_000u128));
+        }
+        other => panic!("unexpected event {:?}", other),
+    }
+    match &events[1] {
+        Activity::Withdrawal { block, withdrawal } => {
+            assert_eq!(block.number, 101);
+            assert_eq!(withdrawal.address, WALLET.parse().unwrap());
+        }
+        other => panic!("unexpected event {:?}", other),
+    }
+    match &events[2] {
+        Activity::Erc20 {
+            block,
+            token,
+            transfer,
+            log_index,
+            ..
+        } => {
+            assert_eq!((block.number, *log_index), (102, 0));
+            assert_eq!(*token, TOKEN.parse().unwrap());
+            assert_eq!(transfer.to, WALLET.parse().unwrap());
+            assert_eq!(transfer.value, U256::from(100_000_000u64));
+        }
+        other => panic!("unexpected event {:?}", other),
+    }
+    match &events[3] {
+        Activity::Erc721 {
+            token,
+            transfer,
+            log_index,
+            ..
+        } => {
+            assert_eq!(*log_index, 1);
+            assert_eq!(*token, NFT.parse().unwrap());
+            assert_eq!(transfer.from, WALLET.parse().unwrap());
+            assert_eq!(transfer.token_id, U256::from(7u64));
+        }
+        other => panic!("unexpected event {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn reorged_blocks_are_retracted() {
+    let chain = Arc::new(Mutex::new(Chain {
+        head: 102,
+        fork: None,
+    }));
+    let server = serving(chain.clone()).await;
+    let rpc = Web3::new(server.uri());
+    let mut activity = rpc
+        .watch_address_activity(wallet(), Duration::from_millis(10))
+        .from_block(100);
+    for _ in 0..4 {
+        activity.next().await.unwrap();
+    }
+
+    *chain.lock().unwrap() = Chain {
+        head: 103,
+        fork: Some(101),
+    };
+    let mut retracted = Vec::new();
+    for _ in 0..2 {
+        match activity.next().await.unwrap() {
+            Activity::Retracted { block } => retracted.push((block.number, block.hash)),
+            other => panic!("unexpected event {:?}", other),
+        }
+    }
+    let old = Chain {
+        head: 102,
+        fork: None,
+    };
+    assert_eq!(
+        retracted,
+        [
+            (102, old.hash(102).parse().unwrap()),
+            (101, old.hash(101).parse().unwrap())
+        ]
+    );
+
+    // the new branch from the fork on
+    match activity.next().await.unwrap() {
+        Activity::Withdrawal { block, .. } => {
+            assert_eq!(block.number, 102);
+            assert_eq!(block.hash, chain.lock().unwrap().hash(102).parse().unwrap());
+        }
+        other => panic!("unexpected event {:?}", other),
+    }
+    assert_eq!(activity.next_block(), Some(104));
+}
+
+#[tokio::test]
+async fn starts_at_the_head() {
+    let chain = Arc::new(Mutex::new(Chain {
+        head: 100,
+        fork: None,
+    }));
+    let server = serving(chain.clone()).await;
+    let rpc = Web3::new(server.uri());
+
+    let next = tokio::spawn(async move {
+        let mut activity = rpc.watch_address_activity(wallet(), Duration::from_millis(10));
+        let event = activity.next().await.unwrap();
+        (event, activity.next_block())
+    });
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    chain.lock().unwrap().head = 101;
+    let (event, next_block) = next.await.unwrap();
+    assert!(matches!(event, Activity::Native { .. }));
+    assert_eq!(event.block().number, 101);
+    assert_eq!(next_block, Some(102));
+}
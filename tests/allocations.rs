@@ -0,0 +1,141 @@
+//! Allocation accounting for response parsing. Kept in its own test binary so the counting
+//! allocator only sees this test.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::io::Read;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use web3_rpc::client::Client;
+use web3_rpc::model::{JsonRpcResult, Log};
+use web3_rpc::streaming::read_items;
+
+struct Counting;
+
+static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+static LIVE: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+/// The counters are global, tests taking them in turn keeps them apart.
+static MEASURING: Mutex<()> = Mutex::new(());
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATED.fetch_add(layout.size(), Ordering::Relaxed);
+        let live = LIVE.fetch_add(layout.size(), Ordering::Relaxed) + layout.size();
+        PEAK.fetch_max(live, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        LIVE.fetch_sub(layout.size(), Ordering::Relaxed);
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+fn allocated_by<T>(f: impl FnOnce() -> T) -> usize {
+    let before = ALLOCATED.load(Ordering::Relaxed);
+    let out = f();
+    let after = ALLOCATED.load(Ordering::Relaxed);
+    drop(out);
+    after - before
+}
+
+/// Most memory held at once while `f` runs, above what was held before.
+fn peak_during(f: impl FnOnce()) -> usize {
+    let before = LIVE.load(Ordering::Relaxed);
+    PEAK.store(before, Ordering::Relaxed);
+    f();
+    PEAK.load(Ordering::Relaxed) - before
+}
+
+const LOGS: usize = 5000;
+
+const LOG: &str = r#"{"address":"0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48","blockHash":"0x8e1e6a4d3a3b3e2d7c0f9a6b5c4d3e2f1a0b9c8d7e6f5a4b3c2d1e0f9a8b7c6d","blockNumber":"0x1036640","data":"0x00000000000000000000000000000000000000000000000000000000000f4240","logIndex":"0x0","removed":false,"topics":["0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef"],"transactionHash":"0x5c1d5e1a8f8d9f0f6c2e6a4a4d2f1b7c8b3e0f4f9a3c2d1e6b5a4f3e2d1c0b9a","transactionIndex":"0x1"}"#;
+
+fn large_logs_response() -> Vec<u8> {
+    let logs = vec![LOG; LOGS].join(",");
+    format!(r#"{{"jsonrpc":"2.0","id":"1","result":[{}]}}"#, logs).into_bytes()
+}
+
+#[test]
+fn parsing_from_bytes_skips_the_string_copies() {
+    let _turn = MEASURING.lock().unwrap_or_else(|e| e.into_inner());
+    let body = large_logs_response();
+    assert!(body.len() > 2_000_000);
+
+    let from_bytes = allocated_by(|| {
+        let r: JsonRpcResult<Vec<Log>> = Client::parse_response(&body).unwrap();
+        r
+    });
+    // what the text() based pipeline did: decode into a String, copy it, parse from &str
+    let through_string = allocated_by(|| {
+        let text = String::from_utf8(body.clone())
+            .unwrap()
+            .as_str()
+            .to_string();
+        let r: JsonRpcResult<Vec<Log>> = serde_json::from_str(&text).unwrap();
+        r
+    });
+    assert!(
+        through_string >= from_bytes + body.len(),
+        "bytes: {} string: {}",
+        from_bytes,
+        through_string
+    );
+}
+
+/// The same response as [`large_logs_response`], produced as it is read like a body coming
+/// off the connection.
+struct LazyLogsResponse {
+    next: usize,
+    part: std::io::Cursor<Vec<u8>>,
+}
+
+impl Read for LazyLogsResponse {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        loop {
+            let n = self.part.read(buf)?;
+            if n > 0 || self.next > LOGS {
+                return Ok(n);
+            }
+            let part = match self.next {
+                0 => format!(r#"{{"jsonrpc":"2.0","id":"1","result":[{}"#, LOG),
+                n if n < LOGS => format!(",{}", LOG),
+                _ => "]}".to_string(),
+            };
+            self.part = std::io::Cursor::new(part.into_bytes());
+            self.next += 1;
+        }
+    }
+}
+
+#[test]
+fn streaming_holds_one_log_at_a_time() {
+    let _turn = MEASURING.lock().unwrap_or_else(|e| e.into_inner());
+
+    let buffered = peak_during(|| {
+        let body = large_logs_response();
+        let r: JsonRpcResult<Vec<Log>> = Client::parse_response(&body).unwrap();
+        assert_eq!(r.result.map(|logs| logs.len()), Some(LOGS));
+    });
+    let streamed = peak_during(|| {
+        let reader = LazyLogsResponse {
+            next: 0,
+            part: std::io::Cursor::new(Vec::new()),
+        };
+        let mut blocks = 0;
+        let count = read_items(reader, |log: Log| {
+            blocks += log.block_number.is_some() as usize
+        })
+        .unwrap();
+        assert_eq!((count, blocks), (LOGS, LOGS));
+    });
+    assert!(
+        streamed * 20 < buffered,
+        "buffered: {} streamed: {}",
+        buffered,
+        streamed
+    );
+}
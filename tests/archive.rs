@@ -0,0 +1,74 @@
+//! State depth detection against mock nodes that keep some of the state.
+
+mod common;
+
+use common::fixture;
+use serde_json::{json, Value};
+use web3_rpc::archive::StateDepth;
+use web3_rpc::web3::Web3;
+use wiremock::{Mock, MockServer, Request, ResponseTemplate};
+
+/// A node at block 1000 that has the state from `earliest`, answering for older blocks with
+/// the error in `fixture`.
+async fn node(earliest: u64, fixture_name: &'static str) -> MockServer {
+    let server = MockServer::start().await;
+    Mock::given(wiremock::matchers::method("POST"))
+        .respond_with(move |request: &Request| {
+            let body: Value = serde_json::from_slice(&request.body).unwrap();
+            if body["method"] == "eth_blockNumber" {
+                return ResponseTemplate::new(200)
+                    .set_body_json(json!({ "jsonrpc": "2.0", "id": "327", "result": "0x3e8" }));
+            }
+            let block = body["params"][1].as_str().unwrap();
+            let block = u64::from_str_radix(block.trim_start_matches("0x"), 16).unwrap();
+            if block < earliest {
+                ResponseTemplate::new(200).set_body_json(fixture(fixture_name))
+            } else {
+                ResponseTemplate::new(200)
+                    .set_body_json(json!({ "jsonrpc": "2.0", "id": "311", "result": "0x0" }))
+            }
+        })
+        .mount(&server)
+        .await;
+    server
+}
+
+#[tokio::test]
+async fn pruned_nodes_of_every_client() {
+    for name in [
+        "state_missing_geth",
+        "state_missing_geth_path",
+        "state_missing_erigon",
+        "state_missing_reth",
+        "state_missing_nethermind",
+    ] {
+        let server = node(873, name).await;
+        let rpc = Web3::new(server.uri());
+        assert!(!rpc.is_archive().await.unwrap(), "{}", name);
+        assert_eq!(
+            rpc.detect_state_depth().await.unwrap(),
+            StateDepth::Pruned {
+                earliest_available_block: 873
+            },
+            "{}",
+            name
+        );
+        let requests = server.received_requests().await.unwrap().len();
+        assert!(requests <= 15, "{}: {} requests", name, requests);
+    }
+}
+
+#[tokio::test]
+async fn archive_and_unknown() {
+    let server = node(0, "state_missing_geth").await;
+    let rpc = Web3::new(server.uri());
+    assert!(rpc.is_archive().await.unwrap());
+    assert_eq!(rpc.detect_state_depth().await.unwrap(), StateDepth::Archive);
+    // one eth_getBalance at block 1 for each
+    assert_eq!(server.received_requests().await.unwrap().len(), 2);
+
+    let server = node(873, "revert_geth").await;
+    let rpc = Web3::new(server.uri());
+    assert!(!rpc.is_archive().await.unwrap());
+    assert_eq!(rpc.detect_state_depth().await.unwrap(), StateDepth::Unknown);
+}
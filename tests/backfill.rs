@@ -0,0 +1,153 @@
+//! Ordered block backfill against a mock server that answers out of order.
+
+use serde_json::{json, Value};
+use std::time::Duration;
+use web3_rpc::backfill::Progress;
+use web3_rpc::client::{Error, RetryPolicy};
+use web3_rpc::web3::Web3;
+use wiremock::matchers::{body_json, method};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+const BLOCK: &str = include_str!("fixtures/block_post_merge.json");
+const RECEIPT: &str = include_str!("fixtures/receipt_failed.json");
+const FIRST: u64 = 100;
+const LAST: u64 = 109;
+
+fn block_request(n: u64) -> Value {
+    json!({ "jsonrpc": "2.0", "method": "eth_getBlockByNumber", "params": [format!("{:#x}", n), true], "id": "325" })
+}
+
+/// The fixture block renumbered to `n`; its one transaction gets a hash derived from `n`.
+fn block(n: u64) -> Value {
+    let mut block: Value = serde_json::from_str(BLOCK).unwrap();
+    block["number"] = json!(format!("{:#x}", n));
+    block["transactions"][0]["hash"] = json!(tx_hash(n));
+    block
+}
+
+fn tx_hash(n: u64) -> String {
+    format!("0x{:064x}", n)
+}
+
+/// Answers every block in the range, later blocks first.
+async fn mount_blocks(server: &MockServer, blocks: impl Iterator<Item = u64>) {
+    for n in blocks {
+        let delay = Duration::from_millis((LAST - n) * 20);
+        Mock::given(method("POST"))
+            .and(body_json(block_request(n)))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(json!({ "jsonrpc": "2.0", "id": "325", "result": block(n) }))
+                    .set_delay(delay),
+            )
+            .expect(1)
+            .mount(server)
+            .await;
+    }
+}
+
+#[tokio::test]
+async fn blocks_come_out_in_order() {
+    let server = MockServer::start().await;
+    mount_blocks(&server, FIRST..=LAST).await;
+
+    let rpc = Web3::new(server.uri());
+    let mut backfill = rpc.backfill_blocks(FIRST..=LAST, 4, false);
+    assert_eq!(
+        backfill.progress(),
+        Progress {
+            done: 0,
+            remaining: 10,
+            next_block: Some(FIRST)
+        }
+    );
+    let mut numbers = Vec::new();
+    while let Some(item) = backfill.next().await {
+        let (block, receipts) = item.unwrap();
+        assert!(receipts.is_none());
+        numbers.push(block.number.unwrap());
+        assert_eq!(backfill.progress().done, numbers.len() as u64);
+    }
+    assert_eq!(numbers, (FIRST..=LAST).collect::<Vec<_>>());
+    assert_eq!(
+        backfill.progress(),
+        Progress {
+            done: 10,
+            remaining: 0,
+            next_block: None
+        }
+    );
+    assert!(backfill.next().await.is_none());
+}
+
+#[tokio::test]
+async fn resumes_with_receipts_and_retries() {
+    let server = MockServer::start().await;
+    // the first attempt at block 106 hits an overloaded node
+    Mock::given(method("POST"))
+        .and(body_json(block_request(106)))
+        .respond_with(ResponseTemplate::new(503))
+        .up_to_n_times(1)
+        .expect(1)
+        .mount(&server)
+        .await;
+    mount_blocks(&server, 105..=LAST).await;
+    for n in 105..=LAST {
+        let mut receipt: Value = serde_json::from_str(RECEIPT).unwrap();
+        receipt["transactionHash"] = json!(tx_hash(n));
+        Mock::given(method("POST"))
+            .and(body_json(json!([
+                { "jsonrpc": "2.0", "method": "eth_getTransactionReceipt", "params": [tx_hash(n)], "id": "0" }
+            ])))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(json!([{ "jsonrpc": "2.0", "id": "0", "result": receipt }])),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+    }
+
+    let rpc = Web3::new(server.uri());
+    let mut backfill = rpc
+        .backfill_blocks(FIRST..=LAST, 3, true)
+        .resume_from(105)
+        .retry(RetryPolicy::new(2).initial_backoff(Duration::from_millis(1)));
+    assert_eq!(backfill.progress().done, 5);
+    let mut numbers = Vec::new();
+    while let Some(item) = backfill.next().await {
+        let (block, receipts) = item.unwrap();
+        let receipts = receipts.unwrap();
+        assert_eq!(receipts.len(), 1);
+        assert_eq!(receipts[0].transaction_hash, block.transactions.hashes()[0]);
+        numbers.push(block.number.unwrap());
+    }
+    assert_eq!(numbers, (105..=LAST).collect::<Vec<_>>());
+}
+
+#[tokio::test]
+async fn stops_at_the_first_failure() {
+    let server = MockServer::start().await;
+    mount_blocks(&server, FIRST..=101).await;
+    Mock::given(method("POST"))
+        .and(body_json(block_request(102)))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_json(json!({ "jsonrpc": "2.0", "id": "325", "result": null })),
+        )
+        .mount(&server)
+        .await;
+
+    let rpc = Web3::new(server.uri());
+    let mut backfill = rpc.backfill_blocks(FIRST..=102, 2, false);
+    assert_eq!(backfill.next().await.unwrap().unwrap().0.number, Some(100));
+    assert_eq!(backfill.next().await.unwrap().unwrap().0.number, Some(101));
+    let report = backfill.next().await.unwrap().unwrap_err();
+    assert!(matches!(
+        report.current_context(),
+        Error::UnexpectedResponseFormat
+    ));
+    assert!(backfill.next().await.is_none());
+    // where a new backfill picks up
+    assert_eq!(backfill.progress().next_block, Some(102));
+}
@@ -0,0 +1,270 @@
+//! Concurrent calls going out as one JSON-RPC batch.
+
+use serde_json::{json, Value};
+use std::time::Duration;
+use tokio::task::JoinSet;
+use tokio::time::Instant;
+use web3_rpc::batching::MicroBatchPolicy;
+use web3_rpc::client::{Error, RetryPolicy};
+use web3_rpc::web3::Web3;
+use wiremock::matchers::method;
+use wiremock::{Mock, MockServer, Request, ResponseTemplate};
+
+const CALLERS: usize = 20;
+
+/// The balance of `0x00..0i` is `i`, except for address 7, which the node fails.
+fn answer(call: &Value) -> Value {
+    let id = call["id"].clone();
+    match call["method"].as_str().unwrap() {
+        "eth_getBalance" => {
+            let address = call["params"][0].as_str().unwrap();
+            let i = u64::from_str_radix(&address[2..], 16).unwrap();
+            if i == 7 {
+                json!({ "jsonrpc": "2.0", "id": id, "error": { "code": -32000, "message": "missing trie node" } })
+            } else {
+                json!({ "jsonrpc": "2.0", "id": id, "result": format!("{:#x}", i) })
+            }
+        }
+        "eth_sendRawTransaction" => {
+            json!({ "jsonrpc": "2.0", "id": id, "result": format!("0x{:064x}", 1) })
+        }
+        _ => json!({ "jsonrpc": "2.0", "id": id, "result": "0x1" }),
+    }
+}
+
+/// Answers single calls and batches, expecting `requests` of them.
+async fn serving(requests: u64) -> MockServer {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(|request: &Request| {
+            let body: Value = serde_json::from_slice(&request.body).unwrap();
+            let reply = match body.as_array() {
+                Some(batch) => Value::Array(batch.iter().map(answer).collect()),
+                None => answer(&body),
+            };
+            ResponseTemplate::new(200).set_body_json(reply)
+        })
+        .expect(requests)
+        .mount(&server)
+        .await;
+    server
+}
+
+fn batching(server: &MockServer, policy: MicroBatchPolicy) -> Web3 {
+    Web3::builder()
+        .url(server.uri())
+        .micro_batch(policy)
+        .build()
+        .unwrap()
+}
+
+fn address(i: usize) -> String {
+    format!("0x{:040x}", i)
+}
+
+async fn bodies(server: &MockServer) -> Vec<Value> {
+    let requests = server.received_requests().await.unwrap();
+    requests
+        .iter()
+        .map(|r| serde_json::from_slice(&r.body).unwrap())
+        .collect()
+}
+
+#[tokio::test]
+async fn concurrent_calls_share_one_request() {
+    let server = serving(1).await;
+    let rpc = batching(&server, MicroBatchPolicy::new(Duration::from_millis(5)));
+
+    let mut tasks = JoinSet::new();
+    for i in 0..CALLERS {
+        let rpc = rpc.clone();
+        tasks.spawn(async move { (i, rpc.eth_get_balance(address(i).as_str(), None).await) });
+    }
+    while let Some(result) = tasks.join_next().await {
+        match result.unwrap() {
+            // the one failure stays with its caller
+            (7, r) => {
+                let report = r.unwrap_err();
+                assert!(report.current_context().is_missing_state());
+            }
+            (i, r) => assert_eq!(r.unwrap().result.unwrap(), format!("{:#x}", i)),
+        }
+    }
+    let bodies = bodies(&server).await;
+    assert_eq!(bodies[0].as_array().unwrap().len(), CALLERS);
+}
+
+#[tokio::test]
+async fn lone_calls_go_out_at_once() {
+    let server = serving(2).await;
+    let rpc = batching(&server, MicroBatchPolicy::new(Duration::from_millis(500)));
+
+    for _ in 0..2 {
+        let start = Instant::now();
+        rpc.eth_block_number().await.unwrap();
+        assert!(start.elapsed() < Duration::from_millis(250));
+    }
+    // as they would have been without batching
+    for body in bodies(&server).await {
+        assert_eq!(body["method"], "eth_blockNumber");
+        assert_eq!(body["id"], "327");
+    }
+}
+
+#[tokio::test]
+async fn full_batches_do_not_wait() {
+    let server = serving(4).await;
+    let rpc = batching(
+        &server,
+        MicroBatchPolicy::new(Duration::from_secs(5)).max_batch(5),
+    );
+
+    let start = Instant::now();
+    let mut tasks = JoinSet::new();
+    for i in 0..CALLERS {
+        let rpc = rpc.clone();
+        tasks.spawn(async move { rpc.eth_get_balance(address(i + 10).as_str(), None).await });
+    }
+    while let Some(result) = tasks.join_next().await {
+        result.unwrap().unwrap();
+    }
+    assert!(start.elapsed() < Duration::from_secs(1));
+    assert!(bodies(&server)
+        .await
+        .iter()
+        .all(|body| body.as_array().unwrap().len() == 5));
+}
+
+#[tokio::test]
+async fn excluded_methods_go_alone() {
+    // three sends on their own, the reads together
+    let server = serving(4).await;
+    let rpc = batching(
+        &server,
+        MicroBatchPolicy::new(Duration::from_millis(5)).exclude_sends(),
+    );
+
+    let mut tasks = JoinSet::new();
+    for i in 0..3 {
+        let sender = rpc.clone();
+        tasks.spawn(async move { sender.eth_send_raw_transaction("0x02f8").await.map(|_| ()) });
+        let reader = rpc.clone();
+        tasks.spawn(async move {
+            reader
+                .eth_get_balance(address(i).as_str(), None)
+                .await
+                .map(|_| ())
+        });
+    }
+    while let Some(result) = tasks.join_next().await {
+        result.unwrap().unwrap();
+    }
+    for body in bodies(&server).await {
+        match body.as_array() {
+            Some(batch) => assert!(batch.iter().all(|call| call["method"] == "eth_getBalance")),
+            None => assert_eq!(body["method"], "eth_sendRawTransaction"),
+        }
+    }
+}
+
+#[tokio::test]
+async fn refused_batches_are_sent_call_by_call() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(|request: &Request| {
+            let body: Value = serde_json::from_slice(&request.body).unwrap();
+            let reply = match body.is_array() {
+                true => json!({ "jsonrpc": "2.0", "id": null, "error": { "code": -32600, "message": "batch requests are not supported" } }),
+                false => answer(&body),
+            };
+            ResponseTemplate::new(200).set_body_json(reply)
+        })
+        .expect(1 + 5)
+        .mount(&server)
+        .await;
+    let rpc = batching(&server, MicroBatchPolicy::default());
+
+    let mut tasks = JoinSet::new();
+    for i in 0..5 {
+        let rpc = rpc.clone();
+        tasks.spawn(async move { (i, rpc.eth_get_balance(address(i).as_str(), None).await) });
+    }
+    while let Some(result) = tasks.join_next().await {
+        let (i, r) = result.unwrap();
+        assert_eq!(r.unwrap().result.unwrap(), format!("{:#x}", i));
+    }
+}
+
+#[tokio::test]
+async fn failed_batches_fail_every_call() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(502))
+        .expect(1)
+        .mount(&server)
+        .await;
+    let rpc = batching(&server, MicroBatchPolicy::default());
+
+    let mut tasks = JoinSet::new();
+    for i in 0..5 {
+        let rpc = rpc.clone();
+        tasks.spawn(async move { rpc.eth_get_balance(address(i).as_str(), None).await });
+    }
+    while let Some(result) = tasks.join_next().await {
+        let report = result.unwrap().unwrap_err();
+        assert!(
+            matches!(report.current_context(), Error::HttpError(status) if status.as_u16() == 502)
+        );
+    }
+}
+
+#[tokio::test]
+async fn rate_limited_sends_in_a_batch_are_not_resent() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(|request: &Request| {
+            let body: Value = serde_json::from_slice(&request.body).unwrap();
+            let reply = |call: &Value| match call["method"].as_str().unwrap() {
+                "eth_sendTransaction" => json!({ "jsonrpc": "2.0", "id": call["id"], "error": { "code": -32005, "message": "project ID request rate exceeded" } }),
+                _ => answer(call),
+            };
+            let reply = match body.as_array() {
+                Some(batch) => Value::Array(batch.iter().map(reply).collect()),
+                None => reply(&body),
+            };
+            ResponseTemplate::new(200).set_body_json(reply)
+        })
+        .mount(&server)
+        .await;
+    let rpc = Web3::builder()
+        .url(server.uri())
+        .retry(RetryPolicy::new(2).initial_backoff(Duration::from_millis(1)))
+        .micro_batch(MicroBatchPolicy::new(Duration::from_millis(5)))
+        .build()
+        .unwrap();
+
+    let sender = rpc.clone();
+    let send =
+        tokio::spawn(async move { sender.call_raw("eth_sendTransaction", json!([{}])).await });
+    let reader = rpc.clone();
+    let read = tokio::spawn(async move { reader.eth_get_balance(address(1).as_str(), None).await });
+    let report = send.await.unwrap().unwrap_err();
+    assert!(matches!(
+        report.current_context(),
+        Error::CapacityExceeded { .. }
+    ));
+    read.await.unwrap().unwrap();
+
+    let bodies = bodies(&server).await;
+    assert!(bodies[0].is_array());
+    let sends = bodies
+        .iter()
+        .flat_map(|body| {
+            body.as_array()
+                .cloned()
+                .unwrap_or_else(|| vec![body.clone()])
+        })
+        .filter(|call| call["method"] == "eth_sendTransaction")
+        .count();
+    assert_eq!(sends, 1);
+}
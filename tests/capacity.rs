@@ -0,0 +1,132 @@
+//! Rate limit and capacity responses from different providers, normalized.
+
+mod common;
+
+use common::fixture;
+use std::time::Duration;
+use web3_rpc::client::{Error, RetryPolicy};
+use web3_rpc::model::RpcErrorObject;
+use web3_rpc::web3::Web3;
+use wiremock::matchers::method;
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+fn block_number() -> ResponseTemplate {
+    ResponseTemplate::new(200).set_body_string(r#"{"jsonrpc":"2.0","id":"327","result":"0x10"}"#)
+}
+
+async fn capacity_error(response: ResponseTemplate) -> (Option<Duration>, &'static str) {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(response)
+        .mount(&server)
+        .await;
+    let err = Web3::new(server.uri())
+        .eth_block_number()
+        .await
+        .unwrap_err();
+    match err.current_context() {
+        Error::CapacityExceeded {
+            retry_after,
+            provider_hint,
+        } => (*retry_after, *provider_hint),
+        e => panic!("unexpected error {:?}", e),
+    }
+}
+
+#[tokio::test]
+async fn every_provider_shape() {
+    let infura = ResponseTemplate::new(200).set_body_json(fixture("capacity_infura"));
+    assert_eq!(
+        capacity_error(infura).await,
+        (Some(Duration::from_secs(30)), "infura")
+    );
+
+    let alchemy = ResponseTemplate::new(429).set_body_json(fixture("capacity_alchemy"));
+    assert_eq!(capacity_error(alchemy).await, (None, "alchemy"));
+    let alchemy = ResponseTemplate::new(200).set_body_json(fixture("capacity_alchemy"));
+    assert_eq!(capacity_error(alchemy).await, (None, "alchemy"));
+
+    let public = ResponseTemplate::new(200).set_body_json(fixture("capacity_public"));
+    assert_eq!(capacity_error(public).await, (None, "public node"));
+
+    let unavailable = ResponseTemplate::new(503)
+        .insert_header("retry-after", "2")
+        .set_body_string("service unavailable");
+    assert_eq!(
+        capacity_error(unavailable).await,
+        (Some(Duration::from_secs(2)), "http 503")
+    );
+}
+
+#[tokio::test]
+async fn error_object_stays_attached() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(fixture("capacity_infura")))
+        .mount(&server)
+        .await;
+    let err = Web3::new(server.uri())
+        .eth_block_number()
+        .await
+        .unwrap_err();
+    assert_eq!(err.downcast_ref::<RpcErrorObject>().unwrap().code, -32005);
+}
+
+#[tokio::test]
+async fn other_limit_errors_are_not_capacity() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(
+            r#"{"jsonrpc":"2.0","id":"330","error":{"code":-32005,"message":"query returned more than 10000 results"}}"#,
+        ))
+        .mount(&server)
+        .await;
+    let err = Web3::new(server.uri())
+        .eth_block_number()
+        .await
+        .unwrap_err();
+    assert_eq!(err.current_context().code(), Some(-32005));
+}
+
+#[tokio::test]
+async fn retries_wait_as_asked() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(429).insert_header("retry-after", "1"))
+        .up_to_n_times(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .respond_with(block_number())
+        .mount(&server)
+        .await;
+    let rpc = Web3::builder()
+        .url(server.uri())
+        .retry(RetryPolicy::new(2).initial_backoff(Duration::from_millis(1)))
+        .build()
+        .unwrap();
+    let start = std::time::Instant::now();
+    rpc.eth_block_number().await.unwrap();
+    assert!(start.elapsed() >= Duration::from_secs(1));
+}
+
+#[tokio::test]
+async fn waits_past_the_policy_are_left_to_the_caller() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(fixture("capacity_infura")))
+        .expect(1)
+        .mount(&server)
+        .await;
+    let rpc = Web3::builder()
+        .url(server.uri())
+        .retry(RetryPolicy::new(3))
+        .build()
+        .unwrap();
+    let err = rpc.eth_block_number().await.unwrap_err();
+    assert!(
+        matches!(err.current_context(), Error::CapacityExceeded { retry_after: Some(after), .. } if *after == Duration::from_secs(30)),
+        "{:?}",
+        err
+    );
+}
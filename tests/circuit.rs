@@ -0,0 +1,174 @@
+//! Circuit breaking against failing mock endpoints.
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use web3_rpc::circuit::{CircuitBreakerPolicy, CircuitObserver, CircuitState};
+use web3_rpc::client::{Error, RetryPolicy};
+use web3_rpc::endpoints::SelectionStrategy;
+use web3_rpc::web3::Web3;
+use wiremock::matchers::method;
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+#[derive(Clone, Default)]
+struct Transitions(Arc<Mutex<Vec<(String, CircuitState, CircuitState)>>>);
+
+impl Transitions {
+    fn states(&self) -> Vec<(CircuitState, CircuitState)> {
+        self.0
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(_, from, to)| (*from, *to))
+            .collect()
+    }
+
+    fn endpoints(&self) -> Vec<String> {
+        self.0
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(endpoint, _, _)| endpoint.clone())
+            .collect()
+    }
+}
+
+impl CircuitObserver for Transitions {
+    fn transition(&self, endpoint: &str, from: CircuitState, to: CircuitState) {
+        self.0
+            .lock()
+            .unwrap()
+            .push((endpoint.to_string(), from, to));
+    }
+}
+
+async fn reply(server: &MockServer, response: ResponseTemplate) {
+    server.reset().await;
+    Mock::given(method("POST"))
+        .respond_with(response)
+        .mount(server)
+        .await;
+}
+
+#[tokio::test]
+async fn opens_fails_fast_and_probes() {
+    let server = MockServer::start().await;
+    reply(&server, ResponseTemplate::new(502)).await;
+    let transitions = Transitions::default();
+    let cool_down = Duration::from_millis(100);
+    let rpc = Web3::builder()
+        .url(server.uri())
+        .retry(RetryPolicy::new(5).initial_backoff(Duration::from_millis(1)))
+        .circuit_breaker(CircuitBreakerPolicy::new(3).cool_down(cool_down))
+        .circuit_observer(Box::new(transitions.clone()))
+        .build()
+        .unwrap();
+
+    // the retries stop once the circuit opens
+    let err = rpc.eth_block_number().await.unwrap_err();
+    assert!(matches!(err.current_context(), Error::CircuitOpen { .. }));
+    assert_eq!(server.received_requests().await.unwrap().len(), 3);
+    // clones share the state
+    let err = rpc.clone().eth_chain_id().await.unwrap_err();
+    assert!(matches!(err.current_context(), Error::CircuitOpen { .. }));
+    assert_eq!(server.received_requests().await.unwrap().len(), 3);
+    assert_eq!(rpc.client.circuit_state(), Some(CircuitState::Open));
+
+    // a failed probe opens it again
+    tokio::time::sleep(cool_down).await;
+    assert!(rpc.eth_block_number().await.is_err());
+    assert_eq!(server.received_requests().await.unwrap().len(), 4);
+
+    tokio::time::sleep(cool_down).await;
+    reply(
+        &server,
+        ResponseTemplate::new(200)
+            .set_body_string(r#"{"jsonrpc":"2.0","id":"327","result":"0x10"}"#),
+    )
+    .await;
+    rpc.eth_block_number().await.unwrap();
+    assert_eq!(rpc.client.circuit_state(), Some(CircuitState::Closed));
+    assert_eq!(
+        transitions.states(),
+        [
+            (CircuitState::Closed, CircuitState::Open),
+            (CircuitState::Open, CircuitState::HalfOpen),
+            (CircuitState::HalfOpen, CircuitState::Open),
+            (CircuitState::Open, CircuitState::HalfOpen),
+            (CircuitState::HalfOpen, CircuitState::Closed),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn json_rpc_errors_do_not_count() {
+    let server = MockServer::start().await;
+    reply(
+        &server,
+        ResponseTemplate::new(200).set_body_string(
+            r#"{"jsonrpc":"2.0","id":"330","error":{"code":-32602,"message":"invalid argument 0"}}"#,
+        ),
+    )
+    .await;
+    let rpc = Web3::builder()
+        .url(server.uri())
+        .circuit_breaker(CircuitBreakerPolicy::new(1))
+        .build()
+        .unwrap();
+    for _ in 0..3 {
+        let err = rpc.eth_block_number().await.unwrap_err();
+        assert!(err.current_context().is_invalid_params());
+    }
+    assert_eq!(rpc.client.circuit_state(), Some(CircuitState::Closed));
+}
+
+#[tokio::test]
+async fn each_endpoint_has_its_own_circuit() {
+    let (bad, good) = (MockServer::start().await, MockServer::start().await);
+    reply(&bad, ResponseTemplate::new(502)).await;
+    let answer = || {
+        ResponseTemplate::new(200)
+            .set_body_string(r#"{"jsonrpc":"2.0","id":"327","result":"0x10"}"#)
+    };
+    reply(&good, answer()).await;
+    let transitions = Transitions::default();
+    let rpc = Web3::builder()
+        .endpoints([bad.uri(), good.uri()], SelectionStrategy::Priority)
+        .retry(RetryPolicy::new(3).initial_backoff(Duration::from_millis(1)))
+        .circuit_breaker(CircuitBreakerPolicy::new(1).cool_down(Duration::from_secs(60)))
+        .circuit_observer(Box::new(transitions.clone()))
+        .build()
+        .unwrap();
+
+    // the failing endpoint's circuit opens, calls go on through the other one
+    for _ in 0..3 {
+        rpc.eth_block_number().await.unwrap();
+    }
+    assert_eq!(bad.received_requests().await.unwrap().len(), 1);
+    assert_eq!(rpc.client.circuit_state(), Some(CircuitState::Closed));
+    let circuits: Vec<_> = rpc
+        .client
+        .endpoint_stats()
+        .iter()
+        .map(|s| s.circuit)
+        .collect();
+    assert_eq!(
+        circuits,
+        [Some(CircuitState::Open), Some(CircuitState::Closed)]
+    );
+
+    // calls fail fast only once every circuit is open
+    reply(&good, ResponseTemplate::new(503)).await;
+    let err = rpc.eth_block_number().await.unwrap_err();
+    assert!(matches!(err.current_context(), Error::CircuitOpen { .. }));
+    assert_eq!(rpc.client.circuit_state(), Some(CircuitState::Open));
+    let err = rpc.eth_chain_id().await.unwrap_err();
+    assert!(matches!(err.current_context(), Error::CircuitOpen { .. }));
+    assert_eq!(good.received_requests().await.unwrap().len(), 1);
+
+    let host = |server: &MockServer| server.uri().trim_start_matches("http://").to_string();
+    assert_eq!(transitions.endpoints(), [host(&bad), host(&good)]);
+    assert!(transitions
+        .states()
+        .iter()
+        .all(|t| *t == (CircuitState::Closed, CircuitState::Open)));
+}
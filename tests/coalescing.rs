@@ -0,0 +1,113 @@
+//! Identical concurrent reads sharing one upstream request.
+
+use serde_json::json;
+use std::time::Duration;
+use tokio::task::JoinSet;
+use web3_rpc::client::Error;
+use web3_rpc::web3::Web3;
+use wiremock::matchers::{body_partial_json, method};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+const CALLERS: usize = 20;
+
+/// Answers `rpc_method` after a delay long enough for every caller to join, expecting
+/// exactly `requests` requests.
+async fn slow(server: &MockServer, rpc_method: &str, reply: ResponseTemplate, requests: u64) {
+    Mock::given(method("POST"))
+        .and(body_partial_json(json!({ "method": rpc_method })))
+        .respond_with(reply.set_delay(Duration::from_millis(300)))
+        .expect(requests)
+        .mount(server)
+        .await;
+}
+
+fn coalescing(server: &MockServer) -> Web3 {
+    Web3::builder()
+        .url(server.uri())
+        .coalesce(64)
+        .build()
+        .unwrap()
+}
+
+#[tokio::test]
+async fn identical_reads_share_one_request() {
+    let server = MockServer::start().await;
+    let reply = json!({ "jsonrpc": "2.0", "id": "327", "result": "0x1036640" });
+    slow(
+        &server,
+        "eth_blockNumber",
+        ResponseTemplate::new(200).set_body_json(reply),
+        1,
+    )
+    .await;
+
+    let rpc = coalescing(&server);
+    let mut tasks = JoinSet::new();
+    for _ in 0..CALLERS {
+        let rpc = rpc.clone();
+        tasks.spawn(async move { rpc.eth_block_number().await });
+    }
+    let mut answered = 0;
+    while let Some(result) = tasks.join_next().await {
+        let r = result.unwrap().unwrap();
+        assert_eq!(r.result.as_deref(), Some("0x1036640"));
+        answered += 1;
+    }
+    assert_eq!(answered, CALLERS);
+}
+
+#[tokio::test]
+async fn errors_reach_every_waiter() {
+    let server = MockServer::start().await;
+    slow(&server, "eth_gasPrice", ResponseTemplate::new(502), 1).await;
+
+    let rpc = coalescing(&server);
+    let mut tasks = JoinSet::new();
+    for _ in 0..CALLERS {
+        let rpc = rpc.clone();
+        tasks.spawn(async move { rpc.eth_gas_price().await });
+    }
+    while let Some(result) = tasks.join_next().await {
+        let report = result.unwrap().unwrap_err();
+        assert!(matches!(
+            report.current_context(),
+            Error::HttpError(status) if status.as_u16() == 502
+        ));
+    }
+}
+
+#[tokio::test]
+async fn writes_and_finished_reads_are_not_shared() {
+    let server = MockServer::start().await;
+    let hash = "0xe812a49745d691961893d7cfd3902d78d710751bab872f12215ee23f27f3efa9";
+    let reply = json!({ "jsonrpc": "2.0", "id": "321", "result": hash });
+    slow(
+        &server,
+        "eth_sendRawTransaction",
+        ResponseTemplate::new(200).set_body_json(reply),
+        3,
+    )
+    .await;
+    let reply = json!({ "jsonrpc": "2.0", "id": "327", "result": "0x1" });
+    slow(
+        &server,
+        "eth_blockNumber",
+        ResponseTemplate::new(200).set_body_json(reply),
+        2,
+    )
+    .await;
+
+    let rpc = coalescing(&server);
+    let mut tasks = JoinSet::new();
+    for _ in 0..3 {
+        let rpc = rpc.clone();
+        tasks.spawn(async move { rpc.eth_send_raw_transaction("0x02f8").await });
+    }
+    while let Some(result) = tasks.join_next().await {
+        assert_eq!(result.unwrap().unwrap().result.as_deref(), Some(hash));
+    }
+
+    // one after the other, so the second finds nothing in flight
+    rpc.eth_block_number().await.unwrap();
+    rpc.eth_block_number().await.unwrap();
+}
@@ -0,0 +1,51 @@
+//! Helpers shared by the integration tests.
+#![allow(dead_code)]
+
+use serde_json::{json, Value};
+#[cfg(feature = "test-util")]
+use web3_rpc::test_util::{AnvilInstance, AnvilOptions};
+use wiremock::matchers::{body_partial_json, method};
+use wiremock::{Mock, MockServer, Request, ResponseTemplate};
+
+/// Anvil spawned with `options`, or `None` with a note on stderr when the binary is not
+/// installed and `test` has to be skipped.
+#[cfg(feature = "test-util")]
+pub async fn anvil(test: &str, options: AnvilOptions) -> Option<AnvilInstance> {
+    if !options.is_installed() {
+        eprintln!(
+            "skipping `{}`: {} is not installed",
+            test,
+            options.binary.display()
+        );
+        return None;
+    }
+    Some(AnvilInstance::spawn(options).await.unwrap())
+}
+
+/// The JSON in `tests/fixtures/<name>.json`.
+pub fn fixture(name: &str) -> Value {
+    let path = format!(
+        "{}/tests/fixtures/{}.json",
+        env!("CARGO_MANIFEST_DIR"),
+        name
+    );
+    serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap()
+}
+
+/// A mock answering calls that contain `call` with `result`, under the caller's id.
+pub fn responding(call: Value, result: Value) -> Mock {
+    Mock::given(method("POST"))
+        .and(body_partial_json(call))
+        .respond_with(move |request: &Request| {
+            let request: Value = serde_json::from_slice(&request.body).unwrap();
+            ResponseTemplate::new(200)
+                .set_body_json(json!({ "jsonrpc": "2.0", "id": request["id"], "result": result }))
+        })
+}
+
+/// Answers every `rpc_method` call on `server` with `result`.
+pub async fn answer(server: &MockServer, rpc_method: &str, result: Value) {
+    responding(json!({ "method": rpc_method }), result)
+        .mount(server)
+        .await;
+}
@@ -0,0 +1,173 @@
+//! Outcomes of `send_and_confirm` against a mock server, and end to end against anvil when
+//! the binary is on the PATH and `test-util` is enabled.
+
+mod common;
+
+use common::{answer, fixture};
+use serde_json::{json, Value};
+use std::time::Duration;
+use web3_rpc::client::Error;
+use web3_rpc::model::TransactionRequest;
+use web3_rpc::send::Sender;
+#[cfg(feature = "test-util")]
+use web3_rpc::test_util::AnvilOptions;
+use web3_rpc::types::Address;
+use web3_rpc::web3::Web3;
+use wiremock::matchers::{body_partial_json, method};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+/// The EIP-155 example transaction, nonce 9.
+const RAW: &str = "0xf86c098504a817c800825208943535353535353535353535353535353535353535880de0b6b3a76400008025a028ef61340bd939bc2195fe537567866003e1a15d3c71ff63e1590620aa636276a067cbe9d8997f761aecb703304b3800ccf555c9f3dc64214b297fb1966a3b6d83";
+const TX: &str = "0x33469b22e9f636356c4160a87eb19df52b7412e8eac32a4a55ffe88ea8350788";
+/// `Error(string)` with "insufficient balance".
+const REVERT_DATA: &str = "0x08c379a000000000000000000000000000000000000000000000000000000000000000200000000000000000000000000000000000000000000000000000000000000014696e73756666696369656e742062616c616e6365000000000000000000000000";
+
+async fn mined(server: &MockServer, receipt: &str, head: &str) {
+    answer(server, "eth_sendRawTransaction", json!(TX)).await;
+    answer(server, "eth_getTransactionReceipt", fixture(receipt)).await;
+    answer(server, "eth_blockNumber", json!(head)).await;
+}
+
+#[tokio::test]
+async fn confirmed() {
+    let server = MockServer::start().await;
+    // mined in 0x1036640, two blocks on top
+    mined(&server, "receipt_contract_creation", "0x1036642").await;
+
+    let receipt = Web3::new(server.uri())
+        .send_and_confirm(RAW, 3, Duration::from_secs(5))
+        .await
+        .unwrap();
+    assert_eq!(receipt.status, Some(true));
+}
+
+#[tokio::test]
+async fn confirmed_from_a_request() {
+    let server = MockServer::start().await;
+    answer(&server, "eth_sendTransaction", json!(TX)).await;
+    answer(
+        &server,
+        "eth_getTransactionReceipt",
+        fixture("receipt_contract_creation"),
+    )
+    .await;
+    answer(&server, "eth_blockNumber", json!("0x1036640")).await;
+
+    let from: Address = "0x4838b106fce9647bdf1e7877bf73ce8b0bad5f97"
+        .parse()
+        .unwrap();
+    let request = TransactionRequest::new().data("0x6000").gas(100_000u64);
+    Web3::new(server.uri())
+        .send_and_confirm((request, Sender::Node(from)), 1, Duration::from_secs(5))
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn reverted_with_replayed_reason() {
+    let server = MockServer::start().await;
+    mined(&server, "receipt_failed", "0x1036640").await;
+    answer(&server, "eth_getTransactionByHash", fixture("tx_legacy")).await;
+    Mock::given(method("POST"))
+        .and(body_partial_json(json!({ "method": "eth_call" })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "jsonrpc": "2.0",
+            "id": "1",
+            "error": { "code": 3, "message": "execution reverted", "data": REVERT_DATA }
+        })))
+        .mount(&server)
+        .await;
+
+    let err = Web3::new(server.uri())
+        .send_and_confirm(RAW, 1, Duration::from_secs(5))
+        .await
+        .unwrap_err();
+    match err.current_context() {
+        Error::TransactionReverted { tx_hash, reason } => {
+            assert_eq!(tx_hash.to_string(), TX);
+            assert_eq!(reason.as_deref(), Some("insufficient balance"));
+        }
+        e => panic!("unexpected error {:?}", e),
+    }
+
+    let requests = server.received_requests().await.unwrap();
+    let replay = requests
+        .iter()
+        .map(|r| serde_json::from_slice::<Value>(&r.body).unwrap())
+        .find(|body| body["method"] == "eth_call")
+        .unwrap();
+    assert_eq!(
+        replay["params"][0]["from"],
+        json!("0x39fa8c5f2793459d6622857e7d9fbb4bd91766d3")
+    );
+    assert_eq!(replay["params"][1], json!("0x1036640"));
+}
+
+#[tokio::test]
+async fn dropped_once_the_nonce_is_used() {
+    let server = MockServer::start().await;
+    answer(&server, "eth_sendRawTransaction", json!(TX)).await;
+    answer(&server, "eth_getTransactionReceipt", Value::Null).await;
+    answer(&server, "eth_getTransactionByHash", Value::Null).await;
+    answer(&server, "eth_getTransactionCount", json!("0xa")).await;
+
+    let err = Web3::new(server.uri())
+        .send_and_confirm(RAW, 1, Duration::from_secs(5))
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(err.current_context(), Error::TransactionDropped { tx_hash } if tx_hash.to_string() == TX),
+        "{:?}",
+        err
+    );
+}
+
+#[tokio::test]
+async fn pending_past_the_deadline_times_out() {
+    let server = MockServer::start().await;
+    answer(&server, "eth_sendRawTransaction", json!(TX)).await;
+    answer(&server, "eth_getTransactionReceipt", Value::Null).await;
+    answer(&server, "eth_getTransactionByHash", fixture("tx_pending")).await;
+
+    let err = Web3::new(server.uri())
+        .send_and_confirm(RAW, 1, Duration::from_millis(100))
+        .await
+        .unwrap_err();
+    assert!(matches!(err.current_context(), Error::Timeout), "{:?}", err);
+}
+
+#[tokio::test]
+async fn too_shallow_past_the_deadline_times_out() {
+    let server = MockServer::start().await;
+    mined(&server, "receipt_contract_creation", "0x1036640").await;
+
+    let err = Web3::new(server.uri())
+        .send_and_confirm(RAW, 2, Duration::from_millis(100))
+        .await
+        .unwrap_err();
+    assert!(matches!(err.current_context(), Error::Timeout), "{:?}", err);
+}
+
+#[cfg(feature = "test-util")]
+#[tokio::test]
+async fn confirms_on_anvil() {
+    let options = AnvilOptions {
+        block_time: Some(1),
+        ..Default::default()
+    };
+    let Some(anvil) = common::anvil("confirms_on_anvil", options).await else {
+        return;
+    };
+    let rpc = anvil.web3();
+    let (from, to) = (anvil.accounts()[0], anvil.accounts()[1]);
+    assert_eq!(anvil.private_keys().len(), anvil.accounts().len());
+    assert_eq!(anvil.chain_id(), 31_337);
+
+    let request = TransactionRequest::new().to(to).value(1_000u64);
+    let receipt = rpc
+        .send_and_confirm((request, Sender::Node(from)), 2, Duration::from_secs(20))
+        .await
+        .unwrap();
+    assert_eq!(receipt.status, Some(true));
+    assert_eq!(receipt.to, Some(to));
+}
@@ -0,0 +1,187 @@
+//! Contract deployment against a mock server, and end to end against anvil when the binary
+//! is on the PATH and `test-util` is enabled.
+
+mod common;
+
+use common::answer;
+use serde_json::{json, Value};
+use std::time::Duration;
+use web3_rpc::abi::Token;
+use web3_rpc::client::Error;
+use web3_rpc::deploy::DeployOptions;
+use web3_rpc::model::TransactionRequest;
+use web3_rpc::send::{Sender, TransactionSigner};
+#[cfg(feature = "test-util")]
+use web3_rpc::test_util::AnvilOptions;
+use web3_rpc::types::{Address, U256};
+use web3_rpc::web3::Web3;
+use wiremock::MockServer;
+
+const RECEIPT: &str = include_str!("fixtures/receipt_contract_creation.json");
+const TX: &str = "0x1c8a5b4e8f11a56e2f23d0e0f7c9c3f5a0b8d7e6c5b4a3f2e1d0c9b8a7f6e5d4";
+
+/// Stores its constructor argument in slot 0; the runtime code returns that slot.
+const STORE_INIT: &str = "0x60206024600039600051600055600b6019600039600b6000f3600054600052602060\
+                          00f3";
+
+fn deployer() -> Address {
+    "0x4838b106fce9647bdf1e7877bf73ce8b0bad5f97"
+        .parse()
+        .unwrap()
+}
+
+fn options() -> DeployOptions {
+    DeployOptions {
+        poll_interval: Duration::from_millis(10),
+        timeout: Duration::from_secs(2),
+        ..DeployOptions::default()
+    }
+}
+
+fn sent(requests: &[wiremock::Request], rpc_method: &str) -> Value {
+    requests
+        .iter()
+        .map(|r| serde_json::from_slice::<Value>(&r.body).unwrap())
+        .find(|body| body["method"] == rpc_method)
+        .unwrap_or_else(|| panic!("no {} request", rpc_method))
+}
+
+#[tokio::test]
+async fn deploys_from_a_node_account() {
+    let server = MockServer::start().await;
+    answer(&server, "eth_estimateGas", json!("0x8fd37")).await;
+    answer(&server, "eth_sendTransaction", json!(TX)).await;
+    answer(
+        &server,
+        "eth_getTransactionReceipt",
+        serde_json::from_str(RECEIPT).unwrap(),
+    )
+    .await;
+
+    let address = Web3::new(server.uri())
+        .deploy_contract(
+            STORE_INIT,
+            &[Token::Uint(U256::from(42u64))],
+            deployer(),
+            options(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        address,
+        "0x5fbdb2315678afecb367f032d93f642f64180aa3"
+            .parse()
+            .unwrap()
+    );
+
+    let requests = server.received_requests().await.unwrap();
+    let send = sent(&requests, "eth_sendTransaction");
+    let tx = &send["params"][0];
+    assert_eq!(tx["from"], json!(deployer()));
+    assert_eq!(tx["gas"], json!("0x8fd37"));
+    assert!(tx.get("to").is_none());
+    let data = tx["data"].as_str().unwrap();
+    assert!(data.starts_with(STORE_INIT));
+    assert!(data.ends_with(&format!("{:064x}", 42)));
+}
+
+#[tokio::test]
+async fn reverted_creation_is_an_error() {
+    let server = MockServer::start().await;
+    let mut receipt: Value = serde_json::from_str(RECEIPT).unwrap();
+    receipt["status"] = json!("0x0");
+    receipt["contractAddress"] = Value::Null;
+    answer(&server, "eth_sendTransaction", json!(TX)).await;
+    answer(&server, "eth_getTransactionReceipt", receipt).await;
+
+    let options = DeployOptions {
+        gas: Some(U256::from(1_000_000u64)),
+        ..options()
+    };
+    let err = Web3::new(server.uri())
+        .deploy_contract(STORE_INIT, &[], deployer(), options)
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(err.current_context(), Error::TransactionReverted { tx_hash, .. } if tx_hash.to_string() == TX),
+        "{:?}",
+        err
+    );
+}
+
+/// Records what it was asked to sign and returns fixed bytes.
+struct FakeSigner(std::sync::Mutex<Option<TransactionRequest>>);
+
+impl TransactionSigner for FakeSigner {
+    fn address(&self) -> Address {
+        deployer()
+    }
+
+    fn sign_transaction(
+        &self,
+        request: &TransactionRequest,
+    ) -> Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>> {
+        *self.0.lock().unwrap() = Some(request.clone());
+        Ok(vec![0x02, 0xf8])
+    }
+}
+
+#[tokio::test]
+async fn local_signer_gets_a_complete_request() {
+    let server = MockServer::start().await;
+    answer(&server, "eth_estimateGas", json!("0x8fd37")).await;
+    answer(&server, "eth_getTransactionCount", json!("0x7")).await;
+    answer(&server, "eth_chainId", json!("0x7a69")).await;
+    answer(&server, "eth_gasPrice", json!("0x3b9aca00")).await;
+    answer(&server, "eth_sendRawTransaction", json!(TX)).await;
+    answer(
+        &server,
+        "eth_getTransactionReceipt",
+        serde_json::from_str(RECEIPT).unwrap(),
+    )
+    .await;
+
+    let signer = FakeSigner(Default::default());
+    Web3::new(server.uri())
+        .deploy_contract(STORE_INIT, &[], Sender::Signer(&signer), options())
+        .await
+        .unwrap();
+
+    let signed = signer.0.lock().unwrap().clone().unwrap();
+    assert_eq!(signed.from, Some(deployer()));
+    assert_eq!(signed.nonce, Some(U256::from(7u64)));
+    assert_eq!(signed.chain_id, Some(U256::from(31_337u64)));
+    assert_eq!(signed.gas_price, Some(U256::from(1_000_000_000u64)));
+    assert_eq!(signed.gas, Some(U256::from(0x8fd37u64)));
+
+    let requests = server.received_requests().await.unwrap();
+    let count = sent(&requests, "eth_getTransactionCount");
+    assert_eq!(count["params"][1], json!("pending"));
+    assert_eq!(
+        sent(&requests, "eth_sendRawTransaction")["params"],
+        json!(["0x02f8"])
+    );
+}
+
+#[cfg(feature = "test-util")]
+#[tokio::test]
+async fn deploys_on_anvil() {
+    let Some(anvil) = common::anvil("deploys_on_anvil", AnvilOptions::default()).await else {
+        return;
+    };
+    let rpc = anvil.web3();
+    let from = anvil.accounts()[0];
+    let address = rpc
+        .deploy_contract(
+            STORE_INIT,
+            &[Token::Uint(U256::from(42u64))],
+            from,
+            options(),
+        )
+        .await
+        .unwrap();
+
+    let call = TransactionRequest::new().to(address).data("0x");
+    let stored = rpc.eth_call_request(&call).await.unwrap().result.unwrap();
+    assert_eq!(stored, format!("0x{:064x}", 42));
+}
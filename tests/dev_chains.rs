@@ -0,0 +1,384 @@
+//! Anvil and Hardhat cheat methods against a mock server, and end to end against anvil when the
+//! binary is on the PATH and `test-util` is enabled.
+
+mod common;
+
+use serde_json::{json, Value};
+use web3_rpc::client::Error;
+use web3_rpc::model::Tag;
+#[cfg(feature = "test-util")]
+use web3_rpc::test_util::AnvilOptions;
+use web3_rpc::types::{Address, H256, U256};
+use web3_rpc::web3::dev_chains::{Forking, SnapshotId};
+use web3_rpc::web3::Web3;
+use wiremock::matchers::{body_json, method};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+const ADDRESS: &str = "0x846c4dc9f4e2514206ef179eaa0bcfae007e37d2";
+const HASH: &str = "0xe812a49745d691961893d7cfd3902d78d710751bab872f12215ee23f27f3efa9";
+
+fn address() -> Address {
+    ADDRESS.parse().unwrap()
+}
+
+async fn expect(method_name: &str, params: Value, id: &str, result: Value) -> (MockServer, Web3) {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(body_json(
+            json!({ "jsonrpc": "2.0", "method": method_name, "params": params, "id": id }),
+        ))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_json(json!({ "jsonrpc": "2.0", "id": id, "result": result })),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+    let rpc = Web3::new(server.uri());
+    (server, rpc)
+}
+
+#[tokio::test]
+async fn anvil_methods() {
+    let (_server, rpc) = expect(
+        "anvil_impersonateAccount",
+        json!([ADDRESS]),
+        "1401",
+        json!(null),
+    )
+    .await;
+    rpc.anvil_impersonate_account(address()).await.unwrap();
+
+    let (_server, rpc) = expect(
+        "anvil_stopImpersonatingAccount",
+        json!([ADDRESS]),
+        "1402",
+        json!(null),
+    )
+    .await;
+    rpc.anvil_stop_impersonating_account(address())
+        .await
+        .unwrap();
+
+    let (_server, rpc) = expect(
+        "anvil_setBalance",
+        json!([ADDRESS, "0xde0b6b3a7640000"]),
+        "1403",
+        json!(null),
+    )
+    .await;
+    rpc.anvil_set_balance(address(), U256::from(10u64.pow(18)))
+        .await
+        .unwrap();
+
+    let (_server, rpc) = expect(
+        "anvil_setCode",
+        json!([ADDRESS, "0x6000"]),
+        "1404",
+        json!(null),
+    )
+    .await;
+    rpc.anvil_set_code(address(), &[0x60, 0x00]).await.unwrap();
+
+    let (_server, rpc) = expect(
+        "anvil_setNonce",
+        json!([ADDRESS, "0x2a"]),
+        "1405",
+        json!(null),
+    )
+    .await;
+    rpc.anvil_set_nonce(address(), 42).await.unwrap();
+
+    // anvil answers `true` here
+    let (_server, rpc) = expect(
+        "anvil_setStorageAt",
+        json!([ADDRESS, "0x0", HASH]),
+        "1406",
+        json!(true),
+    )
+    .await;
+    rpc.anvil_set_storage_at(address(), 0u64, HASH.parse().unwrap())
+        .await
+        .unwrap();
+
+    let (_server, rpc) = expect("anvil_mine", json!([null, null]), "1407", json!(null)).await;
+    rpc.anvil_mine(None, None).await.unwrap();
+    let (_server, rpc) = expect("anvil_mine", json!(["0xa", "0xc"]), "1407", json!(null)).await;
+    rpc.anvil_mine(Some(10), Some(12)).await.unwrap();
+
+    let (_server, rpc) = expect("anvil_reset", json!([]), "1408", json!(null)).await;
+    rpc.anvil_reset(None).await.unwrap();
+    let (_server, rpc) = expect(
+        "anvil_reset",
+        json!([{ "forking": { "jsonRpcUrl": "https://rpc.example.com", "blockNumber": 17000000 } }]),
+        "1408",
+        json!(null),
+    )
+    .await;
+    let forking = Forking::new("https://rpc.example.com").block_number(17_000_000);
+    rpc.anvil_reset(Some(&forking)).await.unwrap();
+
+    let (_server, rpc) = expect("anvil_dropTransaction", json!([HASH]), "1409", json!(HASH)).await;
+    let dropped = rpc
+        .anvil_drop_transaction(HASH.parse().unwrap())
+        .await
+        .unwrap();
+    assert_eq!(dropped.result, Some(Some(HASH.parse::<H256>().unwrap())));
+
+    let (_server, rpc) = expect(
+        "anvil_setNextBlockBaseFeePerGas",
+        json!(["0x7"]),
+        "1400",
+        json!(null),
+    )
+    .await;
+    rpc.anvil_set_next_block_base_fee_per_gas(U256::from(7u64))
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn hardhat_methods() {
+    let (_server, rpc) = expect(
+        "hardhat_impersonateAccount",
+        json!([ADDRESS]),
+        "1421",
+        json!(true),
+    )
+    .await;
+    rpc.hardhat_impersonate_account(address()).await.unwrap();
+
+    let (_server, rpc) = expect(
+        "hardhat_stopImpersonatingAccount",
+        json!([ADDRESS]),
+        "1422",
+        json!(true),
+    )
+    .await;
+    rpc.hardhat_stop_impersonating_account(address())
+        .await
+        .unwrap();
+
+    let (_server, rpc) = expect(
+        "hardhat_setBalance",
+        json!([ADDRESS, "0x3e8"]),
+        "1423",
+        json!(true),
+    )
+    .await;
+    rpc.hardhat_set_balance(address(), U256::from(1000u64))
+        .await
+        .unwrap();
+
+    let (_server, rpc) = expect(
+        "hardhat_setCode",
+        json!([ADDRESS, "0x"]),
+        "1424",
+        json!(true),
+    )
+    .await;
+    rpc.hardhat_set_code(address(), &[]).await.unwrap();
+
+    let (_server, rpc) = expect(
+        "hardhat_setNonce",
+        json!([ADDRESS, "0x0"]),
+        "1425",
+        json!(true),
+    )
+    .await;
+    rpc.hardhat_set_nonce(address(), 0).await.unwrap();
+
+    let (_server, rpc) = expect(
+        "hardhat_setStorageAt",
+        json!([ADDRESS, "0x2", HASH]),
+        "1426",
+        json!(true),
+    )
+    .await;
+    rpc.hardhat_set_storage_at(address(), U256::from(2u64), HASH.parse().unwrap())
+        .await
+        .unwrap();
+
+    let (_server, rpc) = expect("hardhat_mine", json!(["0x3e8", null]), "1427", json!(true)).await;
+    rpc.hardhat_mine(Some(1000), None).await.unwrap();
+
+    let (_server, rpc) = expect(
+        "hardhat_reset",
+        json!([{ "forking": { "jsonRpcUrl": "https://rpc.example.com" } }]),
+        "1428",
+        json!(true),
+    )
+    .await;
+    rpc.hardhat_reset(Some(&Forking::new("https://rpc.example.com")))
+        .await
+        .unwrap();
+
+    let (_server, rpc) = expect(
+        "hardhat_dropTransaction",
+        json!([HASH]),
+        "1429",
+        json!(false),
+    )
+    .await;
+    let dropped = rpc
+        .hardhat_drop_transaction(HASH.parse().unwrap())
+        .await
+        .unwrap();
+    assert_eq!(dropped.result, Some(false));
+
+    let (_server, rpc) = expect(
+        "hardhat_setNextBlockBaseFeePerGas",
+        json!(["0x3b9aca00"]),
+        "1420",
+        json!(true),
+    )
+    .await;
+    rpc.hardhat_set_next_block_base_fee_per_gas(U256::from(1_000_000_000u64))
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn evm_methods() {
+    let (_server, rpc) = expect("evm_snapshot", json!([]), "1441", json!("0x01")).await;
+    let snapshot = rpc.evm_snapshot().await.unwrap();
+    assert_eq!(snapshot, SnapshotId("0x01".into()));
+
+    // sent back exactly as received, leading zero included
+    let (_server, rpc) = expect("evm_revert", json!(["0x01"]), "1442", json!(true)).await;
+    assert!(rpc.evm_revert(&snapshot).await.unwrap());
+    let (_server, rpc) = expect("evm_revert", json!(["0x01"]), "1442", json!(false)).await;
+    assert!(!rpc.evm_revert(&snapshot).await.unwrap());
+
+    for offset in [json!(3600), json!("3600"), json!("0xe10")] {
+        let (_server, rpc) = expect("evm_increaseTime", json!([3600]), "1443", offset).await;
+        assert_eq!(rpc.evm_increase_time(3600).await.unwrap(), 3600);
+    }
+    let (_server, rpc) = expect("evm_increaseTime", json!([1]), "1443", json!("soon")).await;
+    let err = rpc.evm_increase_time(1).await.unwrap_err();
+    assert!(matches!(
+        err.current_context(),
+        Error::UnexpectedResponseFormat
+    ));
+
+    let (_server, rpc) = expect(
+        "evm_setNextBlockTimestamp",
+        json!([1700000000]),
+        "1444",
+        json!(null),
+    )
+    .await;
+    rpc.evm_set_next_block_timestamp(1_700_000_000)
+        .await
+        .unwrap();
+
+    let (_server, rpc) = expect("evm_setAutomine", json!([false]), "1445", json!(true)).await;
+    rpc.evm_set_automine(false).await.unwrap();
+
+    let (_server, rpc) = expect("evm_mine", json!([]), "1446", json!("0x0")).await;
+    rpc.evm_mine(None).await.unwrap();
+    let (_server, rpc) = expect("evm_mine", json!([1700000012]), "1446", json!("0x0")).await;
+    rpc.evm_mine(Some(1_700_000_012)).await.unwrap();
+}
+
+#[tokio::test]
+async fn cheat_method_errors_are_reported() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "jsonrpc": "2.0",
+            "id": "1405",
+            "error": { "code": -32603, "message": "Nonce cannot be decreased" }
+        })))
+        .mount(&server)
+        .await;
+    let rpc = Web3::new(server.uri());
+    let err = rpc.anvil_set_nonce(address(), 0).await.unwrap_err();
+    assert!(matches!(
+        err.current_context(),
+        Error::JsonRpcError { code: -32603, .. }
+    ));
+}
+
+#[cfg(feature = "test-util")]
+#[tokio::test]
+async fn cheats_on_anvil() {
+    let Some(anvil) = common::anvil("cheats_on_anvil", AnvilOptions::default()).await else {
+        return;
+    };
+    let rpc = anvil.web3();
+    let target = address();
+
+    rpc.anvil_set_balance(target, U256::from(12_345u64))
+        .await
+        .unwrap();
+    let balance = rpc.eth_get_balance_typed(target, None).await.unwrap();
+    assert_eq!(balance.result, Some(U256::from(12_345u64)));
+
+    rpc.anvil_set_nonce(target, 7).await.unwrap();
+    let nonce = rpc.eth_get_transaction_count(target, None).await.unwrap();
+    assert_eq!(nonce.result.as_deref(), Some("0x7"));
+
+    rpc.anvil_set_code(target, &[0x60, 0x00]).await.unwrap();
+    let code = rpc.eth_get_code(target, None).await.unwrap();
+    assert_eq!(code.result.as_deref(), Some("0x6000"));
+
+    let word: H256 = HASH.parse().unwrap();
+    rpc.anvil_set_storage_at(target, 1u64, word).await.unwrap();
+    let stored = rpc.eth_get_storage_at(target, 1u64, None).await.unwrap();
+    assert_eq!(stored.result, Some(HASH.to_string()));
+
+    let before = rpc.eth_block_number().await.unwrap().result.unwrap();
+    rpc.anvil_mine(Some(5), None).await.unwrap();
+    let after = rpc.eth_block_number().await.unwrap().result.unwrap();
+    assert_ne!(before, after);
+
+    rpc.anvil_impersonate_account(target).await.unwrap();
+    rpc.anvil_stop_impersonating_account(target).await.unwrap();
+
+    rpc.anvil_reset(None).await.unwrap();
+    let reset = rpc
+        .eth_get_balance_typed(target, Some(Tag::Latest))
+        .await
+        .unwrap();
+    assert_eq!(reset.result, Some(U256::ZERO));
+}
+
+#[cfg(feature = "test-util")]
+#[tokio::test]
+async fn evm_methods_on_anvil() {
+    let Some(anvil) = common::anvil("evm_methods_on_anvil", AnvilOptions::default()).await else {
+        return;
+    };
+    let rpc = anvil.web3();
+    let target = address();
+    let balance = || async {
+        rpc.eth_get_balance_typed(target, None)
+            .await
+            .unwrap()
+            .result
+            .unwrap()
+    };
+
+    let snapshot = rpc.evm_snapshot().await.unwrap();
+    rpc.anvil_set_balance(target, U256::ONE).await.unwrap();
+    assert_eq!(balance().await, U256::ONE);
+    assert!(rpc.evm_revert(&snapshot).await.unwrap());
+    assert_eq!(balance().await, U256::ZERO);
+    assert!(!rpc.evm_revert(&snapshot).await.unwrap());
+
+    assert!(rpc.evm_increase_time(3600).await.unwrap() >= 3600);
+
+    let timestamp = 4_000_000_000;
+    rpc.evm_set_next_block_timestamp(timestamp).await.unwrap();
+    rpc.evm_mine(None).await.unwrap();
+    let block = rpc
+        .eth_get_block_by_number(Tag::Latest, false)
+        .await
+        .unwrap();
+    assert_eq!(block.result.unwrap().timestamp, timestamp);
+    rpc.evm_mine(Some(timestamp + 12)).await.unwrap();
+
+    rpc.evm_set_automine(false).await.unwrap();
+    rpc.evm_set_automine(true).await.unwrap();
+}
@@ -0,0 +1,159 @@
+//! Endpoint selection across mock servers of different latencies.
+
+use std::time::Duration;
+use web3_rpc::client::RetryPolicy;
+use web3_rpc::endpoints::SelectionStrategy;
+use web3_rpc::web3::Web3;
+use wiremock::matchers::method;
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+const BLOCK_NUMBER: &str = r#"{"jsonrpc":"2.0","id":"327","result":"0x10"}"#;
+
+async fn server(delay_ms: u64) -> MockServer {
+    let server = MockServer::start().await;
+    answer(&server, delay_ms).await;
+    server
+}
+
+async fn answer(server: &MockServer, delay_ms: u64) {
+    server.reset().await;
+    Mock::given(method("POST"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_string(BLOCK_NUMBER)
+                .set_delay(Duration::from_millis(delay_ms)),
+        )
+        .mount(server)
+        .await;
+}
+
+async fn received(servers: &[MockServer]) -> Vec<usize> {
+    let mut counts = Vec::new();
+    for server in servers {
+        counts.push(server.received_requests().await.unwrap().len());
+    }
+    counts
+}
+
+fn rpc(servers: &[MockServer], strategy: SelectionStrategy) -> Web3 {
+    Web3::builder()
+        .endpoints(servers.iter().map(|s| s.uri()), strategy)
+        .retry(RetryPolicy::new(2).initial_backoff(Duration::from_millis(1)))
+        .build()
+        .unwrap()
+}
+
+#[tokio::test]
+async fn traffic_shifts_to_the_fastest() {
+    let servers = [server(40).await, server(1).await, server(20).await];
+    let rpc = rpc(&servers, SelectionStrategy::Fastest { exploration: 0.1 });
+    for _ in 0..60 {
+        rpc.eth_block_number().await.unwrap();
+    }
+    let counts = received(&servers).await;
+    assert!(counts[1] >= 45, "{:?}", counts);
+    // each was measured at least once
+    assert!(counts.iter().all(|n| *n > 0), "{:?}", counts);
+
+    let stats = rpc.client.endpoint_stats();
+    assert_eq!(stats.len(), 3);
+    assert!(stats[1].latency < stats[2].latency, "{:?}", stats);
+    assert_eq!(
+        stats
+            .iter()
+            .map(|s| s.requests as usize)
+            .collect::<Vec<_>>(),
+        counts
+    );
+
+    // the fast one slows down, exploration notices the third got faster
+    answer(&servers[0], 40).await;
+    answer(&servers[1], 60).await;
+    answer(&servers[2], 1).await;
+    for _ in 0..60 {
+        rpc.eth_block_number().await.unwrap();
+    }
+    let counts = received(&servers).await;
+    assert!(counts[2] >= 40, "{:?}", counts);
+}
+
+#[tokio::test]
+async fn failed_endpoints_are_skipped() {
+    let servers = [server(1).await, server(1).await];
+    servers[0].reset().await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(502))
+        .mount(&servers[0])
+        .await;
+
+    // priority: the retry goes to the fallback, later calls skip the primary
+    let rpc = rpc(&servers, SelectionStrategy::Priority);
+    for _ in 0..5 {
+        rpc.eth_block_number().await.unwrap();
+    }
+    assert_eq!(received(&servers).await, [1, 5]);
+    let stats = rpc.client.endpoint_stats();
+    assert!(!stats[0].healthy && stats[1].healthy);
+    assert_eq!(stats[0].failures, 1);
+}
+
+#[tokio::test]
+async fn round_robin_spreads_evenly() {
+    let servers = [server(1).await, server(1).await, server(1).await];
+    let rpc = rpc(&servers, SelectionStrategy::RoundRobin);
+    for _ in 0..9 {
+        rpc.eth_block_number().await.unwrap();
+    }
+    assert_eq!(received(&servers).await, [3, 3, 3]);
+}
+
+async fn at_block(block: u64) -> MockServer {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(format!(
+            r#"{{"jsonrpc":"2.0","id":"327","result":"{:#x}"}}"#,
+            block
+        )))
+        .mount(&server)
+        .await;
+    server
+}
+
+#[tokio::test]
+async fn lagging_endpoints_are_skipped() {
+    let servers = [at_block(17_000_000).await, at_block(17_000_040).await];
+    let behind = Web3::new(servers[0].uri());
+    let ahead = Web3::new(servers[1].uri());
+    assert_eq!(behind.head_lag(&ahead).await.unwrap(), 40);
+    assert_eq!(ahead.head_lag(&behind).await.unwrap(), -40);
+
+    let checked = rpc(&servers, SelectionStrategy::Priority);
+    let check = checked.spawn_head_check(Duration::from_millis(10), 5);
+    let stats = loop {
+        let stats = checked.client.endpoint_stats();
+        if !stats[0].healthy {
+            break stats;
+        }
+        tokio::time::sleep(Duration::from_millis(5)).await;
+    };
+    assert_eq!(stats[0].lag, Some(40));
+    assert_eq!(stats[1].head, Some(17_000_040));
+    // the task ends with the client
+    drop(checked);
+    tokio::time::timeout(Duration::from_secs(1), check)
+        .await
+        .unwrap()
+        .unwrap();
+
+    let rpc = rpc(&servers, SelectionStrategy::Priority);
+    rpc.check_heads(5).await;
+    assert!(rpc.client.endpoint_stats()[0].healthy);
+    rpc.check_heads(5).await;
+    assert!(!rpc.client.endpoint_stats()[0].healthy);
+    for server in &servers {
+        server.reset().await;
+    }
+    answer(&servers[1], 1).await;
+    rpc.eth_block_number().await.unwrap();
+    assert_eq!(received(&servers).await, [0, 1]);
+}
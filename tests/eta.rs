@@ -0,0 +1,73 @@
+//! Block and time projections against a mock chain with 12 second slots.
+
+use serde_json::{json, Value};
+use web3_rpc::web3::Web3;
+use wiremock::matchers::method;
+use wiremock::{Mock, MockServer, Request, ResponseTemplate};
+
+const HEAD: u64 = 19_000_000;
+const GENESIS: u64 = 1_606_824_023;
+
+/// Every slot filled except each 100th.
+fn timestamp(n: u64) -> u64 {
+    GENESIS + 12 * (n + n / 100)
+}
+
+fn answer(request: &Value) -> Value {
+    let result = match request["method"].as_str().unwrap() {
+        "eth_blockNumber" => json!(format!("{:#x}", HEAD)),
+        "eth_getBlockByNumber" => {
+            let n = u64::from_str_radix(&request["params"][0].as_str().unwrap()[2..], 16).unwrap();
+            json!({ "number": format!("{:#x}", n), "timestamp": format!("{:#x}", timestamp(n)) })
+        }
+        other => panic!("unexpected method {}", other),
+    };
+    json!({ "jsonrpc": "2.0", "id": request["id"], "result": result })
+}
+
+async fn serving() -> MockServer {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(|request: &Request| {
+            let request: Value = serde_json::from_slice(&request.body).unwrap();
+            let response = match request.as_array() {
+                Some(batch) => json!(batch.iter().map(answer).collect::<Vec<_>>()),
+                None => answer(&request),
+            };
+            ResponseTemplate::new(200).set_body_json(response)
+        })
+        .mount(&server)
+        .await;
+    server
+}
+
+#[tokio::test]
+async fn projections_on_a_slotted_chain() {
+    let server = serving().await;
+    let rpc = Web3::new(server.uri());
+
+    let eta = rpc.estimate_time_of_block(HEAD + 1_000).await.unwrap();
+    assert_eq!(eta.block_time.slot, Some(12));
+    assert_eq!(eta.block_time.samples, 256);
+    assert_eq!(eta.earliest, timestamp(HEAD) + 12_000);
+    // the sample missed 3 slots in 256 blocks
+    assert_eq!(eta.timestamp, timestamp(HEAD) + 12_144);
+    assert!(eta.latest > eta.timestamp);
+
+    let mined = rpc.estimate_time_of_block(HEAD - 10).await.unwrap();
+    let known = timestamp(HEAD - 10);
+    assert_eq!(
+        (mined.earliest, mined.timestamp, mined.latest),
+        (known, known, known)
+    );
+
+    let at = rpc
+        .estimate_block_at(timestamp(HEAD) + 3_600)
+        .await
+        .unwrap();
+    assert!(at.lowest <= at.block && at.block <= at.highest);
+    assert_eq!(at.highest, HEAD + 300);
+    assert_eq!(at.block, HEAD + 296);
+
+    assert!(rpc.estimate_block_at(timestamp(HEAD) - 1).await.is_err());
+}
@@ -0,0 +1,122 @@
+//! Fee breakdown of a mined transaction, and fee market statistics, against a mock node.
+
+mod common;
+
+use common::{fixture, responding};
+use serde_json::{json, Value};
+use web3_rpc::fees::BaseFeeTrend;
+use web3_rpc::web3::Web3;
+use wiremock::MockServer;
+
+/// Answers one `rpc_method` call with these `params`.
+async fn answer(server: &MockServer, rpc_method: &str, params: Value, result: Value) {
+    responding(json!({ "method": rpc_method, "params": params }), result)
+        .expect(1)
+        .mount(server)
+        .await;
+}
+
+#[tokio::test]
+async fn fee_of_a_blob_transaction() {
+    let server = MockServer::start().await;
+    let receipt = fixture("receipt_blob");
+    let block = fixture("block_cancun");
+    assert_eq!(receipt["blockHash"], block["hash"]);
+    answer(
+        &server,
+        "eth_getTransactionReceipt",
+        json!([receipt["transactionHash"]]),
+        receipt.clone(),
+    )
+    .await;
+    answer(
+        &server,
+        "eth_getBlockByHash",
+        json!([block["hash"], false]),
+        block,
+    )
+    .await;
+
+    let hash = receipt["transactionHash"].as_str().unwrap();
+    let fee = Web3::new(server.uri())
+        .transaction_fee(hash)
+        .await
+        .unwrap()
+        .unwrap();
+    let base_fee_per_gas = 0x3c27d9a4d_u128;
+    assert_eq!(fee.base_fee, 21_000 * base_fee_per_gas);
+    assert_eq!(
+        fee.priority_fee,
+        21_000 * (20_000_000_000 - base_fee_per_gas)
+    );
+    assert_eq!(fee.blob_fee, 0x20000);
+    assert_eq!(fee.total, fee.base_fee + fee.priority_fee + fee.blob_fee);
+}
+
+#[tokio::test]
+async fn pending_transactions_have_no_fee_yet() {
+    let server = MockServer::start().await;
+    let hash = "0x5c1d5e1a8f8d9f0f6c2e6a4a4d2f1b7c8b3e0f4f9a3c2d1e6b5a4f3e2d1c0b9a";
+    answer(
+        &server,
+        "eth_getTransactionReceipt",
+        json!([hash]),
+        Value::Null,
+    )
+    .await;
+    assert!(Web3::new(server.uri())
+        .transaction_fee(hash)
+        .await
+        .unwrap()
+        .is_none());
+}
+
+/// A history of `count` blocks ending at `newest`, base fee rising 1 gwei per block from
+/// 10 gwei at block 1000, and tips of 1, 2 and 3 gwei.
+fn history(count: u64, newest: u64) -> Value {
+    let oldest = newest + 1 - count;
+    let gwei = |n: u64| format!("{:#x}", n * 1_000_000_000);
+    json!({
+        "oldestBlock": format!("{:#x}", oldest),
+        "baseFeePerGas": (oldest..=newest + 1).map(|n| gwei(n - 990)).collect::<Vec<_>>(),
+        "gasUsedRatio": (oldest..=newest).map(|_| 0.9).collect::<Vec<_>>(),
+        "reward": (oldest..=newest).map(|_| [gwei(1), gwei(2), gwei(3)]).collect::<Vec<_>>()
+    })
+}
+
+#[tokio::test]
+async fn fee_percentiles_over_a_long_window() {
+    let server = MockServer::start().await;
+    let percentiles = json!([25.0, 50.0, 75.0]);
+    answer(
+        &server,
+        "eth_feeHistory",
+        json!(["0x400", "latest", percentiles]),
+        history(1024, 2999),
+    )
+    .await;
+    answer(
+        &server,
+        "eth_feeHistory",
+        json!(["0x1dc", "0x7b7", percentiles]),
+        history(476, 1975),
+    )
+    .await;
+    let rpc = Web3::new(server.uri());
+
+    let stats = rpc
+        .fee_percentiles(1500, &[25.0, 50.0, 75.0])
+        .await
+        .unwrap();
+    assert_eq!((stats.oldest_block, stats.newest_block), (1500, 2999));
+    assert_eq!(stats.base_fees.len(), 1500);
+    assert_eq!(stats.base_fees[0], 510_000_000_000);
+    assert_eq!(stats.next_base_fee, 2_010_000_000_000);
+    assert_eq!(stats.trend, BaseFeeTrend::Rising);
+    let fees: Vec<u128> = stats.priority_fees.iter().map(|p| p.fee).collect();
+    assert_eq!(fees, [1_000_000_000, 2_000_000_000, 3_000_000_000]);
+    assert_eq!(stats.priority_fees[1].per_block.len(), 1500);
+
+    assert!(rpc.fee_percentiles(10, &[50.0, 25.0]).await.is_err());
+    assert!(rpc.fee_percentiles(0, &[50.0]).await.is_err());
+}
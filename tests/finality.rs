@@ -0,0 +1,171 @@
+//! Finality checks against a mock server, including a reorg between inclusion and
+//! finalization.
+
+use serde_json::{json, Value};
+use std::time::Duration;
+use web3_rpc::client::Error;
+use web3_rpc::types::H256;
+use web3_rpc::web3::Web3;
+use wiremock::matchers::{body_json, method};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+const TX: &str = "0x9aba9e042034f025ebbe9fddde27c2c091afe6bb24d1c6f6418abbb3103519ee";
+const RECEIPT: &str = include_str!("fixtures/receipt_failed.json");
+
+fn hash(byte: u8) -> H256 {
+    H256([byte; 32])
+}
+
+fn by_number(block: &str) -> Value {
+    json!({ "jsonrpc": "2.0", "method": "eth_getBlockByNumber", "params": [block, false], "id": "325" })
+}
+
+fn head(number: u64, hash: H256) -> Value {
+    json!({ "number": format!("{:#x}", number), "hash": hash })
+}
+
+/// Answers `request` with `result`, at most `times` times when given; earlier mounts win
+/// until they are used up.
+async fn answer(server: &MockServer, request: Value, result: Value, times: Option<u64>) {
+    let reply = json!({ "jsonrpc": "2.0", "id": request["id"], "result": result });
+    let mock = Mock::given(method("POST"))
+        .and(body_json(request))
+        .respond_with(ResponseTemplate::new(200).set_body_json(reply));
+    match times {
+        Some(n) => mock.up_to_n_times(n).mount(server).await,
+        None => mock.mount(server).await,
+    }
+}
+
+fn receipt(number: u64, block_hash: H256) -> Value {
+    let mut receipt: Value = serde_json::from_str(RECEIPT).unwrap();
+    receipt["blockNumber"] = json!(format!("{:#x}", number));
+    receipt["blockHash"] = json!(block_hash);
+    receipt
+}
+
+fn receipt_request() -> Value {
+    json!({ "jsonrpc": "2.0", "method": "eth_getTransactionReceipt", "params": [TX], "id": "328" })
+}
+
+#[tokio::test]
+async fn is_finalized_compares_with_the_finalized_tag() {
+    let server = MockServer::start().await;
+    answer(&server, by_number("finalized"), head(100, hash(0xf0)), None).await;
+    answer(&server, by_number("0x64"), head(100, hash(0xaa)), None).await;
+    answer(&server, by_number("0x65"), head(101, hash(0xbb)), None).await;
+    let by_hash = |h: H256| json!({ "jsonrpc": "2.0", "method": "eth_getBlockByHash", "params": [h, false], "id": "324" });
+    answer(&server, by_hash(hash(0xaa)), head(100, hash(0xaa)), None).await;
+    // an uncle at height 100 that lost the fork choice
+    answer(&server, by_hash(hash(0xcc)), head(100, hash(0xcc)), None).await;
+    answer(&server, by_hash(hash(0xdd)), Value::Null, None).await;
+
+    let rpc = Web3::new(server.uri());
+    assert!(rpc.is_finalized(100u64).await.unwrap());
+    assert!(!rpc.is_finalized(101u64).await.unwrap());
+    assert!(rpc.is_finalized(hash(0xaa)).await.unwrap());
+    assert!(!rpc.is_finalized(hash(0xcc)).await.unwrap());
+    assert!(!rpc.is_finalized(hash(0xdd)).await.unwrap());
+}
+
+#[tokio::test]
+async fn falls_back_to_confirmations_without_a_finalized_tag() {
+    let server = MockServer::start().await;
+    let reply = json!({ "jsonrpc": "2.0", "id": "325", "error": { "code": -32000, "message": "finalized block not found" } });
+    Mock::given(method("POST"))
+        .and(body_json(by_number("finalized")))
+        .respond_with(ResponseTemplate::new(200).set_body_json(reply))
+        .mount(&server)
+        .await;
+    answer(&server, by_number("latest"), head(1_000, hash(1)), None).await;
+
+    let rpc = Web3::new(server.uri());
+    let finality = rpc.finality().confirmations(12);
+    assert_eq!(finality.finalized_number().await.unwrap(), 988);
+    assert_eq!(
+        rpc.finality().finalized_number().await.unwrap(),
+        1_000 - web3_rpc::finality::DEFAULT_CONFIRMATIONS
+    );
+}
+
+#[tokio::test]
+async fn node_failures_do_not_fall_back() {
+    let server = MockServer::start().await;
+    let reply = json!({ "jsonrpc": "2.0", "id": "325", "error": { "code": -32603, "message": "internal error" } });
+    Mock::given(method("POST"))
+        .and(body_json(by_number("finalized")))
+        .respond_with(ResponseTemplate::new(200).set_body_json(reply))
+        .mount(&server)
+        .await;
+    answer(&server, by_number("latest"), head(1_000, hash(1)), None).await;
+    answer(&server, by_number("0x64"), head(100, hash(0xaa)), None).await;
+
+    let rpc = Web3::new(server.uri());
+    let err = rpc.finality().finalized_number().await.unwrap_err();
+    assert_eq!(err.current_context().code(), Some(-32603));
+    assert!(rpc.is_finalized(100u64).await.is_err());
+}
+
+#[tokio::test]
+async fn waits_out_a_reorg_before_finalization() {
+    let server = MockServer::start().await;
+    // included in block 100 (0xaa); a reorg then replaces 100 with 0xbb and the
+    // transaction is re-included in block 101 (0xcc)
+    answer(
+        &server,
+        receipt_request(),
+        receipt(100, hash(0xaa)),
+        Some(2),
+    )
+    .await;
+    answer(&server, receipt_request(), receipt(101, hash(0xcc)), None).await;
+    answer(
+        &server,
+        by_number("finalized"),
+        head(99, hash(0x99)),
+        Some(1),
+    )
+    .await;
+    answer(
+        &server,
+        by_number("finalized"),
+        head(100, hash(0xbb)),
+        Some(1),
+    )
+    .await;
+    answer(&server, by_number("finalized"), head(101, hash(0xcc)), None).await;
+    answer(&server, by_number("0x64"), head(100, hash(0xbb)), None).await;
+    answer(&server, by_number("0x65"), head(101, hash(0xcc)), None).await;
+
+    let receipt = Web3::new(server.uri())
+        .finality()
+        .poll_interval(Duration::from_millis(5))
+        .wait_for_finalization(TX.parse().unwrap(), Duration::from_secs(5))
+        .await
+        .unwrap();
+    assert_eq!(receipt.block_number, "0x65");
+    assert_eq!(receipt.block_hash, hash(0xcc));
+
+    let receipts = server
+        .received_requests()
+        .await
+        .unwrap()
+        .iter()
+        .filter(|r| r.body_json::<Value>().unwrap()["method"] == "eth_getTransactionReceipt")
+        .count();
+    assert_eq!(receipts, 3);
+}
+
+#[tokio::test]
+async fn times_out_while_pending() {
+    let server = MockServer::start().await;
+    answer(&server, receipt_request(), Value::Null, None).await;
+
+    let report = Web3::new(server.uri())
+        .finality()
+        .poll_interval(Duration::from_millis(5))
+        .wait_for_finalization(TX.parse().unwrap(), Duration::from_millis(30))
+        .await
+        .unwrap_err();
+    assert!(matches!(report.current_context(), Error::Timeout));
+}
@@ -0,0 +1,80 @@
+//! Queries to a node's `/graphql` endpoint.
+
+mod common;
+
+use common::fixture;
+use serde_json::json;
+use web3_rpc::client::Error;
+use web3_rpc::web3::Web3;
+use wiremock::matchers::{body_partial_json, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+#[tokio::test]
+async fn block_with_receipts_in_one_query() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/graphql"))
+        .and(body_partial_json(
+            json!({ "variables": { "number": 19_000_000 } }),
+        ))
+        .respond_with(ResponseTemplate::new(200).set_body_json(fixture("graphql_block")))
+        .expect(1)
+        .mount(&server)
+        .await;
+    // the JSON-RPC endpoint's path is replaced
+    let rpc = Web3::new(format!("{}/rpc", server.uri()));
+    let found = rpc
+        .gql_block_with_receipts(19_000_000)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(found.block.number, Some(19_000_000));
+    assert_eq!(found.receipts.len(), found.block.transactions.len());
+}
+
+#[tokio::test]
+async fn partial_data_comes_with_the_errors() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/graphql"))
+        .respond_with(ResponseTemplate::new(400).set_body_json(json!({
+            "errors": [{ "message": "missing trie node", "path": ["block", "account", "balance"] }],
+            "data": { "block": { "number": "0x10", "account": null } }
+        })))
+        .mount(&server)
+        .await;
+    let query = "{ block { number account(address: \"0x00000000219ab540356cbb839cbe05303d7705fa\") { balance } } }";
+    let report = Web3::new(server.uri())
+        .graphql(query, json!({}))
+        .await
+        .unwrap_err();
+    match report.current_context() {
+        Error::GraphQl { errors, data } => {
+            assert_eq!(errors[0].message, "missing trie node");
+            assert_eq!(data.as_ref().unwrap()["block"]["number"], "0x10");
+        }
+        other => panic!("unexpected error {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn missing_block_is_none() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/gql"))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_json(json!({ "data": { "block": null } })),
+        )
+        .mount(&server)
+        .await;
+    let rpc = Web3 {
+        client: Web3::new(server.uri())
+            .client
+            .with_graphql_url(format!("{}/gql", server.uri())),
+    };
+    assert!(rpc
+        .gql_block_with_receipts(u64::MAX >> 1)
+        .await
+        .unwrap()
+        .is_none());
+}
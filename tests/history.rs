@@ -0,0 +1,92 @@
+//! Balance history sampling against a mock server.
+
+use serde_json::{json, Value};
+use web3_rpc::client::Error;
+use web3_rpc::types::{Address, U256};
+use web3_rpc::web3::Web3;
+use wiremock::matchers::{body_json, method};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+const HOLDER: &str = "0x846c4dc9f4e2514206ef179eaa0bcfae007e37d2";
+const HEIGHTS: [u64; 5] = [17_000_000, 17_000_050, 17_000_100, 17_000_150, 17_000_160];
+
+/// One request per sampled height; the batch sends each request's position as its id.
+fn batch(method: &str, params: impl Fn(u64) -> Value) -> Value {
+    HEIGHTS
+        .iter()
+        .enumerate()
+        .map(|(i, n)| {
+            json!({ "jsonrpc": "2.0", "method": method, "params": params(*n), "id": i.to_string() })
+        })
+        .collect()
+}
+
+async fn mount(server: &MockServer, request: Value, reply: Value) {
+    Mock::given(method("POST"))
+        .and(body_json(request))
+        .respond_with(ResponseTemplate::new(200).set_body_json(reply))
+        .expect(1)
+        .mount(server)
+        .await;
+}
+
+fn replies(result: impl Fn(usize, u64) -> Value) -> Value {
+    HEIGHTS
+        .iter()
+        .enumerate()
+        .map(|(i, n)| json!({ "jsonrpc": "2.0", "id": i.to_string(), "result": result(i, *n) }))
+        .collect()
+}
+
+#[tokio::test]
+async fn samples_every_step_and_the_last_block() {
+    let server = MockServer::start().await;
+    mount(
+        &server,
+        batch("eth_getBalance", |n| json!([HOLDER, format!("{:#x}", n)])),
+        replies(|i, _| json!(format!("{:#x}", 1_000 * (i + 1)))),
+    )
+    .await;
+    mount(
+        &server,
+        batch("eth_getBlockByNumber", |n| {
+            json!([format!("{:#x}", n), false])
+        }),
+        replies(
+            |_, n| json!({ "number": format!("{:#x}", n), "timestamp": format!("{:#x}", n * 12) }),
+        ),
+    )
+    .await;
+
+    let history = Web3::new(server.uri())
+        .balance_history(HOLDER.parse().unwrap(), 17_000_000, 17_000_160, 50)
+        .await
+        .unwrap();
+    let expected: Vec<(u64, u64, U256)> = HEIGHTS
+        .iter()
+        .enumerate()
+        .map(|(i, n)| (*n, n * 12, U256::from(1_000 * (i as u64 + 1))))
+        .collect();
+    assert_eq!(history, expected);
+}
+
+#[tokio::test]
+async fn pruned_state_is_a_typed_error() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+            { "jsonrpc": "2.0", "id": "0", "error": { "code": -32000, "message": "missing trie node 6c4f1e (path )" } }
+        ])))
+        .mount(&server)
+        .await;
+
+    let holder: Address = HOLDER.parse().unwrap();
+    let report = Web3::new(server.uri())
+        .balance_history(holder, 1_000, 1_000, 1)
+        .await
+        .unwrap_err();
+    assert!(matches!(report.current_context(), Error::NotArchiveNode));
+    assert!(report
+        .downcast_ref::<web3_rpc::model::RpcErrorObject>()
+        .is_some());
+}
@@ -0,0 +1,148 @@
+//! Chunked eth_getLogs and the deposit scanner built on it, against a mock server.
+
+use serde_json::{json, Value};
+use web3_rpc::deposit::{DepositEvent, DEPOSIT_CONTRACT};
+use web3_rpc::model::LogFilter;
+use web3_rpc::web3::Web3;
+use wiremock::matchers::{body_json, method};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+const TOPIC: &str = "0x649bbc62d0e31342afea4e5cd82d4049e7e1ee912fc0889aa790803be39038c5";
+const DEPOSIT_LOG: &str = include_str!("fixtures/log_deposit.json");
+
+fn get_logs(from: u64, to: u64) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "method": "eth_getLogs",
+        "params": [{
+            "fromBlock": format!("{:#x}", from),
+            "toBlock": format!("{:#x}", to),
+            "address": [DEPOSIT_CONTRACT],
+            "topics": [[TOPIC]]
+        }],
+        "id": "338"
+    })
+}
+
+async fn mount(server: &MockServer, request: Value, reply: Value) {
+    Mock::given(method("POST"))
+        .and(body_json(request))
+        .respond_with(ResponseTemplate::new(200).set_body_json(reply))
+        .expect(1)
+        .mount(server)
+        .await;
+}
+
+async fn logs(server: &MockServer, from: u64, to: u64, logs: Value) {
+    mount(
+        server,
+        get_logs(from, to),
+        json!({ "jsonrpc": "2.0", "id": "338", "result": logs }),
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn chunks_and_halves_rejected_ranges() {
+    let server = MockServer::start().await;
+    let deposit: Value = serde_json::from_str(DEPOSIT_LOG).unwrap();
+    logs(&server, 100, 199, json!([])).await;
+    mount(
+        &server,
+        get_logs(200, 299),
+        json!({ "jsonrpc": "2.0", "id": "338", "error": { "code": -32000, "message": "query returned more than 10000 results" } }),
+    )
+    .await;
+    logs(&server, 200, 249, json!([deposit])).await;
+    logs(&server, 250, 299, json!([])).await;
+    logs(&server, 300, 320, json!([deposit])).await;
+
+    let filter = LogFilter::new()
+        .address(DEPOSIT_CONTRACT)
+        .topic(0, DepositEvent::topic());
+    let found = Web3::new(server.uri())
+        .get_logs_chunked(&filter, 100, 320, 100)
+        .await
+        .unwrap();
+    assert_eq!(found.len(), 2);
+}
+
+#[tokio::test]
+async fn scans_deposits() {
+    let server = MockServer::start().await;
+    let deposit: Value = serde_json::from_str(DEPOSIT_LOG).unwrap();
+    logs(&server, 17_000_000, 17_000_010, json!([deposit])).await;
+
+    let deposits = Web3::new(server.uri())
+        .scan_deposits(17_000_000, 17_000_010)
+        .await
+        .unwrap();
+    assert_eq!(deposits.len(), 1);
+    assert_eq!(deposits[0].amount, 32_000_000_000);
+    assert_eq!(deposits[0].index, 123_456);
+}
+
+#[tokio::test]
+async fn other_errors_are_not_retried() {
+    let server = MockServer::start().await;
+    mount(
+        &server,
+        get_logs(0, 9),
+        json!({ "jsonrpc": "2.0", "id": "338", "error": { "code": -32000, "message": "header not found" } }),
+    )
+    .await;
+    let report = Web3::new(server.uri())
+        .scan_deposits(0, 9)
+        .await
+        .unwrap_err();
+    assert_eq!(report.current_context().code(), Some(-32000));
+}
+
+#[tokio::test]
+async fn streams_logs_from_the_body() {
+    let server = MockServer::start().await;
+    let deposit: Value = serde_json::from_str(DEPOSIT_LOG).unwrap();
+    logs(&server, 0, 9, json!([deposit, deposit, deposit])).await;
+    mount(
+        &server,
+        get_logs(10, 19),
+        json!({ "jsonrpc": "2.0", "id": "338", "error": { "code": -32000, "message": "query returned more than 10000 results" } }),
+    )
+    .await;
+    Mock::given(method("POST"))
+        .and(body_json(get_logs(20, 29)))
+        .respond_with(ResponseTemplate::new(502).set_body_string("Bad Gateway"))
+        .mount(&server)
+        .await;
+
+    let rpc = Web3::new(server.uri());
+    let filter = |from, to| {
+        LogFilter::new()
+            .blocks(from, to)
+            .address(DEPOSIT_CONTRACT)
+            .topic(0, DepositEvent::topic())
+    };
+    let mut seen = Vec::new();
+    let count = rpc
+        .eth_get_logs_streaming(&filter(0, 9), |log| seen.push(log))
+        .await
+        .unwrap();
+    assert_eq!(count, 3);
+    assert_eq!(seen.len(), 3);
+    assert_eq!(seen[0].address, DEPOSIT_CONTRACT);
+
+    let report = rpc
+        .eth_get_logs_streaming(&filter(10, 19), |_| {})
+        .await
+        .unwrap_err();
+    assert_eq!(report.current_context().code(), Some(-32000));
+
+    let report = rpc
+        .eth_get_logs_streaming(&filter(20, 29), |_| {})
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        report.current_context(),
+        web3_rpc::client::Error::HttpError(_)
+    ));
+}
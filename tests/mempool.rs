@@ -0,0 +1,123 @@
+//! Mempool watching over a pending transaction filter against a mock server.
+
+use serde_json::{json, Value};
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use web3_rpc::types::{Address, H256};
+use web3_rpc::web3::Web3;
+use wiremock::matchers::method;
+use wiremock::{Mock, MockServer, Request, ResponseTemplate};
+
+const TX: &str = include_str!("fixtures/tx_pending.json");
+const WALLET: &str = "0x1111111111111111111111111111111111111111";
+const OTHER: &str = "0x2222222222222222222222222222222222222222";
+
+fn hash(n: u64) -> String {
+    format!("0x{:064x}", n)
+}
+
+/// Pending transactions 1 to 4: 1 from the wallet, 2 dropped before it is looked up, 3
+/// between others and 4 to the wallet.
+fn transaction(hash: &str) -> Value {
+    let parties = match u64::from_str_radix(&hash[2..], 16).unwrap() {
+        1 => (WALLET, OTHER),
+        3 => (OTHER, OTHER),
+        4 => (OTHER, WALLET),
+        _ => return Value::Null,
+    };
+    let mut tx: Value = serde_json::from_str(TX).unwrap();
+    tx["hash"] = json!(hash);
+    tx["from"] = json!(parties.0);
+    tx["to"] = json!(parties.1);
+    tx
+}
+
+/// The first filter announces 1, 2, 3 and 1 again, then expires; the second announces 1 and 4.
+struct Node {
+    filters: u64,
+    polls: u64,
+    looked_up: Vec<String>,
+    uninstalled: Vec<String>,
+}
+
+impl Node {
+    fn answer(&mut self, request: &Value) -> Value {
+        let result = match request["method"].as_str().unwrap() {
+            "eth_newPendingTransactionFilter" => {
+                self.filters += 1;
+                json!(format!("{:#x}", self.filters))
+            }
+            "eth_getFilterChanges" => {
+                self.polls += 1;
+                match (request["params"][0].as_str().unwrap(), self.polls) {
+                    ("0x1", 1) => json!([hash(1), hash(2), hash(3), hash(1)]),
+                    ("0x1", _) => {
+                        return json!({ "jsonrpc": "2.0", "id": request["id"], "error": { "code": -32000, "message": "filter not found" } })
+                    }
+                    ("0x2", _) if self.polls == 3 => json!([hash(1), hash(4)]),
+                    _ => json!([]),
+                }
+            }
+            "eth_getTransactionByHash" => {
+                let hash = request["params"][0].as_str().unwrap();
+                self.looked_up.push(hash.to_string());
+                transaction(hash)
+            }
+            "eth_uninstallFilter" => {
+                self.uninstalled
+                    .push(request["params"][0].as_str().unwrap().to_string());
+                json!(true)
+            }
+            other => panic!("unexpected method {}", other),
+        };
+        json!({ "jsonrpc": "2.0", "id": request["id"], "result": result })
+    }
+}
+
+async fn serving(node: Arc<Mutex<Node>>) -> MockServer {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(move |request: &Request| {
+            let request: Value = serde_json::from_slice(&request.body).unwrap();
+            let mut node = node.lock().unwrap();
+            let response = match request.as_array() {
+                Some(batch) => json!(batch.iter().map(|r| node.answer(r)).collect::<Vec<_>>()),
+                None => node.answer(&request),
+            };
+            ResponseTemplate::new(200).set_body_json(response)
+        })
+        .mount(&server)
+        .await;
+    server
+}
+
+#[tokio::test]
+async fn watched_transactions_once_each() {
+    let node = Arc::new(Mutex::new(Node {
+        filters: 0,
+        polls: 0,
+        looked_up: Vec::new(),
+        uninstalled: Vec::new(),
+    }));
+    let server = serving(node.clone()).await;
+    let rpc = Web3::new(server.uri());
+    let wallet: Address = WALLET.parse().unwrap();
+    let mut pending = rpc
+        .watch_mempool_for(HashSet::from([wallet]))
+        .poll_interval(Duration::from_millis(10));
+
+    let first = pending.next().await.unwrap();
+    assert_eq!(first.hash, hash(1).parse::<H256>().unwrap());
+    assert_eq!(first.from, wallet);
+    // the filter expired in between and was installed again
+    let second = pending.next().await.unwrap();
+    assert_eq!(second.hash, hash(4).parse::<H256>().unwrap());
+    assert_eq!(second.to, Some(wallet));
+    pending.close().await.unwrap();
+
+    let node = node.lock().unwrap();
+    assert_eq!(node.filters, 2);
+    assert_eq!(node.looked_up, [hash(1), hash(2), hash(3), hash(4)]);
+    assert_eq!(node.uninstalled, ["0x2"]);
+}
@@ -0,0 +1,159 @@
+//! Multicall3 aggregation and its JSON-RPC batch fallback against a mock server.
+
+use serde_json::{json, Value};
+use web3_rpc::abi::{self, Token};
+use web3_rpc::client::Error;
+use web3_rpc::multicall::{CallResult, MULTICALL3};
+use web3_rpc::types::{Address, U256};
+use web3_rpc::web3::Web3;
+use wiremock::matchers::{body_json, method};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+const TOKEN: &str = "0x4e910ef89c176119744977d90093858089454223";
+const MULTICALL3_HEX: &str = "0xca11bde05977b3631167028862be2a173976ca11";
+
+fn hex(data: &[u8]) -> String {
+    let digits: String = data.iter().map(|b| format!("{:02x}", b)).collect();
+    format!("0x{}", digits)
+}
+
+fn word(n: u64) -> Vec<u8> {
+    U256::from(n).to_be_bytes().to_vec()
+}
+
+async fn mount(server: &MockServer, request: Value, reply: Value) {
+    Mock::given(method("POST"))
+        .and(body_json(request))
+        .respond_with(ResponseTemplate::new(200).set_body_json(reply))
+        .expect(1)
+        .mount(server)
+        .await;
+}
+
+async fn mount_code(server: &MockServer, code: &str) {
+    mount(
+        server,
+        json!({ "jsonrpc": "2.0", "method": "eth_getCode", "params": [MULTICALL3_HEX, "latest"], "id": "318" }),
+        json!({ "jsonrpc": "2.0", "id": "318", "result": code }),
+    )
+    .await;
+}
+
+fn calls() -> (Address, Vec<u8>, Vec<u8>) {
+    let token: Address = TOKEN.parse().unwrap();
+    let decimals = abi::encode_call("decimals()", &[]);
+    let balance = abi::encode_call("balanceOf(address)", &[Token::Address(MULTICALL3)]);
+    (token, decimals, balance)
+}
+
+#[tokio::test]
+async fn aggregates_through_multicall3() {
+    let server = MockServer::start().await;
+    mount_code(&server, "0x6080604052").await;
+    let (token, decimals, balance) = calls();
+
+    let calldata = abi::encode_call(
+        "tryAggregate(bool,(address,bytes)[])",
+        &[
+            Token::Bool(false),
+            Token::Array(vec![
+                Token::Tuple(vec![Token::Address(token), Token::Bytes(decimals.clone())]),
+                Token::Tuple(vec![Token::Address(token), Token::Bytes(balance.clone())]),
+            ]),
+        ],
+    );
+    let returned = abi::encode(&[Token::Array(vec![
+        Token::Tuple(vec![Token::Bool(true), Token::Bytes(word(18))]),
+        Token::Tuple(vec![Token::Bool(false), Token::Bytes(vec![])]),
+    ])]);
+    mount(
+        &server,
+        json!({ "jsonrpc": "2.0", "method": "eth_call", "params": [{ "to": MULTICALL3_HEX, "data": hex(&calldata) }, "latest"], "id": "322" }),
+        json!({ "jsonrpc": "2.0", "id": "322", "result": hex(&returned) }),
+    )
+    .await;
+
+    let results = Web3::new(server.uri())
+        .multicall()
+        .add(token, decimals)
+        .add(token, balance)
+        .try_aggregate(true)
+        .await
+        .unwrap();
+    assert_eq!(
+        results,
+        vec![
+            CallResult {
+                success: true,
+                return_data: word(18)
+            },
+            CallResult {
+                success: false,
+                return_data: vec![]
+            },
+        ]
+    );
+}
+
+#[tokio::test]
+async fn falls_back_to_a_batch_without_multicall3() {
+    let server = MockServer::start().await;
+    mount_code(&server, "0x").await;
+    let (token, decimals, balance) = calls();
+
+    // answered out of order, as nodes may
+    let revert = "0x08c379a0";
+    mount(
+        &server,
+        json!([
+            { "jsonrpc": "2.0", "method": "eth_call", "params": [{ "to": TOKEN, "data": hex(&decimals) }, "latest"], "id": "0" },
+            { "jsonrpc": "2.0", "method": "eth_call", "params": [{ "to": TOKEN, "data": hex(&balance) }, "latest"], "id": "1" }
+        ]),
+        json!([
+            { "jsonrpc": "2.0", "id": "1", "error": { "code": 3, "message": "execution reverted", "data": revert } },
+            { "jsonrpc": "2.0", "id": "0", "result": hex(&word(18)) }
+        ]),
+    )
+    .await;
+
+    let rpc = Web3::new(server.uri());
+    let results = rpc
+        .multicall()
+        .add(token, decimals.clone())
+        .add(token, balance.clone())
+        .try_aggregate(true)
+        .await
+        .unwrap();
+    assert_eq!(results[0].return_data, word(18));
+    assert!(results[0].success);
+    assert!(!results[1].success);
+    assert_eq!(results[1].return_data, vec![0x08, 0xc3, 0x79, 0xa0]);
+}
+
+#[tokio::test]
+async fn batch_fallback_fails_fast_without_allow_failure() {
+    let server = MockServer::start().await;
+    mount_code(&server, "0x").await;
+    let (token, decimals, _) = calls();
+    mount(
+        &server,
+        json!([
+            { "jsonrpc": "2.0", "method": "eth_call", "params": [{ "to": TOKEN, "data": hex(&decimals) }, "latest"], "id": "0" }
+        ]),
+        json!([
+            { "jsonrpc": "2.0", "id": "0", "error": { "code": 3, "message": "execution reverted" } }
+        ]),
+    )
+    .await;
+
+    let report = Web3::new(server.uri())
+        .multicall()
+        .add(token, decimals)
+        .try_aggregate(false)
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        report.current_context(),
+        Error::JsonRpcError { code: 3, .. }
+    ));
+}
@@ -0,0 +1,242 @@
+//! Reads in native types against a mock node.
+
+mod common;
+
+use common::{answer, fixture};
+use serde_json::{json, Value};
+use web3_rpc::client::Error;
+use web3_rpc::model::Tag;
+use web3_rpc::types::H256;
+use web3_rpc::web3::Web3;
+use wiremock::matchers::{body_partial_json, method};
+use wiremock::{Mock, MockServer, Request, ResponseTemplate};
+
+const ADDRESS: &str = "0x00000000219ab540356cbb839cbe05303d7705fa";
+const TX: &str = "0x9aba9e042034f025ebbe9fddde27c2c091afe6bb24d1c6f6418abbb3103519ee";
+
+/// Answers `rpc_method` with `result`, echoing the request id.
+#[tokio::test]
+async fn quantities_are_parsed() {
+    let server = MockServer::start().await;
+    answer(&server, "eth_getBalance", json!("0x1bc16d674ec80000")).await;
+    answer(&server, "eth_getTransactionCount", json!("0x2a")).await;
+    answer(&server, "eth_blockNumber", json!("0x121eac0")).await;
+    answer(&server, "eth_gasPrice", json!("0x3b9aca0e")).await;
+    let rpc = Web3::new(server.uri());
+
+    assert_eq!(
+        rpc.balance(ADDRESS).await.unwrap(),
+        2_000_000_000_000_000_000
+    );
+    assert_eq!(rpc.nonce(ADDRESS).await.unwrap(), 42);
+    assert_eq!(rpc.block_number().await.unwrap(), 19_000_000);
+    assert_eq!(rpc.gas_price().await.unwrap(), 1_000_000_014);
+}
+
+#[tokio::test]
+async fn out_of_range_is_an_error() {
+    let server = MockServer::start().await;
+    answer(&server, "eth_blockNumber", json!("0x10000000000000000")).await;
+    let report = Web3::new(server.uri()).block_number().await.unwrap_err();
+    assert!(matches!(
+        report.current_context(),
+        Error::UnexpectedResponseFormat
+    ));
+}
+
+#[tokio::test]
+async fn null_results_are_none() {
+    let server = MockServer::start().await;
+    answer(&server, "eth_getBlockByNumber", Value::Null).await;
+    answer(&server, "eth_getTransactionByHash", Value::Null).await;
+    answer(&server, "eth_getTransactionReceipt", Value::Null).await;
+    let rpc = Web3::new(server.uri());
+
+    assert!(rpc.get_block(u64::MAX >> 1).await.unwrap().is_none());
+    assert!(rpc.get_transaction(TX).await.unwrap().is_none());
+    assert!(rpc.get_receipt(TX).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn blocks_by_number_tag_or_hash() {
+    let server = MockServer::start().await;
+    let block = fixture("block_cancun");
+    Mock::given(method("POST"))
+        .and(body_partial_json(
+            json!({ "method": "eth_getBlockByHash", "params": [block["hash"], true] }),
+        ))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_json(json!({ "jsonrpc": "2.0", "id": "324", "result": block })),
+        )
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(body_partial_json(
+            json!({ "method": "eth_getBlockByNumber", "params": ["finalized", false] }),
+        ))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_json(json!({ "jsonrpc": "2.0", "id": "325", "result": block })),
+        )
+        .mount(&server)
+        .await;
+    let rpc = Web3::new(server.uri());
+
+    let hash: H256 = block["hash"].as_str().unwrap().parse().unwrap();
+    let full = rpc.get_block_with_txs(hash).await.unwrap().unwrap();
+    assert_eq!(full.hash, Some(hash));
+    assert!(rpc.get_block(Tag::Finalized).await.unwrap().is_some());
+}
+
+#[tokio::test]
+async fn rpc_errors_stay_errors() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "jsonrpc": "2.0",
+            "id": "328",
+            "error": { "code": -32000, "message": "transaction indexing is in progress" }
+        })))
+        .mount(&server)
+        .await;
+    let report = Web3::new(server.uri()).get_receipt(TX).await.unwrap_err();
+    assert_eq!(report.current_context().code(), Some(-32000));
+}
+
+/// The eip1559 fixture and a receipt for it, both in block `block_hash`.
+fn mined(block_hash: &str) -> (Value, Value) {
+    let mut tx = fixture("tx_eip1559");
+    tx["blockHash"] = json!(block_hash);
+    let mut receipt = fixture("receipt_many_logs");
+    receipt["blockHash"] = json!(block_hash);
+    receipt["transactionHash"] = tx["hash"].clone();
+    (tx, receipt)
+}
+
+/// Answers batches with `answers`, one pair of results per batch in turn, the last one from
+/// then on.
+async fn batches(server: &MockServer, answers: Vec<(Value, Value)>) {
+    let served = std::sync::atomic::AtomicUsize::new(0);
+    Mock::given(method("POST"))
+        .respond_with(move |request: &Request| {
+            let batch: Vec<Value> = serde_json::from_slice(&request.body).unwrap();
+            assert_eq!(batch[0]["method"], "eth_getTransactionByHash");
+            assert_eq!(batch[1]["method"], "eth_getTransactionReceipt");
+            let n = served.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            let (tx, receipt) = answers[n.min(answers.len() - 1)].clone();
+            ResponseTemplate::new(200).set_body_json(json!([
+                { "jsonrpc": "2.0", "id": batch[0]["id"], "result": tx },
+                { "jsonrpc": "2.0", "id": batch[1]["id"], "result": receipt },
+            ]))
+        })
+        .mount(server)
+        .await;
+}
+
+const BLOCK_A: &str = "0xe812a49745d691961893d7cfd3902d78d710751bab872f12215ee23f27f3efa9";
+const BLOCK_B: &str = "0x4d10b4d2e0fb3ef6e1e0f0b5a2e1a8c3a4e4c2f2c1c7b1f2a1a0e2d7c9e6b8d1";
+
+#[tokio::test]
+async fn transaction_and_receipt_in_one_batch() {
+    let server = MockServer::start().await;
+    batches(&server, vec![mined(BLOCK_A)]).await;
+    let (tx, receipt) = Web3::new(server.uri())
+        .get_transaction_with_receipt(TX)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(receipt.unwrap().transaction_hash, tx.hash);
+    assert_eq!(server.received_requests().await.unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn pending_and_unknown_transactions() {
+    let server = MockServer::start().await;
+    let mut pending = fixture("tx_eip1559");
+    pending["blockHash"] = Value::Null;
+    pending["blockNumber"] = Value::Null;
+    pending["transactionIndex"] = Value::Null;
+    batches(&server, vec![(pending, Value::Null)]).await;
+    let (tx, receipt) = Web3::new(server.uri())
+        .get_transaction_with_receipt(TX)
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(tx.block_hash.is_none() && receipt.is_none());
+
+    let server = MockServer::start().await;
+    batches(&server, vec![(Value::Null, Value::Null)]).await;
+    let found = Web3::new(server.uri())
+        .get_transaction_with_receipt(TX)
+        .await
+        .unwrap();
+    assert!(found.is_none());
+}
+
+#[tokio::test]
+async fn a_reorg_is_read_again_once() {
+    let server = MockServer::start().await;
+    let (tx, _) = mined(BLOCK_A);
+    let (_, receipt) = mined(BLOCK_B);
+    batches(&server, vec![(tx, receipt), mined(BLOCK_B)]).await;
+    let (tx, _) = Web3::new(server.uri())
+        .get_transaction_with_receipt(TX)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(tx.block_hash.unwrap().to_string(), BLOCK_B);
+    assert_eq!(server.received_requests().await.unwrap().len(), 2);
+
+    let server = MockServer::start().await;
+    let (tx, _) = mined(BLOCK_A);
+    let (_, receipt) = mined(BLOCK_B);
+    batches(&server, vec![(tx, receipt)]).await;
+    let report = Web3::new(server.uri())
+        .get_transaction_with_receipt(TX)
+        .await
+        .unwrap_err();
+    match report.current_context() {
+        Error::ReceiptMismatch {
+            transaction_block,
+            receipt_block,
+            ..
+        } => {
+            assert_eq!(transaction_block.unwrap().to_string(), BLOCK_A);
+            assert_eq!(receipt_block.unwrap().to_string(), BLOCK_B);
+        }
+        other => panic!("unexpected error {:?}", other),
+    }
+    assert_eq!(server.received_requests().await.unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn endpoints_without_batches_get_separate_requests() {
+    let server = MockServer::start().await;
+    let (tx, receipt) = mined(BLOCK_A);
+    Mock::given(method("POST"))
+        .respond_with(move |request: &Request| {
+            let request: Value = serde_json::from_slice(&request.body).unwrap();
+            let result =
+                match request["method"].as_str() {
+                    Some("eth_getTransactionByHash") => tx.clone(),
+                    Some("eth_getTransactionReceipt") => receipt.clone(),
+                    _ => return ResponseTemplate::new(200).set_body_json(json!({
+                        "jsonrpc": "2.0",
+                        "id": null,
+                        "error": { "code": -32600, "message": "batch requests are not supported" }
+                    })),
+                };
+            ResponseTemplate::new(200)
+                .set_body_json(json!({ "jsonrpc": "2.0", "id": request["id"], "result": result }))
+        })
+        .mount(&server)
+        .await;
+    let (_, receipt) = Web3::new(server.uri())
+        .get_transaction_with_receipt(TX)
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(receipt.is_some());
+    assert_eq!(server.received_requests().await.unwrap().len(), 3);
+}
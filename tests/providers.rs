@@ -0,0 +1,128 @@
+//! Provider keys reach the endpoint where it expects them and nowhere else.
+
+use serde_json::json;
+use web3_rpc::provider::{KeyPlacement, Provider};
+use web3_rpc::web3::Web3;
+use wiremock::matchers::{header, method, path, query_param};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+const KEY: &str = "k3y-0123456789";
+
+fn block_number() -> ResponseTemplate {
+    ResponseTemplate::new(200)
+        .set_body_json(json!({ "jsonrpc": "2.0", "id": "327", "result": "0x10" }))
+}
+
+async fn call(provider: Provider) -> Web3 {
+    let web3 = Web3::builder().provider(provider).build().unwrap();
+    assert!(!web3.url().contains(KEY), "{}", web3.url());
+    assert_eq!(
+        web3.eth_block_number().await.unwrap().result.as_deref(),
+        Some("0x10")
+    );
+    web3
+}
+
+#[tokio::test]
+async fn key_placements() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path(format!("/v2/{}", KEY)))
+        .respond_with(block_number())
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path(format!("/{}/rpc", KEY)))
+        .respond_with(block_number())
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/query"))
+        .and(query_param("apikey", KEY))
+        .respond_with(block_number())
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/header"))
+        .and(header("x-api-key", KEY))
+        .respond_with(block_number())
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/bearer"))
+        .and(header("authorization", format!("Bearer {}", KEY).as_str()))
+        .respond_with(block_number())
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let uri = server.uri();
+    let web3 = call(Provider::custom(
+        format!("{}/v2", uri),
+        KEY,
+        KeyPlacement::Path,
+    ))
+    .await;
+    assert_eq!(web3.url(), format!("{}/v2/***", uri));
+    call(Provider::custom(
+        format!("{}/{{key}}/rpc", uri),
+        KEY,
+        KeyPlacement::Template,
+    ))
+    .await;
+    call(Provider::custom(
+        format!("{}/query", uri),
+        KEY,
+        KeyPlacement::Query("apikey".to_string()),
+    ))
+    .await;
+    call(Provider::custom(
+        format!("{}/header", uri),
+        KEY,
+        KeyPlacement::Header("x-api-key".to_string()),
+    ))
+    .await;
+    call(Provider::custom(
+        format!("{}/bearer", uri),
+        KEY,
+        KeyPlacement::Bearer,
+    ))
+    .await;
+}
+
+#[tokio::test]
+async fn key_stays_out_of_error_reports() {
+    // nothing listens on this port
+    let port = std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+    let provider = Provider::custom(
+        format!("http://127.0.0.1:{}", port),
+        KEY,
+        KeyPlacement::Path,
+    );
+    let web3 = Web3::builder().provider(provider.clone()).build().unwrap();
+    let err = web3.eth_block_number().await.unwrap_err();
+    let report = format!("{:?}", err);
+    assert!(
+        report.contains(&format!("127.0.0.1:{}", port)),
+        "{}",
+        report
+    );
+    assert!(!report.contains(KEY), "{}", report);
+    assert!(!format!("{:?}", provider).contains(KEY));
+
+    let err = Web3::builder()
+        .url("http://127.0.0.1:8545")
+        .provider(Provider::infura("mainnet", KEY))
+        .build()
+        .err()
+        .unwrap();
+    assert!(format!("{:?}", err).contains("`url` cannot be combined with `provider`"));
+}
@@ -0,0 +1,194 @@
+//! Simulating signed transactions before broadcast.
+
+use serde_json::{json, Value};
+use web3_rpc::client::Error;
+use web3_rpc::web3::Web3;
+use wiremock::matchers::{body_partial_json, method};
+use wiremock::{Mock, MockServer, Request, Respond, ResponseTemplate};
+
+/// The EIP-155 example: 1 ether to 0x3535...35 from 0x9d8a...4f with nonce 9.
+const RAW: &str = "0xf86c098504a817c800825208943535353535353535353535353535353535353535880de0b6b3a76400008025a028ef61340bd939bc2195fe537567866003e1a15d3c71ff63e1590620aa636276a067cbe9d8997f761aecb703304b3800ccf555c9f3dc64214b297fb1966a3b6d83";
+const TX: &str = "0x33469b22e9f636356c4160a87eb19df52b7412e8eac32a4a55ffe88ea8350788";
+/// `Error(string)` with "insufficient balance".
+const REVERT_DATA: &str = "0x08c379a000000000000000000000000000000000000000000000000000000000000000200000000000000000000000000000000000000000000000000000000000000014696e73756666696369656e742062616c616e6365000000000000000000000000";
+
+/// Answers with the request's id, like a node.
+fn ok(result: Value) -> impl Respond {
+    move |request: &Request| {
+        let request: Value = serde_json::from_slice(&request.body).unwrap();
+        ResponseTemplate::new(200)
+            .set_body_json(json!({ "jsonrpc": "2.0", "id": request["id"], "result": result }))
+    }
+}
+
+fn rpc_error(code: i64, message: &str, data: Option<&str>) -> ResponseTemplate {
+    ResponseTemplate::new(200).set_body_json(json!({
+        "jsonrpc": "2.0",
+        "id": "1",
+        "error": { "code": code, "message": message, "data": data }
+    }))
+}
+
+async fn answer(server: &MockServer, rpc_method: &str, response: impl Respond + 'static) {
+    Mock::given(method("POST"))
+        .and(body_partial_json(json!({ "method": rpc_method })))
+        .respond_with(response)
+        .mount(server)
+        .await;
+}
+
+async fn calls(server: &MockServer, rpc_method: &str) -> Vec<Value> {
+    server
+        .received_requests()
+        .await
+        .unwrap()
+        .iter()
+        .map(|r| serde_json::from_slice::<Value>(&r.body).unwrap())
+        .filter(|body| body["method"] == rpc_method)
+        .collect()
+}
+
+#[tokio::test]
+async fn broadcasts_after_a_successful_simulation() {
+    let server = MockServer::start().await;
+    answer(&server, "eth_call", ok(json!("0x"))).await;
+    answer(&server, "eth_sendRawTransaction", ok(json!(TX))).await;
+
+    let hash = Web3::new(server.uri())
+        .send_raw_transaction_checked(RAW, false)
+        .await
+        .unwrap();
+    assert_eq!(hash.to_string(), TX);
+
+    let call = &calls(&server, "eth_call").await[0];
+    assert_eq!(
+        call["params"],
+        json!([
+            {
+                "from": "0x9d8a62f656a8d1615c1294fd71e9cfb3e4855a4f",
+                "to": "0x3535353535353535353535353535353535353535",
+                "value": "0xde0b6b3a7640000",
+                "data": "0x"
+            },
+            "latest",
+            { "0x9d8a62f656a8d1615c1294fd71e9cfb3e4855a4f": { "nonce": "0x9" } }
+        ])
+    );
+    assert_eq!(
+        calls(&server, "eth_sendRawTransaction").await[0]["params"],
+        json!([RAW])
+    );
+}
+
+#[tokio::test]
+async fn revert_is_not_broadcast() {
+    let server = MockServer::start().await;
+    answer(
+        &server,
+        "eth_call",
+        rpc_error(3, "execution reverted", Some(REVERT_DATA)),
+    )
+    .await;
+    answer(&server, "eth_sendRawTransaction", ok(json!(TX))).await;
+
+    let err = Web3::new(server.uri())
+        .send_raw_transaction_checked(RAW, false)
+        .await
+        .unwrap_err();
+    match err.current_context() {
+        Error::WouldRevert { reason, data } => {
+            assert_eq!(reason.as_deref(), Some("insufficient balance"));
+            assert_eq!(data.as_deref(), Some(REVERT_DATA));
+        }
+        e => panic!("unexpected error {:?}", e),
+    }
+    assert!(calls(&server, "eth_sendRawTransaction").await.is_empty());
+}
+
+#[tokio::test]
+async fn reason_from_the_message_without_data() {
+    let server = MockServer::start().await;
+    answer(
+        &server,
+        "eth_call",
+        rpc_error(-32000, "execution reverted: paused", None),
+    )
+    .await;
+
+    let err = Web3::new(server.uri())
+        .send_raw_transaction_checked(RAW, false)
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(err.current_context(), Error::WouldRevert { reason: Some(r), data: None } if r == "paused"),
+        "{:?}",
+        err
+    );
+}
+
+#[tokio::test]
+async fn force_broadcasts_a_reverting_transaction() {
+    let server = MockServer::start().await;
+    answer(
+        &server,
+        "eth_call",
+        rpc_error(3, "execution reverted", Some(REVERT_DATA)),
+    )
+    .await;
+    answer(&server, "eth_sendRawTransaction", ok(json!(TX))).await;
+
+    let hash = Web3::new(server.uri())
+        .send_raw_transaction_checked(RAW, true)
+        .await
+        .unwrap();
+    assert_eq!(hash.to_string(), TX);
+    assert_eq!(calls(&server, "eth_sendRawTransaction").await.len(), 1);
+}
+
+#[tokio::test]
+async fn falls_back_when_overrides_are_rejected() {
+    let server = MockServer::start().await;
+    let with_overrides = |r: &Request| {
+        let body: Value = serde_json::from_slice(&r.body).unwrap();
+        body["params"].as_array().map(Vec::len) == Some(3)
+    };
+    Mock::given(method("POST"))
+        .and(body_partial_json(json!({ "method": "eth_call" })))
+        .and(with_overrides)
+        .respond_with(rpc_error(
+            -32602,
+            "too many arguments, want at most 2",
+            None,
+        ))
+        .mount(&server)
+        .await;
+    answer(&server, "eth_call", ok(json!("0x"))).await;
+    answer(&server, "eth_sendRawTransaction", ok(json!(TX))).await;
+
+    Web3::new(server.uri())
+        .send_raw_transaction_checked(RAW, false)
+        .await
+        .unwrap();
+    let eth_calls = calls(&server, "eth_call").await;
+    assert_eq!(eth_calls.len(), 2);
+    assert_eq!(eth_calls[1]["params"].as_array().unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn malformed_raw_transactions_are_rejected_locally() {
+    let server = MockServer::start().await;
+    let web3 = Web3::new(server.uri());
+    for raw in ["0x", "0x05c0", "f86c", &RAW[..40]] {
+        let err = web3
+            .send_raw_transaction_checked(raw, true)
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(err.current_context(), Error::InvalidTransactionRequest),
+            "{}: {:?}",
+            raw,
+            err
+        );
+    }
+    assert!(server.received_requests().await.unwrap().is_empty());
+}
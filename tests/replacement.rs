@@ -0,0 +1,119 @@
+//! Speeding up a pending transaction against a mock server.
+
+use serde_json::{json, Value};
+use std::sync::Mutex;
+use web3_rpc::client::Error;
+use web3_rpc::model::TransactionRequest;
+use web3_rpc::send::TransactionSigner;
+use web3_rpc::types::{Address, H256, U256};
+use web3_rpc::web3::Web3;
+use wiremock::matchers::{body_partial_json, method};
+use wiremock::{Mock, MockServer, Request, ResponseTemplate};
+
+const PENDING: &str = include_str!("fixtures/tx_pending.json");
+const REPLACEMENT: &str = "0x00000000000000000000000000000000000000000000000000000000000000aa";
+
+fn original() -> H256 {
+    let tx: Value = serde_json::from_str(PENDING).unwrap();
+    tx["hash"].as_str().unwrap().parse().unwrap()
+}
+
+/// Records what it was asked to sign.
+struct FakeSigner(Mutex<Option<TransactionRequest>>);
+
+impl TransactionSigner for FakeSigner {
+    fn address(&self) -> Address {
+        "0x4838b106fce9647bdf1e7877bf73ce8b0bad5f97"
+            .parse()
+            .unwrap()
+    }
+
+    fn sign_transaction(
+        &self,
+        request: &TransactionRequest,
+    ) -> Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>> {
+        *self.0.lock().unwrap() = Some(request.clone());
+        Ok(vec![0x02, 0xf8])
+    }
+}
+
+async fn serving(tx: Value, broadcast: Value) -> MockServer {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(body_partial_json(
+            json!({ "method": "eth_getTransactionByHash" }),
+        ))
+        .respond_with(move |request: &Request| {
+            let request: Value = serde_json::from_slice(&request.body).unwrap();
+            ResponseTemplate::new(200)
+                .set_body_json(json!({ "jsonrpc": "2.0", "id": request["id"], "result": tx }))
+        })
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(body_partial_json(
+            json!({ "method": "eth_sendRawTransaction" }),
+        ))
+        .respond_with(move |request: &Request| {
+            let request: Value = serde_json::from_slice(&request.body).unwrap();
+            let mut response = broadcast.clone();
+            response["jsonrpc"] = json!("2.0");
+            response["id"] = request["id"].clone();
+            ResponseTemplate::new(200).set_body_json(response)
+        })
+        .mount(&server)
+        .await;
+    server
+}
+
+#[tokio::test]
+async fn replacement_pays_more_with_the_same_nonce() {
+    let tx: Value = serde_json::from_str(PENDING).unwrap();
+    let server = serving(tx, json!({ "result": REPLACEMENT })).await;
+    let rpc = Web3::new(server.uri());
+    let signer = FakeSigner(Mutex::new(None));
+
+    // asking for less than the pools accept still bumps by 10%
+    let hash = rpc.speed_up(original(), 5, &signer).await.unwrap();
+    assert_eq!(hash, REPLACEMENT.parse().unwrap());
+    let signed = signer.0.lock().unwrap().clone().unwrap();
+    assert_eq!(signed.nonce, Some(U256::from(0x1a2b4u64)));
+    assert_eq!(signed.value, Some(U256::from(0x1bc16d674ec80000u64)));
+    assert_eq!(signed.max_fee_per_gas, Some(U256::from(33_000_000_000u64)));
+    assert_eq!(
+        signed.max_priority_fee_per_gas,
+        Some(U256::from(1_100_000_000u64))
+    );
+    assert_eq!(signed.chain_id, Some(U256::from(1u64)));
+}
+
+#[tokio::test]
+async fn mined_originals_are_left_alone() {
+    let mut tx: Value = serde_json::from_str(PENDING).unwrap();
+    tx["blockHash"] = json!(format!("0x{:064x}", 1));
+    tx["blockNumber"] = json!("0x10");
+    tx["transactionIndex"] = json!("0x0");
+    let server = serving(tx, json!({ "result": REPLACEMENT })).await;
+    let rpc = Web3::new(server.uri());
+    let signer = FakeSigner(Mutex::new(None));
+
+    let report = rpc.speed_up(original(), 20, &signer).await.unwrap_err();
+    assert!(
+        matches!(report.current_context(), Error::AlreadyMined { tx_hash } if *tx_hash == original())
+    );
+    assert!(signer.0.lock().unwrap().is_none());
+}
+
+#[tokio::test]
+async fn original_mined_before_the_broadcast() {
+    let tx: Value = serde_json::from_str(PENDING).unwrap();
+    let rejection = json!({ "error": { "code": -32000, "message": "nonce too low: next nonce 107189, tx nonce 107188" } });
+    let server = serving(tx, rejection).await;
+    let rpc = Web3::new(server.uri());
+    let signer = FakeSigner(Mutex::new(None));
+
+    let report = rpc.speed_up(original(), 20, &signer).await.unwrap_err();
+    assert!(
+        matches!(report.current_context(), Error::AlreadyMined { tx_hash } if *tx_hash == original())
+    );
+}
@@ -0,0 +1,81 @@
+//! Responses are matched to requests by id.
+
+use serde_json::{json, Value};
+use web3_rpc::client::Error;
+use web3_rpc::web3::Web3;
+use wiremock::matchers::method;
+use wiremock::{Mock, MockServer, Request, ResponseTemplate};
+
+fn batch(ids: &[usize]) -> Vec<Value> {
+    ids.iter()
+        .map(|n| json!({ "jsonrpc": "2.0", "method": "eth_getBalance", "params": [format!("{:#x}", n)], "id": "x" }))
+        .collect()
+}
+
+/// Answers a batch with each request's first param as its result, in the order of `order`.
+async fn answer_batch(server: &MockServer, order: &'static [usize]) {
+    Mock::given(method("POST"))
+        .respond_with(move |request: &Request| {
+            let requests: Vec<Value> = serde_json::from_slice(&request.body).unwrap();
+            let responses: Vec<Value> = order
+                .iter()
+                .map(|i| json!({ "jsonrpc": "2.0", "id": requests[*i]["id"], "result": requests[*i]["params"][0] }))
+                .collect();
+            ResponseTemplate::new(200).set_body_json(responses)
+        })
+        .mount(server)
+        .await;
+}
+
+#[tokio::test]
+async fn swapped_ids_are_rejected() {
+    let server = MockServer::start().await;
+    // a proxy handing eth_chainId's answer to eth_blockNumber
+    Mock::given(method("POST"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_json(json!({ "jsonrpc": "2.0", "id": "339", "result": "0x1" })),
+        )
+        .mount(&server)
+        .await;
+    let report = Web3::new(server.uri())
+        .eth_block_number()
+        .await
+        .unwrap_err();
+    match report.current_context() {
+        Error::IdMismatch { expected, got } => {
+            assert_eq!(expected, "327");
+            assert_eq!(got, "339");
+        }
+        other => panic!("unexpected error {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn out_of_order_batches_are_reassembled() {
+    let server = MockServer::start().await;
+    answer_batch(&server, &[2, 0, 3, 1]).await;
+    let results = Web3::new(server.uri())
+        .client
+        .call_batch::<String>(batch(&[10, 11, 12, 13]))
+        .await
+        .unwrap();
+    let results: Vec<_> = results.into_iter().map(|r| r.result.unwrap()).collect();
+    assert_eq!(results, ["0xa", "0xb", "0xc", "0xd"]);
+}
+
+#[tokio::test]
+async fn batch_ids_must_be_distinct() {
+    let server = MockServer::start().await;
+    answer_batch(&server, &[0, 1, 1]).await;
+    let report = Web3::new(server.uri())
+        .client
+        .call_batch::<String>(batch(&[10, 11, 12]))
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(report.current_context(), Error::IdMismatch { got, .. } if got == "1"),
+        "{:?}",
+        report
+    );
+}
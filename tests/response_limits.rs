@@ -0,0 +1,95 @@
+//! Oversized responses are abandoned while streaming instead of being buffered.
+
+use serde_json::json;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+use tokio::task::JoinHandle;
+use web3_rpc::client::Error;
+use web3_rpc::web3::Web3;
+use wiremock::matchers::method;
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+const LIMIT: usize = 1024 * 1024;
+const CHUNK: usize = 64 * 1024;
+
+/// Streams an endless chunked body (a getLogs result that never ends) to the first
+/// connection and returns how many bytes it managed to write before the client hung up.
+async fn serve_endless() -> (String, JoinHandle<usize>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let handle = tokio::spawn(async move {
+        let (mut socket, _) = listener.accept().await.unwrap();
+        let mut request = vec![0u8; 4096];
+        let _ = socket.read(&mut request).await.unwrap();
+        socket
+            .write_all(b"HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ntransfer-encoding: chunked\r\n\r\n")
+            .await
+            .unwrap();
+        let mut written = 0;
+        let mut chunk = format!("{:x}\r\n", CHUNK).into_bytes();
+        chunk.resize(chunk.len() + CHUNK, b' ');
+        chunk.extend_from_slice(b"\r\n");
+        // a gigabyte, far more than any client buffer, unless the client stops reading
+        while written < 1024 * 1024 * 1024 {
+            if socket.write_all(&chunk).await.is_err() {
+                break;
+            }
+            written += CHUNK;
+        }
+        written
+    });
+    (url, handle)
+}
+
+#[tokio::test]
+async fn streamed_bodies_stop_at_the_limit() {
+    let (url, server) = serve_endless().await;
+    let rpc = Web3::builder()
+        .url(url)
+        .max_response_size(LIMIT)
+        .build()
+        .unwrap();
+    let report = rpc.eth_block_number().await.unwrap_err();
+    match report.current_context() {
+        Error::ResponseTooLarge { limit, method } => {
+            assert_eq!(*limit, LIMIT);
+            assert_eq!(method, "eth_blockNumber");
+        }
+        other => panic!("unexpected error {:?}", other),
+    }
+    drop(rpc);
+    // whatever sits in socket buffers aside, the server was cut off early
+    let written = server.await.unwrap();
+    assert!(written < 64 * LIMIT, "server wrote {} bytes", written);
+}
+
+#[tokio::test]
+async fn declared_length_is_checked_before_reading() {
+    let server = MockServer::start().await;
+    let logs: Vec<_> = (0..2_000).map(|i| json!({ "logIndex": i })).collect();
+    let body = json!({ "jsonrpc": "2.0", "id": "1", "result": logs });
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(body))
+        .mount(&server)
+        .await;
+
+    let rpc = Web3::builder()
+        .url(server.uri())
+        .max_response_size(16 * 1024)
+        .build()
+        .unwrap();
+    let report = rpc.call_raw("eth_getLogs", json!([{}])).await.unwrap_err();
+    assert!(matches!(
+        report.current_context(),
+        Error::ResponseTooLarge { limit: 16384, method } if method == "eth_getLogs"
+    ));
+
+    // one call that knowingly pulls more, the original client keeps its limit
+    let r = rpc
+        .with_max_response_size(1024 * 1024)
+        .call_raw("eth_getLogs", json!([{}]))
+        .await
+        .unwrap();
+    assert_eq!(r.result.unwrap().as_array().unwrap().len(), 2_000);
+    assert!(rpc.call_raw("eth_getLogs", json!([{}])).await.is_err());
+}
@@ -0,0 +1,170 @@
+//! Revert reasons decoded from the error shapes different nodes and providers return.
+
+mod common;
+
+use common::fixture;
+use serde_json::{json, Value};
+use web3_rpc::abi::{self, Token};
+use web3_rpc::client::{DecodedRevert, Error};
+use web3_rpc::model::{JsonRpcErrorMessage, RpcErrorObject, TransactionRequest};
+use web3_rpc::types::{Address, U256};
+use web3_rpc::web3::Web3;
+use wiremock::matchers::method;
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+async fn reverting_node(response: Value) -> (MockServer, Web3) {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(response))
+        .mount(&server)
+        .await;
+    let web3 = Web3::new(server.uri());
+    (server, web3)
+}
+
+fn call() -> TransactionRequest {
+    TransactionRequest::new()
+        .to("0x4e910ef89c176119744977d90093858089454223"
+            .parse()
+            .unwrap())
+        .data("0xf2fde38b")
+}
+
+async fn reverted_call(response: Value) -> error_stack::Report<Error> {
+    let (_server, web3) = reverting_node(response).await;
+    web3.eth_call_request(&call()).await.unwrap_err()
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn custom_revert(data: &[u8]) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": "322",
+        "error": { "code": 3, "message": "execution reverted", "data": format!("0x{}", hex(data)) }
+    })
+}
+
+#[tokio::test]
+async fn same_reason_from_every_provider() {
+    for name in ["revert_geth", "revert_nethermind", "revert_infura"] {
+        let err = reverted_call(fixture(name)).await;
+        assert!(err.current_context().is_execution_reverted(), "{}", name);
+        let object = err.downcast_ref::<RpcErrorObject>().unwrap();
+        assert!(
+            object.revert_data().unwrap().starts_with("0x08c379a0"),
+            "{}",
+            name
+        );
+        assert_eq!(
+            object.revert_reason().as_deref(),
+            Some("Ownable: caller is not the owner"),
+            "{}",
+            name
+        );
+        assert_eq!(object.panic_code(), None, "{}", name);
+        assert_eq!(
+            err.current_context().revert_reason().as_deref(),
+            Some("Ownable: caller is not the owner"),
+            "{}",
+            name
+        );
+    }
+}
+
+#[tokio::test]
+async fn panic_code() {
+    // Panic(0x11), arithmetic overflow
+    let data = format!("0x4e487b71{:064x}", 0x11);
+    let err = reverted_call(json!({
+        "jsonrpc": "2.0",
+        "id": "322",
+        "error": { "code": 3, "message": "execution reverted", "data": data }
+    }))
+    .await;
+    let object = err.downcast_ref::<RpcErrorObject>().unwrap();
+    assert_eq!(object.panic_code(), Some(0x11));
+    assert_eq!(object.revert_reason().as_deref(), Some("panic 0x11"));
+}
+
+#[test]
+fn reason_without_data() {
+    let object = JsonRpcErrorMessage {
+        message: "execution reverted: paused".to_string(),
+        code: -32000,
+        data: None,
+    };
+    assert_eq!(object.revert_data(), None);
+    assert_eq!(object.revert_reason().as_deref(), Some("paused"));
+
+    let empty = JsonRpcErrorMessage {
+        message: "execution reverted".to_string(),
+        code: 3,
+        data: Some(json!("0x")),
+    };
+    assert_eq!(empty.revert_data(), Some("0x"));
+    assert_eq!(empty.revert_reason(), None);
+}
+
+#[test]
+fn addresses_in_other_errors_are_not_revert_data() {
+    let object = JsonRpcErrorMessage {
+        message: "insufficient funds for gas * price + value: address 0x4838b106fce9647bdf1e7877bf73ce8b0bad5f97 have 0 want 1".to_string(),
+        code: -32000,
+        data: None,
+    };
+    assert_eq!(object.revert_data(), None);
+    assert_eq!(object.revert_reason(), None);
+}
+
+#[tokio::test]
+async fn registered_custom_errors_are_decoded_on_every_path() {
+    let holder: Address = "0x4838b106fce9647bdf1e7877bf73ce8b0bad5f97"
+        .parse()
+        .unwrap();
+    let data = abi::encode_call(
+        "InsufficientBalance(address,uint256)",
+        &[Token::Address(holder), Token::Uint(U256::from(100u64))],
+    );
+    let (_server, web3) = reverting_node(custom_revert(&data)).await;
+    // registered on one handle, seen by its clones
+    web3.clone()
+        .register_error("InsufficientBalance(address,uint256)")
+        .unwrap();
+    let expected = "InsufficientBalance(0x4838B106FCe9647Bdf1E7877BF73cE8B0BAD5f97, 100)";
+
+    let err = web3.eth_call_request(&call()).await.unwrap_err();
+    assert_eq!(err.downcast_ref::<DecodedRevert>().unwrap().0, expected);
+    assert!(format!("{:?}", err).contains(expected));
+
+    let err = web3.eth_estimate_gas_request(&call()).await.unwrap_err();
+    assert_eq!(err.downcast_ref::<DecodedRevert>().unwrap().0, expected);
+
+    // the EIP-155 example transaction
+    let raw = "0xf86c098504a817c800825208943535353535353535353535353535353535353535880de0b6b3a76400008025a028ef61340bd939bc2195fe537567866003e1a15d3c71ff63e1590620aa636276a067cbe9d8997f761aecb703304b3800ccf555c9f3dc64214b297fb1966a3b6d83";
+    let err = web3
+        .send_raw_transaction_checked(raw, false)
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(err.current_context(), Error::WouldRevert { reason: Some(r), .. } if r == expected),
+        "{:?}",
+        err
+    );
+}
+
+#[tokio::test]
+async fn unknown_custom_errors_keep_selector_and_args() {
+    let data = abi::encode_call("Paused(uint256)", &[Token::Uint(U256::from(7u64))]);
+    let err = reverted_call(custom_revert(&data)).await;
+    assert_eq!(
+        err.downcast_ref::<DecodedRevert>().unwrap().0,
+        format!(
+            "unknown error 0x{} with args 0x{:064x}",
+            hex(&abi::selector("Paused(uint256)")),
+            7
+        )
+    );
+}
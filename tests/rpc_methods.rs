@@ -0,0 +1,1535 @@
+//! Wire-level tests for every public RPC method against a local mock server: each test
+//! pins the exact request body and checks the response is decoded.
+
+use serde_json::{json, Value};
+use web3_rpc::abi::Token;
+use web3_rpc::client::Error;
+use web3_rpc::model::{
+    AccountProof, Block, BlockId, FeeHistory, JsonRpcErrorMessage, Log, LogFilter, Receipt,
+    StateOverride, SyncStatus, Tag, Transaction, TransactionRequest,
+};
+use web3_rpc::types::{Address, StorageSlot, H256, U256};
+use web3_rpc::web3::Web3;
+use wiremock::matchers::{body_json, method};
+use wiremock::{Mock, MockServer, Request, ResponseTemplate};
+
+const ADDRESS: &str = "0x4e910ef89c176119744977d90093858089454223";
+const HASH: &str = "0xe812a49745d691961893d7cfd3902d78d710751bab872f12215ee23f27f3efa9";
+
+const BLOCK: &str = include_str!("fixtures/block_post_merge.json");
+const TRANSACTION: &str = include_str!("fixtures/tx_eip1559.json");
+const RECEIPT: &str = include_str!("fixtures/receipt_failed.json");
+const FEE_HISTORY: &str = include_str!("fixtures/fee_history.json");
+const PROOF: &str = include_str!("fixtures/proof_account.json");
+const DEPOSIT_LOG: &str = include_str!("fixtures/log_deposit.json");
+
+/// Starts a server that answers `request` (matched on the whole body) with `result`.
+/// Any other body gets a 404, and the server verifies on drop that it saw `request`
+/// exactly once.
+async fn expect(request: Value, result: Value) -> (MockServer, Web3) {
+    let reply = json!({ "jsonrpc": "2.0", "id": request["id"], "result": result });
+    respond(request, reply).await
+}
+
+async fn respond(request: Value, reply: Value) -> (MockServer, Web3) {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(body_json(request))
+        .respond_with(ResponseTemplate::new(200).set_body_json(reply))
+        .expect(1)
+        .mount(&server)
+        .await;
+    let rpc = Web3::new(server.uri());
+    (server, rpc)
+}
+
+fn payload(method: &str, params: Value, id: &str) -> Value {
+    json!({ "jsonrpc": "2.0", "method": method, "params": params, "id": id })
+}
+
+fn fixture(text: &str) -> Value {
+    serde_json::from_str(text).unwrap()
+}
+
+fn address() -> Address {
+    ADDRESS.parse().unwrap()
+}
+
+fn hash() -> H256 {
+    HASH.parse().unwrap()
+}
+
+fn call_request() -> TransactionRequest {
+    TransactionRequest::new()
+        .from(
+            "0x846c4dc9f4e2514206ef179eaa0bcfae007e37d2"
+                .parse()
+                .unwrap(),
+        )
+        .to(address())
+        .gas(21_000u64)
+        .value(1u64)
+        .data("0x313ce567")
+}
+
+fn call_request_json() -> Value {
+    json!({
+        "from": "0x846c4dc9f4e2514206ef179eaa0bcfae007e37d2",
+        "to": ADDRESS,
+        "gas": "0x5208",
+        "value": "0x1",
+        "data": "0x313ce567"
+    })
+}
+
+#[tokio::test]
+async fn web3_client_version() {
+    let (_server, rpc) = expect(
+        payload("web3_clientVersion", json!([]), "101"),
+        json!("Geth/v1.13.14-stable/linux-amd64/go1.21.7"),
+    )
+    .await;
+    let r = rpc.web3_client_version().await.unwrap();
+    assert_eq!(
+        r.result.unwrap(),
+        "Geth/v1.13.14-stable/linux-amd64/go1.21.7"
+    );
+}
+
+#[tokio::test]
+async fn web3_sha3() {
+    let digest = "0x47173285a8d7341e5e972fc677286384f802f8ef42a5ec5f03bbfa254cb01fad";
+    let (_server, rpc) = expect(
+        payload("web3_sha3", json!(["0x68656c6c6f20776f726c64"]), "102"),
+        json!(digest),
+    )
+    .await;
+    let r = rpc.web3_sha3("0x68656c6c6f20776f726c64").await.unwrap();
+    assert_eq!(r.result.unwrap(), digest);
+}
+
+#[tokio::test]
+async fn request_and_call_raw() {
+    let (_server, rpc) = expect(payload("eth_chainId", json!([]), "1"), json!("0x1")).await;
+    let r: web3_rpc::model::JsonRpcResult<U256> =
+        rpc.request("eth_chainId", json!([])).await.unwrap();
+    assert_eq!(r.result.unwrap(), U256::from(1u64));
+
+    let (_server, rpc) = expect(
+        payload("eth_getLogs", json!([{ "fromBlock": "latest" }]), "1"),
+        json!([]),
+    )
+    .await;
+    let r = rpc
+        .call_raw("eth_getLogs", json!([{ "fromBlock": "latest" }]))
+        .await
+        .unwrap();
+    assert_eq!(r.result.unwrap(), json!([]));
+}
+
+#[tokio::test]
+async fn net_methods() {
+    let (_server, rpc) = expect(payload("net_version", json!([]), "401"), json!("1")).await;
+    assert_eq!(rpc.net_version().await.unwrap().result.unwrap(), "1");
+
+    let (_server, rpc) = expect(payload("net_listening", json!([]), "402"), json!(true)).await;
+    assert!(rpc.net_listening().await.unwrap().result.unwrap());
+
+    let (_server, rpc) = expect(payload("net_peerCount", json!([]), "303"), json!(25)).await;
+    assert_eq!(rpc.net_peer_count().await.unwrap().result.unwrap(), 25);
+}
+
+#[tokio::test]
+async fn eth_node_status() {
+    let (_server, rpc) = expect(
+        payload("eth_protocolVersion", json!([]), "304"),
+        json!("0x41"),
+    )
+    .await;
+    assert_eq!(
+        rpc.eth_protocol_version().await.unwrap().result.unwrap(),
+        "0x41"
+    );
+
+    let (_server, rpc) = expect(payload("eth_syncing", json!([]), "305"), json!(false)).await;
+    assert_eq!(
+        rpc.eth_syncing().await.unwrap().result.unwrap(),
+        SyncStatus::NotSyncing
+    );
+
+    let (_server, rpc) = expect(
+        payload("eth_syncing", json!([]), "305"),
+        json!({ "startingBlock": "0x0", "currentBlock": "0x10", "highestBlock": "0x20" }),
+    )
+    .await;
+    match rpc.eth_syncing().await.unwrap().result.unwrap() {
+        SyncStatus::Syncing(progress) => assert_eq!(progress.blocks_remaining(), 16),
+        other => panic!("expected syncing, got {:?}", other),
+    }
+
+    let (_server, rpc) = expect(payload("eth_coinbase", json!([]), "306"), json!(ADDRESS)).await;
+    assert_eq!(rpc.eth_coinbase().await.unwrap().result.unwrap(), ADDRESS);
+
+    let (_server, rpc) = expect(payload("eth_mining", json!([]), "307"), json!(false)).await;
+    assert!(!rpc.eth_mining().await.unwrap().result.unwrap());
+
+    let (_server, rpc) = expect(payload("eth_hashrate", json!([]), "308"), json!("0x0")).await;
+    assert_eq!(rpc.eth_hashrate().await.unwrap().result.unwrap(), "0x0");
+
+    let (_server, rpc) = expect(payload("eth_chainId", json!([]), "339"), json!("0x7a69")).await;
+    assert_eq!(
+        rpc.eth_chain_id().await.unwrap().result.unwrap(),
+        U256::from(31_337u64)
+    );
+
+    let (_server, rpc) = expect(payload("eth_accounts", json!([]), "310"), json!([ADDRESS])).await;
+    assert_eq!(
+        rpc.eth_accounts().await.unwrap().result.unwrap(),
+        vec![ADDRESS]
+    );
+
+    let (_server, rpc) = expect(payload("eth_getCompilers", json!([]), "333"), json!([])).await;
+    assert!(rpc
+        .eth_get_compilers()
+        .await
+        .unwrap()
+        .result
+        .unwrap()
+        .is_empty());
+}
+
+#[tokio::test]
+async fn eth_gas_price_and_block_number() {
+    let (_server, rpc) = expect(
+        payload("eth_gasPrice", json!([]), "309"),
+        json!("0x3b9aca00"),
+    )
+    .await;
+    assert_eq!(
+        rpc.eth_gas_price().await.unwrap().result.unwrap(),
+        "0x3b9aca00"
+    );
+
+    let (_server, rpc) = expect(
+        payload("eth_gasPrice", json!([]), "309"),
+        json!("0x3b9aca00"),
+    )
+    .await;
+    assert_eq!(
+        rpc.eth_gas_price_typed().await.unwrap().result.unwrap(),
+        U256::from(1_000_000_000u64)
+    );
+
+    let (_server, rpc) = expect(
+        payload("eth_blockNumber", json!([]), "327"),
+        json!("0x1036640"),
+    )
+    .await;
+    assert_eq!(
+        rpc.eth_block_number().await.unwrap().result.unwrap(),
+        "0x1036640"
+    );
+
+    let (_server, rpc) = expect(
+        payload("eth_blockNumber", json!([]), "327"),
+        json!("0x1036640"),
+    )
+    .await;
+    assert_eq!(
+        rpc.eth_block_number_typed().await.unwrap().result.unwrap(),
+        U256::from(17_000_000u64)
+    );
+}
+
+#[tokio::test]
+async fn eth_account_state() {
+    let (_server, rpc) = expect(
+        payload("eth_getBalance", json!([ADDRESS, "latest"]), "311"),
+        json!("0xde0b6b3a7640000"),
+    )
+    .await;
+    let r = rpc.eth_get_balance(ADDRESS, None).await.unwrap();
+    assert_eq!(r.result.unwrap(), "0xde0b6b3a7640000");
+
+    let (_server, rpc) = expect(
+        payload(
+            "eth_getBalance",
+            json!([ADDRESS, { "blockHash": HASH }]),
+            "311",
+        ),
+        json!("0x0"),
+    )
+    .await;
+    let r = rpc
+        .eth_get_balance_at_block(address(), hash())
+        .await
+        .unwrap();
+    assert_eq!(r.result.unwrap(), "0x0");
+
+    let (_server, rpc) = expect(
+        payload("eth_getBalance", json!([ADDRESS, "safe"]), "311"),
+        json!("0xde0b6b3a7640000"),
+    )
+    .await;
+    let r = rpc
+        .eth_get_balance_typed(ADDRESS, Some(Tag::Safe))
+        .await
+        .unwrap();
+    assert_eq!(r.result.unwrap(), U256::from(1_000_000_000_000_000_000u64));
+
+    let slot = "0x0000000000000000000000000000000000000000000000000000000000000001";
+    let (_server, rpc) = expect(
+        payload(
+            "eth_getStorageAt",
+            json!([ADDRESS, "0x0", "finalized"]),
+            "312",
+        ),
+        json!(slot),
+    )
+    .await;
+    let r = rpc
+        .eth_get_storage_at(ADDRESS, "0x0", Some(Tag::Finalized))
+        .await
+        .unwrap();
+    assert_eq!(r.result.unwrap(), slot);
+
+    let (_server, rpc) = expect(
+        payload(
+            "eth_getStorageAt",
+            json!([ADDRESS, "0x0", "0x1036640"]),
+            "312",
+        ),
+        json!(slot),
+    )
+    .await;
+    let r = rpc
+        .eth_get_storage_at_block(ADDRESS, "0x0", 17_000_000u64)
+        .await
+        .unwrap();
+    assert_eq!(r.result.unwrap(), slot);
+
+    let (_server, rpc) = expect(
+        payload(
+            "eth_getTransactionCount",
+            json!([ADDRESS, "pending"]),
+            "313",
+        ),
+        json!("0x2a"),
+    )
+    .await;
+    let r = rpc
+        .eth_get_transaction_count(ADDRESS, Some(Tag::Pending))
+        .await
+        .unwrap();
+    assert_eq!(r.result.unwrap(), "0x2a");
+
+    let (_server, rpc) = expect(
+        payload(
+            "eth_getTransactionCount",
+            json!([ADDRESS, "earliest"]),
+            "313",
+        ),
+        json!("0x0"),
+    )
+    .await;
+    let r = rpc
+        .eth_get_transaction_count_at_block(ADDRESS, Tag::Earliest)
+        .await
+        .unwrap();
+    assert_eq!(r.result.unwrap(), "0x0");
+
+    let (_server, rpc) = expect(
+        payload("eth_getTransactionCount", json!([ADDRESS, "latest"]), "313"),
+        json!("0x2a"),
+    )
+    .await;
+    let r = rpc
+        .eth_get_transaction_count_typed(ADDRESS, None)
+        .await
+        .unwrap();
+    assert_eq!(r.result.unwrap(), U256::from(42u64));
+
+    let (_server, rpc) = expect(
+        payload("eth_getCode", json!([ADDRESS, "latest"]), "318"),
+        json!("0x6080"),
+    )
+    .await;
+    let r = rpc.eth_get_code(ADDRESS, None).await.unwrap();
+    assert_eq!(r.result.unwrap(), "0x6080");
+
+    let (_server, rpc) = expect(
+        payload(
+            "eth_getCode",
+            json!([ADDRESS, { "blockHash": HASH }]),
+            "318",
+        ),
+        json!("0x"),
+    )
+    .await;
+    let r = rpc.eth_get_code_at_block(ADDRESS, hash()).await.unwrap();
+    assert_eq!(r.result.unwrap(), "0x");
+}
+
+#[tokio::test]
+async fn default_tag_fills_omitted_tags() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(|request: &Request| {
+            let request: Value = serde_json::from_slice(&request.body).unwrap();
+            ResponseTemplate::new(200)
+                .set_body_json(json!({ "jsonrpc": "2.0", "id": request["id"], "result": "0x0" }))
+        })
+        .mount(&server)
+        .await;
+    let latest = Web3::new(server.uri());
+    let finalized = latest.with_default_tag(Tag::Finalized);
+
+    finalized.eth_get_balance(ADDRESS, None).await.unwrap();
+    finalized
+        .eth_get_balance_typed(ADDRESS, None)
+        .await
+        .unwrap();
+    finalized
+        .eth_get_storage_at(ADDRESS, "0x0", None)
+        .await
+        .unwrap();
+    finalized
+        .eth_get_transaction_count(ADDRESS, None)
+        .await
+        .unwrap();
+    finalized
+        .eth_get_transaction_count_typed(ADDRESS, None)
+        .await
+        .unwrap();
+    finalized.eth_get_code(ADDRESS, None).await.unwrap();
+    finalized.eth_call_request(&call_request()).await.unwrap();
+    // explicit tags win, and the original handle keeps `latest`
+    finalized
+        .eth_get_code(ADDRESS, Some(Tag::Pending))
+        .await
+        .unwrap();
+    latest.eth_get_code(ADDRESS, None).await.unwrap();
+    Web3::builder()
+        .url(server.uri())
+        .default_tag(Tag::Safe)
+        .build()
+        .unwrap()
+        .eth_get_code(ADDRESS, None)
+        .await
+        .unwrap();
+
+    let blocks: Vec<Value> = server
+        .received_requests()
+        .await
+        .unwrap()
+        .iter()
+        .map(|r| {
+            let body: Value = serde_json::from_slice(&r.body).unwrap();
+            body["params"].as_array().unwrap().last().unwrap().clone()
+        })
+        .collect();
+    assert_eq!(
+        blocks,
+        [
+            "finalized",
+            "finalized",
+            "finalized",
+            "finalized",
+            "finalized",
+            "finalized",
+            "finalized",
+            "pending",
+            "latest",
+            "safe"
+        ]
+    );
+}
+
+#[tokio::test]
+async fn storage_slot_params() {
+    let word = "0x000000000000000000000000000000000000000000000000000000000000002a";
+    let mut bytes = [0u8; 32];
+    bytes[31] = 0x2a;
+    let cases: [(StorageSlot, &str); 5] = [
+        (42u64.into(), "0x2a"),
+        (U256::from(42u64).into(), "0x2a"),
+        (bytes.into(), "0x2a"),
+        ("0x2a".into(), "0x2a"),
+        (word.into(), word),
+    ];
+    for (slot, expected) in cases {
+        let (_server, rpc) = expect(
+            payload(
+                "eth_getStorageAt",
+                json!([ADDRESS, expected, "latest"]),
+                "312",
+            ),
+            json!(word),
+        )
+        .await;
+        rpc.eth_get_storage_at(ADDRESS, slot, None).await.unwrap();
+    }
+}
+
+#[tokio::test]
+async fn read_mapping_slot() {
+    // slot 0 mapping, address(0) key: keccak256 of two zero words
+    let slot = "0xad3228b676f7d3cd4284a5443f17f1962b36e491b30a40b2405849e597ba5fb5";
+    let word = "0x00000000000000000000000000000000000000000000000000000000000f4240";
+    let (_server, rpc) = expect(
+        payload("eth_getStorageAt", json!([ADDRESS, slot, "latest"]), "312"),
+        json!(word),
+    )
+    .await;
+    let r = rpc
+        .read_mapping_slot(
+            address(),
+            U256::ZERO,
+            &Token::Address(Address::ZERO),
+            Tag::Latest,
+        )
+        .await
+        .unwrap();
+    assert_eq!(U256::from_be_bytes(r.0), U256::from(1_000_000u64));
+}
+
+#[tokio::test]
+async fn eth_block_counts() {
+    let (_server, rpc) = expect(
+        payload("eth_getBlockTransactionCountByHash", json!([HASH]), "314"),
+        json!("0xa3"),
+    )
+    .await;
+    let r = rpc
+        .eth_get_block_transaction_count_by_hash(HASH)
+        .await
+        .unwrap();
+    assert_eq!(r.result.unwrap(), "0xa3");
+
+    let (_server, rpc) = expect(
+        payload(
+            "eth_getBlockTransactionCountByNumber",
+            json!(["0x1036640"]),
+            "315",
+        ),
+        json!("0xa3"),
+    )
+    .await;
+    let r = rpc
+        .eth_get_block_transaction_count_by_number("0x1036640")
+        .await
+        .unwrap();
+    assert_eq!(r.result.unwrap(), "0xa3");
+
+    let (_server, rpc) = expect(
+        payload("eth_getUncleCountByBlockHash", json!([HASH]), "316"),
+        json!("0x0"),
+    )
+    .await;
+    let r = rpc.eth_get_uncle_count_by_block_hash(HASH).await.unwrap();
+    assert_eq!(r.result.unwrap(), "0x0");
+
+    let (_server, rpc) = expect(
+        payload("eth_getUncleCountByBlockNumber", json!(["latest"]), "317"),
+        json!("0x0"),
+    )
+    .await;
+    let r = rpc
+        .eth_get_uncle_count_by_block_number("latest")
+        .await
+        .unwrap();
+    assert_eq!(r.result.unwrap(), "0x0");
+}
+
+#[tokio::test]
+async fn eth_blocks() {
+    let expected: Block = serde_json::from_str(BLOCK).unwrap();
+
+    let (_server, rpc) = expect(
+        payload("eth_getBlockByHash", json!([HASH, false]), "324"),
+        fixture(BLOCK),
+    )
+    .await;
+    let r = rpc.eth_get_block_by_hash(HASH, false).await.unwrap();
+    assert_eq!(r.result.unwrap(), expected);
+
+    let (_server, rpc) = expect(
+        payload("eth_getBlockByNumber", json!(["0x1036640", true]), "325"),
+        fixture(BLOCK),
+    )
+    .await;
+    let r = rpc
+        .eth_get_block_by_number("0x1036640", true)
+        .await
+        .unwrap();
+    assert_eq!(r.result.unwrap(), expected);
+
+    let (_server, rpc) = expect(
+        payload("eth_getBlockByHash", json!([HASH, true]), "324"),
+        fixture(BLOCK),
+    )
+    .await;
+    let r = rpc.eth_get_block(hash(), true).await.unwrap();
+    assert_eq!(r.result.unwrap(), expected);
+
+    let (_server, rpc) = expect(
+        payload("eth_getBlockByNumber", json!(["0x1036640", false]), "325"),
+        fixture(BLOCK),
+    )
+    .await;
+    let r = rpc.eth_get_block(17_000_000u64, false).await.unwrap();
+    assert_eq!(r.result.unwrap(), expected);
+
+    let (_server, rpc) = expect(
+        payload("eth_getBlockByNumber", json!(["latest", false]), "325"),
+        json!(null),
+    )
+    .await;
+    let r = rpc
+        .eth_get_block(BlockId::from(Tag::Latest), false)
+        .await
+        .unwrap();
+    assert_eq!(r.result, None);
+
+    let (_server, rpc) = expect(
+        payload(
+            "eth_getUncleByBlockHashAndIndex",
+            json!([HASH, "0x0"]),
+            "331",
+        ),
+        fixture(BLOCK),
+    )
+    .await;
+    let r = rpc
+        .eth_get_uncle_by_block_hash_and_index(HASH, "0x0")
+        .await
+        .unwrap();
+    assert_eq!(r.result.unwrap(), expected);
+
+    let (_server, rpc) = expect(
+        payload(
+            "eth_getUncleByBlockNumberAndIndex",
+            json!(["0x1036640", "0x0"]),
+            "332",
+        ),
+        fixture(BLOCK),
+    )
+    .await;
+    let r = rpc
+        .eth_get_uncle_by_block_number_and_index("0x1036640", "0x0")
+        .await
+        .unwrap();
+    assert_eq!(r.result.unwrap(), expected);
+}
+
+#[tokio::test]
+async fn numeric_block_and_index_params() {
+    let (_server, rpc) = expect(
+        payload(
+            "eth_getBlockTransactionCountByNumber",
+            json!(["0xf"]),
+            "315",
+        ),
+        json!("0xa3"),
+    )
+    .await;
+    rpc.eth_get_block_transaction_count_by_number(15)
+        .await
+        .unwrap();
+
+    let (_server, rpc) = expect(
+        payload("eth_getUncleCountByBlockNumber", json!(["0x0"]), "317"),
+        json!("0x0"),
+    )
+    .await;
+    rpc.eth_get_uncle_count_by_block_number(0).await.unwrap();
+
+    let (_server, rpc) = expect(
+        payload("eth_getBlockByNumber", json!(["finalized", false]), "325"),
+        json!(null),
+    )
+    .await;
+    rpc.eth_get_block_by_number(Tag::Finalized, false)
+        .await
+        .unwrap();
+
+    let (_server, rpc) = expect(
+        payload("eth_getBlockByNumber", json!(["0x1036640", false]), "325"),
+        json!(null),
+    )
+    .await;
+    rpc.eth_get_block_by_number(17_000_000, false)
+        .await
+        .unwrap();
+
+    let (_server, rpc) = expect(
+        payload(
+            "eth_getTransactionByBlockHashAndIndex",
+            json!([HASH, "0xf"]),
+            "329",
+        ),
+        json!(null),
+    )
+    .await;
+    rpc.eth_get_transaction_by_block_hash_and_index(HASH, 15)
+        .await
+        .unwrap();
+
+    let (_server, rpc) = expect(
+        payload(
+            "eth_getTransactionByBlockNumberAndIndex",
+            json!(["0x1036640", "0x0"]),
+            "330",
+        ),
+        json!(null),
+    )
+    .await;
+    rpc.eth_get_transaction_by_block_number_and_index(17_000_000, 0)
+        .await
+        .unwrap();
+
+    let (_server, rpc) = expect(
+        payload(
+            "eth_getUncleByBlockHashAndIndex",
+            json!([HASH, "0x1"]),
+            "331",
+        ),
+        json!(null),
+    )
+    .await;
+    rpc.eth_get_uncle_by_block_hash_and_index(HASH, 1)
+        .await
+        .unwrap();
+
+    let (_server, rpc) = expect(
+        payload(
+            "eth_getUncleByBlockNumberAndIndex",
+            json!(["latest", "0xa"]),
+            "332",
+        ),
+        json!(null),
+    )
+    .await;
+    rpc.eth_get_uncle_by_block_number_and_index(Tag::Latest, 10)
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn eth_transactions_and_receipts() {
+    let transaction: Transaction = serde_json::from_str(TRANSACTION).unwrap();
+    let receipt: Receipt = serde_json::from_str(RECEIPT).unwrap();
+
+    let (_server, rpc) = expect(
+        payload("eth_getTransactionByHash", json!([HASH]), "326"),
+        fixture(TRANSACTION),
+    )
+    .await;
+    let r = rpc.eth_get_transaction_by_hash(HASH).await.unwrap();
+    assert_eq!(r.result.unwrap(), transaction);
+
+    let (_server, rpc) = expect(
+        payload(
+            "eth_getTransactionByBlockHashAndIndex",
+            json!([HASH, "0x1"]),
+            "329",
+        ),
+        fixture(TRANSACTION),
+    )
+    .await;
+    let r = rpc
+        .eth_get_transaction_by_block_hash_and_index(HASH, "0x1")
+        .await
+        .unwrap();
+    assert_eq!(r.result.unwrap(), transaction);
+
+    let (_server, rpc) = expect(
+        payload(
+            "eth_getTransactionByBlockNumberAndIndex",
+            json!(["0x1036640", "0x1"]),
+            "330",
+        ),
+        fixture(TRANSACTION),
+    )
+    .await;
+    let r = rpc
+        .eth_get_transaction_by_block_number_and_index("0x1036640", "0x1")
+        .await
+        .unwrap();
+    assert_eq!(r.result.unwrap(), transaction);
+
+    let (_server, rpc) = expect(
+        payload("eth_getTransactionReceipt", json!([HASH]), "328"),
+        fixture(RECEIPT),
+    )
+    .await;
+    let r = rpc.eth_get_transaction_receipt(hash()).await.unwrap();
+    assert_eq!(r.result.unwrap(), receipt);
+
+    let (_server, rpc) = expect(
+        payload("eth_getTransactionReceipt", json!([HASH]), "328"),
+        json!(null),
+    )
+    .await;
+    let r = rpc.eth_get_transaction_receipt(HASH).await.unwrap();
+    assert_eq!(r.result, None);
+}
+
+#[tokio::test]
+async fn eth_signing_and_sending() {
+    let signature = "0xa3f20717a250c2b0b729b7e5becbff67fdaef7e0699da4de7ca5895b02a170a12d887fd3b17bfdce3481f10bea41f45ba9f709d39ce8325427b57afcfc994cee1b";
+    let (_server, rpc) = expect(
+        payload("eth_sign", json!([ADDRESS, "0xdeadbeaf"]), "319"),
+        json!(signature),
+    )
+    .await;
+    let r = rpc.eth_sign(ADDRESS, "0xdeadbeaf").await.unwrap();
+    assert_eq!(r.result.unwrap(), signature);
+
+    let (_server, rpc) = expect(
+        payload("eth_sendTransaction", json!([call_request_json()]), "320"),
+        json!(HASH),
+    )
+    .await;
+    let r = rpc.eth_send_transaction(&call_request()).await.unwrap();
+    assert_eq!(r.result.unwrap(), HASH);
+
+    let (_server, rpc) = expect(
+        payload("eth_signTransaction", json!([call_request_json()]), "334"),
+        json!("0x02f8"),
+    )
+    .await;
+    let r = rpc.eth_sign_transaction(&call_request()).await.unwrap();
+    assert_eq!(r.result.unwrap(), "0x02f8");
+
+    let (_server, rpc) = expect(
+        payload("eth_sendRawTransaction", json!(["0x02f8"]), "321"),
+        json!(HASH),
+    )
+    .await;
+    let r = rpc.eth_send_raw_transaction("0x02f8").await.unwrap();
+    assert_eq!(r.result.unwrap(), HASH);
+}
+
+#[tokio::test]
+async fn eth_calls() {
+    let word = "0x0000000000000000000000000000000000000000000000000000000000000012";
+    let raw = json!({ "to": ADDRESS, "data": "0x313ce567" });
+
+    let (_server, rpc) = expect(
+        payload("eth_call", json!([raw, "latest"]), "322"),
+        json!(word),
+    )
+    .await;
+    assert_eq!(
+        rpc.eth_call(raw.clone()).await.unwrap().result.unwrap(),
+        word
+    );
+
+    let (_server, rpc) = expect(
+        payload("eth_call", json!([raw, "0x1036640"]), "322"),
+        json!(word),
+    )
+    .await;
+    let r = rpc
+        .eth_call_at_block(raw.clone(), 17_000_000u64)
+        .await
+        .unwrap();
+    assert_eq!(r.result.unwrap(), word);
+
+    let (_server, rpc) = expect(
+        payload("eth_call", json!([call_request_json(), "latest"]), "322"),
+        json!(word),
+    )
+    .await;
+    let r = rpc.eth_call_request(&call_request()).await.unwrap();
+    assert_eq!(r.result.unwrap(), word);
+
+    let (_server, rpc) = expect(
+        payload(
+            "eth_call",
+            json!([call_request_json(), { "blockHash": HASH }]),
+            "322",
+        ),
+        json!(word),
+    )
+    .await;
+    let r = rpc
+        .eth_call_request_at_block(&call_request(), hash())
+        .await
+        .unwrap();
+    assert_eq!(r.result.unwrap(), word);
+
+    let overrides = StateOverride::new().balance(address(), 1u64);
+    let (_server, rpc) = expect(
+        payload(
+            "eth_call",
+            json!([call_request_json(), "latest", { ADDRESS: { "balance": "0x1" } }]),
+            "322",
+        ),
+        json!(word),
+    )
+    .await;
+    let r = rpc
+        .eth_call_with_overrides(&call_request(), Tag::Latest, &overrides)
+        .await
+        .unwrap();
+    assert_eq!(r.result.unwrap(), word);
+}
+
+#[tokio::test]
+async fn eth_fee_history_and_proofs() {
+    let (_server, rpc) = expect(
+        payload(
+            "eth_feeHistory",
+            json!(["0x4", "latest", [10.0, 50.0, 90.0]]),
+            "336",
+        ),
+        fixture(FEE_HISTORY),
+    )
+    .await;
+    let r = rpc
+        .eth_fee_history(4, Tag::Latest, &[10.0, 50.0, 90.0])
+        .await
+        .unwrap();
+    let expected: FeeHistory = serde_json::from_str(FEE_HISTORY).unwrap();
+    assert_eq!(r.result.unwrap(), expected);
+
+    let slot: H256 = "0x56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421"
+        .parse()
+        .unwrap();
+    let (_server, rpc) = expect(
+        payload("eth_getProof", json!([ADDRESS, [slot], "0x1036640"]), "337"),
+        fixture(PROOF),
+    )
+    .await;
+    let r = rpc
+        .eth_get_proof(ADDRESS, &[slot], 17_000_000u64)
+        .await
+        .unwrap();
+    let expected: AccountProof = serde_json::from_str(PROOF).unwrap();
+    assert_eq!(r.result.unwrap(), expected);
+}
+
+#[tokio::test]
+async fn eth_get_logs() {
+    let topic: H256 = "0x649bbc62d0e31342afea4e5cd82d4049e7e1ee912fc0889aa790803be39038c5"
+        .parse()
+        .unwrap();
+    let filter = json!({
+        "fromBlock": "0x1036640",
+        "toBlock": "latest",
+        "address": [ADDRESS],
+        "topics": [null, [topic, HASH]]
+    });
+    let (_server, rpc) = expect(
+        payload("eth_getLogs", json!([filter]), "338"),
+        json!([fixture(DEPOSIT_LOG)]),
+    )
+    .await;
+    let filter = LogFilter::new()
+        .from_block(Tag::Number(17_000_000))
+        .to_block(Tag::Latest)
+        .address(address())
+        .topic(1, topic)
+        .topic(1, hash());
+    let r = rpc.eth_get_logs(&filter).await.unwrap();
+    let expected: Log = serde_json::from_str(DEPOSIT_LOG).unwrap();
+    assert_eq!(r.result.unwrap(), vec![expected]);
+}
+
+#[tokio::test]
+async fn eth_gas_estimation() {
+    let raw = json!({ "to": ADDRESS, "data": "0x313ce567" });
+
+    let (_server, rpc) = expect(
+        payload("eth_estimateGas", json!([raw]), "323"),
+        json!("0x5208"),
+    )
+    .await;
+    assert_eq!(
+        rpc.eth_estimate_gas(raw.clone())
+            .await
+            .unwrap()
+            .result
+            .unwrap(),
+        "0x5208"
+    );
+
+    let (_server, rpc) = expect(
+        payload("eth_estimateGas", json!([raw, "pending"]), "323"),
+        json!("0x5208"),
+    )
+    .await;
+    let r = rpc
+        .eth_estimate_gas_at_block(raw.clone(), Tag::Pending)
+        .await
+        .unwrap();
+    assert_eq!(r.result.unwrap(), "0x5208");
+
+    let (_server, rpc) = expect(
+        payload("eth_estimateGas", json!([raw]), "323"),
+        json!("0x5208"),
+    )
+    .await;
+    let r = rpc.eth_estimate_gas_typed(raw.clone()).await.unwrap();
+    assert_eq!(r.result.unwrap(), U256::from(21_000u64));
+
+    let (_server, rpc) = expect(
+        payload("eth_estimateGas", json!([call_request_json()]), "323"),
+        json!("0x5208"),
+    )
+    .await;
+    let r = rpc.eth_estimate_gas_request(&call_request()).await.unwrap();
+    assert_eq!(r.result.unwrap(), "0x5208");
+
+    let (_server, rpc) = expect(
+        payload(
+            "eth_estimateGas",
+            json!([call_request_json(), "0x1036640"]),
+            "323",
+        ),
+        json!("0x5208"),
+    )
+    .await;
+    let r = rpc
+        .eth_estimate_gas_request_at_block(&call_request(), 17_000_000u64)
+        .await
+        .unwrap();
+    assert_eq!(r.result.unwrap(), "0x5208");
+
+    let slot = "0x0000000000000000000000000000000000000000000000000000000000000003";
+    let (_server, rpc) = expect(
+        payload(
+            "eth_createAccessList",
+            json!([call_request_json(), "latest"]),
+            "335",
+        ),
+        json!({
+            "accessList": [{ "address": ADDRESS, "storageKeys": [slot] }],
+            "gasUsed": "0x6b3c"
+        }),
+    )
+    .await;
+    let r = rpc
+        .eth_create_access_list(&call_request(), Tag::Latest)
+        .await
+        .unwrap()
+        .result
+        .unwrap();
+    assert_eq!(r.gas_used, U256::from(0x6b3cu64));
+    assert_eq!(r.access_list.0[0].address, address());
+    assert_eq!(
+        r.access_list.0[0].storage_keys,
+        vec![slot.parse::<H256>().unwrap()]
+    );
+    assert_eq!(r.error, None);
+}
+
+#[tokio::test]
+async fn error_object_response() {
+    let request = payload("eth_call", json!([call_request_json(), "latest"]), "322");
+    let reply = json!({
+        "jsonrpc": "2.0",
+        "id": "322",
+        "error": { "code": 3, "message": "execution reverted", "data": "0x08c379a0" }
+    });
+    let (_server, rpc) = respond(request, reply).await;
+    let report = rpc.eth_call_request(&call_request()).await.unwrap_err();
+    match report.current_context() {
+        Error::JsonRpcError {
+            code,
+            message,
+            data,
+        } => {
+            assert_eq!(*code, 3);
+            assert_eq!(message, "execution reverted");
+            assert_eq!(data, &Some(json!("0x08c379a0")));
+        }
+        other => panic!("expected a JSON-RPC error, got {:?}", other),
+    }
+    let object = report.downcast_ref::<JsonRpcErrorMessage<Value>>().unwrap();
+    assert_eq!(object.revert_data(), Some("0x08c379a0"));
+}
+
+#[cfg(feature = "debug-api")]
+#[tokio::test]
+async fn debug_methods() {
+    let overrides = StateOverride::new().balance(address(), 1u64);
+    let (_server, rpc) = expect(
+        payload(
+            "debug_traceCall",
+            json!([
+                call_request_json(),
+                "latest",
+                { "tracer": "callTracer", "stateOverrides": { ADDRESS: { "balance": "0x1" } } }
+            ]),
+            "501",
+        ),
+        json!({ "type": "CALL", "gasUsed": "0x5208" }),
+    )
+    .await;
+    let r = rpc
+        .debug_trace_call(
+            &call_request(),
+            Tag::Latest,
+            Some(json!({ "tracer": "callTracer" })),
+            Some(&overrides),
+        )
+        .await
+        .unwrap();
+    assert_eq!(r.result.unwrap()["type"], "CALL");
+
+    let (_server, rpc) = expect(
+        payload("debug_traceTransaction", json!([HASH, {}]), "502"),
+        json!({ "gas": 21000, "failed": false, "returnValue": "", "structLogs": [] }),
+    )
+    .await;
+    let r = rpc.debug_trace_transaction(HASH, None).await.unwrap();
+    assert_eq!(r.result.unwrap()["gas"], 21000);
+
+    let (_server, rpc) = expect(payload("debug_getBadBlocks", json!([]), "503"), json!([])).await;
+    let r = rpc.debug_get_bad_blocks().await.unwrap();
+    assert!(r.result.unwrap().is_empty());
+
+    // the second block has a gas limit no Block can hold, and is still listed
+    let mut odd = fixture(BLOCK);
+    odd["gasLimit"] = json!("0x10000000000000000");
+    let (_server, rpc) = expect(
+        payload("debug_getBadBlocks", json!([]), "503"),
+        json!([
+            { "hash": fixture(BLOCK)["hash"], "block": fixture(BLOCK), "rlp": "0xf90200" },
+            { "hash": HASH, "block": odd, "rlp": "0xf90201" }
+        ]),
+    )
+    .await;
+    let bad = rpc.debug_get_bad_blocks().await.unwrap().result.unwrap();
+    assert_eq!(bad.len(), 2);
+    let block: Block = serde_json::from_str(BLOCK).unwrap();
+    assert_eq!(bad[0].block().unwrap(), block);
+    assert_eq!(bad[1].hash, HASH.parse::<H256>().unwrap());
+    assert_eq!(bad[1].rlp, "0xf90201");
+    let report = bad[1].block().unwrap_err();
+    assert!(matches!(
+        report.current_context(),
+        Error::FailedToDeserialize
+    ));
+
+    let (_server, rpc) = expect(
+        payload(
+            "debug_traceBlockByNumber",
+            json!(["0x10", { "tracer": "callTracer" }]),
+            "505",
+        ),
+        json!([
+            { "txHash": HASH, "result": { "type": "CALL" } },
+            { "result": { "type": "CREATE" } }
+        ]),
+    )
+    .await;
+    let mut traces = Vec::new();
+    let count = rpc
+        .debug_trace_block_streaming(16u64, Some(json!({ "tracer": "callTracer" })), |trace| {
+            traces.push(trace)
+        })
+        .await
+        .unwrap();
+    assert_eq!(count, 2);
+    assert_eq!(traces[0].tx_hash, Some(hash()));
+    assert_eq!(traces[1].tx_hash, None);
+    assert_eq!(traces[1].result["type"], "CREATE");
+
+    let (_server, rpc) = expect(
+        payload("debug_traceBlockByHash", json!([HASH, {}]), "504"),
+        json!([]),
+    )
+    .await;
+    let count = rpc
+        .debug_trace_block_streaming(hash(), None, |_| {})
+        .await
+        .unwrap();
+    assert_eq!(count, 0);
+}
+
+#[cfg(feature = "trace-api")]
+#[tokio::test]
+async fn trace_methods() {
+    let trace = json!([{ "action": { "callType": "call" }, "type": "call" }]);
+    let (_server, rpc) = expect(
+        payload("trace_transaction", json!([HASH]), "601"),
+        trace.clone(),
+    )
+    .await;
+    let r = rpc.trace_transaction(HASH).await.unwrap();
+    assert_eq!(r.result.unwrap().len(), 1);
+
+    let (_server, rpc) = expect(payload("trace_block", json!(["0x1036640"]), "602"), trace).await;
+    let r = rpc.trace_block(17_000_000u64).await.unwrap();
+    assert_eq!(r.result.unwrap()[0]["type"], "call");
+
+    let entry = json!({
+        "action": {
+            "callType": "staticcall",
+            "from": "0x7a250d5630b4cf539739df2c5dacb4c659f2488d",
+            "gas": "0x1d4c0",
+            "input": "0x0902f1ac",
+            "to": ADDRESS,
+            "value": "0x0"
+        },
+        "blockHash": fixture(BLOCK)["hash"],
+        "blockNumber": 17000000,
+        "result": { "gasUsed": "0x9c8", "output": "0x" },
+        "subtraces": 0,
+        "traceAddress": [1, 10],
+        "transactionHash": HASH,
+        "transactionPosition": 6,
+        "type": "call"
+    });
+    let (_server, rpc) = expect(
+        payload("trace_get", json!([HASH, ["0x1", "0xa"]]), "603"),
+        entry.clone(),
+    )
+    .await;
+    let trace = rpc.trace_get(HASH, &[1, 10]).await.unwrap().result.unwrap();
+    assert_eq!(trace.trace_address, vec![1, 10]);
+    assert_eq!(trace.transaction_position, Some(6));
+    assert_eq!(trace.action["callType"], "staticcall");
+    assert_eq!(trace.error, None);
+    assert_eq!(serde_json::to_value(&trace).unwrap(), entry);
+
+    let (_server, rpc) = expect(
+        payload("trace_get", json!([HASH, ["0x63"]]), "603"),
+        Value::Null,
+    )
+    .await;
+    assert!(rpc.trace_get(HASH, &[99]).await.unwrap().result.is_none());
+}
+
+#[cfg(feature = "txpool-api")]
+#[tokio::test]
+async fn txpool_methods() {
+    let (_server, rpc) = expect(
+        payload("txpool_status", json!([]), "701"),
+        json!({ "pending": "0xa", "queued": "0x7" }),
+    )
+    .await;
+    let status = rpc.txpool_status().await.unwrap().result.unwrap();
+    assert_eq!((status.pending, status.queued), (10, 7));
+
+    let content = json!({ "pending": {}, "queued": {} });
+    let (_server, rpc) = expect(payload("txpool_content", json!([]), "702"), content.clone()).await;
+    assert_eq!(rpc.txpool_content().await.unwrap().result.unwrap(), content);
+
+    let (_server, rpc) = expect(payload("txpool_inspect", json!([]), "703"), content.clone()).await;
+    assert_eq!(rpc.txpool_inspect().await.unwrap().result.unwrap(), content);
+}
+
+#[cfg(feature = "admin-api")]
+#[tokio::test]
+async fn admin_methods() {
+    let info = json!({ "enode": "enode://a@127.0.0.1:30303", "name": "Geth/v1.13.14" });
+    let (_server, rpc) = expect(payload("admin_nodeInfo", json!([]), "801"), info.clone()).await;
+    assert_eq!(rpc.admin_node_info().await.unwrap().result.unwrap(), info);
+
+    let (_server, rpc) = expect(payload("admin_peers", json!([]), "802"), json!([info])).await;
+    assert_eq!(rpc.admin_peers().await.unwrap().result.unwrap().len(), 1);
+}
+
+#[cfg(feature = "engine-api")]
+#[tokio::test]
+async fn engine_methods() {
+    let methods = ["engine_newPayloadV3", "engine_forkchoiceUpdatedV3"];
+    let (_server, rpc) = expect(
+        payload("engine_exchangeCapabilities", json!([methods]), "901"),
+        json!(["engine_newPayloadV3"]),
+    )
+    .await;
+    let r = rpc.engine_exchange_capabilities(&methods).await.unwrap();
+    assert_eq!(r.result.unwrap(), vec!["engine_newPayloadV3"]);
+}
+
+#[cfg(feature = "parity-api")]
+#[tokio::test]
+async fn parity_methods() {
+    use web3_rpc::web3::parity::{Comparison, PendingTxFilter};
+
+    let pending = fixture(include_str!("fixtures/tx_pending.json"));
+    let filter = PendingTxFilter::new()
+        .from(address())
+        .contract_creation()
+        .gas_price(Comparison::Gt(U256::from(1_000_000_000u64)))
+        .value(Comparison::Eq(U256::default()));
+    let (_server, rpc) = expect(
+        payload(
+            "parity_pendingTransactions",
+            json!([
+                10,
+                {
+                    "from": { "eq": ADDRESS },
+                    "to": { "action": "contract_creation" },
+                    "gas_price": { "gt": "0x3b9aca00" },
+                    "value": { "eq": "0x0" }
+                }
+            ]),
+            "1201",
+        ),
+        json!([pending]),
+    )
+    .await;
+    let r = rpc
+        .parity_pending_transactions(Some(10), Some(filter))
+        .await
+        .unwrap();
+    let txs = r.result.unwrap();
+    assert_eq!(txs.len(), 1);
+    assert_eq!(txs[0].block_hash, None);
+    assert_eq!(txs[0].block_number, None);
+    assert_eq!(txs[0].transaction_index, None);
+
+    let (_server, rpc) = expect(
+        payload(
+            "parity_pendingTransactions",
+            json!([null, { "to": { "eq": ADDRESS }, "nonce": { "lt": "0x5" } }]),
+            "1201",
+        ),
+        json!([]),
+    )
+    .await;
+    let filter = PendingTxFilter::new()
+        .to(address())
+        .nonce(Comparison::Lt(U256::from(5u64)));
+    let r = rpc
+        .parity_pending_transactions(None, Some(filter))
+        .await
+        .unwrap();
+    assert!(r.result.unwrap().is_empty());
+}
+
+#[cfg(feature = "polygon")]
+#[tokio::test]
+async fn polygon_methods() {
+    let (_server, rpc) = expect(
+        payload("bor_getAuthor", json!(["latest"]), "1001"),
+        json!(ADDRESS),
+    )
+    .await;
+    let r = rpc.bor_get_author(Tag::Latest).await.unwrap();
+    assert_eq!(r.result.unwrap(), address());
+
+    let (_server, rpc) = expect(
+        payload("bor_getRootHash", json!([1, 16]), "1002"),
+        json!("abcd"),
+    )
+    .await;
+    let r = rpc.bor_get_root_hash(1, 16).await.unwrap();
+    assert_eq!(r.result.unwrap(), "abcd");
+}
+
+#[cfg(feature = "zksync")]
+#[tokio::test]
+async fn zksync_methods() {
+    let (_server, rpc) = expect(payload("zks_L1ChainId", json!([]), "1101"), json!("0x1")).await;
+    assert_eq!(
+        rpc.zks_l1_chain_id().await.unwrap().result.unwrap(),
+        U256::from(1u64)
+    );
+
+    let (_server, rpc) = expect(
+        payload("zks_L1BatchNumber", json!([]), "1102"),
+        json!("0x1d2a"),
+    )
+    .await;
+    assert_eq!(
+        rpc.zks_l1_batch_number().await.unwrap().result.unwrap(),
+        U256::from(0x1d2au64)
+    );
+
+    let bridges = json!({ "l1Erc20DefaultBridge": ADDRESS, "l2Erc20DefaultBridge": ADDRESS });
+    let (_server, rpc) = expect(
+        payload("zks_getBridgeContracts", json!([]), "1103"),
+        bridges.clone(),
+    )
+    .await;
+    assert_eq!(
+        rpc.zks_get_bridge_contracts()
+            .await
+            .unwrap()
+            .result
+            .unwrap(),
+        bridges
+    );
+}
+
+#[cfg(feature = "alchemy")]
+#[tokio::test]
+async fn alchemy_methods() {
+    use web3_rpc::web3::alchemy::{AssetTransfersRequest, TokenSelection, TransferCategory};
+
+    let transfer = |page: &str| {
+        json!({
+            "blockNum": "0xf4b6a9",
+            "uniqueId": format!("{}:log:0x5", HASH),
+            "hash": HASH,
+            "from": ADDRESS,
+            "to": "0x4838b106fce9647bdf1e7877bf73ce8b0bad5f97",
+            "value": 100.5,
+            "erc721TokenId": null,
+            "erc1155Metadata": null,
+            "tokenId": null,
+            "asset": page,
+            "category": "erc20",
+            "rawContract": {
+                "value": "0x0000000000000000000000000000000000000000000000000000000005fd8220",
+                "address": "0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48",
+                "decimal": "0x6"
+            }
+        })
+    };
+    let request = AssetTransfersRequest {
+        from_address: Some(address()),
+        category: vec![TransferCategory::Erc20, TransferCategory::External],
+        max_count: Some(1),
+        ..Default::default()
+    };
+    let params = |page_key: Option<&str>| {
+        let mut params =
+            json!({ "fromAddress": ADDRESS, "category": ["erc20", "external"], "maxCount": "0x1" });
+        if let Some(key) = page_key {
+            params["pageKey"] = json!(key);
+        }
+        json!([params])
+    };
+
+    let server = MockServer::start().await;
+    for (page_key, result) in [
+        (
+            None,
+            json!({ "transfers": [transfer("USDC")], "pageKey": "a1" }),
+        ),
+        (Some("a1"), json!({ "transfers": [transfer("DAI")] })),
+    ] {
+        Mock::given(method("POST"))
+            .and(body_json(payload(
+                "alchemy_getAssetTransfers",
+                params(page_key),
+                "1301",
+            )))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(json!({ "jsonrpc": "2.0", "id": "1301", "result": result })),
+            )
+            .expect(1..)
+            .mount(&server)
+            .await;
+    }
+    let rpc = Web3::new(server.uri());
+    let page = rpc
+        .alchemy_get_asset_transfers(&request)
+        .await
+        .unwrap()
+        .result
+        .unwrap();
+    assert_eq!(page.page_key.as_deref(), Some("a1"));
+    let first = &page.transfers[0];
+    assert_eq!(first.block_num, 0xf4b6a9);
+    assert_eq!(first.category, TransferCategory::Erc20);
+    assert_eq!(first.raw_contract.value, Some(U256::from(100_500_000u64)));
+    assert_eq!(first.raw_contract.decimal, Some(U256::from(6u64)));
+
+    let all = rpc.alchemy_get_all_asset_transfers(&request).await.unwrap();
+    let assets: Vec<_> = all.iter().map(|t| t.asset.as_deref().unwrap()).collect();
+    assert_eq!(assets, ["USDC", "DAI"]);
+
+    let usdc = "0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48";
+    let (_server, rpc) = expect(
+        payload("alchemy_getTokenBalances", json!([ADDRESS, [usdc, ADDRESS]]), "1302"),
+        json!({
+            "address": ADDRESS,
+            "tokenBalances": [
+                { "contractAddress": usdc, "tokenBalance": "0x00000000000000000000000000000000000000000000000000000000000f4240", "error": null },
+                { "contractAddress": ADDRESS, "tokenBalance": null, "error": "execution reverted" }
+            ]
+        }),
+    )
+    .await;
+    let tokens = TokenSelection::Contracts(vec![usdc.parse().unwrap(), address()]);
+    let balances = rpc
+        .alchemy_get_token_balances(address(), &tokens, None)
+        .await
+        .unwrap()
+        .result
+        .unwrap();
+    assert_eq!(
+        balances.token_balances[0].balance(),
+        Ok(U256::from(1_000_000u64))
+    );
+    assert_eq!(
+        balances.token_balances[1].balance(),
+        Err("execution reverted")
+    );
+
+    let (_server, rpc) = expect(
+        payload(
+            "alchemy_getTokenBalances",
+            json!([ADDRESS, "erc20", { "pageKey": "b2" }]),
+            "1302",
+        ),
+        json!({ "address": ADDRESS, "tokenBalances": [] }),
+    )
+    .await;
+    rpc.alchemy_get_token_balances(address(), &TokenSelection::Erc20, Some("b2"))
+        .await
+        .unwrap();
+}
+
+#[cfg(feature = "alloy")]
+#[tokio::test]
+async fn alloy_params() {
+    // checksummed on the alloy side, lowercase on the wire
+    let address: alloy_primitives::Address = "0x4E910EF89c176119744977d90093858089454223"
+        .parse()
+        .unwrap();
+    let hash: alloy_primitives::B256 = HASH.parse().unwrap();
+
+    let (_server, rpc) = expect(
+        payload("eth_getBalance", json!([ADDRESS, "latest"]), "311"),
+        json!("0x1"),
+    )
+    .await;
+    rpc.eth_get_balance(address, None).await.unwrap();
+
+    let (_server, rpc) = expect(
+        payload(
+            "eth_getBalance",
+            json!([ADDRESS, { "blockHash": HASH }]),
+            "311",
+        ),
+        json!("0x1"),
+    )
+    .await;
+    rpc.eth_get_balance_at_block(&address, hash).await.unwrap();
+
+    let word = "0x000000000000000000000000000000000000000000000000000000000000002a";
+    for slot in [
+        StorageSlot::from(alloy_primitives::U256::from(42u64)),
+        StorageSlot::from(alloy_primitives::B256::with_last_byte(0x2a)),
+    ] {
+        let (_server, rpc) = expect(
+            payload(
+                "eth_getStorageAt",
+                json!([ADDRESS, "0x2a", "latest"]),
+                "312",
+            ),
+            json!(word),
+        )
+        .await;
+        rpc.eth_get_storage_at(address, slot, None).await.unwrap();
+    }
+
+    let (_server, rpc) = expect(
+        payload("eth_getTransactionByHash", json!([HASH]), "326"),
+        fixture(TRANSACTION),
+    )
+    .await;
+    rpc.eth_get_transaction_by_hash(hash).await.unwrap();
+}
@@ -0,0 +1,52 @@
+//! Strict mode against a node returning a member the models do not know.
+
+mod common;
+
+use common::fixture;
+use serde_json::{json, Value};
+use web3_rpc::client::Error;
+use web3_rpc::model::Tag;
+use web3_rpc::web3::Web3;
+use wiremock::matchers::method;
+use wiremock::{Mock, MockServer, Request, ResponseTemplate};
+
+async fn serving(block: Value) -> MockServer {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(move |request: &Request| {
+            let request: Value = serde_json::from_slice(&request.body).unwrap();
+            ResponseTemplate::new(200)
+                .set_body_json(json!({ "jsonrpc": "2.0", "id": request["id"], "result": block }))
+        })
+        .mount(&server)
+        .await;
+    server
+}
+
+#[tokio::test]
+async fn unknown_members_fail_only_in_strict_mode() {
+    let mut block = fixture("block_cancun");
+    block["transactions"][0]["authorizationList"] = json!([]);
+    let server = serving(block).await;
+    let rpc = Web3::new(server.uri());
+
+    assert!(rpc.eth_get_block_by_number(Tag::Latest, true).await.is_ok());
+    let report = rpc
+        .strict()
+        .eth_get_block_by_number(Tag::Latest, true)
+        .await
+        .unwrap_err();
+    match report.current_context() {
+        Error::UnknownFields { paths } => {
+            assert_eq!(paths, &["transactions[0].authorizationList"]);
+        }
+        other => panic!("unexpected error {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn known_members_pass_in_strict_mode() {
+    let server = serving(fixture("block_cancun")).await;
+    let rpc = Web3::new(server.uri()).strict();
+    assert!(rpc.eth_get_block_by_number(Tag::Latest, true).await.is_ok());
+}
@@ -0,0 +1,164 @@
+//! Subscriptions with slow consumers against a mock server, under each buffering policy.
+
+use serde_json::{json, Value};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use web3_rpc::client::Error;
+use web3_rpc::subscription::{BufferPolicy, OnFull, SubscriptionItem};
+use web3_rpc::types::H256;
+use web3_rpc::web3::Web3;
+use wiremock::matchers::method;
+use wiremock::{Mock, MockServer, Request, ResponseTemplate};
+
+const BLOCK: &str = include_str!("fixtures/block_post_merge.json");
+const POLL: Duration = Duration::from_millis(5);
+
+fn hash(n: u64) -> H256 {
+    format!("0x{:064x}", n).parse().unwrap()
+}
+
+/// A chain at block 100 growing by 5 blocks every time its head is asked for, and a pool
+/// announcing transactions 1 to 10 on the first poll of its filter.
+#[derive(Default)]
+struct Node {
+    head: u64,
+    polls: u64,
+}
+
+impl Node {
+    fn answer(&mut self, request: &Value) -> Value {
+        let result = match request["method"].as_str().unwrap() {
+            "eth_blockNumber" => {
+                self.head = self.head.max(95) + 5;
+                json!(format!("{:#x}", self.head))
+            }
+            "eth_getBlockByNumber" => {
+                let number = request["params"][0].as_str().unwrap();
+                let mut block: Value = serde_json::from_str(BLOCK).unwrap();
+                block["number"] = json!(number);
+                block
+            }
+            "eth_newPendingTransactionFilter" => json!("0x1"),
+            "eth_getFilterChanges" => {
+                self.polls += 1;
+                match self.polls {
+                    1 => json!((1..=10).map(hash).collect::<Vec<_>>()),
+                    _ => json!([]),
+                }
+            }
+            other => panic!("unexpected method {}", other),
+        };
+        json!({ "jsonrpc": "2.0", "id": request["id"], "result": result })
+    }
+}
+
+async fn serving() -> MockServer {
+    let node = Arc::new(Mutex::new(Node::default()));
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(move |request: &Request| {
+            let request: Value = serde_json::from_slice(&request.body).unwrap();
+            ResponseTemplate::new(200).set_body_json(node.lock().unwrap().answer(&request))
+        })
+        .mount(&server)
+        .await;
+    server
+}
+
+/// Waits for the producer to have dropped `n` items.
+async fn dropped(n: u64, dropped: impl Fn() -> u64) {
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while dropped() < n {
+            tokio::time::sleep(POLL).await;
+        }
+    })
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn blocking_heads_miss_nothing() {
+    let server = serving().await;
+    let rpc = Web3::new(server.uri());
+    let policy = BufferPolicy::Bounded {
+        capacity: 2,
+        on_full: OnFull::Block,
+    };
+    let mut heads = rpc.subscribe_new_heads(POLL, policy);
+
+    // far slower than the chain
+    for expected in 101..=130 {
+        tokio::time::sleep(Duration::from_millis(2)).await;
+        match heads.next().await.unwrap().unwrap() {
+            SubscriptionItem::Item(block) => assert_eq!(block.number, Some(expected)),
+            lagged => panic!("{:?}", lagged),
+        }
+    }
+    assert_eq!(heads.dropped(), 0);
+}
+
+#[tokio::test]
+async fn pending_transactions_drop_the_oldest() {
+    let server = serving().await;
+    let rpc = Web3::new(server.uri());
+    let policy = BufferPolicy::Bounded {
+        capacity: 4,
+        on_full: OnFull::DropOldest,
+    };
+    let mut pending = rpc.subscribe_pending_transactions(POLL, policy);
+
+    dropped(6, || pending.dropped()).await;
+    let mut items = Vec::new();
+    for _ in 0..5 {
+        items.push(pending.next().await.unwrap().unwrap());
+    }
+    let mut expected = vec![SubscriptionItem::Lagged(6)];
+    expected.extend((7..=10).map(|n| SubscriptionItem::Item(hash(n))));
+    assert_eq!(items, expected);
+}
+
+#[tokio::test]
+async fn pending_transactions_drop_the_newest() {
+    let server = serving().await;
+    let rpc = Web3::new(server.uri());
+    let policy = BufferPolicy::Bounded {
+        capacity: 4,
+        on_full: OnFull::DropNewest,
+    };
+    let mut pending = rpc.subscribe_pending_transactions(POLL, policy);
+
+    dropped(6, || pending.dropped()).await;
+    let mut items = Vec::new();
+    for _ in 0..5 {
+        items.push(pending.next().await.unwrap().unwrap());
+    }
+    let mut expected: Vec<_> = (1..=4).map(|n| SubscriptionItem::Item(hash(n))).collect();
+    expected.push(SubscriptionItem::Lagged(6));
+    assert_eq!(items, expected);
+}
+
+#[tokio::test]
+async fn overflowing_heads_end_the_subscription() {
+    let server = serving().await;
+    let rpc = Web3::new(server.uri());
+    let policy = BufferPolicy::Bounded {
+        capacity: 3,
+        on_full: OnFull::Error,
+    };
+    let mut heads = rpc.subscribe_new_heads(POLL, policy);
+
+    // the first poll finds 5 new blocks, more than fit
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    for _ in 0..3 {
+        assert!(matches!(
+            heads.next().await,
+            Some(Ok(SubscriptionItem::Item(_)))
+        ));
+    }
+    let report = heads.next().await.unwrap().unwrap_err();
+    assert!(matches!(
+        report.current_context(),
+        Error::SubscriptionOverflow { capacity: 3 }
+    ));
+    assert!(heads.next().await.is_none());
+}
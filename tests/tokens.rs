@@ -0,0 +1,267 @@
+//! Token helpers against a mock server: the calldata they send and how they read results.
+
+use serde_json::{json, Value};
+use web3_rpc::abi::{self, Token};
+use web3_rpc::erc1155::Erc1155;
+use web3_rpc::erc20::Erc20;
+use web3_rpc::erc721::{Erc721, Ownership};
+use web3_rpc::types::{Address, U256};
+use web3_rpc::web3::Web3;
+use wiremock::matchers::{body_json, method};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+const TOKEN: &str = "0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48";
+const HOLDER: &str = "0x846c4dc9f4e2514206ef179eaa0bcfae007e37d2";
+
+fn hex(data: &[u8]) -> String {
+    let digits: String = data.iter().map(|b| format!("{:02x}", b)).collect();
+    format!("0x{}", digits)
+}
+
+/// Expects one eth_call to `TOKEN` with `calldata` at `block` and answers with `output`.
+async fn expect_call(server: &MockServer, calldata: Vec<u8>, block: Value, output: Vec<u8>) {
+    respond(
+        server,
+        calldata,
+        block,
+        json!({ "jsonrpc": "2.0", "id": "322", "result": hex(&output) }),
+    )
+    .await;
+}
+
+/// Expects one eth_call to `TOKEN` at `latest` and answers with an execution revert.
+async fn expect_revert(server: &MockServer, calldata: Vec<u8>, data: Option<Vec<u8>>) {
+    let mut error = json!({ "code": 3, "message": "execution reverted" });
+    if let Some(data) = data {
+        error["data"] = json!(hex(&data));
+    }
+    respond(
+        server,
+        calldata,
+        json!("latest"),
+        json!({ "jsonrpc": "2.0", "id": "322", "error": error }),
+    )
+    .await;
+}
+
+async fn respond(server: &MockServer, calldata: Vec<u8>, block: Value, reply: Value) {
+    Mock::given(method("POST"))
+        .and(body_json(json!({
+            "jsonrpc": "2.0",
+            "method": "eth_call",
+            "params": [{ "to": TOKEN, "data": hex(&calldata) }, block],
+            "id": "322"
+        })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(reply))
+        .expect(1)
+        .mount(server)
+        .await;
+}
+
+fn address(s: &str) -> Address {
+    s.parse().unwrap()
+}
+
+fn word(n: u64) -> Vec<u8> {
+    U256::from(n).to_be_bytes().to_vec()
+}
+
+#[tokio::test]
+async fn erc20_reads() {
+    let server = MockServer::start().await;
+    let holder = address(HOLDER);
+    let spender = address("0x4e910ef89c176119744977d90093858089454223");
+    // balanceOf, decimals, symbol as bytes32, name as string, totalSupply, allowance
+    expect_call(
+        &server,
+        [&[0x70, 0xa0, 0x82, 0x31][..], &[0u8; 12], holder.as_bytes()].concat(),
+        json!("0x1036640"),
+        word(2_500_000),
+    )
+    .await;
+    expect_call(
+        &server,
+        vec![0x31, 0x3c, 0xe5, 0x67],
+        json!("latest"),
+        word(6),
+    )
+    .await;
+    let mut symbol = [0u8; 32];
+    symbol[..4].copy_from_slice(b"USDC");
+    expect_call(
+        &server,
+        vec![0x95, 0xd8, 0x9b, 0x41],
+        json!("latest"),
+        symbol.to_vec(),
+    )
+    .await;
+    expect_call(
+        &server,
+        vec![0x06, 0xfd, 0xde, 0x03],
+        json!("latest"),
+        abi::encode(&[Token::String("USD Coin".into())]),
+    )
+    .await;
+    expect_call(
+        &server,
+        vec![0x18, 0x16, 0x0d, 0xdd],
+        json!("latest"),
+        word(1 << 40),
+    )
+    .await;
+    expect_call(
+        &server,
+        abi::encode_call(
+            "allowance(address,address)",
+            &[Token::Address(holder), Token::Address(spender)],
+        ),
+        json!("latest"),
+        word(0),
+    )
+    .await;
+
+    let token = Erc20::new(&Web3::new(server.uri()), address(TOKEN));
+    assert_eq!(
+        token.balance_of(holder, 17_000_000u64).await.unwrap(),
+        U256::from(2_500_000u64)
+    );
+    assert_eq!(token.decimals().await.unwrap(), 6);
+    assert_eq!(token.symbol().await.unwrap(), "USDC");
+    assert_eq!(token.name().await.unwrap(), "USD Coin");
+    assert_eq!(token.total_supply().await.unwrap(), U256::from(1u64 << 40));
+    assert_eq!(token.allowance(holder, spender).await.unwrap(), U256::ZERO);
+}
+
+#[tokio::test]
+async fn erc20_rejects_empty_output() {
+    // an address without code answers eth_call with "0x"
+    let server = MockServer::start().await;
+    expect_call(
+        &server,
+        vec![0x31, 0x3c, 0xe5, 0x67],
+        json!("latest"),
+        vec![],
+    )
+    .await;
+    let token = Erc20::new(&Web3::new(server.uri()), address(TOKEN));
+    assert!(token.decimals().await.is_err());
+}
+
+#[tokio::test]
+async fn erc721_reads() {
+    let server = MockServer::start().await;
+    let holder = address(HOLDER);
+    let owner_of = |id: u64| abi::encode_call("ownerOf(uint256)", &[Token::Uint(U256::from(id))]);
+    expect_call(
+        &server,
+        owner_of(1),
+        json!("latest"),
+        abi::encode(&[Token::Address(holder)]),
+    )
+    .await;
+    let reason = [
+        &[0x08, 0xc3, 0x79, 0xa0][..],
+        &abi::encode(&[Token::String("ERC721: invalid token ID".into())]),
+    ]
+    .concat();
+    expect_revert(&server, owner_of(2), Some(reason)).await;
+    expect_revert(&server, owner_of(3), None).await;
+    expect_call(
+        &server,
+        abi::encode_call("balanceOf(address)", &[Token::Address(holder)]),
+        json!("latest"),
+        word(3),
+    )
+    .await;
+    expect_call(
+        &server,
+        abi::encode_call("tokenURI(uint256)", &[Token::Uint(U256::ONE)]),
+        json!("latest"),
+        abi::encode(&[Token::String("ipfs://bafy/1".into())]),
+    )
+    .await;
+    expect_call(
+        &server,
+        abi::encode_call("getApproved(uint256)", &[Token::Uint(U256::ONE)]),
+        json!("latest"),
+        word(0),
+    )
+    .await;
+
+    let nft = Erc721::new(&Web3::new(server.uri()), address(TOKEN));
+    assert_eq!(
+        nft.owner_of(U256::ONE).await.unwrap(),
+        Ownership::Owner(holder)
+    );
+    assert_eq!(
+        nft.owner_of(U256::from(2u64)).await.unwrap(),
+        Ownership::Nonexistent {
+            reason: Some("ERC721: invalid token ID".into())
+        }
+    );
+    assert_eq!(
+        nft.owner_of(U256::from(3u64)).await.unwrap(),
+        Ownership::Nonexistent { reason: None }
+    );
+    assert_eq!(nft.balance_of(holder).await.unwrap(), U256::from(3u64));
+    assert_eq!(nft.token_uri(U256::ONE).await.unwrap(), "ipfs://bafy/1");
+    assert_eq!(nft.get_approved(U256::ONE).await.unwrap(), None);
+}
+
+#[tokio::test]
+async fn erc1155_reads() {
+    let server = MockServer::start().await;
+    let holder = address(HOLDER);
+    let other = address("0x4e910ef89c176119744977d90093858089454223");
+    let id = U256::from(0x4cce0u64);
+    expect_call(
+        &server,
+        abi::encode_call(
+            "balanceOf(address,uint256)",
+            &[Token::Address(holder), Token::Uint(id)],
+        ),
+        json!("latest"),
+        word(7),
+    )
+    .await;
+    expect_call(
+        &server,
+        abi::encode_call(
+            "balanceOfBatch(address[],uint256[])",
+            &[
+                Token::Array(vec![Token::Address(holder), Token::Address(other)]),
+                Token::Array(vec![Token::Uint(id), Token::Uint(U256::ONE)]),
+            ],
+        ),
+        json!("latest"),
+        abi::encode(&[Token::Array(vec![
+            Token::Uint(U256::from(7u64)),
+            Token::Uint(U256::ZERO),
+        ])]),
+    )
+    .await;
+    expect_call(
+        &server,
+        abi::encode_call("uri(uint256)", &[Token::Uint(id)]),
+        json!("latest"),
+        abi::encode(&[Token::String("https://token-cdn-domain/{id}.json".into())]),
+    )
+    .await;
+
+    let multi = Erc1155::new(&Web3::new(server.uri()), address(TOKEN));
+    assert_eq!(
+        multi.balance_of(holder, id).await.unwrap(),
+        U256::from(7u64)
+    );
+    assert_eq!(
+        multi
+            .balance_of_batch(&[(holder, id), (other, U256::ONE)])
+            .await
+            .unwrap(),
+        vec![U256::from(7u64), U256::ZERO]
+    );
+    assert_eq!(
+        multi.uri(id).await.unwrap(),
+        "https://token-cdn-domain/000000000000000000000000000000000000000000000000000000000004cce0.json"
+    );
+}
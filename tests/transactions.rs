@@ -0,0 +1,222 @@
+//! Transactions of a block range, fetched in batches against a mock server.
+
+use serde_json::{json, Value};
+use web3_rpc::client::Error;
+use web3_rpc::types::Address;
+use web3_rpc::web3::Web3;
+use wiremock::matchers::{body_json, method};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+const BLOCK: &str = include_str!("fixtures/block_post_merge.json");
+const WALLET: &str = "0x1111111111111111111111111111111111111111";
+const OTHER: &str = "0x2222222222222222222222222222222222222222";
+
+fn block_request(n: u64, id: usize) -> Value {
+    json!({ "jsonrpc": "2.0", "method": "eth_getBlockByNumber", "params": [format!("{:#x}", n), true], "id": id.to_string() })
+}
+
+fn tx_hash(block: u64, index: u64) -> String {
+    format!("0x{:062x}{:02x}", block, index)
+}
+
+/// The fixture block renumbered to `n`, with three transactions: wallet to other, other to
+/// wallet, and other to itself.
+fn block(n: u64) -> Value {
+    let mut block: Value = serde_json::from_str(BLOCK).unwrap();
+    block["number"] = json!(format!("{:#x}", n));
+    let template = block["transactions"][0].clone();
+    let parties = [(WALLET, OTHER), (OTHER, WALLET), (OTHER, OTHER)];
+    let txs: Vec<Value> = parties
+        .iter()
+        .enumerate()
+        .map(|(i, (from, to))| {
+            let mut tx = template.clone();
+            tx["hash"] = json!(tx_hash(n, i as u64));
+            tx["from"] = json!(from);
+            tx["to"] = json!(to);
+            tx["transactionIndex"] = json!(format!("{:#x}", i));
+            tx["blockNumber"] = block["number"].clone();
+            tx
+        })
+        .collect();
+    block["transactions"] = json!(txs);
+    block
+}
+
+/// Expects exactly one batch request for `blocks`.
+async fn mount_batch(server: &MockServer, blocks: &[u64]) {
+    let requests: Vec<Value> = blocks
+        .iter()
+        .enumerate()
+        .map(|(i, n)| block_request(*n, i))
+        .collect();
+    let responses: Vec<Value> = blocks
+        .iter()
+        .enumerate()
+        .map(|(i, n)| json!({ "jsonrpc": "2.0", "id": i.to_string(), "result": block(*n) }))
+        .collect();
+    Mock::given(method("POST"))
+        .and(body_json(json!(requests)))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!(responses)))
+        .expect(1)
+        .mount(server)
+        .await;
+}
+
+#[tokio::test]
+async fn every_transaction_in_canonical_order() {
+    let server = MockServer::start().await;
+    mount_batch(&server, &[100, 101]).await;
+    mount_batch(&server, &[102, 103]).await;
+    mount_batch(&server, &[104]).await;
+
+    let rpc = Web3::new(server.uri());
+    let mut txs = rpc.transactions_in_range(100, 104).batch_size(2);
+    let mut seen = Vec::new();
+    while let Some(item) = txs.next().await {
+        let (block, tx) = item.unwrap();
+        assert_eq!(tx.block_number, Some(format!("{:#x}", block.number)));
+        assert_eq!(block.timestamp, 0x6430ae13);
+        seen.push(format!("{:?}", tx.hash));
+    }
+    let expected: Vec<String> = (100..=104)
+        .flat_map(|n| (0..3).map(move |i| tx_hash(n, i)))
+        .collect();
+    assert_eq!(seen, expected);
+    assert!(txs.next().await.is_none());
+}
+
+#[tokio::test]
+async fn address_filters_match_either_side() {
+    let server = MockServer::start().await;
+    mount_batch(&server, &[100, 101]).await;
+    let wallet: Address = WALLET.parse().unwrap();
+
+    let rpc = Web3::new(server.uri());
+    let mut sent = Vec::new();
+    let mut txs = rpc
+        .transactions_in_range(100, 101)
+        .from_addresses(&[wallet])
+        .to_addresses(&[wallet]);
+    while let Some(item) = txs.next().await {
+        let (block, tx) = item.unwrap();
+        assert!(tx.from == wallet || tx.to == Some(wallet));
+        sent.push((block.number, tx.from == wallet));
+    }
+    assert_eq!(
+        sent,
+        vec![(100, true), (100, false), (101, true), (101, false)]
+    );
+}
+
+#[tokio::test]
+async fn missing_blocks_end_the_stream() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(body_json(json!([
+            block_request(100, 0),
+            block_request(101, 1)
+        ])))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+            { "jsonrpc": "2.0", "id": "0", "result": block(100) },
+            { "jsonrpc": "2.0", "id": "1", "result": null }
+        ])))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let rpc = Web3::new(server.uri());
+    let mut txs = rpc.transactions_in_range(100, 110).batch_size(2);
+    let report = txs.next().await.unwrap().unwrap_err();
+    assert!(matches!(
+        report.current_context(),
+        Error::UnexpectedResponseFormat
+    ));
+    assert!(txs.next().await.is_none());
+}
+
+/// A hashes-only copy of `block(n)`, and a node answering eth_getTransactionByHash batches
+/// from its full transactions: `null` for index 3, a malformed object for index 1, and a 500
+/// for any batch asking for index 5.
+async fn transaction_node(n: u64) -> (MockServer, web3_rpc::model::Block) {
+    let full = block(n);
+    let mut hashes_only = full.clone();
+    let mut txs: Vec<Value> = Vec::new();
+    for i in 0..6 {
+        let mut tx = full["transactions"][0].clone();
+        tx["hash"] = json!(tx_hash(n, i));
+        tx["transactionIndex"] = json!(format!("{:#x}", i));
+        txs.push(tx);
+    }
+    hashes_only["transactions"] =
+        json!(txs.iter().map(|tx| tx["hash"].clone()).collect::<Vec<_>>());
+
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(move |request: &wiremock::Request| {
+            let batch: Vec<Value> = serde_json::from_slice(&request.body).unwrap();
+            let mut responses = Vec::new();
+            for request in &batch {
+                assert_eq!(request["method"], "eth_getTransactionByHash");
+                let i = txs
+                    .iter()
+                    .position(|tx| tx["hash"] == request["params"][0])
+                    .unwrap();
+                let result = match i {
+                    1 => json!({ "hash": txs[i]["hash"] }),
+                    3 => Value::Null,
+                    5 => return ResponseTemplate::new(500),
+                    _ => txs[i].clone(),
+                };
+                responses.push(json!({ "jsonrpc": "2.0", "id": request["id"], "result": result }));
+            }
+            ResponseTemplate::new(200).set_body_json(responses)
+        })
+        .mount(&server)
+        .await;
+    (server, serde_json::from_value(hashes_only).unwrap())
+}
+
+#[tokio::test]
+async fn hashes_are_fetched_in_batches_with_errors_in_place() {
+    let (server, block) = transaction_node(7).await;
+    let rpc = Web3::new(server.uri());
+    let mut txs = block.transactions_iter(&rpc).batch_size(2);
+    assert_eq!(txs.remaining(), 6);
+
+    let mut seen = Vec::new();
+    while let Some(item) = txs.next().await {
+        seen.push(
+            item.map(|tx| tx.hash.to_string())
+                .map_err(|e| format!("{:?}", e.current_context())),
+        );
+    }
+    assert_eq!(
+        seen,
+        [
+            Ok(tx_hash(7, 0)),
+            Err("FailedToDeserialize".to_string()),
+            Ok(tx_hash(7, 2)),
+            Err("UnexpectedResponseFormat".to_string()),
+            // the batch of 4 and 5 failed as a whole
+            Err("HttpError(500)".to_string()),
+            Err("HttpError(500)".to_string()),
+        ]
+    );
+    assert_eq!(server.received_requests().await.unwrap().len(), 3);
+}
+
+#[tokio::test]
+async fn full_blocks_need_no_requests() {
+    let server = MockServer::start().await;
+    let block: web3_rpc::model::Block = serde_json::from_value(block(8)).unwrap();
+    let rpc = Web3::new(server.uri());
+    let mut txs = block.transactions_iter(&rpc);
+    let mut count = 0;
+    while let Some(tx) = txs.next().await {
+        assert_eq!(tx.unwrap().hash.to_string(), tx_hash(8, count));
+        count += 1;
+    }
+    assert_eq!(count, 3);
+    assert!(server.received_requests().await.unwrap().is_empty());
+}
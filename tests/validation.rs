@@ -0,0 +1,137 @@
+//! Malformed hashes and hex data are rejected before anything is sent.
+
+use serde_json::{json, Value};
+use web3_rpc::client::Error;
+use web3_rpc::web3::Web3;
+use wiremock::matchers::method;
+use wiremock::{Mock, MockServer, Request, ResponseTemplate};
+
+const HASH: &str = "0xe812a49745d691961893d7cfd3902d78d710751bab872f12215ee23f27f3efa9";
+
+fn rejected<T: std::fmt::Debug>(
+    result: Result<T, error_stack::Report<Error>>,
+    expected_param: &str,
+    expected_reason: &str,
+) {
+    let err = result.unwrap_err();
+    match err.current_context() {
+        Error::InvalidArgument { param, reason } => {
+            assert_eq!(*param, expected_param);
+            assert_eq!(reason, expected_reason);
+        }
+        e => panic!("unexpected error {:?}", e),
+    }
+}
+
+#[tokio::test]
+async fn each_rejection_reason() {
+    let server = MockServer::start().await;
+    let rpc = Web3::new(server.uri());
+
+    rejected(
+        rpc.eth_get_transaction_by_hash(&HASH[2..]).await,
+        "hash",
+        "missing 0x prefix",
+    );
+    rejected(
+        rpc.eth_get_block_by_hash(&HASH[..65], false).await,
+        "hash",
+        "expected 64 hex digits, got 63",
+    );
+    let long = format!("{}00", HASH);
+    rejected(
+        rpc.eth_get_transaction_receipt(&long).await,
+        "hash",
+        "expected 64 hex digits, got 66",
+    );
+    let bad = HASH.replace('e', "g");
+    rejected(
+        rpc.eth_get_uncle_by_block_hash_and_index(&bad, 0).await,
+        "hash",
+        "invalid hex character 'g'",
+    );
+    rejected(
+        rpc.eth_get_uncle_count_by_block_hash("").await,
+        "hash",
+        "missing 0x prefix",
+    );
+    rejected(
+        rpc.eth_send_raw_transaction("0x02f8b00181a").await,
+        "raw_transaction",
+        "odd number of hex digits (11)",
+    );
+    rejected(
+        rpc.eth_send_raw_transaction("02f8b00181a0").await,
+        "raw_transaction",
+        "missing 0x prefix",
+    );
+    rejected(rpc.web3_sha3("hello").await, "data", "missing 0x prefix");
+    rejected(
+        rpc.web3_sha3("0x68656c6c6z").await,
+        "data",
+        "invalid hex character 'z'",
+    );
+    const ADDRESS: &str = "0x846c4dc9f4e2514206ef179eaa0bcfae007e37d2";
+    rejected(
+        rpc.eth_get_storage_at(ADDRESS, "5", None).await,
+        "slot",
+        "missing 0x prefix",
+    );
+    rejected(
+        rpc.eth_get_storage_at(ADDRESS, "0x", None).await,
+        "slot",
+        "no hex digits",
+    );
+    rejected(
+        rpc.eth_get_storage_at(ADDRESS, format!("0x{}", "0".repeat(65)), None)
+            .await,
+        "slot",
+        "expected at most 64 hex digits, got 65",
+    );
+
+    assert!(server.received_requests().await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn well_formed_arguments_are_sent() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(|request: &Request| {
+            let request: Value = serde_json::from_slice(&request.body).unwrap();
+            ResponseTemplate::new(200)
+                .set_body_json(json!({ "jsonrpc": "2.0", "id": request["id"], "result": null }))
+        })
+        .mount(&server)
+        .await;
+    let rpc = Web3::new(server.uri());
+    rpc.eth_get_transaction_by_hash(HASH).await.unwrap();
+    rpc.eth_get_transaction_by_hash(format!("0x{}", HASH[2..].to_uppercase()))
+        .await
+        .unwrap();
+    rpc.web3_sha3("0x").await.unwrap();
+    assert_eq!(server.received_requests().await.unwrap().len(), 3);
+}
+
+#[tokio::test]
+async fn validation_can_be_turned_off() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(|request: &Request| {
+            let request: Value = serde_json::from_slice(&request.body).unwrap();
+            ResponseTemplate::new(200)
+                .set_body_json(json!({ "jsonrpc": "2.0", "id": request["id"], "result": null }))
+        })
+        .expect(2)
+        .mount(&server)
+        .await;
+    let rpc = Web3::builder()
+        .url(server.uri())
+        .validate_params(false)
+        .build()
+        .unwrap();
+    // a chain with 20-byte block hashes
+    rpc.eth_get_block_by_hash("0x4838b106fce9647bdf1e7877bf73ce8b0bad5f97", false)
+        .await
+        .unwrap();
+    rpc.web3_sha3("hello").await.unwrap();
+}
@@ -0,0 +1,108 @@
+//! Reads checked against a second provider.
+
+use serde_json::{json, Value};
+use web3_rpc::client::Error;
+use web3_rpc::types::U256;
+use web3_rpc::web3::Web3;
+use wiremock::matchers::{body_partial_json, method};
+use wiremock::{Mock, MockServer, Request, ResponseTemplate};
+
+const ADDRESS: &str = "0x00000000219ab540356cbb839cbe05303d7705fa";
+
+async fn answering(rpc_method: &str, result: Value) -> MockServer {
+    let server = MockServer::start().await;
+    answer(&server, rpc_method, result).await;
+    server
+}
+
+async fn answer(server: &MockServer, rpc_method: &str, result: Value) {
+    Mock::given(method("POST"))
+        .and(body_partial_json(json!({ "method": rpc_method })))
+        .respond_with(move |request: &Request| {
+            let request: Value = serde_json::from_slice(&request.body).unwrap();
+            ResponseTemplate::new(200)
+                .set_body_json(json!({ "jsonrpc": "2.0", "id": request["id"], "result": result }))
+        })
+        .mount(server)
+        .await;
+}
+
+async fn params(server: &MockServer, rpc_method: &str) -> Vec<Value> {
+    server
+        .received_requests()
+        .await
+        .unwrap()
+        .iter()
+        .map(|r| serde_json::from_slice::<Value>(&r.body).unwrap())
+        .filter(|body| body["method"] == rpc_method)
+        .map(|body| body["params"].clone())
+        .collect()
+}
+
+#[tokio::test]
+async fn agreeing_providers() {
+    let ours = answering("eth_getBalance", json!("0x0DE0B6B3A7640000")).await;
+    let theirs = answering("eth_getBalance", json!("0xde0b6b3a7640000")).await;
+    let (a, b) = (Web3::new(ours.uri()), Web3::new(theirs.uri()));
+
+    let balance = a.verified(&b).balance(ADDRESS, None).await.unwrap();
+    assert_eq!(balance, U256::from(1_000_000_000_000_000_000u64));
+    assert_eq!(params(&ours, "eth_getBalance").await.len(), 1);
+    assert_eq!(params(&theirs, "eth_getBalance").await.len(), 1);
+
+    answer(&ours, "eth_getTransactionReceipt", Value::Null).await;
+    answer(&theirs, "eth_getTransactionReceipt", Value::Null).await;
+    let hash = "0x88df016429689c079f3b2f6ad39fa052532c56795b733da78a91ebe6a713944b";
+    assert!(a.verified(&b).receipt(hash).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn disagreeing_providers() {
+    let one = format!("0x{:064x}", 1);
+    let two = format!("0x{:064x}", 2);
+    let ours = answering("eth_getStorageAt", json!(one)).await;
+    let theirs = answering("eth_getStorageAt", json!(two)).await;
+    let (a, b) = (Web3::new(ours.uri()), Web3::new(theirs.uri()));
+
+    let err = a
+        .verified(&b)
+        .storage(ADDRESS, 0u64, None)
+        .await
+        .unwrap_err();
+    match err.current_context() {
+        Error::ProvidersDisagree {
+            method,
+            ours,
+            theirs,
+        } => {
+            assert_eq!(*method, "eth_getStorageAt");
+            assert_ne!(ours, theirs);
+        }
+        other => panic!("{:?}", other),
+    }
+    assert!(format!("{:?}", err).contains("other endpoint"));
+}
+
+#[tokio::test]
+async fn pinned_reads_use_the_lower_head() {
+    let ours = answering("eth_blockNumber", json!("0x65")).await;
+    let theirs = answering("eth_blockNumber", json!("0x64")).await;
+    for server in [&ours, &theirs] {
+        answer(server, "eth_getBalance", json!("0x1")).await;
+    }
+    let (a, b) = (Web3::new(ours.uri()), Web3::new(theirs.uri()));
+
+    let balance = a
+        .verified(&b)
+        .pinned()
+        .balance(ADDRESS, None)
+        .await
+        .unwrap();
+    assert_eq!(balance, U256::from(1u64));
+    for server in [&ours, &theirs] {
+        assert_eq!(
+            params(server, "eth_getBalance").await,
+            [json!([ADDRESS, "0x64"])]
+        );
+    }
+}
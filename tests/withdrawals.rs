@@ -0,0 +1,107 @@
+//! Withdrawal scanning over a block range against a mock server.
+
+use serde_json::{json, Value};
+use web3_rpc::types::{Address, U256};
+use web3_rpc::web3::Web3;
+use wiremock::matchers::{body_json, method};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+const CUSTOMER: &str = "0xb9d7934878b5fb9610b3fe8a5e441e8fad7e293f";
+const OTHER: &str = "0x8306300ffd616049fd7e4b0354a64da835c1a81c";
+const FIRST: u64 = 17_034_870;
+
+fn block(n: u64) -> Value {
+    // a customer withdrawal every tenth block, someone else's in every block
+    let mut withdrawals = vec![json!({
+        "index": format!("{:#x}", n * 16),
+        "validatorIndex": "0x1",
+        "address": OTHER,
+        "amount": "0x1"
+    })];
+    if n.is_multiple_of(10) {
+        withdrawals.push(json!({
+            "index": format!("{:#x}", n * 16 + 1),
+            "validatorIndex": "0x75b11",
+            "address": CUSTOMER,
+            "amount": "0xf47b02"
+        }));
+    }
+    json!({
+        "number": format!("{:#x}", n),
+        "timestamp": format!("{:#x}", 1_681_338_455 + (n - FIRST) * 12),
+        "hash": "0xe812a49745d691961893d7cfd3902d78d710751bab872f12215ee23f27f3efa9",
+        "transactions": ["0x9aba9e042034f025ebbe9fddde27c2c091afe6bb24d1c6f6418abbb3103519ee"],
+        "withdrawals": withdrawals
+    })
+}
+
+async fn mount_batch(server: &MockServer, blocks: std::ops::RangeInclusive<u64>) {
+    let start = *blocks.start();
+    let request: Vec<Value> = blocks
+        .clone()
+        .map(|n| {
+            json!({
+                "jsonrpc": "2.0",
+                "method": "eth_getBlockByNumber",
+                "params": [format!("{:#x}", n), false],
+                "id": (n - start).to_string()
+            })
+        })
+        .collect();
+    // answered back to front
+    let reply: Vec<Value> = blocks
+        .rev()
+        .map(|n| json!({ "jsonrpc": "2.0", "id": (n - start).to_string(), "result": block(n) }))
+        .collect();
+    Mock::given(method("POST"))
+        .and(body_json(request))
+        .respond_with(ResponseTemplate::new(200).set_body_json(reply))
+        .expect(1)
+        .mount(server)
+        .await;
+}
+
+#[tokio::test]
+async fn scans_in_batches_and_filters_by_recipient() {
+    let server = MockServer::start().await;
+    mount_batch(&server, FIRST..=FIRST + 49).await;
+    mount_batch(&server, FIRST + 50..=FIRST + 59).await;
+
+    let customer: Address = CUSTOMER.parse().unwrap();
+    let scan = Web3::new(server.uri())
+        .scan_withdrawals(FIRST, FIRST + 59, &[customer])
+        .await
+        .unwrap();
+
+    let blocks: Vec<u64> = scan.withdrawals.iter().map(|w| w.block_number).collect();
+    assert_eq!(
+        blocks,
+        vec![17_034_870, 17_034_880, 17_034_890, 17_034_900, 17_034_910, 17_034_920]
+    );
+    assert_eq!(scan.withdrawals[1].timestamp, 1_681_338_455 + 120);
+    assert!(scan
+        .withdrawals
+        .iter()
+        .all(|w| w.withdrawal.address == customer));
+    assert_eq!(
+        scan.total_wei,
+        U256::from(6 * 0xf47b02u64 * 1_000_000_000u64)
+    );
+}
+
+#[tokio::test]
+async fn missing_blocks_are_an_error() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+            { "jsonrpc": "2.0", "id": "0", "result": block(FIRST) },
+            { "jsonrpc": "2.0", "id": "1", "result": null }
+        ])))
+        .mount(&server)
+        .await;
+    let report = Web3::new(server.uri())
+        .scan_withdrawals(FIRST, FIRST + 1, &[])
+        .await
+        .unwrap_err();
+    assert!(format!("{:?}", report).contains("block 17034871 not found"));
+}